@@ -33,6 +33,7 @@ export default defineConfig({
             { text: "Streaming", link: "/guide/streaming" },
             { text: "Bulk Insert", link: "/guide/bulk-insert" },
             { text: "Connection Pooling", link: "/guide/pooling" },
+            { text: "Schema Introspection", link: "/guide/schema" },
           ],
         },
         {
@@ -0,0 +1,124 @@
+/**
+ * Chunked reads of large varbinary(max) values on any platform.
+ * @module
+ */
+
+import type { RuntimeFFI } from "./runtime.ts";
+
+/** Default chunk size for sequential reads (1 MiB). */
+const DEFAULT_CHUNK_SIZE = 1024 * 1024;
+
+/**
+ * Reads one varbinary(max) value in chunks via `SUBSTRING`, so multi-hundred-MB
+ * blobs can be streamed without FILESTREAM. Obtain one via `cn.openBlob()`.
+ *
+ * @example
+ * ```ts
+ * await using blob = await cn.openBlob("dbo.Files", "Data", { Id: 42 });
+ * console.log(blob.size);
+ * for await (const chunk of blob) {
+ *   await file.write(chunk);
+ * }
+ * ```
+ */
+export class BlobReader implements AsyncIterable<Uint8Array>, AsyncDisposable {
+  #blobId: bigint;
+  #ffi: RuntimeFFI;
+  #chunkSize: number;
+  #position = 0;
+  #closed = false;
+
+  /** Total size of the value in bytes (0 for NULL). */
+  readonly size: number;
+
+  /** @internal */
+  constructor(
+    blobId: bigint,
+    ffi: RuntimeFFI,
+    chunkSize = DEFAULT_CHUNK_SIZE,
+  ) {
+    this.#blobId = blobId;
+    this.#ffi = ffi;
+    this.#chunkSize = chunkSize;
+    this.size = Number(ffi.blobLength(blobId));
+  }
+
+  /** Offset of the next sequential {@link read}. */
+  get position(): number {
+    return this.#position;
+  }
+
+  /** Read the next chunk. Returns an empty array at the end. */
+  async read(length: number = this.#chunkSize): Promise<Uint8Array> {
+    const chunk = await this.readAt(this.#position, length);
+    this.#position += chunk.length;
+    return chunk;
+  }
+
+  /** Read up to `length` bytes at a 0-based `offset` without moving the position. */
+  async readAt(offset: number, length: number): Promise<Uint8Array> {
+    this.#ensureOpen();
+    const json = await this.#ffi.blobRead(
+      this.#blobId,
+      BigInt(offset),
+      BigInt(length),
+    );
+    if (json === null) throw new Error("Blob read failed");
+    const result = JSON.parse(json);
+    if (result.__error) throw new Error(`Blob read: ${result.__error}`);
+    const binary = atob(result.data);
+    const bytes = new Uint8Array(binary.length);
+    for (let i = 0; i < binary.length; i++) bytes[i] = binary.charCodeAt(i);
+    return bytes;
+  }
+
+  async *[Symbol.asyncIterator](): AsyncIterableIterator<Uint8Array> {
+    try {
+      while (true) {
+        const chunk = await this.read();
+        if (chunk.length === 0) break;
+        yield chunk;
+      }
+    } finally {
+      this.close();
+    }
+  }
+
+  /** Create a Web ReadableStream over the remaining bytes. */
+  toReadableStream(): ReadableStream<Uint8Array> {
+    return new ReadableStream({
+      pull: async (controller) => {
+        try {
+          const chunk = await this.read();
+          if (chunk.length === 0) {
+            controller.close();
+            this.close();
+          } else {
+            controller.enqueue(chunk);
+          }
+        } catch (err) {
+          controller.error(err);
+          this.close();
+        }
+      },
+      cancel: () => {
+        this.close();
+      },
+    });
+  }
+
+  close(): void {
+    if (!this.#closed) {
+      this.#closed = true;
+      this.#ffi.blobClose(this.#blobId);
+    }
+  }
+
+  async [Symbol.asyncDispose](): Promise<void> {
+    this.close();
+  }
+
+  #ensureOpen(): void {
+    if (this.#closed) throw new Error("Blob reader is closed");
+  }
+}
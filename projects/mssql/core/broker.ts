@@ -0,0 +1,111 @@
+/**
+ * Service Broker messaging types and wire helpers.
+ * @module
+ */
+
+/** A message taken off a Service Broker queue by `cn.brokerReceive()`. */
+export interface BrokerMessage {
+  conversationHandle: string;
+  /** e.g. `"//App/Request"` or a system type such as
+   * `"http://schemas.microsoft.com/SQL/ServiceBroker/EndDialog"`. */
+  messageType: string;
+  service: string;
+  contract: string;
+  sequence: number;
+  /**
+   * Raw `message_body`, or null for bodiless messages. Bodies sent as
+   * nvarchar (including text sent by `brokerSend`) are UTF-16LE:
+   * `new TextDecoder("utf-16le").decode(msg.body)`.
+   */
+  body: Uint8Array | null;
+}
+
+/** Options for `cn.brokerSend()`. */
+export interface BrokerSendOptions {
+  /** Existing conversation to send on. Omit to begin a new dialog. */
+  conversation?: string;
+  /** Initiator service for a new dialog. */
+  fromService?: string;
+  /** Target service name for a new dialog. */
+  toService?: string;
+  /** Contract for a new dialog (default: `DEFAULT`). */
+  contract?: string;
+  /** Dialog lifetime in milliseconds (rounded up to seconds). */
+  lifetime?: number;
+  /** Dialog encryption (default: false). */
+  encryption?: boolean;
+  /** Message type (default: `DEFAULT`). */
+  messageType?: string;
+  /** Message body. Strings are sent as nvarchar, bytes as-is. */
+  body?: string | Uint8Array;
+}
+
+/** Options for `cn.brokerEnd()`. */
+export interface BrokerEndOptions {
+  /** End with an error sent to the other side. */
+  error?: { code: number; description: string };
+  /** `WITH CLEANUP`: drop the conversation without notifying the other side. */
+  cleanup?: boolean;
+}
+
+/** @internal */
+export function serializeBrokerSend(opts: BrokerSendOptions): string {
+  const { body } = opts;
+  return JSON.stringify({
+    conversation: opts.conversation ?? null,
+    from_service: opts.fromService ?? null,
+    to_service: opts.toService ?? null,
+    contract: opts.contract ?? null,
+    lifetime_s: opts.lifetime !== undefined
+      ? Math.max(1, Math.ceil(opts.lifetime / 1000))
+      : null,
+    encryption: opts.encryption ?? false,
+    message_type: opts.messageType ?? null,
+    text: typeof body === "string" ? body : null,
+    binary: body instanceof Uint8Array ? btoa(String.fromCharCode(...body)) : null,
+  });
+}
+
+/** @internal */
+export function serializeBrokerEnd(
+  conversation: string,
+  opts?: BrokerEndOptions,
+): string {
+  return JSON.stringify({
+    conversation,
+    error_code: opts?.error?.code ?? null,
+    error_description: opts?.error?.description ?? null,
+    cleanup: opts?.cleanup ?? false,
+  });
+}
+
+/** A message as it crosses the FFI boundary. */
+interface RawBrokerMessage {
+  conversation_handle: string;
+  message_type: string;
+  service: string;
+  contract: string;
+  sequence: number;
+  body: string | null;
+}
+
+/** @internal */
+export function parseBrokerMessages(json: string): BrokerMessage[] {
+  const result = JSON.parse(json);
+  if (result.__error) throw new Error(`Broker receive: ${result.__error}`);
+  return (result.messages as RawBrokerMessage[]).map((m) => ({
+    conversationHandle: m.conversation_handle,
+    messageType: m.message_type,
+    service: m.service,
+    contract: m.contract,
+    sequence: m.sequence,
+    body: m.body === null ? null : decodeBase64(m.body),
+  }));
+}
+
+function decodeBase64(b64: string): Uint8Array {
+  const binary = atob(b64);
+  const bytes = new Uint8Array(binary.length);
+  for (let i = 0; i < binary.length; i++) bytes[i] = binary.charCodeAt(i);
+  return bytes;
+}
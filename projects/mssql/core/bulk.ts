@@ -89,6 +89,7 @@ export class BulkInsertBuilder {
       row.map((val) => {
         if (val instanceof Date) return val.toISOString();
         if (val instanceof Uint8Array) return btoa(String.fromCharCode(...val));
+        if (val instanceof Float32Array) return Array.from(val);
         return val ?? null;
       })
     );
@@ -0,0 +1,218 @@
+/**
+ * Catalog introspection types and wire helpers.
+ * @module
+ */
+
+/** A column, as returned by `cn.tableSchema()`. */
+export interface ColumnSchema {
+  name: string;
+  /** Declared type name, e.g. `"nvarchar"` or an alias type's name. */
+  type: string;
+  /**
+   * Characters for (n)char/(n)varchar, bytes for (var)binary, -1 for
+   * `max`; null for fixed-size types.
+   */
+  length: number | null;
+  /** Total digits for decimal/numeric; null otherwise. */
+  precision: number | null;
+  /** Fractional digits for decimal/numeric and fractional-second precision
+   * for datetime2/datetimeoffset/time; null otherwise. */
+  scale: number | null;
+  nullable: boolean;
+  identity: boolean;
+  computed: boolean;
+  /** Default constraint expression, e.g. `"(getdate())"`. */
+  default: string | null;
+  collation: string | null;
+}
+
+/** An index or primary key. */
+export interface IndexSchema {
+  name: string;
+  /** `"CLUSTERED"`, `"NONCLUSTERED"`, `"CLUSTERED COLUMNSTORE"`, ... */
+  kind: string;
+  unique: boolean;
+  primaryKey: boolean;
+  /** Key columns in key order. */
+  columns: { name: string; descending: boolean }[];
+  /** `INCLUDE` columns. */
+  included: string[];
+}
+
+/** A foreign key constraint. */
+export interface ForeignKeySchema {
+  name: string;
+  columns: string[];
+  referencedSchema: string;
+  referencedTable: string;
+  referencedColumns: string[];
+  /** `"NO_ACTION"`, `"CASCADE"`, `"SET_NULL"` or `"SET_DEFAULT"`. */
+  onDelete: string;
+  onUpdate: string;
+}
+
+/** Structure of a table or view. */
+export interface TableSchema {
+  schema: string;
+  name: string;
+  columns: ColumnSchema[];
+  primaryKey: IndexSchema | null;
+  foreignKeys: ForeignKeySchema[];
+  /** Indexes other than the primary key. */
+  indexes: IndexSchema[];
+}
+
+/** Kinds of object `cn.listObjects()` can return. */
+export type DbObjectType =
+  | "table"
+  | "view"
+  | "procedure"
+  | "function"
+  | "synonym"
+  | "sequence";
+
+/** Filter for `cn.listObjects()`. Omitted fields match everything. */
+export interface ObjectFilter {
+  schema?: string;
+  /** `LIKE` pattern on the object name, e.g. `"Order%"`. */
+  nameLike?: string;
+  types?: DbObjectType[];
+}
+
+/** A user object in the current database. */
+export interface DbObject {
+  schema: string;
+  name: string;
+  type: DbObjectType;
+  /** ISO 8601 creation time (server local). */
+  created: string;
+  /** ISO 8601 last modification time (server local). */
+  modified: string;
+}
+
+/** A database on the server, from `cn.listDatabases()`. */
+export interface DatabaseInfo {
+  name: string;
+  /** `"ONLINE"`, `"RESTORING"`, `"RECOVERING"`, `"OFFLINE"`, ... */
+  state: string;
+  /** Owning login; null if the owner SID no longer maps to a login. */
+  owner: string | null;
+  /** `"FULL"`, `"BULK_LOGGED"` or `"SIMPLE"`. */
+  recoveryModel: string;
+  compatibilityLevel: number;
+  collation: string | null;
+  readOnly: boolean;
+  /** ISO 8601 creation time (server local). */
+  created: string;
+}
+
+/** A schema in a database, from `cn.listSchemas()`. */
+export interface SchemaInfo {
+  name: string;
+  /** Owning database principal. */
+  owner: string | null;
+  /** Built-in schema: `dbo`, `sys`, `guest`, `INFORMATION_SCHEMA`, `db_*`. */
+  system: boolean;
+}
+
+/** @internal */
+export function serializeObjectFilter(filter?: ObjectFilter): string {
+  return JSON.stringify({
+    schema: filter?.schema ?? null,
+    name_like: filter?.nameLike ?? null,
+    types: filter?.types ?? [],
+  });
+}
+
+interface RawIndex {
+  name: string;
+  kind: string;
+  unique: boolean;
+  primary_key: boolean;
+  columns: { name: string; descending: boolean }[];
+  included: string[];
+}
+
+interface RawForeignKey {
+  name: string;
+  columns: string[];
+  referenced_schema: string;
+  referenced_table: string;
+  referenced_columns: string[];
+  on_delete: string;
+  on_update: string;
+}
+
+function toIndex(i: RawIndex): IndexSchema {
+  return {
+    name: i.name,
+    kind: i.kind,
+    unique: i.unique,
+    primaryKey: i.primary_key,
+    columns: i.columns,
+    included: i.included,
+  };
+}
+
+/** @internal */
+export function parseTableSchema(json: string): TableSchema {
+  const result = JSON.parse(json);
+  if (result.__error) throw new Error(`Table schema: ${result.__error}`);
+  return {
+    schema: result.schema,
+    name: result.name,
+    columns: result.columns,
+    primaryKey: result.primary_key ? toIndex(result.primary_key) : null,
+    foreignKeys: (result.foreign_keys as RawForeignKey[]).map((fk) => ({
+      name: fk.name,
+      columns: fk.columns,
+      referencedSchema: fk.referenced_schema,
+      referencedTable: fk.referenced_table,
+      referencedColumns: fk.referenced_columns,
+      onDelete: fk.on_delete,
+      onUpdate: fk.on_update,
+    })),
+    indexes: (result.indexes as RawIndex[]).map(toIndex),
+  };
+}
+
+/** @internal */
+export function parseObjectList(json: string): DbObject[] {
+  const result = JSON.parse(json);
+  if (result.__error) throw new Error(`List objects: ${result.__error}`);
+  return result.objects;
+}
+
+interface RawDatabase {
+  name: string;
+  state: string;
+  owner: string | null;
+  recovery_model: string;
+  compatibility_level: number;
+  collation: string | null;
+  read_only: boolean;
+  created: string;
+}
+
+/** @internal */
+export function parseDatabaseList(json: string): DatabaseInfo[] {
+  const result = JSON.parse(json);
+  if (result.__error) throw new Error(`List databases: ${result.__error}`);
+  return (result.databases as RawDatabase[]).map((d) => ({
+    name: d.name,
+    state: d.state,
+    owner: d.owner,
+    recoveryModel: d.recovery_model,
+    compatibilityLevel: d.compatibility_level,
+    collation: d.collation,
+    readOnly: d.read_only,
+    created: d.created,
+  }));
+}
+
+/** @internal */
+export function parseSchemaList(json: string): SchemaInfo[] {
+  const result = JSON.parse(json);
+  if (result.__error) throw new Error(`List schemas: ${result.__error}`);
+  return result.schemas;
+}
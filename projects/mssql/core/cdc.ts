@@ -0,0 +1,103 @@
+/**
+ * Change Data Capture reader types and wire helpers.
+ * @module
+ */
+
+/** Options for `cn.cdcChanges()`. */
+export interface CdcReadOptions {
+  /**
+   * Last LSN already processed: the `toLsn` of the previous read. Omit to
+   * start from the capture instance's oldest retained change.
+   */
+  sinceLsn?: string;
+  /**
+   * Start from the first change committed after this time instead of an
+   * LSN. Compared with the server's local commit times.
+   */
+  sinceTime?: Date | string;
+  /** One row per key with its final state (`fn_cdc_get_net_changes_*`). */
+  net?: boolean;
+  /** Include update before-images (`all update old`). Ignored for `net`. */
+  updateOld?: boolean;
+}
+
+/** One captured change. */
+export interface CdcChange<T = Record<string, unknown>> {
+  /** Commit LSN, as `0x` + 20 hex digits. */
+  lsn: string;
+  /** Order within the transaction; null for net changes. */
+  seqval: string | null;
+  operation: "delete" | "insert" | "update_before" | "update_after" | "merge";
+  /** Commit time of `lsn`. */
+  commitTime: unknown;
+  /** Base64 bitmask of updated columns; null for net changes. */
+  updateMask: string | null;
+  /** Captured column values. */
+  row: T;
+}
+
+/** Result of a CDC read. */
+export interface CdcChangeSet<T = Record<string, unknown>> {
+  /** First LSN read, or null when there was nothing new. */
+  fromLsn: string | null;
+  /** Pass as `sinceLsn` on the next read. */
+  toLsn: string;
+  /** Commit time of `toLsn`. */
+  toTime: unknown;
+  /** Oldest LSN the capture instance still retains. */
+  minLsn: string;
+  /**
+   * `sinceLsn` is older than the capture instance's retention: changes were
+   * cleaned up, so re-sync and continue from `toLsn`.
+   */
+  resyncRequired: boolean;
+  changes: CdcChange<T>[];
+}
+
+/** @internal */
+export function serializeCdcRead(
+  captureInstance: string,
+  opts?: CdcReadOptions,
+): string {
+  const sinceTime = opts?.sinceTime;
+  return JSON.stringify({
+    capture_instance: captureInstance,
+    since_lsn: opts?.sinceLsn ?? null,
+    since_time: sinceTime instanceof Date
+      ? sinceTime.toISOString()
+      : sinceTime ?? null,
+    net: opts?.net ?? false,
+    update_old: opts?.updateOld ?? false,
+  });
+}
+
+/** A change as it crosses the FFI boundary. */
+interface RawCdcChange {
+  lsn: string;
+  seqval: string | null;
+  operation: CdcChange["operation"];
+  commit_time: unknown;
+  update_mask: string | null;
+  row: Record<string, unknown>;
+}
+
+/** @internal */
+export function parseCdcChangeSet<T>(json: string): CdcChangeSet<T> {
+  const result = JSON.parse(json);
+  if (result.__error) throw new Error(`CDC read: ${result.__error}`);
+  return {
+    fromLsn: result.from_lsn,
+    toLsn: result.to_lsn,
+    toTime: result.to_time,
+    minLsn: result.min_lsn,
+    resyncRequired: result.resync_required,
+    changes: (result.changes as RawCdcChange[]).map((c) => ({
+      lsn: c.lsn,
+      seqval: c.seqval,
+      operation: c.operation,
+      commitTime: c.commit_time,
+      updateMask: c.update_mask,
+      row: c.row as T,
+    })),
+  };
+}
@@ -0,0 +1,100 @@
+/**
+ * Compressed query results.
+ * @module
+ */
+
+import type { RuntimeFFI } from "./runtime.ts";
+import type { CommandOptions } from "./types.ts";
+
+/** Codec for compressed results: LZ4 frame format or Zstandard. */
+export type Compression = "lz4" | "zstd";
+
+/** Options for `cn.queryCompressed()`. */
+export interface CompressOptions extends CommandOptions {
+  compress: Compression;
+  /** Results up to this many bytes come back uncompressed. Default: 1 MiB. */
+  compressThreshold?: number;
+}
+
+/**
+ * Rows of a query as bytes. Small enough to send to a worker as-is; decode
+ * with {@link decodeCompressedRows}.
+ */
+export interface CompressedResult {
+  /** Codec of `data`; null when it is plain UTF-8 JSON under the threshold. */
+  codec: Compression | null;
+  /** Byte length of the JSON rows once decompressed. */
+  length: number;
+  data: Uint8Array;
+}
+
+/** Decompress `data` to the `length` bytes of UTF-8 JSON it holds. */
+export type Decompressor = (
+  data: Uint8Array,
+  codec: Compression,
+  length: number,
+) => Uint8Array;
+
+/** Header the driver returns in place of a compressed result. */
+interface CompressedHeader {
+  __compressed: Compression;
+  length: number;
+  compressedLength: number;
+}
+
+/**
+ * @internal Turn a query payload into a {@link CompressedResult}, collecting
+ * the compressed bytes with `take` when the payload is a header.
+ */
+export function toCompressedResult(
+  payload: string,
+  take: (length: number) => Uint8Array | null,
+): CompressedResult {
+  // Rows are an array, so an object is always the header
+  if (!payload.startsWith("{")) {
+    const data = new TextEncoder().encode(payload);
+    return { codec: null, length: data.length, data };
+  }
+  const header = JSON.parse(payload) as CompressedHeader;
+  const data = take(header.compressedLength);
+  if (data === null) {
+    throw new Error("Compressed result is no longer available");
+  }
+  return { codec: header.__compressed, length: header.length, data };
+}
+
+/**
+ * @internal A {@link Decompressor} for every codec built into the native
+ * library; see `mssql.nativeDecompressor()`.
+ */
+export function nativeDecompressor(ffi: RuntimeFFI): Decompressor {
+  return (data, codec, length) => {
+    const bytes = ffi.decompress(codec, data, length);
+    if (bytes === null) {
+      throw new Error(`Could not decompress ${codec} data`);
+    }
+    return bytes;
+  };
+}
+
+/**
+ * Decode the rows of a {@link CompressedResult}. Pass a decompressor for
+ * compressed data: `await mssql.nativeDecompressor()` handles both codecs
+ * in every runtime, or use one of your own, e.g. Node's
+ * `zlib.zstdDecompressSync`.
+ *
+ * @throws If `data` is compressed and no `decompress` is given.
+ */
+export function decodeCompressedRows<T = Record<string, unknown>>(
+  result: CompressedResult,
+  decompress?: Decompressor,
+): T[] {
+  let bytes = result.data;
+  if (result.codec !== null) {
+    if (!decompress) {
+      throw new Error(`A ${result.codec} decompressor is needed to decode`);
+    }
+    bytes = decompress(result.data, result.codec, result.length);
+  }
+  return JSON.parse(new TextDecoder().decode(bytes)) as T[];
+}
@@ -3,7 +3,17 @@
  * @module
  */
 
-import type { MssqlConfig, NormalizedConfig } from "./types.ts";
+import type {
+  ApplicationIntent,
+  BigIntMode,
+  ColumnCase,
+  DecimalMode,
+  MssqlConfig,
+  NormalizedConfig,
+  TemporalMode,
+  TlsVersion,
+  VarcharDecodePolicy,
+} from "./types.ts";
 
 const DEFAULTS: Omit<NormalizedConfig, "server" | "auth"> = {
   port: 1433,
@@ -16,6 +26,27 @@ const DEFAULTS: Omit<NormalizedConfig, "server" | "auth"> = {
   instance_name: null,
   packet_size: 4096,
   pool: null,
+  decimal_mode: "string",
+  temporal_mode: "iso",
+  bigint_mode: "number-or-string",
+  parse_json: false,
+  column_case: "as-is",
+  varchar_encoding: null,
+  varchar_decode: "lossy",
+  tls_min_version: "1.2",
+  tls_cipher_suites: null,
+  ca_cert_path: null,
+  ca_cert_pem: null,
+  failover_partner: null,
+  multi_subnet_failover: false,
+  application_intent: "read_write",
+  tcp_keepalive_ms: 30000,
+  tcp_nodelay: true,
+  connect_retry_count: 1,
+  connect_retry_interval_ms: 10000,
+  slow_query_ms: null,
+  xact_abort: false,
+  label: null,
 };
 
 // ── ADO.NET key aliases ─────────────────────────────────────
@@ -45,10 +76,22 @@ const KEY_MAP: Record<string, string> = {
   "app": "app_name",
   "packet size": "packet_size",
   "domain": "domain",
+  "server spn": "server_spn",
+  "serverspn": "server_spn",
   "authentication": "authentication",
   "access token": "access_token",
+  "tenant id": "tenant_id",
   "min pool size": "min_pool_size",
   "max pool size": "max_pool_size",
+  "failover partner": "failover_partner",
+  "multisubnetfailover": "multi_subnet_failover",
+  "multi subnet failover": "multi_subnet_failover",
+  "applicationintent": "application_intent",
+  "application intent": "application_intent",
+  "connectretrycount": "connect_retry_count",
+  "connect retry count": "connect_retry_count",
+  "connectretryinterval": "connect_retry_interval",
+  "connect retry interval": "connect_retry_interval",
 };
 
 export function parseConnection(input: string | MssqlConfig): NormalizedConfig {
@@ -56,6 +99,9 @@ export function parseConnection(input: string | MssqlConfig): NormalizedConfig {
     if (input.startsWith("mssql://") || input.startsWith("sqlserver://")) {
       return parseUrl(input);
     }
+    if (/^jdbc:sqlserver:\/\//i.test(input)) {
+      return parseJdbc(input);
+    }
     return parseAdoNet(input);
   }
   return parseConfigObject(input);
@@ -79,15 +125,24 @@ function parseAdoNet(connStr: string): NormalizedConfig {
   const isWindows = ["true", "yes", "sspi"].includes(
     (map.get("integrated_security") ?? "").toLowerCase(),
   );
-  const user = map.get("user");
+  const { user, domain } = splitDomainUser(map.get("user"), map.get("domain"));
   const password = map.get("password") ?? "";
-  const domain = map.get("domain");
   const authentication = (map.get("authentication") ?? "").toLowerCase();
   const accessToken = map.get("access_token");
 
   let auth: NormalizedConfig["auth"];
   if (authentication === "activedirectoryaccesstoken" && accessToken) {
     auth = { type: "azure_ad_token", token: accessToken };
+  } else if (
+    authentication === "active directory managed identity" ||
+    authentication === "active directory msi"
+  ) {
+    // User Id selects a user-assigned identity, as in Microsoft.Data.SqlClient
+    auth = { type: "azure_ad_msi", client_id: user ?? null };
+  } else if (authentication === "active directory service principal") {
+    auth = servicePrincipal(map.get("tenant_id"), user, password);
+  } else if (authentication === "kerberos") {
+    auth = { type: "kerberos", spn: map.get("server_spn") ?? null };
   } else if (isWindows) {
     auth = { type: "windows" };
   } else if (domain && user) {
@@ -124,6 +179,20 @@ function parseAdoNet(connStr: string): NormalizedConfig {
     ),
     app_name: map.get("app_name") ?? DEFAULTS.app_name,
     packet_size: parseInt(map.get("packet_size") ?? "") || DEFAULTS.packet_size,
+    failover_partner: map.get("failover_partner") ?? DEFAULTS.failover_partner,
+    multi_subnet_failover: parseBool(
+      map.get("multi_subnet_failover"),
+      DEFAULTS.multi_subnet_failover,
+    ),
+    application_intent: parseIntent(map.get("application_intent")),
+    connect_retry_count: parseNonNegative(
+      map.get("connect_retry_count"),
+      DEFAULTS.connect_retry_count,
+    ),
+    connect_retry_interval_ms: parseSeconds(
+      map.get("connect_retry_interval"),
+      DEFAULTS.connect_retry_interval_ms,
+    ),
     pool: (minPool !== undefined || maxPool !== undefined)
       ? { min: minPool, max: maxPool }
       : null,
@@ -209,10 +278,12 @@ function parseUrl(urlStr: string): NormalizedConfig {
   const url = new URL(normalized);
 
   const params = url.searchParams;
-  const user = url.username ? decodeURIComponent(url.username) : undefined;
+  const { user, domain } = splitDomainUser(
+    url.username ? decodeURIComponent(url.username) : undefined,
+    params.get("domain") ?? undefined,
+  );
   const password = url.password ? decodeURIComponent(url.password) : "";
   const database = url.pathname.replace(/^\//, "") || DEFAULTS.database;
-  const domain = params.get("domain") ?? undefined;
   const isWindows = parseBool(params.get("integratedSecurity"), false);
   const authentication = (params.get("authentication") ?? "").toLowerCase();
   const token = params.get("token") ?? undefined;
@@ -220,6 +291,18 @@ function parseUrl(urlStr: string): NormalizedConfig {
   let auth: NormalizedConfig["auth"];
   if (authentication === "azure-active-directory-access-token" && token) {
     auth = { type: "azure_ad_token", token };
+  } else if (authentication.startsWith("azure-active-directory-msi")) {
+    auth = { type: "azure_ad_msi", client_id: params.get("clientId") };
+  } else if (
+    authentication === "azure-active-directory-service-principal-secret"
+  ) {
+    auth = servicePrincipal(
+      params.get("tenantId"),
+      params.get("clientId"),
+      params.get("clientSecret"),
+    );
+  } else if (authentication === "kerberos") {
+    auth = { type: "kerberos", spn: params.get("spn") };
   } else if (isWindows) {
     auth = { type: "windows" };
   } else if (domain && user) {
@@ -232,6 +315,7 @@ function parseUrl(urlStr: string): NormalizedConfig {
 
   const minPool = parseInt(params.get("minPoolSize") ?? "") || undefined;
   const maxPool = parseInt(params.get("maxPoolSize") ?? "") || undefined;
+  const caCertPath = params.get("caCertPath");
 
   return {
     ...DEFAULTS,
@@ -243,7 +327,7 @@ function parseUrl(urlStr: string): NormalizedConfig {
     trust_server_certificate: parseBool(
       params.get("trustServerCertificate") ??
         params.get("trust_server_certificate"),
-      DEFAULTS.trust_server_certificate,
+      caCertPath ? false : DEFAULTS.trust_server_certificate,
     ),
     connect_timeout_ms: parseSeconds(
       params.get("connectTimeout"),
@@ -260,9 +344,225 @@ function parseUrl(urlStr: string): NormalizedConfig {
     pool: (minPool !== undefined || maxPool !== undefined)
       ? { min: minPool, max: maxPool }
       : null,
+    decimal_mode: parseDecimalMode(params.get("decimalMode")),
+    temporal_mode: parseTemporalMode(params.get("temporalMode")),
+    bigint_mode: parseBigIntMode(params.get("bigintMode")),
+    parse_json: parseBool(params.get("parseJson"), DEFAULTS.parse_json),
+    column_case: parseColumnCase(params.get("columnCase")),
+    varchar_encoding: params.get("varcharEncoding") ?? DEFAULTS.varchar_encoding,
+    varchar_decode: parseVarcharDecode(params.get("varcharDecode")),
+    tls_min_version: parseTlsVersion(params.get("tlsMinVersion")),
+    tls_cipher_suites: parseList(params.get("tlsCipherSuites")) ??
+      DEFAULTS.tls_cipher_suites,
+    ca_cert_path: caCertPath ?? DEFAULTS.ca_cert_path,
+    failover_partner: params.get("failoverPartner") ??
+      DEFAULTS.failover_partner,
+    multi_subnet_failover: parseBool(
+      params.get("multiSubnetFailover"),
+      DEFAULTS.multi_subnet_failover,
+    ),
+    application_intent: parseIntent(params.get("applicationIntent")),
+    tcp_keepalive_ms: parseNonNegative(
+      params.get("tcpKeepAlive"),
+      DEFAULTS.tcp_keepalive_ms,
+    ),
+    tcp_nodelay: parseBool(params.get("tcpNoDelay"), DEFAULTS.tcp_nodelay),
+    connect_retry_count: parseNonNegative(
+      params.get("connectRetryCount"),
+      DEFAULTS.connect_retry_count,
+    ),
+    connect_retry_interval_ms: parseSeconds(
+      params.get("connectRetryInterval"),
+      DEFAULTS.connect_retry_interval_ms,
+    ),
+    slow_query_ms: params.has("slowQueryMs")
+      ? parseNonNegative(params.get("slowQueryMs"), 0)
+      : DEFAULTS.slow_query_ms,
+    xact_abort: parseBool(params.get("xactAbort"), DEFAULTS.xact_abort),
+    label: params.get("label") ?? DEFAULTS.label,
+  };
+}
+
+// ── JDBC ────────────────────────────────────────────────────
+
+/** JDBC property names (lowercased) mapped to the key used below. */
+const JDBC_PROPERTIES: Record<string, string> = {
+  "servername": "server",
+  "portnumber": "port",
+  "port": "port",
+  "instancename": "instance",
+  "databasename": "database",
+  "database": "database",
+  "user": "user",
+  "username": "user",
+  "password": "password",
+  "domain": "domain",
+  "integratedsecurity": "integrated_security",
+  "authenticationscheme": "authentication_scheme",
+  "authentication": "authentication",
+  "msiclientid": "msi_client_id",
+  "serverspn": "server_spn",
+  "encrypt": "encrypt",
+  "trustservercertificate": "trust_server_certificate",
+  "logintimeout": "connect_timeout",
+  "querytimeout": "request_timeout",
+  "applicationname": "app_name",
+  "packetsize": "packet_size",
+  "failoverpartner": "failover_partner",
+  "multisubnetfailover": "multi_subnet_failover",
+  "applicationintent": "application_intent",
+  "connectretrycount": "connect_retry_count",
+  "connectretryinterval": "connect_retry_interval",
+};
+
+/**
+ * Parse `jdbc:sqlserver://host[\instance][:port][;property=value]...`.
+ * Unknown properties throw so a migrated URL never silently loses a setting.
+ */
+function parseJdbc(jdbcStr: string): NormalizedConfig {
+  const [address, ...segments] = splitJdbcProperties(
+    jdbcStr.replace(/^jdbc:sqlserver:\/\//i, ""),
+  );
+  const map = new Map<string, string>();
+  for (const segment of segments) {
+    const eq = segment.indexOf("=");
+    const name = (eq === -1 ? segment : segment.substring(0, eq)).trim();
+    if (!name) continue;
+    const key = JDBC_PROPERTIES[name.toLowerCase()];
+    if (!key) throw new Error(`Unsupported JDBC property: ${name}`);
+    map.set(key, unbraceJdbcValue(segment.substring(eq + 1).trim()));
+  }
+
+  const addr = parseJdbcAddress(address.trim());
+  const host = map.get("server") ?? (addr.host || "localhost");
+  const portRaw = map.get("port") ?? addr.port;
+  const port = portRaw ? parseInt(portRaw) : DEFAULTS.port;
+  if (isNaN(port)) throw new Error(`Invalid JDBC port: ${portRaw}`);
+
+  const { user, domain } = splitDomainUser(map.get("user"), map.get("domain"));
+  const password = map.get("password") ?? "";
+  const scheme = (map.get("authentication_scheme") ?? "").toLowerCase();
+  const authentication = (map.get("authentication") ?? "").toLowerCase();
+
+  let auth: NormalizedConfig["auth"];
+  if (
+    authentication === "activedirectorymanagedidentity" ||
+    authentication === "activedirectorymsi"
+  ) {
+    auth = {
+      type: "azure_ad_msi",
+      client_id: map.get("msi_client_id") ?? user ?? null,
+    };
+  } else if (
+    authentication && authentication !== "sqlpassword" &&
+    authentication !== "notspecified"
+  ) {
+    throw new Error(
+      `Unsupported JDBC authentication: ${map.get("authentication")}`,
+    );
+  } else if (parseBool(map.get("integrated_security"), false)) {
+    if (scheme === "javakerberos") {
+      auth = { type: "kerberos", spn: map.get("server_spn") ?? null };
+    } else if (scheme === "ntlm" && user && domain) {
+      auth = { type: "ntlm", username: user, password, domain };
+    } else {
+      auth = { type: "windows" };
+    }
+  } else if (domain && user) {
+    auth = { type: "ntlm", username: user, password, domain };
+  } else if (user) {
+    auth = { type: "sql", username: user, password };
+  } else {
+    auth = { type: "windows" };
+  }
+
+  const encrypt = (map.get("encrypt") ?? "").toLowerCase();
+
+  return {
+    ...DEFAULTS,
+    server: host,
+    port,
+    instance_name: map.get("instance") ?? addr.instance ??
+      DEFAULTS.instance_name,
+    database: map.get("database") ?? DEFAULTS.database,
+    auth,
+    encrypt: encrypt === "strict" || encrypt === "mandatory"
+      ? true
+      : encrypt === "optional"
+      ? false
+      : parseBool(map.get("encrypt"), DEFAULTS.encrypt),
+    trust_server_certificate: parseBool(
+      map.get("trust_server_certificate"),
+      DEFAULTS.trust_server_certificate,
+    ),
+    connect_timeout_ms: parseSeconds(
+      map.get("connect_timeout"),
+      DEFAULTS.connect_timeout_ms,
+    ),
+    request_timeout_ms: parseSeconds(
+      map.get("request_timeout"),
+      DEFAULTS.request_timeout_ms,
+    ),
+    app_name: map.get("app_name") ?? DEFAULTS.app_name,
+    packet_size: parseInt(map.get("packet_size") ?? "") || DEFAULTS.packet_size,
+    failover_partner: map.get("failover_partner") ?? DEFAULTS.failover_partner,
+    multi_subnet_failover: parseBool(
+      map.get("multi_subnet_failover"),
+      DEFAULTS.multi_subnet_failover,
+    ),
+    application_intent: parseIntent(map.get("application_intent")),
+    connect_retry_count: parseNonNegative(
+      map.get("connect_retry_count"),
+      DEFAULTS.connect_retry_count,
+    ),
+    connect_retry_interval_ms: parseSeconds(
+      map.get("connect_retry_interval"),
+      DEFAULTS.connect_retry_interval_ms,
+    ),
   };
 }
 
+/** Split on `;`, leaving semicolons inside `{...}` values alone. */
+function splitJdbcProperties(s: string): string[] {
+  const parts: string[] = [];
+  let depth = 0;
+  let start = 0;
+  for (let i = 0; i < s.length; i++) {
+    if (s[i] === "{") depth++;
+    else if (s[i] === "}" && depth > 0) depth--;
+    else if (s[i] === ";" && depth === 0) {
+      parts.push(s.substring(start, i));
+      start = i + 1;
+    }
+  }
+  parts.push(s.substring(start));
+  return parts;
+}
+
+/** JDBC wraps values containing special characters in braces, with `}}` for `}`. */
+function unbraceJdbcValue(value: string): string {
+  return value.startsWith("{") && value.endsWith("}")
+    ? value.slice(1, -1).replaceAll("}}", "}")
+    : value;
+}
+
+/** `host`, `host:port`, `host\instance` or `host\instance:port`. */
+function parseJdbcAddress(
+  raw: string,
+): { host: string; port: string | null; instance: string | null } {
+  const colon = raw.lastIndexOf(":");
+  const port = colon === -1 ? null : raw.substring(colon + 1);
+  const hostPart = colon === -1 ? raw : raw.substring(0, colon);
+  const backslash = hostPart.indexOf("\\");
+  return backslash === -1
+    ? { host: hostPart, port, instance: null }
+    : {
+      host: hostPart.substring(0, backslash),
+      port,
+      instance: hostPart.substring(backslash + 1),
+    };
+}
+
 // ── Config Object ───────────────────────────────────────────
 
 function parseConfigObject(cfg: MssqlConfig): NormalizedConfig {
@@ -272,6 +572,8 @@ function parseConfigObject(cfg: MssqlConfig): NormalizedConfig {
   let auth: NormalizedConfig["auth"];
   if (authCfg?.type === "windows") {
     auth = { type: "windows" };
+  } else if (authCfg?.type === "kerberos") {
+    auth = { type: "kerberos", spn: authCfg.options?.spn ?? null };
   } else if (authCfg?.type === "ntlm") {
     auth = {
       type: "ntlm",
@@ -293,6 +595,22 @@ function parseConfigObject(cfg: MssqlConfig): NormalizedConfig {
       );
     }
     auth = { type: "azure_ad_token", token };
+  } else if (
+    authCfg?.type === "azure-active-directory-msi-vm" ||
+    authCfg?.type === "azure-active-directory-msi-app-service"
+  ) {
+    // The native library fetches and refreshes the token itself
+    auth = { type: "azure_ad_msi", client_id: authCfg.options?.clientId ?? null };
+  } else if (
+    authCfg?.type === "azure-active-directory-service-principal-secret" &&
+    !cfg.tokenProvider
+  ) {
+    // No callback: the native library runs the client credentials flow
+    auth = servicePrincipal(
+      authCfg.options?.tenantId,
+      authCfg.options?.clientId,
+      authCfg.options?.clientSecret,
+    );
   } else if (
     authCfg?.type === "azure-active-directory-default" ||
     authCfg?.type === "azure-active-directory-service-principal-secret"
@@ -318,7 +636,9 @@ function parseConfigObject(cfg: MssqlConfig): NormalizedConfig {
     auth,
     encrypt: opts.encrypt ?? DEFAULTS.encrypt,
     trust_server_certificate: opts.trustServerCertificate ??
-      DEFAULTS.trust_server_certificate,
+      (opts.caCertPath || opts.caCertPem
+        ? false
+        : DEFAULTS.trust_server_certificate),
     connect_timeout_ms: opts.connectTimeout ?? DEFAULTS.connect_timeout_ms,
     request_timeout_ms: opts.requestTimeout ?? DEFAULTS.request_timeout_ms,
     app_name: opts.appName ?? DEFAULTS.app_name,
@@ -331,6 +651,29 @@ function parseConfigObject(cfg: MssqlConfig): NormalizedConfig {
         idle_timeout_ms: cfg.pool.idleTimeoutMillis,
       }
       : null,
+    decimal_mode: opts.decimalMode ?? DEFAULTS.decimal_mode,
+    temporal_mode: opts.temporalMode ?? DEFAULTS.temporal_mode,
+    bigint_mode: opts.bigintMode ?? DEFAULTS.bigint_mode,
+    parse_json: opts.parseJson ?? DEFAULTS.parse_json,
+    column_case: opts.columnCase ?? DEFAULTS.column_case,
+    varchar_encoding: opts.varcharEncoding ?? DEFAULTS.varchar_encoding,
+    varchar_decode: opts.varcharDecode ?? DEFAULTS.varchar_decode,
+    tls_min_version: opts.tlsMinVersion ?? DEFAULTS.tls_min_version,
+    tls_cipher_suites: opts.tlsCipherSuites ?? DEFAULTS.tls_cipher_suites,
+    ca_cert_path: opts.caCertPath ?? DEFAULTS.ca_cert_path,
+    ca_cert_pem: opts.caCertPem ?? DEFAULTS.ca_cert_pem,
+    failover_partner: opts.failoverPartner ?? DEFAULTS.failover_partner,
+    multi_subnet_failover: opts.multiSubnetFailover ??
+      DEFAULTS.multi_subnet_failover,
+    application_intent: parseIntent(opts.applicationIntent),
+    tcp_keepalive_ms: opts.tcpKeepAlive ?? DEFAULTS.tcp_keepalive_ms,
+    tcp_nodelay: opts.tcpNoDelay ?? DEFAULTS.tcp_nodelay,
+    connect_retry_count: opts.connectRetryCount ?? DEFAULTS.connect_retry_count,
+    connect_retry_interval_ms: opts.connectRetryInterval ??
+      DEFAULTS.connect_retry_interval_ms,
+    slow_query_ms: opts.slowQueryMs ?? DEFAULTS.slow_query_ms,
+    xact_abort: opts.xactAbort ?? DEFAULTS.xact_abort,
+    label: opts.label ?? DEFAULTS.label,
   };
 
   if (cfg.tokenProvider) {
@@ -359,11 +702,101 @@ export async function resolveTokenProvider(
 
 // ── Helpers ─────────────────────────────────────────────────
 
+/** Split a `DOMAIN\user` login into NTLM domain and user name. */
+function splitDomainUser(
+  user: string | undefined,
+  domain: string | undefined,
+): { user: string | undefined; domain: string | undefined } {
+  const slash = user?.indexOf("\\") ?? -1;
+  if (domain || !user || slash <= 0) return { user, domain };
+  return { user: user.substring(slash + 1), domain: user.substring(0, slash) };
+}
+
+function servicePrincipal(
+  tenantId: string | null | undefined,
+  clientId: string | null | undefined,
+  clientSecret: string | null | undefined,
+): NormalizedConfig["auth"] {
+  if (!tenantId || !clientId || !clientSecret) {
+    throw new Error(
+      "Service principal authentication requires a tenant id, client id and client secret",
+    );
+  }
+  return {
+    type: "azure_ad_sp",
+    tenant_id: tenantId,
+    client_id: clientId,
+    client_secret: clientSecret,
+  };
+}
+
 function parseBool(val: string | null | undefined, fallback: boolean): boolean {
   if (val === null || val === undefined) return fallback;
   return ["true", "yes", "1"].includes(val.toLowerCase());
 }
 
+function parseDecimalMode(val: string | null | undefined): DecimalMode {
+  return val?.toLowerCase() === "number" ? "number" : DEFAULTS.decimal_mode;
+}
+
+function parseTemporalMode(val: string | null | undefined): TemporalMode {
+  const mode = val?.toLowerCase();
+  return mode === "epoch" || mode === "tagged" ? mode : DEFAULTS.temporal_mode;
+}
+
+function parseColumnCase(val: string | null | undefined): ColumnCase {
+  switch (val?.toLowerCase()) {
+    case "camelcase":
+      return "camelCase";
+    case "lowercase":
+      return "lowercase";
+    default:
+      return DEFAULTS.column_case;
+  }
+}
+
+function parseBigIntMode(val: string | null | undefined): BigIntMode {
+  const mode = val?.toLowerCase();
+  return mode === "always-string" || mode === "tagged-bigint"
+    ? mode
+    : DEFAULTS.bigint_mode;
+}
+
+function parseVarcharDecode(
+  val: string | null | undefined,
+): VarcharDecodePolicy {
+  const policy = val?.toLowerCase();
+  return policy === "error" || policy === "latin1"
+    ? policy
+    : DEFAULTS.varchar_decode;
+}
+
+function parseTlsVersion(val: string | null | undefined): TlsVersion {
+  return val === "1.3" ? val : DEFAULTS.tls_min_version;
+}
+
+function parseIntent(
+  val: ApplicationIntent | string | null | undefined,
+): NormalizedConfig["application_intent"] {
+  return val?.toLowerCase() === "readonly"
+    ? "read_only"
+    : DEFAULTS.application_intent;
+}
+
+function parseList(val: string | null | undefined): string[] | null {
+  const items = val?.split(",").map((s) => s.trim()).filter(Boolean);
+  return items?.length ? items : null;
+}
+
+/** A non-negative integer, e.g. milliseconds or a count. */
+function parseNonNegative(
+  val: string | null | undefined,
+  fallback: number,
+): number {
+  const n = parseInt(val ?? "");
+  return isNaN(n) || n < 0 ? fallback : n;
+}
+
 function parseSeconds(
   val: string | null | undefined,
   fallbackMs: number,
@@ -7,13 +7,26 @@ import type { RuntimeFFI } from "./runtime.ts";
 import { INVALID_HANDLE } from "./runtime.ts";
 import type {
   CommandOptions,
+  ExecuteResult,
   FilestreamMode,
+  FilestreamWriteMode,
+  InsertOptions,
+  InsertResult,
   IsolationLevel,
+  PageRequest,
+  PageResult,
   Params,
   ParamValue,
+  QueryPlanOptions,
+  QueryPlanResult,
+  ScriptOptions,
+  ScriptProgressEvent,
+  ScriptResult,
   SerializedCommand,
   SerializedParam,
   StreamOptions,
+  TransactionOptions,
+  TransactionState,
   TypedParam,
 } from "./types.ts";
 import { ExecResult } from "./exec_result.ts";
@@ -21,6 +34,54 @@ import type { ExecResultRaw } from "./exec_result.ts";
 import { Transaction } from "./transaction.ts";
 import { QueryStream } from "./stream.ts";
 import { BulkInsertBuilder } from "./bulk.ts";
+import { BlobReader } from "./blob.ts";
+import {
+  parseBrokerMessages,
+  serializeBrokerEnd,
+  serializeBrokerSend,
+} from "./broker.ts";
+import type {
+  BrokerEndOptions,
+  BrokerMessage,
+  BrokerSendOptions,
+} from "./broker.ts";
+import { QueryNotification } from "./notification.ts";
+import { parseChangeTrackingResult } from "./tracking.ts";
+import { parseCdcChangeSet, serializeCdcRead } from "./cdc.ts";
+import { parseStatisticsResult } from "./statistics.ts";
+import type { QueryStatisticsResult } from "./statistics.ts";
+import {
+  parseDatabaseList,
+  parseObjectList,
+  parseSchemaList,
+  parseTableSchema,
+  serializeObjectFilter,
+} from "./catalog.ts";
+import type {
+  DatabaseInfo,
+  DbObject,
+  ObjectFilter,
+  SchemaInfo,
+  TableSchema,
+} from "./catalog.ts";
+import {
+  buildTemporalQuery,
+  PERIOD_COLUMNS_SQL,
+  toTemporalRows,
+} from "./temporal.ts";
+import type {
+  PeriodColumns,
+  SystemTime,
+  TemporalQueryOptions,
+  TemporalRow,
+} from "./temporal.ts";
+import type { CdcChangeSet, CdcReadOptions } from "./cdc.ts";
+import type { ChangeTrackingResult } from "./tracking.ts";
+import {
+  buildKeyringConfig,
+  ENCRYPTED_COLUMNS_SQL,
+} from "./encryption.ts";
+import type { EncryptedColumnRow, KeyStoreProvider } from "./encryption.ts";
 import {
   FilestreamDuplex,
   FilestreamHandle,
@@ -28,6 +89,10 @@ import {
   FilestreamWritable,
 } from "./filestream.ts";
 import type { FilestreamWebResult } from "./filestream.ts";
+import { toCompressedResult } from "./compress.ts";
+import type { CompressedResult, CompressOptions } from "./compress.ts";
+import { RowBuffer } from "./row_buffer.ts";
+import type { BufferedQueryOptions } from "./row_buffer.ts";
 import type { Readable, Writable, Duplex } from "node:stream";
 
 /**
@@ -47,6 +112,7 @@ export class MssqlConnection implements Disposable, AsyncDisposable {
   #hasError = false;
   #streams: Set<QueryStream<unknown>> = new Set();
   #transactions: Set<Transaction> = new Set();
+  #label: string | null = null;
 
   /** @internal */
   constructor(connId: bigint, ffi: RuntimeFFI, poolId: bigint | null = null) {
@@ -71,6 +137,183 @@ export class MssqlConnection implements Disposable, AsyncDisposable {
     return JSON.parse(result) as T[];
   }
 
+  /**
+   * Execute a query and return its rows along with the execution plan XML.
+   *
+   * ```ts
+   * const { rows, plans } = await cn.queryWithPlan(
+   *   "SELECT * FROM Orders WHERE CustomerId = @id",
+   *   { id: 42 },
+   *   { capturePlan: "actual" },
+   * );
+   * ```
+   */
+  async queryWithPlan<T = Record<string, unknown>>(
+    sql: string,
+    params?: Params,
+    opts?: QueryPlanOptions,
+  ): Promise<QueryPlanResult<T>> {
+    this.#ensureOpen(opts);
+    const cmdJson = serializeCommand(sql, params, opts, {
+      capture_plan: opts?.capturePlan ?? "actual",
+    });
+    const result = await this.#ffi.query(this.#connId, cmdJson);
+    if (result === null) {
+      this.#hasError = true;
+      throw new Error(this.#ffi.lastError(this.#connId) ?? "Query failed");
+    }
+    return JSON.parse(result) as QueryPlanResult<T>;
+  }
+
+  /**
+   * Execute a query under `SET STATISTICS IO, TIME ON` and return its rows
+   * with per-table reads and compile/execution times.
+   */
+  async queryWithStatistics<T = Record<string, unknown>>(
+    sql: string,
+    params?: Params,
+    opts?: CommandOptions,
+  ): Promise<QueryStatisticsResult<T>> {
+    this.#ensureOpen(opts);
+    const cmdJson = serializeCommand(sql, params, opts, {
+      capture_statistics: true,
+    });
+    const result = await this.#ffi.query(this.#connId, cmdJson);
+    if (result === null) {
+      this.#hasError = true;
+      throw new Error(this.#ffi.lastError(this.#connId) ?? "Query failed");
+    }
+    return parseStatisticsResult<T>(result);
+  }
+
+  /**
+   * Execute a `FOR JSON` query and return its document, parsed. SQL Server
+   * splits long `FOR JSON` output across rows; they are joined back together
+   * before parsing. A query returning no rows, or NULL, gives `null`.
+   *
+   * ```ts
+   * const orders = await cn.queryJson<Order[]>(
+   *   "SELECT Id, Total, (SELECT Sku, Qty FROM Lines l WHERE l.OrderId = o.Id " +
+   *     "FOR JSON PATH) AS lines FROM Orders o FOR JSON PATH",
+   * );
+   * ```
+   */
+  async queryJson<T = unknown>(
+    sql: string,
+    params?: Params,
+    opts?: CommandOptions,
+  ): Promise<T | null> {
+    this.#ensureOpen(opts);
+    const cmdJson = serializeCommand(sql, params, opts, { for_json: true });
+    const result = await this.#ffi.query(this.#connId, cmdJson);
+    if (result === null) {
+      this.#hasError = true;
+      throw new Error(this.#ffi.lastError(this.#connId) ?? "Query failed");
+    }
+    return JSON.parse(result) as T | null;
+  }
+
+  /**
+   * Execute a query and return its rows as bytes, compressed when the JSON
+   * is over `compressThreshold`. Keeps large results compact for handing to a
+   * worker or writing to a cache; decode with `decodeCompressedRows()`.
+   *
+   * @example
+   * ```ts
+   * const result = await cn.queryCompressed("SELECT * FROM Events", {}, {
+   *   compress: "zstd",
+   * });
+   * worker.postMessage(result, [result.data.buffer]);
+   * ```
+   */
+  async queryCompressed(
+    sql: string,
+    params: Params | undefined,
+    opts: CompressOptions,
+  ): Promise<CompressedResult> {
+    this.#ensureOpen(opts);
+    const cmdJson = serializeCommand(sql, params, opts, {
+      compress: opts.compress,
+      compress_threshold: opts.compressThreshold,
+    });
+    const result = await this.#ffi.query(this.#connId, cmdJson);
+    if (result === null) {
+      this.#hasError = true;
+      throw new Error(this.#ffi.lastError(this.#connId) ?? "Query failed");
+    }
+    return toCompressedResult(
+      result,
+      (length) => this.#ffi.takeResult(this.#connId, length),
+    );
+  }
+
+  /**
+   * Execute a query for one page of its rows. The query needs an ORDER BY;
+   * `OFFSET/FETCH` is added after it, fetching one extra row to tell whether
+   * there's a next page. `includeTotal` adds a `COUNT(*) OVER()` column, so
+   * the total comes back in the same round trip.
+   *
+   * @example
+   * ```ts
+   * const { rows, total, hasMore } = await cn.queryPage(
+   *   "SELECT Id, Name FROM Customers WHERE Region = @region ORDER BY Name, Id",
+   *   { region: "EU" },
+   *   { offset: 40, limit: 20, includeTotal: true },
+   * );
+   * ```
+   */
+  async queryPage<T = Record<string, unknown>>(
+    sql: string,
+    params: Params | undefined,
+    page: PageRequest,
+    opts?: CommandOptions,
+  ): Promise<PageResult<T>> {
+    this.#ensureOpen(opts);
+    const cmdJson = serializeCommand(sql, params, opts, {
+      paginate: {
+        offset: page.offset ?? 0,
+        limit: page.limit,
+        include_total: page.includeTotal ?? false,
+      },
+    });
+    const result = await this.#ffi.query(this.#connId, cmdJson);
+    if (result === null) {
+      this.#hasError = true;
+      throw new Error(this.#ffi.lastError(this.#connId) ?? "Query failed");
+    }
+    return JSON.parse(result) as PageResult<T>;
+  }
+
+  /**
+   * Execute a query into a {@link RowBuffer}: rows are copied out as UTF-8
+   * JSON with an offset index and parsed when read. Pass `buffer` to reuse
+   * one across queries.
+   */
+  async queryBuffered(
+    sql: string,
+    params?: Params,
+    opts?: BufferedQueryOptions,
+  ): Promise<RowBuffer> {
+    this.#ensureOpen(opts);
+    const cmdJson = serializeCommand(sql, params, opts);
+    const result = await this.#ffi.queryBuffered(this.#connId, cmdJson);
+    if (result === null) {
+      this.#hasError = true;
+      throw new Error(this.#ffi.lastError(this.#connId) ?? "Query failed");
+    }
+    const { rows, length } = JSON.parse(result) as {
+      rows: number;
+      length: number;
+    };
+    const buffer = opts?.buffer ?? new RowBuffer(length, rows);
+    buffer.fill(
+      rows,
+      length,
+      (data, offsets) => this.#ffi.readRows(this.#connId, data, offsets),
+    );
+    return buffer;
+  }
+
   /** Execute a query and return the first row, or undefined. */
   async queryFirst<T = Record<string, unknown>>(
     sql: string,
@@ -110,6 +353,37 @@ export class MssqlConnection implements Disposable, AsyncDisposable {
     return (keys.length > 0 ? row[keys[0]] : undefined) as T | undefined;
   }
 
+  /**
+   * Query a system-versioned temporal table `FOR SYSTEM_TIME`, with the
+   * instants bound as datetime2 parameters. Each result separates the row's
+   * columns from its validity period.
+   *
+   * @example
+   * ```ts
+   * const rows = await cn.queryTemporal<Price>("dbo.Prices", {
+   *   asOf: new Date("2026-01-01T00:00:00Z"),
+   * }, { where: "Sku = @sku", params: { sku: "A-1" } });
+   * console.log(rows[0].row.Amount, rows[0].validFrom);
+   * ```
+   */
+  async queryTemporal<T = Record<string, unknown>>(
+    table: string,
+    systemTime: SystemTime,
+    opts?: TemporalQueryOptions,
+  ): Promise<TemporalRow<T>[]> {
+    const period = await this.queryFirst<PeriodColumns>(
+      PERIOD_COLUMNS_SQL,
+      { table },
+      { ...opts, columnCase: "as-is" },
+    );
+    if (!period) {
+      throw new Error(`${table} is not a system-versioned temporal table`);
+    }
+    const { sql, params } = buildTemporalQuery(table, period, systemTime, opts);
+    const rows = await this.query(sql, params, opts);
+    return toTemporalRows<T>(rows, period);
+  }
+
   /** Execute a non-query and return the number of rows affected. */
   async execute(
     sql: string,
@@ -126,6 +400,65 @@ export class MssqlConnection implements Disposable, AsyncDisposable {
     return (JSON.parse(result) as { rowsAffected: number }).rowsAffected;
   }
 
+  /**
+   * Run an INSERT and return the keys it generated. With `opts.key`, an
+   * `OUTPUT INSERTED.<key>` clause returns the key of every inserted row;
+   * otherwise `SCOPE_IDENTITY()` returns the last identity value.
+   *
+   * @example
+   * ```ts
+   * const { insertedIds } = await cn.insert(
+   *   "INSERT INTO Users (name) VALUES (@a), (@b)",
+   *   { a: "Alice", b: "Bob" },
+   *   { key: "Id" },
+   * );
+   * ```
+   */
+  async insert<K = number>(
+    sql: string,
+    params?: Params,
+    opts?: InsertOptions,
+  ): Promise<InsertResult<K>> {
+    this.#ensureOpen(opts);
+    const cmdJson = serializeCommand(sql, params, opts, {
+      return_identity: true,
+      ...(opts?.key ? { identity_key: opts.key } : {}),
+    });
+    const result = await this.#ffi.executeNonquery(this.#connId, cmdJson);
+    if (result === null) {
+      this.#hasError = true;
+      throw new Error(this.#ffi.lastError(this.#connId) ?? "Insert failed");
+    }
+    return JSON.parse(result) as InsertResult<K>;
+  }
+
+  /**
+   * Execute a non-query and return both the rows affected and any rows
+   * produced by an `OUTPUT` clause, in a single round trip.
+   *
+   * @example
+   * ```ts
+   * const { rows } = await cn.executeReturning<{ Id: number }>(
+   *   "INSERT INTO Users (name) OUTPUT INSERTED.Id VALUES (@name)",
+   *   { name: "Alice" },
+   * );
+   * ```
+   */
+  async executeReturning<T = Record<string, unknown>>(
+    sql: string,
+    params?: Params,
+    opts?: CommandOptions,
+  ): Promise<ExecuteResult<T>> {
+    this.#ensureOpen(opts);
+    const cmdJson = serializeCommand(sql, params, opts, { return_rows: true });
+    const result = await this.#ffi.executeNonquery(this.#connId, cmdJson);
+    if (result === null) {
+      this.#hasError = true;
+      throw new Error(this.#ffi.lastError(this.#connId) ?? "Execute failed");
+    }
+    return JSON.parse(result) as ExecuteResult<T>;
+  }
+
   /**
    * Execute a stored procedure and return a rich result with OUTPUT
    * parameters and multiple result sets.
@@ -145,6 +478,74 @@ export class MssqlConnection implements Disposable, AsyncDisposable {
     return new ExecResult(JSON.parse(result) as ExecResultRaw);
   }
 
+  /**
+   * Run a multi-batch script (index rebuilds, chunked DELETE loops), split
+   * on `GO` lines like sqlcmd (`GO n` repeats a batch; a `GO` inside a
+   * string or comment doesn't split). PRINT and RAISERROR messages and each
+   * batch's completion are passed to `opts.onProgress` as batches finish.
+   * Throws on the first failing batch unless `opts.continueOnError` is set,
+   * in which case failures are reported in `results`.
+   *
+   * @example
+   * ```ts
+   * await cn.runScript(script, undefined, {
+   *   onProgress: (e) => e.type === "message" && console.log(e.message),
+   * });
+   * ```
+   */
+  async runScript(
+    sql: string,
+    params?: Params,
+    opts?: ScriptOptions,
+  ): Promise<ScriptResult> {
+    this.#ensureOpen(opts);
+    const scriptJson = JSON.stringify({
+      sql,
+      params: serializeParams(params),
+      continue_on_error: opts?.continueOnError ?? false,
+    });
+    const onProgress = opts?.onProgress;
+    const pending = this.#ffi.execScript(this.#connId, scriptJson);
+    let result: string | null;
+    if (onProgress) {
+      const timer = setInterval(
+        () => this.#deliverProgress(onProgress),
+        opts?.pollIntervalMs ?? 250,
+      );
+      try {
+        result = await pending;
+      } finally {
+        clearInterval(timer);
+        this.#deliverProgress(onProgress);
+      }
+    } else {
+      result = await pending;
+    }
+    if (result === null) {
+      this.#hasError = true;
+      throw new Error(this.#ffi.lastError(this.#connId) ?? "Script failed");
+    }
+    const parsed = JSON.parse(result) as ScriptResult;
+    const failed = parsed.results.find((r) => r.error !== null);
+    if (failed && !opts?.continueOnError) {
+      throw new Error(
+        `Batch ${failed.batch + 1} (line ${failed.line}) failed: ${failed.error}`,
+      );
+    }
+    return parsed;
+  }
+
+  #deliverProgress(onProgress: (event: ScriptProgressEvent) => void): void {
+    const json = this.#ffi.progressPoll(this.#connId);
+    if (json === null) return;
+    const { events, dropped } = JSON.parse(json) as {
+      events: ScriptProgressEvent[];
+      dropped: number;
+    };
+    if (dropped > 0) onProgress({ type: "dropped", count: dropped });
+    for (const event of events) onProgress(event);
+  }
+
   /** Tagged template for parameterized queries. */
   sql<T = Record<string, unknown>>(
     strings: TemplateStringsArray,
@@ -183,7 +584,7 @@ export class MssqlConnection implements Disposable, AsyncDisposable {
         this.#ffi.lastError(this.#connId) ?? "Failed to open stream",
       );
     }
-    const stream = new QueryStream<T>(cursorId, this.#ffi);
+    const stream = new QueryStream<T>(cursorId, this.#ffi, opts?.rawBinary);
 
     // Track at connection level; auto-untrack on close
     this.#streams.add(stream as QueryStream<unknown>);
@@ -205,11 +606,259 @@ export class MssqlConnection implements Disposable, AsyncDisposable {
     return new BulkInsertBuilder(table, this.#connId, this.#ffi);
   }
 
+  /**
+   * Open a chunked reader over one varbinary(max) value, located by key
+   * columns that must match exactly one row. Reads run on this connection,
+   * so keep it open until the reader is closed.
+   *
+   * @param table Table name, optionally schema-qualified (`"dbo.Files"`).
+   * @param column The varbinary(max) column to read.
+   * @param key Column/value pairs identifying the row.
+   * @param chunkSize Bytes per sequential read (default 1 MiB).
+   */
+  async openBlob(
+    table: string,
+    column: string,
+    key: Record<string, ParamValue>,
+    chunkSize?: number,
+  ): Promise<BlobReader> {
+    this.#ensureOpen();
+    const req = JSON.stringify({
+      table,
+      column,
+      key: Object.fromEntries(
+        Object.entries(key).map(([k, v]) => [k, serializeValue(v)]),
+      ),
+    });
+    const blobId = await this.#ffi.blobOpen(this.#connId, req);
+    if (blobId === INVALID_HANDLE) {
+      throw new Error(
+        this.#ffi.lastError(this.#connId) ?? "Failed to open blob",
+      );
+    }
+    return new BlobReader(blobId, this.#ffi, chunkSize);
+  }
+
+  /**
+   * Run `sql` with a query notification request and return a subscription
+   * that reports when its results change. Requires Service Broker to be
+   * enabled on the database and a query that meets the notification rules
+   * (two-part table names, explicit column list, no `*`).
+   *
+   * @param sql The query to watch.
+   * @param params Query parameters.
+   * @param options.timeout Milliseconds until the subscription expires
+   *   (server default: 5 days). Expiry fires a `"timeout"` event.
+   */
+  async subscribe(
+    sql: string,
+    params?: Params,
+    options?: { timeout?: number },
+  ): Promise<QueryNotification> {
+    this.#ensureOpen();
+    const req = JSON.stringify({
+      sql,
+      params: serializeParams(params),
+      timeout_s: options?.timeout !== undefined
+        ? Math.max(1, Math.ceil(options.timeout / 1000))
+        : null,
+    });
+    const subId = await this.#ffi.notifySubscribe(this.#connId, req);
+    if (subId === INVALID_HANDLE) {
+      throw new Error(
+        this.#ffi.lastError(this.#connId) ?? "Failed to subscribe",
+      );
+    }
+    return new QueryNotification(subId, this.#ffi);
+  }
+
+  /**
+   * Wait up to `timeoutMs` for messages on a Service Broker queue
+   * (`WAITFOR (RECEIVE ...)`). Returns every ready message of one
+   * conversation group, or an empty array on timeout. Holds the connection
+   * while waiting, so use a dedicated connection for long polls.
+   *
+   * @param queue Queue name, optionally schema-qualified (`"dbo.Inbox"`).
+   */
+  async brokerReceive(
+    queue: string,
+    timeoutMs = 30_000,
+  ): Promise<BrokerMessage[]> {
+    this.#ensureOpen();
+    const json = await this.#ffi.brokerReceive(
+      this.#connId,
+      queue,
+      BigInt(Math.max(0, Math.trunc(timeoutMs))),
+    );
+    if (json === null) throw new Error("Broker receive failed");
+    return parseBrokerMessages(json);
+  }
+
+  /**
+   * Send a Service Broker message, beginning a dialog from `fromService` to
+   * `toService` unless `conversation` is given. Returns the conversation
+   * handle to continue or end the dialog with.
+   */
+  async brokerSend(opts: BrokerSendOptions): Promise<string> {
+    this.#ensureOpen();
+    const json = await this.#ffi.brokerSend(
+      this.#connId,
+      serializeBrokerSend(opts),
+    );
+    if (json === null) throw new Error("Broker send failed");
+    const result = JSON.parse(json);
+    if (result.__error) throw new Error(`Broker send: ${result.__error}`);
+    return result.conversation;
+  }
+
+  /** End a Service Broker conversation, optionally with an error or `CLEANUP`. */
+  async brokerEnd(
+    conversation: string,
+    opts?: BrokerEndOptions,
+  ): Promise<void> {
+    this.#ensureOpen();
+    const json = await this.#ffi.brokerEnd(
+      this.#connId,
+      serializeBrokerEnd(conversation, opts),
+    );
+    if (json === null) throw new Error("Broker end failed");
+    const result = JSON.parse(json);
+    if (result.__error) throw new Error(`Broker end: ${result.__error}`);
+  }
+
+  /**
+   * Rows of a change-tracked table that changed after `sinceVersion`
+   * (`CHANGETABLE(CHANGES ...)`), with the version to poll from next.
+   * For the first poll, pass `CHANGE_TRACKING_CURRENT_VERSION()` as read
+   * just before the initial full load.
+   *
+   * @param table Table name, optionally schema-qualified (`"dbo.Orders"`).
+   * @param sinceVersion The `version` returned by the previous poll.
+   */
+  async changesSince<K = Record<string, unknown>>(
+    table: string,
+    sinceVersion: number | bigint,
+  ): Promise<ChangeTrackingResult<K>> {
+    this.#ensureOpen();
+    const json = await this.#ffi.ctChanges(
+      this.#connId,
+      table,
+      BigInt(sinceVersion),
+    );
+    if (json === null) throw new Error("Change tracking poll failed");
+    return parseChangeTrackingResult<K>(json);
+  }
+
+  /**
+   * Read a CDC capture instance's changes after `opts.sinceLsn` up to the
+   * current maximum LSN, with commit times. Pass the result's `toLsn` as
+   * `sinceLsn` on the next read to tail the table.
+   *
+   * @param captureInstance Capture instance name (e.g. `"dbo_Orders"`).
+   */
+  async cdcChanges<T = Record<string, unknown>>(
+    captureInstance: string,
+    opts?: CdcReadOptions,
+  ): Promise<CdcChangeSet<T>> {
+    this.#ensureOpen();
+    const json = await this.#ffi.cdcChanges(
+      this.#connId,
+      serializeCdcRead(captureInstance, opts),
+    );
+    if (json === null) throw new Error("CDC read failed");
+    return parseCdcChangeSet<T>(json);
+  }
+
+  /**
+   * Describe a table or view: columns (type, length, precision, scale,
+   * nullability, identity, default), primary key, foreign keys and indexes.
+   *
+   * @param table Name, optionally schema-qualified (`"dbo.Orders"`).
+   */
+  async tableSchema(table: string): Promise<TableSchema> {
+    this.#ensureOpen();
+    const json = await this.#ffi.tableSchema(this.#connId, table);
+    if (json === null) throw new Error("Table schema failed");
+    return parseTableSchema(json);
+  }
+
+  /** List user objects in the current database, ordered by schema and name. */
+  async listObjects(filter?: ObjectFilter): Promise<DbObject[]> {
+    this.#ensureOpen();
+    const json = await this.#ffi.listObjects(
+      this.#connId,
+      serializeObjectFilter(filter),
+    );
+    if (json === null) throw new Error("List objects failed");
+    return parseObjectList(json);
+  }
+
+  /** List the databases visible to the login with state and owner. */
+  async listDatabases(): Promise<DatabaseInfo[]> {
+    this.#ensureOpen();
+    const json = await this.#ffi.listDatabases(this.#connId);
+    if (json === null) throw new Error("List databases failed");
+    return parseDatabaseList(json);
+  }
+
+  /**
+   * List the schemas in a database with their owners.
+   *
+   * @param database Database name (default: the current database).
+   */
+  async listSchemas(database?: string): Promise<SchemaInfo[]> {
+    this.#ensureOpen();
+    const json = await this.#ffi.listSchemas(this.#connId, database ?? "");
+    if (json === null) throw new Error("List schemas failed");
+    return parseSchemaList(json);
+  }
+
+  /**
+   * Enable Always Encrypted result decryption on this connection.
+   *
+   * Reads the encrypted-column metadata of the current database, unwraps each
+   * column encryption key with the provider registered under its column
+   * master key's `key_store_provider_name`, and installs the keys natively.
+   * Encrypted columns are then returned as plaintext values. Call again after
+   * switching databases or rotating keys.
+   *
+   * Result columns are matched by name. When tables share an encrypted column
+   * name with different keys or types, that column is left encrypted; pass
+   * `tables` to decrypt only the tables this connection reads.
+   *
+   * Support is read-only: only results are decrypted. Parameters are never
+   * encrypted, so filtering or inserting into encrypted columns with
+   * parameters fails with the server's operand type clash error.
+   *
+   * @param providers Key store providers by provider name.
+   * @param options.tables Tables to decrypt (`"dbo.Patients"` or `"Patients"`).
+   */
+  async enableColumnEncryption(
+    providers: Record<string, KeyStoreProvider>,
+    options?: { tables?: string[] },
+  ): Promise<void> {
+    this.#ensureOpen();
+    const rows = await this.query<EncryptedColumnRow>(
+      ENCRYPTED_COLUMNS_SQL,
+      undefined,
+      { columnCase: "as-is" },
+    );
+    const config = await buildKeyringConfig(rows, providers, options?.tables);
+    if (this.#ffi.aeConfigure(this.#connId, config) === 0) {
+      throw new Error(
+        this.#ffi.lastError(this.#connId) ??
+          "Failed to configure column encryption",
+      );
+    }
+  }
+
   /** Begin a transaction with the given isolation level. */
   async beginTransaction(
     isolation: IsolationLevel = "READ_COMMITTED",
+    opts?: TransactionOptions,
   ): Promise<Transaction> {
     this.#ensureOpen();
+    const readOnly = opts?.readOnly ?? false;
     const tx = new Transaction(
       isolation,
       async (txId: string) => {
@@ -220,9 +869,16 @@ export class MssqlConnection implements Disposable, AsyncDisposable {
         const err = await this.#ffi.rollback(this.#connId, txId);
         if (err !== null) throw new Error(`Rollback failed: ${err}`);
       },
+      readOnly,
     );
 
-    const txJson = JSON.stringify({ id: tx.id, isolation });
+    const txJson = JSON.stringify({
+      id: tx.id,
+      isolation,
+      read_only: readOnly,
+      allow_writes: opts?.allowWrites ?? false,
+      xact_abort: opts?.xactAbort ?? null,
+    });
     const err = await this.#ffi.beginTransaction(this.#connId, txJson);
     if (err !== null) {
       this.#hasError = true;
@@ -233,6 +889,49 @@ export class MssqlConnection implements Disposable, AsyncDisposable {
     return tx;
   }
 
+  /**
+   * Begin a read-only snapshot transaction for consistent reports: every
+   * query sees the same committed data, without blocking or being blocked by
+   * writers. Statements that write are rejected before they are sent unless
+   * `opts.allowWrites` is set.
+   *
+   * @example
+   * ```ts
+   * await using tx = await cn.beginReadOnly();
+   * const orders = await cn.query("SELECT * FROM Orders");
+   * const lines = await cn.query("SELECT * FROM OrderLines");
+   * await tx.commit();
+   * ```
+   */
+  beginReadOnly(
+    opts?: Omit<TransactionOptions, "readOnly">,
+  ): Promise<Transaction> {
+    return this.beginTransaction("SNAPSHOT", { ...opts, readOnly: true });
+  }
+
+  /**
+   * The connection's transaction state as the server sees it. After an
+   * error inside a transaction, `xactState` of -1 means it is doomed:
+   * `commit()` would fail, so roll back instead.
+   *
+   * @example
+   * ```ts
+   * const { xactState } = await cn.transactionState();
+   * if (xactState === -1) await tx.rollback();
+   * else await tx.commit();
+   * ```
+   */
+  async transactionState(): Promise<TransactionState> {
+    this.#ensureOpen();
+    const result = await this.#ffi.transactionState(this.#connId);
+    if (result === null) {
+      throw new Error(
+        this.#ffi.lastError(this.#connId) ?? "Transaction state failed",
+      );
+    }
+    return JSON.parse(result) as TransactionState;
+  }
+
   /**
    * Open a FILESTREAM blob as a `node:stream` Readable, Writable, or Duplex.
    * Compatible with `pipe()` and Node.js stream patterns.
@@ -248,18 +947,28 @@ export class MssqlConnection implements Disposable, AsyncDisposable {
     path: string,
     txContext: Uint8Array | string,
     mode: "write",
-  ): Writable;
+    writeMode?: FilestreamWriteMode,
+  ): FilestreamWritable;
   openFilestream(
     path: string,
     txContext: Uint8Array | string,
     mode: "readwrite",
-  ): Duplex;
+    writeMode?: FilestreamWriteMode,
+  ): FilestreamDuplex;
   openFilestream(
     path: string,
     txContext: Uint8Array | string,
     mode: FilestreamMode,
+    writeMode: FilestreamWriteMode = "overwrite",
   ): Readable | Writable | Duplex {
-    const handle = FilestreamHandle._open(this.#ffi, path, txContext, mode);
+    const handle = FilestreamHandle._open(
+      this.#ffi,
+      path,
+      txContext,
+      mode,
+      this.#label,
+      writeMode,
+    );
     switch (mode) {
       case "read":
         return new FilestreamReadable(handle);
@@ -285,18 +994,141 @@ export class MssqlConnection implements Disposable, AsyncDisposable {
     path: string,
     txContext: Uint8Array | string,
     mode: "write",
+    writeMode?: FilestreamWriteMode,
   ): WritableStream<Uint8Array>;
   openWebstream(
     path: string,
     txContext: Uint8Array | string,
     mode: "readwrite",
+    writeMode?: FilestreamWriteMode,
   ): FilestreamWebResult;
   openWebstream(
     path: string,
     txContext: Uint8Array | string,
     mode: FilestreamMode,
+    writeMode: FilestreamWriteMode = "overwrite",
   ): ReadableStream<Uint8Array> | WritableStream<Uint8Array> | FilestreamWebResult {
-    const handle = FilestreamHandle._open(this.#ffi, path, txContext, mode);
+    const handle = FilestreamHandle._open(
+      this.#ffi,
+      path,
+      txContext,
+      mode,
+      this.#label,
+      writeMode,
+    );
+    switch (mode) {
+      case "read":
+        return handle.toReadableStream();
+      case "write":
+        return handle.toWritableStream();
+      case "readwrite":
+        return {
+          readable: handle.toReadableStream(),
+          writable: handle.toWritableStream(),
+        };
+    }
+  }
+
+  /**
+   * Open the FILESTREAM value of one row as a `node:stream`, located by key
+   * columns that must match exactly one row. The driver looks up
+   * `PathName()` and the transaction context itself, so there is no need to
+   * query them first.
+   *
+   * On Windows this requires an active transaction on this connection and a
+   * value that is not NULL (set it to `0x` before writing). Other platforms
+   * emulate FILESTREAM with chunked statements on this connection, which
+   * also work on plain varbinary(max) columns. The stream holds the
+   * connection until it is closed; outside a transaction its writes commit
+   * when it finishes, and are rolled back if it is destroyed first.
+   *
+   * @param table Table name, optionally schema-qualified (`"dbo.Documents"`).
+   * @param column The FILESTREAM column.
+   * @param key Column/value pairs identifying the row.
+   */
+  openFilestreamFor(
+    table: string,
+    column: string,
+    key: Record<string, ParamValue>,
+    mode: "read",
+  ): Promise<Readable>;
+  openFilestreamFor(
+    table: string,
+    column: string,
+    key: Record<string, ParamValue>,
+    mode: "write",
+    writeMode?: FilestreamWriteMode,
+  ): Promise<FilestreamWritable>;
+  openFilestreamFor(
+    table: string,
+    column: string,
+    key: Record<string, ParamValue>,
+    mode: "readwrite",
+    writeMode?: FilestreamWriteMode,
+  ): Promise<FilestreamDuplex>;
+  async openFilestreamFor(
+    table: string,
+    column: string,
+    key: Record<string, ParamValue>,
+    mode: FilestreamMode,
+    writeMode: FilestreamWriteMode = "overwrite",
+  ): Promise<Readable | Writable | Duplex> {
+    const handle = await this.#openFilestreamFor(
+      table,
+      column,
+      key,
+      mode,
+      writeMode,
+    );
+    switch (mode) {
+      case "read":
+        return new FilestreamReadable(handle);
+      case "write":
+        return new FilestreamWritable(handle);
+      case "readwrite":
+        return new FilestreamDuplex(handle);
+    }
+  }
+
+  /**
+   * Like `openFilestreamFor()`, but returns Web Standard streams.
+   */
+  openWebstreamFor(
+    table: string,
+    column: string,
+    key: Record<string, ParamValue>,
+    mode: "read",
+  ): Promise<ReadableStream<Uint8Array>>;
+  openWebstreamFor(
+    table: string,
+    column: string,
+    key: Record<string, ParamValue>,
+    mode: "write",
+    writeMode?: FilestreamWriteMode,
+  ): Promise<WritableStream<Uint8Array>>;
+  openWebstreamFor(
+    table: string,
+    column: string,
+    key: Record<string, ParamValue>,
+    mode: "readwrite",
+    writeMode?: FilestreamWriteMode,
+  ): Promise<FilestreamWebResult>;
+  async openWebstreamFor(
+    table: string,
+    column: string,
+    key: Record<string, ParamValue>,
+    mode: FilestreamMode,
+    writeMode: FilestreamWriteMode = "overwrite",
+  ): Promise<
+    ReadableStream<Uint8Array> | WritableStream<Uint8Array> | FilestreamWebResult
+  > {
+    const handle = await this.#openFilestreamFor(
+      table,
+      column,
+      key,
+      mode,
+      writeMode,
+    );
     switch (mode) {
       case "read":
         return handle.toReadableStream();
@@ -310,6 +1142,26 @@ export class MssqlConnection implements Disposable, AsyncDisposable {
     }
   }
 
+  #openFilestreamFor(
+    table: string,
+    column: string,
+    key: Record<string, ParamValue>,
+    mode: FilestreamMode,
+    writeMode: FilestreamWriteMode,
+  ): Promise<FilestreamHandle> {
+    this.#ensureOpen();
+    const req = JSON.stringify({
+      table,
+      column,
+      key: Object.fromEntries(
+        Object.entries(key).map(([k, v]) => [k, serializeValue(v)]),
+      ),
+      mode,
+      write_mode: writeMode,
+    });
+    return FilestreamHandle._openFor(this.#ffi, this.#connId, req, mode);
+  }
+
   /**
    * Check if FILESTREAM is available end-to-end: local OLE DB driver,
    * server-level configuration, and database-level filegroup.
@@ -318,7 +1170,8 @@ export class MssqlConnection implements Disposable, AsyncDisposable {
    *                 Defaults to the current connection's database.
    */
   async filestreamAvailable(database?: string): Promise<boolean> {
-    // 1. Local: OLE DB Driver 19 must be installed (Windows only)
+    // 1. Local: OLE DB Driver 19 must be installed (always true off Windows,
+    //    where FILESTREAM is emulated)
     if (!this.#ffi.filestreamAvailable()) return false;
 
     // 2. Server: FILESTREAM access level must be >= 2 (T-SQL + file I/O)
@@ -347,6 +1200,18 @@ export class MssqlConnection implements Disposable, AsyncDisposable {
     }
   }
 
+  /**
+   * Tag this connection. The tag names it in `mssql.leakReport()`, and next
+   * to the config's `label` in diagnostics, the slow query log and error
+   * messages. Streams, blobs and FILESTREAM handles opened afterwards carry
+   * the same label; `null` clears it.
+   */
+  setLabel(label: string | null): void {
+    this.#ensureOpen();
+    this.#label = label;
+    this.#ffi.setLabel(this.#connId, label ?? "");
+  }
+
   /**
    * Close this connection permanently.
    * For pooled connections, the connection is evicted (NOT returned to pool).
@@ -455,6 +1320,7 @@ export function serializeCommand(
   sql: string,
   params?: Params,
   opts?: CommandOptions | StreamOptions,
+  extra?: Partial<SerializedCommand>,
 ): string {
   const cmd: SerializedCommand = {
     sql,
@@ -462,7 +1328,13 @@ export function serializeCommand(
     transaction_id: opts?.transaction?.id ?? null,
     command_timeout_ms: opts?.commandTimeout ?? null,
     command_type: opts?.commandType ?? "text",
+    ...extra,
   };
+  if (opts?.temporalMode) cmd.temporal_mode = opts.temporalMode;
+  if (opts?.columnCase) cmd.column_case = opts.columnCase;
+  if (opts?.nocount !== undefined) cmd.nocount = opts.nocount;
+  if (opts?.rowCount) cmd.rowcount = opts.rowCount;
+  if (opts && "rawBinary" in opts && opts.rawBinary) cmd.raw_binary = true;
   return JSON.stringify(cmd);
 }
 
@@ -482,6 +1354,13 @@ function serializeParams(params?: Params): SerializedParam[] {
       type,
     };
     if (output) param.output = true;
+    if (isTyped) {
+      const { precision, scale, srid, length } = raw as TypedParam;
+      if (precision !== undefined) param.precision = precision;
+      if (scale !== undefined) param.scale = scale;
+      if (srid !== undefined) param.srid = srid;
+      if (length !== undefined) param.length = length;
+    }
     return param;
   });
 }
@@ -490,6 +1369,7 @@ function serializeValue(val: ParamValue): unknown {
   if (val === null || val === undefined) return null;
   if (val instanceof Date) return val.toISOString();
   if (val instanceof Uint8Array) return btoa(String.fromCharCode(...val));
+  if (val instanceof Float32Array) return Array.from(val);
   return val;
 }
 
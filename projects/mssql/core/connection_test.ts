@@ -1,5 +1,7 @@
-import { assertEquals, assertRejects } from "jsr:@std/assert";
+import { assertEquals, assertRejects, assertThrows } from "jsr:@std/assert";
 import { MssqlConnection, serializeCommand } from "./connection.ts";
+import { decodeCompressedRows, nativeDecompressor } from "./compress.ts";
+import { RowBuffer } from "./row_buffer.ts";
 import type { RuntimeFFI } from "./runtime.ts";
 import type { Params } from "./types.ts";
 
@@ -11,31 +13,78 @@ function createMockFFI(overrides: Partial<RuntimeFFI> = {}): RuntimeFFI {
     poolAcquire: () => Promise.resolve(1n),
     poolRelease: () => {},
     poolClose: () => {},
+    poolConfig: () => null,
     connect: () => Promise.resolve(1n),
     disconnect: () => {},
+    setLabel: () => {},
     query: () => Promise.resolve("[]"),
     executeNonquery: () => Promise.resolve('{"rowsAffected":0}'),
     exec: () =>
       Promise.resolve(
         '{"rowsAffected":0,"resultSets":[],"outputParams":{}}',
       ),
+    takeResult: () => null,
+    decompress: () => null,
+    queryBuffered: () => Promise.resolve('{"rows":0,"length":0}'),
+    readRows: () => 0,
+    execScript: () =>
+      Promise.resolve('{"batches":0,"rowsAffected":0,"results":[]}'),
+    progressPoll: () => '{"events":[],"dropped":0}',
     queryStream: () => Promise.resolve(1n),
     streamNext: () => Promise.resolve(null),
+    streamReadBinary: () => null,
     streamClose: () => {},
     bulkInsert: () => Promise.resolve('{"rowsAffected":0}'),
     beginTransaction: () => Promise.resolve(null),
     commit: () => Promise.resolve(null),
     rollback: () => Promise.resolve(null),
+    transactionState: () =>
+      Promise.resolve(
+        '{"xactState":0,"tranCount":0,"transactionId":null,"readOnly":false}',
+      ),
     cancel: () => {},
+    aeConfigure: () => 1,
     lastError: () => null,
     filestreamAvailable: () => false,
     filestreamOpen: () => 0n,
-    filestreamRead: () => null,
-    filestreamWrite: () => 0n,
+    filestreamOpenFor: () => Promise.resolve(0n),
+    filestreamRead: () => Promise.resolve(null),
+    filestreamReadInto: () => Promise.resolve(-1),
+    filestreamWrite: () => Promise.resolve(0n),
+    filestreamSetLength: () => Promise.resolve(null),
+    filestreamFinish: () => Promise.resolve(null),
     filestreamClose: () => {},
+    blobOpen: () => Promise.resolve(1n),
+    blobLength: () => 0n,
+    blobRead: () => Promise.resolve('{"data":"","length":0}'),
+    blobClose: () => {},
+    notifySubscribe: () => Promise.resolve(1n),
+    notifyReceive: () => Promise.resolve('{"events":[]}'),
+    notifyClose: () => Promise.resolve(),
+    brokerReceive: () => Promise.resolve('{"messages":[]}'),
+    brokerSend: () => Promise.resolve('{"conversation":""}'),
+    brokerEnd: () => Promise.resolve("{}"),
+    ctChanges: () =>
+      Promise.resolve('{"version":0,"min_valid_version":0,"resync_required":false,"changes":[]}'),
+    cdcChanges: () =>
+      Promise.resolve('{"from_lsn":null,"to_lsn":"0x00","to_time":null,"min_lsn":"0x00","resync_required":false,"changes":[]}'),
+    tableSchema: () => Promise.resolve('{"__error":"Object not found"}'),
+    listObjects: () => Promise.resolve('{"objects":[]}'),
+    listDatabases: () => Promise.resolve('{"databases":[]}'),
+    listSchemas: () => Promise.resolve('{"schemas":[]}'),
+    versionInfo: () => null,
     diagnosticInfo: () => null,
+    slowQueries: () => "[]",
+    leakReport: () => "[]",
+    metricsPrometheus: () => "",
+    configureTracing: async () => null,
     setDebug: () => {},
+    setMemoryCap: () => {},
+    setConcurrencyLimit: () => {},
+    setDebugConfig: () => null,
+    setLogCallback: () => {},
     closeAll: () => {},
+    closeAllGraceful: () => Promise.resolve(null),
     ...overrides,
   };
 }
@@ -81,6 +130,79 @@ Deno.test("serializeCommand - typed param", () => {
   assertEquals(cmd.params[0].type, "uniqueidentifier");
 });
 
+Deno.test("serializeCommand - decimal param keeps string value and hints", () => {
+  const params: Params = {
+    amount: { value: "12345.678", type: "decimal", precision: 10, scale: 2 },
+    plain: { value: 1, type: "int" },
+  };
+  const json = serializeCommand("SELECT @amount, @plain", params);
+  const cmd = JSON.parse(json);
+  assertEquals(cmd.params[0].value, "12345.678");
+  assertEquals(cmd.params[0].precision, 10);
+  assertEquals(cmd.params[0].scale, 2);
+  assertEquals("precision" in cmd.params[1], false);
+  assertEquals("scale" in cmd.params[1], false);
+});
+
+Deno.test("serializeCommand - string param carries length", () => {
+  const params: Params = {
+    sku: { value: "AB-1234", type: "varchar", length: 50 },
+    note: "free text",
+  };
+  const cmd = JSON.parse(serializeCommand("SELECT @sku, @note", params));
+  assertEquals(cmd.params[0].length, 50);
+  assertEquals("length" in cmd.params[1], false);
+});
+
+Deno.test("serializeCommand - spatial param carries srid", () => {
+  const params: Params = {
+    loc: { value: "POINT(-122.34 47.65)", type: "geography", srid: 4269 },
+    shape: { value: "POINT(1 2)", type: "geometry" },
+  };
+  const cmd = JSON.parse(serializeCommand("SELECT @loc, @shape", params));
+  assertEquals(cmd.params[0].type, "geography");
+  assertEquals(cmd.params[0].srid, 4269);
+  assertEquals("srid" in cmd.params[1], false);
+});
+
+Deno.test("serializeCommand - vector param from Float32Array", () => {
+  const params: Params = {
+    q: { value: new Float32Array([0.5, -1, 2]), type: "vector" },
+  };
+  const cmd = JSON.parse(serializeCommand("SELECT @q", params));
+  assertEquals(cmd.params[0].type, "vector");
+  assertEquals(cmd.params[0].value, [0.5, -1, 2]);
+});
+
+Deno.test("serializeCommand - temporalMode override", () => {
+  const cmd = JSON.parse(
+    serializeCommand("SELECT SYSDATETIME()", undefined, { temporalMode: "epoch" }),
+  );
+  assertEquals(cmd.temporal_mode, "epoch");
+  assertEquals("temporal_mode" in JSON.parse(serializeCommand("SELECT 1")), false);
+});
+
+Deno.test("serializeCommand - columnCase override", () => {
+  const cmd = JSON.parse(
+    serializeCommand("SELECT 1 AS Id", undefined, { columnCase: "camelCase" }),
+  );
+  assertEquals(cmd.column_case, "camelCase");
+  assertEquals("column_case" in JSON.parse(serializeCommand("SELECT 1")), false);
+});
+
+Deno.test("serializeCommand - nocount and rowCount", () => {
+  const cmd = JSON.parse(
+    serializeCommand("UPDATE t SET x = 1", undefined, {
+      nocount: false,
+      rowCount: "last",
+    }),
+  );
+  assertEquals(cmd.nocount, false);
+  assertEquals(cmd.rowcount, "last");
+  const plain = JSON.parse(serializeCommand("SELECT 1"));
+  assertEquals("nocount" in plain || "rowcount" in plain, false);
+});
+
 Deno.test("serializeCommand - Date param converted to ISO string", () => {
   const d = new Date("2024-06-15T10:30:00Z");
   const params: Params = { created: d };
@@ -124,6 +246,11 @@ Deno.test("serializeCommand - with transaction", () => {
   assertEquals(cmd.transaction_id, "tx-123");
 });
 
+Deno.test("serializeCommand - return_rows omitted by default", () => {
+  const cmd = JSON.parse(serializeCommand("DELETE FROM T"));
+  assertEquals(cmd.return_rows, undefined);
+});
+
 // ── MssqlConnection query tests ───────────────────────────────
 
 Deno.test("MssqlConnection.query - returns parsed rows", async () => {
@@ -152,6 +279,135 @@ Deno.test("MssqlConnection.query - throws on null result", async () => {
   await cn.disconnect();
 });
 
+Deno.test("MssqlConnection.queryJson - asks for the FOR JSON document", async () => {
+  let sent = "";
+  const ffi = createMockFFI({
+    query: (_id, cmd) => {
+      sent = cmd;
+      return Promise.resolve('[{"id":1,"lines":[{"sku":"A"}]}]');
+    },
+  });
+  const cn = new MssqlConnection(1n, ffi);
+  const doc = await cn.queryJson<{ id: number }[]>(
+    "SELECT * FROM Orders FOR JSON PATH",
+  );
+  assertEquals(JSON.parse(sent).for_json, true);
+  assertEquals(doc, [{ id: 1, lines: [{ sku: "A" }] }]);
+  await cn.disconnect();
+});
+
+Deno.test("MssqlConnection.queryCompressed - collects compressed bytes", async () => {
+  let sent = "";
+  let taken = 0;
+  const ffi = createMockFFI({
+    query: (_id, cmd) => {
+      sent = cmd;
+      return Promise.resolve(
+        '{"__compressed":"zstd","length":40,"compressedLength":3}',
+      );
+    },
+    takeResult: (_id, length) => {
+      taken = length;
+      return new Uint8Array([1, 2, 3]);
+    },
+  });
+  const cn = new MssqlConnection(1n, ffi);
+  const result = await cn.queryCompressed("SELECT * FROM Events", {}, {
+    compress: "zstd",
+    compressThreshold: 16,
+  });
+  const cmd = JSON.parse(sent);
+  assertEquals([cmd.compress, cmd.compress_threshold], ["zstd", 16]);
+  assertEquals(taken, 3);
+  assertEquals(result.codec, "zstd");
+  assertEquals(result.length, 40);
+  const rows = decodeCompressedRows(result, (_data, codec, length) => {
+    assertEquals([codec, length], ["zstd", 40]);
+    return new TextEncoder().encode('[{"id":1}]');
+  });
+  assertEquals(rows, [{ id: 1 }]);
+  assertThrows(() => decodeCompressedRows(result), Error, "zstd decompressor");
+  await cn.disconnect();
+});
+
+Deno.test("nativeDecompressor - decodes through the driver", () => {
+  const plain = new TextEncoder().encode('[{"id":1}]');
+  const ffi = createMockFFI({
+    decompress: (codec, data, length) =>
+      codec === "lz4" && data.length === 3 && length === plain.length
+        ? plain
+        : null,
+  });
+  const decompress = nativeDecompressor(ffi);
+  const result = {
+    codec: "lz4" as const,
+    length: plain.length,
+    data: new Uint8Array(3),
+  };
+  assertEquals(decodeCompressedRows(result, decompress), [{ id: 1 }]);
+  assertThrows(
+    () => decodeCompressedRows({ ...result, codec: "zstd" }, decompress),
+    Error,
+    "Could not decompress zstd data",
+  );
+});
+
+Deno.test("MssqlConnection.queryCompressed - small results stay plain", async () => {
+  const ffi = createMockFFI({ query: () => Promise.resolve('[{"id":1}]') });
+  const cn = new MssqlConnection(1n, ffi);
+  const result = await cn.queryCompressed("SELECT 1 AS id", {}, {
+    compress: "lz4",
+  });
+  assertEquals([result.codec, result.length], [null, 10]);
+  assertEquals(decodeCompressedRows(result), [{ id: 1 }]);
+  await cn.disconnect();
+});
+
+Deno.test("MssqlConnection.queryPage - sends the page and returns it", async () => {
+  let sent = "";
+  const ffi = createMockFFI({
+    query: (_id, cmd) => {
+      sent = cmd;
+      return Promise.resolve('{"rows":[{"id":3}],"total":5,"hasMore":true}');
+    },
+  });
+  const cn = new MssqlConnection(1n, ffi);
+  const page = await cn.queryPage("SELECT id FROM T ORDER BY id", {}, {
+    offset: 2,
+    limit: 1,
+    includeTotal: true,
+  });
+  assertEquals(JSON.parse(sent).paginate, {
+    offset: 2,
+    limit: 1,
+    include_total: true,
+  });
+  assertEquals(page, { rows: [{ id: 3 }], total: 5, hasMore: true });
+  await cn.disconnect();
+});
+
+Deno.test("MssqlConnection.queryBuffered - reads rows by offset", async () => {
+  const json = '{"id":1}{"id":2,"name":"b"}';
+  const ffi = createMockFFI({
+    queryBuffered: () => Promise.resolve('{"rows":2,"length":27}'),
+    readRows: (_id, data, offsets) => {
+      data.set(new TextEncoder().encode(json));
+      offsets.set([0, 8, 27]);
+      return 2;
+    },
+  });
+  const cn = new MssqlConnection(1n, ffi);
+  const buffer = new RowBuffer(4, 1);
+  const same = await cn.queryBuffered("SELECT * FROM T", {}, { buffer });
+  assertEquals(same, buffer);
+  assertEquals([buffer.rowCount, buffer.byteLength], [2, 27]);
+  assertEquals(buffer.capacity >= 27, true);
+  assertEquals(buffer.row(1), { id: 2, name: "b" });
+  assertEquals(buffer.toArray(), [{ id: 1 }, { id: 2, name: "b" }]);
+  assertThrows(() => buffer.row(2), RangeError);
+  await cn.disconnect();
+});
+
 Deno.test("MssqlConnection.queryFirst - returns first row", async () => {
   const ffi = createMockFFI({
     query: () => Promise.resolve('[{"id":1}]'),
@@ -228,6 +484,447 @@ Deno.test("MssqlConnection.execute - returns rowsAffected", async () => {
   await cn.disconnect();
 });
 
+Deno.test("MssqlConnection.executeReturning - returns rows and count", async () => {
+  let capturedJson = "";
+  const ffi = createMockFFI({
+    executeNonquery: (_connId: bigint, cmdJson: string) => {
+      capturedJson = cmdJson;
+      return Promise.resolve('{"rowsAffected":2,"rows":[{"Id":7},{"Id":8}]}');
+    },
+  });
+  const cn = new MssqlConnection(1n, ffi);
+  const result = await cn.executeReturning<{ Id: number }>(
+    "INSERT INTO T (name) OUTPUT INSERTED.Id VALUES ('a'), ('b')",
+  );
+  assertEquals(result.rowsAffected, 2);
+  assertEquals(result.rows.map((r) => r.Id), [7, 8]);
+  assertEquals(JSON.parse(capturedJson).return_rows, true);
+  await cn.disconnect();
+});
+
+Deno.test("MssqlConnection.openBlob - reads chunks until the end", async () => {
+  let capturedReq = "";
+  const reads: [bigint, bigint][] = [];
+  const ffi = createMockFFI({
+    blobOpen: (_connId: bigint, reqJson: string) => {
+      capturedReq = reqJson;
+      return Promise.resolve(5n);
+    },
+    blobLength: () => 5n,
+    blobRead: (_blobId: bigint, offset: bigint, length: bigint) => {
+      reads.push([offset, length]);
+      const data = offset < 5n ? btoa("abcde".slice(Number(offset), Number(offset + length))) : "";
+      return Promise.resolve(JSON.stringify({ data, length: atob(data).length }));
+    },
+  });
+  const cn = new MssqlConnection(1n, ffi);
+  const blob = await cn.openBlob("dbo.Files", "Data", { Id: 42 }, 3);
+  assertEquals(blob.size, 5);
+  const chunks: number[] = [];
+  for await (const chunk of blob) chunks.push(chunk.length);
+  assertEquals(chunks, [3, 2]);
+  assertEquals(reads, [[0n, 3n], [3n, 3n], [5n, 3n]]);
+  assertEquals(JSON.parse(capturedReq), { table: "dbo.Files", column: "Data", key: { Id: 42 } });
+  await cn.disconnect();
+});
+
+Deno.test("MssqlConnection.openBlob - throws lastError on failure", async () => {
+  const ffi = createMockFFI({
+    blobOpen: () => Promise.resolve(0n),
+    lastError: () => "Blob row not found in dbo.Files",
+  });
+  const cn = new MssqlConnection(1n, ffi);
+  await assertRejects(
+    () => cn.openBlob("dbo.Files", "Data", { Id: 1 }),
+    Error,
+    "Blob row not found",
+  );
+  await cn.disconnect();
+});
+
+Deno.test("MssqlConnection.openFilestreamFor - sends the row target", async () => {
+  let capturedReq = "";
+  const ffi = createMockFFI({
+    filestreamAvailable: () => true,
+    filestreamOpenFor: (_connId: bigint, reqJson: string) => {
+      capturedReq = reqJson;
+      return Promise.resolve(0n);
+    },
+    lastError: () => "FILESTREAM requires an active transaction",
+  });
+  const cn = new MssqlConnection(1n, ffi);
+  await assertRejects(
+    () =>
+      cn.openFilestreamFor("dbo.Documents", "Content", { Id: 7 }, "write", "append"),
+    Error,
+    "active transaction",
+  );
+  assertEquals(JSON.parse(capturedReq), {
+    table: "dbo.Documents",
+    column: "Content",
+    key: { Id: 7 },
+    mode: "write",
+    write_mode: "append",
+  });
+  await cn.disconnect();
+});
+
+Deno.test("MssqlConnection.subscribe - receives change events", async () => {
+  let capturedReq = "";
+  let closed = 0n;
+  const ffi = createMockFFI({
+    notifySubscribe: (_connId: bigint, reqJson: string) => {
+      capturedReq = reqJson;
+      return Promise.resolve(3n);
+    },
+    notifyReceive: (_subId: bigint, timeoutMs: bigint) =>
+      Promise.resolve(JSON.stringify({
+        events: timeoutMs > 0n ? [{ type: "change", source: "data", info: "insert" }] : [],
+      })),
+    notifyClose: (subId: bigint) => {
+      closed = subId;
+      return Promise.resolve();
+    },
+  });
+  const cn = new MssqlConnection(1n, ffi);
+  const sub = await cn.subscribe("SELECT Id FROM dbo.Orders WHERE Status = @s", { s: "open" }, {
+    timeout: 1500,
+  });
+  assertEquals(await sub.receive(0), []);
+  assertEquals(await sub.receive(), [{ type: "change", source: "data", info: "insert" }]);
+  await sub.close();
+  assertEquals(closed, 3n);
+  await assertRejects(() => sub.receive(), Error, "closed");
+  const req = JSON.parse(capturedReq);
+  assertEquals(req.params, [{ name: "s", value: "open", type: null }]);
+  assertEquals(req.timeout_s, 2);
+  await cn.disconnect();
+});
+
+Deno.test("MssqlConnection.subscribe - throws lastError on failure", async () => {
+  const ffi = createMockFFI({
+    notifySubscribe: () => Promise.resolve(0n),
+    lastError: () => "Cannot create notification queue (is Service Broker enabled?)",
+  });
+  const cn = new MssqlConnection(1n, ffi);
+  await assertRejects(
+    () => cn.subscribe("SELECT Id FROM dbo.Orders"),
+    Error,
+    "Service Broker",
+  );
+  await cn.disconnect();
+});
+
+Deno.test("MssqlConnection.brokerReceive - decodes messages", async () => {
+  let captured: [string, bigint] = ["", 0n];
+  const ffi = createMockFFI({
+    brokerReceive: (_connId: bigint, queue: string, timeoutMs: bigint) => {
+      captured = [queue, timeoutMs];
+      return Promise.resolve(JSON.stringify({
+        messages: [{
+          conversation_handle: "8f2e4c1a-0000-0000-0000-000000000001",
+          message_type: "//App/Request",
+          service: "//App/Server",
+          contract: "//App/Contract",
+          sequence: 0,
+          body: btoa("hi"),
+        }, {
+          conversation_handle: "8f2e4c1a-0000-0000-0000-000000000001",
+          message_type: "http://schemas.microsoft.com/SQL/ServiceBroker/EndDialog",
+          service: "//App/Server",
+          contract: "//App/Contract",
+          sequence: 1,
+          body: null,
+        }],
+      }));
+    },
+  });
+  const cn = new MssqlConnection(1n, ffi);
+  const messages = await cn.brokerReceive("dbo.Inbox", 5000);
+  assertEquals(captured, ["dbo.Inbox", 5000n]);
+  assertEquals(messages.length, 2);
+  assertEquals(messages[0].messageType, "//App/Request");
+  assertEquals(messages[0].body, new TextEncoder().encode("hi"));
+  assertEquals(messages[1].body, null);
+  await cn.disconnect();
+});
+
+Deno.test("MssqlConnection.brokerSend/brokerEnd - serialize requests", async () => {
+  const requests: unknown[] = [];
+  const ffi = createMockFFI({
+    brokerSend: (_connId: bigint, reqJson: string) => {
+      requests.push(JSON.parse(reqJson));
+      return Promise.resolve('{"conversation":"8f2e4c1a-0000-0000-0000-000000000001"}');
+    },
+    brokerEnd: (_connId: bigint, reqJson: string) => {
+      requests.push(JSON.parse(reqJson));
+      return Promise.resolve('{"__error":"Invalid conversation handle"}');
+    },
+  });
+  const cn = new MssqlConnection(1n, ffi);
+  const handle = await cn.brokerSend({
+    fromService: "//App/Client",
+    toService: "//App/Server",
+    messageType: "//App/Request",
+    lifetime: 60_000,
+    body: new Uint8Array([1, 2]),
+  });
+  assertEquals(handle, "8f2e4c1a-0000-0000-0000-000000000001");
+  await assertRejects(
+    () => cn.brokerEnd(handle, { error: { code: 50001, description: "bad" } }),
+    Error,
+    "Invalid conversation handle",
+  );
+  assertEquals(requests, [{
+    conversation: null,
+    from_service: "//App/Client",
+    to_service: "//App/Server",
+    contract: null,
+    lifetime_s: 60,
+    encryption: false,
+    message_type: "//App/Request",
+    text: null,
+    binary: btoa("\x01\x02"),
+  }, {
+    conversation: handle,
+    error_code: 50001,
+    error_description: "bad",
+    cleanup: false,
+  }]);
+  await cn.disconnect();
+});
+
+Deno.test("MssqlConnection.changesSince - maps the change set", async () => {
+  let captured: [string, bigint] = ["", 0n];
+  const ffi = createMockFFI({
+    ctChanges: (_connId: bigint, table: string, sinceVersion: bigint) => {
+      captured = [table, sinceVersion];
+      return Promise.resolve(JSON.stringify({
+        version: 42,
+        min_valid_version: 10,
+        resync_required: false,
+        changes: [{ operation: "delete", version: 41, key: { Id: 7 } }],
+      }));
+    },
+  });
+  const cn = new MssqlConnection(1n, ffi);
+  const result = await cn.changesSince<{ Id: number }>("dbo.Orders", 30);
+  assertEquals(captured, ["dbo.Orders", 30n]);
+  assertEquals(result, {
+    version: 42,
+    minValidVersion: 10,
+    resyncRequired: false,
+    changes: [{ operation: "delete", version: 41, key: { Id: 7 } }],
+  });
+  await cn.disconnect();
+});
+
+Deno.test("MssqlConnection.cdcChanges - serializes the read and maps changes", async () => {
+  let capturedReq = "";
+  const ffi = createMockFFI({
+    cdcChanges: (_connId: bigint, reqJson: string) => {
+      capturedReq = reqJson;
+      return Promise.resolve(JSON.stringify({
+        from_lsn: "0x0000002A000001F80004",
+        to_lsn: "0x0000002A000001F80010",
+        to_time: "2026-01-02T03:04:05.000Z",
+        min_lsn: "0x00000020000000100001",
+        resync_required: false,
+        changes: [{
+          lsn: "0x0000002A000001F80004",
+          seqval: "0x0000002A000001F80002",
+          operation: "insert",
+          commit_time: "2026-01-02T03:04:00.000Z",
+          update_mask: "Aw==",
+          row: { Id: 7, Status: "open" },
+        }],
+      }));
+    },
+  });
+  const cn = new MssqlConnection(1n, ffi);
+  const result = await cn.cdcChanges<{ Id: number; Status: string }>("dbo_Orders", {
+    sinceLsn: "0x0000002A000001F80003",
+    updateOld: true,
+  });
+  assertEquals(JSON.parse(capturedReq), {
+    capture_instance: "dbo_Orders",
+    since_lsn: "0x0000002A000001F80003",
+    since_time: null,
+    net: false,
+    update_old: true,
+  });
+  assertEquals(result.toLsn, "0x0000002A000001F80010");
+  assertEquals(result.changes[0].operation, "insert");
+  assertEquals(result.changes[0].updateMask, "Aw==");
+  assertEquals(result.changes[0].row, { Id: 7, Status: "open" });
+  await cn.disconnect();
+});
+
+Deno.test("MssqlConnection.tableSchema - maps keys and indexes", async () => {
+  const ffi = createMockFFI({
+    tableSchema: (_connId: bigint, table: string) => {
+      assertEquals(table, "dbo.Orders");
+      return Promise.resolve(JSON.stringify({
+        schema: "dbo",
+        name: "Orders",
+        columns: [{
+          name: "Id",
+          type: "int",
+          length: null,
+          precision: null,
+          scale: null,
+          nullable: false,
+          identity: true,
+          computed: false,
+          default: null,
+          collation: null,
+        }],
+        primary_key: {
+          name: "PK_Orders",
+          kind: "CLUSTERED",
+          unique: true,
+          primary_key: true,
+          columns: [{ name: "Id", descending: false }],
+          included: [],
+        },
+        foreign_keys: [{
+          name: "FK_Orders_Customers",
+          columns: ["CustomerId"],
+          referenced_schema: "dbo",
+          referenced_table: "Customers",
+          referenced_columns: ["Id"],
+          on_delete: "CASCADE",
+          on_update: "NO_ACTION",
+        }],
+        indexes: [],
+      }));
+    },
+  });
+  const cn = new MssqlConnection(1n, ffi);
+  const table = await cn.tableSchema("dbo.Orders");
+  assertEquals(table.columns[0].identity, true);
+  assertEquals(table.primaryKey?.primaryKey, true);
+  assertEquals(table.foreignKeys[0].referencedTable, "Customers");
+  assertEquals(table.foreignKeys[0].onDelete, "CASCADE");
+  await cn.disconnect();
+});
+
+Deno.test("MssqlConnection.listObjects - serializes the filter", async () => {
+  let capturedFilter = "";
+  const ffi = createMockFFI({
+    listObjects: (_connId: bigint, filterJson: string) => {
+      capturedFilter = filterJson;
+      return Promise.resolve('{"objects":[]}');
+    },
+  });
+  const cn = new MssqlConnection(1n, ffi);
+  await cn.listObjects({ nameLike: "Order%", types: ["table", "view"] });
+  assertEquals(JSON.parse(capturedFilter), {
+    schema: null,
+    name_like: "Order%",
+    types: ["table", "view"],
+  });
+  await cn.disconnect();
+});
+
+Deno.test("MssqlConnection.listDatabases - maps database metadata", async () => {
+  const ffi = createMockFFI({
+    listDatabases: () =>
+      Promise.resolve(JSON.stringify({
+        databases: [{
+          name: "Sales",
+          state: "ONLINE",
+          owner: "sa",
+          recovery_model: "FULL",
+          compatibility_level: 160,
+          collation: "SQL_Latin1_General_CP1_CI_AS",
+          read_only: false,
+          created: "2026-01-02T03:04:05.123",
+        }],
+      })),
+  });
+  const cn = new MssqlConnection(1n, ffi);
+  const [db] = await cn.listDatabases();
+  assertEquals(db.name, "Sales");
+  assertEquals(db.recoveryModel, "FULL");
+  assertEquals(db.compatibilityLevel, 160);
+  assertEquals(db.readOnly, false);
+  await cn.disconnect();
+});
+
+Deno.test("MssqlConnection.listSchemas - defaults to the current database", async () => {
+  const databases: string[] = [];
+  const ffi = createMockFFI({
+    listSchemas: (_connId: bigint, database: string) => {
+      databases.push(database);
+      return Promise.resolve(
+        '{"schemas":[{"name":"sales","owner":"dbo","system":false}]}',
+      );
+    },
+  });
+  const cn = new MssqlConnection(1n, ffi);
+  const schemas = await cn.listSchemas();
+  await cn.listSchemas("Archive");
+  assertEquals(databases, ["", "Archive"]);
+  assertEquals(schemas, [{ name: "sales", owner: "dbo", system: false }]);
+  await cn.disconnect();
+});
+
+Deno.test("MssqlConnection.queryWithPlan - requests the plan and returns it with rows", async () => {
+  let capturedJson = "";
+  const ffi = createMockFFI({
+    query: (_connId: bigint, cmdJson: string) => {
+      capturedJson = cmdJson;
+      return Promise.resolve(JSON.stringify({
+        rows: [{ Id: 1 }],
+        plans: ["<ShowPlanXML/>"],
+      }));
+    },
+  });
+  const cn = new MssqlConnection(1n, ffi);
+  const result = await cn.queryWithPlan("SELECT Id FROM T WHERE Id = @id", { id: 1 });
+  assertEquals(JSON.parse(capturedJson).capture_plan, "actual");
+  assertEquals(result.rows, [{ Id: 1 }]);
+  assertEquals(result.plans, ["<ShowPlanXML/>"]);
+
+  await cn.queryWithPlan("SELECT 1", undefined, { capturePlan: "estimated" });
+  assertEquals(JSON.parse(capturedJson).capture_plan, "estimated");
+  await cn.disconnect();
+});
+
+Deno.test("MssqlConnection.queryWithStatistics - maps IO and time statistics", async () => {
+  let capturedJson = "";
+  const ffi = createMockFFI({
+    query: (_connId: bigint, cmdJson: string) => {
+      capturedJson = cmdJson;
+      return Promise.resolve(JSON.stringify({
+        rows: [{ Id: 1 }],
+        statistics: {
+          tables: [{
+            table: "Orders",
+            scan_count: 1,
+            logical_reads: 12,
+            physical_reads: 2,
+            read_ahead_reads: 8,
+            lob_logical_reads: 0,
+            lob_physical_reads: 0,
+            lob_read_ahead_reads: 0,
+          }],
+          compile: { cpu_ms: 3, elapsed_ms: 4 },
+          execution: { cpu_ms: 15, elapsed_ms: 20 },
+        },
+      }));
+    },
+  });
+  const cn = new MssqlConnection(1n, ffi);
+  const result = await cn.queryWithStatistics("SELECT Id FROM Orders");
+  assertEquals(JSON.parse(capturedJson).capture_statistics, true);
+  assertEquals(result.rows, [{ Id: 1 }]);
+  assertEquals(result.statistics.tables[0].logicalReads, 12);
+  assertEquals(result.statistics.execution, { cpuMs: 15, elapsedMs: 20 });
+  await cn.disconnect();
+});
+
 Deno.test("MssqlConnection.sql - tagged template", async () => {
   let capturedJson = "";
   const ffi = createMockFFI({
@@ -465,6 +1162,167 @@ Deno.test("MssqlConnection.exec - throws on null result", async () => {
   await cn.disconnect();
 });
 
+Deno.test("MssqlConnection.beginReadOnly - begins a snapshot transaction", async () => {
+  const sent: Record<string, unknown>[] = [];
+  const ffi = createMockFFI({
+    beginTransaction: (_id, json) => {
+      sent.push(JSON.parse(json));
+      return Promise.resolve(null);
+    },
+  });
+  const cn = new MssqlConnection(1n, ffi);
+  const tx = await cn.beginReadOnly();
+  assertEquals(tx.isolation, "SNAPSHOT");
+  assertEquals(tx.readOnly, true);
+  assertEquals(sent[0].read_only, true);
+  assertEquals(sent[0].allow_writes, false);
+
+  await cn.beginReadOnly({ allowWrites: true });
+  assertEquals(sent[1].allow_writes, true);
+  const plain = await cn.beginTransaction();
+  assertEquals(plain.readOnly, false);
+  assertEquals(sent[2].read_only, false);
+  await cn.disconnect();
+});
+
+Deno.test("MssqlConnection.beginTransaction - sends xactAbort", async () => {
+  const sent: Record<string, unknown>[] = [];
+  const ffi = createMockFFI({
+    beginTransaction: (_id, json) => {
+      sent.push(JSON.parse(json));
+      return Promise.resolve(null);
+    },
+  });
+  const cn = new MssqlConnection(1n, ffi);
+  await cn.beginTransaction("SERIALIZABLE", { xactAbort: true });
+  await cn.beginTransaction();
+  assertEquals(sent[0].xact_abort, true);
+  // Left to the connection's config default
+  assertEquals(sent[1].xact_abort, null);
+  await cn.disconnect();
+});
+
+Deno.test("MssqlConnection.transactionState - reports a doomed transaction", async () => {
+  const ffi = createMockFFI({
+    transactionState: () =>
+      Promise.resolve(JSON.stringify({
+        xactState: -1,
+        tranCount: 1,
+        transactionId: "tx-1",
+        readOnly: false,
+      })),
+  });
+  const cn = new MssqlConnection(1n, ffi);
+  const state = await cn.transactionState();
+  assertEquals(state.xactState, -1);
+  assertEquals(state.transactionId, "tx-1");
+  await cn.disconnect();
+});
+
+Deno.test("MssqlConnection.runScript - delivers progress events", async () => {
+  const polls = [
+    JSON.stringify({
+      events: [{
+        type: "message",
+        batch: 0,
+        number: 0,
+        class: 0,
+        message: "rebuilt IX_Orders",
+      }],
+      dropped: 0,
+    }),
+    JSON.stringify({
+      events: [{ type: "batch", batch: 0, rowsAffected: 0, durationMs: 5 }],
+      dropped: 3,
+    }),
+  ];
+  // The script finishes once both polls have been taken
+  const execScript = () =>
+    new Promise<string>((resolve) => {
+      const wait = setInterval(() => {
+        if (polls.length) return;
+        clearInterval(wait);
+        resolve('{"batches":1,"rowsAffected":0,"results":[]}');
+      }, 1);
+    });
+  const ffi = createMockFFI({
+    execScript,
+    progressPoll: () => polls.shift() ?? '{"events":[],"dropped":0}',
+  });
+  const cn = new MssqlConnection(1n, ffi);
+  const seen: string[] = [];
+  const result = await cn.runScript("ALTER INDEX ALL ON Orders REBUILD", {}, {
+    pollIntervalMs: 1,
+    onProgress: (e) => seen.push(e.type),
+  });
+  assertEquals(result, { batches: 1, rowsAffected: 0, results: [] });
+  assertEquals(seen, ["message", "dropped", "batch"]);
+  await cn.disconnect();
+});
+
+Deno.test("MssqlConnection.insert - returns insertedIds", async () => {
+  let sent: Record<string, unknown> = {};
+  const ffi = createMockFFI({
+    executeNonquery: (_id, json) => {
+      sent = JSON.parse(json);
+      return Promise.resolve('{"rowsAffected":2,"insertedIds":[7,8]}');
+    },
+  });
+  const cn = new MssqlConnection(1n, ffi);
+  const result = await cn.insert(
+    "INSERT INTO Users (name) VALUES (@a), (@b)",
+    { a: "Alice", b: "Bob" },
+    { key: "Id" },
+  );
+  assertEquals(result, { rowsAffected: 2, insertedIds: [7, 8] });
+  assertEquals(sent.return_identity, true);
+  assertEquals(sent.identity_key, "Id");
+
+  await cn.insert("INSERT INTO Users DEFAULT VALUES");
+  assertEquals("identity_key" in sent, false);
+  await cn.disconnect();
+});
+
+Deno.test("MssqlConnection.runScript - throws on null result", async () => {
+  const ffi = createMockFFI({
+    execScript: () => Promise.resolve(null),
+    lastError: () => "Invalid GO count '0' on line 3",
+  });
+  const cn = new MssqlConnection(1n, ffi);
+  await assertRejects(() => cn.runScript("SELECT 1"), Error, "GO count");
+  await cn.disconnect();
+});
+
+Deno.test("MssqlConnection.runScript - reports failing batches", async () => {
+  let sent: Record<string, unknown> = {};
+  const ffi = createMockFFI({
+    execScript: (_id, json) => {
+      sent = JSON.parse(json);
+      return Promise.resolve(JSON.stringify({
+        batches: 3,
+        rowsAffected: 4,
+        results: [
+          { batch: 0, line: 1, runs: 1, rowsAffected: 4, error: null },
+          { batch: 1, line: 5, runs: 0, rowsAffected: 0, error: "deadlock" },
+        ],
+      }));
+    },
+  });
+  const cn = new MssqlConnection(1n, ffi);
+  const script = "DELETE TOP (4) FROM Log;\nGO\nUPDATE Stats SET n = 0;";
+  await assertRejects(
+    () => cn.runScript(script),
+    Error,
+    "Batch 2 (line 5) failed: deadlock",
+  );
+  assertEquals(sent.continue_on_error, false);
+
+  const result = await cn.runScript(script, {}, { continueOnError: true });
+  assertEquals(sent.continue_on_error, true);
+  assertEquals(result.results[1].error, "deadlock");
+  await cn.disconnect();
+});
+
 Deno.test("serializeCommand - output param includes output flag", () => {
   const params: Params = {
     input: 42,
@@ -679,6 +1537,70 @@ Deno.test("MssqlConnection.disconnect - delegates to close()", async () => {
   assertEquals(disconnected, true);
 });
 
+// ── setLabel() tests ─────────────────────────────────────────
+
+Deno.test("MssqlConnection.setLabel - passes label to ffi, null clears", async () => {
+  const labels: string[] = [];
+  const ffi = createMockFFI({
+    setLabel: (_connId, label) => {
+      labels.push(label);
+    },
+  });
+  const cn = new MssqlConnection(1n, ffi);
+  cn.setLabel("nightly-export");
+  cn.setLabel(null);
+  assertEquals(labels, ["nightly-export", ""]);
+  await cn.close();
+  assertThrows(() => cn.setLabel("late"), Error, "Connection is closed");
+});
+
+// ── enableColumnEncryption() tests ───────────────────────────
+
+Deno.test("MssqlConnection.enableColumnEncryption - installs unwrapped keys", async () => {
+  let configured: string | null = null;
+  const ffi = createMockFFI({
+    query: () =>
+      Promise.resolve(JSON.stringify([{
+        table: "dbo.Patients",
+        name: "Ssn",
+        type: "nvarchar",
+        precision: 0,
+        scale: 0,
+        cek_id: 3,
+        provider: "TEST",
+        key_path: "k",
+        algorithm: "RSA_OAEP",
+        encrypted_value: btoa("wrapped"),
+      }])),
+    aeConfigure: (_connId, json) => {
+      configured = json;
+      return 1;
+    },
+  });
+  const cn = new MssqlConnection(1n, ffi);
+  await cn.enableColumnEncryption({
+    TEST: {
+      decryptColumnEncryptionKey: () => Promise.resolve(new Uint8Array([5])),
+    },
+  });
+  const config = JSON.parse(configured!);
+  assertEquals(config.keys, [{ id: 3, key: btoa("\x05") }]);
+  assertEquals(config.columns[0].name, "Ssn");
+});
+
+Deno.test("MssqlConnection.enableColumnEncryption - surfaces native errors", async () => {
+  const ffi = createMockFFI({
+    aeConfigure: () => 0,
+    lastError: () => "Column encryption key must be 32 bytes, got 1",
+  });
+  const cn = new MssqlConnection(1n, ffi);
+  await assertRejects(
+    () => cn.enableColumnEncryption({}),
+    Error,
+    "32 bytes",
+  );
+});
+
 // ── serializeCommand stream options tests ─────────────────────
 
 Deno.test("serializeCommand - without stream options omits fields", () => {
@@ -0,0 +1,161 @@
+import { assertEquals, assertRejects, assertThrows } from "jsr:@std/assert";
+import {
+  buildKeyringConfig,
+  parseEncryptedCek,
+  rsaKeyStoreProvider,
+} from "./encryption.ts";
+import type { EncryptedColumnRow, KeyStoreProvider } from "./encryption.ts";
+
+function encryptedCek(keyPath: string, ciphertext: Uint8Array): Uint8Array {
+  const path = new Uint8Array(keyPath.length * 2);
+  for (let i = 0; i < keyPath.length; i++) {
+    path[i * 2] = keyPath.charCodeAt(i);
+  }
+  const out = new Uint8Array(5 + path.length + ciphertext.length + 4);
+  const view = new DataView(out.buffer);
+  out[0] = 0x01;
+  view.setUint16(1, path.length, true);
+  view.setUint16(3, ciphertext.length, true);
+  out.set(path, 5);
+  out.set(ciphertext, 5 + path.length);
+  out.set([9, 9, 9, 9], 5 + path.length + ciphertext.length);
+  return out;
+}
+
+function row(overrides: Partial<EncryptedColumnRow> = {}): EncryptedColumnRow {
+  return {
+    table: "dbo.Patients",
+    name: "Ssn",
+    type: "nvarchar",
+    precision: 0,
+    scale: 0,
+    cek_id: 1,
+    provider: "TEST",
+    key_path: "CurrentUser/My/ABC",
+    algorithm: "RSA_OAEP",
+    encrypted_value: btoa("wrapped"),
+    ...overrides,
+  };
+}
+
+Deno.test("parseEncryptedCek - splits key path, ciphertext and signature", () => {
+  const parsed = parseEncryptedCek(
+    encryptedCek("CurrentUser/My/ABC", new Uint8Array([1, 2, 3])),
+  );
+  assertEquals(parsed.keyPath, "CurrentUser/My/ABC");
+  assertEquals([...parsed.ciphertext], [1, 2, 3]);
+  assertEquals([...parsed.signature], [9, 9, 9, 9]);
+});
+
+Deno.test("parseEncryptedCek - rejects unknown version and truncation", () => {
+  assertThrows(() => parseEncryptedCek(new Uint8Array([2, 0, 0, 0, 0])));
+  assertThrows(() => parseEncryptedCek(new Uint8Array([1, 4, 0, 4, 0, 0])));
+});
+
+Deno.test("rsaKeyStoreProvider - unwraps an RSA-OAEP wrapped key", async () => {
+  const pair = await crypto.subtle.generateKey(
+    {
+      name: "RSA-OAEP",
+      modulusLength: 2048,
+      publicExponent: new Uint8Array([1, 0, 1]),
+      hash: "SHA-1",
+    },
+    true,
+    ["encrypt", "decrypt"],
+  );
+  const cek = new Uint8Array(32).fill(7);
+  const wrapped = new Uint8Array(
+    await crypto.subtle.encrypt({ name: "RSA-OAEP" }, pair.publicKey, cek),
+  );
+  const pkcs8 = new Uint8Array(
+    await crypto.subtle.exportKey("pkcs8", pair.privateKey),
+  );
+
+  const provider = rsaKeyStoreProvider(pkcs8);
+  const plain = await provider.decryptColumnEncryptionKey(
+    "CurrentUser/My/ABC",
+    "RSA_OAEP",
+    encryptedCek("CurrentUser/My/ABC", wrapped),
+  );
+  assertEquals(plain, cek);
+  await assertRejects(() =>
+    provider.decryptColumnEncryptionKey("x", "RSA_PKCS1", wrapped)
+  );
+});
+
+Deno.test("buildKeyringConfig - unwraps each key once", async () => {
+  let calls = 0;
+  const provider: KeyStoreProvider = {
+    decryptColumnEncryptionKey: () => {
+      calls++;
+      return Promise.resolve(new Uint8Array([1, 2, 3]));
+    },
+  };
+  const json = await buildKeyringConfig(
+    [row(), row({ name: "Salary", type: "int" })],
+    { TEST: provider },
+  );
+  assertEquals(calls, 1);
+  assertEquals(JSON.parse(json), {
+    keys: [{ id: 1, key: btoa("\x01\x02\x03") }],
+    columns: [
+      {
+        table: "dbo.Patients",
+        name: "Ssn",
+        type: "nvarchar",
+        precision: 0,
+        scale: 0,
+        cek_id: 1,
+      },
+      {
+        table: "dbo.Patients",
+        name: "Salary",
+        type: "int",
+        precision: 0,
+        scale: 0,
+        cek_id: 1,
+      },
+    ],
+  });
+});
+
+Deno.test("buildKeyringConfig - keeps only the listed tables", async () => {
+  const provider: KeyStoreProvider = {
+    decryptColumnEncryptionKey: () => Promise.resolve(new Uint8Array([1])),
+  };
+  const rows = [
+    row(),
+    row({ table: "hr.Staff", type: "varchar", cek_id: 2 }),
+  ];
+
+  const all = JSON.parse(await buildKeyringConfig(rows, { TEST: provider }));
+  assertEquals(all.columns.length, 2);
+
+  for (const tables of [["dbo.Patients"], ["PATIENTS"]]) {
+    const json = await buildKeyringConfig(rows, { TEST: provider }, tables);
+    const config = JSON.parse(json);
+    assertEquals(config.keys.map((k: { id: number }) => k.id), [1]);
+    assertEquals(
+      config.columns.map((c: { table: string }) => c.table),
+      ["dbo.Patients"],
+    );
+  }
+});
+
+Deno.test("buildKeyringConfig - uses any registered CMK and reports missing providers", async () => {
+  const provider: KeyStoreProvider = {
+    decryptColumnEncryptionKey: () => Promise.resolve(new Uint8Array([1])),
+  };
+  // Mid-rotation: one CEK wrapped by two CMKs
+  const json = await buildKeyringConfig(
+    [row({ provider: "OTHER" }), row()],
+    { TEST: provider },
+  );
+  assertEquals(JSON.parse(json).keys.length, 1);
+
+  await assertRejects(
+    () => buildKeyringConfig([row({ provider: "OTHER" })], { TEST: provider }),
+    Error,
+    "OTHER",
+  );
+});
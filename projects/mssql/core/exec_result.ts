@@ -3,11 +3,31 @@
  * @module
  */
 
+/** Metadata for one column of an `exec()` result set. */
+export interface ExecColumn {
+  /** Row key of the column. */
+  name: string;
+  /**
+   * The server's name for a column that had to be renamed: `""` for an
+   * unnamed column, or the repeated name of a duplicate.
+   */
+  sourceName?: string;
+  /** SQL Server type name, e.g. `int` or `nvarchar`. */
+  type: string;
+  nullable: boolean;
+}
+
 /** Raw JSON shape returned by the mssql_exec FFI function. */
 export interface ExecResultRaw {
   rowsAffected: number;
   resultSets: Record<string, unknown>[][];
+  /** Columns of each result set, parallel to `resultSets`. */
+  columns?: ExecColumn[][];
+  /** Some column was unnamed or duplicated and got a generated key. */
+  renamedColumns?: boolean;
   outputParams: Record<string, unknown>;
+  /** RETURN code of the stored procedure (null for text commands). */
+  returnValue?: number | null;
 }
 
 /**
@@ -21,10 +41,12 @@ export interface ExecResultRaw {
  * }, { commandType: "stored_procedure" });
  *
  * result.rowsAffected;             // number
+ * result.returnValue;              // number | null (procedure RETURN code)
  * result.resultSets;               // number (count)
  * result.getOutput<number>("output"); // OUTPUT param value
  * result.getResults<T>(0);          // T[] from result set 0
  * result.getResultFirst<T>(0);      // T | undefined
+ * result.getColumns(0);             // ExecColumn[] in column order
  * ```
  */
 export class ExecResult {
@@ -34,6 +56,19 @@ export class ExecResult {
   /** Number of result sets returned. */
   readonly resultSets: number;
 
+  /**
+   * The stored procedure's RETURN code, or `null` for text commands.
+   * Procedures without an explicit RETURN yield `0`.
+   */
+  readonly returnValue: number | null;
+
+  /**
+   * Whether a result set had unnamed (`SELECT 1`) or duplicate column names.
+   * Those columns are keyed `column<n>` and `<name>_1`, `<name>_2`...; see
+   * `sourceName` in {@link getColumns}.
+   */
+  readonly renamedColumns: boolean;
+
   readonly #data: ExecResultRaw;
 
   /** @internal */
@@ -41,6 +76,8 @@ export class ExecResult {
     this.#data = raw;
     this.rowsAffected = raw.rowsAffected;
     this.resultSets = raw.resultSets.length;
+    this.returnValue = raw.returnValue ?? null;
+    this.renamedColumns = raw.renamedColumns ?? false;
   }
 
   /**
@@ -64,16 +101,22 @@ export class ExecResult {
    * @throws If the index is out of range.
    */
   getResults<T = Record<string, unknown>>(index: number): T[] {
-    if (index < 0 || index >= this.#data.resultSets.length) {
-      throw new RangeError(
-        `Result set index ${index} out of range (0..${
-          this.#data.resultSets.length - 1
-        })`,
-      );
-    }
+    this.#checkIndex(index);
     return this.#data.resultSets[index] as T[];
   }
 
+  /**
+   * Get the columns of the result set at the given index, in column order.
+   * Available for empty result sets too, so a mapper can be built without
+   * a row to look at.
+   *
+   * @throws If the index is out of range.
+   */
+  getColumns(index: number): ExecColumn[] {
+    this.#checkIndex(index);
+    return this.#data.columns?.[index] ?? [];
+  }
+
   /**
    * Get the first row from the result set at the given index, or undefined.
    *
@@ -82,4 +125,14 @@ export class ExecResult {
   getResultFirst<T = Record<string, unknown>>(index: number): T | undefined {
     return this.getResults<T>(index)[0];
   }
+
+  #checkIndex(index: number): void {
+    if (index < 0 || index >= this.#data.resultSets.length) {
+      throw new RangeError(
+        `Result set index ${index} out of range (0..${
+          this.#data.resultSets.length - 1
+        })`,
+      );
+    }
+  }
 }
@@ -16,6 +16,16 @@ Deno.test("ExecResult - rowsAffected", () => {
   assertEquals(r.rowsAffected, 42);
 });
 
+Deno.test("ExecResult - returnValue", () => {
+  const r = new ExecResult(makeRaw({ returnValue: 5 }));
+  assertEquals(r.returnValue, 5);
+});
+
+Deno.test("ExecResult - returnValue null when absent", () => {
+  const r = new ExecResult(makeRaw());
+  assertEquals(r.returnValue, null);
+});
+
 Deno.test("ExecResult - resultSets count", () => {
   const r = new ExecResult(
     makeRaw({ resultSets: [[{ id: 1 }], [{ name: "a" }]] }),
@@ -88,3 +98,35 @@ Deno.test("ExecResult - multiple result sets", () => {
   assertEquals(r.getResultFirst(0), { a: 1 });
   assertEquals(r.getResultFirst(1), { b: 2 });
 });
+
+Deno.test("ExecResult - getColumns describes empty result sets", () => {
+  const columns = [
+    { name: "Id", type: "int", nullable: false },
+    { name: "Name", type: "nvarchar", nullable: true },
+  ];
+  const r = new ExecResult(makeRaw({ resultSets: [[]], columns: [columns] }));
+  assertEquals(r.resultSets, 1);
+  assertEquals(r.getColumns(0), columns);
+  assertThrows(() => r.getColumns(1), RangeError, "out of range");
+});
+
+Deno.test("ExecResult - getColumns empty without metadata", () => {
+  const r = new ExecResult(makeRaw({ resultSets: [[{ id: 1 }]] }));
+  assertEquals(r.getColumns(0), []);
+});
+
+Deno.test("ExecResult - renamedColumns flags generated keys", () => {
+  assertEquals(new ExecResult(makeRaw()).renamedColumns, false);
+  const columns = [
+    { name: "Id", type: "int", nullable: false },
+    { name: "Id_1", type: "int", nullable: false, sourceName: "Id" },
+    { name: "column2", type: "int", nullable: true, sourceName: "" },
+  ];
+  const r = new ExecResult(makeRaw({
+    resultSets: [[{ Id: 1, Id_1: 2, column2: 3 }]],
+    columns: [columns],
+    renamedColumns: true,
+  }));
+  assertEquals(r.renamedColumns, true);
+  assertEquals(r.getColumns(0)[1].sourceName, "Id");
+});
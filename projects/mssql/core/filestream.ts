@@ -1,6 +1,7 @@
 /**
- * FILESTREAM blob access for Windows. Provides both Node.js-compatible
- * streams (`node:stream`) and Web Standard streams.
+ * FILESTREAM blob access: native on Windows, emulated with chunked
+ * statements elsewhere when opened by row key. Provides both
+ * Node.js-compatible streams (`node:stream`) and Web Standard streams.
  * @module
  */
 
@@ -8,7 +9,10 @@ import { Duplex, Readable, Writable } from "node:stream";
 
 import type { RuntimeFFI } from "./runtime.ts";
 import { INVALID_HANDLE } from "./runtime.ts";
-import type { FilestreamMode } from "./types.ts";
+import type { FilestreamMode, FilestreamWriteMode } from "./types.ts";
+
+/** Chunk size used by `read()` without a limit. */
+const READ_ALL_CHUNK = 1024 * 1024;
 
 /**
  * Internal handle to a SQL Server FILESTREAM blob. Windows only.
@@ -39,24 +43,22 @@ export class FilestreamHandle implements AsyncDisposable {
     path: string,
     txContext: Uint8Array | string,
     mode: FilestreamMode,
+    label: string | null = null,
+    writeMode: FilestreamWriteMode = "overwrite",
   ): FilestreamHandle {
-    if (!ffi.filestreamAvailable()) {
-      throw new Error(
-        "FILESTREAM requires Microsoft OLE DB Driver 19 for SQL Server.\n" +
-          "\n" +
-          "Install via:\n" +
-          "  winget install Microsoft.OLEDBDriver\n" +
-          "  https://learn.microsoft.com/en-us/sql/connect/oledb/download-oledb-driver-for-sql-server\n" +
-          "\n" +
-          "This is ONLY needed for FILESTREAM. All other driver features work without it.",
-      );
-    }
+    FilestreamHandle.#ensureAvailable(ffi);
 
     const ctxBase64 = txContext instanceof Uint8Array
       ? btoa(String.fromCharCode(...txContext))
       : txContext;
 
-    const req = JSON.stringify({ path, tx_context_base64: ctxBase64, mode });
+    const req = JSON.stringify({
+      path,
+      tx_context_base64: ctxBase64,
+      mode,
+      write_mode: writeMode,
+      label,
+    });
     const fsId = ffi.filestreamOpen(req);
 
     if (fsId === INVALID_HANDLE) {
@@ -66,6 +68,41 @@ export class FilestreamHandle implements AsyncDisposable {
     return new FilestreamHandle(fsId, ffi, mode);
   }
 
+  /**
+   * Open a row's FILESTREAM value on `connId`; the driver looks up the path
+   * and transaction context itself. Works on every platform: without the
+   * OLE DB driver the I/O is emulated with chunked statements.
+   * @internal
+   */
+  static async _openFor(
+    ffi: RuntimeFFI,
+    connId: bigint,
+    reqJson: string,
+    mode: FilestreamMode,
+  ): Promise<FilestreamHandle> {
+    const fsId = await ffi.filestreamOpenFor(connId, reqJson);
+    if (fsId === INVALID_HANDLE) {
+      throw new Error(
+        ffi.lastError(connId) ?? "Failed to open FILESTREAM handle",
+      );
+    }
+    return new FilestreamHandle(fsId, ffi, mode);
+  }
+
+  static #ensureAvailable(ffi: RuntimeFFI): void {
+    if (!ffi.filestreamAvailable()) {
+      throw new Error(
+        "FILESTREAM requires Microsoft OLE DB Driver 19 for SQL Server.\n" +
+          "\n" +
+          "Install via:\n" +
+          "  winget install Microsoft.OLEDBDriver\n" +
+          "  https://learn.microsoft.com/en-us/sql/connect/oledb/download-oledb-driver-for-sql-server\n" +
+          "\n" +
+          "This is ONLY needed for FILESTREAM. All other driver features work without it.",
+      );
+    }
+  }
+
   /** Check if FILESTREAM is available on this platform. */
   static isAvailable(ffi: RuntimeFFI): boolean {
     return ffi.filestreamAvailable();
@@ -73,14 +110,38 @@ export class FilestreamHandle implements AsyncDisposable {
 
   /** Read up to maxBytes. Omit for entire blob. */
   async read(maxBytes?: number): Promise<Uint8Array> {
+    if (!maxBytes) return await this.#readAll();
+    const buf = new Uint8Array(maxBytes);
+    const n = await this.readInto(buf);
+    return buf.subarray(0, n);
+  }
+
+  /**
+   * Read into a caller-provided buffer, returning bytes read (0 at the end
+   * of the blob). The bytes are copied straight from the driver.
+   */
+  async readInto(buf: Uint8Array): Promise<number> {
     this.#ensureOpen();
-    const ptr = this.#ffi.filestreamRead(this.#fsId, BigInt(maxBytes ?? 0));
-    if (ptr === null) throw new Error("FILESTREAM read failed");
-    const result = JSON.parse(ptr);
-    if (result.__error) throw new Error(`FILESTREAM read: ${result.__error}`);
-    const binary = atob(result.data);
-    const bytes = new Uint8Array(binary.length);
-    for (let i = 0; i < binary.length; i++) bytes[i] = binary.charCodeAt(i);
+    const n = await this.#ffi.filestreamReadInto(this.#fsId, buf);
+    if (n < 0) throw new Error("FILESTREAM read failed");
+    return n;
+  }
+
+  async #readAll(): Promise<Uint8Array> {
+    const chunks: Uint8Array[] = [];
+    let total = 0;
+    for (;;) {
+      const chunk = await this.read(READ_ALL_CHUNK);
+      if (chunk.length === 0) break;
+      chunks.push(chunk);
+      total += chunk.length;
+    }
+    const bytes = new Uint8Array(total);
+    let offset = 0;
+    for (const chunk of chunks) {
+      bytes.set(chunk, offset);
+      offset += chunk.length;
+    }
     return bytes;
   }
 
@@ -88,13 +149,32 @@ export class FilestreamHandle implements AsyncDisposable {
   async write(data: Uint8Array): Promise<number> {
     this.#ensureOpen();
     const b64 = btoa(String.fromCharCode(...data));
-    const written = this.#ffi.filestreamWrite(this.#fsId, b64);
+    const written = await this.#ffi.filestreamWrite(this.#fsId, b64);
     if (written === 0n && data.length > 0) {
       throw new Error("FILESTREAM write failed");
     }
     return Number(written);
   }
 
+  /** Truncate or extend to `length` bytes; later writes continue there. */
+  async setLength(length: number): Promise<void> {
+    this.#ensureOpen();
+    const err = await this.#ffi.filestreamSetLength(this.#fsId, BigInt(length));
+    if (err !== null) throw new Error(err);
+  }
+
+  /**
+   * Commit what was written. Off Windows, a handle opened for writing
+   * outside a transaction writes in one of its own, and closing it without
+   * `finish()` rolls the writes back; inside a transaction (and on
+   * Windows) the writes commit with it and this does nothing.
+   */
+  async finish(): Promise<void> {
+    this.#ensureOpen();
+    const err = await this.#ffi.filestreamFinish(this.#fsId);
+    if (err !== null) throw new Error(err);
+  }
+
   /** Create a Web ReadableStream from this FILESTREAM handle. */
   toReadableStream(chunkSize = 65536): ReadableStream<Uint8Array> {
     return new ReadableStream({
@@ -124,8 +204,12 @@ export class FilestreamHandle implements AsyncDisposable {
       write: async (chunk) => {
         await this.write(chunk);
       },
-      close: () => {
-        this.close();
+      close: async () => {
+        try {
+          await this.finish();
+        } finally {
+          this.close();
+        }
       },
       abort: () => {
         this.close();
@@ -210,6 +294,21 @@ export class FilestreamWritable extends Writable {
       .catch((err) => callback(err instanceof Error ? err : new Error(String(err))));
   }
 
+  override _final(callback: (error?: Error | null) => void): void {
+    this.#handle
+      .finish()
+      .then(() => callback())
+      .catch((err) => callback(err instanceof Error ? err : new Error(String(err))));
+  }
+
+  /**
+   * Truncate or extend the blob to `length` bytes; later writes continue
+   * from there. Call it between writes, not while one is pending.
+   */
+  setLength(length: number): Promise<void> {
+    return this.#handle.setLength(length);
+  }
+
   override _destroy(
     err: Error | null,
     cb: (err: Error | null) => void,
@@ -256,6 +355,21 @@ export class FilestreamDuplex extends Duplex {
       .catch((err) => callback(err instanceof Error ? err : new Error(String(err))));
   }
 
+  override _final(callback: (error?: Error | null) => void): void {
+    this.#handle
+      .finish()
+      .then(() => callback())
+      .catch((err) => callback(err instanceof Error ? err : new Error(String(err))));
+  }
+
+  /**
+   * Truncate or extend the blob to `length` bytes; later writes continue
+   * from there. Call it between writes, not while one is pending.
+   */
+  setLength(length: number): Promise<void> {
+    return this.#handle.setLength(length);
+  }
+
   override _destroy(
     err: Error | null,
     cb: (err: Error | null) => void,
@@ -3,6 +3,41 @@ export { UTF8_COLLATIONS, utf8Column, supportsUtf8, setDatabaseUtf8 } from "./co
 export { Transaction } from "./transaction.ts";
 export { QueryStream } from "./stream.ts";
 export { BulkInsertBuilder } from "./bulk.ts";
+export { BlobReader } from "./blob.ts";
+export type { BrokerEndOptions, BrokerMessage, BrokerSendOptions } from "./broker.ts";
+export type { CdcChange, CdcChangeSet, CdcReadOptions } from "./cdc.ts";
+export { decodeCompressedRows } from "./compress.ts";
+export { RowBuffer } from "./row_buffer.ts";
+export type { BufferedQueryOptions } from "./row_buffer.ts";
+export type {
+  CompressedResult,
+  Compression,
+  CompressOptions,
+  Decompressor,
+} from "./compress.ts";
+export type {
+  QueryStatistics,
+  QueryStatisticsResult,
+  TableIoStatistics,
+  TimingStatistics,
+} from "./statistics.ts";
+export type {
+  ColumnSchema,
+  DatabaseInfo,
+  DbObject,
+  DbObjectType,
+  ForeignKeySchema,
+  IndexSchema,
+  ObjectFilter,
+  SchemaInfo,
+  TableSchema,
+} from "./catalog.ts";
+export type { ChangeTrackingResult, TrackedChange } from "./tracking.ts";
+export type { SystemTime, TemporalQueryOptions, TemporalRow } from "./temporal.ts";
+export { QueryNotification } from "./notification.ts";
+export type { QueryNotificationEvent } from "./notification.ts";
+export { parseEncryptedCek, rsaKeyStoreProvider } from "./encryption.ts";
+export type { EncryptedCek, KeyStoreProvider } from "./encryption.ts";
 export { FilestreamHandle } from "./filestream.ts";
 export { parseConnection } from "./config.ts";
 export { MssqlConnection, serializeCommand } from "./connection.ts";
@@ -13,13 +48,32 @@ export type { RuntimeFFI, RuntimeInfo } from "./runtime.ts";
 export { INVALID_HANDLE } from "./runtime.ts";
 export type {
   MssqlConfig,
+  EffectiveConfig,
   NormalizedConfig,
   CommandOptions,
+  ApplicationIntent,
+  BigIntMode,
+  ColumnCase,
+  DecimalMode,
+  TemporalMode,
+  TlsVersion,
+  TracingOptions,
+  VarcharDecodePolicy,
+  ExecuteResult,
+  PageRequest,
+  PageResult,
   Params,
   ParamValue,
+  PlanCapture,
+  QueryPlanOptions,
+  QueryPlanResult,
   TypedParam,
   SqlType,
   IsolationLevel,
+  LogCategory,
+  LoggingOptions,
+  LogLevel,
+  LogRecord,
   CommandType,
   BulkColumn,
   FilestreamMode,
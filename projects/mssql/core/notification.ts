@@ -0,0 +1,85 @@
+/**
+ * Query notifications (SqlDependency-style change subscriptions).
+ * @module
+ */
+
+import type { RuntimeFFI } from "./runtime.ts";
+
+/** Default wait for {@link QueryNotification.receive} (30 seconds). */
+const DEFAULT_RECEIVE_TIMEOUT_MS = 30_000;
+
+/** Why a query notification fired, as reported by SQL Server. */
+export interface QueryNotificationEvent {
+  /**
+   * `"change"` when the results changed, `"subscribe"` when the query cannot
+   * be subscribed to (see `info`), `"unknown"` otherwise.
+   */
+  type: "change" | "subscribe" | "unknown";
+  /** What fired it, e.g. `"data"`, `"timeout"`, `"object"`, `"statement"`. */
+  source: string;
+  /** Detail, e.g. `"insert"`, `"update"`, `"delete"`, `"invalid"`. */
+  info: string;
+}
+
+/**
+ * A change subscription on a query's results, backed by a private Service
+ * Broker queue and service that are dropped on {@link close}, or when the
+ * connection closes or is released. Obtain one via `cn.subscribe()`.
+ *
+ * Like `SqlDependency`, a subscription fires once: re-run `cn.subscribe()`
+ * after handling an event to keep watching. `receive()` holds the connection
+ * while it waits, so use a dedicated connection.
+ *
+ * @example
+ * ```ts
+ * await using sub = await cn.subscribe(
+ *   "SELECT Id, Status FROM dbo.Orders WHERE Status = @s",
+ *   { s: "open" },
+ * );
+ * const events = await sub.receive(60_000);
+ * if (events.length) console.log("orders changed:", events[0].info);
+ * ```
+ */
+export class QueryNotification implements AsyncDisposable {
+  #subId: bigint;
+  #ffi: RuntimeFFI;
+  #closed = false;
+
+  /** @internal */
+  constructor(subId: bigint, ffi: RuntimeFFI) {
+    this.#subId = subId;
+    this.#ffi = ffi;
+  }
+
+  /**
+   * Wait up to `timeoutMs` for notifications. Returns an empty array on
+   * timeout.
+   */
+  async receive(
+    timeoutMs: number = DEFAULT_RECEIVE_TIMEOUT_MS,
+  ): Promise<QueryNotificationEvent[]> {
+    if (this.#closed) throw new Error("Query notification is closed");
+    const json = await this.#ffi.notifyReceive(
+      this.#subId,
+      BigInt(Math.max(0, Math.trunc(timeoutMs))),
+    );
+    if (json === null) throw new Error("Query notification receive failed");
+    const result = JSON.parse(json);
+    if (result.__error) {
+      throw new Error(`Query notification: ${result.__error}`);
+    }
+    return result.events;
+  }
+
+  /** Drop the subscription's queue and service. */
+  async close(): Promise<void> {
+    if (!this.#closed) {
+      this.#closed = true;
+      await this.#ffi.notifyClose(this.#subId);
+    }
+  }
+
+  async [Symbol.asyncDispose](): Promise<void> {
+    await this.close();
+  }
+}
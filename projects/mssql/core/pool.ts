@@ -5,11 +5,41 @@
 
 import type { RuntimeFFI } from "./runtime.ts";
 import { INVALID_HANDLE } from "./runtime.ts";
-import type { CommandOptions, Params, ParamValue, StreamOptions } from "./types.ts";
+import type {
+  CommandOptions,
+  EffectiveConfig,
+  ExecuteResult,
+  InsertOptions,
+  InsertResult,
+  PageRequest,
+  PageResult,
+  Params,
+  ParamValue,
+  QueryPlanOptions,
+  QueryPlanResult,
+  ScriptOptions,
+  ScriptResult,
+  StreamOptions,
+} from "./types.ts";
 import type { ExecResult } from "./exec_result.ts";
 import { MssqlConnection } from "./connection.ts";
 import { QueryStream } from "./stream.ts";
 import { BulkInsertBuilder } from "./bulk.ts";
+import type { QueryStatisticsResult } from "./statistics.ts";
+import type { CompressedResult, CompressOptions } from "./compress.ts";
+import type { BufferedQueryOptions, RowBuffer } from "./row_buffer.ts";
+import type {
+  DatabaseInfo,
+  DbObject,
+  ObjectFilter,
+  SchemaInfo,
+  TableSchema,
+} from "./catalog.ts";
+import type {
+  SystemTime,
+  TemporalQueryOptions,
+  TemporalRow,
+} from "./temporal.ts";
 
 /**
  * A connection pool for SQL Server. Acquire connections or use
@@ -33,8 +63,12 @@ export class MssqlPool implements Disposable, AsyncDisposable {
     this.#ffi = ffi;
   }
 
-  /** Acquire a connection from the pool. */
-  async connect(): Promise<MssqlConnection> {
+  /**
+   * Acquire a connection from the pool. `tag` names this use of it in
+   * diagnostics, the slow query log, error messages and leak reports, next
+   * to the pool's `label`; it's the same as calling `cn.setLabel(tag)`.
+   */
+  async connect(opts?: { tag?: string }): Promise<MssqlConnection> {
     this.#ensureOpen();
     const connId = await this.#ffi.poolAcquire(this.#poolId);
     if (connId === INVALID_HANDLE) {
@@ -43,7 +77,9 @@ export class MssqlPool implements Disposable, AsyncDisposable {
           "Failed to acquire connection from pool",
       );
     }
-    return new MssqlConnection(connId, this.#ffi, this.#poolId);
+    const cn = new MssqlConnection(connId, this.#ffi, this.#poolId);
+    if (opts?.tag) cn.setLabel(opts.tag);
+    return cn;
   }
 
   /** Execute a query using an auto-acquired connection. */
@@ -60,6 +96,47 @@ export class MssqlPool implements Disposable, AsyncDisposable {
     return await cn.query<T>(sql, params, opts);
   }
 
+  /** Execute a `FOR JSON` query and return its document, parsed. */
+  async queryJson<T = unknown>(
+    sql: string,
+    params?: Params,
+    opts?: CommandOptions,
+  ): Promise<T | null> {
+    await using cn = await this.connect();
+    return await cn.queryJson<T>(sql, params, opts);
+  }
+
+  /** Execute a query for one page of its rows, with a total if asked. */
+  async queryPage<T = Record<string, unknown>>(
+    sql: string,
+    params: Params | undefined,
+    page: PageRequest,
+    opts?: CommandOptions,
+  ): Promise<PageResult<T>> {
+    await using cn = await this.connect();
+    return await cn.queryPage<T>(sql, params, page, opts);
+  }
+
+  /** Execute a query into a `RowBuffer`, reusing `opts.buffer` if given. */
+  async queryBuffered(
+    sql: string,
+    params?: Params,
+    opts?: BufferedQueryOptions,
+  ): Promise<RowBuffer> {
+    await using cn = await this.connect();
+    return await cn.queryBuffered(sql, params, opts);
+  }
+
+  /** Execute a query and return its rows as bytes, compressed when large. */
+  async queryCompressed(
+    sql: string,
+    params: Params | undefined,
+    opts: CompressOptions,
+  ): Promise<CompressedResult> {
+    await using cn = await this.connect();
+    return await cn.queryCompressed(sql, params, opts);
+  }
+
   /** Execute a query and return the first row, or undefined. */
   async queryFirst<T = Record<string, unknown>>(
     sql: string,
@@ -80,6 +157,26 @@ export class MssqlPool implements Disposable, AsyncDisposable {
     return await cn.querySingle<T>(sql, params, opts);
   }
 
+  /** Execute a query and return its rows with the execution plan XML. */
+  async queryWithPlan<T = Record<string, unknown>>(
+    sql: string,
+    params?: Params,
+    opts?: QueryPlanOptions,
+  ): Promise<QueryPlanResult<T>> {
+    await using cn = await this.connect();
+    return await cn.queryWithPlan<T>(sql, params, opts);
+  }
+
+  /** Execute a query and return its rows with STATISTICS IO/TIME. */
+  async queryWithStatistics<T = Record<string, unknown>>(
+    sql: string,
+    params?: Params,
+    opts?: CommandOptions,
+  ): Promise<QueryStatisticsResult<T>> {
+    await using cn = await this.connect();
+    return await cn.queryWithStatistics<T>(sql, params, opts);
+  }
+
   /** Execute a query and return the first column of the first row. */
   async scalar<T = unknown>(
     sql: string,
@@ -90,6 +187,40 @@ export class MssqlPool implements Disposable, AsyncDisposable {
     return await cn.scalar<T>(sql, params, opts);
   }
 
+  /** Query a system-versioned temporal table `FOR SYSTEM_TIME`. */
+  async queryTemporal<T = Record<string, unknown>>(
+    table: string,
+    systemTime: SystemTime,
+    opts?: TemporalQueryOptions,
+  ): Promise<TemporalRow<T>[]> {
+    await using cn = await this.connect();
+    return await cn.queryTemporal<T>(table, systemTime, opts);
+  }
+
+  /** Describe a table or view: columns, keys and indexes. */
+  async tableSchema(table: string): Promise<TableSchema> {
+    await using cn = await this.connect();
+    return await cn.tableSchema(table);
+  }
+
+  /** List user objects in the current database. */
+  async listObjects(filter?: ObjectFilter): Promise<DbObject[]> {
+    await using cn = await this.connect();
+    return await cn.listObjects(filter);
+  }
+
+  /** List the databases visible to the login. */
+  async listDatabases(): Promise<DatabaseInfo[]> {
+    await using cn = await this.connect();
+    return await cn.listDatabases();
+  }
+
+  /** List the schemas in a database (default: the current database). */
+  async listSchemas(database?: string): Promise<SchemaInfo[]> {
+    await using cn = await this.connect();
+    return await cn.listSchemas(database);
+  }
+
   /** Execute a non-query and return the number of rows affected. */
   async execute(
     sql: string,
@@ -100,6 +231,26 @@ export class MssqlPool implements Disposable, AsyncDisposable {
     return await cn.execute(sql, params, opts);
   }
 
+  /** Run an INSERT and return its generated keys. */
+  async insert<K = number>(
+    sql: string,
+    params?: Params,
+    opts?: InsertOptions,
+  ): Promise<InsertResult<K>> {
+    await using cn = await this.connect();
+    return await cn.insert<K>(sql, params, opts);
+  }
+
+  /** Execute a non-query and return rows affected plus OUTPUT clause rows. */
+  async executeReturning<T = Record<string, unknown>>(
+    sql: string,
+    params?: Params,
+    opts?: CommandOptions,
+  ): Promise<ExecuteResult<T>> {
+    await using cn = await this.connect();
+    return await cn.executeReturning<T>(sql, params, opts);
+  }
+
   /** Execute a stored procedure using an auto-acquired connection. */
   async exec(
     sql: string,
@@ -110,6 +261,16 @@ export class MssqlPool implements Disposable, AsyncDisposable {
     return await cn.exec(sql, params, opts);
   }
 
+  /** Run a `GO`-separated script using an auto-acquired connection. */
+  async runScript(
+    sql: string,
+    params?: Params,
+    opts?: ScriptOptions,
+  ): Promise<ScriptResult> {
+    await using cn = await this.connect();
+    return await cn.runScript(sql, params, opts);
+  }
+
   /** Tagged template for parameterized queries. */
   async sql<T = Record<string, unknown>>(
     strings: TemplateStringsArray,
@@ -158,6 +319,17 @@ export class MssqlPool implements Disposable, AsyncDisposable {
     return await cn.filestreamAvailable(database);
   }
 
+  /**
+   * The configuration the pool runs with, secrets masked, for support dumps
+   * and checking what a connection string resolved to.
+   */
+  effectiveConfig(): EffectiveConfig {
+    this.#ensureOpen();
+    const json = this.#ffi.poolConfig(this.#poolId);
+    if (json === null) throw new Error("Pool is closed");
+    return JSON.parse(json);
+  }
+
   /** Close the pool and all connections. */
   close(): void {
     if (!this.#closed) {
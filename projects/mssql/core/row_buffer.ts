@@ -0,0 +1,108 @@
+/**
+ * Reusable buffers for bulk reads.
+ * @module
+ */
+
+import type { CommandOptions } from "./types.ts";
+
+/** Options for `cn.queryBuffered()`. */
+export interface BufferedQueryOptions extends CommandOptions {
+  /** Buffer to read into, reused across calls. Default: a new one. */
+  buffer?: RowBuffer;
+}
+
+/**
+ * Rows of a query as UTF-8 JSON objects laid end to end, with an index of
+ * where each starts. The driver writes them into a buffer the connection
+ * keeps, and they are copied here in one pass instead of as one large JSON
+ * string. Reuse a `RowBuffer` across queries to keep its memory; it grows to
+ * fit the largest result read into it. Rows are parsed when they're read.
+ *
+ * @example
+ * ```ts
+ * const buffer = new RowBuffer(16 * 1024 * 1024);
+ * for (const day of days) {
+ *   await cn.queryBuffered("SELECT * FROM Events WHERE Day = @day", { day }, {
+ *     buffer,
+ *   });
+ *   for (const row of buffer.rows<Event>()) handle(row);
+ * }
+ * ```
+ */
+export class RowBuffer {
+  #data: Uint8Array;
+  #offsets: Uint32Array;
+  #rowCount = 0;
+  #decoder = new TextDecoder();
+
+  /** @param byteLength Initial size of the data buffer. Default: 1 MiB. */
+  constructor(byteLength = 1 << 20, rowCapacity = 4096) {
+    this.#data = new Uint8Array(byteLength);
+    this.#offsets = new Uint32Array(rowCapacity + 1);
+  }
+
+  /** Rows held from the last query. */
+  get rowCount(): number {
+    return this.#rowCount;
+  }
+
+  /** Bytes of JSON held from the last query. */
+  get byteLength(): number {
+    return this.#rowCount === 0 ? 0 : this.#offsets[this.#rowCount];
+  }
+
+  /** Bytes the buffer can hold before it grows. */
+  get capacity(): number {
+    return this.#data.length;
+  }
+
+  /** Row `i` as UTF-8 JSON. */
+  rowBytes(i: number): Uint8Array {
+    this.#check(i);
+    return this.#data.subarray(this.#offsets[i], this.#offsets[i + 1]);
+  }
+
+  /** Row `i`, parsed. */
+  row<T = Record<string, unknown>>(i: number): T {
+    return JSON.parse(this.#decoder.decode(this.rowBytes(i))) as T;
+  }
+
+  /** Parse the rows one at a time. */
+  *rows<T = Record<string, unknown>>(): Generator<T> {
+    for (let i = 0; i < this.#rowCount; i++) yield this.row<T>(i);
+  }
+
+  /** Parse all the rows. */
+  toArray<T = Record<string, unknown>>(): T[] {
+    return [...this.rows<T>()];
+  }
+
+  /**
+   * @internal Size the buffers for `rows` rows of `length` bytes and fill
+   * them with `read`, which returns the row count or -1.
+   */
+  fill(
+    rows: number,
+    length: number,
+    read: (data: Uint8Array, offsets: Uint32Array) => number,
+  ): void {
+    this.#rowCount = 0;
+    if (length > this.#data.length) {
+      this.#data = new Uint8Array(Math.max(length, this.#data.length * 2));
+    }
+    if (rows + 1 > this.#offsets.length) {
+      this.#offsets = new Uint32Array(
+        Math.max(rows + 1, this.#offsets.length * 2),
+      );
+    }
+    const n = read(this.#data, this.#offsets);
+    if (n !== rows) throw new Error("Buffered rows are no longer available");
+    this.#rowCount = n;
+  }
+
+  #check(i: number): void {
+    if (!Number.isInteger(i) || i < 0 || i >= this.#rowCount) {
+      throw new RangeError(`Row ${i} is out of range (${this.#rowCount} rows)`);
+    }
+  }
+}
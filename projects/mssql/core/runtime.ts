@@ -13,25 +13,58 @@
 
 export const INVALID_HANDLE = 0n;
 
+/**
+ * Native log record: level (1 error … 5 trace), target (Rust module path),
+ * message and Unix time in milliseconds.
+ */
+export type NativeLogCallback = (
+  level: number,
+  target: string,
+  message: string,
+  timestampMs: number,
+) => void;
+
 export interface RuntimeFFI {
   // Pool
   poolCreate(configJson: string): Promise<bigint>;
   poolAcquire(poolId: bigint): Promise<bigint>;
   poolRelease(poolId: bigint, connId: bigint): void;
   poolClose(poolId: bigint): void;
+  /** Effective config JSON with secrets masked; null for an unknown pool. */
+  poolConfig(poolId: bigint): string | null;
 
   // Connection
   connect(configJson: string): Promise<bigint>;
   disconnect(connId: bigint): void;
+  /** Name the connection in leak reports; empty clears the label. */
+  setLabel(connId: bigint, label: string): void;
 
   // Queries — return JSON string or null on error
   query(connId: bigint, cmdJson: string): Promise<string | null>;
   executeNonquery(connId: bigint, cmdJson: string): Promise<string | null>;
   exec(connId: bigint, cmdJson: string): Promise<string | null>;
+  /** Collect the compressed result of the last query; null if none. */
+  takeResult(connId: bigint, length: number): Uint8Array | null;
+  /** Decompress `data` to its `length` bytes; null for corrupt data. */
+  decompress(codec: string, data: Uint8Array, length: number): Uint8Array | null;
+  /** Run a query into the connection's row buffer; returns `{rows, length}`. */
+  queryBuffered(connId: bigint, cmdJson: string): Promise<string | null>;
+  /** Copy the buffered rows and their offsets; the row count, or -1. */
+  readRows(connId: bigint, data: Uint8Array, offsets: Uint32Array): number;
+  /** Run a `GO`-separated script; progress is read with `progressPoll`. */
+  execScript(connId: bigint, scriptJson: string): Promise<string | null>;
+  /** Drain the connection's queued progress events (JSON). */
+  progressPoll(connId: bigint): string | null;
 
   // Streaming
   queryStream(connId: bigint, cmdJson: string): Promise<bigint>;
   streamNext(cursorId: bigint): Promise<string | null>;
+  /** Copy a binary column of the current stream row; null if absent or NULL. */
+  streamReadBinary(
+    cursorId: bigint,
+    column: string,
+    length: number,
+  ): Uint8Array | null;
   streamClose(cursorId: bigint): void;
 
   // Bulk
@@ -41,28 +74,94 @@ export interface RuntimeFFI {
   beginTransaction(connId: bigint, txJson: string): Promise<string | null>;
   commit(connId: bigint, txId: string): Promise<string | null>;
   rollback(connId: bigint, txId: string): Promise<string | null>;
+  /** XACT_STATE(), @@TRANCOUNT and the tracked transaction (JSON). */
+  transactionState(connId: bigint): Promise<string | null>;
 
   // Cancel
   cancel(connId: bigint): void;
 
+  // Always Encrypted
+  aeConfigure(connId: bigint, configJson: string): number;
+
   // Error
   lastError(handleId: bigint): string | null;
 
   // FILESTREAM
   filestreamAvailable(): boolean;
   filestreamOpen(reqJson: string): bigint;
-  filestreamRead(fsId: bigint, maxBytes: bigint): string | null;
-  filestreamWrite(fsId: bigint, dataBase64: string): bigint;
+  /** Look up a row's FILESTREAM path and transaction context, then open it. */
+  filestreamOpenFor(connId: bigint, reqJson: string): Promise<bigint>;
+  filestreamRead(fsId: bigint, maxBytes: bigint): Promise<string | null>;
+  /** Read raw bytes into `buf`; returns bytes read, 0 at end, -1 on failure. */
+  filestreamReadInto(fsId: bigint, buf: Uint8Array): Promise<number>;
+  filestreamWrite(fsId: bigint, dataBase64: string): Promise<bigint>;
+  /** Truncate or extend to `length` bytes; returns an error or null. */
+  filestreamSetLength(fsId: bigint, length: bigint): Promise<string | null>;
+  /** Commit the handle's own transaction, if any; returns an error or null. */
+  filestreamFinish(fsId: bigint): Promise<string | null>;
   filestreamClose(fsId: bigint): void;
 
+  // Blob (chunked varbinary(max) reads)
+  blobOpen(connId: bigint, reqJson: string): Promise<bigint>;
+  blobLength(blobId: bigint): bigint;
+  blobRead(
+    blobId: bigint,
+    offset: bigint,
+    length: bigint,
+  ): Promise<string | null>;
+  blobClose(blobId: bigint): void;
+
+  // Query notifications
+  notifySubscribe(connId: bigint, reqJson: string): Promise<bigint>;
+  notifyReceive(subId: bigint, timeoutMs: bigint): Promise<string | null>;
+  notifyClose(subId: bigint): Promise<void>;
+
+  // Service Broker
+  brokerReceive(
+    connId: bigint,
+    queue: string,
+    timeoutMs: bigint,
+  ): Promise<string | null>;
+  brokerSend(connId: bigint, reqJson: string): Promise<string | null>;
+  brokerEnd(connId: bigint, reqJson: string): Promise<string | null>;
+
+  // Change Tracking
+  ctChanges(
+    connId: bigint,
+    table: string,
+    sinceVersion: bigint,
+  ): Promise<string | null>;
+
+  // Change Data Capture
+  cdcChanges(connId: bigint, reqJson: string): Promise<string | null>;
+
+  // Catalog
+  tableSchema(connId: bigint, table: string): Promise<string | null>;
+  listObjects(connId: bigint, filterJson: string): Promise<string | null>;
+  listDatabases(connId: bigint): Promise<string | null>;
+  listSchemas(connId: bigint, database: string): Promise<string | null>;
+
   // Diagnostics
+  versionInfo(): string | null;
   diagnosticInfo(): string | null;
+  slowQueries(): string | null;
+  leakReport(maxAgeMs: number): string | null;
+  metricsPrometheus(): string | null;
+
+  // Tracing
+  configureTracing(configJson: string): Promise<string | null>;
 
   // Debug
   setDebug(enabled: number): void;
+  setMemoryCap(bytes: number): void;
+  setConcurrencyLimit(limit: number): void;
+  setDebugConfig(configJson: string): string | null;
+  setLogCallback(callback: NativeLogCallback | null): void;
 
   // Cleanup
   closeAll(): void;
+  /** Wait for in-flight operations, then close; returns a JSON summary. */
+  closeAllGraceful(timeoutMs: number): Promise<string | null>;
 }
 
 export interface RuntimeInfo {
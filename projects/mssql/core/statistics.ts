@@ -0,0 +1,85 @@
+/**
+ * `SET STATISTICS IO, TIME` results.
+ * @module
+ */
+
+/** `STATISTICS IO` counters for one table (or `Worktable`/`Workfile`). */
+export interface TableIoStatistics {
+  table: string;
+  scanCount: number;
+  logicalReads: number;
+  physicalReads: number;
+  readAheadReads: number;
+  lobLogicalReads: number;
+  lobPhysicalReads: number;
+  lobReadAheadReads: number;
+}
+
+/** CPU and wall-clock time in milliseconds. */
+export interface TimingStatistics {
+  cpuMs: number;
+  elapsedMs: number;
+}
+
+/** Statistics for one command, summed over its statements. */
+export interface QueryStatistics {
+  /** Reads per table, in the order tables were first reported. */
+  tables: TableIoStatistics[];
+  /** Parse and compile time. */
+  compile: TimingStatistics;
+  execution: TimingStatistics;
+}
+
+/** Result of `queryWithStatistics()`. */
+export interface QueryStatisticsResult<T = Record<string, unknown>> {
+  rows: T[];
+  statistics: QueryStatistics;
+  /** Set when unnamed or duplicate columns were given generated keys. */
+  renamedColumns?: boolean;
+}
+
+interface RawTableIo {
+  table: string;
+  scan_count: number;
+  logical_reads: number;
+  physical_reads: number;
+  read_ahead_reads: number;
+  lob_logical_reads: number;
+  lob_physical_reads: number;
+  lob_read_ahead_reads: number;
+}
+
+interface RawTiming {
+  cpu_ms: number;
+  elapsed_ms: number;
+}
+
+function toTiming(t: RawTiming): TimingStatistics {
+  return { cpuMs: t.cpu_ms, elapsedMs: t.elapsed_ms };
+}
+
+/** @internal */
+export function parseStatisticsResult<T>(
+  json: string,
+): QueryStatisticsResult<T> {
+  const result = JSON.parse(json);
+  const stats = result.statistics;
+  return {
+    rows: result.rows,
+    ...(result.renamedColumns && { renamedColumns: true }),
+    statistics: {
+      tables: (stats.tables as RawTableIo[]).map((t) => ({
+        table: t.table,
+        scanCount: t.scan_count,
+        logicalReads: t.logical_reads,
+        physicalReads: t.physical_reads,
+        readAheadReads: t.read_ahead_reads,
+        lobLogicalReads: t.lob_logical_reads,
+        lobPhysicalReads: t.lob_physical_reads,
+        lobReadAheadReads: t.lob_read_ahead_reads,
+      })),
+      compile: toTiming(stats.compile),
+      execution: toTiming(stats.execution),
+    },
+  };
+}
@@ -24,12 +24,14 @@ export class QueryStream<T = Record<string, unknown>> implements AsyncIterable<T
   #ffi: RuntimeFFI;
   #done = false;
   #closed = false;
+  #rawBinary: boolean;
   #onCloseCallbacks: (() => void)[] = [];
 
   /** @internal */
-  constructor(cursorId: bigint, ffi: RuntimeFFI) {
+  constructor(cursorId: bigint, ffi: RuntimeFFI, rawBinary = false) {
     this.#cursorId = cursorId;
     this.#ffi = ffi;
+    this.#rawBinary = rawBinary;
   }
 
   /** @internal Register a callback invoked once when this stream closes. */
@@ -144,6 +146,21 @@ export class QueryStream<T = Record<string, unknown>> implements AsyncIterable<T
     if (parsed && typeof parsed === "object" && "__error" in parsed) {
       throw new Error(`Stream error: ${parsed.__error}`);
     }
+    if (this.#rawBinary) this.#fillBinary(parsed);
     return parsed as T;
   }
+
+  /** Replace `{ __type: "binary", length }` placeholders with the raw bytes. */
+  #fillBinary(row: Record<string, unknown>): void {
+    for (const [column, value] of Object.entries(row)) {
+      if (
+        value === null || typeof value !== "object" ||
+        (value as { __type?: unknown }).__type !== "binary"
+      ) continue;
+      const length = (value as { length: number }).length;
+      row[column] = length === 0
+        ? new Uint8Array(0)
+        : this.#ffi.streamReadBinary(this.#cursorId, column, length);
+    }
+  }
 }
@@ -10,31 +10,78 @@ function createMockFFI(overrides: Partial<RuntimeFFI> = {}): RuntimeFFI {
     poolAcquire: () => Promise.resolve(1n),
     poolRelease: () => {},
     poolClose: () => {},
+    poolConfig: () => null,
     connect: () => Promise.resolve(1n),
     disconnect: () => {},
+    setLabel: () => {},
     query: () => Promise.resolve("[]"),
     executeNonquery: () => Promise.resolve('{"rowsAffected":0}'),
     exec: () =>
       Promise.resolve(
         '{"rowsAffected":0,"resultSets":[],"outputParams":{}}',
       ),
+    takeResult: () => null,
+    decompress: () => null,
+    queryBuffered: () => Promise.resolve('{"rows":0,"length":0}'),
+    readRows: () => 0,
+    execScript: () =>
+      Promise.resolve('{"batches":0,"rowsAffected":0,"results":[]}'),
+    progressPoll: () => '{"events":[],"dropped":0}',
     queryStream: () => Promise.resolve(1n),
     streamNext: () => Promise.resolve(null),
+    streamReadBinary: () => null,
     streamClose: () => {},
     bulkInsert: () => Promise.resolve('{"rowsAffected":0}'),
     beginTransaction: () => Promise.resolve(null),
     commit: () => Promise.resolve(null),
     rollback: () => Promise.resolve(null),
+    transactionState: () =>
+      Promise.resolve(
+        '{"xactState":0,"tranCount":0,"transactionId":null,"readOnly":false}',
+      ),
     cancel: () => {},
+    aeConfigure: () => 1,
     lastError: () => null,
     filestreamAvailable: () => false,
     filestreamOpen: () => 0n,
-    filestreamRead: () => null,
-    filestreamWrite: () => 0n,
+    filestreamOpenFor: () => Promise.resolve(0n),
+    filestreamRead: () => Promise.resolve(null),
+    filestreamReadInto: () => Promise.resolve(-1),
+    filestreamWrite: () => Promise.resolve(0n),
+    filestreamSetLength: () => Promise.resolve(null),
+    filestreamFinish: () => Promise.resolve(null),
     filestreamClose: () => {},
+    blobOpen: () => Promise.resolve(1n),
+    blobLength: () => 0n,
+    blobRead: () => Promise.resolve('{"data":"","length":0}'),
+    blobClose: () => {},
+    notifySubscribe: () => Promise.resolve(1n),
+    notifyReceive: () => Promise.resolve('{"events":[]}'),
+    notifyClose: () => Promise.resolve(),
+    brokerReceive: () => Promise.resolve('{"messages":[]}'),
+    brokerSend: () => Promise.resolve('{"conversation":""}'),
+    brokerEnd: () => Promise.resolve("{}"),
+    ctChanges: () =>
+      Promise.resolve('{"version":0,"min_valid_version":0,"resync_required":false,"changes":[]}'),
+    cdcChanges: () =>
+      Promise.resolve('{"from_lsn":null,"to_lsn":"0x00","to_time":null,"min_lsn":"0x00","resync_required":false,"changes":[]}'),
+    tableSchema: () => Promise.resolve('{"__error":"Object not found"}'),
+    listObjects: () => Promise.resolve('{"objects":[]}'),
+    listDatabases: () => Promise.resolve('{"databases":[]}'),
+    listSchemas: () => Promise.resolve('{"schemas":[]}'),
+    versionInfo: () => null,
     diagnosticInfo: () => null,
+    slowQueries: () => "[]",
+    leakReport: () => "[]",
+    metricsPrometheus: () => "",
+    configureTracing: async () => null,
     setDebug: () => {},
+    setMemoryCap: () => {},
+    setConcurrencyLimit: () => {},
+    setDebugConfig: () => null,
+    setLogCallback: () => {},
     closeAll: () => {},
+    closeAllGraceful: () => Promise.resolve(null),
     ...overrides,
   };
 }
@@ -111,3 +158,29 @@ Deno.test("QueryStream._onClose - callback error is swallowed", () => {
   stream.close(); // Should not throw
   assertEquals(secondCalled, true);
 });
+
+// ── Raw binary tests ─────────────────────────────────────────
+
+Deno.test("QueryStream - rawBinary replaces placeholders with bytes", async () => {
+  const rows = ['{"id":1,"data":{"__type":"binary","length":3},"empty":{"__type":"binary","length":0}}'];
+  const reads: string[] = [];
+  const ffi = createMockFFI({
+    streamNext: () => Promise.resolve(rows.shift() ?? null),
+    streamReadBinary: (_cursorId, column, length) => {
+      reads.push(column);
+      return new Uint8Array(length).fill(7);
+    },
+  });
+  const result = await new QueryStream(1n, ffi, true).toArray();
+  assertEquals(result, [{ id: 1, data: new Uint8Array([7, 7, 7]), empty: new Uint8Array(0) }]);
+  assertEquals(reads, ["data"]);
+});
+
+Deno.test("QueryStream - placeholders left alone without rawBinary", async () => {
+  const rows = ['{"data":{"__type":"binary","length":3}}'];
+  const ffi = createMockFFI({
+    streamNext: () => Promise.resolve(rows.shift() ?? null),
+  });
+  const result = await new QueryStream(1n, ffi).toArray();
+  assertEquals(result, [{ data: { __type: "binary", length: 3 } }]);
+});
@@ -0,0 +1,136 @@
+/**
+ * Point-in-time queries against system-versioned temporal tables.
+ * @module
+ */
+
+import type { CommandOptions, Params, TypedParam } from "./types.ts";
+
+type Instant = Date | string;
+
+/**
+ * A `FOR SYSTEM_TIME` sub-clause. Instants are UTC, like the period columns;
+ * strings are sent as-is (ISO 8601).
+ */
+export type SystemTime =
+  | { asOf: Instant }
+  | { from: Instant; to: Instant }
+  | { between: [Instant, Instant] }
+  | { containedIn: [Instant, Instant] }
+  | "all";
+
+/** Options for `cn.queryTemporal()`. */
+export interface TemporalQueryOptions extends CommandOptions {
+  /** Columns to select (default: all). Names are bracket-quoted. */
+  columns?: string[];
+  /** SQL predicate appended as `WHERE ...`; may reference `params`. */
+  where?: string;
+  /** SQL appended as `ORDER BY ...`. */
+  orderBy?: string;
+  params?: Params;
+}
+
+/** One row version with its validity period, kept apart from the columns. */
+export interface TemporalRow<T = Record<string, unknown>> {
+  row: T;
+  /** Period start, in the connection's temporal mode. */
+  validFrom: unknown;
+  /** Period end (`9999-12-31...` for the current version). */
+  validTo: unknown;
+}
+
+/** Names of a table's period columns, from `sys.periods`. */
+export const PERIOD_COLUMNS_SQL = `SELECT cs.name AS start_column, ce.name AS end_column
+FROM sys.periods p
+JOIN sys.columns cs ON cs.object_id = p.object_id AND cs.column_id = p.start_column_id
+JOIN sys.columns ce ON ce.object_id = p.object_id AND ce.column_id = p.end_column_id
+WHERE p.object_id = OBJECT_ID(@table)`;
+
+/** Result row of {@link PERIOD_COLUMNS_SQL}. */
+export interface PeriodColumns {
+  start_column: string;
+  end_column: string;
+}
+
+const VALID_FROM = "__valid_from";
+const VALID_TO = "__valid_to";
+
+/** Bracket-quote a possibly schema-qualified name (`dbo.T` → `[dbo].[T]`). */
+function quoteObjectName(name: string): string {
+  return name.split(".").map(quoteIdentifier).join(".");
+}
+
+function quoteIdentifier(name: string): string {
+  const clean = name.replace(/^\[/, "").replace(/\]$/, "");
+  return `[${clean.replace(/\]/g, "]]")}]`;
+}
+
+function instantParam(value: Instant): TypedParam {
+  return { value, type: "datetime2" };
+}
+
+/**
+ * Build the `FOR SYSTEM_TIME` clause and its datetime2 parameters
+ * (`@__st_from`, `@__st_to`).
+ */
+export function systemTimeClause(
+  st: SystemTime,
+): { clause: string; params: Params } {
+  if (st === "all") return { clause: "FOR SYSTEM_TIME ALL", params: {} };
+  if ("asOf" in st) {
+    return {
+      clause: "FOR SYSTEM_TIME AS OF @__st_from",
+      params: { __st_from: instantParam(st.asOf) },
+    };
+  }
+  const [keyword, from, to] = "between" in st
+    ? ["BETWEEN @__st_from AND @__st_to", ...st.between]
+    : "containedIn" in st
+    ? ["CONTAINED IN (@__st_from, @__st_to)", ...st.containedIn]
+    : ["FROM @__st_from TO @__st_to", st.from, st.to];
+  return {
+    clause: `FOR SYSTEM_TIME ${keyword}`,
+    params: { __st_from: instantParam(from), __st_to: instantParam(to) },
+  };
+}
+
+/** @internal Build the full temporal SELECT for a table's period columns. */
+export function buildTemporalQuery(
+  table: string,
+  period: PeriodColumns,
+  st: SystemTime,
+  opts?: TemporalQueryOptions,
+): { sql: string; params: Params } {
+  const { clause, params } = systemTimeClause(st);
+  const columns = opts?.columns?.length
+    ? opts.columns.map(quoteIdentifier).join(", ")
+    : "*";
+  let sql = `SELECT ${columns}, ${quoteIdentifier(period.start_column)} AS ${VALID_FROM}, ` +
+    `${quoteIdentifier(period.end_column)} AS ${VALID_TO} ` +
+    `FROM ${quoteObjectName(table)} ${clause}`;
+  if (opts?.where) sql += ` WHERE ${opts.where}`;
+  if (opts?.orderBy) sql += ` ORDER BY ${opts.orderBy}`;
+  return { sql, params: { ...opts?.params, ...params } };
+}
+
+/** Letters and digits only, lowercased, so cased row keys still match. */
+function foldName(name: string): string {
+  return name.replace(/[^\p{L}\p{N}]/gu, "").toLowerCase();
+}
+
+/**
+ * @internal Split the validity period off each row. The period columns are
+ * matched loosely since row keys follow the connection's `columnCase`.
+ */
+export function toTemporalRows<T>(
+  rows: Record<string, unknown>[],
+  period: PeriodColumns,
+): TemporalRow<T>[] {
+  const periodKeys = [period.start_column, period.end_column].map(foldName);
+  return rows.map((raw) => {
+    const { [VALID_FROM]: validFrom, [VALID_TO]: validTo, ...rest } = raw;
+    const row = Object.fromEntries(
+      Object.entries(rest).filter(([k]) => !periodKeys.includes(foldName(k))),
+    );
+    return { row: row as T, validFrom, validTo };
+  });
+}
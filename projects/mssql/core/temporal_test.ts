@@ -0,0 +1,78 @@
+import { assertEquals } from "jsr:@std/assert";
+import {
+  buildTemporalQuery,
+  systemTimeClause,
+  toTemporalRows,
+} from "./temporal.ts";
+
+const period = { start_column: "ValidFrom", end_column: "ValidTo" };
+
+Deno.test("systemTimeClause - AS OF binds one datetime2 param", () => {
+  const at = new Date("2026-01-01T00:00:00Z");
+  assertEquals(systemTimeClause({ asOf: at }), {
+    clause: "FOR SYSTEM_TIME AS OF @__st_from",
+    params: { __st_from: { value: at, type: "datetime2" } },
+  });
+});
+
+Deno.test("systemTimeClause - range forms", () => {
+  const a = "2026-01-01T00:00:00Z";
+  const b = "2026-02-01T00:00:00Z";
+  assertEquals(
+    systemTimeClause({ from: a, to: b }).clause,
+    "FOR SYSTEM_TIME FROM @__st_from TO @__st_to",
+  );
+  assertEquals(
+    systemTimeClause({ between: [a, b] }).clause,
+    "FOR SYSTEM_TIME BETWEEN @__st_from AND @__st_to",
+  );
+  const contained = systemTimeClause({ containedIn: [a, b] });
+  assertEquals(contained.clause, "FOR SYSTEM_TIME CONTAINED IN (@__st_from, @__st_to)");
+  assertEquals(contained.params, {
+    __st_from: { value: a, type: "datetime2" },
+    __st_to: { value: b, type: "datetime2" },
+  });
+  assertEquals(systemTimeClause("all"), { clause: "FOR SYSTEM_TIME ALL", params: {} });
+});
+
+Deno.test("buildTemporalQuery - quotes names and appends filters", () => {
+  const { sql, params } = buildTemporalQuery("dbo.Prices", period, "all", {
+    columns: ["Sku", "Amount"],
+    where: "Sku = @sku",
+    orderBy: "ValidFrom",
+    params: { sku: "A-1" },
+  });
+  assertEquals(
+    sql,
+    "SELECT [Sku], [Amount], [ValidFrom] AS __valid_from, [ValidTo] AS __valid_to " +
+      "FROM [dbo].[Prices] FOR SYSTEM_TIME ALL WHERE Sku = @sku ORDER BY ValidFrom",
+  );
+  assertEquals(params, { sku: "A-1" });
+});
+
+Deno.test("toTemporalRows - separates the validity period", () => {
+  const rows = toTemporalRows<{ Sku: string }>([{
+    Sku: "A-1",
+    ValidFrom: "2026-01-01T00:00:00",
+    ValidTo: "9999-12-31T23:59:59.9999999",
+    __valid_from: "2026-01-01T00:00:00",
+    __valid_to: "9999-12-31T23:59:59.9999999",
+  }], period);
+  assertEquals(rows, [{
+    row: { Sku: "A-1" },
+    validFrom: "2026-01-01T00:00:00",
+    validTo: "9999-12-31T23:59:59.9999999",
+  }]);
+});
+
+Deno.test("toTemporalRows - matches period columns in camelCase rows", () => {
+  const rows = toTemporalRows<{ sku: string }>([{
+    sku: "A-1",
+    validFrom: "2026-01-01T00:00:00",
+    validTo: "9999-12-31T23:59:59.9999999",
+    __valid_from: "2026-01-01T00:00:00",
+    __valid_to: "9999-12-31T23:59:59.9999999",
+  }], { start_column: "Valid_From", end_column: "Valid_To" });
+  assertEquals(rows[0].row, { sku: "A-1" });
+  assertEquals(rows[0].validTo, "9999-12-31T23:59:59.9999999");
+});
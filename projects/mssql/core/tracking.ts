@@ -0,0 +1,42 @@
+/**
+ * Change Tracking polling types.
+ * @module
+ */
+
+/** One changed row, as returned by `cn.changesSince()`. */
+export interface TrackedChange<K = Record<string, unknown>> {
+  /** Net effect of all changes to the row since the requested version. */
+  operation: "insert" | "update" | "delete";
+  /** Version of the row's last change. */
+  version: number;
+  /** Primary key columns of the changed row. */
+  key: K;
+}
+
+/** Result of a Change Tracking poll. */
+export interface ChangeTrackingResult<K = Record<string, unknown>> {
+  /** Pass as `sinceVersion` on the next poll. */
+  version: number;
+  /** Oldest version changes are still retained for. */
+  minValidVersion: number;
+  /**
+   * The requested version is older than `minValidVersion`: changes were
+   * cleaned up, so re-sync the whole table and continue from `version`.
+   */
+  resyncRequired: boolean;
+  changes: TrackedChange<K>[];
+}
+
+/** @internal */
+export function parseChangeTrackingResult<K>(
+  json: string,
+): ChangeTrackingResult<K> {
+  const result = JSON.parse(json);
+  if (result.__error) throw new Error(`Change tracking: ${result.__error}`);
+  return {
+    version: result.version,
+    minValidVersion: result.min_valid_version,
+    resyncRequired: result.resync_required,
+    changes: result.changes,
+  };
+}
@@ -20,6 +20,8 @@ import type { QueryStream } from "./stream.ts";
 export class Transaction implements Disposable, AsyncDisposable {
   readonly id: string;
   readonly isolation: IsolationLevel;
+  /** Begun with `readOnly`; writes are rejected unless `allowWrites` was set. */
+  readonly readOnly: boolean;
 
   #committed = false;
   #rolledBack = false;
@@ -32,9 +34,11 @@ export class Transaction implements Disposable, AsyncDisposable {
     isolation: IsolationLevel,
     commitFn: (txId: string) => Promise<void>,
     rollbackFn: (txId: string) => Promise<void>,
+    readOnly = false,
   ) {
     this.id = crypto.randomUUID();
     this.isolation = isolation;
+    this.readOnly = readOnly;
     this.#commitFn = commitFn;
     this.#rollbackFn = rollbackFn;
   }
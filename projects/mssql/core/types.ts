@@ -13,6 +13,9 @@ export type SqlType =
   | "float"
   | "real"
   | "decimal"
+  | "numeric"
+  | "money"
+  | "smallmoney"
   | "bit"
   | "varchar"
   | "nvarchar"
@@ -28,7 +31,12 @@ export type SqlType =
   | "uniqueidentifier"
   | "varbinary"
   | "xml"
-  | "json";
+  | "json"
+  | "geometry"
+  | "geography"
+  | "rowversion"
+  | "vector"
+  | "udt";
 
 export type IsolationLevel =
   | "READ_UNCOMMITTED"
@@ -41,6 +49,66 @@ export type CommandType = "text" | "stored_procedure";
 
 export type FilestreamMode = "read" | "write" | "readwrite";
 
+/**
+ * What a FILESTREAM opened for writing starts with: `"overwrite"` (default)
+ * starts empty, `"append"` keeps the existing data and writes after it.
+ */
+export type FilestreamWriteMode = "overwrite" | "append";
+
+/**
+ * How DECIMAL/NUMERIC/MONEY result values are returned: `"string"` keeps
+ * every digit (default), `"number"` converts to a JS number (may lose precision).
+ */
+export type DecimalMode = "string" | "number";
+
+/**
+ * How date/time result values are returned:
+ * - `"iso"` — strict ISO 8601 string, e.g. `"2024-06-15T10:30:45.1234567"` (default)
+ * - `"epoch"` — milliseconds since the Unix epoch (offset-less values read as UTC;
+ *   TIME values stay ISO strings)
+ * - `"tagged"` — `{ __type: "<column type>", value: "<iso>" }` for type-aware
+ *   reviving; `__type` is the SQL type (`"date"`, `"datetime"`, `"datetime2"`, ...)
+ */
+export type TemporalMode = "iso" | "epoch" | "tagged";
+
+/**
+ * How BIGINT result values are returned:
+ * - `"number-or-string"` — number inside ±2^53, string outside it (default)
+ * - `"always-string"` — always a decimal string
+ * - `"tagged-bigint"` — `{ __type: "bigint", value: "<digits>" }`
+ */
+export type BigIntMode = "number-or-string" | "always-string" | "tagged-bigint";
+
+/**
+ * How result column names are written as row keys:
+ * - `"as-is"` — the name the server sent (default)
+ * - `"camelCase"` — `Order_ID` and `order id` become `orderId`
+ * - `"lowercase"` — `OrderID` becomes `orderid`
+ *
+ * Names starting with `__` are the driver's own and are never changed.
+ */
+export type ColumnCase = "as-is" | "camelCase" | "lowercase";
+
+/**
+ * What to do with varchar bytes that are neither UTF-8 nor valid in `varcharEncoding`:
+ * - `"error"` — fail the query
+ * - `"lossy"` — substitute U+FFFD replacement characters (default)
+ * - `"latin1"` — map each byte to the same code point (ISO-8859-1)
+ */
+export type VarcharDecodePolicy = "error" | "lossy" | "latin1";
+
+/**
+ * Lowest TLS protocol version accepted for encrypted connections.
+ * TLS 1.0 and 1.1 are never negotiated.
+ */
+export type TlsVersion = "1.2" | "1.3";
+
+/**
+ * Workload type declared at login. `"ReadOnly"` lets an availability group
+ * listener route the connection to a readable secondary.
+ */
+export type ApplicationIntent = "ReadWrite" | "ReadOnly";
+
 /**
  * Common UTF-8 collations available in SQL Server 2019+.
  */
@@ -59,13 +127,26 @@ export type ParamValue =
   | Date
   | null
   | undefined
-  | Uint8Array;
+  | Uint8Array
+  | number[]
+  | Float32Array;
 
 export interface TypedParam {
   value: ParamValue;
   type: SqlType;
   /** Set to `true` for OUTPUT parameters (stored procedures). */
   output?: boolean;
+  /** Total digits for `decimal`/`numeric` values; overflow is rejected. */
+  precision?: number;
+  /** Fractional digits for `decimal`/`numeric` values; the value is rounded to fit. */
+  scale?: number;
+  /** Spatial reference ID for `geometry`/`geography` WKT values (default 0 / 4326). */
+  srid?: number;
+  /**
+   * Declared length for `varchar`/`nvarchar`/`char`/`nchar`/`varbinary`
+   * values (-1 for MAX). Longer values are rejected.
+   */
+  length?: number;
 }
 
 export type Params = Record<string, ParamValue | TypedParam>;
@@ -77,11 +158,198 @@ export interface CommandOptions {
   commandTimeout?: number;
   commandType?: CommandType;
   signal?: AbortSignal;
+  /** Override the connection's `temporalMode` for this command. */
+  temporalMode?: TemporalMode;
+  /** Override the connection's `columnCase` for this command. */
+  columnCase?: ColumnCase;
+  /**
+   * Run the command under `SET NOCOUNT ON` (or `OFF`), restoring the
+   * session's setting afterwards. Rejected for statements that must start
+   * a batch, such as `CREATE PROCEDURE`.
+   */
+  nocount?: boolean;
+  /** How `rowsAffected` is computed; see {@link RowCountMode}. */
+  rowCount?: RowCountMode;
+}
+
+/**
+ * How `rowsAffected` is computed for a multi-statement command:
+ * - `"total"` — every count the server reports, including rows touched by
+ *   triggers. `execute()` only; always 0 under `nocount: true`.
+ * - `"last"` — `@@ROWCOUNT` after the last statement.
+ *
+ * Defaults to `"total"` for `execute()` (`"last"` with `nocount: true`);
+ * `executeReturning()` and `exec()` always report `"last"`.
+ */
+export type RowCountMode = "total" | "last";
+
+export interface InsertOptions extends CommandOptions {
+  /**
+   * Key column whose inserted values are returned, for every row the INSERT
+   * adds. Without it, `SCOPE_IDENTITY()` returns the last identity value.
+   */
+  key?: string;
+}
+
+/**
+ * Which execution plan `queryWithPlan()` captures: `"estimated"` compiles
+ * the query without running it (`SET SHOWPLAN_XML`), `"actual"` runs it and
+ * returns the runtime plan (`SET STATISTICS XML`).
+ */
+export type PlanCapture = "estimated" | "actual";
+
+export interface QueryPlanOptions extends CommandOptions {
+  /** Default: `"actual"`. */
+  capturePlan?: PlanCapture;
+}
+
+/**
+ * Progress from `runScript()`. Batches are numbered from 0 in script order.
+ * `dropped` reports events lost because nothing polled the queue in time.
+ */
+export type ScriptProgressEvent =
+  | {
+    type: "message";
+    batch: number;
+    /** Error number; 0 for PRINT, 50000 for an ad hoc RAISERROR. */
+    number: number;
+    /** Severity. */
+    class: number;
+    message: string;
+  }
+  | { type: "batch"; batch: number; rowsAffected: number; durationMs: number }
+  | { type: "dropped"; count: number };
+
+export interface ScriptOptions extends CommandOptions {
+  /** Called with each progress event while the script runs. */
+  onProgress?: (event: ScriptProgressEvent) => void;
+  /** How often progress is polled. Default: 250. */
+  pollIntervalMs?: number;
+  /**
+   * Keep running later batches after one fails, and report the failures in
+   * `results` instead of throwing. Default: false.
+   */
+  continueOnError?: boolean;
 }
 
 // ── Stream Options ──────────────────────────────────────────
 
 export interface StreamOptions extends CommandOptions {
+  /**
+   * Return binary columns as `Uint8Array` copied straight from native memory,
+   * skipping the base64 encode/decode used by default.
+   */
+  rawBinary?: boolean;
+}
+
+// ── Transaction Options ─────────────────────────────────────
+
+export interface TransactionOptions {
+  /**
+   * Begin a read-only snapshot transaction: every read sees the database as
+   * of its first statement. The isolation level must be `"SNAPSHOT"`, and
+   * the database must allow snapshot isolation. Default: false.
+   */
+  readOnly?: boolean;
+  /**
+   * Let write statements through in a read-only transaction instead of
+   * rejecting them before they are sent. Default: false.
+   */
+  allowWrites?: boolean;
+  /**
+   * Issue `SET XACT_ABORT ON` (or `OFF`) with the begin. With it on, any
+   * runtime error rolls back the whole transaction instead of only the
+   * failing statement. Default: the `xactAbort` config option.
+   */
+  xactAbort?: boolean;
+}
+
+// ── Result Types ────────────────────────────────────────────
+
+/**
+ * Result of `executeReturning()` — the affected count plus any rows
+ * produced by an `OUTPUT` clause (e.g. `OUTPUT INSERTED.*`).
+ */
+export interface ExecuteResult<T = Record<string, unknown>> {
+  rowsAffected: number;
+  rows: T[];
+}
+
+/** Result of `insert()`: the affected count and the generated keys. */
+export interface InsertResult<K = number> {
+  rowsAffected: number;
+  insertedIds: K[];
+}
+
+/**
+ * Result of `queryWithPlan()` — the query's rows (none for an estimated
+ * plan) and the showplan XML, one document per statement.
+ */
+export interface QueryPlanResult<T = Record<string, unknown>> {
+  rows: T[];
+  plans: string[];
+  /** Set when unnamed or duplicate columns were given generated keys. */
+  renamedColumns?: boolean;
+}
+
+/** The page `queryPage()` reads. */
+export interface PageRequest {
+  /** Rows to skip. Default: 0. */
+  offset?: number;
+  /** Rows per page, at least 1. */
+  limit: number;
+  /** Count the rows of the whole result too, with `COUNT(*) OVER()`. */
+  includeTotal?: boolean;
+}
+
+/** Result of `queryPage()`. */
+export interface PageResult<T = Record<string, unknown>> {
+  rows: T[];
+  /**
+   * Rows in the whole result, when `includeTotal` is set; null otherwise,
+   * and for a page past the last row.
+   */
+  total: number | null;
+  /** Whether there are rows after this page. */
+  hasMore: boolean;
+}
+
+/** Result of `transactionState()`. */
+export interface TransactionState {
+  /**
+   * `XACT_STATE()`: 1 when a transaction is open, 0 when none is, -1 when
+   * an error has doomed it and it can only be rolled back.
+   */
+  xactState: -1 | 0 | 1;
+  /** `@@TRANCOUNT`: nested `BEGIN TRANSACTION`s not yet ended. */
+  tranCount: number;
+  /** Id of the `Transaction` the driver has open, if any. */
+  transactionId: string | null;
+  /** Whether writes are being rejected by a read-only transaction. */
+  readOnly: boolean;
+}
+
+/** Outcome of one batch of a `runScript()` script. */
+export interface ScriptBatchResult {
+  /** Index of the batch, from 0. */
+  batch: number;
+  /** Line of the script the batch starts on, from 1. */
+  line: number;
+  /** Times the batch ran; less than the `GO n` count if it failed. */
+  runs: number;
+  rowsAffected: number;
+  durationMs: number;
+  error: string | null;
+}
+
+/** Result of `runScript()`. */
+export interface ScriptResult {
+  /** Batches in the script (`GO` separators split it). */
+  batches: number;
+  /** Rows affected across all batches. */
+  rowsAffected: number;
+  /** One entry per batch run, in script order. */
+  results: ScriptBatchResult[];
 }
 
 // ── Serialized Types (JSON across FFI boundary) ─────────────
@@ -92,6 +360,27 @@ export interface SerializedCommand {
   transaction_id: string | null;
   command_timeout_ms: number | null;
   command_type: string;
+  /** Non-query calls also return rows produced by an OUTPUT clause. */
+  return_rows?: boolean;
+  temporal_mode?: TemporalMode;
+  column_case?: ColumnCase;
+  raw_binary?: boolean;
+  /** Query calls return `{ rows, plans }` with showplan XML. */
+  capture_plan?: PlanCapture;
+  /** Query calls return `{ rows, statistics }` from `SET STATISTICS IO, TIME`. */
+  capture_statistics?: boolean;
+  /** Query calls return the reassembled `FOR JSON` document. */
+  for_json?: boolean;
+  /** Query calls compress results over `compress_threshold` bytes. */
+  compress?: "lz4" | "zstd";
+  compress_threshold?: number;
+  /** Query calls return `{ rows, total, hasMore }` for one page. */
+  paginate?: { offset: number; limit: number; include_total: boolean };
+  nocount?: boolean;
+  rowcount?: RowCountMode;
+  /** Non-query calls return `insertedIds`. */
+  return_identity?: boolean;
+  identity_key?: string;
 }
 
 export interface SerializedParam {
@@ -99,6 +388,10 @@ export interface SerializedParam {
   value: unknown;
   type: string | null;
   output?: boolean;
+  precision?: number;
+  scale?: number;
+  srid?: number;
+  length?: number;
 }
 
 // ── Config Types ────────────────────────────────────────────
@@ -112,10 +405,13 @@ export interface MssqlConfig {
       | "default"
       | "ntlm"
       | "windows"
+      | "kerberos"
       | "azure-active-directory-password"
       | "azure-active-directory-access-token"
       | "azure-active-directory-default"
-      | "azure-active-directory-service-principal-secret";
+      | "azure-active-directory-service-principal-secret"
+      | "azure-active-directory-msi-vm"
+      | "azure-active-directory-msi-app-service";
     options?: {
       userName?: string;
       password?: string;
@@ -124,6 +420,8 @@ export interface MssqlConfig {
       clientId?: string;
       clientSecret?: string;
       tenantId?: string;
+      /** Kerberos service principal name. Default: `MSSQLSvc/<server>:<port>`. */
+      spn?: string;
     };
   };
   /** Async function that returns an Azure AD access token. Used with azure-active-directory-default and service-principal-secret types. */
@@ -134,8 +432,58 @@ export interface MssqlConfig {
     connectTimeout?: number;
     requestTimeout?: number;
     appName?: string;
+    /**
+     * Name of the subsystem that owns the pool or connection, shown in
+     * diagnostics, the slow query log and error messages. Not part of a
+     * pool's identity: a pool shared by configs that differ only in label
+     * keeps the label it was created with.
+     */
+    label?: string;
     instanceName?: string;
     packetSize?: number;
+    /** How DECIMAL/NUMERIC/MONEY results are returned. Default: `"string"`. */
+    decimalMode?: DecimalMode;
+    /** How date/time results are returned. Default: `"iso"`. */
+    temporalMode?: TemporalMode;
+    /** How BIGINT results are returned. Default: `"number-or-string"`. */
+    bigintMode?: BigIntMode;
+    /** Parse native `json` columns into objects instead of strings. Default: `false`. */
+    parseJson?: boolean;
+    /** How result column names are cased. Default: `"as-is"`. */
+    columnCase?: ColumnCase;
+    /** Fallback encoding for non-UTF-8 varchar data, e.g. `"windows-1252"`. */
+    varcharEncoding?: string;
+    /** Policy for undecodable varchar bytes. Default: `"lossy"`. */
+    varcharDecode?: VarcharDecodePolicy;
+    /** Lowest TLS version to accept. Default: `"1.2"`. */
+    tlsMinVersion?: TlsVersion;
+    /** Allowed cipher suites by IANA name, e.g. `"TLS13_AES_256_GCM_SHA384"`. Default: all. */
+    tlsCipherSuites?: string[];
+    /**
+     * PEM (or DER) file of CA certificates to trust for server validation.
+     * Setting a CA turns `trustServerCertificate` off unless it is set explicitly.
+     */
+    caCertPath?: string;
+    /** Inline PEM CA certificates to trust for server validation. */
+    caCertPem?: string;
+    /** Mirroring partner (`host`, `host,port` or `host\instance`) tried when the server is unreachable. */
+    failoverPartner?: string;
+    /** Race connections to every address of the server, for multi-subnet AG listeners. Default: `false`. */
+    multiSubnetFailover?: boolean;
+    /** Declared workload; `"ReadOnly"` is routed to AG read replicas. Default: `"ReadWrite"`. */
+    applicationIntent?: ApplicationIntent;
+    /** TCP keepalive idle time in ms, so idle connections survive firewalls; `0` disables. Default: `30000`. */
+    tcpKeepAlive?: number;
+    /** Disable Nagle's algorithm on the socket. Default: `true`. */
+    tcpNoDelay?: boolean;
+    /** Retries after a transient connect failure. Default: `1`. */
+    connectRetryCount?: number;
+    /** Delay in ms before the first connect retry, doubled for each later one. Default: `10000`. */
+    connectRetryInterval?: number;
+    /** Record commands slower than this many ms in the slow query log. Default: off. */
+    slowQueryMs?: number;
+    /** Begin transactions with `SET XACT_ABORT ON`, so any runtime error rolls them back. Default: `false`. */
+    xactAbort?: boolean;
   };
   pool?: {
     min?: number;
@@ -152,8 +500,16 @@ export interface NormalizedConfig {
     | { type: "sql"; username: string; password: string }
     | { type: "ntlm"; username: string; password: string; domain: string }
     | { type: "windows" }
+    | { type: "kerberos"; spn: string | null }
     | { type: "azure_ad"; username: string; password: string }
-    | { type: "azure_ad_token"; token: string };
+    | { type: "azure_ad_token"; token: string }
+    | { type: "azure_ad_msi"; client_id: string | null }
+    | {
+      type: "azure_ad_sp";
+      tenant_id: string;
+      client_id: string;
+      client_secret: string;
+    };
   encrypt: boolean;
   trust_server_certificate: boolean;
   connect_timeout_ms: number;
@@ -162,10 +518,40 @@ export interface NormalizedConfig {
   instance_name: string | null;
   packet_size: number;
   pool: { min?: number; max?: number; idle_timeout_ms?: number } | null;
+  decimal_mode: DecimalMode;
+  temporal_mode: TemporalMode;
+  bigint_mode: BigIntMode;
+  parse_json: boolean;
+  column_case: ColumnCase;
+  varchar_encoding: string | null;
+  varchar_decode: VarcharDecodePolicy;
+  tls_min_version: TlsVersion;
+  tls_cipher_suites: string[] | null;
+  ca_cert_path: string | null;
+  ca_cert_pem: string | null;
+  failover_partner: string | null;
+  multi_subnet_failover: boolean;
+  application_intent: "read_write" | "read_only";
+  tcp_keepalive_ms: number;
+  tcp_nodelay: boolean;
+  connect_retry_count: number;
+  connect_retry_interval_ms: number;
+  slow_query_ms: number | null;
+  xact_abort: boolean;
+  label: string | null;
   /** Async function that returns an Azure AD access token. Resolved by entry points before FFI serialization. */
   token_provider?: () => Promise<string>;
 }
 
+/**
+ * Configuration a pool was created with, as the driver holds it: passwords,
+ * tokens and client secrets read `"***"`, and `pool` has the sizes and idle
+ * timeout in effect, defaults included.
+ */
+export type EffectiveConfig =
+  & Omit<NormalizedConfig, "pool" | "token_provider">
+  & { pool: { min: number; max: number; idle_timeout_ms: number } };
+
 // ── Bulk Insert Types ───────────────────────────────────────
 
 export interface BulkColumn {
@@ -180,21 +566,63 @@ export interface BulkColumn {
 
 // ── Diagnostics ─────────────────────────────────────────────
 
+/** Command counters for a pool or connection. */
+export interface DiagnosticMetrics {
+  /** Queries, executes, execs, streams and bulk inserts run. */
+  queries: number;
+  errors: number;
+  /** Rows returned by queries and streams. */
+  rows: number;
+  /** Bytes of result JSON serialized. */
+  bytes: number;
+  /** Latency percentiles over the most recent 1024 commands. */
+  latency_ms: { p50: number; p95: number; p99: number; max: number };
+}
+
+/** A recent command on a connection, newest last. */
+export interface DiagnosticStatement {
+  kind: "query" | "execute" | "exec" | "script" | "stream" | "bulk";
+  /** First 120 characters of the SQL, with literals masked. */
+  sql: string;
+  /** Hash of the full SQL text, for telling similar prefixes apart. */
+  sql_hash: string;
+  /** Unix time in ms when the command started. */
+  started_at_ms: number;
+  /** Time taken, or time so far while `running`. */
+  duration_ms: number;
+  status: "running" | "ok" | "error";
+  rows: number | null;
+  error: string | null;
+}
+
 /** Pool status snapshot from the Rust driver. */
 export interface DiagnosticPool {
   id: number;
+  /** The config's `label`. */
+  label: string | null;
   total: number;
   idle: number;
   in_use: number;
   max: number;
+  /** Totals across every connection acquired from the pool. */
+  metrics: DiagnosticMetrics;
 }
 
 /** Connection status snapshot from the Rust driver. */
 export interface DiagnosticConnection {
   id: number;
   pool_id: number | null;
+  /** The config's `label` (the pool's, for pooled connections). */
+  label: string | null;
+  /** Tag given to `pool.connect()` or `cn.setLabel()`. */
+  tag: string | null;
   is_pooled: boolean;
   has_active_transaction: boolean;
+  /** Bytes held for a compressed or buffered result not yet read. */
+  buffered_bytes: number;
+  metrics: DiagnosticMetrics;
+  /** The last 16 commands, plus the one in flight. */
+  recent_statements: DiagnosticStatement[];
 }
 
 /**
@@ -204,4 +632,189 @@ export interface DiagnosticConnection {
 export interface DiagnosticInfo {
   pools: DiagnosticPool[];
   connections: DiagnosticConnection[];
+  /** Open streams. */
+  cursors: DiagnosticCursor[];
+  memory: DiagnosticMemory;
+  concurrency: DiagnosticConcurrency;
+  /** Null until the first call that needs the runtime. */
+  runtime: DiagnosticRuntime | null;
+}
+
+/** Async runtime the driver runs commands on. */
+export interface DiagnosticRuntime {
+  /** Worker threads. */
+  workers: number;
+  /** Calls blocked waiting for their command to finish on the runtime. */
+  blocked_callers: number;
+  alive_tasks: number;
+  /** Tasks waiting for a free worker. */
+  queued_tasks: number;
+  /** Tasks spawned since start; null unless built with `tokio_unstable`. */
+  spawned_tasks: number | null;
+  /** Time workers have spent busy, summed over workers. */
+  busy_ms: number;
+}
+
+/** Open stream snapshot from the Rust driver. */
+export interface DiagnosticCursor {
+  id: number;
+  conn_id: number | null;
+  /** Rows fetched from the server but not yet read. */
+  rows_left: number;
+  /** Approximate bytes held by those rows. */
+  buffered_bytes: number;
+}
+
+/** Concurrency limit and its queue; see `mssql.setConcurrencyLimit()`. */
+export interface DiagnosticConcurrency {
+  limit: number | null;
+  /** Queries, execs and bulk inserts running now. */
+  running: number;
+  /** Calls waiting for a slot. */
+  queued: number;
+  /** Calls that have had to wait, since the driver loaded. */
+  waited: number;
+  wait_ms_total: number;
+  wait_ms_max: number;
+}
+
+/** Memory the driver holds for results, across streams and connections. */
+export interface DiagnosticMemory {
+  /** Approximate total; see `mssql.setMemoryLimit()`. */
+  buffered_bytes: number;
+  soft_cap_bytes: number | null;
+}
+
+/** A crate the native driver is built on, as locked when it was built. */
+export interface VersionInfoDependency {
+  version: string;
+  /** Git revision, for crates built from git. */
+  revision: string | null;
+}
+
+/** Build and capability report from `mssql.versionInfo()`. */
+export interface VersionInfo {
+  /** Version of the native driver library. */
+  version: string;
+  mssql_client: VersionInfoDependency;
+  mssql_driver_pool: VersionInfoDependency;
+  target: { os: string; arch: string; debug: boolean };
+  features: {
+    /**
+     * FILESTREAM access: opened by path on Windows builds, emulated with
+     * chunked statements elsewhere.
+     */
+    filestream: "native" | "emulated";
+    tls: "rustls";
+    /** Integrated auth: SSPI on Windows, Kerberos elsewhere. */
+    integrated_auth: "sspi" | "kerberos";
+    /** Result compression codecs built in (cargo features `lz4`, `zstd`). */
+    compression: ("lz4" | "zstd")[];
+    /** Whether OTLP export is built in (cargo feature `otel`). */
+    opentelemetry: boolean;
+  };
+  /** TDS protocol versions the driver can negotiate, e.g. `"7.4"`. */
+  tds_versions: string[];
+}
+
+/** Severity of a driver log record. */
+export type LogLevel = "error" | "warn" | "info" | "debug" | "trace";
+
+/** Driver subsystem a log record belongs to. */
+export type LogCategory = "pool" | "query" | "stream" | "filestream";
+
+/** Log filter for `mssql.configureLogging()`. Levels include everything more severe. */
+export interface LoggingOptions {
+  /** Level for categories not listed in `categories` (default `"error"`). */
+  level?: LogLevel | "off";
+  /** Per-category overrides, e.g. `{ pool: "trace" }`. */
+  categories?: Partial<Record<LogCategory, LogLevel | "off">>;
+  /**
+   * Write records to this file instead of stderr (a `setLogger()` callback
+   * still takes precedence). Omit to keep the current file; `""` stops
+   * file output.
+   */
+  file?: string;
+  /** Rotate once the file reaches this size (default 10 MiB). */
+  maxFileBytes?: number;
+  /** Rotated files kept as `<file>.1` … `<file>.N` (default 5). */
+  maxFiles?: number;
+  /**
+   * Mask SQL literals and credential values in log output, slow query
+   * entries and spans (default `true`). Turn off only for local debugging.
+   */
+  redact?: boolean;
+}
+
+/** A driver log record delivered to `mssql.setLogger()`. */
+export interface LogRecord {
+  level: LogLevel;
+  /** Rust module that produced the record, e.g. `mssqlts::pool`. */
+  target: string;
+  message: string;
+  timestamp: Date;
+}
+
+/** OTLP/HTTP exporter settings for `mssql.configureTracing()`. */
+export interface TracingOptions {
+  /**
+   * Full traces URL, e.g. `http://collector:4318/v1/traces`. Defaults to the
+   * `OTEL_EXPORTER_OTLP_*` environment variables, then localhost:4318.
+   */
+  endpoint?: string;
+  /** Extra HTTP headers (API keys, tenant ids). */
+  headers?: Record<string, string>;
+  /** `service.name` resource attribute (default `"mssqlts"`). */
+  serviceName?: string;
+  /** Export request timeout in milliseconds. */
+  timeoutMs?: number;
+}
+
+/** A command recorded by the slow query log (see `slowQueryMs`). */
+export interface SlowQuery {
+  /** `"query"`, `"execute"`, `"exec"`, `"script"` or `"bulk"`. */
+  kind: string;
+  /**
+   * First 200 characters of the SQL with literals masked (`INSERT BULK
+   * <table>` for bulk loads).
+   */
+  sql: string;
+  duration_ms: number;
+  /** Rows returned or affected; null when the command failed. */
+  rows: number | null;
+  conn_id: number;
+  pool_id: number | null;
+  /** The config's `label`. */
+  label: string | null;
+  /** The connection's tag. */
+  tag: string | null;
+  /** Unix time in ms when the command finished. */
+  finished_at_ms: number;
+}
+
+/** Result of `mssql.closeAllGraceful()`. */
+export interface CloseSummary {
+  /** Operations still running when the timeout expired. */
+  in_flight: number;
+  pools: number;
+  connections: number;
+  cursors: number;
+  blobs: number;
+  filestreams: number;
+  subscriptions: number;
+}
+
+/** A handle reported by `mssql.leakReport()`. */
+export interface LeakedHandle {
+  kind: "connection" | "cursor" | "blob" | "filestream";
+  id: number;
+  age_ms: number;
+  /** Unix time in ms when the handle was opened. */
+  created_at_ms: number;
+  /** Set with `cn.setLabel()`; inherited by handles opened on the connection. */
+  label: string | null;
+  /** Connection a cursor or blob was opened on. */
+  conn_id: number | null;
+  /** Owning pool of a pooled connection. */
+  pool_id: number | null;
 }
@@ -8,7 +8,7 @@
  * @module
  */
 
-import type { RuntimeFFI } from "../core/runtime.ts";
+import type { NativeLogCallback, RuntimeFFI } from "../core/runtime.ts";
 
 // deno-lint-ignore no-explicit-any
 type BunFFILib = any;
@@ -29,7 +29,7 @@ function toCString(s: string): Uint8Array {
 export async function createFFI(libPath: string): Promise<RuntimeFFI> {
   // Dynamic import to avoid Deno/Node type errors
   const bunFFI = await import("bun:ffi");
-  const { dlopen, FFIType, CString, ptr } = bunFFI;
+  const { dlopen, FFIType, CString, JSCallback, ptr } = bunFFI;
 
   const lib: BunFFILib = dlopen(libPath, {
     mssql_pool_create: { args: [FFIType.ptr], returns: FFIType.u64 },
@@ -39,19 +39,55 @@ export async function createFFI(libPath: string): Promise<RuntimeFFI> {
       returns: FFIType.void,
     },
     mssql_pool_close: { args: [FFIType.u64], returns: FFIType.void },
+    mssql_pool_config: { args: [FFIType.u64], returns: FFIType.ptr },
     mssql_connect: { args: [FFIType.ptr], returns: FFIType.u64 },
     mssql_disconnect: { args: [FFIType.u64], returns: FFIType.void },
+    mssql_set_label: {
+      args: [FFIType.u64, FFIType.ptr],
+      returns: FFIType.void,
+    },
     mssql_query: { args: [FFIType.u64, FFIType.ptr], returns: FFIType.ptr },
     mssql_execute_nonquery: {
       args: [FFIType.u64, FFIType.ptr],
       returns: FFIType.ptr,
     },
     mssql_exec: { args: [FFIType.u64, FFIType.ptr], returns: FFIType.ptr },
+    mssql_take_result: {
+      args: [FFIType.u64, FFIType.ptr, FFIType.u64],
+      returns: FFIType.i64,
+    },
+    mssql_decompress: {
+      args: [FFIType.ptr, FFIType.ptr, FFIType.u64, FFIType.ptr, FFIType.u64],
+      returns: FFIType.i64,
+    },
+    mssql_query_buffered: {
+      args: [FFIType.u64, FFIType.ptr],
+      returns: FFIType.ptr,
+    },
+    mssql_read_rows: {
+      args: [
+        FFIType.u64,
+        FFIType.ptr,
+        FFIType.u64,
+        FFIType.ptr,
+        FFIType.u64,
+      ],
+      returns: FFIType.i64,
+    },
+    mssql_exec_script: {
+      args: [FFIType.u64, FFIType.ptr],
+      returns: FFIType.ptr,
+    },
+    mssql_progress_poll: { args: [FFIType.u64], returns: FFIType.ptr },
     mssql_query_stream: {
       args: [FFIType.u64, FFIType.ptr],
       returns: FFIType.u64,
     },
     mssql_stream_next: { args: [FFIType.u64], returns: FFIType.ptr },
+    mssql_stream_read_binary: {
+      args: [FFIType.u64, FFIType.ptr, FFIType.ptr, FFIType.u64],
+      returns: FFIType.i64,
+    },
     mssql_stream_close: { args: [FFIType.u64], returns: FFIType.void },
     mssql_bulk_insert: {
       args: [FFIType.u64, FFIType.ptr],
@@ -63,30 +99,131 @@ export async function createFFI(libPath: string): Promise<RuntimeFFI> {
     },
     mssql_commit: { args: [FFIType.u64, FFIType.ptr], returns: FFIType.ptr },
     mssql_rollback: { args: [FFIType.u64, FFIType.ptr], returns: FFIType.ptr },
+    mssql_transaction_state: { args: [FFIType.u64], returns: FFIType.ptr },
     mssql_cancel: { args: [FFIType.u64], returns: FFIType.void },
+    mssql_ae_configure: {
+      args: [FFIType.u64, FFIType.ptr],
+      returns: FFIType.u32,
+    },
     mssql_last_error: { args: [FFIType.u64], returns: FFIType.ptr },
     mssql_free_string: { args: [FFIType.ptr], returns: FFIType.void },
     mssql_filestream_available: { args: [], returns: FFIType.u32 },
     mssql_filestream_open: { args: [FFIType.ptr], returns: FFIType.u64 },
+    mssql_filestream_open_for: {
+      args: [FFIType.u64, FFIType.ptr],
+      returns: FFIType.u64,
+    },
     mssql_filestream_read: {
       args: [FFIType.u64, FFIType.u64],
       returns: FFIType.ptr,
     },
+    mssql_filestream_read_into: {
+      args: [FFIType.u64, FFIType.ptr, FFIType.u64],
+      returns: FFIType.i64,
+    },
     mssql_filestream_write: {
       args: [FFIType.u64, FFIType.ptr],
       returns: FFIType.u64,
     },
+    mssql_filestream_set_length: {
+      args: [FFIType.u64, FFIType.u64],
+      returns: FFIType.ptr,
+    },
+    mssql_filestream_finish: { args: [FFIType.u64], returns: FFIType.ptr },
     mssql_filestream_close: { args: [FFIType.u64], returns: FFIType.void },
 
+    // Blob
+    mssql_blob_open: { args: [FFIType.u64, FFIType.ptr], returns: FFIType.u64 },
+    mssql_blob_length: { args: [FFIType.u64], returns: FFIType.i64 },
+    mssql_blob_read: {
+      args: [FFIType.u64, FFIType.u64, FFIType.u64],
+      returns: FFIType.ptr,
+    },
+    mssql_blob_close: { args: [FFIType.u64], returns: FFIType.void },
+
+    // Query notifications
+    mssql_notify_subscribe: {
+      args: [FFIType.u64, FFIType.ptr],
+      returns: FFIType.u64,
+    },
+    mssql_notify_receive: {
+      args: [FFIType.u64, FFIType.u64],
+      returns: FFIType.ptr,
+    },
+    mssql_notify_close: { args: [FFIType.u64], returns: FFIType.void },
+
+    // Service Broker
+    mssql_broker_receive: {
+      args: [FFIType.u64, FFIType.ptr, FFIType.u64],
+      returns: FFIType.ptr,
+    },
+    mssql_broker_send: {
+      args: [FFIType.u64, FFIType.ptr],
+      returns: FFIType.ptr,
+    },
+    mssql_broker_end: {
+      args: [FFIType.u64, FFIType.ptr],
+      returns: FFIType.ptr,
+    },
+
+    // Change Tracking
+    mssql_ct_changes: {
+      args: [FFIType.u64, FFIType.ptr, FFIType.i64],
+      returns: FFIType.ptr,
+    },
+
+    // Change Data Capture
+    mssql_cdc_changes: {
+      args: [FFIType.u64, FFIType.ptr],
+      returns: FFIType.ptr,
+    },
+
+    // Catalog
+    mssql_table_schema: {
+      args: [FFIType.u64, FFIType.ptr],
+      returns: FFIType.ptr,
+    },
+    mssql_list_objects: {
+      args: [FFIType.u64, FFIType.ptr],
+      returns: FFIType.ptr,
+    },
+    mssql_list_databases: {
+      args: [FFIType.u64],
+      returns: FFIType.ptr,
+    },
+    mssql_list_schemas: {
+      args: [FFIType.u64, FFIType.ptr],
+      returns: FFIType.ptr,
+    },
+
     // Diagnostics / Debug
+    mssql_version_info: { args: [], returns: FFIType.ptr },
     mssql_diagnostic_info: { args: [], returns: FFIType.ptr },
+    mssql_slow_queries: { args: [], returns: FFIType.ptr },
+    mssql_leak_report: { args: [FFIType.u64], returns: FFIType.ptr },
+    mssql_metrics_prometheus: { args: [], returns: FFIType.ptr },
     mssql_set_debug: { args: [FFIType.u32], returns: FFIType.void },
+    mssql_set_memory_cap: { args: [FFIType.u64], returns: FFIType.void },
+    mssql_set_concurrency_limit: {
+      args: [FFIType.u32],
+      returns: FFIType.void,
+    },
+    mssql_set_debug_config: { args: [FFIType.ptr], returns: FFIType.ptr },
+    mssql_set_log_callback: { args: [FFIType.ptr], returns: FFIType.void },
+
+    // Tracing
+    mssql_configure_tracing: { args: [FFIType.ptr], returns: FFIType.ptr },
 
     // Cleanup
     mssql_close_all: { args: [], returns: FFIType.void },
+    mssql_close_all_graceful: { args: [FFIType.u64], returns: FFIType.ptr },
   });
 
   const sym = lib.symbols;
+  // Replaced log callbacks stay open: a record may still be in flight on a
+  // driver thread when the pointer is swapped.
+  // deno-lint-ignore no-explicit-any
+  const logCallbacks: any[] = [];
 
   /** Read a C string from a pointer, then free it. Returns null for null pointers. */
   function readAndFree(rawPtr: number | bigint | null): string | null {
@@ -114,6 +251,10 @@ export async function createFFI(libPath: string): Promise<RuntimeFFI> {
       sym.mssql_pool_close(poolId);
     },
 
+    poolConfig(poolId: bigint): string | null {
+      return readAndFree(sym.mssql_pool_config(poolId));
+    },
+
     async connect(configJson: string): Promise<bigint> {
       const buf = toCString(configJson);
       return BigInt(sym.mssql_connect(ptr(buf)));
@@ -123,6 +264,11 @@ export async function createFFI(libPath: string): Promise<RuntimeFFI> {
       sym.mssql_disconnect(connId);
     },
 
+    setLabel(connId: bigint, label: string): void {
+      const buf = toCString(label);
+      sym.mssql_set_label(connId, ptr(buf));
+    },
+
     async query(connId: bigint, cmdJson: string): Promise<string | null> {
       const buf = toCString(cmdJson);
       const result = sym.mssql_query(connId, ptr(buf));
@@ -144,6 +290,69 @@ export async function createFFI(libPath: string): Promise<RuntimeFFI> {
       return readAndFree(result);
     },
 
+    takeResult(connId: bigint, length: number): Uint8Array | null {
+      const out = new Uint8Array(length);
+      const n = Number(
+        sym.mssql_take_result(connId, ptr(out), BigInt(length)),
+      );
+      return n < 0 ? null : out.subarray(0, n);
+    },
+
+    decompress(
+      codec: string,
+      data: Uint8Array,
+      length: number,
+    ): Uint8Array | null {
+      const name = toCString(codec);
+      const out = new Uint8Array(length);
+      const n = Number(
+        sym.mssql_decompress(
+          ptr(name),
+          ptr(data),
+          BigInt(data.length),
+          ptr(out),
+          BigInt(length),
+        ),
+      );
+      return n < 0 ? null : out;
+    },
+
+    async queryBuffered(
+      connId: bigint,
+      cmdJson: string,
+    ): Promise<string | null> {
+      const buf = toCString(cmdJson);
+      const result = sym.mssql_query_buffered(connId, ptr(buf));
+      return readAndFree(result);
+    },
+
+    readRows(connId: bigint, data: Uint8Array, offsets: Uint32Array): number {
+      // ptr() rejects empty buffers; an empty result has nothing to copy
+      const dataPtr = data.length === 0 ? null : ptr(data);
+      return Number(
+        sym.mssql_read_rows(
+          connId,
+          dataPtr,
+          BigInt(data.length),
+          ptr(offsets),
+          BigInt(offsets.length),
+        ),
+      );
+    },
+
+    async execScript(
+      connId: bigint,
+      scriptJson: string,
+    ): Promise<string | null> {
+      const buf = toCString(scriptJson);
+      const result = sym.mssql_exec_script(connId, ptr(buf));
+      return readAndFree(result);
+    },
+
+    progressPoll(connId: bigint): string | null {
+      return readAndFree(sym.mssql_progress_poll(connId));
+    },
+
     async queryStream(connId: bigint, cmdJson: string): Promise<bigint> {
       const buf = toCString(cmdJson);
       return BigInt(sym.mssql_query_stream(connId, ptr(buf)));
@@ -154,6 +363,19 @@ export async function createFFI(libPath: string): Promise<RuntimeFFI> {
       return readAndFree(result);
     },
 
+    streamReadBinary(
+      cursorId: bigint,
+      column: string,
+      length: number,
+    ): Uint8Array | null {
+      const out = new Uint8Array(length);
+      const buf = toCString(column);
+      const n = Number(
+        sym.mssql_stream_read_binary(cursorId, ptr(buf), ptr(out), BigInt(length)),
+      );
+      return n < 0 ? null : out.subarray(0, n);
+    },
+
     streamClose(cursorId: bigint): void {
       sym.mssql_stream_close(cursorId);
     },
@@ -185,10 +407,19 @@ export async function createFFI(libPath: string): Promise<RuntimeFFI> {
       return readAndFree(result);
     },
 
+    async transactionState(connId: bigint): Promise<string | null> {
+      return readAndFree(sym.mssql_transaction_state(connId));
+    },
+
     cancel(connId: bigint): void {
       sym.mssql_cancel(connId);
     },
 
+    aeConfigure(connId: bigint, configJson: string): number {
+      const buf = toCString(configJson);
+      return sym.mssql_ae_configure(connId, ptr(buf));
+    },
+
     lastError(handleId: bigint): string | null {
       const result = sym.mssql_last_error(handleId);
       return readAndFree(result);
@@ -203,31 +434,237 @@ export async function createFFI(libPath: string): Promise<RuntimeFFI> {
       return BigInt(sym.mssql_filestream_open(ptr(buf)));
     },
 
-    filestreamRead(fsId: bigint, maxBytes: bigint): string | null {
+    async filestreamOpenFor(connId: bigint, reqJson: string): Promise<bigint> {
+      const buf = toCString(reqJson);
+      return BigInt(sym.mssql_filestream_open_for(connId, ptr(buf)));
+    },
+
+    async filestreamRead(
+      fsId: bigint,
+      maxBytes: bigint,
+    ): Promise<string | null> {
       const result = sym.mssql_filestream_read(fsId, maxBytes);
       return readAndFree(result);
     },
 
-    filestreamWrite(fsId: bigint, dataBase64: string): bigint {
+    async filestreamReadInto(fsId: bigint, buf: Uint8Array): Promise<number> {
+      return Number(
+        sym.mssql_filestream_read_into(fsId, ptr(buf), BigInt(buf.length)),
+      );
+    },
+
+    async filestreamWrite(fsId: bigint, dataBase64: string): Promise<bigint> {
       const buf = toCString(dataBase64);
       return BigInt(sym.mssql_filestream_write(fsId, ptr(buf)));
     },
 
+    async filestreamSetLength(
+      fsId: bigint,
+      length: bigint,
+    ): Promise<string | null> {
+      const result = sym.mssql_filestream_set_length(fsId, length);
+      return readAndFree(result);
+    },
+
+    async filestreamFinish(fsId: bigint): Promise<string | null> {
+      const result = sym.mssql_filestream_finish(fsId);
+      return readAndFree(result);
+    },
+
     filestreamClose(fsId: bigint): void {
       sym.mssql_filestream_close(fsId);
     },
 
+    async blobOpen(connId: bigint, reqJson: string): Promise<bigint> {
+      const buf = toCString(reqJson);
+      return BigInt(sym.mssql_blob_open(connId, ptr(buf)));
+    },
+
+    blobLength(blobId: bigint): bigint {
+      return BigInt(sym.mssql_blob_length(blobId));
+    },
+
+    async blobRead(
+      blobId: bigint,
+      offset: bigint,
+      length: bigint,
+    ): Promise<string | null> {
+      const result = sym.mssql_blob_read(blobId, offset, length);
+      return readAndFree(result);
+    },
+
+    blobClose(blobId: bigint): void {
+      sym.mssql_blob_close(blobId);
+    },
+
+    async notifySubscribe(connId: bigint, reqJson: string): Promise<bigint> {
+      const buf = toCString(reqJson);
+      return BigInt(sym.mssql_notify_subscribe(connId, ptr(buf)));
+    },
+
+    async notifyReceive(
+      subId: bigint,
+      timeoutMs: bigint,
+    ): Promise<string | null> {
+      const result = sym.mssql_notify_receive(subId, timeoutMs);
+      return readAndFree(result);
+    },
+
+    async notifyClose(subId: bigint): Promise<void> {
+      sym.mssql_notify_close(subId);
+    },
+
+    async brokerReceive(
+      connId: bigint,
+      queue: string,
+      timeoutMs: bigint,
+    ): Promise<string | null> {
+      const buf = toCString(queue);
+      const result = sym.mssql_broker_receive(connId, ptr(buf), timeoutMs);
+      return readAndFree(result);
+    },
+
+    async brokerSend(connId: bigint, reqJson: string): Promise<string | null> {
+      const buf = toCString(reqJson);
+      const result = sym.mssql_broker_send(connId, ptr(buf));
+      return readAndFree(result);
+    },
+
+    async brokerEnd(connId: bigint, reqJson: string): Promise<string | null> {
+      const buf = toCString(reqJson);
+      const result = sym.mssql_broker_end(connId, ptr(buf));
+      return readAndFree(result);
+    },
+
+    async ctChanges(
+      connId: bigint,
+      table: string,
+      sinceVersion: bigint,
+    ): Promise<string | null> {
+      const buf = toCString(table);
+      const result = sym.mssql_ct_changes(connId, ptr(buf), sinceVersion);
+      return readAndFree(result);
+    },
+
+    async cdcChanges(connId: bigint, reqJson: string): Promise<string | null> {
+      const buf = toCString(reqJson);
+      const result = sym.mssql_cdc_changes(connId, ptr(buf));
+      return readAndFree(result);
+    },
+
+    async tableSchema(connId: bigint, table: string): Promise<string | null> {
+      const buf = toCString(table);
+      const result = sym.mssql_table_schema(connId, ptr(buf));
+      return readAndFree(result);
+    },
+
+    async listObjects(
+      connId: bigint,
+      filterJson: string,
+    ): Promise<string | null> {
+      const buf = toCString(filterJson);
+      const result = sym.mssql_list_objects(connId, ptr(buf));
+      return readAndFree(result);
+    },
+
+    async listDatabases(connId: bigint): Promise<string | null> {
+      const result = sym.mssql_list_databases(connId);
+      return readAndFree(result);
+    },
+
+    async listSchemas(
+      connId: bigint,
+      database: string,
+    ): Promise<string | null> {
+      const buf = toCString(database);
+      const result = sym.mssql_list_schemas(connId, ptr(buf));
+      return readAndFree(result);
+    },
+
+    versionInfo(): string | null {
+      const result = sym.mssql_version_info();
+      return readAndFree(result);
+    },
+
     diagnosticInfo(): string | null {
       const result = sym.mssql_diagnostic_info();
       return readAndFree(result);
     },
 
+    slowQueries(): string | null {
+      const result = sym.mssql_slow_queries();
+      return readAndFree(result);
+    },
+
+    leakReport(maxAgeMs: number): string | null {
+      const result = sym.mssql_leak_report(BigInt(maxAgeMs));
+      return readAndFree(result);
+    },
+
+    metricsPrometheus(): string | null {
+      const result = sym.mssql_metrics_prometheus();
+      return readAndFree(result);
+    },
+
+    async configureTracing(configJson: string): Promise<string | null> {
+      const buf = toCString(configJson);
+      const result = sym.mssql_configure_tracing(ptr(buf));
+      return readAndFree(result);
+    },
+
     setDebug(enabled: number): void {
       sym.mssql_set_debug(enabled);
     },
 
+    setMemoryCap(bytes: number): void {
+      sym.mssql_set_memory_cap(BigInt(bytes));
+    },
+
+    setConcurrencyLimit(limit: number): void {
+      sym.mssql_set_concurrency_limit(limit);
+    },
+
+    setDebugConfig(configJson: string): string | null {
+      const buf = toCString(configJson);
+      const result = sym.mssql_set_debug_config(ptr(buf));
+      return readAndFree(result);
+    },
+
+    setLogCallback(callback: NativeLogCallback | null): void {
+      if (!callback) {
+        sym.mssql_set_log_callback(null);
+        return;
+      }
+      const cb = new JSCallback(
+        (
+          level: number,
+          target: number,
+          message: number,
+          timestampMs: number | bigint,
+        ) =>
+          callback(
+            level,
+            new CString(target).toString(),
+            new CString(message).toString(),
+            Number(timestampMs),
+          ),
+        {
+          args: [FFIType.u32, FFIType.ptr, FFIType.ptr, FFIType.u64],
+          returns: FFIType.void,
+          threadsafe: true,
+        },
+      );
+      logCallbacks.push(cb);
+      sym.mssql_set_log_callback(cb.ptr);
+    },
+
     closeAll(): void {
       sym.mssql_close_all();
     },
+
+    async closeAllGraceful(timeoutMs: number): Promise<string | null> {
+      const result = sym.mssql_close_all_graceful(BigInt(timeoutMs));
+      return readAndFree(result);
+    },
   };
 }
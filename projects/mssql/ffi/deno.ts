@@ -8,7 +8,7 @@
  * @module
  */
 
-import type { RuntimeFFI } from "../core/runtime.ts";
+import type { NativeLogCallback, RuntimeFFI } from "../core/runtime.ts";
 
 // FFI symbol definitions matching the C ABI exported from the Rust cdylib.
 // Symbols with `nonblocking: true` run on a separate thread and return Promises.
@@ -22,10 +22,12 @@ const SYMBOLS = {
   mssql_pool_acquire: { parameters: ["u64"], result: "u64", nonblocking: true },
   mssql_pool_release: { parameters: ["u64", "u64"], result: "void" },
   mssql_pool_close: { parameters: ["u64"], result: "void" },
+  mssql_pool_config: { parameters: ["u64"], result: "pointer" },
 
   // Connection — connect does TLS handshake; disconnect drops handle
   mssql_connect: { parameters: ["buffer"], result: "u64", nonblocking: true },
   mssql_disconnect: { parameters: ["u64"], result: "void" },
+  mssql_set_label: { parameters: ["u64", "buffer"], result: "void" },
 
   // Queries — all involve network roundtrips
   mssql_query: {
@@ -43,6 +45,27 @@ const SYMBOLS = {
     result: "pointer",
     nonblocking: true,
   },
+  mssql_take_result: { parameters: ["u64", "buffer", "u64"], result: "i64" },
+  mssql_decompress: {
+    parameters: ["buffer", "buffer", "u64", "buffer", "u64"],
+    result: "i64",
+  },
+  mssql_query_buffered: {
+    parameters: ["u64", "buffer"],
+    result: "pointer",
+    nonblocking: true,
+  },
+  mssql_read_rows: {
+    parameters: ["u64", "buffer", "u64", "buffer", "u64"],
+    result: "i64",
+  },
+  mssql_exec_script: {
+    parameters: ["u64", "buffer"],
+    result: "pointer",
+    nonblocking: true,
+  },
+  // Polled while a script runs, so it stays synchronous
+  mssql_progress_poll: { parameters: ["u64"], result: "pointer" },
 
   // Streaming — open and next involve network I/O; close drops cursor
   mssql_query_stream: {
@@ -55,6 +78,10 @@ const SYMBOLS = {
     result: "pointer",
     nonblocking: true,
   },
+  mssql_stream_read_binary: {
+    parameters: ["u64", "buffer", "buffer", "u64"],
+    result: "i64",
+  },
   mssql_stream_close: { parameters: ["u64"], result: "void" },
 
   // Bulk — network I/O
@@ -80,10 +107,18 @@ const SYMBOLS = {
     result: "pointer",
     nonblocking: true,
   },
+  mssql_transaction_state: {
+    parameters: ["u64"],
+    result: "pointer",
+    nonblocking: true,
+  },
 
   // Cancel — placeholder (no-op)
   mssql_cancel: { parameters: ["u64"], result: "void" },
 
+  // Always Encrypted — key install, no I/O
+  mssql_ae_configure: { parameters: ["u64", "buffer"], result: "u32" },
+
   // Error/memory — fast local operations
   mssql_last_error: { parameters: ["u64"], result: "pointer" },
   mssql_free_string: { parameters: ["pointer"], result: "void" },
@@ -91,16 +126,154 @@ const SYMBOLS = {
   // FILESTREAM — sync file I/O (Windows only)
   mssql_filestream_available: { parameters: [], result: "u32" },
   mssql_filestream_open: { parameters: ["buffer"], result: "u64" },
-  mssql_filestream_read: { parameters: ["u64", "u64"], result: "pointer" },
-  mssql_filestream_write: { parameters: ["u64", "buffer"], result: "u64" },
+  mssql_filestream_open_for: {
+    parameters: ["u64", "buffer"],
+    result: "u64",
+    nonblocking: true,
+  },
+  // Reads and writes run on the driver's blocking pool
+  mssql_filestream_read: {
+    parameters: ["u64", "u64"],
+    result: "pointer",
+    nonblocking: true,
+  },
+  mssql_filestream_read_into: {
+    parameters: ["u64", "buffer", "u64"],
+    result: "i64",
+    nonblocking: true,
+  },
+  mssql_filestream_write: {
+    parameters: ["u64", "buffer"],
+    result: "u64",
+    nonblocking: true,
+  },
+  mssql_filestream_set_length: {
+    parameters: ["u64", "u64"],
+    result: "pointer",
+    nonblocking: true,
+  },
+  mssql_filestream_finish: {
+    parameters: ["u64"],
+    result: "pointer",
+    nonblocking: true,
+  },
   mssql_filestream_close: { parameters: ["u64"], result: "void" },
 
+  // Blob — open/read do network I/O; length/close are local
+  mssql_blob_open: {
+    parameters: ["u64", "buffer"],
+    result: "u64",
+    nonblocking: true,
+  },
+  mssql_blob_length: { parameters: ["u64"], result: "i64" },
+  mssql_blob_read: {
+    parameters: ["u64", "u64", "u64"],
+    result: "pointer",
+    nonblocking: true,
+  },
+  mssql_blob_close: { parameters: ["u64"], result: "void" },
+
+  // Query notifications — all three do network I/O
+  mssql_notify_subscribe: {
+    parameters: ["u64", "buffer"],
+    result: "u64",
+    nonblocking: true,
+  },
+  mssql_notify_receive: {
+    parameters: ["u64", "u64"],
+    result: "pointer",
+    nonblocking: true,
+  },
+  mssql_notify_close: {
+    parameters: ["u64"],
+    result: "void",
+    nonblocking: true,
+  },
+
+  // Service Broker — all do network I/O; receive may block until its timeout
+  mssql_broker_receive: {
+    parameters: ["u64", "buffer", "u64"],
+    result: "pointer",
+    nonblocking: true,
+  },
+  mssql_broker_send: {
+    parameters: ["u64", "buffer"],
+    result: "pointer",
+    nonblocking: true,
+  },
+  mssql_broker_end: {
+    parameters: ["u64", "buffer"],
+    result: "pointer",
+    nonblocking: true,
+  },
+
+  // Change Tracking
+  mssql_ct_changes: {
+    parameters: ["u64", "buffer", "i64"],
+    result: "pointer",
+    nonblocking: true,
+  },
+
+  // Change Data Capture
+  mssql_cdc_changes: {
+    parameters: ["u64", "buffer"],
+    result: "pointer",
+    nonblocking: true,
+  },
+
+  // Catalog
+  mssql_table_schema: {
+    parameters: ["u64", "buffer"],
+    result: "pointer",
+    nonblocking: true,
+  },
+  mssql_list_objects: {
+    parameters: ["u64", "buffer"],
+    result: "pointer",
+    nonblocking: true,
+  },
+  mssql_list_databases: {
+    parameters: ["u64"],
+    result: "pointer",
+    nonblocking: true,
+  },
+  mssql_list_schemas: {
+    parameters: ["u64", "buffer"],
+    result: "pointer",
+    nonblocking: true,
+  },
+
   // Diagnostics / Debug
+  mssql_version_info: { parameters: [], result: "pointer" },
   mssql_diagnostic_info: { parameters: [], result: "pointer" },
+  mssql_slow_queries: { parameters: [], result: "pointer" },
+  mssql_leak_report: { parameters: ["u64"], result: "pointer" },
+  mssql_metrics_prometheus: { parameters: [], result: "pointer" },
   mssql_set_debug: { parameters: ["u32"], result: "void" },
+  mssql_set_memory_cap: { parameters: ["u64"], result: "void" },
+  mssql_set_concurrency_limit: { parameters: ["u32"], result: "void" },
+  mssql_set_debug_config: { parameters: ["buffer"], result: "pointer" },
+  mssql_set_log_callback: { parameters: ["function"], result: "void" },
+
+  // Tracing
+  mssql_configure_tracing: {
+    parameters: ["buffer"],
+    result: "pointer",
+    nonblocking: true,
+  },
 
   // Cleanup
   mssql_close_all: { parameters: [], result: "void" },
+  mssql_close_all_graceful: {
+    parameters: ["u64"],
+    result: "pointer",
+    nonblocking: true,
+  },
+} as const;
+
+const LOG_CALLBACK = {
+  parameters: ["u32", "pointer", "pointer", "u64"],
+  result: "void",
 } as const;
 
 const encoder = new TextEncoder();
@@ -134,6 +307,9 @@ function readAndFree(
  */
 export function createFFI(libPath: string): RuntimeFFI {
   const lib = Deno.dlopen(libPath, SYMBOLS);
+  // Replaced log callbacks stay open: a record may still be in flight on a
+  // driver thread when the pointer is swapped.
+  const logCallbacks: Deno.UnsafeCallback<typeof LOG_CALLBACK>[] = [];
 
   return {
     async poolCreate(configJson: string): Promise<bigint> {
@@ -153,6 +329,11 @@ export function createFFI(libPath: string): RuntimeFFI {
       lib.symbols.mssql_pool_close(poolId);
     },
 
+    poolConfig(poolId: bigint): string | null {
+      const ptr = lib.symbols.mssql_pool_config(poolId) as Deno.PointerValue;
+      return readAndFree(lib, ptr);
+    },
+
     async connect(configJson: string): Promise<bigint> {
       const buf = toCString(configJson);
       return await lib.symbols.mssql_connect(buf);
@@ -162,6 +343,11 @@ export function createFFI(libPath: string): RuntimeFFI {
       lib.symbols.mssql_disconnect(connId);
     },
 
+    setLabel(connId: bigint, label: string): void {
+      const buf = toCString(label);
+      lib.symbols.mssql_set_label(connId, buf);
+    },
+
     async query(connId: bigint, cmdJson: string): Promise<string | null> {
       const buf = toCString(cmdJson);
       const ptr = await lib.symbols.mssql_query(connId, buf);
@@ -183,6 +369,67 @@ export function createFFI(libPath: string): RuntimeFFI {
       return readAndFree(lib, ptr);
     },
 
+    takeResult(connId: bigint, length: number): Uint8Array | null {
+      const out = new Uint8Array(length);
+      const n = Number(
+        lib.symbols.mssql_take_result(connId, out, BigInt(length)),
+      );
+      return n < 0 ? null : out.subarray(0, n);
+    },
+
+    decompress(
+      codec: string,
+      data: Uint8Array,
+      length: number,
+    ): Uint8Array | null {
+      const name = toCString(codec);
+      const out = new Uint8Array(length);
+      const n = Number(
+        lib.symbols.mssql_decompress(
+          name,
+          data,
+          BigInt(data.length),
+          out,
+          BigInt(length),
+        ),
+      );
+      return n < 0 ? null : out;
+    },
+
+    async queryBuffered(
+      connId: bigint,
+      cmdJson: string,
+    ): Promise<string | null> {
+      const buf = toCString(cmdJson);
+      const ptr = await lib.symbols.mssql_query_buffered(connId, buf);
+      return readAndFree(lib, ptr);
+    },
+
+    readRows(connId: bigint, data: Uint8Array, offsets: Uint32Array): number {
+      return Number(
+        lib.symbols.mssql_read_rows(
+          connId,
+          data,
+          BigInt(data.length),
+          offsets,
+          BigInt(offsets.length),
+        ),
+      );
+    },
+
+    async execScript(
+      connId: bigint,
+      scriptJson: string,
+    ): Promise<string | null> {
+      const buf = toCString(scriptJson);
+      const ptr = await lib.symbols.mssql_exec_script(connId, buf);
+      return readAndFree(lib, ptr);
+    },
+
+    progressPoll(connId: bigint): string | null {
+      return readAndFree(lib, lib.symbols.mssql_progress_poll(connId));
+    },
+
     async queryStream(connId: bigint, cmdJson: string): Promise<bigint> {
       const buf = toCString(cmdJson);
       return await lib.symbols.mssql_query_stream(connId, buf);
@@ -193,6 +440,23 @@ export function createFFI(libPath: string): RuntimeFFI {
       return readAndFree(lib, ptr);
     },
 
+    streamReadBinary(
+      cursorId: bigint,
+      column: string,
+      length: number,
+    ): Uint8Array | null {
+      const out = new Uint8Array(length);
+      const n = Number(
+        lib.symbols.mssql_stream_read_binary(
+          cursorId,
+          toCString(column),
+          out,
+          BigInt(length),
+        ),
+      );
+      return n < 0 ? null : out.subarray(0, n);
+    },
+
     streamClose(cursorId: bigint): void {
       lib.symbols.mssql_stream_close(cursorId);
     },
@@ -224,10 +488,20 @@ export function createFFI(libPath: string): RuntimeFFI {
       return readAndFree(lib, ptr);
     },
 
+    async transactionState(connId: bigint): Promise<string | null> {
+      const ptr = await lib.symbols.mssql_transaction_state(connId);
+      return readAndFree(lib, ptr);
+    },
+
     cancel(connId: bigint): void {
       lib.symbols.mssql_cancel(connId);
     },
 
+    aeConfigure(connId: bigint, configJson: string): number {
+      const buf = toCString(configJson);
+      return lib.symbols.mssql_ae_configure(connId, buf) as number;
+    },
+
     lastError(handleId: bigint): string | null {
       const ptr = lib.symbols.mssql_last_error(handleId) as Deno.PointerValue;
       return readAndFree(lib, ptr);
@@ -242,34 +516,236 @@ export function createFFI(libPath: string): RuntimeFFI {
       return lib.symbols.mssql_filestream_open(buf) as bigint;
     },
 
-    filestreamRead(fsId: bigint, maxBytes: bigint): string | null {
-      const ptr = lib.symbols.mssql_filestream_read(
-        fsId,
-        maxBytes,
-      ) as Deno.PointerValue;
+    async filestreamOpenFor(connId: bigint, reqJson: string): Promise<bigint> {
+      const buf = toCString(reqJson);
+      return await lib.symbols.mssql_filestream_open_for(connId, buf);
+    },
+
+    async filestreamRead(
+      fsId: bigint,
+      maxBytes: bigint,
+    ): Promise<string | null> {
+      const ptr = await lib.symbols.mssql_filestream_read(fsId, maxBytes);
       return readAndFree(lib, ptr);
     },
 
-    filestreamWrite(fsId: bigint, dataBase64: string): bigint {
+    async filestreamReadInto(fsId: bigint, buf: Uint8Array): Promise<number> {
+      return Number(
+        await lib.symbols.mssql_filestream_read_into(
+          fsId,
+          buf,
+          BigInt(buf.length),
+        ),
+      );
+    },
+
+    async filestreamWrite(fsId: bigint, dataBase64: string): Promise<bigint> {
       const buf = toCString(dataBase64);
-      return lib.symbols.mssql_filestream_write(fsId, buf) as bigint;
+      return await lib.symbols.mssql_filestream_write(fsId, buf);
+    },
+
+    async filestreamSetLength(
+      fsId: bigint,
+      length: bigint,
+    ): Promise<string | null> {
+      const ptr = await lib.symbols.mssql_filestream_set_length(fsId, length);
+      return readAndFree(lib, ptr);
+    },
+
+    async filestreamFinish(fsId: bigint): Promise<string | null> {
+      const ptr = await lib.symbols.mssql_filestream_finish(fsId);
+      return readAndFree(lib, ptr);
     },
 
     filestreamClose(fsId: bigint): void {
       lib.symbols.mssql_filestream_close(fsId);
     },
 
+    async blobOpen(connId: bigint, reqJson: string): Promise<bigint> {
+      const buf = toCString(reqJson);
+      return await lib.symbols.mssql_blob_open(connId, buf);
+    },
+
+    blobLength(blobId: bigint): bigint {
+      return BigInt(lib.symbols.mssql_blob_length(blobId));
+    },
+
+    async blobRead(
+      blobId: bigint,
+      offset: bigint,
+      length: bigint,
+    ): Promise<string | null> {
+      const ptr = await lib.symbols.mssql_blob_read(blobId, offset, length);
+      return readAndFree(lib, ptr);
+    },
+
+    blobClose(blobId: bigint): void {
+      lib.symbols.mssql_blob_close(blobId);
+    },
+
+    async notifySubscribe(connId: bigint, reqJson: string): Promise<bigint> {
+      const buf = toCString(reqJson);
+      return await lib.symbols.mssql_notify_subscribe(connId, buf);
+    },
+
+    async notifyReceive(
+      subId: bigint,
+      timeoutMs: bigint,
+    ): Promise<string | null> {
+      const ptr = await lib.symbols.mssql_notify_receive(subId, timeoutMs);
+      return readAndFree(lib, ptr);
+    },
+
+    async notifyClose(subId: bigint): Promise<void> {
+      await lib.symbols.mssql_notify_close(subId);
+    },
+
+    async brokerReceive(
+      connId: bigint,
+      queue: string,
+      timeoutMs: bigint,
+    ): Promise<string | null> {
+      const buf = toCString(queue);
+      const ptr = await lib.symbols.mssql_broker_receive(connId, buf, timeoutMs);
+      return readAndFree(lib, ptr);
+    },
+
+    async brokerSend(connId: bigint, reqJson: string): Promise<string | null> {
+      const buf = toCString(reqJson);
+      const ptr = await lib.symbols.mssql_broker_send(connId, buf);
+      return readAndFree(lib, ptr);
+    },
+
+    async brokerEnd(connId: bigint, reqJson: string): Promise<string | null> {
+      const buf = toCString(reqJson);
+      const ptr = await lib.symbols.mssql_broker_end(connId, buf);
+      return readAndFree(lib, ptr);
+    },
+
+    async ctChanges(
+      connId: bigint,
+      table: string,
+      sinceVersion: bigint,
+    ): Promise<string | null> {
+      const buf = toCString(table);
+      const ptr = await lib.symbols.mssql_ct_changes(connId, buf, sinceVersion);
+      return readAndFree(lib, ptr);
+    },
+
+    async cdcChanges(connId: bigint, reqJson: string): Promise<string | null> {
+      const buf = toCString(reqJson);
+      const ptr = await lib.symbols.mssql_cdc_changes(connId, buf);
+      return readAndFree(lib, ptr);
+    },
+
+    async tableSchema(connId: bigint, table: string): Promise<string | null> {
+      const buf = toCString(table);
+      const ptr = await lib.symbols.mssql_table_schema(connId, buf);
+      return readAndFree(lib, ptr);
+    },
+
+    async listObjects(
+      connId: bigint,
+      filterJson: string,
+    ): Promise<string | null> {
+      const buf = toCString(filterJson);
+      const ptr = await lib.symbols.mssql_list_objects(connId, buf);
+      return readAndFree(lib, ptr);
+    },
+
+    async listDatabases(connId: bigint): Promise<string | null> {
+      const ptr = await lib.symbols.mssql_list_databases(connId);
+      return readAndFree(lib, ptr);
+    },
+
+    async listSchemas(
+      connId: bigint,
+      database: string,
+    ): Promise<string | null> {
+      const buf = toCString(database);
+      const ptr = await lib.symbols.mssql_list_schemas(connId, buf);
+      return readAndFree(lib, ptr);
+    },
+
+    versionInfo(): string | null {
+      const ptr = lib.symbols.mssql_version_info() as Deno.PointerValue;
+      return readAndFree(lib, ptr);
+    },
+
     diagnosticInfo(): string | null {
       const ptr = lib.symbols.mssql_diagnostic_info() as Deno.PointerValue;
       return readAndFree(lib, ptr);
     },
 
+    slowQueries(): string | null {
+      const ptr = lib.symbols.mssql_slow_queries() as Deno.PointerValue;
+      return readAndFree(lib, ptr);
+    },
+
+    leakReport(maxAgeMs: number): string | null {
+      const ptr = lib.symbols.mssql_leak_report(
+        BigInt(maxAgeMs),
+      ) as Deno.PointerValue;
+      return readAndFree(lib, ptr);
+    },
+
+    metricsPrometheus(): string | null {
+      const ptr = lib.symbols.mssql_metrics_prometheus() as Deno.PointerValue;
+      return readAndFree(lib, ptr);
+    },
+
+    async configureTracing(configJson: string): Promise<string | null> {
+      const buf = toCString(configJson);
+      const ptr = await lib.symbols.mssql_configure_tracing(buf);
+      return readAndFree(lib, ptr);
+    },
+
     setDebug(enabled: number): void {
       lib.symbols.mssql_set_debug(enabled);
     },
 
+    setMemoryCap(bytes: number): void {
+      lib.symbols.mssql_set_memory_cap(BigInt(bytes));
+    },
+
+    setConcurrencyLimit(limit: number): void {
+      lib.symbols.mssql_set_concurrency_limit(limit);
+    },
+
+    setDebugConfig(configJson: string): string | null {
+      const buf = toCString(configJson);
+      const ptr = lib.symbols.mssql_set_debug_config(buf);
+      return readAndFree(lib, ptr);
+    },
+
+    setLogCallback(callback: NativeLogCallback | null): void {
+      if (!callback) {
+        lib.symbols.mssql_set_log_callback(null);
+        return;
+      }
+      // Records arrive on driver threads, so the callback must be thread-safe.
+      const cb = Deno.UnsafeCallback.threadSafe(
+        LOG_CALLBACK,
+        (level, target, message, timestampMs) =>
+          callback(
+            level,
+            Deno.UnsafePointerView.getCString(target!),
+            Deno.UnsafePointerView.getCString(message!),
+            Number(timestampMs),
+          ),
+      );
+      cb.unref();
+      logCallbacks.push(cb);
+      lib.symbols.mssql_set_log_callback(cb.pointer);
+    },
+
     closeAll(): void {
       lib.symbols.mssql_close_all();
     },
+
+    async closeAllGraceful(timeoutMs: number): Promise<string | null> {
+      const ptr = await lib.symbols.mssql_close_all_graceful(BigInt(timeoutMs));
+      return readAndFree(lib, ptr);
+    },
   };
 }
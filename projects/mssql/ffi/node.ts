@@ -8,7 +8,7 @@
  * @module
  */
 
-import type { RuntimeFFI } from "../core/runtime.ts";
+import type { NativeLogCallback, RuntimeFFI } from "../core/runtime.ts";
 
 // Lazily import koffi to avoid issues when type-checking without it installed.
 // deno-lint-ignore no-explicit-any
@@ -55,17 +55,42 @@ export async function createFFI(libPath: string): Promise<RuntimeFFI> {
     "void mssql_pool_release(uint64_t, uint64_t)",
   );
   const mssql_pool_close = lib.func("void mssql_pool_close(uint64_t)");
+  const mssql_pool_config = lib.func("void * mssql_pool_config(uint64_t)");
   const mssql_connect = lib.func("uint64_t mssql_connect(const char *)");
   const mssql_disconnect = lib.func("void mssql_disconnect(uint64_t)");
+  const mssql_set_label = lib.func(
+    "void mssql_set_label(uint64_t, const char *)",
+  );
   const mssql_query = lib.func("void * mssql_query(uint64_t, const char *)");
   const mssql_execute_nonquery = lib.func(
     "void * mssql_execute_nonquery(uint64_t, const char *)",
   );
   const mssql_exec = lib.func("void * mssql_exec(uint64_t, const char *)");
+  const mssql_take_result = lib.func(
+    "int64_t mssql_take_result(uint64_t, void *, uint64_t)",
+  );
+  const mssql_decompress = lib.func(
+    "int64_t mssql_decompress(const char *, const void *, uint64_t, void *, uint64_t)",
+  );
+  const mssql_query_buffered = lib.func(
+    "void * mssql_query_buffered(uint64_t, const char *)",
+  );
+  const mssql_read_rows = lib.func(
+    "int64_t mssql_read_rows(uint64_t, void *, uint64_t, void *, uint64_t)",
+  );
+  const mssql_exec_script = lib.func(
+    "void * mssql_exec_script(uint64_t, const char *)",
+  );
+  const mssql_progress_poll = lib.func(
+    "void * mssql_progress_poll(uint64_t)",
+  );
   const mssql_query_stream = lib.func(
     "uint64_t mssql_query_stream(uint64_t, const char *)",
   );
   const mssql_stream_next = lib.func("void * mssql_stream_next(uint64_t)");
+  const mssql_stream_read_binary = lib.func(
+    "int64_t mssql_stream_read_binary(uint64_t, const char *, void *, uint64_t)",
+  );
   const mssql_stream_close = lib.func("void mssql_stream_close(uint64_t)");
   const mssql_bulk_insert = lib.func(
     "void * mssql_bulk_insert(uint64_t, const char *)",
@@ -77,7 +102,13 @@ export async function createFFI(libPath: string): Promise<RuntimeFFI> {
   const mssql_rollback = lib.func(
     "void * mssql_rollback(uint64_t, const char *)",
   );
+  const mssql_transaction_state = lib.func(
+    "void * mssql_transaction_state(uint64_t)",
+  );
   const mssql_cancel = lib.func("void mssql_cancel(uint64_t)");
+  const mssql_ae_configure = lib.func(
+    "uint32_t mssql_ae_configure(uint64_t, const char *)",
+  );
   const mssql_last_error = lib.func("void * mssql_last_error(uint64_t)");
   const mssql_free_string = lib.func("void mssql_free_string(void *)");
   const mssql_filestream_available = lib.func(
@@ -86,18 +117,99 @@ export async function createFFI(libPath: string): Promise<RuntimeFFI> {
   const mssql_filestream_open = lib.func(
     "uint64_t mssql_filestream_open(const char *)",
   );
+  const mssql_filestream_open_for = lib.func(
+    "uint64_t mssql_filestream_open_for(uint64_t, const char *)",
+  );
   const mssql_filestream_read = lib.func(
     "void * mssql_filestream_read(uint64_t, uint64_t)",
   );
+  const mssql_filestream_read_into = lib.func(
+    "int64_t mssql_filestream_read_into(uint64_t, void *, uint64_t)",
+  );
   const mssql_filestream_write = lib.func(
     "uint64_t mssql_filestream_write(uint64_t, const char *)",
   );
+  const mssql_filestream_set_length = lib.func(
+    "void * mssql_filestream_set_length(uint64_t, uint64_t)",
+  );
+  const mssql_filestream_finish = lib.func(
+    "void * mssql_filestream_finish(uint64_t)",
+  );
   const mssql_filestream_close = lib.func(
     "void mssql_filestream_close(uint64_t)",
   );
+  const mssql_blob_open = lib.func(
+    "uint64_t mssql_blob_open(uint64_t, const char *)",
+  );
+  const mssql_blob_length = lib.func("int64_t mssql_blob_length(uint64_t)");
+  const mssql_blob_read = lib.func(
+    "void * mssql_blob_read(uint64_t, uint64_t, uint64_t)",
+  );
+  const mssql_blob_close = lib.func("void mssql_blob_close(uint64_t)");
+  const mssql_notify_subscribe = lib.func(
+    "uint64_t mssql_notify_subscribe(uint64_t, const char *)",
+  );
+  const mssql_notify_receive = lib.func(
+    "void * mssql_notify_receive(uint64_t, uint64_t)",
+  );
+  const mssql_notify_close = lib.func("void mssql_notify_close(uint64_t)");
+  const mssql_broker_receive = lib.func(
+    "void * mssql_broker_receive(uint64_t, const char *, uint64_t)",
+  );
+  const mssql_broker_send = lib.func(
+    "void * mssql_broker_send(uint64_t, const char *)",
+  );
+  const mssql_broker_end = lib.func(
+    "void * mssql_broker_end(uint64_t, const char *)",
+  );
+  const mssql_ct_changes = lib.func(
+    "void * mssql_ct_changes(uint64_t, const char *, int64_t)",
+  );
+  const mssql_cdc_changes = lib.func(
+    "void * mssql_cdc_changes(uint64_t, const char *)",
+  );
+  const mssql_table_schema = lib.func(
+    "void * mssql_table_schema(uint64_t, const char *)",
+  );
+  const mssql_list_objects = lib.func(
+    "void * mssql_list_objects(uint64_t, const char *)",
+  );
+  const mssql_list_databases = lib.func(
+    "void * mssql_list_databases(uint64_t)",
+  );
+  const mssql_list_schemas = lib.func(
+    "void * mssql_list_schemas(uint64_t, const char *)",
+  );
+  const mssql_version_info = lib.func("void * mssql_version_info()");
   const mssql_diagnostic_info = lib.func("void * mssql_diagnostic_info()");
+  const mssql_slow_queries = lib.func("void * mssql_slow_queries()");
+  const mssql_leak_report = lib.func("void * mssql_leak_report(uint64_t)");
+  const mssql_metrics_prometheus = lib.func(
+    "void * mssql_metrics_prometheus()",
+  );
   const mssql_set_debug = lib.func("void mssql_set_debug(uint32_t)");
+  const mssql_set_memory_cap = lib.func(
+    "void mssql_set_memory_cap(uint64_t)",
+  );
+  const mssql_set_concurrency_limit = lib.func(
+    "void mssql_set_concurrency_limit(uint32_t)",
+  );
+  const mssql_set_debug_config = lib.func(
+    "void * mssql_set_debug_config(const char *)",
+  );
+  const LogCallback = koffi.proto(
+    "void mssql_log_callback(uint32_t, const char *, const char *, uint64_t)",
+  );
+  const mssql_set_log_callback = lib.func(
+    "void mssql_set_log_callback(mssql_log_callback *)",
+  );
+  const mssql_configure_tracing = lib.func(
+    "void * mssql_configure_tracing(const char *)",
+  );
   const mssql_close_all = lib.func("void mssql_close_all()");
+  const mssql_close_all_graceful = lib.func(
+    "void * mssql_close_all_graceful(uint64_t)",
+  );
 
   /** Read a C string from a pointer, then free it. Returns null for null pointers. */
   function readAndFree(rawPtr: unknown): string | null {
@@ -124,6 +236,10 @@ export async function createFFI(libPath: string): Promise<RuntimeFFI> {
       mssql_pool_close(poolId);
     },
 
+    poolConfig(poolId: bigint): string | null {
+      return readAndFree(mssql_pool_config(poolId));
+    },
+
     async connect(configJson: string): Promise<bigint> {
       return BigInt(mssql_connect(configJson));
     },
@@ -132,6 +248,10 @@ export async function createFFI(libPath: string): Promise<RuntimeFFI> {
       mssql_disconnect(connId);
     },
 
+    setLabel(connId: bigint, label: string): void {
+      mssql_set_label(connId, label);
+    },
+
     async query(connId: bigint, cmdJson: string): Promise<string | null> {
       const ptr = mssql_query(connId, cmdJson);
       return readAndFree(ptr);
@@ -150,6 +270,56 @@ export async function createFFI(libPath: string): Promise<RuntimeFFI> {
       return readAndFree(ptr);
     },
 
+    takeResult(connId: bigint, length: number): Uint8Array | null {
+      const out = new Uint8Array(length);
+      const n = Number(mssql_take_result(connId, out, BigInt(length)));
+      return n < 0 ? null : out.subarray(0, n);
+    },
+
+    decompress(
+      codec: string,
+      data: Uint8Array,
+      length: number,
+    ): Uint8Array | null {
+      const out = new Uint8Array(length);
+      const n = Number(
+        mssql_decompress(codec, data, BigInt(data.length), out, BigInt(length)),
+      );
+      return n < 0 ? null : out;
+    },
+
+    async queryBuffered(
+      connId: bigint,
+      cmdJson: string,
+    ): Promise<string | null> {
+      const ptr = mssql_query_buffered(connId, cmdJson);
+      return readAndFree(ptr);
+    },
+
+    readRows(connId: bigint, data: Uint8Array, offsets: Uint32Array): number {
+      return Number(
+        mssql_read_rows(
+          connId,
+          data,
+          BigInt(data.length),
+          offsets,
+          BigInt(offsets.length),
+        ),
+      );
+    },
+
+    async execScript(
+      connId: bigint,
+      scriptJson: string,
+    ): Promise<string | null> {
+      const ptr = mssql_exec_script(connId, scriptJson);
+      return readAndFree(ptr);
+    },
+
+    progressPoll(connId: bigint): string | null {
+      return readAndFree(mssql_progress_poll(connId));
+    },
+
     async queryStream(connId: bigint, cmdJson: string): Promise<bigint> {
       return BigInt(mssql_query_stream(connId, cmdJson));
     },
@@ -159,6 +329,18 @@ export async function createFFI(libPath: string): Promise<RuntimeFFI> {
       return readAndFree(ptr);
     },
 
+    streamReadBinary(
+      cursorId: bigint,
+      column: string,
+      length: number,
+    ): Uint8Array | null {
+      const out = new Uint8Array(length);
+      const n = Number(
+        mssql_stream_read_binary(cursorId, column, out, BigInt(length)),
+      );
+      return n < 0 ? null : out.subarray(0, n);
+    },
+
     streamClose(cursorId: bigint): void {
       mssql_stream_close(cursorId);
     },
@@ -186,10 +368,18 @@ export async function createFFI(libPath: string): Promise<RuntimeFFI> {
       return readAndFree(ptr);
     },
 
+    async transactionState(connId: bigint): Promise<string | null> {
+      return readAndFree(mssql_transaction_state(connId));
+    },
+
     cancel(connId: bigint): void {
       mssql_cancel(connId);
     },
 
+    aeConfigure(connId: bigint, configJson: string): number {
+      return mssql_ae_configure(connId, configJson);
+    },
+
     lastError(handleId: bigint): string | null {
       const ptr = mssql_last_error(handleId);
       return readAndFree(ptr);
@@ -203,30 +393,212 @@ export async function createFFI(libPath: string): Promise<RuntimeFFI> {
       return BigInt(mssql_filestream_open(reqJson));
     },
 
-    filestreamRead(fsId: bigint, maxBytes: bigint): string | null {
+    async filestreamOpenFor(connId: bigint, reqJson: string): Promise<bigint> {
+      return BigInt(mssql_filestream_open_for(connId, reqJson));
+    },
+
+    async filestreamRead(
+      fsId: bigint,
+      maxBytes: bigint,
+    ): Promise<string | null> {
       const ptr = mssql_filestream_read(fsId, maxBytes);
       return readAndFree(ptr);
     },
 
-    filestreamWrite(fsId: bigint, dataBase64: string): bigint {
+    async filestreamReadInto(fsId: bigint, buf: Uint8Array): Promise<number> {
+      return Number(mssql_filestream_read_into(fsId, buf, BigInt(buf.length)));
+    },
+
+    async filestreamWrite(fsId: bigint, dataBase64: string): Promise<bigint> {
       return BigInt(mssql_filestream_write(fsId, dataBase64));
     },
 
+    async filestreamSetLength(
+      fsId: bigint,
+      length: bigint,
+    ): Promise<string | null> {
+      const ptr = mssql_filestream_set_length(fsId, length);
+      return readAndFree(ptr);
+    },
+
+    async filestreamFinish(fsId: bigint): Promise<string | null> {
+      const ptr = mssql_filestream_finish(fsId);
+      return readAndFree(ptr);
+    },
+
     filestreamClose(fsId: bigint): void {
       mssql_filestream_close(fsId);
     },
 
+    async blobOpen(connId: bigint, reqJson: string): Promise<bigint> {
+      return BigInt(mssql_blob_open(connId, reqJson));
+    },
+
+    blobLength(blobId: bigint): bigint {
+      return BigInt(mssql_blob_length(blobId));
+    },
+
+    async blobRead(
+      blobId: bigint,
+      offset: bigint,
+      length: bigint,
+    ): Promise<string | null> {
+      const ptr = mssql_blob_read(blobId, offset, length);
+      return readAndFree(ptr);
+    },
+
+    blobClose(blobId: bigint): void {
+      mssql_blob_close(blobId);
+    },
+
+    async notifySubscribe(connId: bigint, reqJson: string): Promise<bigint> {
+      return BigInt(mssql_notify_subscribe(connId, reqJson));
+    },
+
+    async notifyReceive(
+      subId: bigint,
+      timeoutMs: bigint,
+    ): Promise<string | null> {
+      const ptr = mssql_notify_receive(subId, timeoutMs);
+      return readAndFree(ptr);
+    },
+
+    async notifyClose(subId: bigint): Promise<void> {
+      mssql_notify_close(subId);
+    },
+
+    async brokerReceive(
+      connId: bigint,
+      queue: string,
+      timeoutMs: bigint,
+    ): Promise<string | null> {
+      const ptr = mssql_broker_receive(connId, queue, timeoutMs);
+      return readAndFree(ptr);
+    },
+
+    async brokerSend(connId: bigint, reqJson: string): Promise<string | null> {
+      const ptr = mssql_broker_send(connId, reqJson);
+      return readAndFree(ptr);
+    },
+
+    async brokerEnd(connId: bigint, reqJson: string): Promise<string | null> {
+      const ptr = mssql_broker_end(connId, reqJson);
+      return readAndFree(ptr);
+    },
+
+    async ctChanges(
+      connId: bigint,
+      table: string,
+      sinceVersion: bigint,
+    ): Promise<string | null> {
+      const ptr = mssql_ct_changes(connId, table, sinceVersion);
+      return readAndFree(ptr);
+    },
+
+    async cdcChanges(connId: bigint, reqJson: string): Promise<string | null> {
+      const ptr = mssql_cdc_changes(connId, reqJson);
+      return readAndFree(ptr);
+    },
+
+    async tableSchema(connId: bigint, table: string): Promise<string | null> {
+      const ptr = mssql_table_schema(connId, table);
+      return readAndFree(ptr);
+    },
+
+    async listObjects(
+      connId: bigint,
+      filterJson: string,
+    ): Promise<string | null> {
+      const ptr = mssql_list_objects(connId, filterJson);
+      return readAndFree(ptr);
+    },
+
+    async listDatabases(connId: bigint): Promise<string | null> {
+      const ptr = mssql_list_databases(connId);
+      return readAndFree(ptr);
+    },
+
+    async listSchemas(
+      connId: bigint,
+      database: string,
+    ): Promise<string | null> {
+      const ptr = mssql_list_schemas(connId, database);
+      return readAndFree(ptr);
+    },
+
+    versionInfo(): string | null {
+      const ptr = mssql_version_info();
+      return readAndFree(ptr);
+    },
+
     diagnosticInfo(): string | null {
       const ptr = mssql_diagnostic_info();
       return readAndFree(ptr);
     },
 
+    slowQueries(): string | null {
+      const ptr = mssql_slow_queries();
+      return readAndFree(ptr);
+    },
+
+    leakReport(maxAgeMs: number): string | null {
+      const ptr = mssql_leak_report(BigInt(maxAgeMs));
+      return readAndFree(ptr);
+    },
+
+    metricsPrometheus(): string | null {
+      const ptr = mssql_metrics_prometheus();
+      return readAndFree(ptr);
+    },
+
+    async configureTracing(configJson: string): Promise<string | null> {
+      const ptr = mssql_configure_tracing(configJson);
+      return readAndFree(ptr);
+    },
+
     setDebug(enabled: number): void {
       mssql_set_debug(enabled);
     },
 
+    setMemoryCap(bytes: number): void {
+      mssql_set_memory_cap(BigInt(bytes));
+    },
+
+    setConcurrencyLimit(limit: number): void {
+      mssql_set_concurrency_limit(limit);
+    },
+
+    setDebugConfig(configJson: string): string | null {
+      const ptr = mssql_set_debug_config(configJson);
+      return readAndFree(ptr);
+    },
+
+    setLogCallback(callback: NativeLogCallback | null): void {
+      if (!callback) {
+        mssql_set_log_callback(null);
+        return;
+      }
+      // Replaced callbacks are not unregistered: a record may still be in
+      // flight on a driver thread when the pointer is swapped.
+      const cb = koffi.register(
+        (
+          level: number,
+          target: string,
+          message: string,
+          timestampMs: number | bigint,
+        ) => callback(level, target, message, Number(timestampMs)),
+        koffi.pointer(LogCallback),
+      );
+      mssql_set_log_callback(cb);
+    },
+
     closeAll(): void {
       mssql_close_all();
     },
+
+    async closeAllGraceful(timeoutMs: number): Promise<string | null> {
+      const ptr = mssql_close_all_graceful(BigInt(timeoutMs));
+      return readAndFree(ptr);
+    },
   };
 }
@@ -17,6 +17,8 @@ import { INVALID_HANDLE } from "./core/runtime.ts";
 import { parseConnection, resolveTokenProvider } from "./core/config.ts";
 import { MssqlConnection } from "./core/connection.ts";
 import { MssqlPool } from "./core/pool.ts";
+import { nativeDecompressor as ffiDecompressor } from "./core/compress.ts";
+import type { Decompressor } from "./core/compress.ts";
 import { getFfi } from "./ffi/resolve.ts";
 
 // ── FFI access ────────────────────────────────────────────────
@@ -67,8 +69,36 @@ export async function connect(
   return new MssqlConnection(connId, ffi);
 }
 
+/**
+ * A decompressor for `decodeCompressedRows()` backed by the native library,
+ * for both codecs on every runtime: Deno and Bun have no LZ4 or zstd
+ * decoder to hand, and Node.js only has zstd from 22.15. Needs no
+ * connection, so a worker can decode results passed to it.
+ *
+ * ```ts
+ * const rows = decodeCompressedRows(result, await mssql.nativeDecompressor());
+ * ```
+ */
+export async function nativeDecompressor(): Promise<Decompressor> {
+  return ffiDecompressor(await getFfi());
+}
+
 // ── Diagnostics / Debug ───────────────────────────────────────
 
+/**
+ * Version of the native driver and the crates it's built on, its target and
+ * compiled-in features, and the TDS versions it supports. Include it in bug
+ * reports, or check it before relying on a platform-specific feature.
+ */
+export async function versionInfo(): Promise<
+  import("./core/types.ts").VersionInfo
+> {
+  const ffi = await getFfi();
+  const json = ffi.versionInfo();
+  if (!json) throw new Error("Native driver returned no version info");
+  return JSON.parse(json);
+}
+
 /**
  * Get diagnostic information about active pools and connections.
  * Contains no credentials, connection strings, or passwords.
@@ -78,20 +108,171 @@ export async function diagnosticInfo(): Promise<
 > {
   const ffi = await getFfi();
   const json = ffi.diagnosticInfo();
-  if (!json) return { pools: [], connections: [] };
+  if (!json) {
+    return {
+      pools: [],
+      connections: [],
+      cursors: [],
+      memory: { buffered_bytes: 0, soft_cap_bytes: null },
+      concurrency: {
+        limit: null,
+        running: 0,
+        queued: 0,
+        waited: 0,
+        wait_ms_total: 0,
+        wait_ms_max: 0,
+      },
+      runtime: null,
+    };
+  }
   return JSON.parse(json);
 }
 
+/**
+ * Commands recorded by the slow query log, oldest first. Only connections
+ * configured with `slowQueryMs` record entries; the newest 256 are kept.
+ */
+export async function slowQueries(): Promise<
+  import("./core/types.ts").SlowQuery[]
+> {
+  const ffi = await getFfi();
+  const json = ffi.slowQueries();
+  if (!json) return [];
+  return JSON.parse(json);
+}
+
+/**
+ * Connections, streams, blobs and FILESTREAM handles that have been open for
+ * at least `maxAgeMs`, oldest first. Use it to find forgotten `close()` or
+ * `disconnect()` calls in long-running services; `cn.setLabel()` tags
+ * handles with where they came from.
+ */
+export async function leakReport(
+  maxAgeMs: number,
+): Promise<import("./core/types.ts").LeakedHandle[]> {
+  const ffi = await getFfi();
+  const json = ffi.leakReport(maxAgeMs);
+  if (!json) return [];
+  return JSON.parse(json);
+}
+
+/**
+ * Driver metrics in Prometheus text format: pool gauges, command counters
+ * and latency histograms, ready to append to an existing `/metrics` response.
+ */
+export async function metricsPrometheus(): Promise<string> {
+  const ffi = await getFfi();
+  return ffi.metricsPrometheus() ?? "";
+}
+
+/**
+ * Export driver spans (connect, acquire, query, exec, bulk, transactions) to
+ * an OpenTelemetry collector over OTLP/HTTP. Calling again replaces the
+ * exporter; `null` removes it after flushing buffered spans.
+ */
+export async function configureTracing(
+  options: import("./core/types.ts").TracingOptions | null,
+): Promise<void> {
+  const ffi = await getFfi();
+  const config = options && {
+    endpoint: options.endpoint,
+    headers: options.headers,
+    service_name: options.serviceName,
+    timeout_ms: options.timeoutMs,
+  };
+  const err = await ffi.configureTracing(config ? JSON.stringify(config) : "");
+  if (err !== null) throw new Error(`Configure tracing failed: ${err}`);
+}
+
 /**
  * Enable or disable debug logging from the Rust driver.
- * When enabled, debug messages are written to stderr.
- * Auto-enabled if the `MSSQLTS_DEBUG=1` environment variable is set.
+ * When enabled, debug messages are written to stderr (or the `setLogger()`
+ * callback). Auto-enabled if the `MSSQLTS_DEBUG=1` environment variable is set.
  */
 export async function setDebug(enabled: boolean): Promise<void> {
   const ffi = await getFfi();
   ffi.setDebug(enabled ? 1 : 0);
 }
 
+/**
+ * Cap the memory the driver holds for results not yet read: rows buffered by
+ * open streams, and compressed or buffered results waiting on connections.
+ * The cap is soft: work already under way finishes, but while the driver is
+ * over it, opening a stream or starting a buffered or compressed query
+ * throws. Totals are in `diagnosticInfo().memory`. Pass `null` to remove the
+ * cap.
+ */
+export async function setMemoryLimit(bytes: number | null): Promise<void> {
+  const ffi = await getFfi();
+  ffi.setMemoryCap(bytes ?? 0);
+}
+
+/**
+ * Limit how many queries, execs and bulk inserts run at once across every
+ * pool and connection. Calls over the limit wait in the driver, in the order
+ * they were made, until one finishes; a bug that fires thousands of queries
+ * then queues them instead of flooding the server. The queue is in
+ * `diagnosticInfo().concurrency`. Pass `null` to remove the limit.
+ */
+export async function setConcurrencyLimit(limit: number | null): Promise<void> {
+  if (limit !== null && (!Number.isInteger(limit) || limit < 1)) {
+    throw new RangeError("Concurrency limit must be a positive integer");
+  }
+  const ffi = await getFfi();
+  ffi.setConcurrencyLimit(limit ?? 0);
+}
+
+/**
+ * Set the log level, optionally per category, e.g.
+ * `{ level: "info", categories: { pool: "trace" } }`, and where records are
+ * written. Replaces any filter set by `setDebug()` or the `MSSQLTS_LOG`
+ * environment variable.
+ */
+export async function configureLogging(
+  options: import("./core/types.ts").LoggingOptions,
+): Promise<void> {
+  const ffi = await getFfi();
+  const err = ffi.setDebugConfig(JSON.stringify({
+    level: options.level,
+    categories: options.categories,
+    file: options.file,
+    max_file_bytes: options.maxFileBytes,
+    max_files: options.maxFiles,
+    redact: options.redact,
+  }));
+  if (err !== null) throw new Error(`Configure logging failed: ${err}`);
+}
+
+const LOG_LEVELS: Record<number, import("./core/types.ts").LogLevel> = {
+  1: "error",
+  2: "warn",
+  3: "info",
+  4: "debug",
+  5: "trace",
+};
+
+/**
+ * Receive driver log records instead of having them written to stderr.
+ * Records pass the same level filter as stderr output (errors only by
+ * default; see `setDebug()` and `configureLogging()`).
+ * Pass `null` to go back to stderr.
+ */
+export async function setLogger(
+  logger: ((record: import("./core/types.ts").LogRecord) => void) | null,
+): Promise<void> {
+  const ffi = await getFfi();
+  ffi.setLogCallback(
+    logger &&
+      ((level, target, message, timestampMs) =>
+        logger({
+          level: LOG_LEVELS[level] ?? "debug",
+          target,
+          message,
+          timestamp: new Date(timestampMs),
+        })),
+  );
+}
+
 /**
  * Close all active pools, connections, cursors, and FILESTREAM handles.
  * Typically called during process shutdown.
@@ -101,6 +282,19 @@ export async function closeAll(): Promise<void> {
   ffi.closeAll();
 }
 
+/**
+ * Like `closeAll()`, but first stops new operations from starting (they fail
+ * with "Driver is shutting down") and waits up to `timeoutMs` for running
+ * queries to finish. Resolves with what was still open when it closed.
+ */
+export async function closeAllGraceful(
+  timeoutMs = 30_000,
+): Promise<import("./core/types.ts").CloseSummary> {
+  const ffi = await getFfi();
+  const json = await ffi.closeAllGraceful(timeoutMs);
+  return JSON.parse(json!);
+}
+
 // ── Re-exports from core (no FFI needed) ──────────────────────
 
 export { newCOMB } from "./core/comb.ts";
@@ -111,10 +305,64 @@ export {
   utf8Column,
 } from "./core/collation.ts";
 export { ExecResult } from "./core/exec_result.ts";
+export type { ExecColumn } from "./core/exec_result.ts";
 export { Transaction } from "./core/transaction.ts";
 export { QueryStream } from "./core/stream.ts";
 export { PooledQueryStream } from "./core/pool.ts";
 export { BulkInsertBuilder } from "./core/bulk.ts";
+export { BlobReader } from "./core/blob.ts";
+export { decodeCompressedRows } from "./core/compress.ts";
+export { RowBuffer } from "./core/row_buffer.ts";
+export type { BufferedQueryOptions } from "./core/row_buffer.ts";
+export type {
+  CompressedResult,
+  Compression,
+  CompressOptions,
+  Decompressor,
+} from "./core/compress.ts";
+export type {
+  QueryStatistics,
+  QueryStatisticsResult,
+  TableIoStatistics,
+  TimingStatistics,
+} from "./core/statistics.ts";
+export type {
+  ColumnSchema,
+  DatabaseInfo,
+  DbObject,
+  DbObjectType,
+  ForeignKeySchema,
+  IndexSchema,
+  ObjectFilter,
+  SchemaInfo,
+  TableSchema,
+} from "./core/catalog.ts";
+export type {
+  CdcChange,
+  CdcChangeSet,
+  CdcReadOptions,
+} from "./core/cdc.ts";
+export type {
+  BrokerEndOptions,
+  BrokerMessage,
+  BrokerSendOptions,
+} from "./core/broker.ts";
+export type {
+  SystemTime,
+  TemporalQueryOptions,
+  TemporalRow,
+} from "./core/temporal.ts";
+export type {
+  ChangeTrackingResult,
+  TrackedChange,
+} from "./core/tracking.ts";
+export { QueryNotification } from "./core/notification.ts";
+export type { QueryNotificationEvent } from "./core/notification.ts";
+export {
+  parseEncryptedCek,
+  rsaKeyStoreProvider,
+} from "./core/encryption.ts";
+export type { EncryptedCek, KeyStoreProvider } from "./core/encryption.ts";
 export {
   FilestreamDuplex,
   FilestreamReadable,
@@ -130,19 +378,59 @@ export {
   resolveLibraryPath,
 } from "./core/binary.ts";
 export type {
+  ApplicationIntent,
+  BigIntMode,
   BulkColumn,
+  CloseSummary,
+  ColumnCase,
   CommandOptions,
   CommandType,
+  DecimalMode,
+  DiagnosticConcurrency,
   DiagnosticConnection,
+  DiagnosticCursor,
   DiagnosticInfo,
+  DiagnosticMemory,
+  DiagnosticMetrics,
   DiagnosticPool,
+  DiagnosticRuntime,
+  DiagnosticStatement,
+  EffectiveConfig,
+  ExecuteResult,
   FilestreamMode,
+  FilestreamWriteMode,
+  InsertOptions,
+  InsertResult,
   IsolationLevel,
+  LeakedHandle,
+  LogCategory,
+  LoggingOptions,
+  LogLevel,
+  LogRecord,
   MssqlConfig,
   NormalizedConfig,
+  PageRequest,
+  PageResult,
   Params,
   ParamValue,
+  PlanCapture,
+  QueryPlanOptions,
+  QueryPlanResult,
+  RowCountMode,
+  ScriptBatchResult,
+  ScriptOptions,
+  ScriptProgressEvent,
+  ScriptResult,
+  SlowQuery,
   SqlType,
+  TemporalMode,
+  TlsVersion,
+  TracingOptions,
+  TransactionOptions,
+  TransactionState,
   TypedParam,
   Utf8Collation,
+  VarcharDecodePolicy,
+  VersionInfo,
+  VersionInfoDependency,
 } from "./core/types.ts";
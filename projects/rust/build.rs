@@ -0,0 +1,42 @@
+//! Records the locked versions of the driver crates, read from Cargo.lock,
+//! for `mssql_version_info`.
+
+use std::fs;
+
+/// Crates to report, and the env var prefix each is exposed under.
+const CRATES: [(&str, &str); 2] = [
+    ("mssql-client", "MSSQLTS_CLIENT"),
+    ("mssql-driver-pool", "MSSQLTS_POOL"),
+];
+
+fn main() {
+    println!("cargo:rerun-if-changed=Cargo.lock");
+    let lock = fs::read_to_string("Cargo.lock").unwrap_or_default();
+    for (name, var) in CRATES {
+        let (version, rev) = locked(&lock, name);
+        println!("cargo:rustc-env={var}_VERSION={version}");
+        println!("cargo:rustc-env={var}_REV={rev}");
+    }
+}
+
+/// Version and git revision of `name`'s `[[package]]` entry, or "unknown".
+fn locked(lock: &str, name: &str) -> (String, String) {
+    let field = |entry: &str, key: &str| {
+        entry
+            .lines()
+            .find_map(|line| line.strip_prefix(key))
+            .map(|value| value.trim_matches('"').to_string())
+    };
+    let entry = lock
+        .split("[[package]]")
+        .find(|entry| field(entry, "name = ").as_deref() == Some(name));
+    let version = entry.and_then(|e| field(e, "version = "));
+    // git+https://...?branch=...#<rev>
+    let rev = entry
+        .and_then(|e| field(e, "source = "))
+        .and_then(|source| source.rsplit_once('#').map(|(_, rev)| rev.to_string()));
+    (
+        version.unwrap_or_else(|| "unknown".into()),
+        rev.unwrap_or_default(),
+    )
+}
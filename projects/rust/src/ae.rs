@@ -0,0 +1,332 @@
+//! Always Encrypted result decryption.
+//!
+//! Column encryption keys (CEKs) are unwrapped on the TypeScript side by a
+//! key store provider and handed over in plaintext together with the
+//! encrypted-column metadata. Cells are AEAD_AES_256_CBC_HMAC_SHA256
+//! ciphertext: `version(1) | MAC(32) | IV(16) | AES-256-CBC(PKCS7)`, with the
+//! MAC computed over `version | IV | ciphertext | version length`.
+
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+
+use aes::cipher::{block_padding::Pkcs7, BlockDecryptMut, KeyIvInit};
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use sha2::Sha256;
+use zeroize::Zeroizing;
+
+use crate::debug::warn_log;
+use crate::error::{MssqlError, Result};
+use crate::secret::Secret;
+use crate::variant::VariantValue;
+
+type HmacSha256 = Hmac<Sha256>;
+
+const ALGORITHM: &str = "AEAD_AES_256_CBC_HMAC_SHA256";
+const VERSION: u8 = 0x01;
+const MAC_LEN: usize = 32;
+const IV_LEN: usize = 16;
+const BLOCK_LEN: usize = 16;
+
+fn hmac(key: &[u8], parts: &[&[u8]]) -> [u8; 32] {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts any key length");
+    for part in parts {
+        mac.update(part);
+    }
+    mac.finalize().into_bytes().into()
+}
+
+/// Encryption, MAC and IV keys derived from one root CEK, wiped on drop.
+pub struct CellKey {
+    enc_key: Zeroizing<[u8; 32]>,
+    mac_key: Zeroizing<[u8; 32]>,
+    /// Only needed to encrypt; parameter encryption needs driver-level TDS support.
+    #[cfg_attr(not(test), allow(dead_code))]
+    iv_key: Zeroizing<[u8; 32]>,
+}
+
+impl CellKey {
+    pub fn derive(root: &[u8]) -> Result<Self> {
+        if root.len() != 32 {
+            return Err(MssqlError::Config(format!(
+                "Column encryption key must be 32 bytes, got {}",
+                root.len()
+            )));
+        }
+        let derive = |purpose: &str| {
+            let label = format!(
+                "Microsoft SQL Server cell {purpose} with encryption algorithm:{ALGORITHM} and key length:256"
+            );
+            let utf16: Vec<u8> = label.encode_utf16().flat_map(u16::to_le_bytes).collect();
+            Zeroizing::new(hmac(root, &[&utf16]))
+        };
+        Ok(Self {
+            enc_key: derive("encryption key"),
+            mac_key: derive("MAC key"),
+            iv_key: derive("IV key"),
+        })
+    }
+
+    /// Decrypt one cell. None if the MAC doesn't verify under this key.
+    pub fn decrypt(&self, cell: &[u8]) -> Option<Vec<u8>> {
+        let header = 1 + MAC_LEN + IV_LEN;
+        if cell.len() < header + BLOCK_LEN || cell[0] != VERSION || (cell.len() - header) % BLOCK_LEN != 0 {
+            return None;
+        }
+        let (mac, rest) = cell[1..].split_at(MAC_LEN);
+        let (iv, ciphertext) = rest.split_at(IV_LEN);
+        let mut verifier = HmacSha256::new_from_slice(&*self.mac_key).ok()?;
+        verifier.update(&[VERSION]);
+        verifier.update(iv);
+        verifier.update(ciphertext);
+        verifier.update(&[1]);
+        verifier.verify_slice(mac).ok()?;
+
+        cbc::Decryptor::<aes::Aes256>::new(&(*self.enc_key).into(), iv.into())
+            .decrypt_padded_vec_mut::<Pkcs7>(ciphertext)
+            .ok()
+    }
+
+    /// Encrypt with deterministic encryption (IV derived from the plaintext).
+    #[cfg(test)]
+    pub fn encrypt_deterministic(&self, plain: &[u8]) -> Vec<u8> {
+        use aes::cipher::BlockEncryptMut;
+        let iv: [u8; IV_LEN] = hmac(&*self.iv_key, &[plain])[..IV_LEN].try_into().unwrap();
+        let ciphertext = cbc::Encryptor::<aes::Aes256>::new(&(*self.enc_key).into(), &iv.into())
+            .encrypt_padded_vec_mut::<Pkcs7>(plain);
+        let mut cell = vec![VERSION];
+        cell.extend(hmac(&*self.mac_key, &[&[VERSION], &iv, &ciphertext, &[1]]));
+        cell.extend(iv);
+        cell.extend(ciphertext);
+        cell
+    }
+}
+
+/// Plaintext key and metadata sent from TypeScript by `mssql_ae_configure`.
+#[derive(Deserialize)]
+pub struct KeyringConfig {
+    pub keys: Vec<KeyConfig>,
+    pub columns: Vec<EncryptedColumn>,
+}
+
+#[derive(Deserialize)]
+pub struct KeyConfig {
+    pub id: i32,
+    /// Base64 plaintext CEK.
+    pub key: Secret,
+}
+
+/// An encrypted column and the plaintext type of its values.
+#[derive(Deserialize)]
+pub struct EncryptedColumn {
+    /// Schema-qualified table, for messages.
+    #[serde(default)]
+    pub table: String,
+    pub name: String,
+    #[serde(rename = "type")]
+    pub type_name: String,
+    #[serde(default)]
+    pub precision: u8,
+    #[serde(default)]
+    pub scale: u8,
+    pub cek_id: i32,
+}
+
+impl EncryptedColumn {
+    /// Whether cells of both columns decrypt and decode the same way.
+    fn same_encryption(&self, other: &Self) -> bool {
+        self.cek_id == other.cek_id
+            && self.type_name.eq_ignore_ascii_case(&other.type_name)
+            && self.precision == other.precision
+            && self.scale == other.scale
+    }
+}
+
+/// Unwrapped CEKs plus the encrypted columns they protect, by lowercased
+/// column name. Result columns only carry names, so a name shared by columns
+/// with different keys or types maps to None and is left encrypted.
+pub struct Keyring {
+    keys: HashMap<i32, CellKey>,
+    columns: HashMap<String, Option<EncryptedColumn>>,
+}
+
+impl std::fmt::Debug for Keyring {
+    // Never print key material
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Keyring")
+            .field("keys", &self.keys.len())
+            .field("columns", &self.columns.len())
+            .finish()
+    }
+}
+
+impl Keyring {
+    pub fn from_config(cfg: KeyringConfig) -> Result<Self> {
+        let mut keys = HashMap::with_capacity(cfg.keys.len());
+        for k in cfg.keys {
+            let root = base64::Engine::decode(&base64::engine::general_purpose::STANDARD, k.key.expose())
+                .map(Zeroizing::new)
+                .map_err(|e| MssqlError::Config(format!("Invalid column encryption key: {e}")))?;
+            keys.insert(k.id, CellKey::derive(&root)?);
+        }
+        let mut columns: HashMap<String, Option<EncryptedColumn>> = HashMap::new();
+        for col in cfg.columns {
+            match columns.entry(col.name.to_lowercase()) {
+                Entry::Vacant(entry) => {
+                    entry.insert(Some(col));
+                }
+                Entry::Occupied(mut entry) => {
+                    let Some(seen) = entry.get() else { continue };
+                    if !seen.same_encryption(&col) {
+                        warn_log!(
+                            "Encrypted column {} is in {} and {} with different keys or types; \
+                             its values are left encrypted. Limit the tables to decrypt",
+                            col.name,
+                            seen.table,
+                            col.table
+                        );
+                        entry.insert(None);
+                    }
+                }
+            }
+        }
+        Ok(Self { keys, columns })
+    }
+
+    /// Decrypt a result cell for `column` with that column's key. None if the
+    /// column isn't encrypted, its name is ambiguous, or the MAC doesn't verify.
+    pub fn decrypt_column(
+        &self,
+        column: &str,
+        cell: &[u8],
+    ) -> Option<(&'static str, VariantValue)> {
+        let col = self.columns.get(&column.to_lowercase())?.as_ref()?;
+        let plain = self.keys.get(&col.cek_id)?.decrypt(cell)?;
+        crate::variant::decode(&plaintext_payload(col, &plain)?)
+    }
+}
+
+/// Wrap a decrypted value in a sql_variant header so the variant decoder can
+/// read it. AE serializes integer types and bit as 8-byte little-endian
+/// values; the decoder reads only the leading bytes it needs.
+fn plaintext_payload(col: &EncryptedColumn, plain: &[u8]) -> Option<Vec<u8>> {
+    let (base, props): (u8, Vec<u8>) = match col.type_name.to_lowercase().as_str() {
+        "tinyint" => (0x30, vec![]),
+        "smallint" => (0x34, vec![]),
+        "int" => (0x38, vec![]),
+        "bigint" => (0x7F, vec![]),
+        "real" => (0x3B, vec![]),
+        "float" => (0x3E, vec![]),
+        "bit" => (0x32, vec![]),
+        "money" => (0x3C, vec![]),
+        "smallmoney" => (0x7A, vec![]),
+        "decimal" => (0x6A, vec![col.precision, col.scale]),
+        "numeric" => (0x6C, vec![col.precision, col.scale]),
+        "uniqueidentifier" => (0x24, vec![]),
+        "date" => (0x28, vec![]),
+        "time" => (0x29, vec![col.scale]),
+        "datetime2" => (0x2A, vec![col.scale]),
+        "datetimeoffset" => (0x2B, vec![col.scale]),
+        "datetime" => (0x3D, vec![]),
+        "smalldatetime" => (0x3A, vec![]),
+        // Collation and max length are unused by the decoder
+        "varchar" => (0xA7, vec![0; 7]),
+        "char" => (0xAF, vec![0; 7]),
+        "nvarchar" => (0xE7, vec![0; 7]),
+        "nchar" => (0xEF, vec![0; 7]),
+        "varbinary" => (0xA5, vec![0; 2]),
+        "binary" => (0xAD, vec![0; 2]),
+        _ => return None,
+    };
+    let mut payload = vec![base, props.len() as u8];
+    payload.extend(props);
+    payload.extend_from_slice(plain);
+    Some(payload)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mssql_client::SqlValue;
+
+    const ROOT: [u8; 32] = [7; 32];
+
+    fn keyring(columns: Vec<EncryptedColumn>) -> Keyring {
+        let key = base64::Engine::encode(&base64::engine::general_purpose::STANDARD, ROOT);
+        Keyring::from_config(KeyringConfig {
+            keys: vec![KeyConfig { id: 1, key: key.into() }],
+            columns,
+        })
+        .unwrap()
+    }
+
+    fn column(name: &str, type_name: &str) -> EncryptedColumn {
+        EncryptedColumn {
+            table: "dbo.Patients".into(),
+            name: name.into(),
+            type_name: type_name.into(),
+            precision: 0,
+            scale: 0,
+            cek_id: 1,
+        }
+    }
+
+    #[test]
+    fn round_trips_and_rejects_tampering() {
+        let key = CellKey::derive(&ROOT).unwrap();
+        let cell = key.encrypt_deterministic(b"secret");
+        assert_eq!(cell.len(), 1 + 32 + 16 + 16);
+        assert_eq!(key.decrypt(&cell).unwrap(), b"secret");
+        // Deterministic encryption yields identical ciphertext
+        assert_eq!(key.encrypt_deterministic(b"secret"), cell);
+
+        let mut tampered = cell.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(key.decrypt(&tampered).is_none());
+        let other = CellKey::derive(&[8; 32]).unwrap();
+        assert!(other.decrypt(&cell).is_none());
+    }
+
+    #[test]
+    fn rejects_short_root_key() {
+        assert!(CellKey::derive(&[1; 16]).is_err());
+    }
+
+    #[test]
+    fn decrypts_typed_columns() {
+        let ring = keyring(vec![column("Ssn", "nvarchar"), column("Salary", "int")]);
+        let key = CellKey::derive(&ROOT).unwrap();
+
+        let ssn: Vec<u8> = "123-45-6789".encode_utf16().flat_map(u16::to_le_bytes).collect();
+        let cell = key.encrypt_deterministic(&ssn);
+        match ring.decrypt_column("SSN", &cell) {
+            Some(("nvarchar", VariantValue::Sql(SqlValue::String(s)))) => assert_eq!(s, "123-45-6789"),
+            _ => panic!("expected nvarchar"),
+        }
+
+        // Integers are normalized to 8 bytes
+        let cell = key.encrypt_deterministic(&85_000i64.to_le_bytes());
+        assert!(matches!(
+            ring.decrypt_column("Salary", &cell),
+            Some(("int", VariantValue::Sql(SqlValue::Int(85_000))))
+        ));
+        assert!(ring.decrypt_column("Other", &cell).is_none());
+    }
+
+    #[test]
+    fn leaves_ambiguous_names_encrypted() {
+        let key = CellKey::derive(&ROOT).unwrap();
+        let cell = key.encrypt_deterministic(&[0x31, 0]);
+        let other_table = |type_name| EncryptedColumn {
+            table: "dbo.Staff".into(),
+            ..column("Ssn", type_name)
+        };
+
+        // The same key and type in another table is still one column
+        let ring = keyring(vec![column("Ssn", "nvarchar"), other_table("nvarchar")]);
+        assert!(ring.decrypt_column("Ssn", &cell).is_some());
+
+        let ring = keyring(vec![column("Ssn", "nvarchar"), other_table("varchar")]);
+        assert!(ring.decrypt_column("Ssn", &cell).is_none());
+    }
+}
@@ -0,0 +1,279 @@
+use mssql_client::{Client, Ready, Row, SqlValue, ToSql};
+use serde::Deserialize;
+
+use crate::bulk::bracket_escape;
+use crate::error::{MssqlError, Result};
+use crate::query::{param_to_boxed, SerializedParam};
+
+/// Request to open a blob: one varbinary(max) value located by its row key.
+#[derive(Deserialize)]
+pub struct BlobOpenRequest {
+    pub table: String,
+    pub column: String,
+    /// Column → value pairs that identify exactly one row.
+    pub key: serde_json::Map<String, serde_json::Value>,
+}
+
+/// An open blob. Reads fetch `SUBSTRING(column, offset, len)` windows of the
+/// row, so large values can be streamed on platforms without FILESTREAM.
+#[derive(Clone)]
+pub struct BlobHandle {
+    pub conn_id: u64,
+    /// Total size in bytes (`DATALENGTH`), 0 for NULL.
+    pub length: u64,
+    chunk_sql: String,
+    key_params: Vec<SerializedParam>,
+}
+
+impl BlobHandle {
+    /// Locate the row and measure the value. Fails unless the key matches one row.
+    pub async fn open(
+        client: &mut Client<Ready>,
+        conn_id: u64,
+        req: &BlobOpenRequest,
+    ) -> Result<Self> {
+        let (length_sql, chunk_sql, key_params) = build_blob_sql(req)?;
+        let owned_values = key_params
+            .iter()
+            .map(param_to_boxed)
+            .collect::<Result<Vec<_>>>()?;
+        let param_refs: Vec<&(dyn ToSql + Sync)> = owned_values
+            .iter()
+            .map(|v| &**v as &(dyn ToSql + Sync))
+            .collect();
+
+        let stream = client
+            .query(&length_sql, &param_refs)
+            .await
+            .map_err(MssqlError::from)?;
+        let rows: Vec<Row> = stream
+            .into_iter()
+            .collect::<std::result::Result<_, _>>()
+            .map_err(MssqlError::from)?;
+
+        let length = match rows.as_slice() {
+            [row] => match row.get_raw(0) {
+                Some(SqlValue::BigInt(n)) => n.max(0) as u64,
+                Some(SqlValue::Int(n)) => n.max(0) as u64,
+                _ => 0,
+            },
+            [] => return Err(MssqlError::Query(format!("Blob row not found in {}", req.table))),
+            _ => {
+                return Err(MssqlError::Query(format!(
+                    "Blob key matches more than one row in {}",
+                    req.table
+                )))
+            }
+        };
+
+        Ok(Self {
+            conn_id,
+            length,
+            chunk_sql,
+            key_params,
+        })
+    }
+
+    /// Read up to `len` bytes starting at the 0-based `offset`.
+    pub async fn read(&self, client: &mut Client<Ready>, offset: u64, len: u64) -> Result<Vec<u8>> {
+        if offset >= self.length || len == 0 {
+            return Ok(Vec::new());
+        }
+        let len = len.min(self.length - offset);
+
+        let mut owned_values = self
+            .key_params
+            .iter()
+            .map(param_to_boxed)
+            .collect::<Result<Vec<_>>>()?;
+        // SUBSTRING is 1-based
+        owned_values.push(Box::new(offset as i64 + 1));
+        owned_values.push(Box::new(len as i64));
+        let param_refs: Vec<&(dyn ToSql + Sync)> = owned_values
+            .iter()
+            .map(|v| &**v as &(dyn ToSql + Sync))
+            .collect();
+
+        let stream = client
+            .query(&self.chunk_sql, &param_refs)
+            .await
+            .map_err(MssqlError::from)?;
+        for result in stream {
+            let row: Row = result.map_err(MssqlError::from)?;
+            if let Some(SqlValue::Binary(bytes)) = row.get_raw(0) {
+                return Ok(bytes.to_vec());
+            }
+        }
+        Ok(Vec::new())
+    }
+}
+
+/// Bracket-quote a possibly schema-qualified name (`dbo.Files` → `[dbo].[Files]`).
+/// Parts already quoted with `[]` or `""` may contain dots, so
+/// `[dbo].[my.table]` is still two parts; an empty part (`db..Files`) is
+/// kept for the default schema.
+pub(crate) fn quote_object_name(name: &str) -> Result<String> {
+    let invalid = || MssqlError::Query(format!("Invalid object name: {name}"));
+    let mut parts = Vec::new();
+    let mut chars = name.chars().peekable();
+    loop {
+        let mut part = String::new();
+        let close = match chars.peek() {
+            Some('[') => Some(']'),
+            Some('"') => Some('"'),
+            _ => None,
+        };
+        if let Some(close) = close {
+            chars.next();
+            loop {
+                match chars.next() {
+                    // A doubled closing quote is an escaped one
+                    Some(c) if c == close && chars.peek() == Some(&close) => {
+                        chars.next();
+                        part.push(c);
+                    }
+                    Some(c) if c == close => break,
+                    Some(c) => part.push(c),
+                    None => return Err(invalid()),
+                }
+            }
+        } else {
+            while let Some(c) = chars.next_if(|&c| c != '.') {
+                part.push(c);
+            }
+        }
+        parts.push(part);
+        match chars.next() {
+            None => break,
+            Some('.') => {}
+            Some(_) => return Err(invalid()),
+        }
+    }
+    let ends_empty = parts[0].is_empty() || parts[parts.len() - 1].is_empty();
+    if parts.len() > 4 || ends_empty {
+        return Err(invalid());
+    }
+    Ok(parts
+        .iter()
+        .map(|part| match part.as_str() {
+            "" => String::new(),
+            part => format!("[{}]", part.replace(']', "]]")),
+        })
+        .collect::<Vec<_>>()
+        .join("."))
+}
+
+/// Build the DATALENGTH and SUBSTRING statements. Key values bind to
+/// @P1..@Pn; the chunk query appends offset and length as @Pn+1, @Pn+2.
+fn build_blob_sql(req: &BlobOpenRequest) -> Result<(String, String, Vec<SerializedParam>)> {
+    let (filter, key_params) = key_filter(&req.key)?;
+    let n = key_params.len();
+    let table = quote_object_name(&req.table)?;
+    let column = bracket_escape(&req.column);
+
+    let length_sql = format!("SELECT DATALENGTH({column}) FROM {table} WHERE {filter}");
+    let chunk_sql = format!(
+        "SELECT SUBSTRING({column}, @P{}, @P{}) FROM {table} WHERE {filter}",
+        n + 1,
+        n + 2
+    );
+    Ok((length_sql, chunk_sql, key_params))
+}
+
+/// `WHERE` condition for a row key, with its values bound to @P1..@Pn.
+pub(crate) fn key_filter(
+    key: &serde_json::Map<String, serde_json::Value>,
+) -> Result<(String, Vec<SerializedParam>)> {
+    if key.is_empty() {
+        return Err(MssqlError::Query(
+            "Row key must name at least one column".into(),
+        ));
+    }
+
+    let mut conditions = Vec::with_capacity(key.len());
+    let mut key_params = Vec::with_capacity(key.len());
+    for (i, (col, value)) in key.iter().enumerate() {
+        conditions.push(format!("{} = @P{}", bracket_escape(col), i + 1));
+        key_params.push(SerializedParam {
+            name: format!("k{i}"),
+            value: value.clone(),
+            param_type: None,
+            output: false,
+            precision: None,
+            scale: None,
+            srid: None,
+            length: None,
+        });
+    }
+    Ok((conditions.join(" AND "), key_params))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(table: &str, key: serde_json::Value) -> BlobOpenRequest {
+        BlobOpenRequest {
+            table: table.into(),
+            column: "Data".into(),
+            key: key.as_object().unwrap().clone(),
+        }
+    }
+
+    #[test]
+    fn quotes_schema_qualified_table() {
+        assert_eq!(quote_object_name("dbo.Files").unwrap(), "[dbo].[Files]");
+        assert_eq!(quote_object_name("Files").unwrap(), "[Files]");
+    }
+
+    #[test]
+    fn keeps_dots_inside_quoted_parts() {
+        assert_eq!(
+            quote_object_name("[dbo].[my.table]").unwrap(),
+            "[dbo].[my.table]"
+        );
+        assert_eq!(
+            quote_object_name(r#""dbo"."my.table""#).unwrap(),
+            "[dbo].[my.table]"
+        );
+        assert_eq!(quote_object_name("[a]]b].Files").unwrap(), "[a]]b].[Files]");
+        assert_eq!(quote_object_name("db..Files").unwrap(), "[db]..[Files]");
+        assert_eq!(quote_object_name("#staging").unwrap(), "[#staging]");
+    }
+
+    #[test]
+    fn rejects_malformed_object_names() {
+        for name in [
+            "",
+            "[dbo.Files",
+            "dbo.",
+            ".Files",
+            "[dbo]x.Files",
+            "a.b.c.d.e",
+        ] {
+            assert!(quote_object_name(name).is_err(), "{name}");
+        }
+    }
+
+    #[test]
+    fn builds_parameterized_blob_sql() {
+        let req = request("dbo.Files", serde_json::json!({ "Id": 7, "Rev": "a" }));
+        let (length_sql, chunk_sql, params) = build_blob_sql(&req).unwrap();
+        assert_eq!(
+            length_sql,
+            "SELECT DATALENGTH([Data]) FROM [dbo].[Files] WHERE [Id] = @P1 AND [Rev] = @P2"
+        );
+        assert_eq!(
+            chunk_sql,
+            "SELECT SUBSTRING([Data], @P3, @P4) FROM [dbo].[Files] WHERE [Id] = @P1 AND [Rev] = @P2"
+        );
+        assert_eq!(params.len(), 2);
+        assert_eq!(params[0].value, serde_json::json!(7));
+    }
+
+    #[test]
+    fn rejects_empty_key() {
+        let req = request("Files", serde_json::json!({}));
+        assert!(build_blob_sql(&req).is_err());
+    }
+}
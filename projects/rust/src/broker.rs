@@ -0,0 +1,279 @@
+//! Service Broker messaging: long-poll `WAITFOR (RECEIVE ...)` on a queue,
+//! plus `BEGIN DIALOG` / `SEND` / `END CONVERSATION` helpers.
+
+use mssql_client::{Client, Ready, Row, SqlValue, ToSql};
+use serde::{Deserialize, Serialize};
+
+use crate::blob::quote_object_name;
+use crate::bulk::bracket_escape;
+use crate::error::{MssqlError, Result};
+
+/// Service Broker's built-in message type for untyped messages.
+const DEFAULT_MESSAGE_TYPE: &str = "DEFAULT";
+
+/// One message taken off a queue.
+#[derive(Serialize)]
+pub struct BrokerMessage {
+    pub conversation_handle: String,
+    pub message_type: String,
+    pub service: String,
+    pub contract: String,
+    pub sequence: i64,
+    /// Base64 of `message_body`, or null for bodiless messages such as
+    /// `EndDialog`.
+    pub body: Option<String>,
+}
+
+/// Request to send one message, beginning a dialog unless `conversation`
+/// names an existing one.
+#[derive(Deserialize)]
+pub struct SendRequest {
+    #[serde(default)]
+    pub conversation: Option<String>,
+    #[serde(default)]
+    pub from_service: Option<String>,
+    #[serde(default)]
+    pub to_service: Option<String>,
+    #[serde(default)]
+    pub contract: Option<String>,
+    /// Dialog lifetime in seconds (server default: max int).
+    #[serde(default)]
+    pub lifetime_s: Option<u32>,
+    #[serde(default)]
+    pub encryption: bool,
+    #[serde(default)]
+    pub message_type: Option<String>,
+    /// Text body, sent as nvarchar (UTF-16LE on the wire).
+    #[serde(default)]
+    pub text: Option<String>,
+    /// Base64 binary body, sent as-is.
+    #[serde(default)]
+    pub binary: Option<String>,
+}
+
+/// Request to end a conversation, optionally with an error or `CLEANUP`.
+#[derive(Deserialize)]
+pub struct EndRequest {
+    pub conversation: String,
+    #[serde(default)]
+    pub error_code: Option<i32>,
+    #[serde(default)]
+    pub error_description: Option<String>,
+    #[serde(default)]
+    pub cleanup: bool,
+}
+
+/// Wait up to `timeout_ms` for messages on `queue`. Like a bare `RECEIVE`,
+/// this takes every ready message of one conversation group. Empty on timeout.
+pub async fn receive(
+    client: &mut Client<Ready>,
+    queue: &str,
+    timeout_ms: u64,
+) -> Result<Vec<BrokerMessage>> {
+    let sql = format!(
+        "WAITFOR (RECEIVE conversation_handle, message_type_name, service_name, \
+         service_contract_name, message_sequence_number, message_body FROM {}), TIMEOUT {}",
+        quote_object_name(queue)?,
+        timeout_ms.min(i32::MAX as u64)
+    );
+    let stream = client.query(&sql, &[]).await.map_err(MssqlError::from)?;
+    let mut messages = Vec::new();
+    for row in stream {
+        let row: Row = row.map_err(MssqlError::from)?;
+        let text = |i| match row.get_raw(i) {
+            Some(SqlValue::String(s)) => s,
+            _ => String::new(),
+        };
+        messages.push(BrokerMessage {
+            conversation_handle: match row.get_raw(0) {
+                Some(SqlValue::Uuid(u)) => u.to_string(),
+                _ => String::new(),
+            },
+            message_type: text(1),
+            service: text(2),
+            contract: text(3),
+            sequence: match row.get_raw(4) {
+                Some(SqlValue::BigInt(n)) => n,
+                _ => 0,
+            },
+            body: match row.get_raw(5) {
+                Some(SqlValue::Binary(bytes)) => {
+                    use base64::Engine;
+                    Some(base64::engine::general_purpose::STANDARD.encode(&bytes))
+                }
+                _ => None,
+            },
+        });
+    }
+    Ok(messages)
+}
+
+/// Send one message and return the conversation handle it went out on.
+pub async fn send(client: &mut Client<Ready>, req: &SendRequest) -> Result<String> {
+    let body: Option<Box<dyn ToSql + Sync>> = match (&req.text, &req.binary) {
+        (Some(_), Some(_)) => {
+            return Err(MssqlError::Query(
+                "Broker message body must be text or binary, not both".into(),
+            ))
+        }
+        (Some(text), None) => Some(Box::new(text.clone())),
+        (None, Some(b64)) => {
+            let bytes = base64::Engine::decode(&base64::engine::general_purpose::STANDARD, b64)
+                .map_err(|e| MssqlError::Query(format!("Invalid base64 body: {e}")))?;
+            Some(Box::new(bytes))
+        }
+        (None, None) => None,
+    };
+    let conversation = match &req.conversation {
+        Some(handle) => Some(parse_handle(handle)?),
+        None => None,
+    };
+
+    let mut params: Vec<&(dyn ToSql + Sync)> = Vec::new();
+    let sql = build_send_sql(req, conversation.is_some(), body.is_some())?;
+    if let Some(handle) = &conversation {
+        params.push(handle);
+    } else if let Some(to) = &req.to_service {
+        params.push(to);
+    }
+    if let Some(body) = &body {
+        params.push(&**body);
+    }
+
+    let rows = client.query(&sql, &params).await.map_err(MssqlError::from)?;
+    for row in rows {
+        let row: Row = row.map_err(MssqlError::from)?;
+        if let Some(SqlValue::Uuid(u)) = row.get_raw(0) {
+            return Ok(u.to_string());
+        }
+    }
+    Err(MssqlError::Query("SEND did not return a conversation handle".into()))
+}
+
+/// End a conversation.
+pub async fn end(client: &mut Client<Ready>, req: &EndRequest) -> Result<()> {
+    let handle = parse_handle(&req.conversation)?;
+    let sql = build_end_sql(req)?;
+    let description = req.error_description.clone().unwrap_or_default();
+    let mut params: Vec<&(dyn ToSql + Sync)> = vec![&handle];
+    if req.error_code.is_some() {
+        params.push(&description);
+    }
+    client.execute(&sql, &params).await.map_err(MssqlError::from)?;
+    Ok(())
+}
+
+fn parse_handle(handle: &str) -> Result<uuid::Uuid> {
+    handle
+        .parse()
+        .map_err(|e| MssqlError::Query(format!("Invalid conversation handle: {e}")))
+}
+
+/// `SEND` on @P1 (an existing handle) or on a dialog begun from
+/// `from_service` to @P1 (the target service name); the body, if any, is the
+/// last parameter. Selects the handle so callers can continue the dialog.
+fn build_send_sql(req: &SendRequest, existing: bool, has_body: bool) -> Result<String> {
+    let mut sql = String::from("DECLARE @h uniqueidentifier");
+    if existing {
+        sql.push_str(" = @P1;");
+    } else {
+        let (Some(from), Some(_)) = (&req.from_service, &req.to_service) else {
+            return Err(MssqlError::Query(
+                "Broker send needs a conversation or both from_service and to_service".into(),
+            ));
+        };
+        sql.push_str(&format!(
+            "; BEGIN DIALOG @h FROM SERVICE {} TO SERVICE @P1",
+            bracket_escape(from)
+        ));
+        if let Some(contract) = &req.contract {
+            sql.push_str(&format!(" ON CONTRACT {}", bracket_escape(contract)));
+        }
+        sql.push_str(&format!(
+            " WITH ENCRYPTION = {}",
+            if req.encryption { "ON" } else { "OFF" }
+        ));
+        if let Some(lifetime) = req.lifetime_s {
+            sql.push_str(&format!(", LIFETIME = {lifetime}"));
+        }
+        sql.push(';');
+    }
+
+    let message_type = req.message_type.as_deref().unwrap_or(DEFAULT_MESSAGE_TYPE);
+    sql.push_str(&format!(
+        " SEND ON CONVERSATION @h MESSAGE TYPE {}",
+        bracket_escape(message_type)
+    ));
+    if has_body {
+        sql.push_str(" (@P2)");
+    }
+    sql.push_str("; SELECT @h;");
+    Ok(sql)
+}
+
+fn build_end_sql(req: &EndRequest) -> Result<String> {
+    match (req.error_code, req.cleanup) {
+        (Some(_), true) => Err(MssqlError::Query(
+            "END CONVERSATION takes an error or CLEANUP, not both".into(),
+        )),
+        (Some(code), false) if code <= 0 => Err(MssqlError::Query(
+            "END CONVERSATION error code must be positive".into(),
+        )),
+        (Some(code), false) => Ok(format!(
+            "END CONVERSATION @P1 WITH ERROR = {code} DESCRIPTION = @P2"
+        )),
+        (None, true) => Ok("END CONVERSATION @P1 WITH CLEANUP".into()),
+        (None, false) => Ok("END CONVERSATION @P1".into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn send_request(json: &str) -> SendRequest {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn send_on_new_dialog() {
+        let req = send_request(
+            r#"{"from_service":"//App/Client","to_service":"//App/Server",
+                "contract":"//App/Contract","message_type":"//App/Request","lifetime_s":600}"#,
+        );
+        assert_eq!(
+            build_send_sql(&req, false, true).unwrap(),
+            "DECLARE @h uniqueidentifier; BEGIN DIALOG @h FROM SERVICE [//App/Client] \
+             TO SERVICE @P1 ON CONTRACT [//App/Contract] WITH ENCRYPTION = OFF, LIFETIME = 600; \
+             SEND ON CONVERSATION @h MESSAGE TYPE [//App/Request] (@P2); SELECT @h;"
+        );
+    }
+
+    #[test]
+    fn send_on_existing_conversation() {
+        let req = send_request(r#"{"conversation":"8f2e4c1a-0000-0000-0000-000000000001"}"#);
+        assert_eq!(
+            build_send_sql(&req, true, false).unwrap(),
+            "DECLARE @h uniqueidentifier = @P1; \
+             SEND ON CONVERSATION @h MESSAGE TYPE [DEFAULT]; SELECT @h;"
+        );
+        assert!(build_send_sql(&send_request(r#"{"to_service":"x"}"#), false, false).is_err());
+    }
+
+    #[test]
+    fn end_variants() {
+        let end = |json: &str| build_end_sql(&serde_json::from_str(json).unwrap());
+        let handle = r#""conversation":"8f2e4c1a-0000-0000-0000-000000000001""#;
+        assert_eq!(end(&format!("{{{handle}}}")).unwrap(), "END CONVERSATION @P1");
+        assert_eq!(
+            end(&format!(r#"{{{handle},"cleanup":true}}"#)).unwrap(),
+            "END CONVERSATION @P1 WITH CLEANUP"
+        );
+        assert_eq!(
+            end(&format!(r#"{{{handle},"error_code":50001,"error_description":"bad"}}"#)).unwrap(),
+            "END CONVERSATION @P1 WITH ERROR = 50001 DESCRIPTION = @P2"
+        );
+        assert!(end(&format!(r#"{{{handle},"error_code":1,"cleanup":true}}"#)).is_err());
+        assert!(end(&format!(r#"{{{handle},"error_code":0}}"#)).is_err());
+    }
+}
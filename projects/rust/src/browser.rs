@@ -0,0 +1,165 @@
+//! SQL Server Browser (SSRP) lookup of a named instance's TCP port over
+//! UDP 1434, with a short-lived cache so pools and reconnects don't query
+//! the browser for every connection.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use lazy_static::lazy_static;
+use tokio::net::UdpSocket;
+
+use crate::debug::debug_log;
+use crate::error::{MssqlError, Result};
+
+const BROWSER_PORT: u16 = 1434;
+/// Upper bound on the wait for a browser response.
+pub const BROWSER_TIMEOUT: Duration = Duration::from_secs(2);
+/// Resolved ports are reused for this long.
+const CACHE_TTL: Duration = Duration::from_secs(10 * 60);
+/// CLNT_UCAST_INST: ask the server for a single instance's details.
+const CLNT_UCAST_INST: u8 = 0x04;
+/// SVR_RESP: header byte of every browser response.
+const SVR_RESP: u8 = 0x05;
+
+lazy_static! {
+    static ref PORTS: Mutex<HashMap<String, (u16, Instant)>> = Mutex::new(HashMap::new());
+}
+
+fn cache_key(host: &str, instance: &str) -> String {
+    format!("{}\\{}", host.to_lowercase(), instance.to_lowercase())
+}
+
+/// Drop a cached port, e.g. after connecting to it failed.
+pub fn forget(host: &str, instance: &str) {
+    PORTS.lock().unwrap().remove(&cache_key(host, instance));
+}
+
+/// TCP port of `instance` on `host`, from the cache or the SQL Browser.
+pub async fn resolve_port(host: &str, instance: &str, timeout: Duration) -> Result<u16> {
+    let key = cache_key(host, instance);
+    if let Some((port, at)) = PORTS.lock().unwrap().get(&key) {
+        if at.elapsed() < CACHE_TTL {
+            return Ok(*port);
+        }
+    }
+
+    debug_log!(
+        target: "mssqlts::pool",
+        "Resolving instance {}\\{} via SQL Browser",
+        host,
+        instance
+    );
+    let port = tokio::time::timeout(timeout, query(host, instance))
+        .await
+        .map_err(|_| {
+            MssqlError::Connection(format!(
+                "SQL Browser on {host} did not answer for instance {instance} within {}ms; \
+                 is the SQL Server Browser service running and UDP 1434 reachable? \
+                 Alternatively set an explicit port",
+                timeout.as_millis()
+            ))
+        })??;
+    debug_log!(target: "mssqlts::pool", "Instance {}\\{} listens on port {}", host, instance, port);
+    PORTS.lock().unwrap().insert(key, (port, Instant::now()));
+    Ok(port)
+}
+
+async fn query(host: &str, instance: &str) -> Result<u16> {
+    let addr: SocketAddr = tokio::net::lookup_host((host, BROWSER_PORT))
+        .await
+        .map_err(|e| MssqlError::Connection(format!("Cannot resolve {host}: {e}")))?
+        .next()
+        .ok_or_else(|| MssqlError::Connection(format!("Cannot resolve {host}")))?;
+    let bind: SocketAddr = if addr.is_ipv4() {
+        ([0, 0, 0, 0], 0).into()
+    } else {
+        ([0u16; 8], 0).into()
+    };
+    let io = |e: std::io::Error| MssqlError::Connection(format!("SQL Browser query failed: {e}"));
+    let socket = UdpSocket::bind(bind).await.map_err(io)?;
+
+    let mut request = Vec::with_capacity(instance.len() + 2);
+    request.push(CLNT_UCAST_INST);
+    request.extend_from_slice(instance.as_bytes());
+    request.push(0);
+    socket.send_to(&request, addr).await.map_err(io)?;
+
+    let mut buf = vec![0u8; 4096];
+    let len = socket.recv(&mut buf).await.map_err(io)?;
+    parse_response(&buf[..len], instance).ok_or_else(|| {
+        MssqlError::Connection(format!(
+            "SQL Browser on {host} reported no TCP port for instance {instance}"
+        ))
+    })
+}
+
+/// Extract the `tcp` port of `instance` from an SVR_RESP packet: a header
+/// byte, a little-endian u16 length, then `key;value;` pairs with `;;`
+/// ending each instance.
+fn parse_response(packet: &[u8], instance: &str) -> Option<u16> {
+    let (&header, rest) = packet.split_first()?;
+    if header != SVR_RESP || rest.len() < 2 {
+        return None;
+    }
+    let size = u16::from_le_bytes([rest[0], rest[1]]) as usize;
+    let data = rest.get(2..2 + size).unwrap_or(&rest[2..]);
+    let text = String::from_utf8_lossy(data);
+
+    text.split(";;").find_map(|entry| {
+        let fields: Vec<&str> = entry.split(';').collect();
+        let value = |key: &str| {
+            fields
+                .chunks(2)
+                .find(|pair| pair[0].eq_ignore_ascii_case(key))
+                .and_then(|pair| pair.get(1).copied())
+        };
+        value("InstanceName")
+            .filter(|name| name.eq_ignore_ascii_case(instance))
+            .and(value("tcp"))
+            .and_then(|port| port.parse().ok())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn packet(text: &str) -> Vec<u8> {
+        let mut p = vec![SVR_RESP];
+        p.extend_from_slice(&(text.len() as u16).to_le_bytes());
+        p.extend_from_slice(text.as_bytes());
+        p
+    }
+
+    #[test]
+    fn parses_instance_port() {
+        let p = packet(
+            "ServerName;SQL01;InstanceName;SQLEXPRESS;IsClustered;No;Version;16.0.1000.6;tcp;49172;;",
+        );
+        assert_eq!(parse_response(&p, "sqlexpress"), Some(49172));
+        assert_eq!(parse_response(&p, "OTHER"), None);
+    }
+
+    #[test]
+    fn rejects_instances_without_tcp() {
+        let p = packet("ServerName;SQL01;InstanceName;PIPES;IsClustered;No;np;\\\\SQL01\\pipe\\sql\\query;;");
+        assert_eq!(parse_response(&p, "PIPES"), None);
+        assert_eq!(parse_response(&[0x01, 0, 0], "X"), None);
+        assert_eq!(parse_response(&[], "X"), None);
+    }
+
+    #[test]
+    fn cache_roundtrip() {
+        PORTS
+            .lock()
+            .unwrap()
+            .insert(cache_key("Host", "Inst"), (50000, Instant::now()));
+        let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        let port = rt.block_on(resolve_port("host", "INST", BROWSER_TIMEOUT));
+        assert_eq!(port.unwrap(), 50000);
+        forget("HOST", "inst");
+        assert!(PORTS.lock().unwrap().get(&cache_key("host", "inst")).is_none());
+    }
+}
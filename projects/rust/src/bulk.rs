@@ -44,6 +44,7 @@ pub async fn execute_bulk(
     let col_names: Vec<&str> = req.columns.iter().map(|c| c.name.as_str()).collect();
 
     debug_log!(
+        target: "mssqlts::query",
         "Bulk insert: table={}, columns={}, rows={}, batch_size={}",
         req.table,
         col_names.len(),
@@ -64,7 +65,11 @@ pub async fn execute_bulk(
         total_affected += affected as u64;
     }
 
-    debug_log!("Bulk insert complete: {} rows affected", total_affected);
+    debug_log!(
+        target: "mssqlts::query",
+        "Bulk insert complete: {} rows affected",
+        total_affected
+    );
     Ok(total_affected)
 }
 
@@ -151,7 +156,7 @@ fn value_to_literal(value: &serde_json::Value, col_type: &str) -> Result<String>
 }
 
 /// Bracket-escape a SQL identifier.
-fn bracket_escape(name: &str) -> String {
+pub(crate) fn bracket_escape(name: &str) -> String {
     // Remove existing brackets and re-wrap
     let clean = name.trim_start_matches('[').trim_end_matches(']');
     format!("[{}]", clean.replace(']', "]]"))
@@ -0,0 +1,521 @@
+//! Catalog introspection: table schemas (columns, keys, indexes), object,
+//! schema and database listings from the `sys.*` views, as structured JSON.
+
+use std::collections::HashMap;
+
+use mssql_client::{Client, Ready, Row, SqlValue, ToSql};
+use serde::{Deserialize, Serialize};
+
+use crate::bulk::bracket_escape;
+use crate::error::{MssqlError, Result};
+
+/// Columns, keys and indexes of one table or view.
+#[derive(Serialize)]
+pub struct TableSchema {
+    pub schema: String,
+    pub name: String,
+    pub columns: Vec<Column>,
+    pub primary_key: Option<Index>,
+    pub foreign_keys: Vec<ForeignKey>,
+    pub indexes: Vec<Index>,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Column {
+    pub name: String,
+    /// Declared type name, e.g. `nvarchar`, `decimal`, or an alias type's name.
+    #[serde(rename = "type")]
+    pub type_name: String,
+    /// Length in characters for (n)char/(n)varchar, bytes for binary types,
+    /// -1 for `max`; null for fixed-size types.
+    pub length: Option<i32>,
+    pub precision: Option<u8>,
+    pub scale: Option<u8>,
+    pub nullable: bool,
+    pub identity: bool,
+    pub computed: bool,
+    /// Default constraint expression, e.g. `(getdate())`.
+    pub default: Option<String>,
+    pub collation: Option<String>,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Index {
+    pub name: String,
+    /// `CLUSTERED`, `NONCLUSTERED`, `CLUSTERED COLUMNSTORE`, ...
+    pub kind: String,
+    pub unique: bool,
+    pub primary_key: bool,
+    /// Key columns in key order.
+    pub columns: Vec<IndexColumn>,
+    /// `INCLUDE` columns.
+    pub included: Vec<String>,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct IndexColumn {
+    pub name: String,
+    pub descending: bool,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct ForeignKey {
+    pub name: String,
+    pub columns: Vec<String>,
+    pub referenced_schema: String,
+    pub referenced_table: String,
+    pub referenced_columns: Vec<String>,
+    /// `NO_ACTION`, `CASCADE`, `SET_NULL` or `SET_DEFAULT`.
+    pub on_delete: String,
+    pub on_update: String,
+}
+
+/// Filter for [`list_objects`]. Empty fields match everything.
+#[derive(Deserialize, Default)]
+pub struct ObjectFilter {
+    #[serde(default)]
+    pub schema: Option<String>,
+    /// `LIKE` pattern on the object name.
+    #[serde(default)]
+    pub name_like: Option<String>,
+    /// Any of `table`, `view`, `procedure`, `function`, `synonym`, `sequence`.
+    #[serde(default)]
+    pub types: Vec<String>,
+}
+
+/// One user object.
+#[derive(Serialize)]
+pub struct DbObject {
+    pub schema: String,
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    pub created: String,
+    pub modified: String,
+}
+
+/// One database on the server.
+#[derive(Serialize)]
+pub struct Database {
+    pub name: String,
+    /// `ONLINE`, `RESTORING`, `RECOVERING`, `SUSPECT`, `OFFLINE`, ...
+    pub state: String,
+    /// Login that owns the database; null if the SID no longer maps to one.
+    pub owner: Option<String>,
+    /// `FULL`, `BULK_LOGGED` or `SIMPLE`.
+    pub recovery_model: String,
+    pub compatibility_level: i64,
+    pub collation: Option<String>,
+    pub read_only: bool,
+    pub created: String,
+}
+
+/// One schema in a database.
+#[derive(Serialize)]
+pub struct Schema {
+    pub name: String,
+    /// Owning database principal.
+    pub owner: Option<String>,
+    /// Built-in schema (`dbo`, `sys`, `guest`, `INFORMATION_SCHEMA`,
+    /// `db_*` role schemas).
+    pub system: bool,
+}
+
+const DATABASES_SQL: &str = "\
+SELECT d.name, d.state_desc, SUSER_SNAME(d.owner_sid), d.recovery_model_desc, \
+d.compatibility_level, d.collation_name, d.is_read_only, \
+CONVERT(varchar(33), d.create_date, 126) \
+FROM sys.databases d ORDER BY d.name";
+
+const COLUMNS_SQL: &str = "\
+SELECT c.name, t.name, bt.name, c.max_length, c.precision, c.scale, c.is_nullable, \
+c.is_identity, c.is_computed, dc.definition, c.collation_name \
+FROM sys.columns c \
+JOIN sys.types t ON t.user_type_id = c.user_type_id \
+LEFT JOIN sys.types bt ON bt.user_type_id = t.system_type_id \
+LEFT JOIN sys.default_constraints dc ON dc.object_id = c.default_object_id \
+WHERE c.object_id = OBJECT_ID(@P1) ORDER BY c.column_id";
+
+const INDEXES_SQL: &str = "\
+SELECT i.name, i.type_desc, i.is_unique, i.is_primary_key, c.name, \
+ic.is_descending_key, ic.is_included_column \
+FROM sys.indexes i \
+JOIN sys.index_columns ic ON ic.object_id = i.object_id AND ic.index_id = i.index_id \
+JOIN sys.columns c ON c.object_id = ic.object_id AND c.column_id = ic.column_id \
+WHERE i.object_id = OBJECT_ID(@P1) AND i.type > 0 \
+ORDER BY i.index_id, ic.is_included_column, ic.key_ordinal, ic.index_column_id";
+
+const FOREIGN_KEYS_SQL: &str = "\
+SELECT fk.name, pc.name, OBJECT_SCHEMA_NAME(fk.referenced_object_id), \
+OBJECT_NAME(fk.referenced_object_id), rc.name, \
+fk.delete_referential_action_desc, fk.update_referential_action_desc \
+FROM sys.foreign_keys fk \
+JOIN sys.foreign_key_columns fkc ON fkc.constraint_object_id = fk.object_id \
+JOIN sys.columns pc ON pc.object_id = fkc.parent_object_id AND pc.column_id = fkc.parent_column_id \
+JOIN sys.columns rc ON rc.object_id = fkc.referenced_object_id AND rc.column_id = fkc.referenced_column_id \
+WHERE fk.parent_object_id = OBJECT_ID(@P1) ORDER BY fk.name, fkc.constraint_column_id";
+
+const PROC_OUTPUTS_SQL: &str = "\
+SELECT SUBSTRING(p.name, 2, 128), t.name, bt.name, p.max_length, p.precision, p.scale, \
+SCHEMA_NAME(t.schema_id) \
+FROM sys.parameters p \
+JOIN sys.types t ON t.user_type_id = p.user_type_id \
+LEFT JOIN sys.types bt ON bt.user_type_id = t.system_type_id \
+WHERE p.object_id = OBJECT_ID(@P1) AND p.is_output = 1";
+
+/// The DECLARE type of each of `procedure`'s OUTPUT parameters, e.g.
+/// `DECIMAL(18, 2)`, keyed by lowercase name without the `@`. Alias types
+/// are declared by their schema-qualified name.
+pub async fn proc_output_types(
+    client: &mut Client<Ready>,
+    procedure: &str,
+) -> Result<HashMap<String, String>> {
+    let name = procedure.to_string();
+    let rows = query_rows(client, PROC_OUTPUTS_SQL, &[&name]).await?;
+    Ok(rows
+        .iter()
+        .map(|row| {
+            let param = text(row, 0).unwrap_or_default().to_lowercase();
+            (param, declare_type(row))
+        })
+        .collect())
+}
+
+fn declare_type(row: &Row) -> String {
+    let type_name = text(row, 1).unwrap_or_default();
+    let base_type = text(row, 2).unwrap_or_else(|| type_name.clone());
+    if type_name != base_type {
+        let schema = text(row, 6).unwrap_or_else(|| "dbo".into());
+        return format!("{}.{}", bracket_escape(&schema), bracket_escape(&type_name));
+    }
+    let facets = type_facets(
+        &base_type,
+        int(row, 3).unwrap_or(0),
+        int(row, 4).unwrap_or(0) as u8,
+        int(row, 5).unwrap_or(0) as u8,
+    );
+    let sql_type = base_type.to_uppercase();
+    match facets {
+        (Some(-1), _, _) => format!("{sql_type}(MAX)"),
+        (Some(length), _, _) => format!("{sql_type}({length})"),
+        (None, Some(precision), Some(scale)) => format!("{sql_type}({precision}, {scale})"),
+        (None, None, Some(scale)) => format!("{sql_type}({scale})"),
+        _ => sql_type,
+    }
+}
+
+/// Describe `table` (optionally schema-qualified). Fails if it doesn't exist.
+pub async fn table_schema(client: &mut Client<Ready>, table: &str) -> Result<TableSchema> {
+    let name = table.to_string();
+    let header = query_rows(
+        client,
+        "SELECT OBJECT_SCHEMA_NAME(OBJECT_ID(@P1)), OBJECT_NAME(OBJECT_ID(@P1))",
+        &[&name],
+    )
+    .await?;
+    let (schema, object) = match header.first().map(|row| (text(row, 0), text(row, 1))) {
+        Some((Some(schema), Some(object))) => (schema, object),
+        _ => return Err(MssqlError::Query(format!("Object not found: {table}"))),
+    };
+
+    let columns = query_rows(client, COLUMNS_SQL, &[&name])
+        .await?
+        .iter()
+        .map(column_from_row)
+        .collect();
+    let mut indexes = group_indexes(&query_rows(client, INDEXES_SQL, &[&name]).await?);
+    let primary_key = indexes
+        .iter()
+        .position(|i| i.primary_key)
+        .map(|pos| indexes.remove(pos));
+    let foreign_keys = group_foreign_keys(&query_rows(client, FOREIGN_KEYS_SQL, &[&name]).await?);
+
+    Ok(TableSchema {
+        schema,
+        name: object,
+        columns,
+        primary_key,
+        foreign_keys,
+        indexes,
+    })
+}
+
+/// User objects matching `filter`, ordered by schema and name.
+pub async fn list_objects(
+    client: &mut Client<Ready>,
+    filter: &ObjectFilter,
+) -> Result<Vec<DbObject>> {
+    let type_codes = object_type_codes(&filter.types)?;
+    let schema = filter.schema.clone().unwrap_or_default();
+    let name_like = filter.name_like.clone().unwrap_or_default();
+    let sql = format!(
+        "SELECT s.name, o.name, o.type, CONVERT(varchar(33), o.create_date, 126), \
+         CONVERT(varchar(33), o.modify_date, 126) \
+         FROM sys.objects o JOIN sys.schemas s ON s.schema_id = o.schema_id \
+         WHERE o.is_ms_shipped = 0 AND o.type IN ({}) \
+         AND (@P1 = N'' OR s.name = @P1) AND (@P2 = N'' OR o.name LIKE @P2) \
+         ORDER BY s.name, o.name",
+        type_codes
+            .iter()
+            .map(|code| format!("'{code}'"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    let rows = query_rows(client, &sql, &[&schema, &name_like]).await?;
+    Ok(rows
+        .iter()
+        .map(|row| DbObject {
+            schema: text(row, 0).unwrap_or_default(),
+            name: text(row, 1).unwrap_or_default(),
+            kind: object_type_name(text(row, 2).unwrap_or_default().trim()),
+            created: text(row, 3).unwrap_or_default(),
+            modified: text(row, 4).unwrap_or_default(),
+        })
+        .collect())
+}
+
+/// Databases visible to the login, ordered by name.
+pub async fn list_databases(client: &mut Client<Ready>) -> Result<Vec<Database>> {
+    let rows = query_rows(client, DATABASES_SQL, &[]).await?;
+    Ok(rows
+        .iter()
+        .map(|row| Database {
+            name: text(row, 0).unwrap_or_default(),
+            state: text(row, 1).unwrap_or_default(),
+            owner: text(row, 2),
+            recovery_model: text(row, 3).unwrap_or_default(),
+            compatibility_level: int(row, 4).unwrap_or_default(),
+            collation: text(row, 5),
+            read_only: flag(row, 6),
+            created: text(row, 7).unwrap_or_default(),
+        })
+        .collect())
+}
+
+/// Schemas in `database` (the current database when empty), ordered by name.
+pub async fn list_schemas(client: &mut Client<Ready>, database: &str) -> Result<Vec<Schema>> {
+    let rows = query_rows(client, &schemas_sql(database), &[]).await?;
+    Ok(rows
+        .iter()
+        .map(|row| Schema {
+            name: text(row, 0).unwrap_or_default(),
+            owner: text(row, 1),
+            system: flag(row, 2),
+        })
+        .collect())
+}
+
+/// `sys.schemas` query, three-part qualified when `database` is given.
+fn schemas_sql(database: &str) -> String {
+    let prefix = if database.is_empty() {
+        String::new()
+    } else {
+        format!("{}.", bracket_escape(database))
+    };
+    // Fixed schemas have ids 1-4 (dbo, guest, INFORMATION_SCHEMA, sys) and
+    // 16384+ (the db_* role schemas)
+    format!(
+        "SELECT s.name, p.name, CAST(CASE WHEN s.schema_id < 5 OR s.schema_id >= 16384 \
+         THEN 1 ELSE 0 END AS bit) \
+         FROM {prefix}sys.schemas s \
+         LEFT JOIN {prefix}sys.database_principals p ON p.principal_id = s.principal_id \
+         ORDER BY s.name"
+    )
+}
+
+// ── Row helpers ───────────────────────────────────────────────
+
+async fn query_rows(
+    client: &mut Client<Ready>,
+    sql: &str,
+    params: &[&(dyn ToSql + Sync)],
+) -> Result<Vec<Row>> {
+    let stream = client.query(sql, params).await.map_err(MssqlError::from)?;
+    stream
+        .into_iter()
+        .collect::<std::result::Result<Vec<Row>, _>>()
+        .map_err(MssqlError::from)
+}
+
+fn text(row: &Row, i: usize) -> Option<String> {
+    match row.get_raw(i) {
+        Some(SqlValue::String(s)) => Some(s),
+        _ => None,
+    }
+}
+
+fn int(row: &Row, i: usize) -> Option<i64> {
+    match row.get_raw(i) {
+        Some(SqlValue::TinyInt(n)) => Some(n as i64),
+        Some(SqlValue::SmallInt(n)) => Some(n as i64),
+        Some(SqlValue::Int(n)) => Some(n as i64),
+        Some(SqlValue::BigInt(n)) => Some(n),
+        _ => None,
+    }
+}
+
+fn flag(row: &Row, i: usize) -> bool {
+    matches!(row.get_raw(i), Some(SqlValue::Bool(true)))
+}
+
+// ── Shaping ───────────────────────────────────────────────────
+
+fn column_from_row(row: &Row) -> Column {
+    let type_name = text(row, 1).unwrap_or_default();
+    let base_type = text(row, 2).unwrap_or_else(|| type_name.clone());
+    let (length, precision, scale) = type_facets(
+        &base_type,
+        int(row, 3).unwrap_or(0),
+        int(row, 4).unwrap_or(0) as u8,
+        int(row, 5).unwrap_or(0) as u8,
+    );
+    Column {
+        name: text(row, 0).unwrap_or_default(),
+        type_name,
+        length,
+        precision,
+        scale,
+        nullable: flag(row, 6),
+        identity: flag(row, 7),
+        computed: flag(row, 8),
+        default: text(row, 9),
+        collation: text(row, 10),
+    }
+}
+
+/// Which of length / precision / scale are meaningful for `base_type`, with
+/// `max_length` converted from bytes to characters for Unicode types.
+fn type_facets(
+    base_type: &str,
+    max_length: i64,
+    precision: u8,
+    scale: u8,
+) -> (Option<i32>, Option<u8>, Option<u8>) {
+    match base_type {
+        "varchar" | "char" | "varbinary" | "binary" => (Some(max_length as i32), None, None),
+        "nvarchar" | "nchar" if max_length == -1 => (Some(-1), None, None),
+        "nvarchar" | "nchar" => (Some((max_length / 2) as i32), None, None),
+        "decimal" | "numeric" => (None, Some(precision), Some(scale)),
+        "datetime2" | "datetimeoffset" | "time" => (None, None, Some(scale)),
+        _ => (None, None, None),
+    }
+}
+
+fn group_indexes(rows: &[Row]) -> Vec<Index> {
+    let mut indexes: Vec<Index> = Vec::new();
+    for row in rows {
+        let name = text(row, 0).unwrap_or_default();
+        if indexes.last().is_none_or(|i| i.name != name) {
+            indexes.push(Index {
+                name,
+                kind: text(row, 1).unwrap_or_default().replace('_', " "),
+                unique: flag(row, 2),
+                primary_key: flag(row, 3),
+                columns: Vec::new(),
+                included: Vec::new(),
+            });
+        }
+        let index = indexes.last_mut().expect("pushed above");
+        let column = text(row, 4).unwrap_or_default();
+        if flag(row, 6) {
+            index.included.push(column);
+        } else {
+            index.columns.push(IndexColumn {
+                name: column,
+                descending: flag(row, 5),
+            });
+        }
+    }
+    indexes
+}
+
+fn group_foreign_keys(rows: &[Row]) -> Vec<ForeignKey> {
+    let mut keys: Vec<ForeignKey> = Vec::new();
+    for row in rows {
+        let name = text(row, 0).unwrap_or_default();
+        if keys.last().is_none_or(|k| k.name != name) {
+            keys.push(ForeignKey {
+                name,
+                columns: Vec::new(),
+                referenced_schema: text(row, 2).unwrap_or_default(),
+                referenced_table: text(row, 3).unwrap_or_default(),
+                referenced_columns: Vec::new(),
+                on_delete: text(row, 5).unwrap_or_default(),
+                on_update: text(row, 6).unwrap_or_default(),
+            });
+        }
+        let key = keys.last_mut().expect("pushed above");
+        key.columns.push(text(row, 1).unwrap_or_default());
+        key.referenced_columns.push(text(row, 4).unwrap_or_default());
+    }
+    keys
+}
+
+/// `sys.objects.type` codes for the filter's type names (all when empty).
+fn object_type_codes(types: &[String]) -> Result<Vec<&'static str>> {
+    const ALL: &[&str] = &["table", "view", "procedure", "function", "synonym", "sequence"];
+    let names: Vec<&str> = if types.is_empty() {
+        ALL.to_vec()
+    } else {
+        types.iter().map(String::as_str).collect()
+    };
+    let mut codes = Vec::new();
+    for name in names {
+        codes.extend_from_slice(match name {
+            "table" => &["U"][..],
+            "view" => &["V"],
+            "procedure" => &["P", "PC"],
+            "function" => &["FN", "IF", "TF", "FS", "FT"],
+            "synonym" => &["SN"],
+            "sequence" => &["SO"],
+            other => {
+                return Err(MssqlError::Query(format!("Unknown object type: {other}")))
+            }
+        });
+    }
+    Ok(codes)
+}
+
+fn object_type_name(code: &str) -> &'static str {
+    match code {
+        "U" => "table",
+        "V" => "view",
+        "P" | "PC" => "procedure",
+        "FN" | "IF" | "TF" | "FS" | "FT" => "function",
+        "SN" => "synonym",
+        "SO" => "sequence",
+        _ => "other",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn facets_by_type() {
+        assert_eq!(type_facets("nvarchar", 100, 0, 0), (Some(50), None, None));
+        assert_eq!(type_facets("nvarchar", -1, 0, 0), (Some(-1), None, None));
+        assert_eq!(type_facets("varbinary", -1, 0, 0), (Some(-1), None, None));
+        assert_eq!(type_facets("decimal", 9, 18, 4), (None, Some(18), Some(4)));
+        assert_eq!(type_facets("datetime2", 8, 27, 7), (None, None, Some(7)));
+        assert_eq!(type_facets("int", 4, 10, 0), (None, None, None));
+    }
+
+    #[test]
+    fn schemas_sql_qualifies_database() {
+        assert!(schemas_sql("").contains("FROM sys.schemas s"));
+        let sql = schemas_sql("Sales]DB");
+        assert!(sql.contains("FROM [Sales]]DB].sys.schemas s"));
+        assert!(sql.contains("JOIN [Sales]]DB].sys.database_principals p"));
+    }
+
+    #[test]
+    fn object_type_round_trip() {
+        let codes = object_type_codes(&["function".into(), "table".into()]).unwrap();
+        assert_eq!(codes, ["FN", "IF", "TF", "FS", "FT", "U"]);
+        assert!(codes.iter().all(|c| ["function", "table"].contains(&object_type_name(c))));
+        assert_eq!(object_type_codes(&[]).unwrap().len(), 12);
+        assert!(object_type_codes(&["trigger".into()]).is_err());
+    }
+}
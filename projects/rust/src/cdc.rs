@@ -0,0 +1,286 @@
+//! Change Data Capture reader: `cdc.fn_cdc_get_all_changes_*` /
+//! `fn_cdc_get_net_changes_*` with the LSN range bookkeeping done here.
+
+use mssql_client::{Client, Ready, Row, SqlValue, ToSql};
+use serde::{Deserialize, Serialize};
+
+use crate::bulk::bracket_escape;
+use crate::config::ColumnCase;
+use crate::error::{MssqlError, Result};
+use crate::query::{column_keys, recase, row_to_json, SerializeOptions};
+
+/// Request to read one capture instance's changes.
+#[derive(Deserialize)]
+pub struct CdcRequest {
+    pub capture_instance: String,
+    /// Last LSN already processed (`to_lsn` of the previous read); changes
+    /// after it are returned. Defaults to the instance's minimum LSN.
+    #[serde(default)]
+    pub since_lsn: Option<String>,
+    /// Start from the first change committed after this time instead
+    /// (ISO 8601, compared with the server's local commit times).
+    #[serde(default)]
+    pub since_time: Option<String>,
+    /// Net changes (one row per key) instead of every change.
+    #[serde(default)]
+    pub net: bool,
+    /// Include update before-images (`all update old`). Ignored for net.
+    #[serde(default)]
+    pub update_old: bool,
+}
+
+/// Changes in one LSN range.
+#[derive(Serialize)]
+pub struct CdcChangeSet {
+    /// First LSN of the range, or null when there was nothing to read.
+    pub from_lsn: Option<String>,
+    /// Pass as `since_lsn` on the next read.
+    pub to_lsn: String,
+    /// Commit time of `to_lsn`.
+    pub to_time: serde_json::Value,
+    pub min_lsn: String,
+    /// `since_lsn` is older than the capture instance's retention: changes
+    /// were cleaned up, so the caller must re-sync.
+    pub resync_required: bool,
+    pub changes: Vec<CdcChange>,
+}
+
+/// One change row.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct CdcChange {
+    pub lsn: String,
+    /// Order within the transaction; null for net changes.
+    pub seqval: Option<String>,
+    /// `delete`, `insert`, `update_before`, `update_after` or `merge`.
+    pub operation: &'static str,
+    pub commit_time: serde_json::Value,
+    /// Base64 bitmask of updated columns, null for net changes.
+    pub update_mask: serde_json::Value,
+    /// Captured column values.
+    pub row: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Read the changes after `since_lsn` (or `since_time`, or from the start)
+/// up to the current maximum LSN.
+pub async fn changes(
+    client: &mut Client<Ready>,
+    req: &CdcRequest,
+    opts: &SerializeOptions,
+) -> Result<CdcChangeSet> {
+    let since = req.since_lsn.as_deref().map(parse_lsn).transpose()?;
+    let instance = req.capture_instance.clone();
+    let since_time = req.since_time.clone().unwrap_or_default();
+
+    let stream = client
+        .query(
+            "SELECT sys.fn_cdc_get_min_lsn(@P1) AS min_lsn, sys.fn_cdc_get_max_lsn() AS max_lsn, \
+             sys.fn_cdc_map_lsn_to_time(sys.fn_cdc_get_max_lsn()) AS max_time, \
+             CASE WHEN @P2 = N'' THEN NULL ELSE sys.fn_cdc_map_time_to_lsn(\
+             'smallest greater than', CONVERT(datetime2, @P2, 127)) END AS time_lsn",
+            &[&instance, &since_time],
+        )
+        .await
+        .map_err(MssqlError::from)?;
+    let row: Row = stream
+        .into_iter()
+        .next()
+        .ok_or_else(|| MssqlError::Query("Cannot read CDC LSN range".into()))?
+        .map_err(MssqlError::from)?;
+    let lsn_at = |i| match row.get_raw(i) {
+        Some(SqlValue::Binary(bytes)) if bytes.len() == LSN_LEN => {
+            let mut lsn = [0u8; LSN_LEN];
+            lsn.copy_from_slice(&bytes);
+            Some(lsn)
+        }
+        _ => None,
+    };
+    let min_lsn = lsn_at(0)
+        .filter(|lsn| lsn.iter().any(|&b| b != 0))
+        .ok_or_else(|| {
+            MssqlError::Query(format!("CDC capture instance not found: {instance}"))
+        })?;
+    let max_lsn = lsn_at(1)
+        .ok_or_else(|| MssqlError::Query("CDC is not enabled for this database".into()))?;
+    let time_lsn = lsn_at(3);
+    // Metadata is looked up by name, so rows are keyed as the server sent them
+    // and only the captured columns are recased
+    let raw = SerializeOptions {
+        column_case: ColumnCase::AsIs,
+        ..opts.clone()
+    };
+    let keys = column_keys(row.columns(), raw.column_case);
+    let to_time = match row_to_json(&row, &keys, &raw)? {
+        serde_json::Value::Object(mut map) => map.remove("max_time").unwrap_or_default(),
+        _ => serde_json::Value::Null,
+    };
+
+    let mut set = CdcChangeSet {
+        from_lsn: None,
+        to_lsn: format_lsn(&max_lsn),
+        to_time,
+        min_lsn: format_lsn(&min_lsn),
+        resync_required: false,
+        changes: Vec::new(),
+    };
+    let from = match (since, time_lsn) {
+        (Some(since), _) if increment_lsn(since) < min_lsn => {
+            set.resync_required = true;
+            return Ok(set);
+        }
+        (Some(since), _) => increment_lsn(since),
+        (None, Some(lsn)) => lsn.max(min_lsn),
+        (None, None) if req.since_time.is_some() => return Ok(set),
+        (None, None) => min_lsn,
+    };
+    if from > max_lsn {
+        return Ok(set);
+    }
+    set.from_lsn = Some(format_lsn(&from));
+
+    // Net changes have no __$seqval; one row per key is already in LSN order
+    let (function, row_filter, order) = if req.net {
+        ("fn_cdc_get_net_changes_", "all", "C.__$start_lsn")
+    } else if req.update_old {
+        ("fn_cdc_get_all_changes_", "all update old", "C.__$start_lsn, C.__$seqval")
+    } else {
+        ("fn_cdc_get_all_changes_", "all", "C.__$start_lsn, C.__$seqval")
+    };
+    let sql = format!(
+        "SELECT sys.fn_cdc_map_lsn_to_time(C.__$start_lsn) AS __$commit_time, C.* \
+         FROM cdc.{}(@P1, @P2, N'{row_filter}') AS C ORDER BY {order}",
+        bracket_escape(&format!("{function}{}", req.capture_instance))
+    );
+    let (from_param, to_param) = (from.to_vec(), max_lsn.to_vec());
+    let params: [&(dyn ToSql + Sync); 2] = [&from_param, &to_param];
+    let stream = client.query(&sql, &params).await.map_err(MssqlError::from)?;
+    let mut keys = None;
+    for row in stream {
+        let row: Row = row.map_err(MssqlError::from)?;
+        let bytes_of = |name: &str| {
+            row.columns()
+                .iter()
+                .find(|c| c.name.eq_ignore_ascii_case(name))
+                .and_then(|c| match row.get_raw(c.index) {
+                    Some(SqlValue::Binary(bytes)) => Some(bytes.to_vec()),
+                    _ => None,
+                })
+                .unwrap_or_default()
+        };
+        let (lsn, seqval) = (bytes_of("__$start_lsn"), bytes_of("__$seqval"));
+        let keys = keys.get_or_insert_with(|| column_keys(row.columns(), raw.column_case));
+        match row_to_json(&row, keys, &raw)? {
+            serde_json::Value::Object(map) => {
+                let mut change = split_change(map, &lsn, &seqval);
+                change.row = recase(change.row, opts.column_case);
+                set.changes.push(change)
+            }
+            _ => unreachable!("row_to_json returns an object"),
+        }
+    }
+    Ok(set)
+}
+
+const LSN_LEN: usize = 10;
+
+/// Parse an LSN written as 20 hex digits, with or without a `0x` prefix.
+fn parse_lsn(s: &str) -> Result<[u8; LSN_LEN]> {
+    let hex = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")).unwrap_or(s);
+    if hex.len() != LSN_LEN * 2 || !hex.is_ascii() {
+        return Err(MssqlError::Query(format!("Invalid LSN: {s}")));
+    }
+    let mut lsn = [0u8; LSN_LEN];
+    for (i, byte) in lsn.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16)
+            .map_err(|e| MssqlError::Query(format!("Invalid LSN: {e}")))?;
+    }
+    Ok(lsn)
+}
+
+/// Format an LSN as `0x` followed by 20 uppercase hex digits.
+fn format_lsn(lsn: &[u8]) -> String {
+    let hex: String = lsn.iter().map(|b| format!("{b:02X}")).collect();
+    format!("0x{hex}")
+}
+
+/// The next LSN, as `sys.fn_cdc_increment_lsn` computes it.
+fn increment_lsn(mut lsn: [u8; LSN_LEN]) -> [u8; LSN_LEN] {
+    for byte in lsn.iter_mut().rev() {
+        let (next, overflow) = byte.overflowing_add(1);
+        *byte = next;
+        if !overflow {
+            break;
+        }
+    }
+    lsn
+}
+
+/// Separate the `__$` metadata columns from the captured columns.
+fn split_change(
+    mut row: serde_json::Map<String, serde_json::Value>,
+    lsn: &[u8],
+    seqval: &[u8],
+) -> CdcChange {
+    let operation = match row.get("__$operation").and_then(|v| v.as_i64()) {
+        Some(1) => "delete",
+        Some(2) => "insert",
+        Some(3) => "update_before",
+        Some(5) => "merge",
+        _ => "update_after",
+    };
+    let commit_time = row.remove("__$commit_time").unwrap_or_default();
+    let update_mask = row.remove("__$update_mask").unwrap_or_default();
+    row.retain(|name, _| !name.starts_with("__$"));
+    CdcChange {
+        lsn: format_lsn(lsn),
+        seqval: (!seqval.is_empty()).then(|| format_lsn(seqval)),
+        operation,
+        commit_time,
+        update_mask,
+        row,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lsn_round_trip() {
+        let lsn = parse_lsn("0x0000002A000001F80003").unwrap();
+        assert_eq!(format_lsn(&lsn), "0x0000002A000001F80003");
+        assert_eq!(parse_lsn("0000002a000001f80003").unwrap(), lsn);
+        assert!(parse_lsn("0x2A").is_err());
+        assert!(parse_lsn("0x0000002A000001F8000G").is_err());
+    }
+
+    #[test]
+    fn increment_carries() {
+        let lsn = parse_lsn("0x0000002A000001F800FF").unwrap();
+        assert_eq!(format_lsn(&increment_lsn(lsn)), "0x0000002A000001F80100");
+        assert!(increment_lsn(lsn) > lsn);
+    }
+
+    #[test]
+    fn split_change_strips_metadata() {
+        let serde_json::Value::Object(row) = serde_json::json!({
+            "__$commit_time": "2026-01-02T03:04:05.000Z",
+            "__$start_lsn": "AAAA",
+            "__$seqval": "AAAA",
+            "__$operation": 4,
+            "__$update_mask": "Aw==",
+            "Id": 7,
+            "Status": "shipped",
+        }) else {
+            unreachable!()
+        };
+        let change = split_change(row, &[0; LSN_LEN], &[1; LSN_LEN]);
+        assert_eq!(change.operation, "update_after");
+        assert_eq!(change.commit_time, "2026-01-02T03:04:05.000Z");
+        assert_eq!(change.update_mask, "Aw==");
+        assert_eq!(change.seqval.as_deref(), Some("0x01010101010101010101"));
+        assert_eq!(
+            serde_json::Value::Object(change.row),
+            serde_json::json!({ "Id": 7, "Status": "shipped" })
+        );
+    }
+}
@@ -0,0 +1,196 @@
+//! Compressed results. A result over the command's threshold is compressed
+//! and held on the connection; the caller gets a small header in its place
+//! saying how many bytes to collect with `mssql_take_result`. The decoders
+//! are exported too (`mssql_decompress`), since not every runtime has them.
+
+#[cfg(any(feature = "lz4", feature = "zstd"))]
+use std::io::Read;
+#[cfg(feature = "lz4")]
+use std::io::Write;
+use std::sync::Mutex;
+
+use serde::Deserialize;
+
+use crate::error::{MssqlError, Result};
+use crate::query::SerializedCommand;
+
+/// Results up to this many bytes are returned as they are.
+pub const DEFAULT_THRESHOLD: u64 = 1 << 20;
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Codec {
+    /// LZ4 frame format: fast, larger output.
+    Lz4,
+    /// Zstandard at its fastest level (pure Rust, no C library): smaller
+    /// output, still quick to decode.
+    Zstd,
+}
+
+impl Codec {
+    fn name(self) -> &'static str {
+        match self {
+            Codec::Lz4 => "lz4",
+            Codec::Zstd => "zstd",
+        }
+    }
+
+    pub fn parse(name: &str) -> Result<Self> {
+        match name {
+            "lz4" => Ok(Codec::Lz4),
+            "zstd" => Ok(Codec::Zstd),
+            other => Err(MssqlError::Config(format!("Unknown codec: {other}"))),
+        }
+    }
+}
+
+/// Compress `bytes`; codecs left out of the build (the `lz4` and `zstd`
+/// cargo features) are an error.
+pub fn compress(codec: Codec, bytes: &[u8]) -> Result<Vec<u8>> {
+    match codec {
+        #[cfg(feature = "lz4")]
+        Codec::Lz4 => {
+            let failed =
+                |e: std::io::Error| MssqlError::Query(format!("lz4 compression failed: {e}"));
+            let mut encoder = lz4_flex::frame::FrameEncoder::new(Vec::new());
+            encoder.write_all(bytes).map_err(failed)?;
+            encoder.finish().map_err(|e| failed(e.into()))
+        }
+        #[cfg(feature = "zstd")]
+        Codec::Zstd => Ok(ruzstd::encoding::compress_to_vec(
+            bytes,
+            ruzstd::encoding::CompressionLevel::Fastest,
+        )),
+        #[allow(unreachable_patterns)]
+        _ => Err(MssqlError::Query(format!(
+            "{} compression is not built into this library",
+            codec.name()
+        ))),
+    }
+}
+
+/// Decompress `bytes` into `out`, which must be the decompressed length.
+pub fn decompress(codec: Codec, bytes: &[u8], out: &mut [u8]) -> Result<()> {
+    let failed = |e: std::io::Error| {
+        MssqlError::Query(format!("{} decompression failed: {e}", codec.name()))
+    };
+    match codec {
+        #[cfg(feature = "lz4")]
+        Codec::Lz4 => lz4_flex::frame::FrameDecoder::new(bytes)
+            .read_exact(out)
+            .map_err(failed),
+        #[cfg(feature = "zstd")]
+        Codec::Zstd => ruzstd::decoding::StreamingDecoder::new(bytes)
+            .map_err(|e| failed(std::io::Error::other(e.to_string())))?
+            .read_exact(out)
+            .map_err(failed),
+        #[allow(unreachable_patterns)]
+        _ => Err(MssqlError::Query(format!(
+            "{} decompression is not built into this library",
+            codec.name()
+        ))),
+    }
+}
+
+/// Compress `json` when `cmd` asks for it and it is over the threshold,
+/// leaving the bytes in `pending` and returning the header that replaces it:
+/// `{"__compressed":"zstd","length":n,"compressedLength":m}`.
+pub fn pack(
+    json: String,
+    cmd: &SerializedCommand,
+    pending: &Mutex<Option<Vec<u8>>>,
+) -> Result<String> {
+    let Some(codec) = cmd.compress else {
+        return Ok(json);
+    };
+    let threshold = cmd.compress_threshold.unwrap_or(DEFAULT_THRESHOLD);
+    if json.len() as u64 <= threshold {
+        return Ok(json);
+    }
+    let bytes = compress(codec, json.as_bytes())?;
+    let header = serde_json::json!({
+        "__compressed": codec.name(),
+        "length": json.len(),
+        "compressedLength": bytes.len(),
+    });
+    *pending.lock().unwrap() = Some(bytes);
+    Ok(header.to_string())
+}
+
+/// Move the pending bytes into `out`. Returns how many were copied, or None
+/// if nothing is pending or `out` is too small (the bytes are kept).
+pub fn take(pending: &Mutex<Option<Vec<u8>>>, out: &mut [u8]) -> Option<usize> {
+    let mut pending = pending.lock().unwrap();
+    let n = pending.as_ref().filter(|b| b.len() <= out.len())?.len();
+    out[..n].copy_from_slice(&pending.take()?);
+    Some(n)
+}
+
+#[cfg(all(test, feature = "lz4", feature = "zstd"))]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    fn command(compress: Option<Codec>, threshold: u64) -> SerializedCommand {
+        serde_json::from_value(serde_json::json!({
+            "sql": "SELECT 1",
+            "params": [],
+            "transaction_id": null,
+            "command_timeout_ms": null,
+            "command_type": "text",
+            "compress": compress.map(Codec::name),
+            "compress_threshold": threshold,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn compresses_over_the_threshold_and_hands_bytes_over_once() {
+        let json =
+            serde_json::json!(vec![serde_json::json!({ "id": 1, "name": "x" }); 200]).to_string();
+        let pending = Mutex::new(None);
+        assert_eq!(
+            pack(json.clone(), &command(None, 0), &pending).unwrap(),
+            json
+        );
+        let big = json.len() as u64 + 1;
+        assert_eq!(
+            pack(json.clone(), &command(Some(Codec::Lz4), big), &pending).unwrap(),
+            json
+        );
+
+        for codec in [Codec::Lz4, Codec::Zstd] {
+            let header = pack(json.clone(), &command(Some(codec), 64), &pending).unwrap();
+            let header: serde_json::Value = serde_json::from_str(&header).unwrap();
+            assert_eq!(header["__compressed"], codec.name());
+            assert_eq!(header["length"], json.len());
+            let n = header["compressedLength"].as_u64().unwrap() as usize;
+            assert!(n < json.len());
+
+            assert_eq!(take(&pending, &mut vec![0; n - 1]), None);
+            let mut out = vec![0; n];
+            assert_eq!(take(&pending, &mut out), Some(n));
+            assert_eq!(take(&pending, &mut out), None);
+            let mut plain = String::new();
+            match codec {
+                Codec::Lz4 => {
+                    lz4_flex::frame::FrameDecoder::new(&out[..])
+                        .read_to_string(&mut plain)
+                        .unwrap();
+                }
+                Codec::Zstd => {
+                    ruzstd::decoding::StreamingDecoder::new(&out[..])
+                        .unwrap()
+                        .read_to_string(&mut plain)
+                        .unwrap();
+                }
+            }
+            assert_eq!(plain, json);
+
+            let mut decoded = vec![0; json.len()];
+            decompress(codec, &out, &mut decoded).unwrap();
+            assert_eq!(decoded, json.as_bytes());
+            assert!(decompress(codec, &out[..n / 2], &mut decoded).is_err());
+        }
+    }
+}
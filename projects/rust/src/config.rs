@@ -1,11 +1,16 @@
-use mssql_client::{Config, Credentials};
-use serde::Deserialize;
+use mssql_client::{ApplicationIntent as ClientIntent, Config, Credentials};
+use serde::{Deserialize, Serialize};
+use serde_json::value::RawValue;
+use std::collections::HashMap;
 use std::time::Duration;
 
 use crate::error::{MssqlError, Result};
+use crate::query::SerializeOptions;
+use crate::secret::Secret;
+use crate::token::AccessToken;
 
 /// JSON config sent from the TypeScript layer.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct NormalizedConfig {
     pub server: String,
     pub port: u16,
@@ -19,64 +24,351 @@ pub struct NormalizedConfig {
     pub instance_name: Option<String>,
     pub packet_size: u16,
     pub pool: Option<PoolConfig>,
+    /// How DECIMAL/NUMERIC/MONEY result values are serialized.
+    #[serde(default)]
+    pub decimal_mode: DecimalMode,
+    /// How date/time result values are serialized.
+    #[serde(default)]
+    pub temporal_mode: TemporalMode,
+    /// How BIGINT result values are serialized.
+    #[serde(default)]
+    pub bigint_mode: BigIntMode,
+    /// Parse json-typed result columns into JSON values instead of strings.
+    #[serde(default)]
+    pub parse_json: bool,
+    /// How result column names are cased.
+    #[serde(default)]
+    pub column_case: ColumnCase,
+    /// Encoding label (e.g. `windows-1252`) tried when varchar bytes are not UTF-8.
+    #[serde(default)]
+    pub varchar_encoding: Option<String>,
+    /// What to do with varchar bytes that can't be decoded.
+    #[serde(default)]
+    pub varchar_decode: VarcharDecodePolicy,
+    /// Lowest TLS version accepted for encrypted connections.
+    #[serde(default)]
+    pub tls_min_version: TlsMinVersion,
+    /// Allowed cipher suites; None allows every suite rustls supports.
+    #[serde(default)]
+    pub tls_cipher_suites: Option<Vec<String>>,
+    /// PEM or DER file with CA certificates trusted for server validation.
+    #[serde(default)]
+    pub ca_cert_path: Option<String>,
+    /// Inline PEM CA certificates trusted for server validation.
+    #[serde(default)]
+    pub ca_cert_pem: Option<String>,
+    /// Mirroring partner tried when the server can't be reached.
+    #[serde(default)]
+    pub failover_partner: Option<String>,
+    /// Race connections to every address of the server (AG listeners).
+    #[serde(default)]
+    pub multi_subnet_failover: bool,
+    /// Workload type declared at login; read-only may be routed to a replica.
+    #[serde(default)]
+    pub application_intent: ApplicationIntent,
+    /// TCP keepalive idle time; None or 0 leaves keepalive off.
+    #[serde(default)]
+    pub tcp_keepalive_ms: Option<u64>,
+    /// Disable Nagle's algorithm; None keeps the default (on).
+    #[serde(default)]
+    pub tcp_nodelay: Option<bool>,
+    /// Extra attempts after a transient failure to connect.
+    #[serde(default)]
+    pub connect_retry_count: u32,
+    /// Delay before the first retry; doubled for each later one.
+    #[serde(default)]
+    pub connect_retry_interval_ms: u64,
+    /// Commands slower than this are recorded in the slow query log.
+    #[serde(default)]
+    pub slow_query_ms: Option<u64>,
+    /// Begin transactions with `SET XACT_ABORT ON` unless the request says
+    /// otherwise.
+    #[serde(default)]
+    pub xact_abort: bool,
+    /// Name of the subsystem that owns the pool or connection, shown in
+    /// diagnostics, the slow query log and error messages.
+    #[serde(default)]
+    pub label: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+/// Workload type sent in the login, used for availability group read-only routing.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq)]
+pub enum ApplicationIntent {
+    #[default]
+    #[serde(rename = "read_write")]
+    ReadWrite,
+    #[serde(rename = "read_only")]
+    ReadOnly,
+}
+
+/// Lowest TLS protocol version to negotiate.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq)]
+pub enum TlsMinVersion {
+    #[default]
+    #[serde(rename = "1.2")]
+    Tls12,
+    #[serde(rename = "1.3")]
+    Tls13,
+}
+
+/// Serialization mode for exact numeric (DECIMAL/NUMERIC/MONEY) results.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq)]
+pub enum DecimalMode {
+    /// Lossless decimal string, e.g. `"12345.6789"` (default).
+    #[default]
+    #[serde(rename = "string")]
+    String,
+    /// JSON number — convenient, but may lose precision beyond f64.
+    #[serde(rename = "number")]
+    Number,
+}
+
+/// Policy for varchar bytes that are neither UTF-8 nor valid in the fallback encoding.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq)]
+pub enum VarcharDecodePolicy {
+    /// Fail the row with an error.
+    #[serde(rename = "error")]
+    Error,
+    /// Substitute U+FFFD for invalid sequences (default).
+    #[default]
+    #[serde(rename = "lossy")]
+    Lossy,
+    /// Map each byte to the code point of the same value (ISO-8859-1).
+    #[serde(rename = "latin1")]
+    Latin1,
+}
+
+/// Serialization mode for DATE/TIME/DATETIME*/DATETIMEOFFSET results.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq)]
+pub enum TemporalMode {
+    /// Strict ISO 8601 string (default).
+    #[default]
+    #[serde(rename = "iso")]
+    Iso,
+    /// Milliseconds since the Unix epoch; offset-less values are read as UTC.
+    #[serde(rename = "epoch")]
+    Epoch,
+    /// `{"__type": "datetime2", "value": "<iso>"}` so callers can revive by type.
+    #[serde(rename = "tagged")]
+    Tagged,
+}
+
+/// How result column names are written as row keys. The driver's own
+/// `__`-prefixed columns are left as they are.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq)]
+pub enum ColumnCase {
+    /// The name the server sent (default).
+    #[default]
+    #[serde(rename = "as-is")]
+    AsIs,
+    /// `Order_ID` and `order id` become `orderId`.
+    #[serde(rename = "camelCase")]
+    Camel,
+    #[serde(rename = "lowercase")]
+    Lower,
+}
+
+/// Serialization policy for BIGINT results.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq)]
+pub enum BigIntMode {
+    /// JSON number inside ±2^53, string outside it (default).
+    #[default]
+    #[serde(rename = "number-or-string")]
+    NumberOrString,
+    /// Always a decimal string, so the column's JS type never varies.
+    #[serde(rename = "always-string")]
+    AlwaysString,
+    /// `{"__type": "bigint", "value": "<digits>"}` for callers that revive to BigInt.
+    #[serde(rename = "tagged-bigint")]
+    TaggedBigInt,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
 #[serde(tag = "type")]
 pub enum AuthConfig {
     #[serde(rename = "sql")]
-    Sql { username: String, password: String },
+    Sql { username: String, password: Secret },
     #[serde(rename = "ntlm")]
     Ntlm {
         username: String,
-        password: String,
+        password: Secret,
         domain: String,
     },
     #[serde(rename = "windows")]
     Windows,
+    /// Kerberos via GSSAPI using the ticket cache (`kinit`) or a client keytab;
+    /// `spn` overrides the default `MSSQLSvc/host:port` service principal.
+    #[serde(rename = "kerberos")]
+    Kerberos {
+        #[serde(default)]
+        spn: Option<String>,
+    },
     #[serde(rename = "azure_ad")]
-    AzureAd { username: String, password: String },
+    AzureAd { username: String, password: Secret },
     #[serde(rename = "azure_ad_token")]
-    AzureAdToken { token: String },
+    AzureAdToken { token: Secret },
+    /// Managed identity; `client_id` selects a user-assigned identity.
+    #[serde(rename = "azure_ad_msi")]
+    AzureAdMsi {
+        #[serde(default)]
+        client_id: Option<String>,
+    },
+    /// Service principal via the client credentials flow.
+    #[serde(rename = "azure_ad_sp")]
+    AzureAdSp {
+        tenant_id: String,
+        client_id: String,
+        client_secret: Secret,
+    },
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct PoolConfig {
     pub min: Option<u32>,
     pub max: Option<u32>,
     pub idle_timeout_ms: Option<u64>,
 }
 
+/// Match `$key` against the listed fields and deserialize `$value` into the
+/// one it names. Evaluates to false for a key that isn't listed.
+macro_rules! set_field {
+    ($cfg:expr, $key:expr, $value:expr, [$($field:ident),* $(,)?]) => {
+        match $key {
+            $(stringify!($field) => {
+                $cfg.$field = serde_json::from_str($value)
+                    .map_err(|e| MssqlError::Config(format!("Invalid config JSON: {}: {e}", $key)))?;
+                true
+            })*
+            _ => false,
+        }
+    };
+}
+
 impl NormalizedConfig {
-    /// Parse from a JSON string sent over FFI.
+    /// Parse from a JSON string sent over FFI. `{"connection_string": "..."}`
+    /// is accepted in place of the connection fields; any other fields given
+    /// with it are applied on top of what the connection string sets.
     pub fn from_json(json: &str) -> Result<Self> {
-        serde_json::from_str(json).map_err(|e| MssqlError::Config(format!("Invalid config JSON: {e}")))
+        let invalid =
+            |e: serde_json::Error| MssqlError::Config(format!("Invalid config JSON: {e}"));
+        let mut fields: HashMap<&str, &RawValue> = serde_json::from_str(json).map_err(invalid)?;
+        let Some(conn_str) = fields.remove("connection_string") else {
+            return serde_json::from_str(json).map_err(invalid);
+        };
+        let conn_str: Secret = serde_json::from_str(conn_str.get()).map_err(invalid)?;
+        let mut cfg = crate::connstr::parse(conn_str.expose())?;
+        for (key, value) in fields {
+            cfg.set_field(key, value.get())?;
+        }
+        Ok(cfg)
+    }
+
+    /// Overwrite one field from its JSON value. Returns false, leaving the
+    /// config as it is, for a key that isn't a config field.
+    fn set_field(&mut self, key: &str, value: &str) -> Result<bool> {
+        Ok(set_field!(
+            self,
+            key,
+            value,
+            [
+                server,
+                port,
+                database,
+                auth,
+                encrypt,
+                trust_server_certificate,
+                connect_timeout_ms,
+                request_timeout_ms,
+                app_name,
+                instance_name,
+                packet_size,
+                pool,
+                decimal_mode,
+                temporal_mode,
+                bigint_mode,
+                parse_json,
+                column_case,
+                varchar_encoding,
+                varchar_decode,
+                tls_min_version,
+                tls_cipher_suites,
+                ca_cert_path,
+                ca_cert_pem,
+                failover_partner,
+                multi_subnet_failover,
+                application_intent,
+                tcp_keepalive_ms,
+                tcp_nodelay,
+                connect_retry_count,
+                connect_retry_interval_ms,
+                slow_query_ms,
+                xact_abort,
+                label,
+            ]
+        ))
+    }
+
+    /// Acquire the access token for auth types that fetch one themselves.
+    pub async fn acquire_token(&self) -> Result<Option<AccessToken>> {
+        match &self.auth {
+            AuthConfig::AzureAdMsi { client_id } => {
+                crate::token::managed_identity(client_id.as_deref()).await.map(Some)
+            }
+            AuthConfig::AzureAdSp {
+                tenant_id,
+                client_id,
+                client_secret,
+            } => crate::token::service_principal(tenant_id, client_id, client_secret.expose())
+                .await
+                .map(Some),
+            _ => Ok(None),
+        }
     }
 
-    /// Convert to an mssql-client Config.
-    pub fn to_client_config(&self) -> Result<Config> {
+    /// Convert to an mssql-client Config. `token` comes from [`Self::acquire_token`].
+    pub fn to_client_config(&self, token: Option<&str>) -> Result<Config> {
+        if let Some(label) = &self.varchar_encoding {
+            if encoding_rs::Encoding::for_label(label.as_bytes()).is_none() {
+                return Err(MssqlError::Config(format!("Unknown varcharEncoding: {label}")));
+            }
+        }
         let credentials = match &self.auth {
             AuthConfig::Sql { username, password } => {
-                Credentials::sql_server(username.clone(), password.clone())
+                Credentials::sql_server(username.clone(), password.expose().to_owned())
             }
             AuthConfig::Ntlm {
                 username,
                 password,
                 domain,
             } => {
-                // mssql-client uses domain\username format for NTLM
-                let full_user = format!("{domain}\\{username}");
-                Credentials::sql_server(full_user, password.clone())
+                // NTLM challenge/response over the SSPI login exchange, with
+                // explicit credentials so it works from non-Windows hosts
+                Credentials::ntlm(username.clone(), password.expose().to_owned(), domain.clone())
             }
             AuthConfig::Windows => {
                 Credentials::integrated_sspi(self.server.clone(), self.port)
             }
+            // SSPI already negotiates Kerberos on Windows
+            #[cfg(windows)]
+            AuthConfig::Kerberos { .. } => {
+                Credentials::integrated_sspi(self.server.clone(), self.port)
+            }
+            #[cfg(not(windows))]
+            AuthConfig::Kerberos { spn } => {
+                Credentials::kerberos(spn.clone().unwrap_or_else(|| self.default_spn()))
+            }
             AuthConfig::AzureAd { username, password } => {
                 // Azure AD password auth maps to SQL auth for now
-                Credentials::sql_server(username.clone(), password.clone())
+                Credentials::sql_server(username.clone(), password.expose().to_owned())
             }
             AuthConfig::AzureAdToken { token } => {
-                Credentials::azure_token(token.clone())
+                Credentials::azure_token(token.expose().to_owned())
+            }
+            AuthConfig::AzureAdMsi { .. } | AuthConfig::AzureAdSp { .. } => {
+                let token = token.ok_or_else(|| {
+                    MssqlError::Config("Azure AD token was not acquired".into())
+                })?;
+                Credentials::azure_token(token.to_string())
             }
         };
 
@@ -104,11 +396,36 @@ impl NormalizedConfig {
             config.packet_size = self.packet_size;
         }
 
+        // Keepalive probes stop firewalls and load balancers from silently
+        // dropping idle pooled connections
+        config.tcp_keepalive = self
+            .tcp_keepalive_ms
+            .filter(|&ms| ms > 0)
+            .map(Duration::from_millis);
+        config.tcp_nodelay = self.tcp_nodelay.unwrap_or(true);
+
+        if self.application_intent == ApplicationIntent::ReadOnly {
+            config = config.application_intent(ClientIntent::ReadOnly);
+        }
+
+        crate::tls::apply(&mut config, self)?;
+
         Ok(config)
     }
 
+    /// SPN SQL Server registers for this endpoint: `MSSQLSvc/host:instance`
+    /// for named instances, `MSSQLSvc/host:port` otherwise.
+    #[cfg_attr(windows, allow(dead_code))]
+    fn default_spn(&self) -> String {
+        match &self.instance_name {
+            Some(instance) => format!("MSSQLSvc/{}:{}", self.server, instance),
+            None => format!("MSSQLSvc/{}:{}", self.server, self.port),
+        }
+    }
+
     /// Canonical identity key for pool deduplication.
-    /// Excludes pool-tuning params (min/max/idle_timeout) and timeouts.
+    /// Excludes pool-tuning params (min/max/idle_timeout), timeouts, retry
+    /// settings, TCP socket options and the diagnostic `label`.
     pub fn dedup_key(&self) -> String {
         let auth_key = match &self.auth {
             AuthConfig::Sql { username, .. } => format!("sql|{}", username),
@@ -116,11 +433,22 @@ impl NormalizedConfig {
                 username, domain, ..
             } => format!("ntlm|{}|{}", domain, username),
             AuthConfig::Windows => "windows".into(),
+            AuthConfig::Kerberos { spn } => {
+                format!("kerberos|{}", spn.as_deref().unwrap_or("").to_lowercase())
+            }
             AuthConfig::AzureAd { username, .. } => format!("azure_ad|{}", username),
             AuthConfig::AzureAdToken { .. } => "azure_ad_token".into(),
+            AuthConfig::AzureAdMsi { client_id } => {
+                format!("azure_ad_msi|{}", client_id.as_deref().unwrap_or(""))
+            }
+            AuthConfig::AzureAdSp {
+                tenant_id,
+                client_id,
+                ..
+            } => format!("azure_ad_sp|{}|{}", tenant_id.to_lowercase(), client_id.to_lowercase()),
         };
         format!(
-            "{}|{}|{}|{}|{}|{}|{}|{}|{}",
+            "{}|{}|{}|{}|{}|{}|{}|{}|{}|{:?}|{:?}|{:?}|{}|{:?}|{}|{:?}|{:?}|{}|{}|{}|{}|{}|{:?}|{:?}|{}",
             self.server.to_lowercase(),
             self.port,
             self.database.to_lowercase(),
@@ -133,9 +461,58 @@ impl NormalizedConfig {
                 .to_lowercase(),
             self.app_name,
             self.packet_size,
+            self.decimal_mode,
+            self.temporal_mode,
+            self.bigint_mode,
+            self.parse_json,
+            self.column_case,
+            self.varchar_encoding.as_deref().unwrap_or("").to_lowercase(),
+            self.varchar_decode,
+            self.tls_min_version,
+            self.tls_cipher_suites
+                .as_ref()
+                .map_or(String::new(), |s| s.join(",").to_uppercase()),
+            self.ca_cert_path.as_deref().unwrap_or(""),
+            self.ca_cert_pem.as_deref().unwrap_or("").trim(),
+            self.failover_partner.as_deref().unwrap_or("").to_lowercase(),
+            self.multi_subnet_failover,
+            self.application_intent,
+            self.slow_query_ms,
+            self.xact_abort,
         )
     }
 
+    /// Row serialization options carried by every handle created from this config.
+    pub fn serialize_options(&self) -> SerializeOptions {
+        SerializeOptions {
+            decimal_mode: self.decimal_mode,
+            temporal_mode: self.temporal_mode,
+            bigint_mode: self.bigint_mode,
+            parse_json: self.parse_json,
+            column_case: self.column_case,
+            varchar_encoding: self
+                .varchar_encoding
+                .as_deref()
+                .and_then(|label| encoding_rs::Encoding::for_label(label.as_bytes())),
+            varchar_decode: self.varchar_decode,
+            raw_binary: false,
+            encryption: None,
+        }
+    }
+
+    /// The config as JSON with secrets masked, and `pool` holding the sizes
+    /// and idle timeout the pool actually uses.
+    pub fn effective(&self) -> serde_json::Value {
+        let pool = self.to_pool_config();
+        let mut value = serde_json::to_value(self).unwrap_or_default();
+        value["pool"] = serde_json::json!({
+            "min": pool.min_connections,
+            "max": pool.max_connections,
+            "idle_timeout_ms": pool.idle_timeout.as_millis() as u64,
+        });
+        value
+    }
+
     /// Build a pool config from the normalized config.
     pub fn to_pool_config(&self) -> mssql_driver_pool::PoolConfig {
         let mut pc = mssql_driver_pool::PoolConfig::default();
@@ -156,7 +533,7 @@ impl NormalizedConfig {
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
 
     #[test]
@@ -178,7 +555,7 @@ mod tests {
         let cfg = NormalizedConfig::from_json(json).unwrap();
         assert_eq!(cfg.server, "localhost");
         assert_eq!(cfg.port, 1433);
-        let client_cfg = cfg.to_client_config().unwrap();
+        let client_cfg = cfg.to_client_config(None).unwrap();
         assert_eq!(client_cfg.host, "localhost");
         assert_eq!(client_cfg.port, 1433);
     }
@@ -223,7 +600,120 @@ mod tests {
         }"#;
         let cfg = NormalizedConfig::from_json(json).unwrap();
         assert!(matches!(cfg.auth, AuthConfig::AzureAdToken { .. }));
-        let _client_cfg = cfg.to_client_config().unwrap();
+        let _client_cfg = cfg.to_client_config(None).unwrap();
+    }
+
+    #[test]
+    fn parse_azure_ad_msi() {
+        let json = r#"{
+            "server": "myserver.database.windows.net",
+            "port": 1433,
+            "database": "mydb",
+            "auth": {"type": "azure_ad_msi", "client_id": "abc"},
+            "encrypt": true,
+            "trust_server_certificate": false,
+            "connect_timeout_ms": 15000,
+            "request_timeout_ms": 30000,
+            "app_name": "test",
+            "instance_name": null,
+            "packet_size": 4096,
+            "pool": null
+        }"#;
+        let cfg = NormalizedConfig::from_json(json).unwrap();
+        assert!(matches!(&cfg.auth, AuthConfig::AzureAdMsi { client_id: Some(id) } if id == "abc"));
+        assert!(cfg.dedup_key().contains("azure_ad_msi|abc"));
+        // The token must be acquired before building the client config
+        assert!(cfg.to_client_config(None).is_err());
+        assert!(cfg.to_client_config(Some("token")).is_ok());
+    }
+
+    #[test]
+    fn parse_azure_ad_sp() {
+        let json = r#"{
+            "server": "myserver.database.windows.net",
+            "port": 1433,
+            "database": "mydb",
+            "auth": {"type": "azure_ad_sp", "tenant_id": "T1", "client_id": "C1", "client_secret": "s"},
+            "encrypt": true,
+            "trust_server_certificate": false,
+            "connect_timeout_ms": 15000,
+            "request_timeout_ms": 30000,
+            "app_name": "test",
+            "instance_name": null,
+            "packet_size": 4096,
+            "pool": null
+        }"#;
+        let cfg = NormalizedConfig::from_json(json).unwrap();
+        assert!(matches!(cfg.auth, AuthConfig::AzureAdSp { .. }));
+        // The secret never appears in the pool identity
+        assert!(cfg.dedup_key().contains("azure_ad_sp|t1|c1"));
+        assert!(!cfg.dedup_key().contains("|s|"));
+    }
+
+    #[test]
+    fn parse_kerberos() {
+        let mut cfg = make_config("sql01.corp.example", "mydb", None, None);
+        cfg.auth = serde_json::from_str(r#"{"type": "kerberos"}"#).unwrap();
+        assert!(matches!(cfg.auth, AuthConfig::Kerberos { spn: None }));
+        assert_eq!(cfg.default_spn(), "MSSQLSvc/sql01.corp.example:1433");
+        cfg.instance_name = Some("SQLEXPRESS".into());
+        assert_eq!(cfg.default_spn(), "MSSQLSvc/sql01.corp.example:SQLEXPRESS");
+        assert!(cfg.to_client_config(None).is_ok());
+
+        let mut other = make_config("sql01.corp.example", "mydb", None, None);
+        other.auth = AuthConfig::Kerberos {
+            spn: Some("MSSQLSvc/alias.corp.example:1433".into()),
+        };
+        other.instance_name = cfg.instance_name.clone();
+        assert_ne!(cfg.dedup_key(), other.dedup_key());
+    }
+
+    #[test]
+    fn parse_connection_string_json() {
+        let cfg = NormalizedConfig::from_json(
+            r#"{"connection_string": "Server=tcp:db.example.com,1444;Database=app;User Id=sa;Password=p;"}"#,
+        )
+        .unwrap();
+        assert_eq!((cfg.server.as_str(), cfg.port), ("db.example.com", 1444));
+        assert!(matches!(cfg.auth, AuthConfig::Sql { .. }));
+    }
+
+    #[test]
+    fn connection_string_json_keeps_other_fields() {
+        let cfg = NormalizedConfig::from_json(
+            r#"{
+                "connection_string": "Server=db;Database=app;User Id=sa;Password=p;Max Pool Size=5",
+                "database": "other", "label": "billing", "xact_abort": true,
+                "temporal_mode": "epoch", "tls_min_version": "1.3", "ca_cert_pem": "PEM",
+                "pool": {"min": 1, "max": 20, "idle_timeout_ms": 1000}
+            }"#,
+        )
+        .unwrap();
+        assert_eq!((cfg.server.as_str(), cfg.database.as_str()), ("db", "other"));
+        assert_eq!(cfg.label.as_deref(), Some("billing"));
+        assert!(cfg.xact_abort);
+        assert_eq!(cfg.temporal_mode, TemporalMode::Epoch);
+        assert_eq!(cfg.tls_min_version, TlsMinVersion::Tls13);
+        assert_eq!(cfg.ca_cert_pem.as_deref(), Some("PEM"));
+        assert_eq!(cfg.pool.as_ref().and_then(|p| p.max), Some(20));
+
+        let err = NormalizedConfig::from_json(r#"{"connection_string": "Server=db", "port": "x"}"#)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("port"), "{err}");
+    }
+
+    #[test]
+    fn every_field_can_be_set_over_a_connection_string() {
+        let mut cfg = make_config("db", "app", Some(1), Some(2));
+        cfg.auth = AuthConfig::Windows;
+        let serde_json::Value::Object(fields) = serde_json::to_value(&cfg).unwrap() else {
+            panic!("config should serialize to an object");
+        };
+        for (key, value) in fields {
+            assert!(cfg.set_field(&key, &value.to_string()).unwrap(), "{key}");
+        }
+        assert!(!cfg.set_field("no_such_field", "1").unwrap());
     }
 
     #[test]
@@ -232,14 +722,14 @@ mod tests {
         assert!(result.is_err());
     }
 
-    fn make_config(server: &str, database: &str, pool_min: Option<u32>, pool_max: Option<u32>) -> NormalizedConfig {
+    pub(crate) fn make_config(server: &str, database: &str, pool_min: Option<u32>, pool_max: Option<u32>) -> NormalizedConfig {
         NormalizedConfig {
             server: server.to_string(),
             port: 1433,
             database: database.to_string(),
             auth: AuthConfig::Sql {
                 username: "sa".to_string(),
-                password: "secret".to_string(),
+                password: "secret".into(),
             },
             encrypt: true,
             trust_server_certificate: true,
@@ -253,6 +743,27 @@ mod tests {
                 max: pool_max,
                 idle_timeout_ms: None,
             }),
+            decimal_mode: DecimalMode::String,
+            temporal_mode: TemporalMode::Iso,
+            bigint_mode: BigIntMode::NumberOrString,
+            parse_json: false,
+            column_case: ColumnCase::AsIs,
+            varchar_encoding: None,
+            varchar_decode: VarcharDecodePolicy::Lossy,
+            tls_min_version: TlsMinVersion::Tls12,
+            tls_cipher_suites: None,
+            ca_cert_path: None,
+            ca_cert_pem: None,
+            failover_partner: None,
+            multi_subnet_failover: false,
+            application_intent: ApplicationIntent::ReadWrite,
+            tcp_keepalive_ms: None,
+            tcp_nodelay: None,
+            connect_retry_count: 0,
+            connect_retry_interval_ms: 0,
+            slow_query_ms: None,
+            xact_abort: false,
+            label: None,
         }
     }
 
@@ -277,6 +788,117 @@ mod tests {
         assert_eq!(a.dedup_key(), b.dedup_key());
     }
 
+    #[test]
+    fn decimal_mode_defaults_to_string() {
+        let json = r#"{
+            "server": "localhost",
+            "port": 1433,
+            "database": "master",
+            "auth": {"type": "sql", "username": "sa", "password": "secret"},
+            "encrypt": true,
+            "trust_server_certificate": true,
+            "connect_timeout_ms": 15000,
+            "request_timeout_ms": 30000,
+            "app_name": "test",
+            "instance_name": null,
+            "packet_size": 4096,
+            "pool": null
+        }"#;
+        let cfg = NormalizedConfig::from_json(json).unwrap();
+        assert_eq!(cfg.decimal_mode, DecimalMode::String);
+        assert_eq!(cfg.temporal_mode, TemporalMode::Iso);
+        assert_eq!(cfg.bigint_mode, BigIntMode::NumberOrString);
+        assert!(!cfg.parse_json);
+        assert_eq!(cfg.varchar_decode, VarcharDecodePolicy::Lossy);
+        assert_eq!(cfg.tls_min_version, TlsMinVersion::Tls12);
+        assert!(cfg.tls_cipher_suites.is_none());
+    }
+
+    #[test]
+    fn rejects_unknown_varchar_encoding() {
+        let mut cfg = make_config("localhost", "mydb", None, None);
+        cfg.varchar_encoding = Some("not-a-codepage".into());
+        assert!(cfg.to_client_config(None).is_err());
+        cfg.varchar_encoding = Some("windows-1252".into());
+        assert!(cfg.serialize_options().varchar_encoding.is_some());
+    }
+
+    #[test]
+    fn bigint_mode_parses_kebab_case() {
+        let mode: BigIntMode = serde_json::from_str("\"tagged-bigint\"").unwrap();
+        assert_eq!(mode, BigIntMode::TaggedBigInt);
+        let mode: BigIntMode = serde_json::from_str("\"always-string\"").unwrap();
+        assert_eq!(mode, BigIntMode::AlwaysString);
+    }
+
+    #[test]
+    fn column_case_parses_and_splits_pools() {
+        let case: ColumnCase = serde_json::from_str("\"camelCase\"").unwrap();
+        assert_eq!(case, ColumnCase::Camel);
+        let a = make_config("localhost", "mydb", None, None);
+        let mut b = make_config("localhost", "mydb", None, None);
+        b.column_case = ColumnCase::Lower;
+        assert_ne!(a.dedup_key(), b.dedup_key());
+        assert_eq!(b.serialize_options().column_case, ColumnCase::Lower);
+    }
+
+    #[test]
+    fn dedup_key_different_decimal_mode() {
+        let a = make_config("localhost", "mydb", None, None);
+        let mut b = make_config("localhost", "mydb", None, None);
+        b.decimal_mode = DecimalMode::Number;
+        assert_ne!(a.dedup_key(), b.dedup_key());
+    }
+
+    #[test]
+    fn dedup_key_different_temporal_mode() {
+        let a = make_config("localhost", "mydb", None, None);
+        let mut b = make_config("localhost", "mydb", None, None);
+        b.temporal_mode = TemporalMode::Epoch;
+        assert_ne!(a.dedup_key(), b.dedup_key());
+    }
+
+    #[test]
+    fn dedup_key_different_tls_policy() {
+        let a = make_config("localhost", "mydb", None, None);
+        let mut b = make_config("localhost", "mydb", None, None);
+        b.tls_min_version = TlsMinVersion::Tls13;
+        assert_ne!(a.dedup_key(), b.dedup_key());
+        let mut c = make_config("localhost", "mydb", None, None);
+        c.tls_cipher_suites = Some(vec!["TLS13_AES_256_GCM_SHA384".into()]);
+        assert_ne!(a.dedup_key(), c.dedup_key());
+    }
+
+    #[test]
+    fn dedup_key_ignores_label() {
+        let a = make_config("localhost", "mydb", None, None);
+        let mut b = make_config("localhost", "mydb", None, None);
+        b.label = Some("reports".into());
+        assert_eq!(a.dedup_key(), b.dedup_key());
+    }
+
+    #[test]
+    fn secrets_are_masked_in_debug_and_effective_config() {
+        let json = r#"{
+            "server": "db", "port": 1433, "database": "app",
+            "auth": {"type": "azure_ad_sp", "tenant_id": "t", "client_id": "c", "client_secret": "hunter2"},
+            "encrypt": true, "trust_server_certificate": false,
+            "connect_timeout_ms": 15000, "request_timeout_ms": 15000,
+            "app_name": "test", "instance_name": null, "packet_size": 4096,
+            "pool": {"max": 4}
+        }"#;
+        let cfg = NormalizedConfig::from_json(json).unwrap();
+        assert!(!format!("{cfg:?}").contains("hunter2"));
+
+        let effective = cfg.effective();
+        assert_eq!(effective["auth"]["client_secret"], "***");
+        assert_eq!(effective["auth"]["client_id"], "c");
+        assert_eq!(effective["pool"]["max"], 4);
+        assert!(effective["pool"]["min"].is_u64());
+        assert_eq!(effective["column_case"], "as-is");
+        assert!(!effective.to_string().contains("hunter2"));
+    }
+
     #[test]
     fn dedup_key_case_insensitive_server() {
         let a = make_config("MyServer", "mydb", None, None);
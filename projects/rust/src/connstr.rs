@@ -0,0 +1,381 @@
+//! ADO.NET connection string parsing, so a config JSON of the form
+//! `{"connection_string": "Server=tcp:host,1433;Database=db;..."}` can be
+//! used wherever a full [`NormalizedConfig`] is accepted, with any other
+//! config fields in the JSON applied on top. Defaults match the TypeScript
+//! parser in `core/config.ts`.
+
+use std::collections::HashMap;
+
+use zeroize::Zeroizing;
+
+use crate::config::{
+    ApplicationIntent, AuthConfig, BigIntMode, ColumnCase, DecimalMode, NormalizedConfig,
+    PoolConfig, TemporalMode, TlsMinVersion, VarcharDecodePolicy,
+};
+use crate::debug::warn_log;
+use crate::error::{MssqlError, Result};
+use crate::secret::Secret;
+
+/// Keyword aliases mapped to their canonical key.
+const KEYWORDS: &[(&str, &str)] = &[
+    ("server", "server"),
+    ("data source", "server"),
+    ("addr", "server"),
+    ("address", "server"),
+    ("network address", "server"),
+    ("database", "database"),
+    ("initial catalog", "database"),
+    ("user id", "user"),
+    ("uid", "user"),
+    ("user", "user"),
+    ("password", "password"),
+    ("pwd", "password"),
+    ("domain", "domain"),
+    ("integrated security", "integrated_security"),
+    ("trusted_connection", "integrated_security"),
+    ("authentication", "authentication"),
+    ("access token", "access_token"),
+    ("tenant id", "tenant_id"),
+    ("server spn", "server_spn"),
+    ("serverspn", "server_spn"),
+    ("encrypt", "encrypt"),
+    ("trustservercertificate", "trust_server_certificate"),
+    ("trust server certificate", "trust_server_certificate"),
+    ("connect timeout", "connect_timeout"),
+    ("connection timeout", "connect_timeout"),
+    ("timeout", "connect_timeout"),
+    ("command timeout", "request_timeout"),
+    ("request timeout", "request_timeout"),
+    ("application name", "app_name"),
+    ("app", "app_name"),
+    ("packet size", "packet_size"),
+    ("min pool size", "min_pool_size"),
+    ("max pool size", "max_pool_size"),
+    ("failover partner", "failover_partner"),
+    ("multisubnetfailover", "multi_subnet_failover"),
+    ("multi subnet failover", "multi_subnet_failover"),
+    ("applicationintent", "application_intent"),
+    ("application intent", "application_intent"),
+    ("connectretrycount", "connect_retry_count"),
+    ("connect retry count", "connect_retry_count"),
+    ("connectretryinterval", "connect_retry_interval"),
+    ("connect retry interval", "connect_retry_interval"),
+];
+
+/// Parse an ADO.NET connection string into a [`NormalizedConfig`].
+pub fn parse(conn_str: &str) -> Result<NormalizedConfig> {
+    let mut map = HashMap::new();
+    for (key, value) in split_pairs(conn_str)? {
+        let key = key.to_lowercase();
+        match KEYWORDS.iter().find(|(alias, _)| *alias == key) {
+            Some((_, canonical)) => {
+                map.insert(*canonical, value);
+            }
+            None => warn_log!("Ignoring connection string keyword: {}", key),
+        }
+    }
+    let get = |key: &str| map.get(key).map(|value: &Zeroizing<String>| value.as_str());
+
+    let (server, port, instance_name) = parse_server(get("server").unwrap_or("localhost"))?;
+
+    Ok(NormalizedConfig {
+        server,
+        port: port.unwrap_or(1433),
+        database: get("database").unwrap_or("master").to_string(),
+        auth: parse_auth(&get)?,
+        encrypt: parse_encrypt(get("encrypt"))?,
+        trust_server_certificate: parse_bool("TrustServerCertificate", get("trust_server_certificate"), true)?,
+        connect_timeout_ms: parse_seconds("Connect Timeout", get("connect_timeout"), 15)?,
+        request_timeout_ms: parse_seconds("Command Timeout", get("request_timeout"), 15)?,
+        app_name: get("app_name").unwrap_or("@tracker1/mssql").to_string(),
+        instance_name,
+        packet_size: parse_number("Packet Size", get("packet_size"))?.unwrap_or(4096),
+        pool: parse_pool(&get)?,
+        decimal_mode: DecimalMode::default(),
+        temporal_mode: TemporalMode::default(),
+        bigint_mode: BigIntMode::default(),
+        parse_json: false,
+        column_case: ColumnCase::AsIs,
+        varchar_encoding: None,
+        varchar_decode: VarcharDecodePolicy::default(),
+        tls_min_version: TlsMinVersion::default(),
+        tls_cipher_suites: None,
+        ca_cert_path: None,
+        ca_cert_pem: None,
+        failover_partner: get("failover_partner").map(str::to_string),
+        multi_subnet_failover: parse_bool("MultiSubnetFailover", get("multi_subnet_failover"), false)?,
+        application_intent: parse_intent(get("application_intent"))?,
+        tcp_keepalive_ms: None,
+        tcp_nodelay: None,
+        connect_retry_count: parse_number("ConnectRetryCount", get("connect_retry_count"))?
+            .unwrap_or(1),
+        connect_retry_interval_ms: parse_seconds(
+            "ConnectRetryInterval",
+            get("connect_retry_interval"),
+            10,
+        )?,
+        slow_query_ms: None,
+        xact_abort: false,
+        label: None,
+    })
+}
+
+/// Split `key=value;` pairs. Values may be quoted with `'` or `"`, with a
+/// doubled quote standing for a literal one. Values are wiped when dropped,
+/// as any of them may be a password or token.
+fn split_pairs(s: &str) -> Result<Vec<(String, Zeroizing<String>)>> {
+    let mut pairs = Vec::new();
+    let mut rest = s;
+    loop {
+        rest = rest.trim_start_matches(|c: char| c == ';' || c.is_whitespace());
+        if rest.is_empty() {
+            return Ok(pairs);
+        }
+        let eq = rest.find('=').ok_or_else(|| {
+            MssqlError::Config(format!("Connection string segment has no '=': {rest}"))
+        })?;
+        let key = rest[..eq].trim().to_string();
+        rest = rest[eq + 1..].trim_start();
+
+        let value = match rest.chars().next() {
+            Some(quote @ ('\'' | '"')) => {
+                // Sized up front so no partial copy is left behind by a regrow
+                let mut value = String::with_capacity(rest.len());
+                let mut chars = rest[1..].char_indices();
+                let mut end = None;
+                while let Some((i, c)) = chars.next() {
+                    if c != quote {
+                        value.push(c);
+                    } else if rest[1 + i + 1..].starts_with(quote) {
+                        value.push(quote);
+                        chars.next();
+                    } else {
+                        end = Some(1 + i + 1);
+                        break;
+                    }
+                }
+                let end = end.ok_or_else(|| {
+                    MssqlError::Config(format!("Unterminated quoted value for '{key}'"))
+                })?;
+                rest = &rest[end..];
+                Zeroizing::new(value)
+            }
+            _ => {
+                let semi = rest.find(';').unwrap_or(rest.len());
+                let value = Zeroizing::new(rest[..semi].trim().to_string());
+                rest = &rest[semi..];
+                value
+            }
+        };
+        if !key.is_empty() {
+            pairs.push((key, value));
+        }
+    }
+}
+
+/// `[tcp:]host`, `host,port` or `host\instance`.
+fn parse_server(raw: &str) -> Result<(String, Option<u16>, Option<String>)> {
+    let s = raw.trim();
+    let s = s
+        .get(..4)
+        .filter(|p| p.eq_ignore_ascii_case("tcp:"))
+        .map_or(s, |_| &s[4..]);
+    if let Some((host, instance)) = s.split_once('\\') {
+        return Ok((host.to_string(), None, Some(instance.to_string())));
+    }
+    if let Some((host, port)) = s.split_once(',') {
+        let port = port
+            .trim()
+            .parse()
+            .map_err(|_| MssqlError::Config(format!("Invalid port in Server: {raw}")))?;
+        return Ok((host.trim().to_string(), Some(port), None));
+    }
+    Ok((s.to_string(), None, None))
+}
+
+fn parse_auth<'a>(get: &impl Fn(&str) -> Option<&'a str>) -> Result<AuthConfig> {
+    let password: Secret = get("password").unwrap_or("").into();
+    let (user, domain) = match (get("user"), get("domain")) {
+        (Some(user), None) => match user.split_once('\\') {
+            Some((domain, user)) if !domain.is_empty() => (Some(user), Some(domain)),
+            _ => (Some(user), None),
+        },
+        other => other,
+    };
+    let authentication = get("authentication")
+        .unwrap_or("")
+        .to_lowercase()
+        .replace([' ', '_'], "");
+
+    let auth = match authentication.as_str() {
+        "activedirectoryaccesstoken" => AuthConfig::AzureAdToken {
+            token: get("access_token")
+                .ok_or_else(|| MssqlError::Config("ActiveDirectoryAccessToken requires Access Token".into()))?
+                .into(),
+        },
+        "activedirectorymanagedidentity" | "activedirectorymsi" => AuthConfig::AzureAdMsi {
+            client_id: user.map(str::to_string),
+        },
+        "activedirectoryserviceprincipal" => AuthConfig::AzureAdSp {
+            tenant_id: get("tenant_id")
+                .ok_or_else(|| MssqlError::Config("Service principal auth requires Tenant Id".into()))?
+                .to_string(),
+            client_id: user
+                .ok_or_else(|| MssqlError::Config("Service principal auth requires User Id".into()))?
+                .to_string(),
+            client_secret: password,
+        },
+        "activedirectorypassword" => AuthConfig::AzureAd {
+            username: user.unwrap_or("").to_string(),
+            password,
+        },
+        "kerberos" => AuthConfig::Kerberos {
+            spn: get("server_spn").map(str::to_string),
+        },
+        "activedirectoryintegrated" => AuthConfig::Windows,
+        "" | "sqlpassword" => {
+            let integrated = get("integrated_security")
+                .is_some_and(|v| ["true", "yes", "sspi"].contains(&v.to_lowercase().as_str()));
+            match (user, domain) {
+                _ if integrated => AuthConfig::Windows,
+                (Some(user), Some(domain)) => AuthConfig::Ntlm {
+                    username: user.to_string(),
+                    password,
+                    domain: domain.to_string(),
+                },
+                (Some(user), None) => AuthConfig::Sql {
+                    username: user.to_string(),
+                    password,
+                },
+                (None, _) => AuthConfig::Windows,
+            }
+        }
+        _ => {
+            return Err(MssqlError::Config(format!(
+                "Unsupported Authentication: {}",
+                get("authentication").unwrap_or("")
+            )))
+        }
+    };
+    Ok(auth)
+}
+
+/// `Encrypt` accepts booleans plus the SqlClient 5 modes.
+fn parse_encrypt(value: Option<&str>) -> Result<bool> {
+    match value.map(str::to_lowercase).as_deref() {
+        Some("mandatory" | "strict") => Ok(true),
+        Some("optional") => Ok(false),
+        _ => parse_bool("Encrypt", value, true),
+    }
+}
+
+fn parse_intent(value: Option<&str>) -> Result<ApplicationIntent> {
+    match value.map(str::to_lowercase).as_deref() {
+        None | Some("readwrite") => Ok(ApplicationIntent::ReadWrite),
+        Some("readonly") => Ok(ApplicationIntent::ReadOnly),
+        Some(_) => Err(MssqlError::Config(format!(
+            "Invalid ApplicationIntent: {}",
+            value.unwrap_or("")
+        ))),
+    }
+}
+
+fn parse_bool(key: &str, value: Option<&str>, default: bool) -> Result<bool> {
+    let Some(value) = value else {
+        return Ok(default);
+    };
+    match value.to_lowercase().as_str() {
+        "true" | "yes" | "1" => Ok(true),
+        "false" | "no" | "0" => Ok(false),
+        _ => Err(MssqlError::Config(format!("Invalid {key}: {value}"))),
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(key: &str, value: Option<&str>) -> Result<Option<T>> {
+    value
+        .map(|v| {
+            v.trim()
+                .parse()
+                .map_err(|_| MssqlError::Config(format!("Invalid {key}: {v}")))
+        })
+        .transpose()
+}
+
+fn parse_seconds(key: &str, value: Option<&str>, default_secs: u64) -> Result<u64> {
+    Ok(parse_number::<u64>(key, value)?.unwrap_or(default_secs) * 1000)
+}
+
+fn parse_pool<'a>(get: &impl Fn(&str) -> Option<&'a str>) -> Result<Option<PoolConfig>> {
+    let min = parse_number("Min Pool Size", get("min_pool_size"))?;
+    let max = parse_number("Max Pool Size", get("max_pool_size"))?;
+    Ok((min.is_some() || max.is_some()).then_some(PoolConfig {
+        min,
+        max,
+        idle_timeout_ms: None,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_azure_style_string() {
+        let cfg = parse(
+            "Server=tcp:myserver.database.windows.net,1433;Initial Catalog=mydb;\
+             User ID=app;Password='p;w''d';Encrypt=True;TrustServerCertificate=False;\
+             Connection Timeout=30;MultipleActiveResultSets=False;MultiSubnetFailover=True;\
+             Failover Partner=mirror;",
+        )
+        .unwrap();
+        assert_eq!(cfg.server, "myserver.database.windows.net");
+        assert_eq!(cfg.port, 1433);
+        assert_eq!(cfg.database, "mydb");
+        assert!(matches!(&cfg.auth, AuthConfig::Sql { username, password }
+            if username == "app" && password.expose() == "p;w'd"));
+        assert!(cfg.encrypt);
+        assert!(!cfg.trust_server_certificate);
+        assert_eq!(cfg.connect_timeout_ms, 30_000);
+        assert!(cfg.multi_subnet_failover);
+        assert_eq!(cfg.failover_partner.as_deref(), Some("mirror"));
+    }
+
+    #[test]
+    fn parses_instance_and_defaults() {
+        let cfg = parse("Data Source=host\\SQLEXPRESS;Integrated Security=SSPI").unwrap();
+        assert_eq!(cfg.server, "host");
+        assert_eq!(cfg.instance_name.as_deref(), Some("SQLEXPRESS"));
+        assert_eq!(cfg.port, 1433);
+        assert_eq!(cfg.database, "master");
+        assert!(matches!(cfg.auth, AuthConfig::Windows));
+        assert!(cfg.pool.is_none());
+    }
+
+    #[test]
+    fn parses_auth_modes() {
+        let auth = |s: &str| parse(s).unwrap().auth;
+        assert!(matches!(auth("Server=h;User Id=CORP\\bob;Password=p"),
+            AuthConfig::Ntlm { domain, username, .. } if domain == "CORP" && username == "bob"));
+        assert!(matches!(auth("Server=h;Authentication=Active Directory Managed Identity"),
+            AuthConfig::AzureAdMsi { client_id: None }));
+        assert!(matches!(auth("Server=h;Authentication=ActiveDirectoryServicePrincipal;User Id=c;Password=s;Tenant Id=t"),
+            AuthConfig::AzureAdSp { .. }));
+        assert!(parse("Server=h;Authentication=ActiveDirectoryDeviceCodeFlow").is_err());
+    }
+
+    #[test]
+    fn encrypt_modes_and_pool() {
+        let cfg = parse("Server=h;Encrypt=Optional;Min Pool Size=2;Max Pool Size=8").unwrap();
+        assert!(!cfg.encrypt);
+        assert_eq!(cfg.application_intent, ApplicationIntent::ReadWrite);
+        let cfg = parse("Server=h;ApplicationIntent=ReadOnly").unwrap();
+        assert_eq!(cfg.application_intent, ApplicationIntent::ReadOnly);
+        assert!(parse("Server=h;ApplicationIntent=ReadMostly").is_err());
+        let pool = cfg.pool.unwrap();
+        assert_eq!((pool.min, pool.max), (Some(2), Some(8)));
+        assert!(parse("Server=h;Encrypt=Strict").unwrap().encrypt);
+        assert!(parse("Server=h;Encrypt=maybe").is_err());
+        assert!(parse("Server=h,notaport").is_err());
+        assert!(parse("Server=h;Password='open").is_err());
+    }
+}
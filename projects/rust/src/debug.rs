@@ -1,39 +1,404 @@
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::os::raw::c_char;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Mutex, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
 
-static DEBUG_ENABLED: AtomicBool = AtomicBool::new(false);
+use serde::Deserialize;
 
-/// Initialize debug mode from environment variable.
-/// Called once during the first FFI call.
+use crate::logfile::{self, LogFile};
+use crate::redact;
+
+/// Most verbose level any category is enabled at (0 = off), so disabled
+/// records are skipped without taking the filter lock.
+static MAX_LEVEL: AtomicU32 = AtomicU32::new(Level::Error as u32);
+static FILTER: RwLock<Filter> = RwLock::new(Filter::errors_only());
+
+/// Receives each log record: level, target (Rust module path), message and
+/// Unix time in milliseconds. The strings are only valid during the call.
+pub type LogCallback = extern "C" fn(u32, *const c_char, *const c_char, u64);
+
+static LOG_CALLBACK: RwLock<Option<LogCallback>> = RwLock::new(None);
+static LOG_FILE: Mutex<Option<LogFile>> = Mutex::new(None);
+
+/// Log record severity, passed to the callback as its `u32` value.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+#[repr(u32)]
+pub enum Level {
+    Error = 1,
+    Warn = 2,
+    Info = 3,
+    Debug = 4,
+    Trace = 5,
+}
+
+impl Level {
+    fn name(self) -> &'static str {
+        match self {
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+            Level::Trace => "TRACE",
+        }
+    }
+
+    /// Parse a level name; `off` is `Ok(None)`.
+    fn parse(s: &str) -> Result<Option<Level>, String> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "off" => Ok(None),
+            "error" => Ok(Some(Level::Error)),
+            "warn" => Ok(Some(Level::Warn)),
+            "info" => Ok(Some(Level::Info)),
+            "debug" => Ok(Some(Level::Debug)),
+            "trace" => Ok(Some(Level::Trace)),
+            other => Err(format!("Unknown log level: {other}")),
+        }
+    }
+}
+
+/// Which records are emitted: a default level plus per-category overrides.
+/// A record's category is the last segment of its target (`mssqlts::pool`
+/// → `pool`).
+#[derive(Debug, PartialEq)]
+pub struct Filter {
+    default: Option<Level>,
+    categories: Vec<(String, Option<Level>)>,
+}
+
+impl Filter {
+    const fn errors_only() -> Self {
+        Filter {
+            default: Some(Level::Error),
+            categories: Vec::new(),
+        }
+    }
+
+    /// Parse `MSSQLTS_LOG` syntax: comma-separated `category=level` pairs,
+    /// with an optional bare level as the default (`info,pool=trace`).
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut filter = Filter::errors_only();
+        for part in spec.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            match part.split_once('=') {
+                Some((category, level)) => filter
+                    .categories
+                    .push((category.trim().to_ascii_lowercase(), Level::parse(level)?)),
+                None => filter.default = Level::parse(part)?,
+            }
+        }
+        Ok(filter)
+    }
+
+    fn level_for(&self, target: &str) -> Option<Level> {
+        let category = target.rsplit("::").next().unwrap_or(target);
+        self.categories
+            .iter()
+            .find(|(name, _)| name == category)
+            .map_or(self.default, |(_, level)| *level)
+    }
+
+    fn max_level(&self) -> u32 {
+        self.categories
+            .iter()
+            .map(|(_, level)| *level)
+            .chain([self.default])
+            .map(|level| level.map_or(0, |l| l as u32))
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// `mssql_set_debug_config` JSON: `{"level":"info","categories":{"pool":"trace"}}`,
+/// optionally with `file`, `max_file_bytes` and `max_files`.
+#[derive(Deserialize, Debug, Default)]
+pub struct LogConfig {
+    pub level: Option<String>,
+    #[serde(default)]
+    pub categories: HashMap<String, String>,
+    /// Log file path; omitted leaves file output as it is, `""` stops it.
+    pub file: Option<String>,
+    pub max_file_bytes: Option<u64>,
+    pub max_files: Option<u32>,
+    /// `false` logs SQL literals and secrets unmasked; omitted leaves it as is.
+    pub redact: Option<bool>,
+}
+
+impl LogConfig {
+    pub fn into_filter(self) -> Result<Filter, String> {
+        let mut filter = Filter::errors_only();
+        if let Some(level) = self.level {
+            filter.default = Level::parse(&level)?;
+        }
+        for (category, level) in self.categories {
+            filter
+                .categories
+                .push((category.to_ascii_lowercase(), Level::parse(&level)?));
+        }
+        Ok(filter)
+    }
+
+    /// Install the filter and, when `file` is given, the log file.
+    pub fn apply(mut self) -> Result<(), String> {
+        let file = self.file.take();
+        let redact = self.redact;
+        let (max_bytes, max_files) = (self.max_file_bytes, self.max_files);
+        let filter = self.into_filter()?;
+        if let Some(path) = file {
+            let path = Some(path).filter(|p| !p.is_empty());
+            set_log_file(path.as_deref(), max_bytes, max_files)
+                .map_err(|e| format!("Cannot open log file: {e}"))?;
+        }
+        if let Some(enabled) = redact {
+            redact::set_enabled(enabled);
+        }
+        set_filter(filter);
+        Ok(())
+    }
+}
+
+/// Initialize the filter from `MSSQLTS_LOG`, or `MSSQLTS_DEBUG=1` for debug
+/// level everywhere, file output from `MSSQLTS_LOG_FILE`, and
+/// `MSSQLTS_LOG_REDACT=0` to turn redaction off. Called once during the first
+/// FFI call.
 pub fn init() {
-    if std::env::var("MSSQLTS_DEBUG")
+    if std::env::var("MSSQLTS_LOG_REDACT")
+        .map(|v| v == "0" || v.eq_ignore_ascii_case("false"))
+        .unwrap_or(false)
+    {
+        redact::set_enabled(false);
+    }
+    if let Ok(path) = std::env::var("MSSQLTS_LOG_FILE") {
+        if let Err(e) = set_log_file(Some(&path), None, None) {
+            log(
+                Level::Error,
+                module_path!(),
+                format!("MSSQLTS_LOG_FILE: {e}"),
+            );
+        }
+    }
+    if let Ok(spec) = std::env::var("MSSQLTS_LOG") {
+        match Filter::parse(&spec) {
+            Ok(filter) => set_filter(filter),
+            Err(e) => log(Level::Error, module_path!(), format!("MSSQLTS_LOG: {e}")),
+        }
+    } else if std::env::var("MSSQLTS_DEBUG")
         .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
         .unwrap_or(false)
     {
-        DEBUG_ENABLED.store(true, Ordering::SeqCst);
-        eprintln!("[mssqlts] Debug mode enabled via MSSQLTS_DEBUG");
+        set_debug(true);
     }
 }
 
-/// Set debug mode at runtime.
+/// Debug level for every category, or back to errors only.
 pub fn set_debug(enabled: bool) {
-    DEBUG_ENABLED.store(enabled, Ordering::SeqCst);
+    set_filter(Filter {
+        default: Some(if enabled { Level::Debug } else { Level::Error }),
+        categories: Vec::new(),
+    });
     if enabled {
-        eprintln!("[mssqlts] Debug mode enabled");
+        log(Level::Info, module_path!(), "Debug mode enabled".into());
     }
 }
 
-/// Check if debug mode is active.
-pub fn is_debug() -> bool {
-    DEBUG_ENABLED.load(Ordering::SeqCst)
+/// Replace the active filter.
+pub fn set_filter(filter: Filter) {
+    let mut active = FILTER.write().unwrap();
+    MAX_LEVEL.store(filter.max_level(), Ordering::SeqCst);
+    *active = filter;
 }
 
-/// Log a debug message to stderr if debug mode is enabled.
-macro_rules! debug_log {
-    ($($arg:tt)*) => {
-        if $crate::debug::is_debug() {
-            eprintln!("[mssqlts] {}", format!($($arg)*));
+/// Whether a record at `level` from `target` would be emitted.
+pub fn enabled(level: Level, target: &str) -> bool {
+    if level as u32 > MAX_LEVEL.load(Ordering::Relaxed) {
+        return false;
+    }
+    FILTER
+        .read()
+        .unwrap()
+        .level_for(target)
+        .is_some_and(|max| level <= max)
+}
+
+/// Write records to a size-rotated file instead of stderr; `None` closes it.
+pub fn set_log_file(
+    path: Option<&str>,
+    max_bytes: Option<u64>,
+    max_files: Option<u32>,
+) -> std::io::Result<()> {
+    let file = path
+        .map(|path| {
+            LogFile::open(
+                path,
+                max_bytes.unwrap_or(logfile::DEFAULT_MAX_BYTES),
+                max_files.unwrap_or(logfile::DEFAULT_MAX_FILES),
+            )
+        })
+        .transpose()?;
+    let mut active = LOG_FILE.lock().unwrap();
+    if let Some(old) = active.as_mut() {
+        let _ = old.flush();
+    }
+    *active = file;
+    Ok(())
+}
+
+/// Flush buffered log file output.
+pub fn flush() {
+    if let Some(file) = LOG_FILE.lock().unwrap().as_mut() {
+        let _ = file.flush();
+    }
+}
+
+/// Deliver records to `callback` instead of stderr; `None` restores stderr.
+/// A record already being delivered on another thread may still reach the
+/// previous callback, so it must stay callable.
+pub fn set_log_callback(callback: Option<LogCallback>) {
+    *LOG_CALLBACK.write().unwrap() = callback;
+}
+
+/// Send a record to the registered callback, else the log file, else stderr.
+/// Secrets in the message are masked unless redaction is off.
+pub fn log(level: Level, target: &str, message: String) {
+    let message = redact::text(message);
+    // Copy the pointer out so a slow callback doesn't block replacing it.
+    let callback = *LOG_CALLBACK.read().unwrap();
+    let Some(callback) = callback else {
+        if let Some(file) = LOG_FILE.lock().unwrap().as_mut() {
+            let line = format!(
+                "{} {:5} {target} {message}",
+                chrono::Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ"),
+                level.name()
+            );
+            // Flush on errors so the lines leading up to a failure are on disk.
+            if file.write_line(&line, level == Level::Error).is_ok() {
+                return;
+            }
+        }
+        match level {
+            Level::Error => eprintln!("[@tracker1/mssql] {message}"),
+            _ => eprintln!("[mssqlts] {message}"),
+        }
+        return;
+    };
+    let timestamp_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default();
+    let target = c_string(target.to_string());
+    let message = c_string(message);
+    callback(
+        level as u32,
+        target.as_ptr(),
+        message.as_ptr(),
+        timestamp_ms,
+    );
+}
+
+/// NUL bytes would cut the message short on the C side; drop them.
+fn c_string(s: String) -> CString {
+    CString::new(s).unwrap_or_else(|e| {
+        let mut bytes = e.into_vec();
+        bytes.retain(|&b| b != 0);
+        CString::new(bytes).unwrap_or_default()
+    })
+}
+
+/// Log at `level` if the filter allows it. The target defaults to the
+/// calling module; `target: "mssqlts::query"` files a record under another
+/// category.
+macro_rules! log_at {
+    ($level:expr, target: $target:expr, $($arg:tt)*) => {{
+        let level = $level;
+        let target: &str = $target;
+        if $crate::debug::enabled(level, target) {
+            $crate::debug::log(level, target, format!($($arg)*));
         }
+    }};
+    ($level:expr, $($arg:tt)*) => {
+        $crate::debug::log_at!($level, target: module_path!(), $($arg)*)
     };
 }
 
+macro_rules! error_log {
+    ($($arg:tt)*) => { $crate::debug::log_at!($crate::debug::Level::Error, $($arg)*) };
+}
+
+macro_rules! warn_log {
+    ($($arg:tt)*) => { $crate::debug::log_at!($crate::debug::Level::Warn, $($arg)*) };
+}
+
+macro_rules! info_log {
+    ($($arg:tt)*) => { $crate::debug::log_at!($crate::debug::Level::Info, $($arg)*) };
+}
+
+macro_rules! debug_log {
+    ($($arg:tt)*) => { $crate::debug::log_at!($crate::debug::Level::Debug, $($arg)*) };
+}
+
+macro_rules! trace_log {
+    ($($arg:tt)*) => { $crate::debug::log_at!($crate::debug::Level::Trace, $($arg)*) };
+}
+
 pub(crate) use debug_log;
+pub(crate) use error_log;
+pub(crate) use info_log;
+pub(crate) use log_at;
+pub(crate) use trace_log;
+pub(crate) use warn_log;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CStr;
+    use std::sync::Mutex;
+
+    static RECEIVED: Mutex<Vec<(u32, String, String, u64)>> = Mutex::new(Vec::new());
+
+    extern "C" fn capture(level: u32, target: *const c_char, message: *const c_char, ts: u64) {
+        let (target, message) = unsafe { (CStr::from_ptr(target), CStr::from_ptr(message)) };
+        RECEIVED.lock().unwrap().push((
+            level,
+            target.to_string_lossy().into_owned(),
+            message.to_string_lossy().into_owned(),
+            ts,
+        ));
+    }
+
+    #[test]
+    fn callback_receives_records() {
+        set_log_callback(Some(capture));
+        log(Level::Info, "mssqlts::pool", "pool\0 exhausted".into());
+        set_log_callback(None);
+        let received = RECEIVED.lock().unwrap();
+        let record = received
+            .iter()
+            .find(|r| r.1 == "mssqlts::pool")
+            .expect("record delivered");
+        assert_eq!((record.0, record.2.as_str()), (3, "pool exhausted"));
+        assert!(record.3 > 0);
+    }
+
+    #[test]
+    fn filter_spec_sets_default_and_categories() {
+        let filter = Filter::parse("info, pool=trace,query=off").unwrap();
+        assert_eq!(filter.level_for("mssqlts::pool"), Some(Level::Trace));
+        assert_eq!(filter.level_for("mssqlts::query"), None);
+        assert_eq!(filter.level_for("mssqlts::stream"), Some(Level::Info));
+        assert_eq!(filter.max_level(), Level::Trace as u32);
+
+        let filter = Filter::parse("stream=debug").unwrap();
+        assert_eq!(filter.level_for("mssqlts"), Some(Level::Error));
+        assert!(Filter::parse("pool=loud").is_err());
+    }
+
+    #[test]
+    fn config_json_matches_spec() {
+        let config: LogConfig =
+            serde_json::from_str(r#"{"level":"warn","categories":{"Pool":"debug"}}"#).unwrap();
+        assert_eq!(
+            config.into_filter().unwrap(),
+            Filter::parse("warn,pool=debug").unwrap()
+        );
+    }
+}
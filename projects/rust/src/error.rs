@@ -7,6 +7,8 @@ pub enum MssqlError {
     Query(String),
     Transaction(String),
     Pool(String),
+    /// A handle id that was freed, is of another kind, or was never issued.
+    StaleHandle(String),
     Cancelled,
 }
 
@@ -18,6 +20,7 @@ impl fmt::Display for MssqlError {
             MssqlError::Query(msg) => write!(f, "Query error: {msg}"),
             MssqlError::Transaction(msg) => write!(f, "Transaction error: {msg}"),
             MssqlError::Pool(msg) => write!(f, "Pool error: {msg}"),
+            MssqlError::StaleHandle(msg) => write!(f, "Stale handle: {msg}"),
             MssqlError::Cancelled => write!(f, "Operation cancelled"),
         }
     }
@@ -61,6 +64,9 @@ impl From<mssql_client::Error> for MssqlError {
                 MssqlError::Connection(format!("Authentication error: {e}"))
             }
             mssql_client::Error::Cancelled => MssqlError::Cancelled,
+            mssql_client::Error::Routing { host, port } => MssqlError::Connection(format!(
+                "Server routed the connection to {host}:{port} after too many redirects"
+            )),
             other => MssqlError::Query(format!("{other}")),
         }
     }
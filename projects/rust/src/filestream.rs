@@ -1,13 +1,25 @@
+use mssql_client::{Client, Ready, Row, SqlValue, ToSql};
+use serde::Deserialize;
+
+use crate::blob::{key_filter, quote_object_name};
+use crate::bulk::bracket_escape;
+use crate::error::{MssqlError, Result};
+use crate::query::{param_to_boxed, SerializedParam};
+
 #[cfg(windows)]
 mod platform {
     use std::ffi::CString;
     use std::sync::OnceLock;
 
     use windows::Win32::Foundation::{CloseHandle, HANDLE};
-    use windows::Win32::Storage::FileSystem::{ReadFile, WriteFile};
+    use windows::Win32::Storage::FileSystem::{
+        ReadFile, SetEndOfFile, SetFilePointerEx, WriteFile, FILE_BEGIN, FILE_END,
+    };
     use windows::Win32::System::LibraryLoader::{GetProcAddress, LoadLibraryA};
+    use windows::Win32::System::IO::DeviceIoControl;
     use windows::core::PCSTR;
 
+    use super::{block, locate, with_client, FilestreamTarget, WriteMode};
     use crate::error::{MssqlError, Result};
 
     type OpenSqlFilestreamFn = unsafe extern "system" fn(
@@ -23,6 +35,10 @@ mod platform {
     const SQL_FILESTREAM_WRITE: u32 = 1;
     const SQL_FILESTREAM_READWRITE: u32 = 2;
 
+    /// CTL_CODE(FILE_DEVICE_FILE_SYSTEM, 2392, METHOD_BUFFERED, FILE_ANY_ACCESS).
+    /// Without it a handle opened for writing starts out empty.
+    const FSCTL_SQL_FILESTREAM_FETCH_OLD_CONTENT: u32 = 0x0009_2560;
+
     static OPEN_FN: OnceLock<std::result::Result<OpenSqlFilestreamFn, String>> = OnceLock::new();
 
     fn resolve_open_fn() -> std::result::Result<OpenSqlFilestreamFn, String> {
@@ -70,7 +86,12 @@ mod platform {
     }
 
     impl FilestreamHandle {
-        pub fn open(path: &str, tx_context: &[u8], mode: FilestreamMode) -> Result<Self> {
+        pub fn open(
+            path: &str,
+            tx_context: &[u8],
+            mode: FilestreamMode,
+            write_mode: WriteMode,
+        ) -> Result<Self> {
             let open_fn = get_open_fn()?;
 
             let path_cstr = CString::new(path)
@@ -99,7 +120,61 @@ mod platform {
                 )));
             }
 
-            Ok(Self { handle })
+            let fs = Self { handle };
+            if let (WriteMode::Append, FilestreamMode::Write | FilestreamMode::ReadWrite) =
+                (write_mode, mode)
+            {
+                fs.keep_old_content()?;
+            }
+            Ok(fs)
+        }
+
+        /// Look up the row's path and transaction context on the connection,
+        /// then open it. Runs on the blocking pool.
+        pub fn open_for(
+            conn_id: u64,
+            target: &FilestreamTarget,
+            mode: FilestreamMode,
+            write_mode: WriteMode,
+        ) -> Result<Self> {
+            let (path, tx_context) = with_client(conn_id, |client| block(locate(client, target)))?;
+            Self::open(&path, &tx_context, mode, write_mode)
+        }
+
+        /// Copy the existing value into the write handle and move to its end.
+        fn keep_old_content(&self) -> Result<()> {
+            let mut returned: u32 = 0;
+            unsafe {
+                DeviceIoControl(
+                    self.handle,
+                    FSCTL_SQL_FILESTREAM_FETCH_OLD_CONTENT,
+                    None,
+                    0,
+                    None,
+                    0,
+                    Some(&mut returned),
+                    None,
+                )
+                .map_err(|e| {
+                    MssqlError::Query(format!("FILESTREAM fetch of old content failed: {e}"))
+                })?;
+                SetFilePointerEx(self.handle, 0, None, FILE_END)
+                    .map_err(|e| MssqlError::Query(format!("FILESTREAM seek failed: {e}")))?;
+            }
+            Ok(())
+        }
+
+        /// Cut or extend the value to `len` bytes; later writes start there.
+        pub fn set_length(&self, len: u64) -> Result<()> {
+            let len = i64::try_from(len)
+                .map_err(|_| MssqlError::Query(format!("FILESTREAM length {len} too large")))?;
+            unsafe {
+                SetFilePointerEx(self.handle, len, None, FILE_BEGIN)
+                    .map_err(|e| MssqlError::Query(format!("FILESTREAM seek failed: {e}")))?;
+                SetEndOfFile(self.handle)
+                    .map_err(|e| MssqlError::Query(format!("FILESTREAM set length failed: {e}")))?;
+            }
+            Ok(())
         }
 
         pub fn read(&self, buf: &mut [u8]) -> Result<usize> {
@@ -144,6 +219,16 @@ mod platform {
             }
             Ok(())
         }
+
+        /// Nothing to do: the writes commit with the caller's transaction.
+        pub fn finish(&self) -> Result<()> {
+            Ok(())
+        }
+
+        /// The file handle is closed on drop.
+        pub fn close(&self) -> Result<()> {
+            Ok(())
+        }
     }
 
     impl Drop for FilestreamHandle {
@@ -156,9 +241,9 @@ mod platform {
 
     // HANDLE is a Windows kernel object. Transferring ownership between threads
     // is safe — Win32 file handles are reference-counted by the kernel and can
-    // be used from any thread. All access in this crate is serialized through
-    // the Mutex<HashMap<u64, FilestreamHandle>> in lib.rs, so there is no risk
-    // of concurrent ReadFile/WriteFile on the same handle.
+    // be used from any thread. Each handle sits behind its own Mutex in lib.rs
+    // (the I/O runs on the blocking pool), so there is no risk of concurrent
+    // ReadFile/WriteFile on the same handle.
     unsafe impl Send for FilestreamHandle {}
 
     pub fn is_available() -> bool {
@@ -166,9 +251,28 @@ mod platform {
     }
 }
 
+/// Without OpenSqlFilestream the handle is emulated with chunked statements
+/// on the connection that opened it: `SUBSTRING` windows for reads and
+/// `UPDATE ... SET column.WRITE(...)` for writes. Only row-key opens work,
+/// and the column must be a plain varbinary(max) for writes (`.WRITE` cannot
+/// target FILESTREAM storage).
+///
+/// The handle keeps the connection's client until it is closed, so nothing
+/// else runs on the connection in between. A write handle opened outside a
+/// transaction runs in one of its own, committed by `finish`; closing it
+/// any other way rolls the writes back.
 #[cfg(not(windows))]
 mod platform {
+    use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    use mssql_client::{Client, Ready};
+
+    use super::{block, EmulatedSql, FilestreamTarget, WriteMode};
+    use crate::debug::warn_log;
     use crate::error::{MssqlError, Result};
+    use crate::handle::{ConnHandle, MssqlClient};
+    use crate::transaction::{self, Session};
 
     #[derive(Clone, Copy)]
     pub enum FilestreamMode {
@@ -177,24 +281,591 @@ mod platform {
         ReadWrite,
     }
 
-    pub struct FilestreamHandle;
+    /// Most zero bytes written by one statement when `set_length` extends.
+    const ZERO_FILL_CHUNK: u64 = 1024 * 1024;
+
+    pub struct FilestreamHandle {
+        conn: Arc<ConnHandle>,
+        /// Checked out of the connection until `close`.
+        client: Mutex<Option<MssqlClient>>,
+        /// The settings from before the handle's own transaction, while
+        /// that is open.
+        own_transaction: Mutex<Option<Session>>,
+        /// Set by a failed statement; `finish` then rolls back.
+        failed: AtomicBool,
+        sql: EmulatedSql,
+        position: AtomicU64,
+        length: AtomicU64,
+    }
 
-    #[allow(dead_code)] // stub implementation for non-Windows platforms
     impl FilestreamHandle {
-        pub fn open(_path: &str, _tx_context: &[u8], _mode: FilestreamMode) -> Result<Self> {
+        pub fn open(
+            _path: &str,
+            _tx_context: &[u8],
+            _mode: FilestreamMode,
+            _write_mode: WriteMode,
+        ) -> Result<Self> {
             Err(MssqlError::Connection(
-                "FILESTREAM is only available on Windows.\n\
-                 Use varbinary(max) with standard queries on other platforms."
+                "Opening FILESTREAM by path is only available on Windows.\n\
+                 Open it by table, column and row key on other platforms."
                     .into(),
             ))
         }
-        pub fn read(&self, _buf: &mut [u8]) -> Result<usize> { unreachable!() }
-        pub fn read_all(&self) -> Result<Vec<u8>> { unreachable!() }
-        pub fn write(&self, _data: &[u8]) -> Result<usize> { unreachable!() }
-        pub fn write_all(&self, _data: &[u8]) -> Result<()> { unreachable!() }
+
+        /// Check out the connection, check the row exists and prepare it
+        /// for writing. Runs on the blocking pool.
+        pub fn open_for(
+            conn_id: u64,
+            target: &FilestreamTarget,
+            mode: FilestreamMode,
+            write_mode: WriteMode,
+        ) -> Result<Self> {
+            let sql = EmulatedSql::build(target)?;
+            let start = match mode {
+                FilestreamMode::Read => None,
+                FilestreamMode::Write | FilestreamMode::ReadWrite => Some(write_mode),
+            };
+            let conn = crate::handle::get_conn(conn_id)?;
+            let in_transaction = conn.active_transaction.lock().unwrap().is_some();
+            let mut mc = conn
+                .client
+                .lock()
+                .unwrap()
+                .take()
+                .ok_or_else(|| MssqlError::Connection("Connection is in use".into()))?;
+            let opened = match mc.as_client_mut() {
+                Some(client) => block(async {
+                    let own = match start {
+                        Some(_) if !in_transaction => Some(begin(client).await?),
+                        _ => None,
+                    };
+                    match sql.open(client, start).await {
+                        Ok(length) => Ok((length, own)),
+                        Err(e) => {
+                            if let Some(prior) = own {
+                                let end = "ROLLBACK TRANSACTION";
+                                let _ = transaction::end(client, end, Some(prior)).await;
+                            }
+                            Err(e)
+                        }
+                    }
+                }),
+                None => Err(MssqlError::Connection("Cannot access client".into())),
+            };
+            let (length, own) = match opened {
+                Ok(opened) => opened,
+                Err(e) => {
+                    *conn.client.lock().unwrap() = Some(mc);
+                    return Err(e);
+                }
+            };
+            let position = match start {
+                Some(WriteMode::Append) => length,
+                _ => 0,
+            };
+            Ok(Self {
+                conn,
+                client: Mutex::new(Some(mc)),
+                own_transaction: Mutex::new(own),
+                failed: AtomicBool::new(false),
+                sql,
+                position: AtomicU64::new(position),
+                length: AtomicU64::new(length),
+            })
+        }
+
+        /// Run `f` on the checked-out client; a failure marks the handle.
+        fn with_client<T>(&self, f: impl FnOnce(&mut Client<Ready>) -> Result<T>) -> Result<T> {
+            let mut guard = self.client.lock().unwrap();
+            let result = match guard.as_mut().and_then(|mc| mc.as_client_mut()) {
+                Some(client) => f(client),
+                None => Err(MssqlError::Connection("FILESTREAM handle is closed".into())),
+            };
+            if result.is_err() {
+                self.failed.store(true, Ordering::SeqCst);
+            }
+            result
+        }
+
+        pub fn read(&self, buf: &mut [u8]) -> Result<usize> {
+            let offset = self.position.load(Ordering::SeqCst);
+            let data =
+                self.with_client(|client| block(self.sql.read(client, offset, buf.len() as u64)))?;
+            let n = data.len().min(buf.len());
+            buf[..n].copy_from_slice(&data[..n]);
+            self.position.fetch_add(n as u64, Ordering::SeqCst);
+            Ok(n)
+        }
+
+        pub fn read_all(&self) -> Result<Vec<u8>> {
+            let mut result = Vec::new();
+            let mut buf = vec![0u8; 1024 * 1024];
+            loop {
+                let n = self.read(&mut buf)?;
+                if n == 0 {
+                    break;
+                }
+                result.extend_from_slice(&buf[..n]);
+            }
+            Ok(result)
+        }
+
+        pub fn write(&self, data: &[u8]) -> Result<usize> {
+            let offset = self.position.load(Ordering::SeqCst);
+            self.with_client(|client| block(self.sql.write(client, offset, data)))?;
+            let end = offset + data.len() as u64;
+            self.position.store(end, Ordering::SeqCst);
+            self.length.fetch_max(end, Ordering::SeqCst);
+            Ok(data.len())
+        }
+
+        pub fn write_all(&self, data: &[u8]) -> Result<()> {
+            self.write(data).map(|_| ())
+        }
+
+        pub fn set_length(&self, len: u64) -> Result<()> {
+            let length = self.length.load(Ordering::SeqCst);
+            if len < length {
+                self.with_client(|client| block(self.sql.truncate(client, len)))?;
+            } else if len > length {
+                // Zero-filled a chunk at a time, so a large extension isn't
+                // held in memory (or cut short by a 32-bit usize)
+                let zeros = vec![0u8; (len - length).min(ZERO_FILL_CHUNK) as usize];
+                let mut end = length;
+                while end < len {
+                    let n = (len - end).min(ZERO_FILL_CHUNK) as usize;
+                    self.with_client(|client| block(self.sql.write(client, end, &zeros[..n])))?;
+                    end += n as u64;
+                    self.length.store(end, Ordering::SeqCst);
+                }
+            }
+            self.position.store(len, Ordering::SeqCst);
+            self.length.store(len, Ordering::SeqCst);
+            Ok(())
+        }
+
+        /// Commit the handle's own transaction, or roll it back if a
+        /// statement failed. The handle stays open until `close`.
+        pub fn finish(&self) -> Result<()> {
+            let failed = self.failed.load(Ordering::SeqCst);
+            let end = if failed {
+                "ROLLBACK TRANSACTION"
+            } else {
+                "COMMIT TRANSACTION"
+            };
+            self.end_own_transaction(end)?;
+            if failed {
+                return Err(MssqlError::Query(
+                    "FILESTREAM writes rolled back after a failed statement".into(),
+                ));
+            }
+            Ok(())
+        }
+
+        /// Roll back writes not yet finished and give the client back to
+        /// the connection.
+        pub fn close(&self) -> Result<()> {
+            let result = self.end_own_transaction("ROLLBACK TRANSACTION");
+            if let Some(mc) = self.client.lock().unwrap().take() {
+                *self.conn.client.lock().unwrap() = Some(mc);
+            }
+            result
+        }
+
+        fn end_own_transaction(&self, end: &str) -> Result<()> {
+            let mut own = self.own_transaction.lock().unwrap();
+            let Some(prior) = *own else {
+                return Ok(());
+            };
+            let mut guard = self.client.lock().unwrap();
+            let Some(client) = guard.as_mut().and_then(|mc| mc.as_client_mut()) else {
+                return Err(MssqlError::Connection("Cannot access client".into()));
+            };
+            let (result, pending) = block(transaction::end(client, end, Some(prior)));
+            if result.is_ok() {
+                *own = None;
+                if pending.is_some() {
+                    *self.conn.restore_session.lock().unwrap() = pending;
+                }
+            }
+            result
+        }
+    }
+
+    impl Drop for FilestreamHandle {
+        fn drop(&mut self) {
+            let Some(mc) = self.client.get_mut().unwrap().take() else {
+                return;
+            };
+            // Dropped without `close` (at shutdown): a pooled connection's
+            // release rolls the transaction back
+            if let Some(prior) = self.own_transaction.get_mut().unwrap().take() {
+                warn_log!(
+                    target: "mssqlts::filestream",
+                    "FILESTREAM dropped with its writes uncommitted"
+                );
+                *self.conn.restore_session.lock().unwrap() = Some(prior);
+            }
+            *self.conn.client.lock().unwrap() = Some(mc);
+        }
+    }
+
+    async fn begin(client: &mut Client<Ready>) -> Result<Session> {
+        let prior = transaction::session_state(client).await?;
+        client
+            .simple_query("BEGIN TRANSACTION")
+            .await
+            .map_err(|e| MssqlError::Transaction(e.to_string()))?;
+        Ok(prior)
     }
 
-    pub fn is_available() -> bool { false }
+    /// The emulation is always there.
+    pub fn is_available() -> bool {
+        true
+    }
 }
 
 pub use platform::{is_available, FilestreamHandle, FilestreamMode};
+
+/// Request to open the FILESTREAM value of one row, located by its key,
+/// inside the connection's current transaction.
+#[derive(Deserialize)]
+pub struct FilestreamTarget {
+    pub table: String,
+    pub column: String,
+    /// Column → value pairs that identify exactly one row.
+    pub key: serde_json::Map<String, serde_json::Value>,
+    pub mode: String,
+    /// `overwrite` (default) or `append`.
+    pub write_mode: Option<String>,
+    /// Shown in leak reports; defaults to the connection's label.
+    pub label: Option<String>,
+}
+
+pub fn parse_mode(mode: &str) -> Result<FilestreamMode> {
+    match mode {
+        "read" => Ok(FilestreamMode::Read),
+        "write" => Ok(FilestreamMode::Write),
+        "readwrite" => Ok(FilestreamMode::ReadWrite),
+        other => Err(MssqlError::Config(format!("Invalid mode: {other}"))),
+    }
+}
+
+/// What a handle opened for writing starts with.
+#[derive(Clone, Copy)]
+pub enum WriteMode {
+    /// An empty value; the old data is replaced when the transaction commits.
+    Overwrite,
+    /// The existing data, positioned at its end.
+    Append,
+}
+
+pub fn parse_write_mode(write_mode: Option<&str>) -> Result<WriteMode> {
+    match write_mode {
+        None | Some("overwrite") => Ok(WriteMode::Overwrite),
+        Some("append") => Ok(WriteMode::Append),
+        Some(other) => Err(MssqlError::Config(format!("Invalid write mode: {other}"))),
+    }
+}
+
+/// Fetch the `PathName()` of the target value and the transaction context
+/// that `OpenSqlFilestream` needs. Must run inside a transaction.
+#[cfg_attr(not(windows), allow(dead_code))]
+async fn locate(
+    client: &mut Client<Ready>,
+    target: &FilestreamTarget,
+) -> Result<(String, Vec<u8>)> {
+    let (sql, key_params) = build_locate_sql(target)?;
+    let owned_values = key_params
+        .iter()
+        .map(param_to_boxed)
+        .collect::<Result<Vec<_>>>()?;
+    let param_refs: Vec<&(dyn ToSql + Sync)> = owned_values
+        .iter()
+        .map(|v| &**v as &(dyn ToSql + Sync))
+        .collect();
+
+    let stream = client
+        .query(&sql, &param_refs)
+        .await
+        .map_err(MssqlError::from)?;
+    let rows: Vec<Row> = stream
+        .into_iter()
+        .collect::<std::result::Result<_, _>>()
+        .map_err(MssqlError::from)?;
+
+    let row = match rows.as_slice() {
+        [row] => row,
+        [] => {
+            return Err(MssqlError::Query(format!(
+                "FILESTREAM row not found in {}",
+                target.table
+            )))
+        }
+        _ => {
+            return Err(MssqlError::Query(format!(
+                "FILESTREAM key matches more than one row in {}",
+                target.table
+            )))
+        }
+    };
+    let path = match row.get_raw(0) {
+        Some(SqlValue::String(path)) => path,
+        // PathName() is NULL until the column holds a value, even 0x
+        _ => {
+            return Err(MssqlError::Query(format!(
+                "FILESTREAM value in {}.{} is NULL; set it to 0x before opening it",
+                target.table, target.column
+            )))
+        }
+    };
+    match row.get_raw(1) {
+        Some(SqlValue::Binary(tx_context)) => Ok((path, tx_context.to_vec())),
+        _ => Err(MssqlError::Transaction(
+            "FILESTREAM requires an active transaction".into(),
+        )),
+    }
+}
+
+/// Key values bind to @P1..@Pn.
+#[cfg_attr(not(windows), allow(dead_code))]
+fn build_locate_sql(target: &FilestreamTarget) -> Result<(String, Vec<SerializedParam>)> {
+    let (filter, key_params) = key_filter(&target.key)?;
+    let sql = format!(
+        "SELECT {}.PathName(), GET_FILESTREAM_TRANSACTION_CONTEXT() FROM {} WHERE {filter}",
+        bracket_escape(&target.column),
+        quote_object_name(&target.table)?,
+    );
+    Ok((sql, key_params))
+}
+
+/// Run a future to completion from a blocking-pool thread.
+fn block<F: std::future::Future>(fut: F) -> F::Output {
+    tokio::runtime::Handle::current().block_on(fut)
+}
+
+/// Run `f` with the connection's client checked out, as the FFI calls do.
+#[cfg(windows)]
+fn with_client<T>(conn_id: u64, f: impl FnOnce(&mut Client<Ready>) -> Result<T>) -> Result<T> {
+    let conn = crate::handle::get_conn(conn_id)?;
+    let mut mc = conn
+        .client
+        .lock()
+        .unwrap()
+        .take()
+        .ok_or_else(|| MssqlError::Connection("Connection is in use".into()))?;
+    let result = match mc.as_client_mut() {
+        Some(client) => f(client),
+        None => Err(MssqlError::Connection("Cannot access client".into())),
+    };
+    *conn.client.lock().unwrap() = Some(mc);
+    result
+}
+
+/// Statements for the emulated handle. Key values bind to @P1..@Pn; the
+/// offset, length and data of each call follow them.
+#[cfg_attr(windows, allow(dead_code))]
+struct EmulatedSql {
+    table: String,
+    key_params: Vec<SerializedParam>,
+    length_sql: String,
+    overwrite_sql: String,
+    append_sql: String,
+    read_sql: String,
+    write_sql: String,
+    truncate_sql: String,
+}
+
+#[cfg_attr(windows, allow(dead_code))]
+impl EmulatedSql {
+    fn build(target: &FilestreamTarget) -> Result<Self> {
+        let (filter, key_params) = key_filter(&target.key)?;
+        let n = key_params.len();
+        let table = quote_object_name(&target.table)?;
+        let column = bracket_escape(&target.column);
+        Ok(Self {
+            table: target.table.clone(),
+            key_params,
+            length_sql: format!("SELECT DATALENGTH({column}) FROM {table} WHERE {filter}"),
+            overwrite_sql: format!("UPDATE {table} SET {column} = 0x WHERE {filter}"),
+            // .WRITE cannot extend a NULL value
+            append_sql: format!(
+                "UPDATE {table} SET {column} = ISNULL({column}, 0x) WHERE {filter}"
+            ),
+            read_sql: format!(
+                "SELECT SUBSTRING({column}, @P{}, @P{}) FROM {table} WHERE {filter}",
+                n + 1,
+                n + 2
+            ),
+            write_sql: format!(
+                "UPDATE {table} SET {column}.WRITE(@P{}, @P{}, @P{}) WHERE {filter}",
+                n + 1,
+                n + 2,
+                n + 3
+            ),
+            truncate_sql: format!(
+                "UPDATE {table} SET {column}.WRITE(NULL, @P{}, NULL) WHERE {filter}",
+                n + 1
+            ),
+        })
+    }
+
+    /// Key values followed by `extra`.
+    fn params(&self, extra: Vec<Box<dyn ToSql + Sync>>) -> Result<Vec<Box<dyn ToSql + Sync>>> {
+        let mut owned = self
+            .key_params
+            .iter()
+            .map(param_to_boxed)
+            .collect::<Result<Vec<_>>>()?;
+        owned.extend(extra);
+        Ok(owned)
+    }
+
+    async fn execute(
+        &self,
+        client: &mut Client<Ready>,
+        sql: &str,
+        extra: Vec<Box<dyn ToSql + Sync>>,
+    ) -> Result<u64> {
+        let owned_values = self.params(extra)?;
+        let param_refs: Vec<&(dyn ToSql + Sync)> = owned_values
+            .iter()
+            .map(|v| &**v as &(dyn ToSql + Sync))
+            .collect();
+        client
+            .execute(sql, &param_refs)
+            .await
+            .map_err(MssqlError::from)
+    }
+
+    async fn query(
+        &self,
+        client: &mut Client<Ready>,
+        sql: &str,
+        extra: Vec<Box<dyn ToSql + Sync>>,
+    ) -> Result<Vec<Row>> {
+        let owned_values = self.params(extra)?;
+        let param_refs: Vec<&(dyn ToSql + Sync)> = owned_values
+            .iter()
+            .map(|v| &**v as &(dyn ToSql + Sync))
+            .collect();
+        let stream = client
+            .query(sql, &param_refs)
+            .await
+            .map_err(MssqlError::from)?;
+        stream
+            .into_iter()
+            .collect::<std::result::Result<_, _>>()
+            .map_err(MssqlError::from)
+    }
+
+    /// Reset or prepare the value for writing, then return its length.
+    async fn open(&self, client: &mut Client<Ready>, write: Option<WriteMode>) -> Result<u64> {
+        let prepare = match write {
+            Some(WriteMode::Overwrite) => Some(&self.overwrite_sql),
+            Some(WriteMode::Append) => Some(&self.append_sql),
+            None => None,
+        };
+        if let Some(sql) = prepare {
+            self.execute(client, sql, Vec::new()).await?;
+        }
+        let rows = self.query(client, &self.length_sql, Vec::new()).await?;
+        match rows.as_slice() {
+            [row] => Ok(match row.get_raw(0) {
+                Some(SqlValue::BigInt(n)) => n.max(0) as u64,
+                Some(SqlValue::Int(n)) => n.max(0) as u64,
+                _ => 0,
+            }),
+            [] => Err(MssqlError::Query(format!(
+                "FILESTREAM row not found in {}",
+                self.table
+            ))),
+            _ => Err(MssqlError::Query(format!(
+                "FILESTREAM key matches more than one row in {}",
+                self.table
+            ))),
+        }
+    }
+
+    async fn read(&self, client: &mut Client<Ready>, offset: u64, len: u64) -> Result<Vec<u8>> {
+        if len == 0 {
+            return Ok(Vec::new());
+        }
+        // SUBSTRING is 1-based
+        let extra: Vec<Box<dyn ToSql + Sync>> =
+            vec![Box::new(offset as i64 + 1), Box::new(len as i64)];
+        let rows = self.query(client, &self.read_sql, extra).await?;
+        Ok(match rows.first().and_then(|row| row.get_raw(0)) {
+            Some(SqlValue::Binary(bytes)) => bytes.to_vec(),
+            _ => Vec::new(),
+        })
+    }
+
+    /// Replace `data.len()` bytes at `offset`, extending the value if needed.
+    async fn write(&self, client: &mut Client<Ready>, offset: u64, data: &[u8]) -> Result<()> {
+        let extra: Vec<Box<dyn ToSql + Sync>> = vec![
+            Box::new(data.to_vec()),
+            Box::new(offset as i64),
+            Box::new(data.len() as i64),
+        ];
+        self.execute(client, &self.write_sql, extra).await?;
+        Ok(())
+    }
+
+    async fn truncate(&self, client: &mut Client<Ready>, len: u64) -> Result<()> {
+        let extra: Vec<Box<dyn ToSql + Sync>> = vec![Box::new(len as i64)];
+        self.execute(client, &self.truncate_sql, extra).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_parameterized_locate_sql() {
+        let target = FilestreamTarget {
+            table: "dbo.Documents".into(),
+            column: "Content".into(),
+            key: serde_json::json!({ "Id": 7 }).as_object().unwrap().clone(),
+            mode: "read".into(),
+            write_mode: None,
+            label: None,
+        };
+        let (sql, params) = build_locate_sql(&target).unwrap();
+        assert_eq!(
+            sql,
+            "SELECT [Content].PathName(), GET_FILESTREAM_TRANSACTION_CONTEXT() \
+             FROM [dbo].[Documents] WHERE [Id] = @P1"
+        );
+        assert_eq!(params.len(), 1);
+        assert!(parse_mode("append").is_err());
+        assert!(parse_write_mode(Some("truncate")).is_err());
+    }
+
+    #[test]
+    fn builds_emulated_io_sql() {
+        let target = FilestreamTarget {
+            table: "Files".into(),
+            column: "Data".into(),
+            key: serde_json::json!({ "Id": 7 }).as_object().unwrap().clone(),
+            mode: "write".into(),
+            write_mode: Some("append".into()),
+            label: None,
+        };
+        let sql = EmulatedSql::build(&target).unwrap();
+        assert_eq!(
+            sql.read_sql,
+            "SELECT SUBSTRING([Data], @P2, @P3) FROM [Files] WHERE [Id] = @P1"
+        );
+        assert_eq!(
+            sql.write_sql,
+            "UPDATE [Files] SET [Data].WRITE(@P2, @P3, @P4) WHERE [Id] = @P1"
+        );
+        assert_eq!(
+            sql.truncate_sql,
+            "UPDATE [Files] SET [Data].WRITE(NULL, @P2, NULL) WHERE [Id] = @P1"
+        );
+    }
+}
@@ -1,26 +1,28 @@
-use std::collections::HashMap;
-use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
-use std::sync::{Arc, Mutex};
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
 
 use lazy_static::lazy_static;
 use mssql_client::{Client, Ready};
 use mssql_driver_pool::{Pool, PooledConnection};
 
+use crate::ae::Keyring;
 use crate::config::NormalizedConfig;
 use crate::error::{MssqlError, Result};
+use crate::history::History;
+use crate::ids::{IdGen, Kind};
+use crate::leak::{LeakedHandle, Origin};
+use crate::memory::Usage;
+use crate::progress::Progress;
+use crate::query::{SerializeOptions, SerializedCommand};
+use crate::rowbuf::RowBuffer;
+use crate::transaction::Session;
 
 // ── Handle ID counters ────────────────────────────────────────
 
-static NEXT_POOL_ID: AtomicU64 = AtomicU64::new(1);
-static NEXT_CONN_ID: AtomicU64 = AtomicU64::new(1);
-
-fn next_pool_id() -> u64 {
-    NEXT_POOL_ID.fetch_add(1, Ordering::SeqCst)
-}
-
-fn next_conn_id() -> u64 {
-    NEXT_CONN_ID.fetch_add(1, Ordering::SeqCst)
-}
+static POOL_IDS: IdGen = IdGen::new(Kind::Pool);
+static CONN_IDS: IdGen = IdGen::new(Kind::Connection);
 
 // ── Global handle maps ───────────────────────────────────────
 
@@ -31,15 +33,136 @@ lazy_static! {
     static ref POOL_DEDUP: Mutex<HashMap<String, u64>> = Mutex::new(HashMap::new());
 }
 
+// ── Command metrics ──────────────────────────────────────────
+
+/// Latency samples kept for percentiles.
+const LATENCY_SAMPLES: usize = 1024;
+
+/// Upper bounds (seconds) of the latency histogram buckets.
+pub const LATENCY_BUCKETS_S: [f64; 12] = [
+    0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// Command counters for one connection or pool, a latency histogram, and a
+/// window of recent latencies for percentiles.
+#[derive(Default)]
+pub struct Metrics {
+    queries: AtomicU64,
+    errors: AtomicU64,
+    /// Rows returned by queries and streams.
+    rows: AtomicU64,
+    /// JSON bytes serialized into results.
+    bytes: AtomicU64,
+    latency_sum_us: AtomicU64,
+    /// Per-bucket (not cumulative) counts for `LATENCY_BUCKETS_S`.
+    latency_buckets: [AtomicU64; LATENCY_BUCKETS_S.len()],
+    latencies_us: Mutex<VecDeque<u64>>,
+}
+
+/// Point-in-time copy of a [`Metrics`].
+pub struct MetricsCounters {
+    pub queries: u64,
+    pub errors: u64,
+    pub rows: u64,
+    pub bytes: u64,
+    /// Cumulative counts for `LATENCY_BUCKETS_S`, as Prometheus expects.
+    pub buckets: Vec<u64>,
+    pub latency_sum_s: f64,
+}
+
+impl Metrics {
+    /// Count one command; `outcome` is `(rows, bytes)`, or None if it failed.
+    pub fn record(&self, elapsed: Duration, outcome: Option<(u64, u64)>) {
+        self.queries.fetch_add(1, Ordering::Relaxed);
+        match outcome {
+            Some((rows, bytes)) => {
+                self.rows.fetch_add(rows, Ordering::Relaxed);
+                self.bytes.fetch_add(bytes, Ordering::Relaxed);
+            }
+            None => {
+                self.errors.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        let us = elapsed.as_micros() as u64;
+        self.latency_sum_us.fetch_add(us, Ordering::Relaxed);
+        let secs = elapsed.as_secs_f64();
+        if let Some(i) = LATENCY_BUCKETS_S.iter().position(|&bound| secs <= bound) {
+            self.latency_buckets[i].fetch_add(1, Ordering::Relaxed);
+        }
+        let mut latencies = self.latencies_us.lock().unwrap();
+        if latencies.len() == LATENCY_SAMPLES {
+            latencies.pop_front();
+        }
+        latencies.push_back(us);
+    }
+
+    pub fn counters(&self) -> MetricsCounters {
+        let mut total = 0;
+        let buckets = self
+            .latency_buckets
+            .iter()
+            .map(|b| {
+                total += b.load(Ordering::Relaxed);
+                total
+            })
+            .collect();
+        MetricsCounters {
+            queries: self.queries.load(Ordering::Relaxed),
+            errors: self.errors.load(Ordering::Relaxed),
+            rows: self.rows.load(Ordering::Relaxed),
+            bytes: self.bytes.load(Ordering::Relaxed),
+            buckets,
+            latency_sum_s: self.latency_sum_us.load(Ordering::Relaxed) as f64 / 1e6,
+        }
+    }
+
+    pub fn snapshot(&self) -> serde_json::Value {
+        let mut sorted: Vec<u64> = self.latencies_us.lock().unwrap().iter().copied().collect();
+        sorted.sort_unstable();
+        let ms = |us: u64| us as f64 / 1000.0;
+        serde_json::json!({
+            "queries": self.queries.load(Ordering::Relaxed),
+            "errors": self.errors.load(Ordering::Relaxed),
+            "rows": self.rows.load(Ordering::Relaxed),
+            "bytes": self.bytes.load(Ordering::Relaxed),
+            "latency_ms": {
+                "p50": ms(percentile(&sorted, 50)),
+                "p95": ms(percentile(&sorted, 95)),
+                "p99": ms(percentile(&sorted, 99)),
+                "max": ms(sorted.last().copied().unwrap_or(0)),
+            },
+        })
+    }
+}
+
+/// Nearest-rank percentile of sorted samples; 0 when there are none.
+fn percentile(sorted: &[u64], p: usize) -> u64 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = (p * sorted.len()).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
+
 // ── Pool handle ──────────────────────────────────────────────
 
 /// The pool holds an mssql-driver-pool Pool plus the original config
 /// for creating bare (non-pooled) connections.
 pub struct PoolHandle {
-    pub pool: Pool,
+    /// Swapped out when the pool is rebuilt with a fresh Azure AD token.
+    pub pool: Mutex<Arc<Pool>>,
+    pub config: NormalizedConfig,
+    /// Expiry of the Azure AD token the pool was built with, if any.
+    pub token_expires_on: Mutex<Option<u64>>,
+    /// Held while the pool is rebuilt, so concurrent callers rebuild it once.
+    pub rebuilding: tokio::sync::Mutex<()>,
     pub last_error: Mutex<Option<String>>,
     pub ref_count: AtomicU32,
     pub dedup_key: String,
+    /// Row serialization options inherited by connections acquired from this pool.
+    pub serialize: SerializeOptions,
+    /// Totals across every connection acquired from this pool.
+    pub metrics: Arc<Metrics>,
 }
 
 // ── Connection handle ────────────────────────────────────────
@@ -52,6 +175,87 @@ pub struct ConnHandle {
     pub pool_id: Option<u64>,
     pub last_error: Mutex<Option<String>>,
     pub active_transaction: Mutex<Option<String>>,
+    /// Set while a read-only transaction that rejects writes is open.
+    pub read_only: AtomicBool,
+    /// Session settings to put back when the open transaction ends.
+    pub restore_session: Mutex<Option<Session>>,
+    /// Default for transactions that don't say whether to set XACT_ABORT.
+    pub xact_abort: bool,
+    pub serialize: SerializeOptions,
+    /// Always Encrypted keys installed by `mssql_ae_configure`.
+    pub encryption: Mutex<Option<Arc<Keyring>>>,
+    /// Whether the server has the native `json` type, looked up by the
+    /// first exec that binds a json OUTPUT parameter.
+    pub native_json: OnceLock<bool>,
+    /// Slow query log threshold from the config.
+    pub slow_query_ms: Option<u64>,
+    pub metrics: Metrics,
+    /// The owning pool's metrics, for pooled connections.
+    pub pool_metrics: Option<Arc<Metrics>>,
+    /// The config's label (the pool's, for pooled connections).
+    pub label: Option<String>,
+    /// Open time and caller label, for leak reports. The caller label is
+    /// the connection's tag, set per acquire.
+    pub origin: Origin,
+    /// Recent commands, for diagnostics.
+    pub history: History,
+    /// Events from the script running on this connection.
+    pub progress: Progress,
+    /// Compressed result waiting for `mssql_take_result`.
+    pub pending_result: Mutex<Option<Vec<u8>>>,
+    /// Rows of the last `mssql_query_buffered`, for `mssql_read_rows`.
+    pub row_buffer: Mutex<RowBuffer>,
+    /// Bytes held by `pending_result` and `row_buffer`.
+    pub buffered: Usage,
+}
+
+impl ConnHandle {
+    /// The handle's serialization options for one command, including any
+    /// column encryption keys.
+    pub fn options_for(&self, cmd: &SerializedCommand) -> SerializeOptions {
+        SerializeOptions {
+            encryption: self.encryption.lock().unwrap().clone(),
+            ..self.serialize.for_command(cmd)
+        }
+    }
+
+    /// Reject `sql` if it writes while a read-only transaction is open.
+    pub fn check_writable(&self, sql: &str) -> Result<()> {
+        if !self.read_only.load(Ordering::SeqCst) {
+            return Ok(());
+        }
+        match crate::readonly::write_keyword(sql) {
+            Some(keyword) => Err(MssqlError::Transaction(format!(
+                "{keyword} is not allowed in a read-only transaction"
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// The tag given when the connection was acquired.
+    pub fn tag(&self) -> Option<String> {
+        self.origin.label()
+    }
+
+    /// `label (tag)`, or whichever of them is set.
+    pub fn context(&self) -> Option<String> {
+        context(self.label.as_deref(), self.tag().as_deref())
+    }
+
+    /// Recount `buffered` after `pending_result` or `row_buffer` changed.
+    pub fn update_buffered(&self) {
+        let pending = self.pending_result.lock().unwrap().as_ref().map(Vec::len);
+        let rows = self.row_buffer.lock().unwrap().capacity();
+        self.buffered.set((pending.unwrap_or(0) + rows) as u64);
+    }
+
+    /// Count a command against this connection and its pool.
+    pub fn record_command(&self, elapsed: Duration, outcome: Option<(u64, u64)>) {
+        self.metrics.record(elapsed, outcome);
+        if let Some(pool) = &self.pool_metrics {
+            pool.record(elapsed, outcome);
+        }
+    }
 }
 
 /// Either a pool-managed connection or a standalone one.
@@ -75,7 +279,7 @@ impl MssqlClient {
 /// Store a pool, returning its handle ID. If a pool with the same dedup key
 /// already exists, the existing pool's refcount is incremented and its ID is
 /// returned (the new Pool is dropped).
-pub fn store_pool(pool: Pool, config: NormalizedConfig) -> u64 {
+pub fn store_pool(pool: Pool, token_expires_on: Option<u64>, config: NormalizedConfig) -> u64 {
     let key = config.dedup_key();
     let mut dedup = POOL_DEDUP.lock().unwrap();
     let mut pools = POOLS.lock().unwrap();
@@ -90,12 +294,17 @@ pub fn store_pool(pool: Pool, config: NormalizedConfig) -> u64 {
         dedup.remove(&key);
     }
 
-    let id = next_pool_id();
+    let id = POOL_IDS.next();
     let handle = Arc::new(PoolHandle {
-        pool,
+        pool: Mutex::new(Arc::new(pool)),
+        token_expires_on: Mutex::new(token_expires_on),
+        rebuilding: tokio::sync::Mutex::new(()),
         last_error: Mutex::new(None),
         ref_count: AtomicU32::new(1),
         dedup_key: key.clone(),
+        serialize: config.serialize_options(),
+        metrics: Arc::new(Metrics::default()),
+        config,
     });
     pools.insert(id, handle);
     dedup.insert(key, id);
@@ -108,7 +317,7 @@ pub fn get_pool(id: u64) -> Result<Arc<PoolHandle>> {
         .unwrap()
         .get(&id)
         .cloned()
-        .ok_or_else(|| MssqlError::Pool(format!("Pool {id} not found")))
+        .ok_or_else(|| POOL_IDS.missing(id))
 }
 
 /// Decrement the pool's refcount. Only removes from the map when refcount
@@ -131,21 +340,47 @@ pub fn remove_pool(id: u64) -> Option<Arc<PoolHandle>> {
     None
 }
 
-/// Remove all pools and clear the dedup registry.
-pub fn remove_all_pools() {
-    POOLS.lock().unwrap().clear();
+/// Remove all pools and clear the dedup registry. Returns how many there were.
+pub fn remove_all_pools() -> usize {
+    let removed = std::mem::take(&mut *POOLS.lock().unwrap()).len();
     POOL_DEDUP.lock().unwrap().clear();
+    removed
 }
 
 // ── Connection operations ────────────────────────────────────
 
-pub fn store_conn(client: MssqlClient, pool_id: Option<u64>) -> u64 {
-    let id = next_conn_id();
+pub fn store_conn(
+    client: MssqlClient,
+    pool_id: Option<u64>,
+    serialize: SerializeOptions,
+    slow_query_ms: Option<u64>,
+    xact_abort: bool,
+    label: Option<String>,
+) -> u64 {
+    let id = CONN_IDS.next();
+    let pool_metrics =
+        pool_id.and_then(|pid| POOLS.lock().unwrap().get(&pid).map(|p| p.metrics.clone()));
     let handle = Arc::new(ConnHandle {
         client: Mutex::new(Some(client)),
         pool_id,
         last_error: Mutex::new(None),
         active_transaction: Mutex::new(None),
+        read_only: AtomicBool::new(false),
+        restore_session: Mutex::new(None),
+        xact_abort,
+        serialize,
+        encryption: Mutex::new(None),
+        native_json: OnceLock::new(),
+        slow_query_ms,
+        metrics: Metrics::default(),
+        pool_metrics,
+        label,
+        origin: Origin::new(None, None),
+        history: History::default(),
+        progress: Progress::default(),
+        pending_result: Mutex::new(None),
+        row_buffer: Mutex::default(),
+        buffered: Usage::default(),
     });
     CONNS.lock().unwrap().insert(id, handle);
     id
@@ -157,34 +392,100 @@ pub fn get_conn(id: u64) -> Result<Arc<ConnHandle>> {
         .unwrap()
         .get(&id)
         .cloned()
-        .ok_or_else(|| MssqlError::Connection(format!("Connection {id} not found")))
+        .ok_or_else(|| CONN_IDS.missing(id))
 }
 
 pub fn remove_conn(id: u64) -> Option<Arc<ConnHandle>> {
     CONNS.lock().unwrap().remove(&id)
 }
 
-/// Remove all connections.
-pub fn remove_all_conns() {
-    CONNS.lock().unwrap().clear();
+/// Remove all connections. Returns how many there were.
+pub fn remove_all_conns() -> usize {
+    std::mem::take(&mut *CONNS.lock().unwrap()).len()
 }
 
 // ── Error helpers ────────────────────────────────────────────
 
+/// How a handle's label and tag appear in messages.
+pub fn context(label: Option<&str>, tag: Option<&str>) -> Option<String> {
+    match (label, tag) {
+        (Some(label), Some(tag)) => Some(format!("{label} ({tag})")),
+        (Some(label), None) => Some(label.to_string()),
+        (None, Some(tag)) => Some(format!("({tag})")),
+        (None, None) => None,
+    }
+}
+
+/// `msg` prefixed with the handle's context, if it has one.
+fn with_context(context: Option<String>, msg: String) -> String {
+    match context {
+        Some(context) => format!("[{context}] {msg}"),
+        None => msg,
+    }
+}
+
 impl ConnHandle {
     pub fn set_error(&self, msg: String) {
-        *self.last_error.lock().unwrap() = Some(msg);
+        *self.last_error.lock().unwrap() = Some(with_context(self.context(), msg));
     }
 }
 
 impl PoolHandle {
     pub fn set_error(&self, msg: String) {
-        *self.last_error.lock().unwrap() = Some(msg);
+        let context = context(self.config.label.as_deref(), None);
+        *self.last_error.lock().unwrap() = Some(with_context(context, msg));
     }
 }
 
 // ── Diagnostics ──────────────────────────────────────────────
 
+/// One pool's status and metrics, for exposition.
+pub struct PoolSample {
+    pub id: u64,
+    pub idle: u32,
+    pub in_use: u32,
+    pub max: u32,
+    pub metrics: MetricsCounters,
+}
+
+/// Pools, plus the metrics of standalone (non-pooled) connections by id, and
+/// the number of open connection handles.
+pub fn metrics_samples() -> (Vec<PoolSample>, Vec<(u64, MetricsCounters)>, usize) {
+    let pools = POOLS.lock().unwrap();
+    let conns = CONNS.lock().unwrap();
+    let mut pool_samples: Vec<PoolSample> = pools
+        .iter()
+        .map(|(&id, handle)| {
+            let status = handle.pool.lock().unwrap().status();
+            PoolSample {
+                id,
+                idle: status.available,
+                in_use: status.in_use,
+                max: status.max,
+                metrics: handle.metrics.counters(),
+            }
+        })
+        .collect();
+    pool_samples.sort_by_key(|p| p.id);
+    let mut bare: Vec<(u64, MetricsCounters)> = conns
+        .iter()
+        .filter(|(_, handle)| handle.pool_id.is_none())
+        .map(|(&id, handle)| (id, handle.metrics.counters()))
+        .collect();
+    bare.sort_by_key(|(id, _)| *id);
+    (pool_samples, bare, conns.len())
+}
+
+/// Connections opened (or acquired) at least `min_age` ago.
+pub fn leaked_conns(min_age: Duration) -> Vec<LeakedHandle> {
+    CONNS
+        .lock()
+        .unwrap()
+        .iter()
+        .filter_map(|(&id, h)| h.origin.report("connection", id, h.pool_id, min_age))
+        .collect()
+}
+
 /// Snapshot of all pools and connections for diagnostics.
 pub fn diagnostic_snapshot() -> serde_json::Value {
     let pools = POOLS.lock().unwrap();
@@ -193,14 +494,16 @@ pub fn diagnostic_snapshot() -> serde_json::Value {
     let pool_info: Vec<serde_json::Value> = pools
         .iter()
         .map(|(id, handle)| {
-            let status = handle.pool.status();
+            let status = handle.pool.lock().unwrap().status();
             serde_json::json!({
                 "id": id,
+                "label": handle.config.label,
                 "total": status.total,
                 "idle": status.available,
                 "in_use": status.in_use,
                 "max": status.max,
                 "ref_count": handle.ref_count.load(Ordering::SeqCst),
+                "metrics": handle.metrics.snapshot(),
             })
         })
         .collect();
@@ -217,8 +520,13 @@ pub fn diagnostic_snapshot() -> serde_json::Value {
             serde_json::json!({
                 "id": id,
                 "pool_id": handle.pool_id,
+                "label": handle.label,
+                "tag": handle.tag(),
                 "is_pooled": is_pooled,
                 "has_active_transaction": has_tx,
+                "buffered_bytes": handle.buffered.bytes(),
+                "metrics": handle.metrics.snapshot(),
+                "recent_statements": handle.history.snapshot(),
             })
         })
         .collect();
@@ -228,3 +536,47 @@ pub fn diagnostic_snapshot() -> serde_json::Value {
         "connections": conn_info,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percentile_nearest_rank() {
+        let samples: Vec<u64> = (1..=100).collect();
+        assert_eq!(percentile(&samples, 50), 50);
+        assert_eq!(percentile(&samples, 99), 99);
+        assert_eq!(percentile(&[7], 95), 7);
+        assert_eq!(percentile(&[], 50), 0);
+    }
+
+    #[test]
+    fn context_names_label_and_tag() {
+        assert_eq!(
+            context(Some("billing"), Some("invoice-run")).as_deref(),
+            Some("billing (invoice-run)")
+        );
+        assert_eq!(
+            context(None, Some("invoice-run")).as_deref(),
+            Some("(invoice-run)")
+        );
+        assert_eq!(
+            with_context(context(Some("billing"), None), "Timed out".into()),
+            "[billing] Timed out"
+        );
+        assert_eq!(with_context(None, "Timed out".into()), "Timed out");
+    }
+
+    #[test]
+    fn metrics_count_errors_and_rows() {
+        let metrics = Metrics::default();
+        metrics.record(Duration::from_millis(2), Some((10, 300)));
+        metrics.record(Duration::from_millis(4), None);
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot["queries"], 2);
+        assert_eq!(snapshot["errors"], 1);
+        assert_eq!(snapshot["rows"], 10);
+        assert_eq!(snapshot["bytes"], 300);
+        assert_eq!(snapshot["latency_ms"]["max"], 4.0);
+    }
+}
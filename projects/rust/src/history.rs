@@ -0,0 +1,141 @@
+//! Recent statement history per connection: the last few commands with their
+//! timing and outcome, plus the one in flight, so diagnostics can show what a
+//! connection was doing when it failed or hung.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+
+use crate::error::Result;
+
+/// Finished statements kept per connection.
+pub const CAPACITY: usize = 16;
+/// SQL text kept per entry, in characters (literals masked).
+const SQL_PREFIX_CHARS: usize = 120;
+/// Error text kept per entry, in characters.
+const ERROR_PREFIX_CHARS: usize = 200;
+
+/// One command run on the connection.
+#[derive(Serialize, Clone, Debug)]
+pub struct Statement {
+    /// `query`, `execute`, `exec`, `script`, `stream` or `bulk`.
+    pub kind: &'static str,
+    /// Start of the SQL text.
+    pub sql: String,
+    /// FNV-1a hash of the full SQL text, to match entries whose prefixes
+    /// look alike.
+    pub sql_hash: String,
+    /// Unix time in milliseconds when the command started.
+    pub started_at_ms: u64,
+    /// Time taken, or time so far while running.
+    pub duration_ms: u64,
+    /// `running`, `ok` or `error`.
+    pub status: &'static str,
+    /// Rows returned or affected, when known.
+    pub rows: Option<u64>,
+    pub error: Option<String>,
+}
+
+#[derive(Default)]
+pub struct History {
+    inner: Mutex<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    running: Option<(Instant, Statement)>,
+    finished: VecDeque<Statement>,
+}
+
+impl History {
+    /// Mark `sql` as the statement in flight.
+    pub fn begin(&self, kind: &'static str, sql: &str) {
+        let statement = Statement {
+            kind,
+            sql: crate::redact::statement(sql, SQL_PREFIX_CHARS),
+            sql_hash: format!("{:016x}", fnv1a(sql.as_bytes())),
+            started_at_ms: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or_default(),
+            duration_ms: 0,
+            status: "running",
+            rows: None,
+            error: None,
+        };
+        self.inner.lock().unwrap().running = Some((Instant::now(), statement));
+    }
+
+    /// Move the statement in flight into the history with its outcome.
+    pub fn finish<T>(&self, result: &Result<T>, rows: Option<u64>) {
+        let mut inner = self.inner.lock().unwrap();
+        let Some((started, mut statement)) = inner.running.take() else {
+            return;
+        };
+        statement.duration_ms = started.elapsed().as_millis() as u64;
+        match result {
+            Ok(_) => {
+                statement.status = "ok";
+                statement.rows = rows;
+            }
+            Err(e) => {
+                statement.status = "error";
+                let message = crate::redact::text(e.to_string());
+                statement.error = Some(message.chars().take(ERROR_PREFIX_CHARS).collect());
+            }
+        }
+        if inner.finished.len() == CAPACITY {
+            inner.finished.pop_front();
+        }
+        inner.finished.push_back(statement);
+    }
+
+    /// Finished statements oldest first, then the one in flight, if any.
+    pub fn snapshot(&self) -> Vec<Statement> {
+        let inner = self.inner.lock().unwrap();
+        let mut statements: Vec<Statement> = inner.finished.iter().cloned().collect();
+        if let Some((started, statement)) = &inner.running {
+            statements.push(Statement {
+                duration_ms: started.elapsed().as_millis() as u64,
+                ..statement.clone()
+            });
+        }
+        statements
+    }
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &b| {
+        (hash ^ b as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::MssqlError;
+
+    #[test]
+    fn keeps_last_statements_and_the_running_one() {
+        let history = History::default();
+        for i in 0..CAPACITY + 2 {
+            history.begin("query", &format!("SELECT {i}"));
+            history.finish(&Ok(()), Some(1));
+        }
+        history.begin("execute", "UPDATE t SET x = 'secret'");
+        history.finish::<()>(&Err(MssqlError::Query("deadlock".into())), None);
+        history.begin("stream", "SELECT * FROM big");
+
+        let statements = history.snapshot();
+        assert_eq!(statements.len(), CAPACITY + 1);
+        assert_eq!(statements[0].sql, "SELECT 3");
+        let failed = &statements[CAPACITY - 1];
+        assert_eq!(failed.status, "error");
+        assert_eq!(failed.sql, "UPDATE t SET x = '***'");
+        assert!(failed.error.as_deref().unwrap().contains("deadlock"));
+        assert_eq!(statements[CAPACITY].status, "running");
+        assert_eq!(statements[CAPACITY].kind, "stream");
+    }
+}
@@ -0,0 +1,140 @@
+//! Handle ids. Each id packs a per-load generation, the handle kind and a
+//! sequence number into 53 bits, so it stays exact as a JS number and a
+//! lookup that misses can tell a freed handle from one of another kind or
+//! from an earlier load of the library.
+//!
+//! ```text
+//!  52        41 40  38 37                  0
+//! [ generation ][ kind ][      sequence      ]
+//! ```
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+
+use crate::error::MssqlError;
+
+const SEQ_BITS: u32 = 38;
+const KIND_BITS: u32 = 3;
+const GEN_BITS: u32 = 12;
+const SEQ_MASK: u64 = (1 << SEQ_BITS) - 1;
+const KIND_MASK: u64 = (1 << KIND_BITS) - 1;
+const GEN_MASK: u64 = (1 << GEN_BITS) - 1;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Kind {
+    Pool = 1,
+    Connection = 2,
+    Cursor = 3,
+    Filestream = 4,
+    Blob = 5,
+    Subscription = 6,
+}
+
+impl Kind {
+    fn from_bits(bits: u64) -> Option<Kind> {
+        [
+            Kind::Pool,
+            Kind::Connection,
+            Kind::Cursor,
+            Kind::Filestream,
+            Kind::Blob,
+            Kind::Subscription,
+        ]
+        .into_iter()
+        .find(|k| *k as u64 == bits)
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Kind::Pool => "pool",
+            Kind::Connection => "connection",
+            Kind::Cursor => "cursor",
+            Kind::Filestream => "FILESTREAM",
+            Kind::Blob => "blob",
+            Kind::Subscription => "subscription",
+        }
+    }
+}
+
+/// Random, non-zero, fixed for the life of the loaded library.
+fn generation() -> u64 {
+    static GENERATION: OnceLock<u64> = OnceLock::new();
+    *GENERATION.get_or_init(|| (uuid::Uuid::new_v4().as_u128() as u64 % GEN_MASK) + 1)
+}
+
+/// Issues ids for one kind of handle.
+pub struct IdGen {
+    kind: Kind,
+    next: AtomicU64,
+}
+
+impl IdGen {
+    pub const fn new(kind: Kind) -> Self {
+        IdGen {
+            kind,
+            next: AtomicU64::new(1),
+        }
+    }
+
+    pub fn next(&self) -> u64 {
+        let seq = match self.next.fetch_add(1, Ordering::Relaxed) & SEQ_MASK {
+            // 0 is the FFI failure value; skip it when the sequence wraps
+            0 => self.next.fetch_add(1, Ordering::Relaxed) & SEQ_MASK,
+            seq => seq,
+        };
+        pack(generation(), self.kind, seq)
+    }
+
+    /// The error for `id` not being in this kind's map.
+    pub fn missing(&self, id: u64) -> MssqlError {
+        let kind = self.kind.name();
+        let reason = match unpack(id) {
+            (issued_by, _, _) if issued_by != generation() => {
+                format!("{id} is not a {kind} handle from this driver instance")
+            }
+            (_, Some(found), _) if found != self.kind => {
+                format!("{id} is a {} handle, not a {kind} handle", found.name())
+            }
+            (_, Some(_), seq) if seq < self.next.load(Ordering::Relaxed) => {
+                format!("{kind} {id} has already been closed")
+            }
+            _ => format!("{kind} {id} was never opened"),
+        };
+        MssqlError::StaleHandle(reason)
+    }
+}
+
+fn pack(generation: u64, kind: Kind, seq: u64) -> u64 {
+    (generation << (KIND_BITS + SEQ_BITS)) | ((kind as u64) << SEQ_BITS) | seq
+}
+
+fn unpack(id: u64) -> (u64, Option<Kind>, u64) {
+    (
+        (id >> (KIND_BITS + SEQ_BITS)) & GEN_MASK,
+        Kind::from_bits((id >> SEQ_BITS) & KIND_MASK),
+        id & SEQ_MASK,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ids_fit_a_js_number_and_explain_misses() {
+        let conns = IdGen::new(Kind::Connection);
+        let cursors = IdGen::new(Kind::Cursor);
+        let conn = conns.next();
+        let cursor = cursors.next();
+        assert!(conn > 0 && conn < (1 << 53));
+        assert_eq!(unpack(conn), (generation(), Some(Kind::Connection), 1));
+
+        let msg = |e: MssqlError| e.to_string();
+        assert!(msg(conns.missing(conn)).contains("already been closed"));
+        assert!(msg(conns.missing(cursor)).contains("is a cursor handle"));
+        assert!(msg(conns.missing(conn + 5)).contains("never opened"));
+        let other_load = pack(generation() % GEN_MASK + 1, Kind::Connection, 1);
+        assert!(msg(conns.missing(other_load)).contains("this driver instance"));
+        assert!(msg(conns.missing(42)).contains("this driver instance"));
+    }
+}
@@ -0,0 +1,139 @@
+//! Handle leak detection: every connection, cursor, blob and FILESTREAM
+//! handle remembers when it was opened and an optional caller label, so
+//! `mssql_leak_report` can list the ones that have stayed open too long.
+
+use std::collections::HashMap;
+use std::ops::{Deref, DerefMut};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+
+/// When a handle was opened and what the caller called it.
+pub struct Origin {
+    created: Instant,
+    created_at_ms: u64,
+    label: Mutex<Option<String>>,
+    /// Connection the handle was opened on, for cursors and blobs.
+    conn_id: Option<u64>,
+}
+
+impl Origin {
+    pub fn new(label: Option<String>, conn_id: Option<u64>) -> Self {
+        Origin {
+            created: Instant::now(),
+            created_at_ms: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or_default(),
+            label: Mutex::new(label),
+            conn_id,
+        }
+    }
+
+    pub fn label(&self) -> Option<String> {
+        self.label.lock().unwrap().clone()
+    }
+
+    pub fn set_label(&self, label: Option<String>) {
+        *self.label.lock().unwrap() = label;
+    }
+
+    pub fn conn_id(&self) -> Option<u64> {
+        self.conn_id
+    }
+
+    pub fn age(&self) -> Duration {
+        self.created.elapsed()
+    }
+
+    /// Report entry for this handle, or None if it is younger than `min_age`.
+    pub fn report(
+        &self,
+        kind: &'static str,
+        id: u64,
+        pool_id: Option<u64>,
+        min_age: Duration,
+    ) -> Option<LeakedHandle> {
+        let age = self.age();
+        (age >= min_age).then(|| LeakedHandle {
+            kind,
+            id,
+            age_ms: age.as_millis() as u64,
+            created_at_ms: self.created_at_ms,
+            label: self.label(),
+            conn_id: self.conn_id,
+            pool_id,
+        })
+    }
+}
+
+/// A handle stored in one of the FFI maps, with its origin.
+pub struct Tracked<T> {
+    pub inner: T,
+    pub origin: Origin,
+}
+
+impl<T> Tracked<T> {
+    pub fn new(inner: T, origin: Origin) -> Self {
+        Tracked { inner, origin }
+    }
+}
+
+impl<T> Deref for Tracked<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.inner
+    }
+}
+
+impl<T> DerefMut for Tracked<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+}
+
+/// One handle that is older than the report threshold.
+#[derive(Serialize, Debug)]
+pub struct LeakedHandle {
+    /// `connection`, `cursor`, `blob` or `filestream`.
+    pub kind: &'static str,
+    pub id: u64,
+    pub age_ms: u64,
+    /// Unix time in milliseconds when the handle was opened.
+    pub created_at_ms: u64,
+    pub label: Option<String>,
+    pub conn_id: Option<u64>,
+    pub pool_id: Option<u64>,
+}
+
+/// Entries for the tracked handles in `map` older than `min_age`.
+pub fn collect<T>(
+    kind: &'static str,
+    map: &HashMap<u64, Tracked<T>>,
+    min_age: Duration,
+) -> Vec<LeakedHandle> {
+    map.iter()
+        .filter_map(|(&id, h)| h.origin.report(kind, id, None, min_age))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_only_handles_past_the_threshold() {
+        let mut map = HashMap::new();
+        let origin = Origin::new(Some("nightly job".into()), Some(7));
+        map.insert(1, Tracked::new((), origin));
+        assert_eq!(collect("cursor", &map, Duration::from_secs(60)).len(), 0);
+
+        let leaked = collect("cursor", &map, Duration::ZERO);
+        assert_eq!(leaked.len(), 1);
+        assert_eq!(leaked[0].label.as_deref(), Some("nightly job"));
+        assert_eq!(leaked[0].conn_id, Some(7));
+        assert!(leaked[0].created_at_ms > 0);
+    }
+}
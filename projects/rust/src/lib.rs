@@ -4,28 +4,70 @@
 // read_cstr() is the standard pattern for receiving strings across FFI boundaries.
 #![allow(clippy::not_unsafe_ptr_arg_deref)]
 
+mod ae;
+mod blob;
+mod broker;
+mod browser;
 mod bulk;
+mod catalog;
+mod cdc;
+mod compress;
 mod config;
+mod connstr;
 mod debug;
 mod error;
 mod filestream;
 mod handle;
+mod history;
+mod ids;
+mod leak;
+mod limiter;
+mod logfile;
+mod memory;
+mod notify;
+mod paginate;
 mod pool;
+mod progress;
+mod prometheus;
 mod query;
+mod readonly;
+mod redact;
+mod rowbuf;
+mod runtime_stats;
+mod secret;
+mod shutdown;
+mod slowlog;
+mod spatial;
+mod stats;
 mod stream;
+mod telemetry;
+mod text;
+mod tls;
+mod token;
+mod tracking;
+mod transaction;
+mod variant;
+mod vector;
+mod version;
 
 use std::collections::HashMap;
 use std::ffi::{CStr, CString};
+use std::future::Future;
 use std::os::raw::c_char;
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::atomic::Ordering;
 use std::sync::OnceLock;
+use std::time::{Duration, Instant};
 
 use serde::Deserialize;
 use tokio::runtime::Runtime;
+use tracing::Instrument;
 
 use config::NormalizedConfig;
 use error::MssqlError;
 use handle::MssqlClient;
+use ids::{IdGen, Kind};
+use leak::{Origin, Tracked};
+use mssql_client::{Client, Ready};
 use query::SerializedCommand;
 
 static RUNTIME: OnceLock<Runtime> = OnceLock::new();
@@ -38,23 +80,66 @@ fn rt() -> &'static Runtime {
     })
 }
 
+/// Run one FFI operation on the runtime, counted as in flight for
+/// `mssql_close_all_graceful`. Fails without running once that has started.
+fn block_on<T>(fut: impl Future<Output = error::Result<T>>) -> error::Result<T> {
+    let _op = shutdown::enter()?;
+    let _blocked = runtime_stats::blocked();
+    rt().block_on(fut)
+}
+
+/// `block_on` for queries, execs and bulk inserts, which first wait for a
+/// slot under the global concurrency limit.
+fn block_on_limited<T>(fut: impl Future<Output = error::Result<T>>) -> error::Result<T> {
+    let _op = shutdown::enter()?;
+    let _slot = limiter::acquire();
+    let _blocked = runtime_stats::blocked();
+    rt().block_on(fut)
+}
+
 unsafe fn read_cstr<'a>(ptr: *const c_char) -> &'a str {
     CStr::from_ptr(ptr).to_str().unwrap_or("")
 }
 
+/// Run blocking work (file I/O) on the runtime's blocking pool and wait for
+/// it, as one in-flight operation.
+fn blocking<T: Send + 'static>(
+    work: impl FnOnce() -> error::Result<T> + Send + 'static,
+) -> error::Result<T> {
+    block_on(async {
+        tokio::task::spawn_blocking(work)
+            .await
+            .map_err(|e| MssqlError::Query(format!("Blocking task failed: {e}")))?
+    })
+}
+
+/// `{"__error": ...}` for FFI calls that report failures in their JSON.
+fn error_json(e: MssqlError) -> *mut c_char {
+    to_cstring(&serde_json::json!({ "__error": e.to_string() }).to_string())
+}
+
 fn to_cstring(s: &str) -> *mut c_char {
     CString::new(s).unwrap_or_default().into_raw()
 }
 
-// ── Cursor / FILESTREAM storage ───────────────────────────────
+// ── Cursor / FILESTREAM / blob / notification storage ────────────────────────
 
-static NEXT_CURSOR_ID: AtomicU64 = AtomicU64::new(1);
-static NEXT_FS_ID: AtomicU64 = AtomicU64::new(1);
+type SharedFilestream = std::sync::Arc<std::sync::Mutex<filestream::FilestreamHandle>>;
+
+static CURSOR_IDS: IdGen = IdGen::new(Kind::Cursor);
+static FS_IDS: IdGen = IdGen::new(Kind::Filestream);
+static BLOB_IDS: IdGen = IdGen::new(Kind::Blob);
+static NOTIFY_IDS: IdGen = IdGen::new(Kind::Subscription);
 
 lazy_static::lazy_static! {
-    static ref CURSORS: std::sync::Mutex<HashMap<u64, stream::RowCursor>> =
+    static ref CURSORS: std::sync::Mutex<HashMap<u64, Tracked<stream::RowCursor>>> =
+        std::sync::Mutex::new(HashMap::new());
+    /// Each handle has its own lock so I/O on one does not block the others.
+    static ref FS_HANDLES: std::sync::Mutex<HashMap<u64, Tracked<SharedFilestream>>> =
         std::sync::Mutex::new(HashMap::new());
-    static ref FS_HANDLES: std::sync::Mutex<HashMap<u64, filestream::FilestreamHandle>> =
+    static ref BLOBS: std::sync::Mutex<HashMap<u64, Tracked<blob::BlobHandle>>> =
+        std::sync::Mutex::new(HashMap::new());
+    static ref NOTIFIERS: std::sync::Mutex<HashMap<u64, notify::Subscription>> =
         std::sync::Mutex::new(HashMap::new());
 }
 
@@ -65,23 +150,24 @@ lazy_static::lazy_static! {
 #[no_mangle]
 pub extern "C" fn mssql_pool_create(config_json: *const c_char) -> u64 {
     let json = unsafe { read_cstr(config_json) };
-    let result = rt().block_on(async {
+    let result = block_on(async {
         let config = NormalizedConfig::from_json(json)?;
         debug::debug_log!(
+            target: "mssqlts::pool",
             "Creating pool for {}:{}",
             config.server,
             config.port
         );
-        let pool = pool::create_pool(&config).await?;
-        Ok::<_, MssqlError>(handle::store_pool(pool, config))
+        let (pool, token_expires_on) = pool::create_pool(&config).await?;
+        Ok::<_, MssqlError>(handle::store_pool(pool, token_expires_on, config))
     });
     match result {
         Ok(id) => {
-            debug::debug_log!("Pool created: id={}", id);
+            debug::info_log!(target: "mssqlts::pool", "Pool created: id={}", id);
             id
         }
         Err(e) => {
-            eprintln!("[@tracker1/mssql] Pool creation failed: {e}");
+            debug::error_log!(target: "mssqlts::pool", "Pool creation failed: {e}");
             0
         }
     }
@@ -89,22 +175,33 @@ pub extern "C" fn mssql_pool_create(config_json: *const c_char) -> u64 {
 
 #[no_mangle]
 pub extern "C" fn mssql_pool_acquire(pool_id: u64) -> u64 {
-    let result = rt().block_on(async {
+    let span = telemetry::connect_span("acquire", Some(pool_id));
+    let result = block_on(async {
         let pool_handle = handle::get_pool(pool_id)?;
-        debug::debug_log!("Acquiring connection from pool {}", pool_id);
+        debug::debug_log!(target: "mssqlts::pool", "Acquiring connection from pool {}", pool_id);
 
-        let pooled_conn = pool_handle
-            .pool
-            .get()
-            .await
-            .map_err(MssqlError::from)?;
+        let pooled_conn = pool::acquire(&pool_handle).await?;
 
         let client = MssqlClient::Pooled(Box::new(pooled_conn));
-        Ok::<_, MssqlError>(handle::store_conn(client, Some(pool_id)))
-    });
+        Ok::<_, MssqlError>(handle::store_conn(
+            client,
+            Some(pool_id),
+            pool_handle.serialize.clone(),
+            pool_handle.config.slow_query_ms,
+            pool_handle.config.xact_abort,
+            pool_handle.config.label.clone(),
+        ))
+    }.instrument(span.clone()));
+    telemetry::finish(&span, &result, None);
     match result {
         Ok(id) => {
-            debug::debug_log!("Pool {} acquired connection {}", pool_id, id);
+            span.record("mssql.conn_id", id);
+            debug::debug_log!(
+                target: "mssqlts::pool",
+                "Pool {} acquired connection {}",
+                pool_id,
+                id
+            );
             id
         }
         Err(e) => {
@@ -119,18 +216,59 @@ pub extern "C" fn mssql_pool_acquire(pool_id: u64) -> u64 {
 #[no_mangle]
 pub extern "C" fn mssql_pool_release(pool_id: u64, conn_id: u64) {
     debug::debug_log!(
+        target: "mssqlts::pool",
         "Releasing connection {} back to pool {}",
         conn_id,
         pool_id
     );
     // Remove the connection handle — the PooledConnection's Drop impl
     // automatically returns it to the pool.
-    handle::remove_conn(conn_id);
+    let Some(conn) = handle::remove_conn(conn_id) else {
+        return;
+    };
+    close_subscriptions(conn_id, &conn);
+    // A transaction left open would hand its session settings (and locks)
+    // to the next borrower
+    let Some(prior) = conn.restore_session.lock().unwrap().take() else {
+        return;
+    };
+    let Some(mut mc) = conn.client.lock().unwrap().take() else {
+        return;
+    };
+    let result = block_on(async {
+        match mc.as_client_mut() {
+            Some(client) => {
+                let end = "IF @@TRANCOUNT > 0 ROLLBACK TRANSACTION";
+                match transaction::end(client, end, Some(prior)).await {
+                    (Ok(()), Some(_)) => Err(MssqlError::Transaction(
+                        "session settings not restored".into(),
+                    )),
+                    (result, _) => result,
+                }
+            }
+            None => Ok(()),
+        }
+    });
+    if let Err(e) = result {
+        debug::warn_log!(target: "mssqlts::pool", "Connection {} reset failed: {}", conn_id, e);
+    }
+    *conn.client.lock().unwrap() = Some(mc);
+}
+
+/// The pool's effective configuration as JSON, with passwords, tokens and
+/// client secrets masked and pool sizes filled in from the defaults. Null
+/// for an unknown pool. Caller must free with `mssql_free_string`.
+#[no_mangle]
+pub extern "C" fn mssql_pool_config(pool_id: u64) -> *mut c_char {
+    match handle::get_pool(pool_id) {
+        Ok(pool_handle) => to_cstring(&pool_handle.config.effective().to_string()),
+        Err(_) => std::ptr::null_mut(),
+    }
 }
 
 #[no_mangle]
 pub extern "C" fn mssql_pool_close(pool_id: u64) {
-    debug::debug_log!("Closing pool {}", pool_id);
+    debug::debug_log!(target: "mssqlts::pool", "Closing pool {}", pool_id);
     handle::remove_pool(pool_id);
 }
 
@@ -141,9 +279,11 @@ pub extern "C" fn mssql_pool_close(pool_id: u64) {
 #[no_mangle]
 pub extern "C" fn mssql_connect(config_json: *const c_char) -> u64 {
     let json = unsafe { read_cstr(config_json) };
-    let result = rt().block_on(async {
+    let span = telemetry::connect_span("connect", None);
+    let result = block_on(async {
         let config = NormalizedConfig::from_json(json)?;
         debug::debug_log!(
+            target: "mssqlts::pool",
             "Connecting to {}:{}",
             config.server,
             config.port
@@ -152,15 +292,21 @@ pub extern "C" fn mssql_connect(config_json: *const c_char) -> u64 {
         Ok::<_, MssqlError>(handle::store_conn(
             MssqlClient::Bare(Box::new(client)),
             None,
+            config.serialize_options(),
+            config.slow_query_ms,
+            config.xact_abort,
+            config.label.clone(),
         ))
-    });
+    }.instrument(span.clone()));
+    telemetry::finish(&span, &result, None);
     match result {
         Ok(id) => {
-            debug::debug_log!("Connection established: id={}", id);
+            span.record("mssql.conn_id", id);
+            debug::info_log!(target: "mssqlts::pool", "Connection established: id={}", id);
             id
         }
         Err(e) => {
-            eprintln!("[@tracker1/mssql] Connection failed: {e}");
+            debug::error_log!(target: "mssqlts::pool", "Connection failed: {e}");
             0
         }
     }
@@ -168,31 +314,92 @@ pub extern "C" fn mssql_connect(config_json: *const c_char) -> u64 {
 
 #[no_mangle]
 pub extern "C" fn mssql_disconnect(conn_id: u64) {
-    debug::debug_log!("Disconnecting connection {}", conn_id);
-    handle::remove_conn(conn_id);
+    debug::debug_log!(target: "mssqlts::pool", "Disconnecting connection {}", conn_id);
+    if let Some(conn) = handle::remove_conn(conn_id) {
+        close_subscriptions(conn_id, &conn);
+    }
+}
+
+/// Name the connection in leak reports; cursors and blobs opened on it
+/// afterwards inherit the label. An empty label clears it.
+#[no_mangle]
+pub extern "C" fn mssql_set_label(conn_id: u64, label: *const c_char) {
+    let label = unsafe { read_cstr(label) };
+    if let Ok(conn) = handle::get_conn(conn_id) {
+        let label = (!label.is_empty()).then(|| label.to_string());
+        conn.origin.set_label(label);
+    }
 }
 
 // ══════════════════════════════════════════════════════════════
 // Query FFI
 // ══════════════════════════════════════════════════════════════
 
+/// Run one command on the connection's client. Rejects writes on a read-only
+/// connection, holds the client while `run` runs, and records the command in
+/// its span, history, metrics and slow log. `measure` gives a successful
+/// result's row count and the `(rows, bytes)` counted in the metrics.
+async fn with_conn_client<T>(
+    conn: &handle::ConnHandle,
+    conn_id: u64,
+    kind: &'static str,
+    sql: &str,
+    run: impl AsyncFnOnce(&mut Client<Ready>) -> error::Result<T>,
+    measure: impl FnOnce(&T) -> (Option<u64>, (u64, u64)),
+) -> error::Result<T> {
+    conn.check_writable(sql)?;
+    let mut mc = conn
+        .client
+        .lock()
+        .unwrap()
+        .take()
+        .ok_or_else(|| MssqlError::Connection("Connection is in use".into()))?;
+    let span = telemetry::command_span(kind, conn_id, conn.pool_id, sql);
+    conn.history.begin(kind, sql);
+    let started = Instant::now();
+    let result = match mc.as_client_mut() {
+        Some(client) => run(client).instrument(span.clone()).await,
+        None => Err(MssqlError::Connection("Cannot access client".into())),
+    };
+    *conn.client.lock().unwrap() = Some(mc);
+    let measured = result.as_ref().ok().map(measure);
+    let rows = measured.and_then(|(rows, _)| rows);
+    telemetry::finish(&span, &result, rows);
+    conn.history.finish(&result, rows);
+    conn.record_command(started.elapsed(), measured.map(|(_, counted)| counted));
+    slowlog::record(conn, conn_id, kind, sql, started, || rows);
+    result
+}
+
 #[no_mangle]
 pub extern "C" fn mssql_query(conn_id: u64, cmd_json: *const c_char) -> *mut c_char {
     let json = unsafe { read_cstr(cmd_json) };
-    let result = rt().block_on(async {
+    let result = block_on_limited(async {
         let cmd: SerializedCommand =
             serde_json::from_str(json).map_err(|e| MssqlError::Query(e.to_string()))?;
-        debug::debug_log!("Query on conn {}: {}", conn_id, &cmd.sql[..cmd.sql.len().min(100)]);
+        debug::debug_log!(
+            target: "mssqlts::query",
+            "Query on conn {}: {}",
+            conn_id,
+            redact::statement(&cmd.sql, 100)
+        );
         let conn = handle::get_conn(conn_id)?;
-        let mut mc = conn.client.lock().unwrap()
-            .take()
-            .ok_or_else(|| MssqlError::Connection("Connection is in use".into()))?;
-        let result = match mc.as_client_mut() {
-            Some(client) => query::execute_query(client, &cmd).await,
-            None => Err(MssqlError::Connection("Cannot access client".into())),
-        };
-        *conn.client.lock().unwrap() = Some(mc);
-        result
+        if cmd.compress.is_some() {
+            memory::check("a compressed query")?;
+        }
+        let opts = conn.options_for(&cmd);
+        let result = with_conn_client(
+            &conn,
+            conn_id,
+            "query",
+            &cmd.sql,
+            async |client| query::execute_query(client, &cmd, &opts).await,
+            |(json, rows)| (Some(*rows), (*rows, json.len() as u64)),
+        )
+        .await;
+        let packed = result.and_then(|(json, _)| compress::pack(json, &cmd, &conn.pending_result));
+        conn.update_buffered();
+        packed
     });
     match result {
         Ok(json) => to_cstring(&json),
@@ -208,20 +415,26 @@ pub extern "C" fn mssql_query(conn_id: u64, cmd_json: *const c_char) -> *mut c_c
 #[no_mangle]
 pub extern "C" fn mssql_execute_nonquery(conn_id: u64, cmd_json: *const c_char) -> *mut c_char {
     let json = unsafe { read_cstr(cmd_json) };
-    let result = rt().block_on(async {
+    let result = block_on_limited(async {
         let cmd: SerializedCommand =
             serde_json::from_str(json).map_err(|e| MssqlError::Query(e.to_string()))?;
-        debug::debug_log!("Execute on conn {}: {}", conn_id, &cmd.sql[..cmd.sql.len().min(100)]);
+        debug::debug_log!(
+            target: "mssqlts::query",
+            "Execute on conn {}: {}",
+            conn_id,
+            redact::statement(&cmd.sql, 100)
+        );
         let conn = handle::get_conn(conn_id)?;
-        let mut mc = conn.client.lock().unwrap()
-            .take()
-            .ok_or_else(|| MssqlError::Connection("Connection is in use".into()))?;
-        let result = match mc.as_client_mut() {
-            Some(client) => query::execute_nonquery(client, &cmd).await,
-            None => Err(MssqlError::Connection("Cannot access client".into())),
-        };
-        *conn.client.lock().unwrap() = Some(mc);
-        result
+        let opts = conn.options_for(&cmd);
+        with_conn_client(
+            &conn,
+            conn_id,
+            "execute",
+            &cmd.sql,
+            async |client| query::execute_nonquery(client, &cmd, &opts).await,
+            |json| (slowlog::rows_in(json), (0, json.len() as u64)),
+        )
+        .await
     });
     match result {
         Ok(json) => to_cstring(&json),
@@ -241,20 +454,189 @@ pub extern "C" fn mssql_execute_nonquery(conn_id: u64, cmd_json: *const c_char)
 #[no_mangle]
 pub extern "C" fn mssql_exec(conn_id: u64, cmd_json: *const c_char) -> *mut c_char {
     let json = unsafe { read_cstr(cmd_json) };
-    let result = rt().block_on(async {
+    let result = block_on_limited(async {
+        let cmd: SerializedCommand =
+            serde_json::from_str(json).map_err(|e| MssqlError::Query(e.to_string()))?;
+        debug::debug_log!(
+            target: "mssqlts::query",
+            "Exec on conn {}: {}",
+            conn_id,
+            redact::statement(&cmd.sql, 100)
+        );
+        let conn = handle::get_conn(conn_id)?;
+        let opts = conn.options_for(&cmd);
+        with_conn_client(
+            &conn,
+            conn_id,
+            "exec",
+            &cmd.sql,
+            async |client| query::execute_exec(client, &cmd, &opts, &conn.native_json).await,
+            |json| (slowlog::rows_in(json), (0, json.len() as u64)),
+        )
+        .await
+    });
+    match result {
+        Ok(json) => to_cstring(&json),
+        Err(e) => {
+            if let Ok(conn) = handle::get_conn(conn_id) {
+                conn.set_error(e.to_string());
+            }
+            std::ptr::null_mut()
+        }
+    }
+}
+
+/// Run a query, writing its rows into the connection's row buffer instead of
+/// returning them. Returns `{"rows", "length"}`, the sizes to read them with
+/// `mssql_read_rows`.
+#[no_mangle]
+pub extern "C" fn mssql_query_buffered(conn_id: u64, cmd_json: *const c_char) -> *mut c_char {
+    let json = unsafe { read_cstr(cmd_json) };
+    let result = block_on_limited(async {
         let cmd: SerializedCommand =
             serde_json::from_str(json).map_err(|e| MssqlError::Query(e.to_string()))?;
-        debug::debug_log!("Exec on conn {}: {}", conn_id, &cmd.sql[..cmd.sql.len().min(100)]);
+        debug::debug_log!(
+            target: "mssqlts::query",
+            "Buffered query on conn {}: {}",
+            conn_id,
+            redact::statement(&cmd.sql, 100)
+        );
         let conn = handle::get_conn(conn_id)?;
-        let mut mc = conn.client.lock().unwrap()
-            .take()
-            .ok_or_else(|| MssqlError::Connection("Connection is in use".into()))?;
-        let result = match mc.as_client_mut() {
-            Some(client) => query::execute_exec(client, &cmd).await,
-            None => Err(MssqlError::Connection("Cannot access client".into())),
-        };
-        *conn.client.lock().unwrap() = Some(mc);
-        result
+        memory::check("a buffered query")?;
+        let opts = conn.options_for(&cmd);
+        // Filled outside the lock, which isn't held across the query
+        let mut buf = std::mem::take(&mut *conn.row_buffer.lock().unwrap());
+        let result = with_conn_client(
+            &conn,
+            conn_id,
+            "query",
+            &cmd.sql,
+            async |client| {
+                let rows = query::execute_query_into(client, &cmd, &opts, &mut buf).await?;
+                Ok((rows, buf.header(), buf.bytes() as u64))
+            },
+            |(rows, _, bytes)| (Some(*rows), (*rows, *bytes)),
+        )
+        .await;
+        *conn.row_buffer.lock().unwrap() = buf;
+        conn.update_buffered();
+        result.map(|(_, header, _)| header)
+    });
+    match result {
+        Ok(json) => to_cstring(&json),
+        Err(e) => {
+            if let Ok(conn) = handle::get_conn(conn_id) {
+                conn.set_error(e.to_string());
+            }
+            std::ptr::null_mut()
+        }
+    }
+}
+
+/// Copy the rows of the connection's last `mssql_query_buffered` into
+/// caller-owned buffers: `data` of at least `length` bytes and `offsets` of
+/// at least `rows + 1` u32s. Row `i` is `data[offsets[i]..offsets[i + 1]]`.
+/// The rows stay put until the next buffered query, so a caller can grow its
+/// buffers and read again. Returns the row count, or -1 if a buffer is too
+/// small.
+#[no_mangle]
+pub extern "C" fn mssql_read_rows(
+    conn_id: u64,
+    data: *mut u8,
+    data_len: u64,
+    offsets: *mut u32,
+    offsets_len: u64,
+) -> i64 {
+    if offsets.is_null() || (data.is_null() && data_len > 0) {
+        return -1;
+    }
+    // An empty result has nothing to copy, and may come with no data buffer
+    let data: &mut [u8] = if data.is_null() {
+        &mut []
+    } else {
+        unsafe { std::slice::from_raw_parts_mut(data, data_len as usize) }
+    };
+    let offsets = unsafe { std::slice::from_raw_parts_mut(offsets, offsets_len as usize) };
+    handle::get_conn(conn_id)
+        .ok()
+        .and_then(|conn| conn.row_buffer.lock().unwrap().copy_to(data, offsets))
+        .map_or(-1, |n| n as i64)
+}
+
+/// Copy the compressed result left by the connection's last query into a
+/// caller-owned buffer of at least its `compressedLength`. Returns bytes
+/// copied, or -1 if nothing is waiting or the buffer is too small.
+#[no_mangle]
+pub extern "C" fn mssql_take_result(conn_id: u64, buf: *mut u8, buf_len: u64) -> i64 {
+    if buf.is_null() {
+        return -1;
+    }
+    let out = unsafe { std::slice::from_raw_parts_mut(buf, buf_len as usize) };
+    let Ok(conn) = handle::get_conn(conn_id) else {
+        return -1;
+    };
+    let taken = compress::take(&conn.pending_result, out);
+    conn.update_buffered();
+    taken.map_or(-1, |n| n as i64)
+}
+
+/// Decompress a result taken with `mssql_take_result` into a caller-owned
+/// buffer of exactly its decompressed `length`. Needs no connection, so
+/// workers can decode what was handed to them. Returns the bytes written,
+/// or -1 for an unknown codec or corrupt data.
+#[no_mangle]
+pub extern "C" fn mssql_decompress(
+    codec: *const c_char,
+    data: *const u8,
+    data_len: u64,
+    buf: *mut u8,
+    buf_len: u64,
+) -> i64 {
+    if data.is_null() || (buf.is_null() && buf_len > 0) {
+        return -1;
+    }
+    let codec = unsafe { read_cstr(codec) };
+    let data = unsafe { std::slice::from_raw_parts(data, data_len as usize) };
+    let out: &mut [u8] = if buf_len == 0 {
+        &mut []
+    } else {
+        unsafe { std::slice::from_raw_parts_mut(buf, buf_len as usize) }
+    };
+    let result = compress::Codec::parse(codec).and_then(|c| compress::decompress(c, data, out));
+    match result {
+        Ok(()) => buf_len as i64,
+        Err(e) => {
+            debug::warn_log!(target: "mssqlts::query", "Decompressing a result failed: {e}");
+            -1
+        }
+    }
+}
+
+/// Run a script batch by batch (split on `GO` lines), queueing progress
+/// events for `mssql_progress_poll`. Returns
+/// `{"batches", "rowsAffected", "results": [...]}` with each batch's outcome.
+#[no_mangle]
+pub extern "C" fn mssql_exec_script(conn_id: u64, script_json: *const c_char) -> *mut c_char {
+    let json = unsafe { read_cstr(script_json) };
+    let result = block_on_limited(async {
+        let req: progress::ScriptRequest =
+            serde_json::from_str(json).map_err(|e| MssqlError::Query(e.to_string()))?;
+        debug::debug_log!(
+            target: "mssqlts::query",
+            "Script on conn {}: {}",
+            conn_id,
+            redact::statement(&req.sql, 100)
+        );
+        let conn = handle::get_conn(conn_id)?;
+        with_conn_client(
+            &conn,
+            conn_id,
+            "script",
+            &req.sql,
+            async |client| progress::exec_script(client, &req, &conn.progress).await,
+            |json| (slowlog::rows_in(json), (0, 0)),
+        )
+        .await
     });
     match result {
         Ok(json) => to_cstring(&json),
@@ -267,6 +649,21 @@ pub extern "C" fn mssql_exec(conn_id: u64, cmd_json: *const c_char) -> *mut c_ch
     }
 }
 
+/// Progress events queued since the last poll: `{"events":[...],"dropped":n}`.
+/// Doesn't need the connection's client, so it can be called while a script
+/// runs.
+#[no_mangle]
+pub extern "C" fn mssql_progress_poll(conn_id: u64) -> *mut c_char {
+    let response = match handle::get_conn(conn_id) {
+        Ok(conn) => {
+            let (events, dropped) = conn.progress.drain();
+            serde_json::json!({ "events": events, "dropped": dropped })
+        }
+        Err(e) => serde_json::json!({ "__error": e.to_string() }),
+    };
+    to_cstring(&response.to_string())
+}
+
 // ══════════════════════════════════════════════════════════════
 // Streaming FFI
 // ══════════════════════════════════════════════════════════════
@@ -274,31 +671,45 @@ pub extern "C" fn mssql_exec(conn_id: u64, cmd_json: *const c_char) -> *mut c_ch
 #[no_mangle]
 pub extern "C" fn mssql_query_stream(conn_id: u64, cmd_json: *const c_char) -> u64 {
     let json = unsafe { read_cstr(cmd_json) };
-    let result = rt().block_on(async {
+    let result = block_on_limited(async {
         let cmd: SerializedCommand =
             serde_json::from_str(json).map_err(|e| MssqlError::Query(e.to_string()))?;
         debug::debug_log!(
+            target: "mssqlts::stream",
             "Stream query on conn {}: {}",
             conn_id,
-            &cmd.sql[..cmd.sql.len().min(100)]
+            redact::statement(&cmd.sql, 100)
         );
 
         // Execute query and collect all rows (mssql-client buffers anyway)
         let conn = handle::get_conn(conn_id)?;
-        let mut mc = conn.client.lock().unwrap()
-            .take()
-            .ok_or_else(|| MssqlError::Connection("Connection is in use".into()))?;
-        let result = match mc.as_client_mut() {
-            Some(client) => query::execute_query_stream(client, &cmd).await,
-            None => Err(MssqlError::Connection("Cannot access client".into())),
-        };
-        *conn.client.lock().unwrap() = Some(mc);
-        let rows = result?;
+        memory::check("a stream")?;
+        let rows = with_conn_client(
+            &conn,
+            conn_id,
+            "stream",
+            &cmd.sql,
+            async |client| query::execute_query_stream(client, &cmd).await,
+            |rows| (Some(rows.len() as u64), (rows.len() as u64, 0)),
+        )
+        .await?;
 
-        let cursor = stream::RowCursor::new(rows);
-        let cursor_id = NEXT_CURSOR_ID.fetch_add(1, Ordering::Relaxed);
+        let serialize = conn.options_for(&cmd);
+        if let Some(row) = rows.first() {
+            query::warn_renamed(row.columns(), serialize.column_case);
+        }
+        let cursor = Tracked::new(
+            stream::RowCursor::new(rows, serialize),
+            Origin::new(conn.origin.label(), Some(conn_id)),
+        );
+        let cursor_id = CURSOR_IDS.next();
         CURSORS.lock().unwrap().insert(cursor_id, cursor);
-        debug::debug_log!("Stream cursor {} opened on conn {}", cursor_id, conn_id);
+        debug::debug_log!(
+            target: "mssqlts::stream",
+            "Stream cursor {} opened on conn {}",
+            cursor_id,
+            conn_id
+        );
         Ok::<_, MssqlError>(cursor_id)
     });
     match result {
@@ -317,20 +728,39 @@ pub extern "C" fn mssql_stream_next(cursor_id: u64) -> *mut c_char {
     let mut map = CURSORS.lock().unwrap();
     let cursor = match map.get_mut(&cursor_id) {
         Some(c) => c,
-        None => return std::ptr::null_mut(),
+        None => return error_json(CURSOR_IDS.missing(cursor_id)),
     };
-    match cursor.next_row() {
-        Some(row) => {
-            let json = query::row_to_json(&row);
-            to_cstring(&json.to_string())
-        }
+    match cursor.next_json() {
+        Some(Ok(json)) => to_cstring(&json.to_string()),
+        Some(Err(e)) => to_cstring(&serde_json::json!({ "__error": e.to_string() }).to_string()),
         None => std::ptr::null_mut(),
     }
 }
 
+/// Copy a binary column of the cursor's current row (requested with
+/// `raw_binary`) into a caller-owned buffer. Returns bytes copied, or -1
+/// if the cursor, row, or non-NULL binary column does not exist.
+#[no_mangle]
+pub extern "C" fn mssql_stream_read_binary(
+    cursor_id: u64,
+    column: *const c_char,
+    buf: *mut u8,
+    buf_len: u64,
+) -> i64 {
+    if buf.is_null() {
+        return -1;
+    }
+    let column = unsafe { read_cstr(column) };
+    let out = unsafe { std::slice::from_raw_parts_mut(buf, buf_len as usize) };
+    let map = CURSORS.lock().unwrap();
+    map.get(&cursor_id)
+        .and_then(|c| c.read_binary(column, out))
+        .map_or(-1, |n| n as i64)
+}
+
 #[no_mangle]
 pub extern "C" fn mssql_stream_close(cursor_id: u64) {
-    debug::debug_log!("Closing stream cursor {}", cursor_id);
+    debug::trace_log!(target: "mssqlts::stream", "Closing stream cursor {}", cursor_id);
     CURSORS.lock().unwrap().remove(&cursor_id);
 }
 
@@ -341,19 +771,20 @@ pub extern "C" fn mssql_stream_close(cursor_id: u64) {
 #[no_mangle]
 pub extern "C" fn mssql_bulk_insert(conn_id: u64, req_json: *const c_char) -> *mut c_char {
     let json = unsafe { read_cstr(req_json) };
-    let result = rt().block_on(async {
+    let result = block_on_limited(async {
         let req: bulk::BulkInsertRequest =
             serde_json::from_str(json).map_err(|e| MssqlError::Query(e.to_string()))?;
         let conn = handle::get_conn(conn_id)?;
-        let mut mc = conn.client.lock().unwrap()
-            .take()
-            .ok_or_else(|| MssqlError::Connection("Connection is in use".into()))?;
-        let result = match mc.as_client_mut() {
-            Some(client) => bulk::execute_bulk(client, &req).await,
-            None => Err(MssqlError::Connection("Cannot access client".into())),
-        };
-        *conn.client.lock().unwrap() = Some(mc);
-        let count = result?;
+        let sql = format!("INSERT BULK {}", req.table);
+        let count = with_conn_client(
+            &conn,
+            conn_id,
+            "bulk",
+            &sql,
+            async |client| bulk::execute_bulk(client, &req).await,
+            |count| (Some(*count), (0, 0)),
+        )
+        .await?;
         Ok::<_, MssqlError>(serde_json::json!({ "rowsAffected": count }).to_string())
     });
     match result {
@@ -375,50 +806,78 @@ pub extern "C" fn mssql_bulk_insert(conn_id: u64, req_json: *const c_char) -> *m
 struct BeginTxRequest {
     id: String,
     isolation: String,
+    /// Check snapshot isolation is enabled first, and reject writes until
+    /// the transaction ends unless `allow_writes` is set.
+    #[serde(default)]
+    read_only: bool,
+    #[serde(default)]
+    allow_writes: bool,
+    /// `SET XACT_ABORT` for the transaction; None uses the config default.
+    #[serde(default)]
+    xact_abort: Option<bool>,
 }
 
 #[no_mangle]
 pub extern "C" fn mssql_begin_transaction(conn_id: u64, tx_json: *const c_char) -> *mut c_char {
     let json = unsafe { read_cstr(tx_json) };
-    let result = rt().block_on(async {
+    let result = block_on(async {
         let req: BeginTxRequest =
             serde_json::from_str(json).map_err(|e| MssqlError::Transaction(e.to_string()))?;
 
-        let isolation_sql = match req.isolation.as_str() {
-            "READ_UNCOMMITTED" => "READ UNCOMMITTED",
-            "READ_COMMITTED" => "READ COMMITTED",
-            "REPEATABLE_READ" => "REPEATABLE READ",
-            "SNAPSHOT" => "SNAPSHOT",
-            "SERIALIZABLE" => "SERIALIZABLE",
-            other => {
-                return Err(MssqlError::Transaction(format!(
-                    "Unknown isolation level: {other}"
-                )))
-            }
-        };
+        let isolation_sql = transaction::isolation_sql(&req.isolation)?;
+        if req.read_only && isolation_sql != "SNAPSHOT" {
+            return Err(MssqlError::Transaction(format!(
+                "Read-only transactions use SNAPSHOT isolation, not {isolation_sql}"
+            )));
+        }
 
         debug::debug_log!(
+            target: "mssqlts::query",
             "Begin transaction on conn {}: isolation={}",
             conn_id,
             isolation_sql
         );
 
         let conn = handle::get_conn(conn_id)?;
+        let xact_abort = req.xact_abort.or(conn.xact_abort.then_some(true));
         let mut mc = conn.client.lock().unwrap()
             .take()
             .ok_or_else(|| MssqlError::Connection("Connection is in use".into()))?;
+        let sql = transaction::begin_sql(isolation_sql, xact_abort);
+        let span = telemetry::command_span("begin_transaction", conn_id, conn.pool_id, &sql);
         let result = match mc.as_client_mut() {
-            Some(client) => client
-                .simple_query(&format!(
-                    "SET TRANSACTION ISOLATION LEVEL {isolation_sql}; BEGIN TRANSACTION"
-                ))
+            Some(client) => {
+                async {
+                    if req.read_only {
+                        readonly::check_snapshot(client).await?;
+                    }
+                    let prior = transaction::session_state(client).await?;
+                    client
+                        .simple_query(&sql)
+                        .await
+                        .map(|_| prior)
+                        .map_err(|e| MssqlError::Transaction(e.to_string()))
+                }
+                .instrument(span.clone())
                 .await
-                .map_err(|e| MssqlError::Transaction(e.to_string())),
+            }
             None => Err(MssqlError::Connection("Cannot access client".into())),
         };
         *conn.client.lock().unwrap() = Some(mc);
-        result?;
+        telemetry::finish(&span, &result, None);
+        let prior = result?;
         *conn.active_transaction.lock().unwrap() = Some(req.id);
+        // A nested begin keeps the settings from before the outermost one
+        let now = transaction::Session {
+            isolation: isolation_sql,
+            xact_abort: xact_abort.unwrap_or(prior.xact_abort),
+        };
+        let mut restore = conn.restore_session.lock().unwrap();
+        if restore.is_none() && prior != now {
+            *restore = Some(prior);
+        }
+        conn.read_only
+            .store(req.read_only && !req.allow_writes, Ordering::SeqCst);
         Ok::<_, MssqlError>(())
     });
     match result {
@@ -429,22 +888,33 @@ pub extern "C" fn mssql_begin_transaction(conn_id: u64, tx_json: *const c_char)
 
 #[no_mangle]
 pub extern "C" fn mssql_commit(conn_id: u64, _tx_id: *const c_char) -> *mut c_char {
-    let result = rt().block_on(async {
-        debug::debug_log!("Commit transaction on conn {}", conn_id);
+    let result = block_on(async {
+        debug::debug_log!(target: "mssqlts::query", "Commit transaction on conn {}", conn_id);
         let conn = handle::get_conn(conn_id)?;
         let mut mc = conn.client.lock().unwrap()
             .take()
             .ok_or_else(|| MssqlError::Connection("Connection is in use".into()))?;
-        let result = match mc.as_client_mut() {
-            Some(client) => client
-                .simple_query("COMMIT TRANSACTION")
-                .await
-                .map_err(|e| MssqlError::Transaction(e.to_string())),
-            None => Err(MssqlError::Connection("Cannot access client".into())),
+        let prior = *conn.restore_session.lock().unwrap();
+        let sql = "COMMIT TRANSACTION";
+        let span = telemetry::command_span("commit", conn_id, conn.pool_id, sql);
+        let (result, pending) = match mc.as_client_mut() {
+            Some(client) => {
+                transaction::end(client, sql, prior)
+                    .instrument(span.clone())
+                    .await
+            }
+            None => (
+                Err(MssqlError::Connection("Cannot access client".into())),
+                prior,
+            ),
         };
         *conn.client.lock().unwrap() = Some(mc);
+        // Kept until it runs; pool release retries what's still pending
+        *conn.restore_session.lock().unwrap() = pending;
+        telemetry::finish(&span, &result, None);
         result?;
         *conn.active_transaction.lock().unwrap() = None;
+        conn.read_only.store(false, Ordering::SeqCst);
         Ok::<_, MssqlError>(())
     });
     match result {
@@ -455,22 +925,33 @@ pub extern "C" fn mssql_commit(conn_id: u64, _tx_id: *const c_char) -> *mut c_ch
 
 #[no_mangle]
 pub extern "C" fn mssql_rollback(conn_id: u64, _tx_id: *const c_char) -> *mut c_char {
-    let result = rt().block_on(async {
-        debug::debug_log!("Rollback transaction on conn {}", conn_id);
+    let result = block_on(async {
+        debug::debug_log!(target: "mssqlts::query", "Rollback transaction on conn {}", conn_id);
         let conn = handle::get_conn(conn_id)?;
         let mut mc = conn.client.lock().unwrap()
             .take()
             .ok_or_else(|| MssqlError::Connection("Connection is in use".into()))?;
-        let result = match mc.as_client_mut() {
-            Some(client) => client
-                .simple_query("ROLLBACK TRANSACTION")
-                .await
-                .map_err(|e| MssqlError::Transaction(e.to_string())),
-            None => Err(MssqlError::Connection("Cannot access client".into())),
+        let prior = *conn.restore_session.lock().unwrap();
+        let sql = "ROLLBACK TRANSACTION";
+        let span = telemetry::command_span("rollback", conn_id, conn.pool_id, sql);
+        let (result, pending) = match mc.as_client_mut() {
+            Some(client) => {
+                transaction::end(client, sql, prior)
+                    .instrument(span.clone())
+                    .await
+            }
+            None => (
+                Err(MssqlError::Connection("Cannot access client".into())),
+                prior,
+            ),
         };
         *conn.client.lock().unwrap() = Some(mc);
+        // Kept until it runs; pool release retries what's still pending
+        *conn.restore_session.lock().unwrap() = pending;
+        telemetry::finish(&span, &result, None);
         result?;
         *conn.active_transaction.lock().unwrap() = None;
+        conn.read_only.store(false, Ordering::SeqCst);
         Ok::<_, MssqlError>(())
     });
     match result {
@@ -479,6 +960,43 @@ pub extern "C" fn mssql_rollback(conn_id: u64, _tx_id: *const c_char) -> *mut c_
     }
 }
 
+/// The connection's transaction state:
+/// `{"xactState", "tranCount", "transactionId", "readOnly"}`. `xactState`
+/// is -1 when the transaction is doomed and can only be rolled back.
+#[no_mangle]
+pub extern "C" fn mssql_transaction_state(conn_id: u64) -> *mut c_char {
+    let result = block_on(async {
+        let conn = handle::get_conn(conn_id)?;
+        let mut mc = conn.client.lock().unwrap()
+            .take()
+            .ok_or_else(|| MssqlError::Connection("Connection is in use".into()))?;
+        let result = match mc.as_client_mut() {
+            Some(client) => transaction::server_state(client).await,
+            None => Err(MssqlError::Connection("Cannot access client".into())),
+        };
+        *conn.client.lock().unwrap() = Some(mc);
+        let (xact_state, tran_count) = result?;
+        Ok::<_, MssqlError>(
+            serde_json::json!({
+                "xactState": xact_state,
+                "tranCount": tran_count,
+                "transactionId": conn.active_transaction.lock().unwrap().clone(),
+                "readOnly": conn.read_only.load(Ordering::SeqCst),
+            })
+            .to_string(),
+        )
+    });
+    match result {
+        Ok(json) => to_cstring(&json),
+        Err(e) => {
+            if let Ok(conn) = handle::get_conn(conn_id) {
+                conn.set_error(e.to_string());
+            }
+            std::ptr::null_mut()
+        }
+    }
+}
+
 // ══════════════════════════════════════════════════════════════
 // Cancel FFI
 // ══════════════════════════════════════════════════════════════
@@ -489,6 +1007,35 @@ pub extern "C" fn mssql_cancel(_conn_id: u64) {
     // signal. AbortSignal is checked before/after FFI calls on the JS side.
 }
 
+// ══════════════════════════════════════════════════════════════
+// Always Encrypted FFI
+// ══════════════════════════════════════════════════════════════
+
+/// Install plaintext column encryption keys and encrypted-column metadata on
+/// a connection. Returns 1 on success, 0 on error (see mssql_last_error).
+#[no_mangle]
+pub extern "C" fn mssql_ae_configure(conn_id: u64, config_json: *const c_char) -> u32 {
+    let json = unsafe { read_cstr(config_json) };
+    let result = (|| -> error::Result<()> {
+        let conn = handle::get_conn(conn_id)?;
+        let cfg: ae::KeyringConfig =
+            serde_json::from_str(json).map_err(|e| MssqlError::Config(e.to_string()))?;
+        let keyring = ae::Keyring::from_config(cfg)?;
+        debug::debug_log!("Column encryption configured on conn {}: {:?}", conn_id, keyring);
+        *conn.encryption.lock().unwrap() = Some(std::sync::Arc::new(keyring));
+        Ok(())
+    })();
+    match result {
+        Ok(()) => 1,
+        Err(e) => {
+            if let Ok(conn) = handle::get_conn(conn_id) {
+                conn.set_error(e.to_string());
+            }
+            0
+        }
+    }
+}
+
 // ══════════════════════════════════════════════════════════════
 // FILESTREAM FFI
 // ══════════════════════════════════════════════════════════════
@@ -503,6 +1050,10 @@ struct FilestreamOpenRequest {
     path: String,
     tx_context_base64: String,
     mode: String,
+    /// `overwrite` (default) or `append`.
+    write_mode: Option<String>,
+    /// Shown in leak reports.
+    label: Option<String>,
 }
 
 #[no_mangle]
@@ -517,43 +1068,96 @@ pub extern "C" fn mssql_filestream_open(req_json: *const c_char) -> u64 {
             .decode(&req.tx_context_base64)
             .map_err(|e| MssqlError::Config(format!("Invalid tx_context base64: {e}")))?;
 
-        let mode = match req.mode.as_str() {
-            "read" => filestream::FilestreamMode::Read,
-            "write" => filestream::FilestreamMode::Write,
-            "readwrite" => filestream::FilestreamMode::ReadWrite,
-            other => return Err(MssqlError::Config(format!("Invalid mode: {other}"))),
-        };
+        let mode = filestream::parse_mode(&req.mode)?;
+        let write_mode = filestream::parse_write_mode(req.write_mode.as_deref())?;
+        let path = req.path.clone();
+        let handle = blocking(move || {
+            filestream::FilestreamHandle::open(&path, &tx_context, mode, write_mode)
+        })?;
+        Ok(store_filestream(handle, Origin::new(req.label, None), &req.path))
+    })();
+    match result {
+        Ok(id) => id,
+        Err(e) => {
+            debug::error_log!(target: "mssqlts::filestream", "FILESTREAM open failed: {e}");
+            0
+        }
+    }
+}
 
-        let handle = filestream::FilestreamHandle::open(&req.path, &tx_context, mode)?;
-        let id = NEXT_FS_ID.fetch_add(1, Ordering::Relaxed);
-        FS_HANDLES.lock().unwrap().insert(id, handle);
-        Ok(id)
+/// Open a FILESTREAM value of one row inside the connection's transaction.
+/// On Windows this looks up `PathName()` and the transaction context and
+/// opens the file; elsewhere the handle is emulated with chunked statements
+/// on the connection. Returns 0 on error (see mssql_last_error).
+#[no_mangle]
+pub extern "C" fn mssql_filestream_open_for(conn_id: u64, req_json: *const c_char) -> u64 {
+    let json = unsafe { read_cstr(req_json) };
+    let result = (|| -> error::Result<u64> {
+        let req: filestream::FilestreamTarget =
+            serde_json::from_str(json).map_err(|e| MssqlError::Config(e.to_string()))?;
+        let mode = filestream::parse_mode(&req.mode)?;
+        let write_mode = filestream::parse_write_mode(req.write_mode.as_deref())?;
+        let conn = handle::get_conn(conn_id)?;
+        let label = req.label.clone().or_else(|| conn.origin.label());
+        let target = format!("{}.{}", req.table, req.column);
+        let handle = blocking(move || {
+            filestream::FilestreamHandle::open_for(conn_id, &req, mode, write_mode)
+        })?;
+        Ok(store_filestream(handle, Origin::new(label, Some(conn_id)), &target))
     })();
     match result {
         Ok(id) => id,
         Err(e) => {
-            eprintln!("[@tracker1/mssql] FILESTREAM open failed: {e}");
+            debug::error_log!(target: "mssqlts::filestream", "FILESTREAM open failed: {e}");
+            if let Ok(conn) = handle::get_conn(conn_id) {
+                conn.set_error(e.to_string());
+            }
             0
         }
     }
 }
 
+fn store_filestream(handle: filestream::FilestreamHandle, origin: Origin, opened: &str) -> u64 {
+    let handle = Tracked::new(std::sync::Arc::new(std::sync::Mutex::new(handle)), origin);
+    let id = FS_IDS.next();
+    FS_HANDLES.lock().unwrap().insert(id, handle);
+    debug::debug_log!(
+        target: "mssqlts::filestream",
+        "FILESTREAM {} opened: {}",
+        id,
+        opened
+    );
+    id
+}
+
+/// Run blocking I/O on one FILESTREAM handle on the runtime's blocking pool.
+/// Only that handle is locked, so other handles are not held up.
+fn filestream_io<T: Send + 'static>(
+    fs_id: u64,
+    io: impl FnOnce(&filestream::FilestreamHandle) -> error::Result<T> + Send + 'static,
+) -> error::Result<T> {
+    let handle = FS_HANDLES
+        .lock()
+        .unwrap()
+        .get(&fs_id)
+        .map(|h| h.inner.clone())
+        .ok_or_else(|| FS_IDS.missing(fs_id))?;
+    blocking(move || io(&handle.lock().unwrap()))
+}
+
 #[no_mangle]
 pub extern "C" fn mssql_filestream_read(fs_id: u64, max_bytes: u64) -> *mut c_char {
-    let map = FS_HANDLES.lock().unwrap();
-    let handle = match map.get(&fs_id) {
-        Some(h) => h,
-        None => return std::ptr::null_mut(),
-    };
-    let result = if max_bytes == 0 {
-        handle.read_all()
-    } else {
-        let mut buf = vec![0u8; max_bytes as usize];
-        handle.read(&mut buf).map(|n| {
-            buf.truncate(n);
-            buf
-        })
-    };
+    let result = filestream_io(fs_id, move |handle| {
+        if max_bytes == 0 {
+            handle.read_all()
+        } else {
+            let mut buf = vec![0u8; max_bytes as usize];
+            handle.read(&mut buf).map(|n| {
+                buf.truncate(n);
+                buf
+            })
+        }
+    });
     match result {
         Ok(data) => {
             use base64::Engine;
@@ -561,9 +1165,37 @@ pub extern "C" fn mssql_filestream_read(fs_id: u64, max_bytes: u64) -> *mut c_ch
             let response = serde_json::json!({ "data": b64, "length": data.len() });
             to_cstring(&response.to_string())
         }
+        Err(e) => error_json(e),
+    }
+}
+
+/// Read up to `buf_len` bytes of the FILESTREAM into a caller-owned buffer,
+/// without the base64 envelope of `mssql_filestream_read`. Returns bytes
+/// read (0 at the end of the data), or -1 if the handle does not exist or
+/// the read failed.
+#[no_mangle]
+pub extern "C" fn mssql_filestream_read_into(fs_id: u64, buf: *mut u8, buf_len: u64) -> i64 {
+    if buf.is_null() {
+        return -1;
+    }
+    // ReadFile takes a u32 length
+    let len = buf_len.min(u32::MAX as u64) as usize;
+    let addr = buf as usize;
+    let result = filestream_io(fs_id, move |handle| {
+        // The caller's buffer outlives the read: this call waits for it.
+        let out = unsafe { std::slice::from_raw_parts_mut(addr as *mut u8, len) };
+        handle.read(out)
+    });
+    match result {
+        Ok(n) => n as i64,
         Err(e) => {
-            let response = serde_json::json!({ "__error": e.to_string() });
-            to_cstring(&response.to_string())
+            debug::error_log!(
+                target: "mssqlts::filestream",
+                "FILESTREAM {} read failed: {}",
+                fs_id,
+                e
+            );
+            -1
         }
     }
 }
@@ -571,37 +1203,605 @@ pub extern "C" fn mssql_filestream_read(fs_id: u64, max_bytes: u64) -> *mut c_ch
 #[no_mangle]
 pub extern "C" fn mssql_filestream_write(fs_id: u64, data_base64: *const c_char) -> u64 {
     let b64 = unsafe { read_cstr(data_base64) };
-    let map = FS_HANDLES.lock().unwrap();
-    let handle = match map.get(&fs_id) {
-        Some(h) => h,
-        None => return 0,
-    };
     use base64::Engine;
     let data = match base64::engine::general_purpose::STANDARD.decode(b64) {
         Ok(d) => d,
         Err(_) => return 0,
     };
-    match handle.write_all(&data) {
-        Ok(()) => data.len() as u64,
+    let len = data.len() as u64;
+    match filestream_io(fs_id, move |handle| handle.write_all(&data)) {
+        Ok(()) => len,
         Err(_) => 0,
     }
 }
 
+/// Truncate or extend the FILESTREAM to `len` bytes; writes continue from
+/// there. Returns null on success, or an error message.
+#[no_mangle]
+pub extern "C" fn mssql_filestream_set_length(fs_id: u64, len: u64) -> *mut c_char {
+    match filestream_io(fs_id, move |handle| handle.set_length(len)) {
+        Ok(()) => std::ptr::null_mut(),
+        Err(e) => to_cstring(&e.to_string()),
+    }
+}
+
+/// Commit what the handle wrote, when it runs in a transaction of its own
+/// (emulated handles opened outside one). Returns null on success, or an
+/// error message.
+#[no_mangle]
+pub extern "C" fn mssql_filestream_finish(fs_id: u64) -> *mut c_char {
+    match filestream_io(fs_id, move |handle| handle.finish()) {
+        Ok(()) => std::ptr::null_mut(),
+        Err(e) => to_cstring(&e.to_string()),
+    }
+}
+
+/// Close the handle. Emulated writes not committed by
+/// `mssql_filestream_finish` are rolled back.
 #[no_mangle]
 pub extern "C" fn mssql_filestream_close(fs_id: u64) {
-    FS_HANDLES.lock().unwrap().remove(&fs_id);
+    debug::trace_log!(target: "mssqlts::filestream", "Closing FILESTREAM {}", fs_id);
+    let Some(handle) = FS_HANDLES.lock().unwrap().remove(&fs_id) else {
+        return;
+    };
+    let handle = handle.inner;
+    if let Err(e) = blocking(move || handle.lock().unwrap().close()) {
+        debug::warn_log!(
+            target: "mssqlts::filestream",
+            "FILESTREAM {} close failed: {}",
+            fs_id,
+            e
+        );
+    }
+}
+
+// ══════════════════════════════════════════════════════════════
+// Blob FFI (chunked varbinary(max) reads, all platforms)
+// ══════════════════════════════════════════════════════════════
+
+#[no_mangle]
+pub extern "C" fn mssql_blob_open(conn_id: u64, req_json: *const c_char) -> u64 {
+    let json = unsafe { read_cstr(req_json) };
+    let result = block_on(async {
+        let req: blob::BlobOpenRequest =
+            serde_json::from_str(json).map_err(|e| MssqlError::Query(e.to_string()))?;
+        debug::debug_log!(
+            target: "mssqlts::stream",
+            "Blob open on conn {}: {}.{}",
+            conn_id,
+            req.table,
+            req.column
+        );
+        let conn = handle::get_conn(conn_id)?;
+        let mut mc = conn.client.lock().unwrap()
+            .take()
+            .ok_or_else(|| MssqlError::Connection("Connection is in use".into()))?;
+        let result = match mc.as_client_mut() {
+            Some(client) => blob::BlobHandle::open(client, conn_id, &req).await,
+            None => Err(MssqlError::Connection("Cannot access client".into())),
+        };
+        *conn.client.lock().unwrap() = Some(mc);
+        let blob = Tracked::new(result?, Origin::new(conn.origin.label(), Some(conn_id)));
+
+        let blob_id = BLOB_IDS.next();
+        BLOBS.lock().unwrap().insert(blob_id, blob);
+        Ok::<_, MssqlError>(blob_id)
+    });
+    match result {
+        Ok(id) => id,
+        Err(e) => {
+            if let Ok(conn) = handle::get_conn(conn_id) {
+                conn.set_error(e.to_string());
+            }
+            0
+        }
+    }
+}
+
+/// Total blob size in bytes, or -1 if the handle does not exist.
+#[no_mangle]
+pub extern "C" fn mssql_blob_length(blob_id: u64) -> i64 {
+    BLOBS
+        .lock()
+        .unwrap()
+        .get(&blob_id)
+        .map_or(-1, |b| b.length as i64)
+}
+
+#[no_mangle]
+pub extern "C" fn mssql_blob_read(blob_id: u64, offset: u64, len: u64) -> *mut c_char {
+    let result = block_on(async {
+        let blob = BLOBS
+            .lock()
+            .unwrap()
+            .get(&blob_id)
+            .map(|b| b.inner.clone())
+            .ok_or_else(|| BLOB_IDS.missing(blob_id))?;
+        let conn = handle::get_conn(blob.conn_id)?;
+        let mut mc = conn.client.lock().unwrap()
+            .take()
+            .ok_or_else(|| MssqlError::Connection("Connection is in use".into()))?;
+        let result = match mc.as_client_mut() {
+            Some(client) => blob.read(client, offset, len).await,
+            None => Err(MssqlError::Connection("Cannot access client".into())),
+        };
+        *conn.client.lock().unwrap() = Some(mc);
+        result
+    });
+    let response = match result {
+        Ok(data) => {
+            use base64::Engine;
+            let b64 = base64::engine::general_purpose::STANDARD.encode(&data);
+            serde_json::json!({ "data": b64, "length": data.len() })
+        }
+        Err(e) => serde_json::json!({ "__error": e.to_string() }),
+    };
+    to_cstring(&response.to_string())
+}
+
+#[no_mangle]
+pub extern "C" fn mssql_blob_close(blob_id: u64) {
+    debug::trace_log!(target: "mssqlts::stream", "Closing blob {}", blob_id);
+    BLOBS.lock().unwrap().remove(&blob_id);
+}
+
+// ══════════════════════════════════════════════════════════════
+// Query Notification FFI (Service Broker change subscriptions)
+// ══════════════════════════════════════════════════════════════
+
+#[no_mangle]
+pub extern "C" fn mssql_notify_subscribe(conn_id: u64, req_json: *const c_char) -> u64 {
+    let json = unsafe { read_cstr(req_json) };
+    let result = block_on(async {
+        let req: notify::SubscribeRequest =
+            serde_json::from_str(json).map_err(|e| MssqlError::Query(e.to_string()))?;
+        debug::debug_log!("Notification subscribe on conn {}", conn_id);
+        let conn = handle::get_conn(conn_id)?;
+        let mut mc = conn.client.lock().unwrap()
+            .take()
+            .ok_or_else(|| MssqlError::Connection("Connection is in use".into()))?;
+        let result = match mc.as_client_mut() {
+            Some(client) => notify::Subscription::open(client, conn_id, &req).await,
+            None => Err(MssqlError::Connection("Cannot access client".into())),
+        };
+        *conn.client.lock().unwrap() = Some(mc);
+        let sub = result?;
+
+        let sub_id = NOTIFY_IDS.next();
+        NOTIFIERS.lock().unwrap().insert(sub_id, sub);
+        Ok::<_, MssqlError>(sub_id)
+    });
+    match result {
+        Ok(id) => id,
+        Err(e) => {
+            if let Ok(conn) = handle::get_conn(conn_id) {
+                conn.set_error(e.to_string());
+            }
+            0
+        }
+    }
+}
+
+/// Block up to `timeout_ms` for change events: `{"events":[...]}`, empty on timeout.
+#[no_mangle]
+pub extern "C" fn mssql_notify_receive(sub_id: u64, timeout_ms: u64) -> *mut c_char {
+    let result = block_on(async {
+        let sub = NOTIFIERS
+            .lock()
+            .unwrap()
+            .get(&sub_id)
+            .cloned()
+            .ok_or_else(|| NOTIFY_IDS.missing(sub_id))?;
+        let conn = handle::get_conn(sub.conn_id)?;
+        let mut mc = conn.client.lock().unwrap()
+            .take()
+            .ok_or_else(|| MssqlError::Connection("Connection is in use".into()))?;
+        let result = match mc.as_client_mut() {
+            Some(client) => sub.receive(client, timeout_ms).await,
+            None => Err(MssqlError::Connection("Cannot access client".into())),
+        };
+        *conn.client.lock().unwrap() = Some(mc);
+        result
+    });
+    let response = match result {
+        Ok(events) => serde_json::json!({ "events": events }),
+        Err(e) => serde_json::json!({ "__error": e.to_string() }),
+    };
+    to_cstring(&response.to_string())
+}
+
+/// Drop the subscription's queue and service. Best effort: the handle is
+/// released even if the connection is gone.
+#[no_mangle]
+pub extern "C" fn mssql_notify_close(sub_id: u64) {
+    debug::debug_log!("Closing subscription {}", sub_id);
+    let Some(sub) = NOTIFIERS.lock().unwrap().remove(&sub_id) else {
+        return;
+    };
+    if let Ok(conn) = handle::get_conn(sub.conn_id) {
+        drop_subscriptions(&conn, vec![(sub_id, sub)]);
+    }
+}
+
+/// Release the subscriptions still open on `conn_id` and drop their queues
+/// and services, before the connection closes or goes back to its pool.
+fn close_subscriptions(conn_id: u64, conn: &handle::ConnHandle) {
+    let subs: Vec<_> = {
+        let mut notifiers = NOTIFIERS.lock().unwrap();
+        let ids: Vec<u64> = notifiers
+            .iter()
+            .filter(|(_, sub)| sub.conn_id == conn_id)
+            .map(|(&id, _)| id)
+            .collect();
+        ids.into_iter()
+            .filter_map(|id| notifiers.remove(&id).map(|sub| (id, sub)))
+            .collect()
+    };
+    if !subs.is_empty() {
+        debug::debug_log!("Closing {} subscriptions on conn {}", subs.len(), conn_id);
+        drop_subscriptions(conn, subs);
+    }
+}
+
+/// Drop the queues and services of released subscriptions on `conn`. Best
+/// effort: skipped if the connection is busy, and failures are logged.
+fn drop_subscriptions(conn: &handle::ConnHandle, subs: Vec<(u64, notify::Subscription)>) {
+    let Some(mut mc) = conn.client.lock().unwrap().take() else {
+        return;
+    };
+    // Not gated by `shutdown::enter`: this also runs while closing everything
+    let _blocked = runtime_stats::blocked();
+    rt().block_on(async {
+        let Some(client) = mc.as_client_mut() else {
+            return;
+        };
+        for (sub_id, sub) in subs {
+            if let Err(e) = sub.close(client).await {
+                debug::warn_log!("Subscription {} cleanup failed: {}", sub_id, e);
+            }
+        }
+    });
+    *conn.client.lock().unwrap() = Some(mc);
+}
+
+// ══════════════════════════════════════════════════════════════
+// Service Broker FFI (RECEIVE long-poll, SEND, END CONVERSATION)
+// ══════════════════════════════════════════════════════════════
+
+/// Block up to `timeout_ms` for messages on `queue`: `{"messages":[...]}`,
+/// empty on timeout. Holds the connection while waiting.
+#[no_mangle]
+pub extern "C" fn mssql_broker_receive(
+    conn_id: u64,
+    queue: *const c_char,
+    timeout_ms: u64,
+) -> *mut c_char {
+    let queue = unsafe { read_cstr(queue) };
+    debug::debug_log!("Broker receive on conn {}: {}", conn_id, queue);
+    let result = block_on(async {
+        let conn = handle::get_conn(conn_id)?;
+        let mut mc = conn.client.lock().unwrap()
+            .take()
+            .ok_or_else(|| MssqlError::Connection("Connection is in use".into()))?;
+        let result = match mc.as_client_mut() {
+            Some(client) => broker::receive(client, queue, timeout_ms).await,
+            None => Err(MssqlError::Connection("Cannot access client".into())),
+        };
+        *conn.client.lock().unwrap() = Some(mc);
+        result
+    });
+    let response = match result {
+        Ok(messages) => serde_json::json!({ "messages": messages }),
+        Err(e) => serde_json::json!({ "__error": e.to_string() }),
+    };
+    to_cstring(&response.to_string())
+}
+
+/// Send one message: `{"conversation":"<handle>"}` or `{"__error":...}`.
+#[no_mangle]
+pub extern "C" fn mssql_broker_send(conn_id: u64, req_json: *const c_char) -> *mut c_char {
+    let json = unsafe { read_cstr(req_json) };
+    let result = block_on(async {
+        let req: broker::SendRequest =
+            serde_json::from_str(json).map_err(|e| MssqlError::Query(e.to_string()))?;
+        let conn = handle::get_conn(conn_id)?;
+        let mut mc = conn.client.lock().unwrap()
+            .take()
+            .ok_or_else(|| MssqlError::Connection("Connection is in use".into()))?;
+        let result = match mc.as_client_mut() {
+            Some(client) => broker::send(client, &req).await,
+            None => Err(MssqlError::Connection("Cannot access client".into())),
+        };
+        *conn.client.lock().unwrap() = Some(mc);
+        result
+    });
+    let response = match result {
+        Ok(handle) => serde_json::json!({ "conversation": handle }),
+        Err(e) => serde_json::json!({ "__error": e.to_string() }),
+    };
+    to_cstring(&response.to_string())
+}
+
+/// End a conversation: `{}` or `{"__error":...}`.
+#[no_mangle]
+pub extern "C" fn mssql_broker_end(conn_id: u64, req_json: *const c_char) -> *mut c_char {
+    let json = unsafe { read_cstr(req_json) };
+    let result = block_on(async {
+        let req: broker::EndRequest =
+            serde_json::from_str(json).map_err(|e| MssqlError::Query(e.to_string()))?;
+        let conn = handle::get_conn(conn_id)?;
+        let mut mc = conn.client.lock().unwrap()
+            .take()
+            .ok_or_else(|| MssqlError::Connection("Connection is in use".into()))?;
+        let result = match mc.as_client_mut() {
+            Some(client) => broker::end(client, &req).await,
+            None => Err(MssqlError::Connection("Cannot access client".into())),
+        };
+        *conn.client.lock().unwrap() = Some(mc);
+        result
+    });
+    let response = match result {
+        Ok(()) => serde_json::json!({}),
+        Err(e) => serde_json::json!({ "__error": e.to_string() }),
+    };
+    to_cstring(&response.to_string())
+}
+
+// ══════════════════════════════════════════════════════════════
+// Change Tracking FFI
+// ══════════════════════════════════════════════════════════════
+
+/// Changes to `table` after `since_version`:
+/// `{"version","min_valid_version","resync_required","changes":[...]}`.
+#[no_mangle]
+pub extern "C" fn mssql_ct_changes(
+    conn_id: u64,
+    table: *const c_char,
+    since_version: i64,
+) -> *mut c_char {
+    let table = unsafe { read_cstr(table) };
+    debug::debug_log!(
+        target: "mssqlts::query",
+        "Change tracking poll on conn {}: {} since {}",
+        conn_id,
+        table,
+        since_version
+    );
+    let result = block_on(async {
+        let conn = handle::get_conn(conn_id)?;
+        let opts = conn.serialize.clone();
+        let mut mc = conn.client.lock().unwrap()
+            .take()
+            .ok_or_else(|| MssqlError::Connection("Connection is in use".into()))?;
+        let result = match mc.as_client_mut() {
+            Some(client) => tracking::changes(client, table, since_version, &opts).await,
+            None => Err(MssqlError::Connection("Cannot access client".into())),
+        };
+        *conn.client.lock().unwrap() = Some(mc);
+        result
+    });
+    let response = match result {
+        Ok(changes) => serde_json::to_value(changes).unwrap(),
+        Err(e) => serde_json::json!({ "__error": e.to_string() }),
+    };
+    to_cstring(&response.to_string())
+}
+
+// ══════════════════════════════════════════════════════════════
+// Change Data Capture FFI
+// ══════════════════════════════════════════════════════════════
+
+/// Changes of one capture instance after `since_lsn`:
+/// `{"from_lsn","to_lsn","to_time","min_lsn","resync_required","changes":[...]}`.
+#[no_mangle]
+pub extern "C" fn mssql_cdc_changes(conn_id: u64, req_json: *const c_char) -> *mut c_char {
+    let json = unsafe { read_cstr(req_json) };
+    let result = block_on(async {
+        let req: cdc::CdcRequest =
+            serde_json::from_str(json).map_err(|e| MssqlError::Query(e.to_string()))?;
+        debug::debug_log!(
+            target: "mssqlts::query",
+            "CDC read on conn {}: {}",
+            conn_id,
+            req.capture_instance
+        );
+        let conn = handle::get_conn(conn_id)?;
+        let opts = conn.serialize.clone();
+        let mut mc = conn.client.lock().unwrap()
+            .take()
+            .ok_or_else(|| MssqlError::Connection("Connection is in use".into()))?;
+        let result = match mc.as_client_mut() {
+            Some(client) => cdc::changes(client, &req, &opts).await,
+            None => Err(MssqlError::Connection("Cannot access client".into())),
+        };
+        *conn.client.lock().unwrap() = Some(mc);
+        result
+    });
+    let response = match result {
+        Ok(changes) => serde_json::to_value(changes).unwrap(),
+        Err(e) => serde_json::json!({ "__error": e.to_string() }),
+    };
+    to_cstring(&response.to_string())
+}
+
+// ══════════════════════════════════════════════════════════════
+// Catalog FFI (schema introspection)
+// ══════════════════════════════════════════════════════════════
+
+/// Columns, primary/foreign keys and indexes of `table` as JSON.
+#[no_mangle]
+pub extern "C" fn mssql_table_schema(conn_id: u64, table: *const c_char) -> *mut c_char {
+    let table = unsafe { read_cstr(table) };
+    debug::debug_log!(target: "mssqlts::query", "Table schema on conn {}: {}", conn_id, table);
+    let result = block_on(async {
+        let conn = handle::get_conn(conn_id)?;
+        let mut mc = conn.client.lock().unwrap()
+            .take()
+            .ok_or_else(|| MssqlError::Connection("Connection is in use".into()))?;
+        let result = match mc.as_client_mut() {
+            Some(client) => catalog::table_schema(client, table).await,
+            None => Err(MssqlError::Connection("Cannot access client".into())),
+        };
+        *conn.client.lock().unwrap() = Some(mc);
+        result
+    });
+    let response = match result {
+        Ok(schema) => serde_json::to_value(schema).unwrap(),
+        Err(e) => serde_json::json!({ "__error": e.to_string() }),
+    };
+    to_cstring(&response.to_string())
+}
+
+/// User objects matching `filter_json`: `{"objects":[...]}`.
+#[no_mangle]
+pub extern "C" fn mssql_list_objects(conn_id: u64, filter_json: *const c_char) -> *mut c_char {
+    let json = unsafe { read_cstr(filter_json) };
+    let result = block_on(async {
+        let filter: catalog::ObjectFilter =
+            serde_json::from_str(json).map_err(|e| MssqlError::Query(e.to_string()))?;
+        let conn = handle::get_conn(conn_id)?;
+        let mut mc = conn.client.lock().unwrap()
+            .take()
+            .ok_or_else(|| MssqlError::Connection("Connection is in use".into()))?;
+        let result = match mc.as_client_mut() {
+            Some(client) => catalog::list_objects(client, &filter).await,
+            None => Err(MssqlError::Connection("Cannot access client".into())),
+        };
+        *conn.client.lock().unwrap() = Some(mc);
+        result
+    });
+    let response = match result {
+        Ok(objects) => serde_json::json!({ "objects": objects }),
+        Err(e) => serde_json::json!({ "__error": e.to_string() }),
+    };
+    to_cstring(&response.to_string())
+}
+
+/// Databases on the server: `{"databases":[...]}`.
+#[no_mangle]
+pub extern "C" fn mssql_list_databases(conn_id: u64) -> *mut c_char {
+    let result = block_on(async {
+        let conn = handle::get_conn(conn_id)?;
+        let mut mc = conn.client.lock().unwrap()
+            .take()
+            .ok_or_else(|| MssqlError::Connection("Connection is in use".into()))?;
+        let result = match mc.as_client_mut() {
+            Some(client) => catalog::list_databases(client).await,
+            None => Err(MssqlError::Connection("Cannot access client".into())),
+        };
+        *conn.client.lock().unwrap() = Some(mc);
+        result
+    });
+    let response = match result {
+        Ok(databases) => serde_json::json!({ "databases": databases }),
+        Err(e) => serde_json::json!({ "__error": e.to_string() }),
+    };
+    to_cstring(&response.to_string())
+}
+
+/// Schemas in `database` (empty for the current one): `{"schemas":[...]}`.
+#[no_mangle]
+pub extern "C" fn mssql_list_schemas(conn_id: u64, database: *const c_char) -> *mut c_char {
+    let database = unsafe { read_cstr(database) };
+    let result = block_on(async {
+        let conn = handle::get_conn(conn_id)?;
+        let mut mc = conn.client.lock().unwrap()
+            .take()
+            .ok_or_else(|| MssqlError::Connection("Connection is in use".into()))?;
+        let result = match mc.as_client_mut() {
+            Some(client) => catalog::list_schemas(client, database).await,
+            None => Err(MssqlError::Connection("Cannot access client".into())),
+        };
+        *conn.client.lock().unwrap() = Some(mc);
+        result
+    });
+    let response = match result {
+        Ok(schemas) => serde_json::json!({ "schemas": schemas }),
+        Err(e) => serde_json::json!({ "__error": e.to_string() }),
+    };
+    to_cstring(&response.to_string())
 }
 
 // ══════════════════════════════════════════════════════════════
 // Diagnostics FFI (Phase 13.1 — built in from the start)
 // ══════════════════════════════════════════════════════════════
 
+/// Driver version, dependency versions, target and compiled-in features as
+/// JSON. Caller must free with `mssql_free_string`.
+#[no_mangle]
+pub extern "C" fn mssql_version_info() -> *mut c_char {
+    to_cstring(&version::info().to_string())
+}
+
 #[no_mangle]
 pub extern "C" fn mssql_diagnostic_info() -> *mut c_char {
-    let snapshot = handle::diagnostic_snapshot();
+    let mut snapshot = handle::diagnostic_snapshot();
+    let cursors: Vec<serde_json::Value> = CURSORS
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(id, cursor)| {
+            serde_json::json!({
+                "id": id,
+                "conn_id": cursor.origin.conn_id(),
+                "rows_left": cursor.rows_left(),
+                "buffered_bytes": cursor.usage.bytes(),
+            })
+        })
+        .collect();
+    snapshot["cursors"] = cursors.into();
+    snapshot["memory"] = memory::snapshot();
+    snapshot["concurrency"] = limiter::snapshot();
+    snapshot["runtime"] = RUNTIME.get().map(runtime_stats::snapshot).into();
     to_cstring(&snapshot.to_string())
 }
 
+/// Limit how many queries, execs and bulk inserts run at once across all
+/// connections; 0 removes the limit. Calls over it wait in arrival order.
+#[no_mangle]
+pub extern "C" fn mssql_set_concurrency_limit(limit: u32) {
+    limiter::set_limit(Some(limit as usize).filter(|&l| l > 0));
+}
+
+/// Set the soft cap on buffered results, in bytes; 0 removes it. While the
+/// driver holds more than this across all cursors and connections, opening
+/// streams, buffered queries and compressed queries fail.
+#[no_mangle]
+pub extern "C" fn mssql_set_memory_cap(bytes: u64) {
+    memory::set_cap(Some(bytes).filter(|&b| b > 0));
+}
+
+/// Pool gauges, command counters and latency histograms in Prometheus text
+/// format.
+#[no_mangle]
+pub extern "C" fn mssql_metrics_prometheus() -> *mut c_char {
+    to_cstring(&prometheus::render())
+}
+
+/// Connections, cursors, blobs and FILESTREAM handles that have been open
+/// for at least `max_age_ms`, oldest first, as a JSON array.
+#[no_mangle]
+pub extern "C" fn mssql_leak_report(max_age_ms: u64) -> *mut c_char {
+    let min_age = Duration::from_millis(max_age_ms);
+    let mut leaked = handle::leaked_conns(min_age);
+    leaked.extend(leak::collect("cursor", &CURSORS.lock().unwrap(), min_age));
+    leaked.extend(leak::collect("blob", &BLOBS.lock().unwrap(), min_age));
+    leaked.extend(leak::collect(
+        "filestream",
+        &FS_HANDLES.lock().unwrap(),
+        min_age,
+    ));
+    leaked.sort_by(|a, b| b.age_ms.cmp(&a.age_ms));
+    to_cstring(&serde_json::to_string(&leaked).unwrap())
+}
+
+/// Commands recorded by the slow query log, oldest first.
+#[no_mangle]
+pub extern "C" fn mssql_slow_queries() -> *mut c_char {
+    let entries = slowlog::snapshot();
+    to_cstring(&serde_json::to_string(&entries).unwrap())
+}
+
 // ══════════════════════════════════════════════════════════════
 // Debug FFI (Phase 13.2 — built in from the start)
 // ══════════════════════════════════════════════════════════════
@@ -611,6 +1811,52 @@ pub extern "C" fn mssql_set_debug(enabled: u32) {
     debug::set_debug(enabled != 0);
 }
 
+/// Set the log level filter and log file from JSON (`{"level":"info",
+/// "categories":{"pool":"trace"},"file":"/var/log/mssqlts.log"}`). Returns
+/// null on success, an error message otherwise.
+#[no_mangle]
+pub extern "C" fn mssql_set_debug_config(config_json: *const c_char) -> *mut c_char {
+    let json = unsafe { read_cstr(config_json) };
+    let result = serde_json::from_str::<debug::LogConfig>(json)
+        .map_err(|e| e.to_string())
+        .and_then(debug::LogConfig::apply);
+    match result {
+        Ok(()) => std::ptr::null_mut(),
+        Err(e) => to_cstring(&MssqlError::Config(e).to_string()),
+    }
+}
+
+/// Deliver log records to `callback` (level, target, message, Unix ms)
+/// instead of stderr. Pass null to go back to stderr.
+#[no_mangle]
+pub extern "C" fn mssql_set_log_callback(callback: Option<debug::LogCallback>) {
+    debug::set_log_callback(callback);
+}
+
+// ══════════════════════════════════════════════════════════════
+// Tracing FFI
+// ══════════════════════════════════════════════════════════════
+
+/// Install an OTLP/HTTP span exporter from a JSON config, or remove it when
+/// the config is empty or `null`. Returns null on success, an error message
+/// otherwise.
+#[no_mangle]
+pub extern "C" fn mssql_configure_tracing(config_json: *const c_char) -> *mut c_char {
+    let json = unsafe { read_cstr(config_json) };
+    let result = (|| -> error::Result<()> {
+        let config: Option<telemetry::TracingConfig> = if json.trim().is_empty() {
+            None
+        } else {
+            serde_json::from_str(json).map_err(|e| MssqlError::Config(e.to_string()))?
+        };
+        telemetry::configure(config)
+    })();
+    match result {
+        Ok(()) => std::ptr::null_mut(),
+        Err(e) => to_cstring(&e.to_string()),
+    }
+}
+
 // ══════════════════════════════════════════════════════════════
 // Close All FFI
 // ══════════════════════════════════════════════════════════════
@@ -618,10 +1864,51 @@ pub extern "C" fn mssql_set_debug(enabled: u32) {
 #[no_mangle]
 pub extern "C" fn mssql_close_all() {
     debug::debug_log!("Closing all handles");
-    CURSORS.lock().unwrap().clear();
-    FS_HANDLES.lock().unwrap().clear();
-    handle::remove_all_conns();
-    handle::remove_all_pools();
+    close_handles(0);
+}
+
+/// Stop accepting new operations, wait up to `timeout_ms` for the running
+/// ones, then close every handle. Returns a JSON summary of the operations
+/// still running and the handles that were open.
+#[no_mangle]
+pub extern "C" fn mssql_close_all_graceful(timeout_ms: u64) -> *mut c_char {
+    debug::debug_log!(
+        "Closing all handles after in-flight operations (up to {} ms)",
+        timeout_ms
+    );
+    let in_flight = shutdown::drain(Duration::from_millis(timeout_ms));
+    if in_flight > 0 {
+        debug::warn_log!(
+            "{} operations still running after {} ms, closing anyway",
+            in_flight,
+            timeout_ms
+        );
+    }
+    let summary = close_handles(in_flight);
+    shutdown::reopen();
+    to_cstring(&serde_json::to_string(&summary).unwrap())
+}
+
+fn close_handles(in_flight: usize) -> shutdown::Summary {
+    // Drop the broker objects while the connections are still open
+    let subscriptions = std::mem::take(&mut *NOTIFIERS.lock().unwrap());
+    let subscription_count = subscriptions.len();
+    for (sub_id, sub) in subscriptions {
+        if let Ok(conn) = handle::get_conn(sub.conn_id) {
+            drop_subscriptions(&conn, vec![(sub_id, sub)]);
+        }
+    }
+    let summary = shutdown::Summary {
+        in_flight,
+        cursors: std::mem::take(&mut *CURSORS.lock().unwrap()).len(),
+        filestreams: std::mem::take(&mut *FS_HANDLES.lock().unwrap()).len(),
+        blobs: std::mem::take(&mut *BLOBS.lock().unwrap()).len(),
+        subscriptions: subscription_count,
+        connections: handle::remove_all_conns(),
+        pools: handle::remove_all_pools(),
+    };
+    debug::flush();
+    summary
 }
 
 // ══════════════════════════════════════════════════════════════
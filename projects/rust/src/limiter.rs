@@ -0,0 +1,124 @@
+//! Global concurrency limit: with a limit set, at most that many queries,
+//! execs and bulk inserts run at once across every connection, and the rest
+//! wait their turn in arrival order. Waits are counted for diagnostics.
+
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+struct State {
+    /// 0 is no limit.
+    limit: usize,
+    running: usize,
+    /// Tickets handed to waiters, and the next one allowed to run.
+    next_ticket: u64,
+    serving: u64,
+    waited: u64,
+    wait_total: Duration,
+    wait_max: Duration,
+}
+
+static STATE: Mutex<State> = Mutex::new(State {
+    limit: 0,
+    running: 0,
+    next_ticket: 0,
+    serving: 0,
+    waited: 0,
+    wait_total: Duration::ZERO,
+    wait_max: Duration::ZERO,
+});
+static FREED: Condvar = Condvar::new();
+
+/// A running operation's place under the limit; dropping it frees the place.
+pub struct Slot(());
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        STATE.lock().unwrap().running -= 1;
+        FREED.notify_all();
+    }
+}
+
+/// Set the limit; None removes it and lets every waiter run.
+pub fn set_limit(limit: Option<usize>) {
+    STATE.lock().unwrap().limit = limit.unwrap_or(0);
+    FREED.notify_all();
+}
+
+/// Take a slot, waiting behind earlier callers while the limit is reached.
+pub fn acquire() -> Slot {
+    let mut state = STATE.lock().unwrap();
+    let ticket = state.next_ticket;
+    state.next_ticket += 1;
+    let full = |s: &State| s.limit > 0 && s.running >= s.limit;
+    if ticket != state.serving || full(&state) {
+        let started = Instant::now();
+        state = FREED
+            .wait_while(state, |s| ticket != s.serving || full(s))
+            .unwrap();
+        let waited = started.elapsed();
+        state.waited += 1;
+        state.wait_total += waited;
+        state.wait_max = state.wait_max.max(waited);
+    }
+    state.serving += 1;
+    state.running += 1;
+    drop(state);
+    // The next ticket may be able to run too
+    FREED.notify_all();
+    Slot(())
+}
+
+/// `{"limit", "running", "queued", "waited", "wait_ms_total", "wait_ms_max"}`
+/// for diagnostics. `waited` counts operations that had to queue.
+pub fn snapshot() -> serde_json::Value {
+    let state = STATE.lock().unwrap();
+    serde_json::json!({
+        "limit": Some(state.limit).filter(|&l| l > 0),
+        "running": state.running,
+        "queued": state.next_ticket - state.serving,
+        "waited": state.waited,
+        "wait_ms_total": state.wait_total.as_millis() as u64,
+        "wait_ms_max": state.wait_max.as_millis() as u64,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn waiters_run_in_order_once_a_slot_frees() {
+        set_limit(Some(1));
+        let first = acquire();
+        let (tx, rx) = std::sync::mpsc::channel();
+        let workers: Vec<_> = (0..3u64)
+            .map(|i| {
+                let tx = tx.clone();
+                let worker = std::thread::spawn(move || {
+                    let _slot = acquire();
+                    tx.send(i).unwrap();
+                });
+                // Let each take its ticket before the next starts
+                while snapshot()["queued"].as_u64() < Some(i + 1) {
+                    std::thread::yield_now();
+                }
+                worker
+            })
+            .collect();
+        assert_eq!(snapshot()["running"], 1);
+        drop(first);
+        for worker in workers {
+            worker.join().unwrap();
+        }
+        assert_eq!(rx.try_iter().collect::<Vec<_>>(), [0, 1, 2]);
+
+        let stats = snapshot();
+        assert_eq!(
+            (stats["running"].as_u64(), stats["queued"].as_u64()),
+            (Some(0), Some(0))
+        );
+        assert!(stats["waited"].as_u64().unwrap() >= 3);
+        set_limit(None);
+        assert_eq!(snapshot()["limit"], serde_json::Value::Null);
+    }
+}
@@ -0,0 +1,121 @@
+//! Size-rotated log file: `path` is the active file and `path.1` ..
+//! `path.N` hold older output, newest first.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+pub const DEFAULT_MAX_BYTES: u64 = 10 * 1024 * 1024;
+pub const DEFAULT_MAX_FILES: u32 = 5;
+
+pub struct LogFile {
+    path: PathBuf,
+    max_bytes: u64,
+    max_files: u32,
+    /// `None` only while rotating, so the handle is closed before renames
+    /// (required on Windows).
+    writer: Option<BufWriter<File>>,
+    size: u64,
+}
+
+impl LogFile {
+    /// Open `path` for appending. `max_files` rotated files are kept; with 0
+    /// the file is truncated instead.
+    pub fn open(path: impl Into<PathBuf>, max_bytes: u64, max_files: u32) -> io::Result<Self> {
+        let path = path.into();
+        let file = append(&path)?;
+        let size = file.metadata()?.len();
+        Ok(LogFile {
+            path,
+            max_bytes: max_bytes.max(1),
+            max_files,
+            writer: Some(BufWriter::new(file)),
+            size,
+        })
+    }
+
+    /// Append one line, rotating first if it would push the file past the
+    /// size limit. `flush` forces buffered lines to disk.
+    pub fn write_line(&mut self, line: &str, flush: bool) -> io::Result<()> {
+        let len = line.len() as u64 + 1;
+        if self.size > 0 && self.size + len > self.max_bytes {
+            self.rotate()?;
+        }
+        let writer = self.writer()?;
+        writeln!(writer, "{line}")?;
+        if flush {
+            writer.flush()?;
+        }
+        self.size += len;
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer()?.flush()
+    }
+
+    fn writer(&mut self) -> io::Result<&mut BufWriter<File>> {
+        if self.writer.is_none() {
+            self.writer = Some(BufWriter::new(append(&self.path)?));
+        }
+        Ok(self.writer.as_mut().unwrap())
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        if let Some(mut writer) = self.writer.take() {
+            writer.flush()?;
+        }
+        if self.max_files == 0 {
+            File::create(&self.path)?;
+        } else {
+            for n in (1..self.max_files).rev() {
+                rename_if_exists(&self.rotated(n), &self.rotated(n + 1))?;
+            }
+            rename_if_exists(&self.path, &self.rotated(1))?;
+        }
+        self.size = 0;
+        Ok(())
+    }
+
+    fn rotated(&self, n: u32) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(format!(".{n}"));
+        name.into()
+    }
+}
+
+fn append(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+fn rename_if_exists(from: &Path, to: &Path) -> io::Result<()> {
+    match fs::rename(from, to) {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotates_by_size_and_keeps_max_files() {
+        let dir = std::env::temp_dir().join(format!("mssqlts-logfile-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("driver.log");
+
+        let mut log = LogFile::open(&path, 20, 2).unwrap();
+        for line in ["first line", "second line", "third line", "fourth line"] {
+            log.write_line(line, false).unwrap();
+        }
+        log.flush().unwrap();
+
+        let read = |p: &Path| fs::read_to_string(p).unwrap();
+        assert_eq!(read(&path), "fourth line\n");
+        assert_eq!(read(&dir.join("driver.log.1")), "third line\n");
+        assert_eq!(read(&dir.join("driver.log.2")), "second line\n");
+        assert!(!dir.join("driver.log.3").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,130 @@
+//! Memory accounting: approximate bytes the driver holds for the caller,
+//! such as rows buffered by stream cursors and results waiting on
+//! connections. Each holder keeps a `Usage` that counts towards a global
+//! total, reported in diagnostics. With a soft cap set, new buffering work
+//! fails once the total is over it; work already under way isn't cut short.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use mssql_client::{Row, SqlValue};
+
+use crate::error::{MssqlError, Result};
+
+/// Bytes held across every `Usage`.
+static TOTAL: AtomicU64 = AtomicU64::new(0);
+/// Soft cap in bytes; 0 is none.
+static CAP: AtomicU64 = AtomicU64::new(0);
+
+/// Rough per-row and per-value overhead on top of the data itself.
+const ROW_OVERHEAD: u64 = 64;
+const VALUE_OVERHEAD: u64 = 32;
+
+/// Bytes held by one cursor or connection. Dropping it releases them.
+#[derive(Default)]
+pub struct Usage {
+    bytes: AtomicU64,
+}
+
+impl Usage {
+    pub fn bytes(&self) -> u64 {
+        self.bytes.load(Ordering::Relaxed)
+    }
+
+    pub fn add(&self, n: u64) {
+        self.bytes.fetch_add(n, Ordering::Relaxed);
+        TOTAL.fetch_add(n, Ordering::Relaxed);
+    }
+
+    pub fn sub(&self, n: u64) {
+        self.bytes.fetch_sub(n, Ordering::Relaxed);
+        TOTAL.fetch_sub(n, Ordering::Relaxed);
+    }
+
+    pub fn set(&self, n: u64) {
+        let old = self.bytes.swap(n, Ordering::Relaxed);
+        if n >= old {
+            TOTAL.fetch_add(n - old, Ordering::Relaxed);
+        } else {
+            TOTAL.fetch_sub(old - n, Ordering::Relaxed);
+        }
+    }
+}
+
+impl Drop for Usage {
+    fn drop(&mut self) {
+        TOTAL.fetch_sub(self.bytes(), Ordering::Relaxed);
+    }
+}
+
+pub fn total() -> u64 {
+    TOTAL.load(Ordering::Relaxed)
+}
+
+/// Set the soft cap; None removes it.
+pub fn set_cap(bytes: Option<u64>) {
+    CAP.store(bytes.unwrap_or(0), Ordering::Relaxed);
+}
+
+pub fn cap() -> Option<u64> {
+    Some(CAP.load(Ordering::Relaxed)).filter(|&cap| cap > 0)
+}
+
+/// Fail if the total is over the soft cap, naming the work refused.
+pub fn check(work: &str) -> Result<()> {
+    match cap() {
+        Some(cap) if total() > cap => Err(MssqlError::Query(format!(
+            "Cannot start {work}: the driver is holding {} bytes of buffered results, over its \
+             {cap} byte limit. Close open streams or read pending results first.",
+            total()
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// `{"buffered_bytes", "soft_cap_bytes"}` for diagnostics.
+pub fn snapshot() -> serde_json::Value {
+    serde_json::json!({ "buffered_bytes": total(), "soft_cap_bytes": cap() })
+}
+
+/// Approximate bytes held by a row: its text and binary values, plus a
+/// fixed overhead per row and per value.
+pub fn row_size(row: &Row) -> u64 {
+    let values: u64 = row
+        .columns()
+        .iter()
+        .map(|col| match row.get_raw(col.index) {
+            Some(SqlValue::String(s)) | Some(SqlValue::Xml(s)) => s.len() as u64,
+            Some(SqlValue::Binary(b)) => b.len() as u64,
+            _ => 0,
+        })
+        .sum();
+    ROW_OVERHEAD + values + VALUE_OVERHEAD * row.columns().len() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn usage_counts_towards_the_total_until_dropped() {
+        let before = total();
+        let a = Usage::default();
+        a.add(1000);
+        a.sub(400);
+        let b = Usage::default();
+        b.set(50);
+        b.set(20);
+        assert_eq!((a.bytes(), b.bytes()), (600, 20));
+        assert_eq!(total() - before, 620);
+
+        set_cap(Some(1));
+        let err = check("a stream").unwrap_err().to_string();
+        assert!(err.contains("Cannot start a stream"), "{err}");
+        set_cap(None);
+        assert!(check("a stream").is_ok());
+
+        drop(a);
+        drop(b);
+        assert_eq!(total(), before);
+    }
+}
@@ -0,0 +1,211 @@
+//! Query notifications (SqlDependency-style): run a query with a
+//! notification request attached, then wait on a private Service Broker
+//! queue for the message SQL Server posts when the query's results change.
+
+use mssql_client::{Client, QueryNotification, Ready, Row, SqlValue, ToSql};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{MssqlError, Result};
+use crate::query::{bind_params, rewrite_named_params, SerializedParam};
+
+/// Contract SQL Server posts query notification messages on.
+const NOTIFICATION_CONTRACT: &str =
+    "http://schemas.microsoft.com/SQL/Notifications/PostQueryNotification";
+const NOTIFICATION_MESSAGE: &str =
+    "http://schemas.microsoft.com/SQL/Notifications/QueryNotification";
+
+/// Request to subscribe to changes in a query's results.
+#[derive(Deserialize)]
+pub struct SubscribeRequest {
+    pub sql: String,
+    #[serde(default)]
+    pub params: Vec<SerializedParam>,
+    /// Seconds until the subscription expires (server default: 5 days).
+    #[serde(default)]
+    pub timeout_s: Option<u32>,
+}
+
+/// One notification: why the subscription fired.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct NotificationEvent {
+    /// `change` when data changed, `subscribe` when the query can't be
+    /// subscribed to, `unknown` otherwise.
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// What fired it, e.g. `data`, `timeout`, `object`, `statement`.
+    pub source: String,
+    /// Detail, e.g. `insert`, `update`, `delete`, `truncate`, `invalid`.
+    pub info: String,
+}
+
+/// A live subscription and the queue/service created for it.
+#[derive(Clone)]
+pub struct Subscription {
+    pub conn_id: u64,
+    /// Broker object name, used for both the queue and the service.
+    name: String,
+}
+
+impl Subscription {
+    /// Create the queue and service, then run the query with a notification
+    /// request that posts to them.
+    pub async fn open(
+        client: &mut Client<Ready>,
+        conn_id: u64,
+        req: &SubscribeRequest,
+    ) -> Result<Self> {
+        let name = format!("mssqlts_qn_{}", uuid::Uuid::new_v4().simple());
+        client
+            .execute(
+                &format!(
+                    "CREATE QUEUE [{name}]; \
+                     CREATE SERVICE [{name}] ON QUEUE [{name}] ([{NOTIFICATION_CONTRACT}]);"
+                ),
+                &[],
+            )
+            .await
+            .map_err(|e| {
+                MssqlError::Query(format!(
+                    "Cannot create notification queue (is Service Broker enabled?): {}",
+                    MssqlError::from(e)
+                ))
+            })?;
+        let sub = Self { conn_id, name };
+
+        let (sql, order) = rewrite_named_params(&req.sql, &req.params);
+        let (sql, owned_values) = bind_params(sql, &req.params, &order)?;
+        let param_refs: Vec<&(dyn ToSql + Sync)> = owned_values
+            .iter()
+            .map(|v| &**v as &(dyn ToSql + Sync))
+            .collect();
+        let mut notification = QueryNotification::new(
+            sub.name.clone(),
+            format!("service={};local database={}", sub.name, current_database(client).await?),
+        );
+        if let Some(timeout) = req.timeout_s {
+            notification = notification.timeout(timeout);
+        }
+
+        let result = client
+            .query_with_notification(&sql, &param_refs, notification)
+            .await
+            .map(|rows| rows.into_iter().collect::<std::result::Result<Vec<Row>, _>>());
+        match result {
+            Ok(Ok(_)) => Ok(sub),
+            Ok(Err(e)) | Err(e) => {
+                // Don't leave the broker objects behind for a failed subscribe
+                let _ = sub.close(client).await;
+                Err(e.into())
+            }
+        }
+    }
+
+    /// Wait up to `timeout_ms` for notifications. Empty on timeout.
+    pub async fn receive(
+        &self,
+        client: &mut Client<Ready>,
+        timeout_ms: u64,
+    ) -> Result<Vec<NotificationEvent>> {
+        let sql = format!(
+            "WAITFOR (RECEIVE message_type_name, \
+             CAST(CAST(message_body AS xml) AS nvarchar(max)) AS body FROM [{}]), TIMEOUT {}",
+            self.name,
+            timeout_ms.min(i32::MAX as u64)
+        );
+        let stream = client.query(&sql, &[]).await.map_err(MssqlError::from)?;
+        let mut events = Vec::new();
+        for row in stream {
+            let row: Row = row.map_err(MssqlError::from)?;
+            let text = |i| match row.get_raw(i) {
+                Some(SqlValue::String(s)) => s,
+                _ => String::new(),
+            };
+            if text(0) == NOTIFICATION_MESSAGE {
+                events.push(parse_notification(&text(1)));
+            }
+        }
+        Ok(events)
+    }
+
+    /// Drop the service and queue. Pending subscriptions targeting them are
+    /// discarded by the server.
+    pub async fn close(&self, client: &mut Client<Ready>) -> Result<()> {
+        client
+            .execute(
+                &format!(
+                    "IF OBJECT_ID(N'{0}', N'SQ') IS NOT NULL BEGIN \
+                     DROP SERVICE [{0}]; DROP QUEUE [{0}]; END",
+                    self.name
+                ),
+                &[],
+            )
+            .await
+            .map_err(MssqlError::from)?;
+        Ok(())
+    }
+}
+
+async fn current_database(client: &mut Client<Ready>) -> Result<String> {
+    let rows = client
+        .query("SELECT DB_NAME()", &[])
+        .await
+        .map_err(MssqlError::from)?;
+    for row in rows {
+        let row: Row = row.map_err(MssqlError::from)?;
+        if let Some(SqlValue::String(name)) = row.get_raw(0) {
+            return Ok(name);
+        }
+    }
+    Err(MssqlError::Query("Cannot determine current database".into()))
+}
+
+/// Read the attributes of a `<qn:QueryNotification type=".." source=".."
+/// info="..">` message.
+fn parse_notification(xml: &str) -> NotificationEvent {
+    let start = xml.find("QueryNotification").unwrap_or(0);
+    let tag = &xml[start..xml[start..].find('>').map_or(xml.len(), |end| start + end)];
+    let attr = |name: &str| {
+        let key = format!(" {name}=\"");
+        tag.find(&key)
+            .and_then(|i| {
+                let rest = &tag[i + key.len()..];
+                rest.find('"').map(|end| rest[..end].to_string())
+            })
+            .unwrap_or_default()
+    };
+    let kind = match attr("type").as_str() {
+        kind @ ("change" | "subscribe") => kind.to_string(),
+        _ => "unknown".to_string(),
+    };
+    NotificationEvent {
+        kind,
+        source: attr("source"),
+        info: attr("info"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_change_notification() {
+        let xml = r#"<qn:QueryNotification xmlns:qn="http://schemas.microsoft.com/SQL/Notifications/QueryNotification" id="5" type="change" source="data" info="update" database_id="6" sid="0x01"><qn:Message>mssqlts_qn_1</qn:Message></qn:QueryNotification>"#;
+        assert_eq!(
+            parse_notification(xml),
+            NotificationEvent {
+                kind: "change".into(),
+                source: "data".into(),
+                info: "update".into(),
+            }
+        );
+    }
+
+    #[test]
+    fn parses_invalid_subscription() {
+        let xml = r#"<qn:QueryNotification id="0" type="subscribe" source="statement" info="invalid"/>"#;
+        let event = parse_notification(xml);
+        assert_eq!((event.kind.as_str(), event.info.as_str()), ("subscribe", "invalid"));
+        assert_eq!(parse_notification("garbage").kind, "unknown");
+    }
+}
@@ -0,0 +1,259 @@
+//! Paged queries: a SELECT with an ORDER BY gets `OFFSET/FETCH` appended,
+//! and optionally a `COUNT(*) OVER()` column for the total, so a page comes
+//! back as `{"rows", "total", "hasMore"}` in one round trip.
+
+use mssql_client::{Client, Ready, Row, ToSql};
+use serde::Deserialize;
+
+use crate::error::{MssqlError, Result};
+use crate::query::{
+    bind_params, closing, column_keys, is_ident_byte, rewrite_named_params, row_to_json,
+    skip_trivia, warn_renamed, SerializeOptions, SerializedCommand,
+};
+
+/// Column holding the total; `__` names are never recased.
+const TOTAL_COLUMN: &str = "__page_total";
+
+/// The page a command asks for.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+pub struct Paginate {
+    #[serde(default)]
+    pub offset: u64,
+    pub limit: u64,
+    /// Count the rows of the whole result too.
+    #[serde(default)]
+    pub include_total: bool,
+}
+
+/// The words (and `;`s) of `sql` outside parentheses, strings, quoted names
+/// and comments, with their offsets.
+fn top_level_tokens(sql: &str) -> Vec<(usize, &str)> {
+    let b = sql.as_bytes();
+    let mut tokens = Vec::new();
+    let mut depth = 0usize;
+    let mut i = 0;
+    loop {
+        i = skip_trivia(b, i);
+        let Some(&c) = b.get(i) else {
+            return tokens;
+        };
+        let start = i;
+        i = match c {
+            b'\'' => closing(b, i, b'\'').unwrap_or(b.len()),
+            b'[' => closing(b, i, b']').unwrap_or(b.len()),
+            b'"' => closing(b, i, b'"').unwrap_or(b.len()),
+            b'(' => {
+                depth += 1;
+                i + 1
+            }
+            b')' => {
+                depth = depth.saturating_sub(1);
+                i + 1
+            }
+            // Variables and temp tables are skipped whole
+            c if is_ident_byte(c) || c == b'@' || c == b'#' => {
+                i += 1;
+                while i < b.len() && is_ident_byte(b[i]) {
+                    i += 1;
+                }
+                i
+            }
+            _ => i + 1,
+        };
+        if depth == 0 && (is_ident_byte(c) || c == b';') {
+            tokens.push((start, &sql[start..i]));
+        }
+    }
+}
+
+/// `sql` with `OFFSET/FETCH` for `page` added after its ORDER BY, fetching
+/// one row more than the limit to tell whether there's another page. With
+/// `include_total`, the total is added as the first column.
+pub fn page_sql(sql: &str, page: &Paginate) -> Result<String> {
+    let err = |msg: &str| MssqlError::Query(format!("paginate: {msg}"));
+    if page.limit == 0 {
+        return Err(err("limit must be at least 1"));
+    }
+    let mut tokens = top_level_tokens(sql);
+    let mut end = sql.len();
+    while let Some(&(at, ";")) = tokens.last() {
+        end = at;
+        tokens.pop();
+    }
+    let is = |t: &(usize, &str), kw: &str| t.1.eq_ignore_ascii_case(kw);
+    if tokens.iter().any(|t| t.1 == ";") {
+        return Err(err("the command must be a single SELECT statement"));
+    }
+    let select = tokens
+        .iter()
+        .position(|t| is(t, "SELECT"))
+        .ok_or_else(|| err("the command must be a SELECT statement"))?;
+    let order = tokens
+        .windows(2)
+        .rposition(|w| is(&w[0], "ORDER") && is(&w[1], "BY"))
+        .filter(|&order| order > select)
+        .ok_or_else(|| err("the query needs an ORDER BY so pages are stable"))?;
+    if tokens[order..].iter().any(|t| is(t, "OFFSET")) {
+        return Err(err("the query already has an OFFSET"));
+    }
+    // OFFSET/FETCH goes before an OPTION (...) clause
+    let tail = tokens[order..]
+        .iter()
+        .find(|t| is(t, "OPTION"))
+        .map_or(end, |t| t.0);
+
+    let mut out = String::with_capacity(sql.len() + 80);
+    let mut from = 0;
+    if page.include_total {
+        let combined = tokens
+            .iter()
+            .any(|t| is(t, "UNION") || is(t, "EXCEPT") || is(t, "INTERSECT"));
+        if combined || tokens.get(select + 1).is_some_and(|t| is(t, "DISTINCT")) {
+            return Err(err(
+                "a total can't be added to DISTINCT, UNION, EXCEPT or INTERSECT queries",
+            ));
+        }
+        from = tokens[select].0 + "SELECT".len();
+        out.push_str(&sql[..from]);
+        out.push_str(&format!(" COUNT(*) OVER() AS [{TOTAL_COLUMN}],"));
+    }
+    out.push_str(&sql[from..tail]);
+    // On its own line, in case the query ends in a -- comment
+    out.push_str(&format!(
+        "\nOFFSET {} ROWS FETCH NEXT {} ROWS ONLY\n",
+        page.offset,
+        page.limit.saturating_add(1)
+    ));
+    out.push_str(&sql[tail..end]);
+    Ok(out)
+}
+
+/// Execute `cmd` for one page and return JSON { rows, total, hasMore }, with
+/// the row count. `total` is null unless asked for, and when the page is past
+/// the last row, as there's no row to carry it.
+pub async fn execute_page(
+    client: &mut Client<Ready>,
+    cmd: &SerializedCommand,
+    opts: &SerializeOptions,
+    page: &Paginate,
+) -> Result<(String, u64)> {
+    let sql = page_sql(&cmd.sql, page)?;
+    let (rewritten_sql, order) = rewrite_named_params(&sql, &cmd.params);
+    let (rewritten_sql, owned_values) = bind_params(rewritten_sql, &cmd.params, &order)?;
+    let param_refs: Vec<&(dyn ToSql + Sync)> = owned_values
+        .iter()
+        .map(|v| &**v as &(dyn ToSql + Sync))
+        .collect();
+
+    let stream = if param_refs.is_empty() {
+        client.query(&sql, &[]).await
+    } else {
+        client.query(&rewritten_sql, &param_refs).await
+    }
+    .map_err(MssqlError::from)?;
+
+    let mut rows = Vec::new();
+    let mut total = None;
+    let mut keys = None;
+    for result in stream {
+        let row: Row = result.map_err(MssqlError::from)?;
+        let keys = keys.get_or_insert_with(|| {
+            warn_renamed(row.columns(), opts.column_case);
+            column_keys(row.columns(), opts.column_case)
+        });
+        let mut json = row_to_json(&row, keys, opts)?;
+        if let Some(n) = json.as_object_mut().and_then(|o| o.remove(TOTAL_COLUMN)) {
+            total = n.as_u64();
+        }
+        rows.push(json);
+    }
+    Ok(page_envelope(rows, total, page))
+}
+
+fn page_envelope(
+    mut rows: Vec<serde_json::Value>,
+    total: Option<u64>,
+    page: &Paginate,
+) -> (String, u64) {
+    let has_more = rows.len() as u64 > page.limit;
+    rows.truncate(page.limit as usize);
+    // An empty first page has nothing to count
+    let total = match total {
+        None if page.include_total && page.offset == 0 => Some(0),
+        total => total,
+    };
+    let count = rows.len() as u64;
+    let envelope = serde_json::json!({ "rows": rows, "total": total, "hasMore": has_more });
+    (envelope.to_string(), count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn page(offset: u64, limit: u64, include_total: bool) -> Paginate {
+        Paginate {
+            offset,
+            limit,
+            include_total,
+        }
+    }
+
+    #[test]
+    fn appends_offset_fetch_after_the_top_level_order_by() {
+        let sql = "WITH recent AS (SELECT TOP 100 * FROM Orders ORDER BY Id DESC)\n\
+                   SELECT o.Id, (SELECT MAX(x) FROM y ORDER BY 1 OFFSET 0 ROWS) AS m\n\
+                   FROM recent o ORDER BY o.Id -- newest first\n;";
+        assert_eq!(
+            page_sql(sql, &page(40, 20, false)).unwrap(),
+            "WITH recent AS (SELECT TOP 100 * FROM Orders ORDER BY Id DESC)\n\
+             SELECT o.Id, (SELECT MAX(x) FROM y ORDER BY 1 OFFSET 0 ROWS) AS m\n\
+             FROM recent o ORDER BY o.Id -- newest first\n\
+             \nOFFSET 40 ROWS FETCH NEXT 21 ROWS ONLY\n"
+        );
+        assert_eq!(
+            page_sql(
+                "SELECT * FROM t ORDER BY a OPTION (RECOMPILE)",
+                &page(0, 9, true)
+            )
+            .unwrap(),
+            "SELECT COUNT(*) OVER() AS [__page_total], * FROM t ORDER BY a \
+             \nOFFSET 0 ROWS FETCH NEXT 10 ROWS ONLY\nOPTION (RECOMPILE)"
+        );
+        let rejected = [
+            ("SELECT * FROM t", false),
+            (
+                "SELECT * FROM (SELECT a FROM t ORDER BY a OFFSET 0 ROWS) x",
+                true,
+            ),
+            ("SELECT 1; SELECT * FROM t ORDER BY a", false),
+            ("SELECT * FROM t ORDER BY a OFFSET 5 ROWS", false),
+            ("SELECT DISTINCT a FROM t ORDER BY a", true),
+            ("SELECT a FROM t UNION SELECT a FROM u ORDER BY a", true),
+        ];
+        for (sql, include_total) in rejected {
+            assert!(page_sql(sql, &page(0, 10, include_total)).is_err(), "{sql}");
+        }
+        assert!(page_sql("SELECT * FROM t ORDER BY a", &page(0, 0, false)).is_err());
+    }
+
+    #[test]
+    fn trims_the_extra_row_into_has_more() {
+        let rows = vec![json!({ "id": 1 }), json!({ "id": 2 }), json!({ "id": 3 })];
+        let (body, count) = page_envelope(rows.clone(), Some(7), &page(0, 2, true));
+        let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(count, 2);
+        assert_eq!(body["rows"], json!([{ "id": 1 }, { "id": 2 }]));
+        assert_eq!(body["total"], 7);
+        assert_eq!(body["hasMore"], true);
+
+        let (body, _) = page_envelope(rows, None, &page(4, 3, false));
+        assert_eq!(
+            body,
+            r#"{"hasMore":false,"rows":[{"id":1},{"id":2},{"id":3}],"total":null}"#
+        );
+        let (body, _) = page_envelope(vec![], None, &page(0, 3, true));
+        assert_eq!(body, r#"{"hasMore":false,"rows":[],"total":0}"#);
+    }
+}
@@ -1,37 +1,649 @@
-use mssql_client::{Client, Ready};
-use mssql_driver_pool::Pool;
+use std::collections::HashMap;
+use std::fmt;
+use std::io::ErrorKind;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
-use crate::config::NormalizedConfig;
-use crate::debug::debug_log;
+use lazy_static::lazy_static;
+use mssql_client::{Client, Config, Ready};
+use mssql_driver_pool::{Pool, PoolError, PooledConnection};
+use tokio::net::TcpStream;
+use tokio::task::JoinSet;
+
+use crate::config::{ApplicationIntent, NormalizedConfig};
+use crate::debug::{debug_log, trace_log, warn_log};
 use crate::error::{MssqlError, Result};
+use crate::handle::PoolHandle;
+
+/// Port SQL Server's default instance listens on; a named instance given
+/// with this port has its real port looked up via the SQL Browser.
+const DEFAULT_PORT: u16 = 1433;
+
+/// Routing ENVCHANGEs followed per login before giving up: an Azure gateway
+/// redirect, then read-only routing, with room for one more.
+const MAX_ROUTING_HOPS: usize = 3;
+
+/// Cap on the doubled delay between connect retries.
+const MAX_RETRY_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Login errors worth retrying: the database is failing over, or the
+/// service is busy or throttling (the set SqlClient retries).
+const TRANSIENT_SERVER_ERRORS: [i32; 10] = [
+    4060, 4221, 10928, 10929, 40197, 40501, 40613, 49918, 49919, 49920,
+];
+
+/// Azure SQL host suffixes whose gateways redirect logins to the node
+/// hosting the database (the default connection policy inside Azure).
+const AZURE_SQL_SUFFIXES: [&str; 4] = [
+    ".database.windows.net",
+    ".database.chinacloudapi.cn",
+    ".database.usgovcloudapi.net",
+    ".database.cloudapi.de",
+];
+
+lazy_static! {
+    /// Where logins to each configured target were routed, keyed by pool
+    /// identity and target, so creating or rebuilding a pool doesn't spend
+    /// a probe login every time.
+    static ref ROUTES: Mutex<HashMap<String, Target>> = Mutex::new(HashMap::new());
+}
+
+/// A failed connect attempt, classified while its source is still known.
+#[derive(Debug)]
+struct ConnectError {
+    error: MssqlError,
+    /// The server or network was unreachable, busy or failing over, as
+    /// opposed to bad config, an unknown host, a rejected certificate or
+    /// rejected credentials, so a retry may succeed.
+    transient: bool,
+}
+
+type ConnectResult<T> = std::result::Result<T, ConnectError>;
+
+impl ConnectError {
+    fn unreachable(error: MssqlError) -> Self {
+        Self {
+            error,
+            transient: true,
+        }
+    }
+}
+
+impl fmt::Display for ConnectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.error, f)
+    }
+}
+
+/// Errors raised here (config, Azure AD tokens, the SQL Browser) are final.
+impl From<MssqlError> for ConnectError {
+    fn from(error: MssqlError) -> Self {
+        Self {
+            error,
+            transient: false,
+        }
+    }
+}
+
+impl From<mssql_client::Error> for ConnectError {
+    fn from(e: mssql_client::Error) -> Self {
+        Self {
+            transient: is_transient(&e),
+            error: e.into(),
+        }
+    }
+}
 
-/// Create a connection pool from the normalized config.
-pub async fn create_pool(config: &NormalizedConfig) -> Result<Pool> {
-    let client_config = config.to_client_config()?;
-    let pool_config = config.to_pool_config();
+impl From<PoolError> for ConnectError {
+    fn from(e: PoolError) -> Self {
+        Self {
+            transient: matches!(
+                e,
+                PoolError::ConnectionCreation(_) | PoolError::Connection(_)
+            ),
+            error: e.into(),
+        }
+    }
+}
+
+/// Whether a client connect failure may succeed on retry: a timeout, a
+/// refused or dropped socket, or a transient login error. DNS failures,
+/// TLS and certificate errors and rejected logins are not.
+fn is_transient(e: &mssql_client::Error) -> bool {
+    use mssql_client::Error;
+    match e {
+        Error::ConnectTimeout
+        | Error::TlsTimeout
+        | Error::ConnectionTimeout
+        | Error::ConnectionClosed => true,
+        Error::Io(io) => matches!(
+            io.kind(),
+            ErrorKind::ConnectionRefused
+                | ErrorKind::ConnectionReset
+                | ErrorKind::ConnectionAborted
+                | ErrorKind::NotConnected
+                | ErrorKind::BrokenPipe
+                | ErrorKind::TimedOut
+                | ErrorKind::UnexpectedEof
+                | ErrorKind::HostUnreachable
+                | ErrorKind::NetworkUnreachable
+        ),
+        Error::Server { number, .. } => TRANSIENT_SERVER_ERRORS.contains(number),
+        _ => false,
+    }
+}
+
+/// A server to connect to: the configured server, its failover partner, or
+/// where either routed the login.
+#[derive(Clone)]
+struct Target {
+    host: String,
+    port: u16,
+    instance: Option<String>,
+}
+
+impl Target {
+    /// The instance to resolve via the SQL Browser: set, with no explicit port.
+    fn browsed_instance(&self) -> Option<&str> {
+        self.instance.as_deref().filter(|_| self.port == DEFAULT_PORT)
+    }
+
+    /// Forget a browsed port after failing to connect, in case the instance
+    /// restarted on a new dynamic port.
+    fn forget_instance(&self) {
+        if let Some(instance) = self.browsed_instance() {
+            crate::browser::forget(&self.host, instance);
+        }
+    }
+}
+
+/// The configured server, then the failover partner if one is set. The
+/// partner accepts the same `host`, `host,port` and `host\instance` forms.
+fn targets(config: &NormalizedConfig) -> Result<Vec<Target>> {
+    let mut targets = vec![Target {
+        host: config.server.clone(),
+        port: config.port,
+        instance: config.instance_name.clone(),
+    }];
+    if let Some(partner) = config.failover_partner.as_deref().map(str::trim) {
+        let target = if let Some((host, instance)) = partner.split_once('\\') {
+            Target {
+                host: host.to_string(),
+                port: DEFAULT_PORT,
+                instance: Some(instance.to_string()),
+            }
+        } else if let Some((host, port)) = partner.split_once(',') {
+            Target {
+                host: host.trim().to_string(),
+                port: port.trim().parse().map_err(|_| {
+                    MssqlError::Config(format!("Invalid failover partner port: {partner}"))
+                })?,
+                instance: None,
+            }
+        } else {
+            Target {
+                host: partner.to_string(),
+                port: config.port,
+                instance: None,
+            }
+        };
+        targets.push(target);
+    }
+    Ok(targets)
+}
 
-    debug_log!(
-        "Creating pool: min={}, max={}, timeout={}ms",
-        pool_config.min_connections,
-        pool_config.max_connections,
-        pool_config.connection_timeout.as_millis()
-    );
+/// Resolve every address of `host` and race TCP connects to them, returning
+/// the first to answer. Availability group listeners spanning subnets
+/// register one address per subnet, and only the primary's accepts.
+///
+/// The client opens its own socket for the login, so the winning probe is
+/// closed and its address connected to again. The other attempts are
+/// aborted and their sockets closed before that reconnect starts.
+async fn fastest_address(host: &str, port: u16, timeout: Duration) -> ConnectResult<SocketAddr> {
+    let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host, port))
+        .await
+        .map_err(|e| MssqlError::Connection(format!("Cannot resolve {host}: {e}")))?
+        .collect();
+    trace_log!("Racing {} addresses for {}", addrs.len(), host);
+
+    let mut attempts = JoinSet::new();
+    for addr in addrs {
+        attempts.spawn(async move {
+            tokio::time::timeout(timeout, TcpStream::connect(addr))
+                .await
+                .ok()
+                .and_then(|r| r.ok())
+                .map(|_| addr)
+        });
+    }
+    while let Some(attempt) = attempts.join_next().await {
+        if let Ok(Some(addr)) = attempt {
+            trace_log!("{} answered first for {}", addr, host);
+            // Abort the attempts still in flight and wait for them to go,
+            // so no half-open sockets outlive the race
+            attempts.shutdown().await;
+            return Ok(addr);
+        }
+    }
+    Err(ConnectError::unreachable(MssqlError::Connection(format!(
+        "No address of {host} accepted a connection on port {port}"
+    ))))
+}
+
+/// Build the client config for `target`, fetching an Azure AD token,
+/// resolving a named instance's dynamic port and picking the answering
+/// address for multi-subnet listeners as needed. Also returns the token's
+/// expiry.
+async fn client_config(
+    config: &NormalizedConfig,
+    target: &Target,
+) -> ConnectResult<(Config, Option<u64>)> {
+    let token = config.acquire_token().await?;
+    let mut client_config = config.to_client_config(token.as_ref().map(|t| t.token.expose()))?;
+    let connect_timeout = Duration::from_millis(config.connect_timeout_ms);
+
+    let mut port = target.port;
+    client_config.instance = target.instance.clone();
+    if let Some(instance) = target.browsed_instance() {
+        let timeout = connect_timeout.min(crate::browser::BROWSER_TIMEOUT);
+        port = crate::browser::resolve_port(&target.host, instance, timeout).await?;
+        client_config.instance = None;
+    }
+
+    client_config = if config.multi_subnet_failover {
+        let addr = fastest_address(&target.host, port, connect_timeout).await?;
+        let mut c = client_config.host(&addr.ip().to_string());
+        // Validate the certificate against the listener name, not the IP
+        c.tls.server_name = Some(target.host.clone());
+        c
+    } else {
+        client_config.host(&target.host)
+    };
+    Ok((client_config.port(port), token.map(|t| t.expires_on)))
+}
+
+/// Connect to `target`, following routing ENVCHANGEs: an Azure SQL gateway
+/// redirects logins to the database's node, and an availability group
+/// listener sends read-only logins on to a readable secondary. Returns the
+/// target finally connected to.
+async fn connect(
+    config: &NormalizedConfig,
+    mut target: Target,
+) -> (Target, ConnectResult<Client<Ready>>) {
+    let mut hops = 0;
+    loop {
+        let client_config = match client_config(config, &target).await {
+            Ok((client_config, _)) => client_config,
+            Err(e) => return (target, Err(e)),
+        };
+        match Client::connect(client_config).await {
+            Err(mssql_client::Error::Routing { host, port }) if hops < MAX_ROUTING_HOPS => {
+                debug_log!("{} routed the connection to {}:{}", target.host, host, port);
+                hops += 1;
+                target = Target {
+                    host,
+                    port,
+                    instance: None,
+                };
+            }
+            result => return (target, result.map_err(ConnectError::from)),
+        }
+    }
+}
 
-    let pool = Pool::new(pool_config, client_config).await?;
-    debug_log!("Pool created successfully");
+/// Whether logins to `config` may be routed elsewhere, so a pool should
+/// find the final target before it opens connections.
+fn may_route(config: &NormalizedConfig) -> bool {
+    let server = config.server.to_lowercase();
+    config.application_intent == ApplicationIntent::ReadOnly
+        || AZURE_SQL_SUFFIXES.iter().any(|s| server.ends_with(s))
+}
+
+fn route_key(config: &NormalizedConfig, target: &Target) -> String {
+    format!(
+        "{}|{}:{}\\{}",
+        config.dedup_key(),
+        target.host.to_lowercase(),
+        target.port,
+        target.instance.as_deref().unwrap_or("").to_lowercase()
+    )
+}
+
+/// Where logins to `target` end up: the cached route, or the target a
+/// probe login is routed to.
+async fn routed_target(
+    config: &NormalizedConfig,
+    key: &str,
+    target: Target,
+) -> (Target, ConnectResult<()>) {
+    if let Some(routed) = ROUTES.lock().unwrap().get(key) {
+        return (routed.clone(), Ok(()));
+    }
+    match connect(config, target).await {
+        (routed, Ok(_probe)) => {
+            ROUTES
+                .lock()
+                .unwrap()
+                .insert(key.to_string(), routed.clone());
+            (routed, Ok(()))
+        }
+        (target, Err(e)) => (target, Err(e)),
+    }
+}
+
+/// Drop the cached routes of a pool identity, e.g. after its target
+/// stopped answering.
+fn forget_routes(config: &NormalizedConfig) {
+    let prefix = format!("{}|", config.dedup_key());
+    ROUTES
+        .lock()
+        .unwrap()
+        .retain(|key, _| !key.starts_with(&prefix));
+}
+
+/// Run `connect` against each target in turn, returning the first success
+/// or the last error.
+async fn with_failover<T, F, Fut>(config: &NormalizedConfig, mut connect: F) -> ConnectResult<T>
+where
+    F: FnMut(Target) -> Fut,
+    Fut: std::future::Future<Output = (Target, ConnectResult<T>)>,
+{
+    let mut last_err = None;
+    for target in targets(config)? {
+        match connect(target).await {
+            (_, Ok(value)) => return Ok(value),
+            (target, Err(e)) => {
+                debug_log!("Connecting to {} failed: {}", target.host, e);
+                target.forget_instance();
+                last_err = Some(e);
+            }
+        }
+    }
+    Err(last_err.expect("at least one target"))
+}
+
+/// Run `attempt`, retrying transient failures up to `connect_retry_count`
+/// times with exponential backoff from `connect_retry_interval_ms`.
+async fn with_retry<T, F, Fut>(config: &NormalizedConfig, mut attempt: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = ConnectResult<T>>,
+{
+    let mut retries = config.connect_retry_count;
+    let mut delay = Duration::from_millis(config.connect_retry_interval_ms);
+    loop {
+        match attempt().await {
+            Err(e) if retries > 0 && e.transient => {
+                debug_log!(
+                    "Connect failed ({}), retrying in {}ms ({} left)",
+                    e,
+                    delay.as_millis(),
+                    retries
+                );
+                tokio::time::sleep(delay).await;
+                delay = (delay * 2).min(MAX_RETRY_INTERVAL);
+                retries -= 1;
+            }
+            result => return result.map_err(|e| e.error),
+        }
+    }
+}
+
+/// Create a connection pool from the normalized config, retrying transient
+/// failures and falling back to the failover partner if the server is
+/// unreachable. Also returns the expiry of the Azure AD token it was built
+/// with, if one was fetched.
+pub async fn create_pool(config: &NormalizedConfig) -> Result<(Pool, Option<u64>)> {
+    with_retry(config, || create_pool_once(config)).await
+}
+
+async fn create_pool_once(config: &NormalizedConfig) -> ConnectResult<(Pool, Option<u64>)> {
+    with_failover(config, |target| async move {
+        // Pooled logins can't follow routing themselves, so find where the
+        // server routes logins and point the pool there. This also skips
+        // the Azure gateway's proxy hop for every pooled connection
+        let key = route_key(config, &target);
+        let target = if may_route(config) {
+            match routed_target(config, &key, target).await {
+                (target, Ok(())) => target,
+                (target, Err(e)) => return (target, Err(e)),
+            }
+        } else {
+            target
+        };
+        let result = async {
+            let (client_config, token_expires_on) = client_config(config, &target).await?;
+            let pool_config = config.to_pool_config();
+
+            debug_log!(
+                "Creating pool for {}: min={}, max={}, timeout={}ms",
+                target.host,
+                pool_config.min_connections,
+                pool_config.max_connections,
+                pool_config.connection_timeout.as_millis()
+            );
+
+            let pool = Pool::new(pool_config, client_config).await?;
+            debug_log!("Pool created successfully");
+            Ok::<_, ConnectError>((pool, token_expires_on))
+        }
+        .await;
+        if result.is_err() {
+            // The route may be stale; probe again on the next attempt
+            ROUTES.lock().unwrap().remove(&key);
+        }
+        (target, result)
+    })
+    .await
+}
+
+/// The pool to acquire from. A pool built with a fetched Azure AD token is
+/// rebuilt with a fresh token once the old one nears expiry, so connections
+/// it opens from then on can still log in. Connections already handed out
+/// keep their sessions.
+pub async fn current_pool(handle: &PoolHandle) -> Result<Arc<Pool>> {
+    let pool = handle.pool.lock().unwrap().clone();
+    let expires_on = *handle.token_expires_on.lock().unwrap();
+    if expires_on.is_some_and(crate::token::needs_refresh) {
+        debug_log!("Azure AD token near expiry, rebuilding pool");
+        rebuild_pool(handle, &pool).await?;
+        return Ok(handle.pool.lock().unwrap().clone());
+    }
     Ok(pool)
 }
 
-/// Create a single (non-pooled) connection.
+/// Replace `stale` with a newly built pool. Callers that find the pool
+/// stale at the same time wait for the first to rebuild it, then use its
+/// pool rather than building another.
+async fn rebuild_pool(handle: &PoolHandle, stale: &Arc<Pool>) -> Result<()> {
+    let _rebuilding = handle.rebuilding.lock().await;
+    if !Arc::ptr_eq(stale, &handle.pool.lock().unwrap()) {
+        return Ok(());
+    }
+    let (pool, expires_on) = create_pool(&handle.config).await?;
+    // Expiry first: a caller that sees the new pool then sees its expiry
+    *handle.token_expires_on.lock().unwrap() = expires_on;
+    *handle.pool.lock().unwrap() = Arc::new(pool);
+    Ok(())
+}
+
+/// Acquire a pooled connection. When the pool can't open a connection and
+/// its target may have moved (a failover partner is configured, or logins
+/// are routed), the pool is rebuilt against wherever the server now answers
+/// and the acquire is tried once more.
+pub async fn acquire(handle: &PoolHandle) -> Result<PooledConnection> {
+    let retarget = handle.config.failover_partner.is_some() || may_route(&handle.config);
+    let pool = current_pool(handle).await?;
+    match pool.get().await {
+        Ok(conn) => Ok(conn),
+        Err(e @ (PoolError::ConnectionCreation(_) | PoolError::Connection(_))) if retarget => {
+            warn_log!("Acquire failed ({}), rebuilding pool against the current target", e);
+            forget_routes(&handle.config);
+            rebuild_pool(handle, &pool).await?;
+            Ok(current_pool(handle).await?.get().await?)
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Create a single (non-pooled) connection, retrying transient failures and
+/// falling back to the failover partner if the server is unreachable.
 pub async fn create_single(config: &NormalizedConfig) -> Result<Client<Ready>> {
-    let client_config = config.to_client_config()?;
+    with_retry(config, || create_single_once(config)).await
+}
 
-    debug_log!("Creating bare connection to {}:{}", config.server, config.port);
+async fn create_single_once(config: &NormalizedConfig) -> ConnectResult<Client<Ready>> {
+    with_failover(config, |target| async move {
+        debug_log!("Creating bare connection to {}:{}", target.host, target.port);
+        let (target, result) = connect(config, target).await;
+        if result.is_ok() {
+            debug_log!("Bare connection established to {}", target.host);
+        }
+        (target, result)
+    })
+    .await
+}
 
-    let client = Client::connect(client_config)
-        .await
-        .map_err(MssqlError::from)?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::tests::make_config;
+
+    #[test]
+    fn failover_targets() {
+        let mut cfg = make_config("primary", "db", None, None);
+        assert_eq!(targets(&cfg).unwrap().len(), 1);
+
+        cfg.failover_partner = Some("mirror,1500".into());
+        let t = targets(&cfg).unwrap();
+        assert_eq!((t[1].host.as_str(), t[1].port), ("mirror", 1500));
+
+        cfg.failover_partner = Some("mirror\\INST".into());
+        let t = targets(&cfg).unwrap();
+        assert_eq!(t[1].browsed_instance(), Some("INST"));
+
+        cfg.failover_partner = Some("mirror,x".into());
+        assert!(targets(&cfg).is_err());
+    }
+
+    #[test]
+    fn pools_probe_routing_for_azure_and_read_only() {
+        let mut cfg = make_config("sql01.corp.example", "db", None, None);
+        assert!(!may_route(&cfg));
+        cfg.application_intent = ApplicationIntent::ReadOnly;
+        assert!(may_route(&cfg));
+        let cfg = make_config("MyServer.Database.Windows.Net", "db", None, None);
+        assert!(may_route(&cfg));
+    }
+
+    #[test]
+    fn routes_are_cached_per_pool_identity() {
+        let cfg = make_config("route-cache.database.windows.net", "db", None, None);
+        let other = make_config("route-cache.database.windows.net", "other", None, None);
+        let target = targets(&cfg).unwrap().remove(0);
+        let routed = Target {
+            host: "node1.route-cache.database.windows.net".into(),
+            port: 11000,
+            instance: None,
+        };
+        let key = route_key(&cfg, &target);
+        assert_ne!(key, route_key(&other, &target));
+        ROUTES.lock().unwrap().insert(key.clone(), routed);
+
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        // Served from the cache: no probe login
+        let (found, result) = rt.block_on(routed_target(&cfg, &key, target));
+        assert!(result.is_ok());
+        assert_eq!(
+            (found.host.as_str(), found.port),
+            ("node1.route-cache.database.windows.net", 11000)
+        );
+
+        forget_routes(&other);
+        assert!(ROUTES.lock().unwrap().contains_key(&key));
+        forget_routes(&cfg);
+        assert!(!ROUTES.lock().unwrap().contains_key(&key));
+    }
+
+    #[test]
+    fn retries_transient_failures_with_backoff() {
+        let mut cfg = make_config("localhost", "db", None, None);
+        cfg.connect_retry_count = 2;
+        cfg.connect_retry_interval_ms = 1;
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        let mut calls = 0;
+        let result = rt.block_on(with_retry(&cfg, || {
+            calls += 1;
+            let n = calls;
+            async move {
+                if n < 3 {
+                    let refused = std::io::Error::from(ErrorKind::ConnectionRefused);
+                    Err(mssql_client::Error::from(refused).into())
+                } else {
+                    Ok(n)
+                }
+            }
+        }));
+        assert_eq!(result.unwrap(), 3);
+
+        let mut calls = 0;
+        let result: Result<()> = rt.block_on(with_retry(&cfg, || {
+            calls += 1;
+            async {
+                Err(MssqlError::Connection("Azure AD token request failed (401)".into()).into())
+            }
+        }));
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn classifies_connect_failures_by_source() {
+        use mssql_client::Error;
+        let io = |kind| Error::from(std::io::Error::from(kind));
+        let server = |number| Error::Server {
+            number,
+            class: 14,
+            state: 1,
+            message: String::new(),
+            server: None,
+            procedure: None,
+            line: 0,
+        };
+        assert!(is_transient(&io(ErrorKind::ConnectionRefused)));
+        assert!(is_transient(&Error::ConnectTimeout));
+        assert!(is_transient(&server(40613)));
+        // DNS lookup failures surface as uncategorized IO errors
+        assert!(!is_transient(&Error::from(std::io::Error::other(
+            "failed to lookup address information"
+        ))));
+        let tls = Error::Tls("invalid peer certificate".into());
+        assert!(!is_transient(&tls));
+        assert!(!is_transient(&server(18456)));
+        assert!(!ConnectError::from(MssqlError::Config("bad".into())).transient);
+    }
 
-    debug_log!("Bare connection established");
-    Ok(client)
+    #[test]
+    fn races_to_listening_address() {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        rt.block_on(async {
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let port = listener.local_addr().unwrap().port();
+            let addr = fastest_address("127.0.0.1", port, Duration::from_secs(1))
+                .await
+                .unwrap();
+            assert_eq!(addr.port(), port);
+        });
+    }
 }
@@ -0,0 +1,343 @@
+//! Script execution: scripts run batch by batch (split on `GO` lines), and
+//! the informational messages and completion of each batch are queued on the
+//! connection so the caller can poll them while the script runs.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use mssql_client::{Client, Ready, ToSql};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{MssqlError, Result};
+use crate::query::{bind_params, rewrite_named_params, SerializedParam};
+
+/// Events kept per connection before the oldest are dropped.
+pub const CAPACITY: usize = 1024;
+
+/// One progress event.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Event {
+    /// PRINT or RAISERROR output (severity 10 and below).
+    Message {
+        batch: usize,
+        number: u32,
+        class: u8,
+        message: String,
+    },
+    /// A batch finished.
+    Batch {
+        batch: usize,
+        #[serde(rename = "rowsAffected")]
+        rows_affected: u64,
+        #[serde(rename = "durationMs")]
+        duration_ms: u64,
+    },
+}
+
+/// A connection's progress queue.
+#[derive(Default)]
+pub struct Progress {
+    inner: Mutex<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    events: VecDeque<Event>,
+    /// Events dropped since the last drain because the queue was full.
+    dropped: u64,
+}
+
+impl Progress {
+    pub fn push(&self, event: Event) {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.events.len() == CAPACITY {
+            inner.events.pop_front();
+            inner.dropped += 1;
+        }
+        inner.events.push_back(event);
+    }
+
+    /// Take the queued events and how many were dropped since the last call.
+    pub fn drain(&self) -> (Vec<Event>, u64) {
+        let mut inner = self.inner.lock().unwrap();
+        let dropped = std::mem::take(&mut inner.dropped);
+        (inner.events.drain(..).collect(), dropped)
+    }
+
+    fn clear(&self) {
+        *self.inner.lock().unwrap() = Inner::default();
+    }
+}
+
+/// One batch of a script.
+#[derive(Debug, PartialEq)]
+pub struct Batch {
+    pub sql: String,
+    /// Line the batch starts on, from 1.
+    pub line: usize,
+    /// Times to run it, from `GO n`.
+    pub count: u32,
+}
+
+/// Where the splitter is at the end of a line.
+#[derive(Clone, Copy, PartialEq)]
+enum Scan {
+    Code,
+    /// Inside a literal or quoted name that ends with this character.
+    Quoted(char),
+    /// Inside `/* */` comments, nested this deep.
+    Comment(usize),
+}
+
+/// Split a script into batches on lines holding only `GO` (or `GO n` to run
+/// the batch n times), the way sqlcmd and SSMS do. A `GO` inside a string,
+/// quoted name or block comment doesn't count. Blank batches are skipped.
+pub fn split_batches(sql: &str) -> Result<Vec<Batch>> {
+    let mut batches = Vec::new();
+    let mut current = String::new();
+    let mut start_line = 1;
+    let mut scan = Scan::Code;
+    for (i, line) in sql.lines().enumerate() {
+        if scan == Scan::Code {
+            if let Some(count) = go_count(line, i + 1)? {
+                if !current.trim().is_empty() {
+                    batches.push(Batch {
+                        sql: std::mem::take(&mut current),
+                        line: start_line,
+                        count,
+                    });
+                }
+                current.clear();
+                start_line = i + 2;
+                continue;
+            }
+        }
+        scan = scan_line(line, scan);
+        current.push_str(line);
+        current.push('\n');
+    }
+    if !current.trim().is_empty() {
+        batches.push(Batch {
+            sql: current,
+            line: start_line,
+            count: 1,
+        });
+    }
+    Ok(batches)
+}
+
+/// The repeat count if `line` is a `GO` separator, optionally followed by a
+/// count and a `--` comment.
+fn go_count(line: &str, line_no: usize) -> Result<Option<u32>> {
+    let line = line.split("--").next().unwrap_or_default();
+    let mut words = line.split_whitespace();
+    if !words.next().is_some_and(|w| w.eq_ignore_ascii_case("go")) {
+        return Ok(None);
+    }
+    match (words.next(), words.next()) {
+        (None, _) => Ok(Some(1)),
+        (Some(n), None) => match n.parse::<u32>() {
+            Ok(count) if count > 0 => Ok(Some(count)),
+            _ => Err(MssqlError::Query(format!(
+                "Invalid GO count '{n}' on line {line_no}"
+            ))),
+        },
+        // `go to_the_store` and the like aren't separators
+        _ => Ok(None),
+    }
+}
+
+/// Carry the quote and comment state across one line.
+fn scan_line(line: &str, mut scan: Scan) -> Scan {
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        scan = match (scan, c) {
+            (Scan::Code, '\'') => Scan::Quoted('\''),
+            (Scan::Code, '"') => Scan::Quoted('"'),
+            (Scan::Code, '[') => Scan::Quoted(']'),
+            (Scan::Code, '-') if chars.next_if_eq(&'-').is_some() => return Scan::Code,
+            (Scan::Code, '/') if chars.next_if_eq(&'*').is_some() => Scan::Comment(1),
+            // A doubled closing character is an escaped one
+            (Scan::Quoted(close), c) if c == close => match chars.next_if_eq(&close) {
+                Some(_) => scan,
+                None => Scan::Code,
+            },
+            (Scan::Comment(depth), '/') if chars.next_if_eq(&'*').is_some() => {
+                Scan::Comment(depth + 1)
+            }
+            (Scan::Comment(depth), '*') if chars.next_if_eq(&'/').is_some() => match depth {
+                1 => Scan::Code,
+                _ => Scan::Comment(depth - 1),
+            },
+            _ => scan,
+        };
+    }
+    scan
+}
+
+/// Request for `mssql_exec_script`.
+#[derive(Deserialize)]
+pub struct ScriptRequest {
+    pub sql: String,
+    #[serde(default)]
+    pub params: Vec<SerializedParam>,
+    /// Keep running the batches after one fails.
+    #[serde(default)]
+    pub continue_on_error: bool,
+}
+
+/// Outcome of one batch.
+#[derive(Serialize, Debug)]
+pub struct BatchResult {
+    pub batch: usize,
+    pub line: usize,
+    /// Runs completed, out of the `GO n` count.
+    pub runs: u32,
+    #[serde(rename = "rowsAffected")]
+    pub rows_affected: u64,
+    #[serde(rename = "durationMs")]
+    pub duration_ms: u64,
+    pub error: Option<String>,
+}
+
+/// Run a script batch by batch, queueing each batch's messages and
+/// completion on `progress` as it finishes. Stops at the first failing batch
+/// unless `continue_on_error` is set; either way the failure is reported in
+/// that batch's result. Returns JSON { batches, rowsAffected, results }.
+pub async fn exec_script(
+    client: &mut Client<Ready>,
+    req: &ScriptRequest,
+    progress: &Progress,
+) -> Result<String> {
+    progress.clear();
+    // Messages left over from earlier commands aren't part of this script
+    client.take_info_messages();
+    let batches = split_batches(&req.sql)?;
+    let mut results = Vec::with_capacity(batches.len());
+    for (i, batch) in batches.iter().enumerate() {
+        let result = run_batch(client, req, i, batch, progress).await;
+        progress.push(Event::Batch {
+            batch: i,
+            rows_affected: result.rows_affected,
+            duration_ms: result.duration_ms,
+        });
+        let failed = result.error.is_some();
+        results.push(result);
+        if failed && !req.continue_on_error {
+            break;
+        }
+    }
+    Ok(serde_json::json!({
+        "batches": batches.len(),
+        "rowsAffected": results.iter().map(|r| r.rows_affected).sum::<u64>(),
+        "results": results,
+    })
+    .to_string())
+}
+
+/// Run one batch `count` times, stopping at the first error.
+async fn run_batch(
+    client: &mut Client<Ready>,
+    req: &ScriptRequest,
+    index: usize,
+    batch: &Batch,
+    progress: &Progress,
+) -> BatchResult {
+    let started = Instant::now();
+    let mut result = BatchResult {
+        batch: index,
+        line: batch.line,
+        runs: 0,
+        rows_affected: 0,
+        duration_ms: 0,
+        error: None,
+    };
+    let (sql, order) = rewrite_named_params(&batch.sql, &req.params);
+    while result.runs < batch.count && result.error.is_none() {
+        let outcome = execute(client, &sql, &req.params, &order).await;
+        // Messages raised before a failure are still worth seeing
+        for m in client.take_info_messages() {
+            progress.push(Event::Message {
+                batch: index,
+                number: m.number,
+                class: m.class,
+                message: m.message,
+            });
+        }
+        match outcome {
+            Ok(rows) => {
+                result.runs += 1;
+                result.rows_affected += rows;
+            }
+            Err(e) => result.error = Some(e.to_string()),
+        }
+    }
+    result.duration_ms = started.elapsed().as_millis() as u64;
+    result
+}
+
+async fn execute(
+    client: &mut Client<Ready>,
+    sql: &str,
+    params: &[SerializedParam],
+    order: &[usize],
+) -> Result<u64> {
+    let (sql, owned_values) = bind_params(sql.to_string(), params, order)?;
+    let param_refs: Vec<&(dyn ToSql + Sync)> = owned_values
+        .iter()
+        .map(|v| &**v as &(dyn ToSql + Sync))
+        .collect();
+    client
+        .execute(&sql, &param_refs)
+        .await
+        .map_err(MssqlError::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_on_go_lines_outside_strings_and_comments() {
+        let script = "PRINT 'a';\nGO\n  go  \nUPDATE t SET category = 'GO';\nGOTO done;\n\
+                      SELECT 'multi\nGO\nline';\n/* outer /* inner */\nGO\n*/\n\
+                      go 3 -- seed\nINSERT t DEFAULT VALUES";
+        let batches = split_batches(script).unwrap();
+        let sql: Vec<&str> = batches.iter().map(|b| b.sql.as_str()).collect();
+        assert_eq!(
+            sql,
+            vec![
+                "PRINT 'a';\n",
+                "UPDATE t SET category = 'GO';\nGOTO done;\n\
+                 SELECT 'multi\nGO\nline';\n/* outer /* inner */\nGO\n*/\n",
+                "INSERT t DEFAULT VALUES\n",
+            ]
+        );
+        let lines: Vec<(usize, u32)> = batches.iter().map(|b| (b.line, b.count)).collect();
+        assert_eq!(lines, vec![(1, 1), (4, 3), (13, 1)]);
+        assert!(split_batches("SELECT 1\nGO 0").is_err());
+        assert_eq!(split_batches("GO\n\nGO").unwrap(), vec![]);
+    }
+
+    #[test]
+    fn queue_drops_oldest_when_full() {
+        let progress = Progress::default();
+        for batch in 0..CAPACITY + 2 {
+            progress.push(Event::Batch {
+                batch,
+                rows_affected: 0,
+                duration_ms: 0,
+            });
+        }
+        let (events, dropped) = progress.drain();
+        assert_eq!(dropped, 2);
+        assert_eq!(events.len(), CAPACITY);
+        assert!(matches!(events[0], Event::Batch { batch: 2, .. }));
+        assert_eq!(progress.drain(), (vec![], 0));
+        let json = serde_json::to_value(&events[0]).unwrap();
+        assert_eq!(json["type"], "batch");
+        assert_eq!(json["rowsAffected"], 0);
+    }
+}
@@ -0,0 +1,145 @@
+//! Prometheus text exposition (format 0.0.4) of the driver's pool gauges,
+//! command counters and latency histograms.
+
+use std::fmt::Write;
+
+use crate::handle::{self, MetricsCounters, PoolSample, LATENCY_BUCKETS_S};
+
+/// Render every pool and standalone connection as Prometheus text.
+///
+/// Pools are labelled `pool="<id>"`; connections acquired from a pool count
+/// towards the pool. Standalone connections are labelled `connection="<id>"`.
+pub fn render() -> String {
+    let (pools, conns, open) = handle::metrics_samples();
+    format_samples(&pools, &conns, open)
+}
+
+fn format_samples(pools: &[PoolSample], conns: &[(u64, MetricsCounters)], open: usize) -> String {
+    let mut out = String::new();
+
+    family(
+        &mut out,
+        "mssqlts_connections_open",
+        "gauge",
+        "Open connection handles.",
+    );
+    let _ = writeln!(out, "mssqlts_connections_open {open}");
+
+    family(
+        &mut out,
+        "mssqlts_pool_connections",
+        "gauge",
+        "Pool connections by state.",
+    );
+    for p in pools {
+        let _ = writeln!(
+            out,
+            "mssqlts_pool_connections{{pool=\"{}\",state=\"idle\"}} {}",
+            p.id, p.idle
+        );
+        let _ = writeln!(
+            out,
+            "mssqlts_pool_connections{{pool=\"{}\",state=\"in_use\"}} {}",
+            p.id, p.in_use
+        );
+    }
+    family(
+        &mut out,
+        "mssqlts_pool_max_connections",
+        "gauge",
+        "Pool size limit.",
+    );
+    for p in pools {
+        let _ = writeln!(
+            out,
+            "mssqlts_pool_max_connections{{pool=\"{}\"}} {}",
+            p.id, p.max
+        );
+    }
+
+    let series: Vec<(String, &MetricsCounters)> = pools
+        .iter()
+        .map(|p| (format!("pool=\"{}\"", p.id), &p.metrics))
+        .chain(
+            conns
+                .iter()
+                .map(|(id, m)| (format!("connection=\"{id}\""), m)),
+        )
+        .collect();
+    let counters: [(&str, &str, fn(&MetricsCounters) -> u64); 4] = [
+        ("mssqlts_queries_total", "Commands run.", |m| m.queries),
+        ("mssqlts_query_errors_total", "Commands that failed.", |m| {
+            m.errors
+        }),
+        (
+            "mssqlts_rows_returned_total",
+            "Rows returned by queries and streams.",
+            |m| m.rows,
+        ),
+        (
+            "mssqlts_result_bytes_total",
+            "Bytes of result JSON serialized.",
+            |m| m.bytes,
+        ),
+    ];
+    for (name, help, value) in counters {
+        family(&mut out, name, "counter", help);
+        for (labels, m) in &series {
+            let _ = writeln!(out, "{name}{{{labels}}} {}", value(m));
+        }
+    }
+
+    let name = "mssqlts_query_duration_seconds";
+    family(&mut out, name, "histogram", "Command latency.");
+    for (labels, m) in &series {
+        for (bound, count) in LATENCY_BUCKETS_S.iter().zip(&m.buckets) {
+            let _ = writeln!(out, "{name}_bucket{{{labels},le=\"{bound}\"}} {count}");
+        }
+        let _ = writeln!(out, "{name}_bucket{{{labels},le=\"+Inf\"}} {}", m.queries);
+        let _ = writeln!(out, "{name}_sum{{{labels}}} {}", m.latency_sum_s);
+        let _ = writeln!(out, "{name}_count{{{labels}}} {}", m.queries);
+    }
+    out
+}
+
+fn family(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counters(queries: u64, buckets: Vec<u64>) -> MetricsCounters {
+        MetricsCounters {
+            queries,
+            errors: 1,
+            rows: 40,
+            bytes: 900,
+            buckets,
+            latency_sum_s: 0.25,
+        }
+    }
+
+    #[test]
+    fn formats_gauges_counters_and_histogram() {
+        let pools = [PoolSample {
+            id: 1,
+            idle: 3,
+            in_use: 2,
+            max: 10,
+            metrics: counters(5, vec![1, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4]),
+        }];
+        let conns = [(7, counters(2, vec![2; 12]))];
+        let text = format_samples(&pools, &conns, 3);
+        assert!(text.contains("# TYPE mssqlts_queries_total counter\n"));
+        assert!(text.contains("mssqlts_connections_open 3\n"));
+        assert!(text.contains("mssqlts_pool_connections{pool=\"1\",state=\"in_use\"} 2\n"));
+        assert!(text.contains("mssqlts_queries_total{pool=\"1\"} 5\n"));
+        assert!(text.contains("mssqlts_queries_total{connection=\"7\"} 2\n"));
+        assert!(text.contains("mssqlts_query_duration_seconds_bucket{pool=\"1\",le=\"0.005\"} 4\n"));
+        assert!(text.contains("mssqlts_query_duration_seconds_bucket{pool=\"1\",le=\"+Inf\"} 5\n"));
+        assert!(text.contains("mssqlts_query_duration_seconds_sum{connection=\"7\"} 0.25\n"));
+    }
+}
@@ -1,9 +1,21 @@
-use std::collections::HashMap;
-
-use mssql_client::{Client, Ready, Row, SqlValue, ToSql};
-use serde::Deserialize;
-
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use chrono::Timelike;
+use mssql_client::{Client, Column, Ready, Row, SqlValue, ToSql};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::{Deserialize, Deserializer};
+
+use crate::catalog;
+use crate::compress::Codec;
+use crate::config::{BigIntMode, ColumnCase, DecimalMode, TemporalMode, VarcharDecodePolicy};
 use crate::error::{MssqlError, Result};
+use crate::paginate::{self, Paginate};
+use crate::rowbuf::RowBuffer;
+use crate::stats::Statistics;
+use crate::variant::VariantValue;
 
 // ── Serialized command from TypeScript ─────────────────────────
 
@@ -24,16 +36,182 @@ pub struct SerializedCommand {
     #[serde(default)]
     #[allow(dead_code)]
     pub fetch_size: Option<u32>,
+    /// Non-query calls also return rows produced by an OUTPUT clause.
+    #[serde(default)]
+    pub return_rows: bool,
+    /// Per-command override of the connection's temporal serialization mode.
+    #[serde(default)]
+    pub temporal_mode: Option<TemporalMode>,
+    /// Per-command override of the connection's column name casing.
+    #[serde(default)]
+    pub column_case: Option<ColumnCase>,
+    /// Streams emit binary columns as `{"__type":"binary","length":n}` placeholders;
+    /// the bytes are copied out with `mssql_stream_read_binary`.
+    #[serde(default)]
+    pub raw_binary: bool,
+    /// Query calls return `{"rows":[...],"plans":[...]}` with showplan XML.
+    #[serde(default)]
+    pub capture_plan: Option<PlanCapture>,
+    /// Query calls return `{"rows":[...],"statistics":{...}}` from
+    /// `SET STATISTICS IO, TIME`.
+    #[serde(default)]
+    pub capture_statistics: bool,
+    /// Query calls return the document a `FOR JSON` query produced, put back
+    /// together from the rows it was split across.
+    #[serde(default)]
+    pub for_json: bool,
+    /// Query calls compress results over `compress_threshold` bytes; see
+    /// `compress::pack`.
+    #[serde(default)]
+    pub compress: Option<Codec>,
+    #[serde(default)]
+    pub compress_threshold: Option<u64>,
+    /// Query calls return one page as `{"rows":[...],"total":n,"hasMore":b}`.
+    #[serde(default)]
+    pub paginate: Option<Paginate>,
+    /// `SET NOCOUNT ON` / `OFF` for the command; the session's setting is
+    /// restored afterwards.
+    #[serde(default)]
+    pub nocount: Option<bool>,
+    /// How non-query and exec calls compute `rowsAffected`.
+    #[serde(default)]
+    pub rowcount: Option<RowCount>,
+    /// Non-query calls return the generated keys as `insertedIds`.
+    #[serde(default)]
+    pub return_identity: bool,
+    /// Column whose inserted values are returned, via an OUTPUT clause added
+    /// to the INSERT. Without it, `SCOPE_IDENTITY()` gives the last one.
+    #[serde(default)]
+    pub identity_key: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+/// How `rowsAffected` is computed for a multi-statement command.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RowCount {
+    /// Every DONE count the server sends, including triggers and nested
+    /// statements. Non-query calls only; zero under `SET NOCOUNT ON`.
+    Total,
+    /// `@@ROWCOUNT` after the command's last statement.
+    Last,
+}
+
+impl SerializedCommand {
+    /// The row count mode: `last` under NOCOUNT, which suppresses the DONE
+    /// counts `total` adds up, otherwise `total`.
+    fn rowcount(&self) -> RowCount {
+        match (self.rowcount, self.nocount) {
+            (Some(mode), _) => mode,
+            (None, Some(true)) => RowCount::Last,
+            (None, _) => RowCount::Total,
+        }
+    }
+
+    /// Reject `total` for calls that can only report `@@ROWCOUNT`.
+    fn require_last_rowcount(&self) -> Result<()> {
+        if self.rowcount == Some(RowCount::Total) {
+            return Err(MssqlError::Query(
+                "rowcount \"total\" is only supported by execute without returned rows".into(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Wrap `sql` in the command's NOCOUNT setting, putting the session's own
+/// setting back at the end. Statements that must start a batch (CREATE
+/// PROCEDURE and the like) can't be wrapped, so they are rejected.
+fn with_nocount(sql: String, nocount: Option<bool>) -> Result<String> {
+    let Some(on) = nocount else {
+        return Ok(sql);
+    };
+    if let Some(statement) = batch_leading_statement(&sql) {
+        return Err(MssqlError::Query(format!(
+            "nocount can't be used with {statement}, which must be the first statement \
+             in its batch; leave nocount unset and run SET NOCOUNT separately"
+        )));
+    }
+    let setting = if on { "ON" } else { "OFF" };
+    Ok(format!(
+        "DECLARE @__nocount INT = @@OPTIONS & 512;\nSET NOCOUNT {setting};\n{sql};\n\
+         IF @__nocount = 0 SET NOCOUNT OFF ELSE SET NOCOUNT ON;"
+    ))
+}
+
+/// Objects whose CREATE (or ALTER, for the first five) must start a batch.
+const BATCH_LEADING_OBJECTS: [&str; 8] = [
+    "PROCEDURE",
+    "PROC",
+    "VIEW",
+    "FUNCTION",
+    "TRIGGER",
+    "SCHEMA",
+    "DEFAULT",
+    "RULE",
+];
+
+/// The leading statement of `sql` if it must be the first in its batch,
+/// e.g. `CREATE PROCEDURE` or `CREATE OR ALTER VIEW`.
+fn batch_leading_statement(sql: &str) -> Option<String> {
+    let b = sql.as_bytes();
+    let start = skip_trivia(b, 0);
+    let (verb, objects, end) = if let Some(end) = keyword_at(b, start, "CREATE") {
+        let or_alter = keyword_at(b, skip_trivia(b, end), "OR")
+            .and_then(|or| keyword_at(b, skip_trivia(b, or), "ALTER"));
+        match or_alter {
+            Some(alter) => ("CREATE OR ALTER", &BATCH_LEADING_OBJECTS[..5], alter),
+            None => ("CREATE", &BATCH_LEADING_OBJECTS[..], end),
+        }
+    } else {
+        let end = keyword_at(b, start, "ALTER")?;
+        ("ALTER", &BATCH_LEADING_OBJECTS[..5], end)
+    };
+    let object_at = skip_trivia(b, end);
+    let object = objects
+        .iter()
+        .find(|object| keyword_at(b, object_at, object).is_some())?;
+    Some(format!("{verb} {object}"))
+}
+
+/// Which execution plan a query captures.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PlanCapture {
+    /// `SET SHOWPLAN_XML ON`: compile only, the query is not run.
+    Estimated,
+    /// `SET STATISTICS XML ON`: run the query and return the runtime plan.
+    Actual,
+}
+
+#[derive(Debug, Clone, Deserialize)]
 pub struct SerializedParam {
     pub name: String,
     pub value: serde_json::Value,
-    #[serde(rename = "type")]
+    /// Type hint, lowercased as it's read so every match below can compare
+    /// against lowercase names.
+    #[serde(rename = "type", default, deserialize_with = "lowercase_type")]
     pub param_type: Option<String>,
     #[serde(default)]
     pub output: bool,
+    /// Total digits for decimal params (validated before sending).
+    #[serde(default)]
+    pub precision: Option<u8>,
+    /// Fractional digits for decimal params (value is rounded to this scale).
+    #[serde(default)]
+    pub scale: Option<u8>,
+    /// Spatial reference ID for geometry/geography params given as WKT.
+    #[serde(default)]
+    pub srid: Option<i32>,
+    /// Declared length for string/binary params (-1 for MAX); longer values
+    /// are rejected.
+    #[serde(default)]
+    pub length: Option<i32>,
+}
+
+fn lowercase_type<'de, D: Deserializer<'de>>(
+    d: D,
+) -> std::result::Result<Option<String>, D::Error> {
+    Ok(Option::<String>::deserialize(d)?.map(|t| t.to_lowercase()))
 }
 
 // ── Named param rewriting (@name → @P1) ──────────────────────
@@ -42,6 +220,86 @@ fn is_sql_ident_char(c: char) -> bool {
     c.is_alphanumeric() || c == '_'
 }
 
+/// Placeholder SQL for a param. Spatial params are sent as WKT text and
+/// converted server-side, defaulting to SRID 0 (geometry) or 4326
+/// (geography). Other params are plain `@Pn`; their types are declared by
+/// [`bind_params`].
+fn param_placeholder(param: &SerializedParam, pos: usize) -> String {
+    match param.param_type.as_deref() {
+        Some("geometry") => format!("geometry::STGeomFromText(@P{pos}, {})", param.srid.unwrap_or(0)),
+        Some("geography") => {
+            format!("geography::STGeomFromText(@P{pos}, {})", param.srid.unwrap_or(4326))
+        }
+        _ => format!("@P{pos}"),
+    }
+}
+
+/// The sized SQL type for a param with a length or precision hint, e.g.
+/// `NVARCHAR(50)` or `DECIMAL(18, 4)`. None when there is no hint for its type.
+fn sized_type(param: &SerializedParam) -> Option<String> {
+    let sql_type = param.param_type.as_deref()?;
+    let name = sql_type.to_uppercase();
+    match sql_type {
+        "varchar" | "nvarchar" | "char" | "nchar" | "varbinary" => match param.length? {
+            len if len < 0 => Some(format!("{name}(MAX)")),
+            len => Some(format!("{name}({len})")),
+        },
+        "decimal" | "numeric" => {
+            let precision = param.precision?;
+            let scale = param.scale.unwrap_or(0);
+            Some(format!("{name}({precision}, {scale})"))
+        }
+        _ => None,
+    }
+}
+
+/// The type to declare a param as in the statement's parameter list: its
+/// sized type, XML (so methods like `@doc.value(...)` work on it), or a
+/// vector of the array's dimension. None leaves it as the type it's sent as.
+fn declared_type(param: &SerializedParam) -> Option<String> {
+    match param.param_type.as_deref() {
+        Some("xml") => Some("XML".into()),
+        // Float arrays are sent as JSON text; the dimension comes from the array
+        Some("vector") => match &param.value {
+            serde_json::Value::Array(items) => Some(format!("VECTOR({})", items.len())),
+            _ => None,
+        },
+        _ => sized_type(param),
+    }
+}
+
+/// The type the driver sends a bound value as: strings, and the decimals
+/// and temporals it encodes as text, go as NVARCHAR.
+fn sent_type(value: &SqlValue) -> String {
+    match value {
+        SqlValue::Bool(_) => "BIT".into(),
+        SqlValue::TinyInt(_) => "TINYINT".into(),
+        SqlValue::SmallInt(_) => "SMALLINT".into(),
+        SqlValue::Int(_) => "INT".into(),
+        SqlValue::BigInt(_) => "BIGINT".into(),
+        SqlValue::Float(_) => "REAL".into(),
+        SqlValue::Double(_) => "FLOAT".into(),
+        SqlValue::Uuid(_) => "UNIQUEIDENTIFIER".into(),
+        SqlValue::Binary(b) if b.len() <= 8000 => format!("VARBINARY({})", b.len().max(1)),
+        SqlValue::Binary(_) => "VARBINARY(MAX)".into(),
+        SqlValue::Null => "NVARCHAR(1)".into(),
+        SqlValue::String(s) if s.encode_utf16().count() <= 4000 => "NVARCHAR(4000)".into(),
+        _ => "NVARCHAR(MAX)".into(),
+    }
+}
+
+/// Reject a value longer than the param's declared length, which the
+/// declared type would otherwise truncate silently.
+fn check_length(param: &SerializedParam, len: usize) -> Result<()> {
+    match param.length {
+        Some(max) if max >= 0 && len > max as usize => Err(MssqlError::Query(format!(
+            "Value for @{} has length {len}, over its declared length {max}",
+            param.name
+        ))),
+        _ => Ok(()),
+    }
+}
+
 /// Rewrite named @param placeholders to positional @P1, @P2, ... markers.
 /// Returns the rewritten SQL and the reordered parameter indices.
 pub fn rewrite_named_params(
@@ -111,7 +369,7 @@ pub fn rewrite_named_params(
                 let name: String = chars[start..end].iter().collect();
                 if let Some(&idx) = name_to_idx.get(&name.to_lowercase()) {
                     pos += 1;
-                    result.push_str(&format!("@P{pos}"));
+                    result.push_str(&param_placeholder(&params[idx], pos));
                     order.push(idx);
                     i = end;
                     continue;
@@ -133,6 +391,9 @@ pub fn param_to_boxed(param: &SerializedParam) -> Result<Box<dyn ToSql + Sync>>
     match &param.value {
         serde_json::Value::Null => Ok(Box::new(Option::<String>::None)),
         serde_json::Value::Bool(b) => Ok(Box::new(*b)),
+        serde_json::Value::Number(n) if is_decimal_type(param) => {
+            Ok(Box::new(parse_decimal(&n.to_string(), param)?))
+        }
         serde_json::Value::Number(n) => {
             if let Some(i) = n.as_i64() {
                 match param.param_type.as_deref() {
@@ -140,6 +401,7 @@ pub fn param_to_boxed(param: &SerializedParam) -> Result<Box<dyn ToSql + Sync>>
                     Some("smallint") => Ok(Box::new(i as i16)),
                     Some("int") => Ok(Box::new(i as i32)),
                     Some("bigint") => Ok(Box::new(i)),
+                    Some("rowversion" | "timestamp") => Ok(Box::new((i as u64).to_be_bytes().to_vec())),
                     Some("float") | Some("real") => Ok(Box::new(i as f64)),
                     _ => {
                         if (i32::MIN as i64..=i32::MAX as i64).contains(&i) {
@@ -163,6 +425,9 @@ pub fn param_to_boxed(param: &SerializedParam) -> Result<Box<dyn ToSql + Sync>>
                         .map_err(|e| MssqlError::Query(format!("Invalid UUID: {e}")))?;
                     Ok(Box::new(uuid))
                 }
+                Some("decimal" | "numeric" | "money" | "smallmoney") => {
+                    Ok(Box::new(parse_decimal(s, param)?))
+                }
                 Some("date") => {
                     let d: chrono::NaiveDate = s
                         .parse()
@@ -173,27 +438,34 @@ pub fn param_to_boxed(param: &SerializedParam) -> Result<Box<dyn ToSql + Sync>>
                     let t: chrono::NaiveTime = s
                         .parse()
                         .map_err(|e| MssqlError::Query(format!("Invalid time: {e}")))?;
-                    Ok(Box::new(t))
+                    Ok(Box::new(truncate_to_ticks(t)))
                 }
                 Some("datetime" | "datetime2") => {
                     let dt = parse_datetime(s)?;
-                    Ok(Box::new(dt))
+                    Ok(Box::new(dt.date().and_time(truncate_to_ticks(dt.time()))))
                 }
                 Some("datetimeoffset") => {
                     let dt: chrono::DateTime<chrono::FixedOffset> = s
                         .parse()
                         .map_err(|e| MssqlError::Query(format!("Invalid datetimeoffset: {e}")))?;
+                    let dt = dt.with_nanosecond(ticks_nanos(dt.nanosecond())).unwrap_or(dt);
                     Ok(Box::new(dt))
                 }
-                Some("varbinary") => {
+                Some("varbinary" | "udt") => {
                     let bytes = base64::Engine::decode(
                         &base64::engine::general_purpose::STANDARD,
                         s,
                     )
                     .map_err(|e| MssqlError::Query(format!("Invalid base64: {e}")))?;
+                    check_length(param, bytes.len())?;
                     Ok(Box::new(bytes))
                 }
-                _ => Ok(Box::new(s.clone())),
+                Some("rowversion" | "timestamp") => Ok(Box::new(parse_rowversion(s)?)),
+                Some("xml") => Ok(Box::new(strip_xml_encoding(s))),
+                _ => {
+                    check_length(param, s.encode_utf16().count())?;
+                    Ok(Box::new(s.clone()))
+                }
             }
         }
         serde_json::Value::Array(_) | serde_json::Value::Object(_) => {
@@ -202,79 +474,136 @@ pub fn param_to_boxed(param: &SerializedParam) -> Result<Box<dyn ToSql + Sync>>
     }
 }
 
-/// Convert a SerializedParam to an SqlValue for literal embedding
-/// (used in OUTPUT param batches where we can't use parameterized queries).
-pub fn param_to_sql_value(param: &SerializedParam) -> Result<SqlValue> {
-    match &param.value {
-        serde_json::Value::Null => Ok(SqlValue::Null),
-        serde_json::Value::Bool(b) => Ok(SqlValue::Bool(*b)),
-        serde_json::Value::Number(n) => {
-            if let Some(i) = n.as_i64() {
-                match param.param_type.as_deref() {
-                    Some("tinyint") => Ok(SqlValue::TinyInt(i as u8)),
-                    Some("smallint") => Ok(SqlValue::SmallInt(i as i16)),
-                    Some("int") => Ok(SqlValue::Int(i as i32)),
-                    Some("bigint") => Ok(SqlValue::BigInt(i)),
-                    Some("float") | Some("real") => Ok(SqlValue::Double(i as f64)),
-                    _ => {
-                        if (i32::MIN as i64..=i32::MAX as i64).contains(&i) {
-                            Ok(SqlValue::Int(i as i32))
-                        } else {
-                            Ok(SqlValue::BigInt(i))
-                        }
-                    }
-                }
-            } else if let Some(f) = n.as_f64() {
-                Ok(SqlValue::Double(f))
-            } else {
-                Err(MssqlError::Query(format!("Unsupported number: {n}")))
-            }
-        }
-        serde_json::Value::String(s) => {
-            match param.param_type.as_deref() {
-                Some("uniqueidentifier") => {
-                    let uuid: uuid::Uuid = s
-                        .parse()
-                        .map_err(|e| MssqlError::Query(format!("Invalid UUID: {e}")))?;
-                    Ok(SqlValue::Uuid(uuid))
-                }
-                Some("date") => {
-                    let d: chrono::NaiveDate = s
-                        .parse()
-                        .map_err(|e| MssqlError::Query(format!("Invalid date: {e}")))?;
-                    Ok(SqlValue::Date(d))
-                }
-                Some("time") => {
-                    let t: chrono::NaiveTime = s
-                        .parse()
-                        .map_err(|e| MssqlError::Query(format!("Invalid time: {e}")))?;
-                    Ok(SqlValue::Time(t))
-                }
-                Some("datetime" | "datetime2") => {
-                    let dt = parse_datetime(s)?;
-                    Ok(SqlValue::DateTime(dt))
-                }
-                Some("datetimeoffset") => {
-                    let dt: chrono::DateTime<chrono::FixedOffset> = s
-                        .parse()
-                        .map_err(|e| MssqlError::Query(format!("Invalid datetimeoffset: {e}")))?;
-                    Ok(SqlValue::DateTimeOffset(dt))
-                }
-                Some("varbinary") => {
-                    let bytes = base64::Engine::decode(
-                        &base64::engine::general_purpose::STANDARD,
-                        s,
-                    )
-                    .map_err(|e| MssqlError::Query(format!("Invalid base64: {e}")))?;
-                    Ok(SqlValue::Binary(bytes.into()))
-                }
-                _ => Ok(SqlValue::String(s.clone())),
-            }
+/// Drop the `encoding` attribute from a leading XML declaration. The text is
+/// sent as UTF-16, so a declared `encoding="utf-8"` would make SQL Server reject
+/// the conversion ("unable to switch the encoding").
+fn strip_xml_encoding(s: &str) -> String {
+    let body = s.trim_start();
+    let Some(decl_end) = body.starts_with("<?xml").then(|| body.find("?>")).flatten() else {
+        return s.to_string();
+    };
+    let decl = &body[..decl_end];
+    let Some(attr) = decl.find(" encoding=") else {
+        return s.to_string();
+    };
+    let value_start = attr + " encoding=".len();
+    let Some(quote) = decl[value_start..].chars().next().filter(|c| *c == '"' || *c == '\'') else {
+        return s.to_string();
+    };
+    let Some(value_len) = decl[value_start + 1..].find(quote) else {
+        return s.to_string();
+    };
+    let attr_end = value_start + 1 + value_len + 1;
+    format!("{}{}{}", &body[..attr], &decl[attr_end..], &body[decl_end..])
+}
+
+/// Parse a rowversion written as 16 hex digits, with or without a `0x` prefix.
+fn parse_rowversion(s: &str) -> Result<Vec<u8>> {
+    let hex = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")).unwrap_or(s);
+    if hex.len() != 16 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(MssqlError::Query(format!("Invalid rowversion: {s}")));
+    }
+    (0..16)
+        .step_by(2)
+        .map(|i| {
+            u8::from_str_radix(&hex[i..i + 2], 16)
+                .map_err(|e| MssqlError::Query(format!("Invalid rowversion: {e}")))
+        })
+        .collect()
+}
+
+/// Format an 8-byte rowversion as `0x` followed by 16 uppercase hex digits.
+fn rowversion_to_json(type_name: &str, bytes: &[u8]) -> Option<serde_json::Value> {
+    let is_rowversion =
+        type_name.eq_ignore_ascii_case("timestamp") || type_name.eq_ignore_ascii_case("rowversion");
+    if !is_rowversion || bytes.len() != 8 {
+        return None;
+    }
+    let hex: String = bytes.iter().map(|b| format!("{b:02X}")).collect();
+    Some(serde_json::Value::String(format!("0x{hex}")))
+}
+
+fn is_decimal_type(param: &SerializedParam) -> bool {
+    matches!(
+        param.param_type.as_deref(),
+        Some("decimal" | "numeric" | "money" | "smallmoney")
+    )
+}
+
+/// Precision and scale to bind with: the param's hints, or the fixed sizes
+/// of MONEY (19, 4) and SMALLMONEY (10, 4).
+fn decimal_hints(param: &SerializedParam) -> Result<(Option<u8>, Option<u8>)> {
+    let (precision, scale) = match param.param_type.as_deref() {
+        Some("money") => (Some(19), Some(4)),
+        Some("smallmoney") => (Some(10), Some(4)),
+        _ => (param.precision, param.scale),
+    };
+    if let Some(p) = precision {
+        if !(1..=38).contains(&p) || scale.is_some_and(|s| s > p) {
+            return Err(MssqlError::Query(format!(
+                "Invalid decimal size ({p}, {}) for @{}",
+                scale.unwrap_or(0),
+                param.name
+            )));
         }
-        serde_json::Value::Array(_) | serde_json::Value::Object(_) => {
-            Ok(SqlValue::String(serde_json::to_string(&param.value).unwrap()))
+    }
+    Ok((precision, scale))
+}
+
+/// Parse an exact decimal, applying the param's scale and precision hints.
+/// Rounds half away from zero, matching SQL Server's conversion behavior.
+fn parse_decimal(s: &str, param: &SerializedParam) -> Result<Decimal> {
+    let (precision, scale) = decimal_hints(param)?;
+    let mut d = Decimal::from_str(s.trim())
+        .or_else(|_| Decimal::from_scientific(s.trim()))
+        .map_err(|e| MssqlError::Query(format!("Invalid decimal for @{}: {e}", param.name)))?;
+    if let Some(scale) = scale {
+        d = d.round_dp_with_strategy(scale as u32, RoundingStrategy::MidpointAwayFromZero);
+    }
+    if let Some(precision) = precision {
+        let scale = scale.unwrap_or(0);
+        let int_digits = d.trunc().abs().to_string().trim_start_matches('0').len();
+        if int_digits + scale as usize > precision as usize {
+            return Err(MssqlError::Query(format!(
+                "Decimal {s} for @{} exceeds precision ({precision}, {scale})",
+                param.name
+            )));
         }
     }
+    Ok(d)
+}
+
+// ── Temporal precision (100ns ticks) ──────────────────────────
+
+/// SQL Server's finest temporal resolution (datetime2(7), time(7)) is 100ns.
+const NANOS_PER_TICK: u32 = 100;
+
+/// Round nanoseconds down to a whole 100ns tick, clamping leap-second values.
+fn ticks_nanos(nanos: u32) -> u32 {
+    nanos.min(999_999_999) / NANOS_PER_TICK * NANOS_PER_TICK
+}
+
+fn truncate_to_ticks(t: chrono::NaiveTime) -> chrono::NaiveTime {
+    t.with_nanosecond(ticks_nanos(t.nanosecond())).unwrap_or(t)
+}
+
+/// Format a time as `HH:MM:SS.fffffff` — always 7 fractional digits.
+fn format_time(t: &chrono::NaiveTime) -> String {
+    format!(
+        "{}.{:07}",
+        t.format("%H:%M:%S"),
+        ticks_nanos(t.nanosecond()) / NANOS_PER_TICK
+    )
+}
+
+/// Format a datetime as strict ISO 8601 `YYYY-MM-DDTHH:MM:SS.fffffff`.
+fn format_datetime(dt: &chrono::NaiveDateTime) -> String {
+    format!("{}T{}", dt.format("%Y-%m-%d"), format_time(&dt.time()))
+}
+
+/// Format a datetimeoffset as ISO 8601 with a `±HH:MM` offset.
+fn format_datetimeoffset(dt: &chrono::DateTime<chrono::FixedOffset>) -> String {
+    format!("{}{}", format_datetime(&dt.naive_local()), dt.format("%:z"))
 }
 
 fn parse_datetime(s: &str) -> Result<chrono::NaiveDateTime> {
@@ -298,43 +627,12 @@ fn parse_datetime(s: &str) -> Result<chrono::NaiveDateTime> {
     Err(MssqlError::Query(format!("Invalid datetime: {s}")))
 }
 
-// ── SQL literal conversion (for OUTPUT param batches) ─────────
+// ── OUTPUT param declarations ─────────────────────────────────
 
-/// Convert an SqlValue to a SQL literal string for embedding in
-/// simple_query batches (OUTPUT params, etc.).
-pub fn sql_value_to_literal(val: &SqlValue) -> String {
-    match val {
-        SqlValue::Null => "NULL".to_string(),
-        SqlValue::Bool(b) => if *b { "1" } else { "0" }.to_string(),
-        SqlValue::TinyInt(n) => n.to_string(),
-        SqlValue::SmallInt(n) => n.to_string(),
-        SqlValue::Int(n) => n.to_string(),
-        SqlValue::BigInt(n) => n.to_string(),
-        SqlValue::Float(n) => {
-            if n.is_nan() || n.is_infinite() { "NULL".to_string() }
-            else { n.to_string() }
-        }
-        SqlValue::Double(n) => {
-            if n.is_nan() || n.is_infinite() { "NULL".to_string() }
-            else { n.to_string() }
-        }
-        SqlValue::String(s) => format!("N'{}'", s.replace('\'', "''")),
-        SqlValue::Binary(bytes) => {
-            let hex: String = bytes.iter().map(|b| format!("{b:02X}")).collect();
-            format!("0x{hex}")
-        }
-        SqlValue::Uuid(u) => format!("'{u}'"),
-        SqlValue::Date(d) => format!("'{d}'"),
-        SqlValue::Time(t) => format!("'{t}'"),
-        SqlValue::DateTime(dt) => format!("'{dt}'"),
-        SqlValue::DateTimeOffset(dt) => format!("'{dt}'"),
-        _ => "NULL".to_string(),
-    }
-}
-
-/// Map a type hint string to a SQL Server DECLARE type.
+/// Map a type hint (lowercase, as [`SerializedParam`] holds it) to a SQL Server
+/// DECLARE type.
 pub fn sql_type_for_declare(type_hint: &str) -> Result<&'static str> {
-    match type_hint.to_lowercase().as_str() {
+    match type_hint {
         "int" => Ok("INT"),
         "bigint" => Ok("BIGINT"),
         "smallint" => Ok("SMALLINT"),
@@ -342,6 +640,9 @@ pub fn sql_type_for_declare(type_hint: &str) -> Result<&'static str> {
         "float" => Ok("FLOAT"),
         "real" => Ok("REAL"),
         "decimal" => Ok("DECIMAL(38, 18)"),
+        "numeric" => Ok("NUMERIC(38, 18)"),
+        "money" => Ok("MONEY"),
+        "smallmoney" => Ok("SMALLMONEY"),
         "bit" => Ok("BIT"),
         "varchar" => Ok("VARCHAR(MAX)"),
         "nvarchar" => Ok("NVARCHAR(MAX)"),
@@ -355,65 +656,433 @@ pub fn sql_type_for_declare(type_hint: &str) -> Result<&'static str> {
         "datetimeoffset" => Ok("DATETIMEOFFSET"),
         "time" => Ok("TIME"),
         "uniqueidentifier" => Ok("UNIQUEIDENTIFIER"),
-        "varbinary" => Ok("VARBINARY(MAX)"),
+        "varbinary" | "udt" => Ok("VARBINARY(MAX)"),
         "xml" => Ok("XML"),
         "json" => Ok("NVARCHAR(MAX)"),
+        "geometry" => Ok("GEOMETRY"),
+        "geography" => Ok("GEOGRAPHY"),
+        "rowversion" | "timestamp" => Ok("BINARY(8)"),
         other => Err(MssqlError::Query(format!("Unknown SQL type: {other}"))),
     }
 }
 
 // ── Row to JSON conversion ────────────────────────────────────
 
-/// Convert a Row from mssql-client to a JSON object.
-pub fn row_to_json(row: &Row) -> serde_json::Value {
+/// Per-handle options controlling how row values are serialized to JSON.
+#[derive(Debug, Clone, Default)]
+pub struct SerializeOptions {
+    pub decimal_mode: DecimalMode,
+    pub temporal_mode: TemporalMode,
+    pub bigint_mode: BigIntMode,
+    pub parse_json: bool,
+    pub column_case: ColumnCase,
+    /// Fallback for varchar bytes that aren't UTF-8 (from `varchar_encoding`).
+    pub varchar_encoding: Option<&'static encoding_rs::Encoding>,
+    pub varchar_decode: VarcharDecodePolicy,
+    pub raw_binary: bool,
+    /// Column encryption keys, set via `mssql_ae_configure`.
+    pub encryption: Option<std::sync::Arc<crate::ae::Keyring>>,
+}
+
+impl SerializeOptions {
+    /// Apply any per-command overrides on top of the handle's options.
+    pub fn for_command(&self, cmd: &SerializedCommand) -> SerializeOptions {
+        SerializeOptions {
+            temporal_mode: cmd.temporal_mode.unwrap_or(self.temporal_mode),
+            column_case: cmd.column_case.unwrap_or(self.column_case),
+            raw_binary: cmd.raw_binary,
+            ..self.clone()
+        }
+    }
+}
+
+fn decimal_to_json(d: Decimal, opts: &SerializeOptions) -> serde_json::Value {
+    match opts.decimal_mode {
+        DecimalMode::String => serde_json::Value::String(d.to_string()),
+        DecimalMode::Number => d
+            .to_f64()
+            .and_then(serde_json::Number::from_f64)
+            .map(serde_json::Value::Number)
+            .unwrap_or_else(|| serde_json::Value::String(d.to_string())),
+    }
+}
+
+fn bigint_to_json(n: i64, opts: &SerializeOptions) -> serde_json::Value {
+    match opts.bigint_mode {
+        // JavaScript safe integer range
+        BigIntMode::NumberOrString if (-(1i64 << 53)..=(1i64 << 53)).contains(&n) => {
+            serde_json::json!(n)
+        }
+        BigIntMode::NumberOrString | BigIntMode::AlwaysString => {
+            serde_json::Value::String(n.to_string())
+        }
+        BigIntMode::TaggedBigInt => {
+            serde_json::json!({ "__type": "bigint", "value": n.to_string() })
+        }
+    }
+}
+
+/// Emit a temporal value per the temporal mode. `epoch_ms` is None for TIME,
+/// which has no date and always stays an ISO string in epoch mode.
+fn temporal_to_json(
+    sql_type: &str,
+    iso: String,
+    epoch_ms: Option<i64>,
+    opts: &SerializeOptions,
+) -> serde_json::Value {
+    match (opts.temporal_mode, epoch_ms) {
+        (TemporalMode::Epoch, Some(ms)) => serde_json::json!(ms),
+        (TemporalMode::Tagged, _) => serde_json::json!({ "__type": sql_type, "value": iso }),
+        _ => serde_json::Value::String(iso),
+    }
+}
+
+/// Tag for a date-and-time column: the driver decodes datetime, smalldatetime
+/// and datetime2 to the same value, so the column's declared type tells them
+/// apart. Columns without a known type (sql_variant values, output params)
+/// are datetime2.
+fn datetime_type(type_name: &str) -> &'static str {
+    match type_name.to_lowercase().as_str() {
+        "datetime" | "datetimen" => "datetime",
+        "smalldatetime" | "datetime4" => "smalldatetime",
+        _ => "datetime2",
+    }
+}
+
+/// Decode a vector column (binary or JSON text form) to an array of numbers.
+fn vector_to_json(value: &SqlValue) -> Option<serde_json::Value> {
+    let values = match value {
+        SqlValue::Binary(bytes) => crate::vector::decode(bytes)?,
+        SqlValue::String(s) => crate::vector::parse_text(s)?,
+        _ => return None,
+    };
+    Some(crate::vector::to_json(&values))
+}
+
+/// Decode a geometry/geography column to `{"__type", "srid", "wkt"}`.
+/// Returns None (falling back to the binary path) for shapes the decoder can't read.
+fn spatial_to_json(type_name: &str, bytes: &[u8]) -> Option<serde_json::Value> {
+    let kind = type_name.to_lowercase();
+    let kind = if kind.contains("geography") {
+        "geography"
+    } else if kind.contains("geometry") {
+        "geometry"
+    } else {
+        return None;
+    };
+    let spatial = crate::spatial::decode(bytes, kind == "geography")?;
+    Some(serde_json::json!({ "__type": kind, "srid": spatial.srid, "wkt": spatial.wkt }))
+}
+
+fn binary_to_json(bytes: &[u8], opts: &SerializeOptions) -> serde_json::Value {
+    if opts.raw_binary {
+        serde_json::json!({ "__type": "binary", "length": bytes.len() })
+    } else {
+        serde_json::Value::String(
+            base64::Engine::encode(&base64::engine::general_purpose::STANDARD, bytes),
+        )
+    }
+}
+
+/// Column types that hold CLR user-defined type values: the driver's UDT
+/// type, and the system CLR types reported by name. Anything else binary is
+/// plain binary.
+const UDT_TYPES: [&str; 4] = ["udt", "hierarchyid", "geometry", "geography"];
+
+/// Tag a CLR user-defined type value (hierarchyid, custom UDTs, undecodable
+/// spatial shapes) as `{"__type":"udt","type":<udt name>,"value":<base64>}`
+/// so it can be sent back unchanged as a `udt` param.
+fn udt_to_json(type_name: &str, bytes: &[u8]) -> Option<serde_json::Value> {
+    let lower = type_name.to_lowercase();
+    let base = lower.split('(').next().unwrap_or("").trim();
+    if !UDT_TYPES.contains(&base) {
+        return None;
+    }
+    let value = base64::Engine::encode(&base64::engine::general_purpose::STANDARD, bytes);
+    Some(serde_json::json!({ "__type": "udt", "type": type_name, "value": value }))
+}
+
+/// SQL type name for a value the driver has already decoded.
+fn sql_value_type(value: &SqlValue) -> &'static str {
+    match value {
+        SqlValue::Bool(_) => "bit",
+        SqlValue::TinyInt(_) => "tinyint",
+        SqlValue::SmallInt(_) => "smallint",
+        SqlValue::Int(_) => "int",
+        SqlValue::BigInt(_) => "bigint",
+        SqlValue::Float(_) => "real",
+        SqlValue::Double(_) => "float",
+        SqlValue::Decimal(_) => "decimal",
+        SqlValue::String(_) => "nvarchar",
+        SqlValue::Binary(_) => "varbinary",
+        SqlValue::Uuid(_) => "uniqueidentifier",
+        SqlValue::Date(_) => "date",
+        SqlValue::Time(_) => "time",
+        SqlValue::DateTime(_) => "datetime2",
+        SqlValue::DateTimeOffset(_) => "datetimeoffset",
+        SqlValue::Xml(_) => "xml",
+        _ => "unknown",
+    }
+}
+
+/// Convert a decoded sql_variant (or decrypted Always Encrypted) base value.
+fn variant_value_to_json(value: VariantValue, opts: &SerializeOptions) -> Result<serde_json::Value> {
+    match value {
+        VariantValue::Sql(v) => value_to_json(Some(v), "", opts),
+        VariantValue::Binary(b) => Ok(binary_to_json(&b, opts)),
+        VariantValue::Varchar(b) => Ok(serde_json::Value::String(crate::text::decode_varchar(&b, opts)?)),
+    }
+}
+
+/// Tag a sql_variant value as `{"__type":"sql_variant","type":<base type>,"value":...}`.
+/// Raw payloads are decoded here; values the driver already unwrapped are
+/// tagged with the type of their SqlValue.
+fn variant_to_json(value: SqlValue, opts: &SerializeOptions) -> Result<serde_json::Value> {
+    let (base_type, inner) = match value {
+        SqlValue::Binary(bytes) => match crate::variant::decode(&bytes) {
+            Some((name, v)) => (name, variant_value_to_json(v, opts)?),
+            None => return Ok(binary_to_json(&bytes, opts)),
+        },
+        other => (sql_value_type(&other), value_to_json(Some(other), "", opts)?),
+    };
+    Ok(serde_json::json!({ "__type": "sql_variant", "type": base_type, "value": inner }))
+}
+
+/// Whether a column type holds single-byte character data.
+fn is_varchar_type(type_name: &str) -> bool {
+    let lower = type_name.to_lowercase();
+    ["varchar", "char", "text", "bigvarchar", "bigchar"]
+        .iter()
+        .any(|t| lower == *t || lower.starts_with(&format!("{t}(")))
+}
+
+/// Convert one column value to JSON. `type_name` is the column's declared type,
+/// used to pick out spatial, sql_variant and other specially-encoded columns.
+/// Fails only for undecodable varchar bytes under the `error` decode policy.
+fn value_to_json(
+    value: Option<SqlValue>,
+    type_name: &str,
+    opts: &SerializeOptions,
+) -> Result<serde_json::Value> {
+    Ok(match value {
+        None | Some(SqlValue::Null) => serde_json::Value::Null,
+        Some(v) if type_name.eq_ignore_ascii_case("sql_variant") => variant_to_json(v, opts)?,
+        Some(v) if type_name.to_lowercase().starts_with("vector") => match vector_to_json(&v) {
+            Some(json) => json,
+            None => value_to_json(Some(v), "", opts)?,
+        },
+        // Varchar in a code page the driver couldn't decode arrives as raw bytes
+        Some(SqlValue::Binary(bytes)) if is_varchar_type(type_name) => {
+            serde_json::Value::String(crate::text::decode_varchar(&bytes, opts)?)
+        }
+        Some(SqlValue::Bool(b)) => serde_json::Value::Bool(b),
+        Some(SqlValue::TinyInt(n)) => serde_json::json!(n),
+        Some(SqlValue::SmallInt(n)) => serde_json::json!(n),
+        Some(SqlValue::Int(n)) => serde_json::json!(n),
+        Some(SqlValue::BigInt(n)) => bigint_to_json(n, opts),
+        Some(SqlValue::Float(n)) => serde_json::json!(n),
+        Some(SqlValue::Double(n)) => serde_json::json!(n),
+        Some(SqlValue::Decimal(d)) => decimal_to_json(d, opts),
+        Some(SqlValue::String(s)) if opts.parse_json && type_name.eq_ignore_ascii_case("json") => {
+            serde_json::from_str(&s).unwrap_or(serde_json::Value::String(s))
+        }
+        Some(SqlValue::String(s)) => serde_json::Value::String(s),
+        Some(SqlValue::Binary(bytes)) => rowversion_to_json(type_name, &bytes)
+            .or_else(|| spatial_to_json(type_name, &bytes))
+            .or_else(|| udt_to_json(type_name, &bytes))
+            .unwrap_or_else(|| binary_to_json(&bytes, opts)),
+        Some(SqlValue::Uuid(u)) => serde_json::Value::String(u.to_string()),
+        Some(SqlValue::Date(d)) => temporal_to_json(
+            "date",
+            d.to_string(),
+            Some(d.and_time(chrono::NaiveTime::MIN).and_utc().timestamp_millis()),
+            opts,
+        ),
+        Some(SqlValue::Time(t)) => temporal_to_json("time", format_time(&t), None, opts),
+        Some(SqlValue::DateTime(dt)) => temporal_to_json(
+            datetime_type(type_name),
+            format_datetime(&dt),
+            Some(dt.and_utc().timestamp_millis()),
+            opts,
+        ),
+        Some(SqlValue::DateTimeOffset(dt)) => temporal_to_json(
+            "datetimeoffset",
+            format_datetimeoffset(&dt),
+            Some(dt.timestamp_millis()),
+            opts,
+        ),
+        Some(SqlValue::Xml(s)) => serde_json::Value::String(s),
+        Some(other) => serde_json::Value::String(format!("{other:?}")),
+    })
+}
+
+/// Convert a Row from mssql-client to a JSON object. `keys` are the row keys
+/// from [`column_keys`], worked out once for the row's result set.
+pub fn row_to_json(
+    row: &Row,
+    keys: &[String],
+    opts: &SerializeOptions,
+) -> Result<serde_json::Value> {
     let mut map = serde_json::Map::new();
-    for col in row.columns() {
-        let value = match row.get_raw(col.index) {
-            None | Some(SqlValue::Null) => serde_json::Value::Null,
-            Some(SqlValue::Bool(b)) => serde_json::Value::Bool(b),
-            Some(SqlValue::TinyInt(n)) => serde_json::json!(n),
-            Some(SqlValue::SmallInt(n)) => serde_json::json!(n),
-            Some(SqlValue::Int(n)) => serde_json::json!(n),
-            Some(SqlValue::BigInt(n)) => {
-                // JavaScript safe integer range
-                if (-(1i64 << 53)..=(1i64 << 53)).contains(&n) {
-                    serde_json::json!(n)
-                } else {
-                    serde_json::Value::String(n.to_string())
-                }
+    for (col, key) in row.columns().iter().zip(keys) {
+        let key = key.clone();
+        let raw = row.get_raw(col.index);
+        // Encrypted columns arrive as varbinary ciphertext
+        if let (Some(keyring), Some(SqlValue::Binary(cell))) = (&opts.encryption, &raw) {
+            if let Some((_, plain)) = keyring.decrypt_column(&col.name, cell) {
+                map.insert(key, variant_value_to_json(plain, opts)?);
+                continue;
             }
-            Some(SqlValue::Float(n)) => serde_json::json!(n),
-            Some(SqlValue::Double(n)) => serde_json::json!(n),
-            Some(SqlValue::String(s)) => serde_json::Value::String(s),
-            Some(SqlValue::Binary(bytes)) => {
-                serde_json::Value::String(
-                    base64::Engine::encode(&base64::engine::general_purpose::STANDARD, &bytes),
-                )
+        }
+        let value = value_to_json(raw, &col.type_name, opts)?;
+        map.insert(key, value);
+    }
+    Ok(serde_json::Value::Object(map))
+}
+
+/// Row keys for a result's columns, in column order. A column without a name
+/// (`SELECT 1`) is `column<n>` by its position from 0, and a repeated name
+/// gets `_1`, `_2`... so no column is dropped from the row object.
+pub fn column_keys(columns: &[Column], case: ColumnCase) -> Vec<String> {
+    unique_keys(columns.iter().map(|c| c.name.as_str()), case)
+}
+
+fn unique_keys<'a>(names: impl Iterator<Item = &'a str>, case: ColumnCase) -> Vec<String> {
+    let names: Vec<String> = names
+        .enumerate()
+        .map(|(i, name)| match name {
+            "" => format!("column{i}"),
+            name => column_key(name, case),
+        })
+        .collect();
+    // A suffixed key mustn't take the name of another column
+    let mut taken: HashSet<String> = names.iter().cloned().collect();
+    let mut seen = HashSet::new();
+    names
+        .into_iter()
+        .map(|name| {
+            if seen.insert(name.clone()) {
+                return name;
             }
-            Some(SqlValue::Uuid(u)) => serde_json::Value::String(u.to_string()),
-            Some(SqlValue::Date(d)) => serde_json::Value::String(d.to_string()),
-            Some(SqlValue::Time(t)) => serde_json::Value::String(t.to_string()),
-            Some(SqlValue::DateTime(dt)) => serde_json::Value::String(dt.to_string()),
-            Some(SqlValue::DateTimeOffset(dt)) => {
-                serde_json::Value::String(dt.to_rfc3339())
+            (1..)
+                .map(|n| format!("{name}_{n}"))
+                .find(|key| taken.insert(key.clone()))
+                .expect("an unused suffix")
+        })
+        .collect()
+}
+
+/// Whether [`column_keys`] had to rename any column.
+fn renames_columns(columns: &[Column], keys: &[String], case: ColumnCase) -> bool {
+    columns
+        .iter()
+        .zip(keys)
+        .any(|(col, key)| *key != column_key(&col.name, case))
+}
+
+/// Log renamed columns for results returned as bare rows, which have no
+/// envelope to flag them in.
+pub fn warn_renamed(columns: &[Column], case: ColumnCase) {
+    let keys = column_keys(columns, case);
+    if renames_columns(columns, &keys, case) {
+        crate::debug::warn_log!(
+            target: "mssqlts::query",
+            "Result has unnamed or duplicate columns, returned as: {}",
+            keys.join(", ")
+        );
+    }
+}
+
+/// The row key for a column name. Names starting with `__` are the driver's
+/// own (`__rc`, `__out_x`, CDC's `__$operation`) and are never changed.
+pub fn column_key(name: &str, case: ColumnCase) -> String {
+    if name.starts_with("__") {
+        return name.to_string();
+    }
+    match case {
+        ColumnCase::AsIs => name.to_string(),
+        ColumnCase::Lower => name.to_lowercase(),
+        ColumnCase::Camel => camel_case(name),
+    }
+}
+
+/// Recase the keys of a row built with `ColumnCase::AsIs`.
+pub fn recase(
+    row: serde_json::Map<String, serde_json::Value>,
+    case: ColumnCase,
+) -> serde_json::Map<String, serde_json::Value> {
+    match case {
+        ColumnCase::AsIs => row,
+        _ => row
+            .into_iter()
+            .map(|(name, value)| (column_key(&name, case), value))
+            .collect(),
+    }
+}
+
+/// `camelCase` from words split on anything not alphanumeric and on case
+/// changes: `Order_ID` is `orderId`, `URLPath` is `urlPath`, `Line2Total`
+/// is `line2Total`. A name with no letters or digits is kept.
+fn camel_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut words: Vec<String> = Vec::new();
+    let mut word = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            if !word.is_empty() {
+                words.push(std::mem::take(&mut word));
             }
-            Some(SqlValue::Xml(s)) => serde_json::Value::String(s),
-            Some(other) => serde_json::Value::String(format!("{other:?}")),
-        };
-        map.insert(col.name.clone(), value);
+            continue;
+        }
+        let prev = i.checked_sub(1).map(|p| chars[p]);
+        let next = chars.get(i + 1);
+        // orderId splits before the I; URLPath splits before the P
+        let after_lower = prev.is_some_and(|p| p.is_lowercase() || p.is_numeric());
+        let ends_acronym =
+            prev.is_some_and(char::is_uppercase) && next.is_some_and(|n| n.is_lowercase());
+        let boundary = c.is_uppercase() && (after_lower || ends_acronym);
+        if boundary && !word.is_empty() {
+            words.push(std::mem::take(&mut word));
+        }
+        word.push(c);
     }
-    serde_json::Value::Object(map)
+    if !word.is_empty() {
+        words.push(word);
+    }
+    if words.is_empty() {
+        return name.to_string();
+    }
+    let mut out = words[0].to_lowercase();
+    for word in &words[1..] {
+        let mut chars = word.chars();
+        if let Some(first) = chars.next() {
+            out.extend(first.to_uppercase());
+            out.push_str(&chars.as_str().to_lowercase());
+        }
+    }
+    out
 }
 
 // ── Query execution ───────────────────────────────────────────
 
-/// Execute a query and return a JSON array of rows.
+/// Execute a query and return a JSON array of rows, with the row count.
 pub async fn execute_query(
     client: &mut Client<Ready>,
     cmd: &SerializedCommand,
-) -> Result<String> {
+    opts: &SerializeOptions,
+) -> Result<(String, u64)> {
+    if cmd.for_json {
+        return execute_query_for_json(client, cmd).await;
+    }
+    if let Some(page) = &cmd.paginate {
+        return paginate::execute_page(client, cmd, opts, page).await;
+    }
+    if cmd.capture_plan.is_some() || cmd.capture_statistics {
+        return execute_query_captured(client, cmd, opts).await;
+    }
+
     let (rewritten_sql, order) = rewrite_named_params(&cmd.sql, &cmd.params);
-    let owned_values = build_param_boxes(&cmd.params, &order)?;
+    let (rewritten_sql, owned_values) = bind_params(rewritten_sql, &cmd.params, &order)?;
     let param_refs: Vec<&(dyn ToSql + Sync)> = owned_values
         .iter()
         .map(|v| &**v as &(dyn ToSql + Sync))
@@ -427,69 +1096,544 @@ pub async fn execute_query(
     .map_err(MssqlError::from)?;
 
     let mut rows_json = Vec::new();
+    let mut keys = None;
     for result in stream {
         let row: Row = result.map_err(MssqlError::from)?;
-        rows_json.push(row_to_json(&row));
+        let keys = keys.get_or_insert_with(|| {
+            warn_renamed(row.columns(), opts.column_case);
+            column_keys(row.columns(), opts.column_case)
+        });
+        rows_json.push(row_to_json(&row, keys, opts)?);
     }
 
-    Ok(serde_json::to_string(&rows_json).unwrap())
+    let count = rows_json.len() as u64;
+    Ok((serde_json::to_string(&rows_json).unwrap(), count))
 }
 
-/// Execute a non-query and return JSON { rowsAffected }.
-pub async fn execute_nonquery(
+/// Execute a query, writing its rows into `buf` rather than one JSON array.
+/// Returns the row count.
+pub async fn execute_query_into(
     client: &mut Client<Ready>,
     cmd: &SerializedCommand,
-) -> Result<String> {
+    opts: &SerializeOptions,
+    buf: &mut RowBuffer,
+) -> Result<u64> {
+    buf.clear();
     let (rewritten_sql, order) = rewrite_named_params(&cmd.sql, &cmd.params);
-    let owned_values = build_param_boxes(&cmd.params, &order)?;
+    let (rewritten_sql, owned_values) = bind_params(rewritten_sql, &cmd.params, &order)?;
     let param_refs: Vec<&(dyn ToSql + Sync)> = owned_values
         .iter()
         .map(|v| &**v as &(dyn ToSql + Sync))
         .collect();
 
-    let rows_affected = if param_refs.is_empty() {
-        client.execute(&cmd.sql, &[]).await
+    let stream = if param_refs.is_empty() {
+        client.query(&cmd.sql, &[]).await
     } else {
-        client.execute(&rewritten_sql, &param_refs).await
+        client.query(&rewritten_sql, &param_refs).await
     }
     .map_err(MssqlError::from)?;
 
-    Ok(serde_json::json!({ "rowsAffected": rows_affected }).to_string())
+    let mut keys = None;
+    for result in stream {
+        let row: Row = result.map_err(MssqlError::from)?;
+        let keys = keys.get_or_insert_with(|| {
+            warn_renamed(row.columns(), opts.column_case);
+            column_keys(row.columns(), opts.column_case)
+        });
+        buf.push(&row_to_json(&row, keys, opts)?)?;
+    }
+    Ok(buf.rows() as u64)
 }
 
-/// Execute a stored procedure or complex query and return JSON with
-/// result sets, rows affected, and output parameters.
-pub async fn execute_exec(
+/// Column name SQL Server gives `FOR JSON` output.
+const FOR_JSON_COLUMN: &str = "JSON_F52E2B61-18A1-11d1-B105-00805F49916B";
+
+/// Execute a `FOR JSON` query and return its document as JSON text. The
+/// server splits long output into rows of about 2033 characters; they are
+/// joined back together. A single-column, single-row result (`SELECT (...
+/// FOR JSON PATH) AS doc`) is taken as the document too. No rows, or NULL,
+/// is `null`.
+async fn execute_query_for_json(
     client: &mut Client<Ready>,
     cmd: &SerializedCommand,
-) -> Result<String> {
-    let has_output = cmd.params.iter().any(|p| p.output);
+) -> Result<(String, u64)> {
+    let (rewritten_sql, order) = rewrite_named_params(&cmd.sql, &cmd.params);
+    let (rewritten_sql, owned_values) = bind_params(rewritten_sql, &cmd.params, &order)?;
+    let param_refs: Vec<&(dyn ToSql + Sync)> = owned_values
+        .iter()
+        .map(|v| &**v as &(dyn ToSql + Sync))
+        .collect();
 
-    if has_output {
-        execute_exec_with_output(client, cmd).await
+    let stream = if param_refs.is_empty() {
+        client.query(&cmd.sql, &[]).await
     } else {
-        execute_exec_simple(client, cmd).await
+        client.query(&rewritten_sql, &param_refs).await
+    }
+    .map_err(MssqlError::from)?;
+
+    let mut fragments = Vec::new();
+    for result in stream {
+        let row: Row = result.map_err(MssqlError::from)?;
+        let col = match row.columns() {
+            [col] => col,
+            columns => {
+                return Err(MssqlError::Query(format!(
+                    "FOR JSON output has one column; this result has {}",
+                    columns.len()
+                )))
+            }
+        };
+        match row.get_raw(col.index) {
+            Some(SqlValue::String(s)) => fragments.push(s),
+            None | Some(SqlValue::Null) => {}
+            Some(other) => {
+                return Err(MssqlError::Query(format!(
+                    "FOR JSON output is text, not {other:?}"
+                )))
+            }
+        }
+        if fragments.len() > 1 && col.name != FOR_JSON_COLUMN {
+            return Err(MssqlError::Query(format!(
+                "Column '{}' is not FOR JSON output and returned more than one row",
+                col.name
+            )));
+        }
     }
+    let count = u64::from(!fragments.is_empty());
+    Ok((for_json_document(&fragments)?, count))
 }
 
-/// exec without OUTPUT params — use query_multiple to collect result sets.
-async fn execute_exec_simple(
+/// Join `FOR JSON` fragments into one document, checked so a bad one fails
+/// as a query error rather than in JS.
+fn for_json_document(fragments: &[String]) -> Result<String> {
+    if fragments.is_empty() {
+        return Ok("null".into());
+    }
+    let document = fragments.concat();
+    serde_json::from_str::<serde::de::IgnoredAny>(&document)
+        .map_err(|e| MssqlError::Query(format!("FOR JSON output is not valid JSON: {e}")))?;
+    Ok(document)
+}
+
+/// Column name SQL Server gives the showplan XML result sets.
+const SHOWPLAN_COLUMN: &str = "Microsoft SQL Server 2005 XML Showplan";
+
+/// Execute a query with plan and/or statistics capture switched on and return
+/// JSON { rows, plans?, statistics? }, one plan per statement. The settings
+/// are switched off again even when the query fails.
+async fn execute_query_captured(
+    client: &mut Client<Ready>,
+    cmd: &SerializedCommand,
+    opts: &SerializeOptions,
+) -> Result<(String, u64)> {
+    let mut settings = Vec::new();
+    if cmd.capture_statistics {
+        settings.push("STATISTICS IO, TIME");
+    }
+    match cmd.capture_plan {
+        Some(PlanCapture::Estimated) => settings.push("SHOWPLAN_XML"),
+        Some(PlanCapture::Actual) => settings.push("STATISTICS XML"),
+        None => {}
+    }
+
+    let result = run_captured(client, cmd, &settings, opts).await;
+    let mut reset = Ok(());
+    for setting in settings.iter().rev() {
+        let off = client
+            .execute(&format!("SET {setting} OFF"), &[])
+            .await
+            .map(|_| ())
+            .map_err(MssqlError::from);
+        reset = reset.and(off);
+    }
+    let (rows, plans, statistics, renamed) = result?;
+    reset?;
+
+    let count = rows.len() as u64;
+    let mut envelope = serde_json::Map::new();
+    envelope.insert("rows".into(), serde_json::Value::Array(rows));
+    if renamed {
+        envelope.insert("renamedColumns".into(), true.into());
+    }
+    if cmd.capture_plan.is_some() {
+        envelope.insert("plans".into(), serde_json::json!(plans));
+    }
+    if cmd.capture_statistics {
+        envelope.insert("statistics".into(), serde_json::json!(statistics));
+    }
+    Ok((serde_json::Value::Object(envelope).to_string(), count))
+}
+
+/// Switch `settings` on and run `cmd`, keeping only the informational
+/// messages the query itself produced.
+async fn run_captured(
+    client: &mut Client<Ready>,
+    cmd: &SerializedCommand,
+    settings: &[&str],
+    opts: &SerializeOptions,
+) -> Result<(Vec<serde_json::Value>, Vec<String>, Statistics, bool)> {
+    // One batch per SET: SET SHOWPLAN_XML must be the only statement in its batch
+    for setting in settings {
+        client
+            .execute(&format!("SET {setting} ON"), &[])
+            .await
+            .map_err(MssqlError::from)?;
+    }
+    // Drop timing messages for the SET batches themselves
+    client.take_info_messages();
+    let (rows, plans, renamed) = collect_rows_and_plans(client, cmd, opts).await?;
+    let messages = client.take_info_messages();
+    let statistics = Statistics::from_messages(messages.iter().map(|m| m.message.as_str()));
+    Ok((rows, plans, statistics, renamed))
+}
+
+/// Run `cmd`, separating showplan result sets from the query's own rows.
+async fn collect_rows_and_plans(
     client: &mut Client<Ready>,
     cmd: &SerializedCommand,
+    opts: &SerializeOptions,
+) -> Result<(Vec<serde_json::Value>, Vec<String>, bool)> {
+    let (rewritten_sql, order) = rewrite_named_params(&cmd.sql, &cmd.params);
+    let (rewritten_sql, owned_values) = bind_params(rewritten_sql, &cmd.params, &order)?;
+    let param_refs: Vec<&(dyn ToSql + Sync)> = owned_values
+        .iter()
+        .map(|v| &**v as &(dyn ToSql + Sync))
+        .collect();
+
+    let mut multi = if param_refs.is_empty() {
+        client.query_multiple(&cmd.sql, &[]).await
+    } else {
+        client.query_multiple(&rewritten_sql, &param_refs).await
+    }
+    .map_err(MssqlError::from)?;
+
+    let mut rows = Vec::new();
+    let mut plans = Vec::new();
+    let mut renamed = false;
+    loop {
+        let mut keys = multi.columns().map(|columns| {
+            let keys = column_keys(columns, opts.column_case);
+            renamed |= renames_columns(columns, &keys, opts.column_case);
+            keys
+        });
+        while let Some(row) = multi.next_row().await.map_err(MssqlError::from)? {
+            match showplan_xml(&row) {
+                Some(xml) => plans.push(xml),
+                None => {
+                    let keys =
+                        keys.get_or_insert_with(|| column_keys(row.columns(), opts.column_case));
+                    rows.push(row_to_json(&row, keys, opts)?);
+                }
+            }
+        }
+        if !multi.next_result().await.map_err(MssqlError::from)? {
+            break;
+        }
+    }
+    Ok((rows, plans, renamed))
+}
+
+/// The plan XML if `row` belongs to a showplan result set.
+fn showplan_xml(row: &Row) -> Option<String> {
+    match row.columns() {
+        [col] if col.name == SHOWPLAN_COLUMN => match row.get_raw(col.index) {
+            Some(SqlValue::Xml(xml)) | Some(SqlValue::String(xml)) => Some(xml),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Execute a non-query and return JSON { rowsAffected }, or
+/// { rowsAffected, rows } when the command asks for OUTPUT clause rows.
+pub async fn execute_nonquery(
+    client: &mut Client<Ready>,
+    cmd: &SerializedCommand,
+    opts: &SerializeOptions,
 ) -> Result<String> {
+    if cmd.return_rows || cmd.return_identity || cmd.rowcount() == RowCount::Last {
+        return execute_nonquery_with_rows(client, cmd, opts).await;
+    }
+
     let (rewritten_sql, order) = rewrite_named_params(&cmd.sql, &cmd.params);
-    let owned_values = build_param_boxes(&cmd.params, &order)?;
+    let sql = with_nocount(rewritten_sql, cmd.nocount)?;
+    let (sql, owned_values) = bind_params(sql, &cmd.params, &order)?;
     let param_refs: Vec<&(dyn ToSql + Sync)> = owned_values
         .iter()
         .map(|v| &**v as &(dyn ToSql + Sync))
         .collect();
 
-    // Append SELECT @@ROWCOUNT to capture rows affected
-    let sql_with_rc = if param_refs.is_empty() {
-        format!("{}; SELECT @@ROWCOUNT AS __rc", cmd.sql)
+    let rows_affected = client
+        .execute(&sql, &param_refs)
+        .await
+        .map_err(MssqlError::from)?;
+
+    Ok(serde_json::json!({ "rowsAffected": rows_affected }).to_string())
+}
+
+/// Execute a non-query whose OUTPUT clause (e.g. `OUTPUT INSERTED.*`) returns
+/// rows, collecting those rows alongside the affected count in one round trip.
+/// Also used for `rowcount: last` and `return_identity`, leaving the rows
+/// out of the result unless they were asked for.
+async fn execute_nonquery_with_rows(
+    client: &mut Client<Ready>,
+    cmd: &SerializedCommand,
+    opts: &SerializeOptions,
+) -> Result<String> {
+    if cmd.return_rows || cmd.return_identity {
+        cmd.require_last_rowcount()?;
+    }
+    let (rewritten_sql, order) = rewrite_named_params(&cmd.sql, &cmd.params);
+    let batch = if cmd.return_identity {
+        build_identity_batch(&rewritten_sql, cmd.identity_key.as_deref())?
     } else {
         format!("{rewritten_sql}; SELECT @@ROWCOUNT AS __rc")
     };
+    let sql_with_rc = with_nocount(batch, cmd.nocount)?;
+    let (sql_with_rc, owned_values) = bind_params(sql_with_rc, &cmd.params, &order)?;
+    let param_refs: Vec<&(dyn ToSql + Sync)> = owned_values
+        .iter()
+        .map(|v| &**v as &(dyn ToSql + Sync))
+        .collect();
+    let mut multi = client
+        .query_multiple(&sql_with_rc, &param_refs)
+        .await
+        .map_err(MssqlError::from)?;
+
+    let mut rows: Vec<serde_json::Value> = Vec::new();
+    let mut inserted_ids: Vec<serde_json::Value> = Vec::new();
+    let mut rows_affected: i64 = 0;
+
+    loop {
+        let mut keys = None;
+        while let Some(row) = multi.next_row().await.map_err(MssqlError::from)? {
+            let keys = keys.get_or_insert_with(|| column_keys(row.columns(), opts.column_case));
+            let json = row_to_json(&row, keys, opts)?;
+            if let Some(rc) = json.get("__rc") {
+                if let Some(n) = rc.as_i64() {
+                    rows_affected = n;
+                    continue;
+                }
+            }
+            if let Some(id) = json.get(IDENTITY_COL) {
+                // SCOPE_IDENTITY() is NULL when nothing was inserted
+                if !id.is_null() {
+                    inserted_ids.push(id.clone());
+                }
+                continue;
+            }
+            rows.push(json);
+        }
+        if !multi.next_result().await.map_err(MssqlError::from)? {
+            break;
+        }
+    }
+
+    let mut envelope = serde_json::Map::new();
+    envelope.insert("rowsAffected".into(), rows_affected.into());
+    if cmd.return_rows {
+        envelope.insert("rows".into(), serde_json::Value::Array(rows));
+    }
+    if cmd.return_identity {
+        envelope.insert("insertedIds".into(), serde_json::Value::Array(inserted_ids));
+    }
+    Ok(serde_json::Value::Object(envelope).to_string())
+}
+
+/// Column the generated keys are SELECTed back as.
+const IDENTITY_COL: &str = "__identity";
+
+/// Build an INSERT batch that SELECTs back its generated keys after the
+/// `__rc` row. With a key column, an `OUTPUT ... INTO` clause is added to
+/// the INSERT (INTO, because a bare OUTPUT fails on tables with triggers);
+/// otherwise `SCOPE_IDENTITY()` gives the last identity value.
+///
+/// ```sql
+/// DECLARE @__ids TABLE (n INT IDENTITY, id SQL_VARIANT);
+/// INSERT INTO Orders (Sku) OUTPUT INSERTED.[Id] INTO @__ids (id) VALUES (@P1);
+/// SELECT @@ROWCOUNT AS __rc;
+/// SELECT id AS __identity FROM @__ids ORDER BY n;
+/// ```
+fn build_identity_batch(sql: &str, key: Option<&str>) -> Result<String> {
+    let Some(key) = key else {
+        return Ok(format!(
+            "{sql};\nSELECT @@ROWCOUNT AS __rc;\n\
+             SELECT CAST(SCOPE_IDENTITY() AS BIGINT) AS {IDENTITY_COL};"
+        ));
+    };
+    let at = insert_output_offset(sql).ok_or_else(|| {
+        MssqlError::Query("identity_key needs the command to be a single INSERT statement".into())
+    })?;
+    let (head, tail) = sql.split_at(at);
+    if keyword_at(tail.as_bytes(), skip_trivia(tail.as_bytes(), 0), "OUTPUT").is_some() {
+        return Err(MssqlError::Query(
+            "identity_key can't be used on an INSERT that already has an OUTPUT clause".into(),
+        ));
+    }
+    Ok(format!(
+        "DECLARE @__ids TABLE (n INT IDENTITY, id SQL_VARIANT);\n\
+         {head} OUTPUT INSERTED.{} INTO @__ids (id){tail};\n\
+         SELECT @@ROWCOUNT AS __rc;\n\
+         SELECT id AS {IDENTITY_COL} FROM @__ids ORDER BY n;",
+        crate::bulk::bracket_escape(key)
+    ))
+}
+
+/// Byte offset in an INSERT statement where an OUTPUT clause goes: after the
+/// target, its table hints and its column list. None when `sql` doesn't
+/// start with INSERT.
+fn insert_output_offset(sql: &str) -> Option<usize> {
+    let b = sql.as_bytes();
+    let mut i = keyword_at(b, skip_trivia(b, 0), "INSERT")?;
+    i = skip_trivia(b, i);
+    if let Some(next) = keyword_at(b, i, "INTO") {
+        i = skip_trivia(b, next);
+    }
+    // Target: dot-separated parts, each bracketed, quoted or bare
+    loop {
+        i = match *b.get(i)? {
+            b'[' => closing(b, i, b']')?,
+            b'"' => closing(b, i, b'"')?,
+            _ => {
+                let start = i;
+                while i < b.len() && (is_ident_byte(b[i]) || matches!(b[i], b'@' | b'#')) {
+                    i += 1;
+                }
+                (i > start).then_some(i)?
+            }
+        };
+        if b.get(i) != Some(&b'.') {
+            break;
+        }
+        i += 1;
+    }
+    let mut end = i;
+    i = skip_trivia(b, i);
+    if let Some(next) = keyword_at(b, i, "WITH") {
+        let open = skip_trivia(b, next);
+        if b.get(open) == Some(&b'(') {
+            end = balanced(b, open)?;
+            i = skip_trivia(b, end);
+        }
+    }
+    if b.get(i) == Some(&b'(') {
+        end = balanced(b, i)?;
+    }
+    Some(end)
+}
+
+pub fn is_ident_byte(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || c == b'_' || c >= 0x80
+}
+
+/// Skip whitespace and comments from `i`.
+pub fn skip_trivia(b: &[u8], mut i: usize) -> usize {
+    loop {
+        while i < b.len() && b[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        if b[i..].starts_with(b"--") {
+            while i < b.len() && b[i] != b'\n' {
+                i += 1;
+            }
+        } else if b[i..].starts_with(b"/*") {
+            i = match b[i + 2..].windows(2).position(|w| w == b"*/") {
+                Some(p) => i + 2 + p + 2,
+                None => b.len(),
+            };
+        } else {
+            return i;
+        }
+    }
+}
+
+/// The offset after `keyword` if it's the word at `i` (case-insensitive).
+fn keyword_at(b: &[u8], i: usize, keyword: &str) -> Option<usize> {
+    let end = i + keyword.len();
+    let word = b.get(i..end)?;
+    let whole = !b.get(end).is_some_and(|&c| is_ident_byte(c));
+    (word.eq_ignore_ascii_case(keyword.as_bytes()) && whole).then_some(end)
+}
+
+/// The offset after the `close` that ends the quoted name opening at `i`;
+/// a doubled `close` is an escaped one.
+pub fn closing(b: &[u8], i: usize, close: u8) -> Option<usize> {
+    let mut j = i + 1;
+    while j < b.len() {
+        if b[j] == close {
+            if b.get(j + 1) != Some(&close) {
+                return Some(j + 1);
+            }
+            j += 1;
+        }
+        j += 1;
+    }
+    None
+}
+
+/// The offset after the `)` matching the `(` at `i`, skipping quoted text.
+fn balanced(b: &[u8], i: usize) -> Option<usize> {
+    let mut depth = 0;
+    let mut j = i;
+    while j < b.len() {
+        match b[j] {
+            b'(' => depth += 1,
+            b')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(j + 1);
+                }
+            }
+            b'\'' => j = closing(b, j, b'\'')? - 1,
+            b'[' => j = closing(b, j, b']')? - 1,
+            b'"' => j = closing(b, j, b'"')? - 1,
+            _ => {}
+        }
+        j += 1;
+    }
+    None
+}
+
+/// Execute a stored procedure or complex query and return JSON with
+/// result sets, rows affected, and output parameters.
+/// `native_json` caches whether the server has the `json` type, for OUTPUT
+/// parameters declared as json.
+pub async fn execute_exec(
+    client: &mut Client<Ready>,
+    cmd: &SerializedCommand,
+    opts: &SerializeOptions,
+    native_json: &OnceLock<bool>,
+) -> Result<String> {
+    cmd.require_last_rowcount()?;
+    let has_output = cmd.params.iter().any(|p| p.output);
+
+    if has_output {
+        execute_exec_with_output(client, cmd, opts, native_json).await
+    } else {
+        execute_exec_simple(client, cmd, opts).await
+    }
+}
+
+/// exec without OUTPUT params — use query_multiple to collect result sets.
+async fn execute_exec_simple(
+    client: &mut Client<Ready>,
+    cmd: &SerializedCommand,
+    opts: &SerializeOptions,
+) -> Result<String> {
+    // Stored procedures are called as EXEC @__ret = proc ... so the RETURN
+    // code can be captured; text commands just append SELECT @@ROWCOUNT.
+    let (sql_with_rc, order) = if cmd.command_type == "stored_procedure" {
+        build_proc_call(&cmd.sql, &cmd.params)
+    } else {
+        let (rewritten_sql, order) = rewrite_named_params(&cmd.sql, &cmd.params);
+        (format!("{rewritten_sql}; SELECT @@ROWCOUNT AS __rc"), order)
+    };
+    let sql_with_rc = with_nocount(sql_with_rc, cmd.nocount)?;
+    let (sql_with_rc, owned_values) = bind_params(sql_with_rc, &cmd.params, &order)?;
+    let param_refs: Vec<&(dyn ToSql + Sync)> = owned_values
+        .iter()
+        .map(|v| &**v as &(dyn ToSql + Sync))
+        .collect();
 
     let mut multi = client
         .query_multiple(&sql_with_rc, &param_refs)
@@ -497,159 +1641,401 @@ async fn execute_exec_simple(
         .map_err(MssqlError::from)?;
 
     let mut result_sets: Vec<Vec<serde_json::Value>> = Vec::new();
+    let mut columns: Vec<serde_json::Value> = Vec::new();
     let mut rows_affected: i64 = 0;
+    let mut return_value = serde_json::Value::Null;
 
     loop {
+        let set_columns = result_columns(multi.columns(), opts);
         let mut current_set = Vec::new();
+        let mut keys = None;
         while let Some(row) = multi.next_row().await.map_err(MssqlError::from)? {
-            let json = row_to_json(&row);
-            // Check if this is the __rc sentinel
+            let keys = keys.get_or_insert_with(|| column_keys(row.columns(), opts.column_case));
+            let json = row_to_json(&row, keys, opts)?;
+            // Check if this is the __rc sentinel (optionally carrying __ret)
             if let Some(rc) = json.get("__rc") {
                 if let Some(n) = rc.as_i64() {
                     rows_affected = n;
+                    if let Some(ret) = json.get("__ret") {
+                        return_value = ret.clone();
+                    }
                     continue;
                 }
             }
             current_set.push(json);
         }
-        if !current_set.is_empty() {
+        // Empty sets are kept when they have columns, so indexes line up
+        // with the statements that produced them.
+        if set_columns.is_some() || !current_set.is_empty() {
             result_sets.push(current_set);
+            columns.push(set_columns.unwrap_or_else(|| serde_json::json!([])));
         }
         if !multi.next_result().await.map_err(MssqlError::from)? {
             break;
         }
     }
 
+    let renamed = renamed_columns(&columns);
     Ok(serde_json::json!({
         "rowsAffected": rows_affected,
         "resultSets": result_sets,
+        "columns": columns,
+        "renamedColumns": renamed,
         "outputParams": {},
+        "returnValue": return_value,
     })
     .to_string())
 }
 
-/// exec with OUTPUT params — build a simple_query batch.
-async fn execute_exec_with_output(
+/// Column metadata for an `exec` result set: name, SQL type and nullability
+/// in column order. None for results without columns and for the driver's
+/// own `__rc` row. Names are the row keys; a renamed column
+/// carries the server's name as `sourceName`.
+fn result_columns(
+    columns: Option<&[Column]>,
+    opts: &SerializeOptions,
+) -> Option<serde_json::Value> {
+    let columns = columns.filter(|c| !c.is_empty())?;
+    if columns[0].name == "__rc" {
+        return None;
+    }
+    let keys = column_keys(columns, opts.column_case);
+    let meta = columns
+        .iter()
+        .zip(keys)
+        .map(|(c, key)| {
+            let renamed = key != column_key(&c.name, opts.column_case);
+            let mut meta = serde_json::json!({
+                "name": key,
+                "type": c.type_name,
+                "nullable": c.nullable,
+            });
+            if renamed {
+                meta["sourceName"] = c.name.clone().into();
+            }
+            meta
+        })
+        .collect();
+    Some(serde_json::Value::Array(meta))
+}
+
+/// Whether any result set's metadata has a renamed column.
+fn renamed_columns(columns: &[serde_json::Value]) -> bool {
+    columns
+        .iter()
+        .filter_map(|set| set.as_array())
+        .flatten()
+        .any(|c| c.get("sourceName").is_some())
+}
+
+/// Whether the server has the native `json` type (SQL Server 2025 / Azure
+/// SQL). Asked once per connection; the answer is kept in `cached`.
+async fn supports_native_json(
     client: &mut Client<Ready>,
-    cmd: &SerializedCommand,
-) -> Result<String> {
-    // Build DECLARE + EXEC batch with OUTPUT params
-    let mut batch = String::new();
-    let mut output_names: Vec<String> = Vec::new();
+    cached: &OnceLock<bool>,
+) -> Result<bool> {
+    if let Some(&native) = cached.get() {
+        return Ok(native);
+    }
+    let stream = client
+        .query("SELECT CAST(CASE WHEN TYPE_ID('json') IS NULL THEN 0 ELSE 1 END AS BIT)", &[])
+        .await
+        .map_err(MssqlError::from)?;
+    let native = match stream.into_iter().next() {
+        Some(result) => {
+            let row: Row = result.map_err(MssqlError::from)?;
+            matches!(row.get_raw(0), Some(SqlValue::Bool(true)))
+        }
+        None => false,
+    };
+    Ok(*cached.get_or_init(|| native))
+}
 
-    for param in &cmd.params {
+/// Build a stored procedure call that captures the RETURN code:
+/// `DECLARE @__ret INT; EXEC @__ret = proc @a = @P1, ...; SELECT @@ROWCOUNT, @__ret`.
+/// Returns the batch SQL and the positional parameter order.
+fn build_proc_call(proc_name: &str, params: &[SerializedParam]) -> (String, Vec<usize>) {
+    let mut locals = String::new();
+    let mut args = String::new();
+    let mut order = Vec::with_capacity(params.len());
+    for (i, param) in params.iter().enumerate() {
         let clean = param.name.trim_start_matches('@');
-        if param.output {
-            let sql_type = if let Some(ref t) = param.param_type {
-                sql_type_for_declare(t)?
-            } else {
-                "NVARCHAR(MAX)"
-            };
-            batch.push_str(&format!("DECLARE @{clean} {sql_type};\n"));
-            output_names.push(clean.to_string());
-            // If the param has an input value too, set it
-            if !param.value.is_null() {
-                let val = param_to_sql_value(param)?;
-                batch.push_str(&format!("SET @{clean} = {};\n", sql_value_to_literal(&val)));
-            }
-        }
+        args.push_str(if i == 0 { " " } else { ", " });
+        let arg = proc_arg(param, i + 1, &mut locals);
+        args.push_str(&format!("@{clean} = {arg}"));
+        order.push(i);
     }
+    let sql = format!(
+        "DECLARE @__ret INT;\n{locals}EXEC @__ret = {proc_name}{args};\n\
+         SELECT @@ROWCOUNT AS __rc, @__ret AS __ret"
+    );
+    (sql, order)
+}
 
-    // Build EXEC call
-    if cmd.command_type == "stored_procedure" {
-        batch.push_str(&format!("EXEC {} ", cmd.sql));
-    } else {
-        batch.push_str(&cmd.sql);
-        batch.push_str(";\n");
-    }
+/// The argument passing the param bound at `@P{pos}` to a procedure. EXEC
+/// only takes variables and constants, so a param whose placeholder is an
+/// expression (spatial WKT) is converted into a local declared in `locals`.
+fn proc_arg(param: &SerializedParam, pos: usize, locals: &mut String) -> String {
+    let placeholder = param_placeholder(param, pos);
+    let is_expression = placeholder != format!("@P{pos}");
+    let Some(sql_type) = param.param_type.as_deref().filter(|_| is_expression) else {
+        return placeholder;
+    };
+    locals.push_str(&format!(
+        "DECLARE @__arg{pos} {} = {placeholder};\n",
+        sql_type.to_uppercase()
+    ));
+    format!("@__arg{pos}")
+}
 
-    if cmd.command_type == "stored_procedure" {
-        let mut param_parts: Vec<String> = Vec::new();
-        for param in &cmd.params {
-            let clean = param.name.trim_start_matches('@');
-            if param.output {
-                param_parts.push(format!("@{clean} = @{clean} OUTPUT"));
-            } else {
-                let val = param_to_sql_value(param)?;
-                param_parts.push(format!("@{clean} = {}", sql_value_to_literal(&val)));
-            }
-        }
-        batch.push_str(&param_parts.join(", "));
-        batch.push_str(";\n");
-    }
+/// A fresh column prefix for one call's OUTPUT row, `__out_<nonce>_`, so no
+/// result set the statement itself returns can be taken for it.
+fn output_prefix() -> String {
+    format!("__out_{:08x}_", uuid::Uuid::new_v4().as_u128() as u32)
+}
 
-    // SELECT output values
-    if !output_names.is_empty() {
-        batch.push_str("SELECT ");
-        let selects: Vec<String> = output_names
+/// Whether `columns` are the OUTPUT row: exactly `names`, in order, under
+/// this call's `prefix`.
+fn is_output_row(columns: &[Column], prefix: &str, names: &[String]) -> bool {
+    !names.is_empty()
+        && columns.len() == names.len()
+        && columns
             .iter()
-            .map(|n| format!("@{n} AS [{n}]"))
-            .collect();
-        batch.push_str(&selects.join(", "));
-        batch.push_str(";\n");
-    }
+            .zip(names)
+            .all(|(c, name)| c.name.strip_prefix(prefix) == Some(name.as_str()))
+}
 
-    batch.push_str("SELECT @@ROWCOUNT AS __rc;\n");
+/// exec with OUTPUT params — a parameterized batch (sent via sp_executesql)
+/// that declares typed locals for each OUTPUT param, binds them to the call,
+/// and SELECTs them back. Input values never get embedded as literals.
+async fn execute_exec_with_output(
+    client: &mut Client<Ready>,
+    cmd: &SerializedCommand,
+    opts: &SerializeOptions,
+    native_json: &OnceLock<bool>,
+) -> Result<String> {
+    let wants_json = cmd
+        .params
+        .iter()
+        .any(|p| p.output && p.param_type.as_deref() == Some("json"));
+    let native = wants_json && supports_native_json(client, native_json).await?;
+    // Untyped OUTPUT params of a procedure take the procedure's own types
+    let untyped = |p: &SerializedParam| p.output && p.param_type.is_none();
+    let proc_types = if cmd.command_type == "stored_procedure" && cmd.params.iter().any(untyped) {
+        catalog::proc_output_types(client, &cmd.sql).await?
+    } else {
+        HashMap::new()
+    };
+    let prefix = output_prefix();
+    let (batch, order, output_names) = build_output_batch(cmd, native, &proc_types, &prefix)?;
+    let batch = with_nocount(batch, cmd.nocount)?;
+    let (batch, owned_values) = bind_params(batch, &cmd.params, &order)?;
+    let param_refs: Vec<&(dyn ToSql + Sync)> = owned_values
+        .iter()
+        .map(|v| &**v as &(dyn ToSql + Sync))
+        .collect();
 
-    // Execute the batch
     let mut multi = client
-        .query_multiple(&batch, &[])
+        .query_multiple(&batch, &param_refs)
         .await
         .map_err(MssqlError::from)?;
 
     let mut result_sets: Vec<Vec<serde_json::Value>> = Vec::new();
+    let mut columns: Vec<serde_json::Value> = Vec::new();
     let mut rows_affected: i64 = 0;
     let mut output_params = serde_json::Map::new();
+    let mut return_value = serde_json::Value::Null;
 
     loop {
+        let outputs = multi
+            .columns()
+            .is_some_and(|c| is_output_row(c, &prefix, &output_names));
+        let set_columns = if outputs {
+            None
+        } else {
+            result_columns(multi.columns(), opts)
+        };
         let mut current_set = Vec::new();
+        let mut keys = None;
         while let Some(row) = multi.next_row().await.map_err(MssqlError::from)? {
-            let json = row_to_json(&row);
-            // Check for __rc sentinel
+            let columns = row.columns();
+            if outputs {
+                for col in columns {
+                    let name = &col.name[prefix.len()..];
+                    let value = row.get_raw(col.index);
+                    let value = output_to_json(&cmd.params, name, value, &col.type_name, opts)?;
+                    output_params.insert(name.to_string(), value);
+                }
+                continue;
+            }
+            let keys = keys.get_or_insert_with(|| column_keys(columns, opts.column_case));
+            let json = row_to_json(&row, keys, opts)?;
+            // Check for __rc sentinel (optionally carrying __ret)
             if let Some(rc) = json.get("__rc") {
                 if let Some(n) = rc.as_i64() {
                     rows_affected = n;
-                    continue;
-                }
-            }
-            // Check for output params (columns match output_names)
-            if !output_names.is_empty() {
-                let obj = json.as_object().unwrap();
-                let is_output_row = output_names
-                    .iter()
-                    .all(|n| obj.contains_key(n));
-                if is_output_row && obj.len() == output_names.len() {
-                    for (k, v) in obj {
-                        output_params.insert(k.clone(), v.clone());
+                    if let Some(ret) = json.get("__ret") {
+                        return_value = ret.clone();
                     }
                     continue;
                 }
             }
             current_set.push(json);
         }
-        if !current_set.is_empty() {
+        // Empty sets are kept when they have columns, so indexes line up
+        // with the statements that produced them.
+        if set_columns.is_some() || !current_set.is_empty() {
             result_sets.push(current_set);
+            columns.push(set_columns.unwrap_or_else(|| serde_json::json!([])));
         }
         if !multi.next_result().await.map_err(MssqlError::from)? {
             break;
         }
     }
 
+    let renamed = renamed_columns(&columns);
     Ok(serde_json::json!({
         "rowsAffected": rows_affected,
         "resultSets": result_sets,
+        "columns": columns,
+        "renamedColumns": renamed,
         "outputParams": output_params,
+        "returnValue": return_value,
     })
     .to_string())
 }
 
+/// Convert an OUTPUT value by the type its param was declared with, so a
+/// `rowversion` or `json` hint shapes it the way a column of that type would
+/// be, whatever type the local came back as.
+fn output_to_json(
+    params: &[SerializedParam],
+    name: &str,
+    value: Option<SqlValue>,
+    column_type: &str,
+    opts: &SerializeOptions,
+) -> Result<serde_json::Value> {
+    let declared = params
+        .iter()
+        .find(|p| p.output && p.name.trim_start_matches('@') == name)
+        .and_then(|p| p.param_type.as_deref());
+    value_to_json(value, declared.unwrap_or(column_type), opts)
+}
+
+/// Declared type for an OUTPUT param of a text command without a type hint,
+/// going by its initial value so numbers and flags don't come back as strings.
+fn inferred_output_type(value: &serde_json::Value) -> &'static str {
+    match value {
+        serde_json::Value::Bool(_) => "BIT",
+        serde_json::Value::Number(n) if n.is_i64() => "BIGINT",
+        serde_json::Value::Number(_) => "FLOAT",
+        _ => "NVARCHAR(MAX)",
+    }
+}
+
+/// Build the parameterized OUTPUT batch. Returns the SQL, the positional
+/// parameter order (indices into `cmd.params`), and the OUTPUT param names.
+/// Untyped OUTPUT params of a procedure are declared as `proc_types` has them
+/// (see [`catalog::proc_output_types`]); the OUTPUT row's columns carry
+/// `prefix`.
+///
+/// ```sql
+/// DECLARE @total INT = @P2;             -- OUTPUT param (initial value bound)
+/// DECLARE @__ret INT;
+/// EXEC @__ret = proc @id = @P1, @total = @total OUTPUT;
+/// SELECT @@ROWCOUNT AS __rc, @__ret AS __ret;
+/// SELECT @total AS [__out_1a2b3c4d_total];
+/// ```
+fn build_output_batch(
+    cmd: &SerializedCommand,
+    native_json: bool,
+    proc_types: &HashMap<String, String>,
+    prefix: &str,
+) -> Result<(String, Vec<usize>, Vec<String>)> {
+    let is_proc = cmd.command_type == "stored_procedure";
+    let mut order: Vec<usize> = Vec::new();
+
+    // The statement itself comes first so its placeholders are @P1..@Pn.
+    let statement = if is_proc {
+        let mut locals = String::new();
+        let mut parts: Vec<String> = Vec::new();
+        for (i, param) in cmd.params.iter().enumerate() {
+            let clean = param.name.trim_start_matches('@');
+            if param.output {
+                parts.push(format!("@{clean} = @{clean} OUTPUT"));
+            } else {
+                order.push(i);
+                let arg = proc_arg(param, order.len(), &mut locals);
+                parts.push(format!("@{clean} = {arg}"));
+            }
+        }
+        let args = if parts.is_empty() {
+            String::new()
+        } else {
+            format!(" {}", parts.join(", "))
+        };
+        format!(
+            "DECLARE @__ret INT;\n{locals}EXEC @__ret = {}{args};\n\
+             SELECT @@ROWCOUNT AS __rc, @__ret AS __ret;\n",
+            cmd.sql
+        )
+    } else {
+        // Only inputs are rewritten; OUTPUT names stay as declared locals.
+        let input_idx: Vec<usize> = (0..cmd.params.len())
+            .filter(|&i| !cmd.params[i].output)
+            .collect();
+        let inputs: Vec<SerializedParam> =
+            input_idx.iter().map(|&i| cmd.params[i].clone()).collect();
+        let (rewritten, input_order) = rewrite_named_params(&cmd.sql, &inputs);
+        order.extend(input_order.iter().map(|&j| input_idx[j]));
+        format!("{rewritten};\nSELECT @@ROWCOUNT AS __rc;\n")
+    };
+
+    // DECLARE a typed local per OUTPUT param, binding any initial value.
+    let mut declares = String::new();
+    let mut output_names: Vec<String> = Vec::new();
+    for (i, param) in cmd.params.iter().enumerate() {
+        if !param.output {
+            continue;
+        }
+        let clean = param.name.trim_start_matches('@');
+        let sql_type = match (&param.param_type, sized_type(param)) {
+            (Some(t), _) if native_json && t == "json" => "JSON".to_string(),
+            (_, Some(sized)) => sized,
+            (Some(t), None) => sql_type_for_declare(t)?.to_string(),
+            (None, None) => match proc_types.get(&clean.to_lowercase()) {
+                Some(declared) => declared.clone(),
+                None => inferred_output_type(&param.value).to_string(),
+            },
+        };
+        if param.value.is_null() {
+            declares.push_str(&format!("DECLARE @{clean} {sql_type};\n"));
+        } else {
+            order.push(i);
+            declares.push_str(&format!(
+                "DECLARE @{clean} {sql_type} = {};\n",
+                param_placeholder(param, order.len())
+            ));
+        }
+        output_names.push(clean.to_string());
+    }
+
+    let selects: Vec<String> = output_names
+        .iter()
+        .map(|n| format!("@{n} AS [{prefix}{n}]"))
+        .collect();
+    let batch = format!("{declares}{statement}SELECT {};", selects.join(", "));
+
+    Ok((batch, order, output_names))
+}
+
 /// Execute a query and return all rows for streaming.
 pub async fn execute_query_stream(
     client: &mut Client<Ready>,
     cmd: &SerializedCommand,
 ) -> Result<Vec<Row>> {
     let (rewritten_sql, order) = rewrite_named_params(&cmd.sql, &cmd.params);
-    let owned_values = build_param_boxes(&cmd.params, &order)?;
+    let (rewritten_sql, owned_values) = bind_params(rewritten_sql, &cmd.params, &order)?;
     let param_refs: Vec<&(dyn ToSql + Sync)> = owned_values
         .iter()
         .map(|v| &**v as &(dyn ToSql + Sync))
@@ -691,6 +2077,47 @@ fn build_param_boxes(
     Ok(ordered)
 }
 
+/// Box the params in `order` and return the batch to send them with. The
+/// driver picks each param's type from its value, so when a param has a
+/// declared type (a length or precision hint, xml or vector) `sql` runs
+/// through an inner `sp_executesql` whose parameter list declares every
+/// param, and the statement sees `@P1 NVARCHAR(50)` rather than whatever the
+/// value was sent as:
+///
+/// ```sql
+/// EXEC sp_executesql N'SELECT ... WHERE code = @P1', N'@P1 NVARCHAR(50)', @P1
+/// ```
+pub fn bind_params(
+    sql: String,
+    params: &[SerializedParam],
+    order: &[usize],
+) -> Result<(String, Vec<Box<dyn ToSql + Sync>>)> {
+    let values = build_param_boxes(params, order)?;
+    let declared: Vec<_> = order.iter().map(|&i| declared_type(&params[i])).collect();
+    if declared.iter().all(Option::is_none) {
+        return Ok((sql, values));
+    }
+    let mut declarations = Vec::with_capacity(order.len());
+    for (pos, (declared, value)) in declared.into_iter().zip(&values).enumerate() {
+        let sql_type = match declared {
+            Some(sql_type) => sql_type,
+            None => {
+                let value = value.to_sql().map_err(|e| MssqlError::Query(e.to_string()));
+                sent_type(&value?)
+            }
+        };
+        declarations.push(format!("@P{} {sql_type}", pos + 1));
+    }
+    let args: Vec<String> = (1..=order.len()).map(|pos| format!("@P{pos}")).collect();
+    let batch = format!(
+        "EXEC sp_executesql N'{}', N'{}', {}",
+        sql.replace('\'', "''"),
+        declarations.join(", "),
+        args.join(", ")
+    );
+    Ok((batch, values))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -701,6 +2128,20 @@ mod tests {
             value: serde_json::Value::Null,
             param_type: None,
             output: false,
+            precision: None,
+            scale: None,
+            srid: None,
+            length: None,
+        }
+    }
+
+    fn decimal_param(value: serde_json::Value, precision: Option<u8>, scale: Option<u8>) -> SerializedParam {
+        SerializedParam {
+            value,
+            param_type: Some("decimal".into()),
+            precision,
+            scale,
+            ..param("amount")
         }
     }
 
@@ -757,10 +2198,25 @@ mod tests {
         assert_eq!(order, vec![0, 0]);
     }
 
+    #[test]
+    fn rewrite_wraps_spatial_params() {
+        let params = vec![
+            SerializedParam { param_type: Some("geometry".into()), ..param("shape") },
+            SerializedParam { param_type: Some("geography".into()), ..param("loc") },
+            SerializedParam { param_type: Some("geography".into()), srid: Some(4269), ..param("area") },
+        ];
+        let (sql, _) = rewrite_named_params("VALUES (@shape, @loc, @area)", &params);
+        assert_eq!(
+            sql,
+            "VALUES (geometry::STGeomFromText(@P1, 0), geography::STGeomFromText(@P2, 4326), \
+             geography::STGeomFromText(@P3, 4269))"
+        );
+    }
+
     #[test]
     fn sql_type_declares() {
         assert_eq!(sql_type_for_declare("int").unwrap(), "INT");
-        assert_eq!(sql_type_for_declare("BIGINT").unwrap(), "BIGINT");
+        assert_eq!(sql_type_for_declare("bigint").unwrap(), "BIGINT");
         assert_eq!(sql_type_for_declare("nvarchar").unwrap(), "NVARCHAR(MAX)");
         assert_eq!(
             sql_type_for_declare("uniqueidentifier").unwrap(),
@@ -768,4 +2224,732 @@ mod tests {
         );
         assert!(sql_type_for_declare("badtype").is_err());
     }
+
+    #[test]
+    fn proc_call_converts_spatial_args_into_locals() {
+        let params = vec![
+            SerializedParam {
+                param_type: Some("geography".into()),
+                ..param("loc")
+            },
+            SerializedParam {
+                param_type: Some("nvarchar".into()),
+                length: Some(10),
+                ..param("code")
+            },
+        ];
+        let (sql, _) = build_proc_call("dbo.sp_Near", &params);
+        assert_eq!(
+            sql,
+            "DECLARE @__ret INT;\nDECLARE @__arg1 GEOGRAPHY = geography::STGeomFromText(@P1, 4326);\n\
+             EXEC @__ret = dbo.sp_Near @loc = @__arg1, @code = @P2;\n\
+             SELECT @@ROWCOUNT AS __rc, @__ret AS __ret"
+        );
+    }
+
+    #[test]
+    fn param_types_are_lowercased_when_read() {
+        let hint = serde_json::json!({ "name": "n", "value": 5, "type": "TinyInt" });
+        let param: SerializedParam = serde_json::from_value(hint).unwrap();
+        assert_eq!(param.param_type.as_deref(), Some("tinyint"));
+        let untyped: SerializedParam =
+            serde_json::from_value(serde_json::json!({ "name": "n", "value": 5 })).unwrap();
+        assert_eq!(untyped.param_type, None);
+    }
+
+    #[test]
+    fn proc_call_captures_return_value() {
+        let params = vec![param("id"), param("@name")];
+        let (sql, order) = build_proc_call("dbo.sp_Test", &params);
+        assert_eq!(
+            sql,
+            "DECLARE @__ret INT;\nEXEC @__ret = dbo.sp_Test @id = @P1, @name = @P2;\n\
+             SELECT @@ROWCOUNT AS __rc, @__ret AS __ret"
+        );
+        assert_eq!(order, vec![0, 1]);
+    }
+
+    fn output_param(name: &str, ty: &str, value: serde_json::Value) -> SerializedParam {
+        SerializedParam {
+            name: name.to_string(),
+            value,
+            param_type: Some(ty.to_string()),
+            output: true,
+            precision: None,
+            scale: None,
+            srid: None,
+            length: None,
+        }
+    }
+
+    fn output_batch(
+        cmd: &SerializedCommand,
+        native_json: bool,
+    ) -> Result<(String, Vec<usize>, Vec<String>)> {
+        build_output_batch(cmd, native_json, &HashMap::new(), "__out_")
+    }
+
+    fn command(sql: &str, command_type: &str, params: Vec<SerializedParam>) -> SerializedCommand {
+        SerializedCommand {
+            sql: sql.to_string(),
+            params,
+            transaction_id: None,
+            command_timeout_ms: None,
+            command_type: command_type.to_string(),
+            stream_mode: None,
+            fetch_size: None,
+            return_rows: false,
+            temporal_mode: None,
+            column_case: None,
+            raw_binary: false,
+            capture_plan: None,
+            capture_statistics: false,
+            for_json: false,
+            compress: None,
+            compress_threshold: None,
+            paginate: None,
+            nocount: None,
+            rowcount: None,
+            return_identity: false,
+            identity_key: None,
+        }
+    }
+
+    #[test]
+    fn nocount_rejects_statements_that_must_start_a_batch() {
+        let leading = [
+            ("CREATE PROCEDURE p AS SELECT 1", "CREATE PROCEDURE"),
+            ("-- setup\n  create proc p AS SELECT 1", "CREATE PROC"),
+            (
+                "/* v */ CREATE OR ALTER VIEW v AS SELECT 1",
+                "CREATE OR ALTER VIEW",
+            ),
+            (
+                "ALTER FUNCTION f() RETURNS INT AS BEGIN RETURN 1 END",
+                "ALTER FUNCTION",
+            ),
+            (
+                "CREATE TRIGGER t ON x AFTER INSERT AS SELECT 1",
+                "CREATE TRIGGER",
+            ),
+            ("CREATE SCHEMA s", "CREATE SCHEMA"),
+        ];
+        for (sql, statement) in leading {
+            assert_eq!(batch_leading_statement(sql).as_deref(), Some(statement));
+            let err = with_nocount(sql.into(), Some(true))
+                .unwrap_err()
+                .to_string();
+            assert!(err.contains(statement), "{err}");
+        }
+        // Fine after other statements, and for objects that needn't lead
+        for sql in [
+            "CREATE TABLE t (id INT)",
+            "ALTER SCHEMA s TRANSFER dbo.t",
+            "CREATE PROCEDUREX",
+            "UPDATE t SET note = 'CREATE VIEW'",
+        ] {
+            assert_eq!(batch_leading_statement(sql), None, "{sql}");
+        }
+        assert!(with_nocount("CREATE VIEW v AS SELECT 1".into(), None).is_ok());
+    }
+
+    #[test]
+    fn nocount_wraps_and_picks_rowcount_mode() {
+        let mut cmd = command("UPDATE t SET x = 1", "text", vec![]);
+        assert_eq!(
+            with_nocount(cmd.sql.clone(), None).unwrap(),
+            "UPDATE t SET x = 1"
+        );
+        assert_eq!(cmd.rowcount(), RowCount::Total);
+        cmd.nocount = Some(true);
+        assert_eq!(cmd.rowcount(), RowCount::Last);
+        assert_eq!(
+            with_nocount(cmd.sql.clone(), cmd.nocount).unwrap(),
+            "DECLARE @__nocount INT = @@OPTIONS & 512;\nSET NOCOUNT ON;\n\
+             UPDATE t SET x = 1;\n\
+             IF @__nocount = 0 SET NOCOUNT OFF ELSE SET NOCOUNT ON;"
+        );
+        cmd.rowcount = Some(RowCount::Total);
+        assert_eq!(cmd.rowcount(), RowCount::Total);
+        assert!(cmd.require_last_rowcount().is_err());
+        let cmd: SerializedCommand = serde_json::from_str(
+            r#"{"sql":"x","command_type":"text","nocount":false,"rowcount":"last"}"#,
+        )
+        .unwrap();
+        assert_eq!(cmd.nocount, Some(false));
+        assert_eq!(cmd.rowcount, Some(RowCount::Last));
+    }
+
+    #[test]
+    fn identity_output_goes_after_target_and_columns() {
+        let at = |sql: &str| insert_output_offset(sql).map(|i| &sql[i..]);
+        assert_eq!(
+            at("INSERT INTO dbo.[Order Items] (Sku, [Qty)]) VALUES (@P1, 2)"),
+            Some(" VALUES (@P1, 2)")
+        );
+        assert_eq!(
+            at("-- add\ninsert Orders DEFAULT VALUES"),
+            Some(" DEFAULT VALUES")
+        );
+        assert_eq!(
+            at("INSERT INTO t WITH (TABLOCK) (a) SELECT ')' FROM s"),
+            Some(" SELECT ')' FROM s")
+        );
+        assert_eq!(at("UPDATE t SET a = 1"), None);
+        assert_eq!(at("INSERTED"), None);
+
+        let batch = build_identity_batch("INSERT INTO t (a) VALUES (@P1)", Some("Id")).unwrap();
+        assert_eq!(
+            batch,
+            "DECLARE @__ids TABLE (n INT IDENTITY, id SQL_VARIANT);\n\
+             INSERT INTO t (a) OUTPUT INSERTED.[Id] INTO @__ids (id) VALUES (@P1);\n\
+             SELECT @@ROWCOUNT AS __rc;\n\
+             SELECT id AS __identity FROM @__ids ORDER BY n;"
+        );
+        let sql = "INSERT INTO t (a) OUTPUT INSERTED.a VALUES (1)";
+        assert!(build_identity_batch(sql, Some("Id")).is_err());
+        let batch = build_identity_batch("INSERT INTO t DEFAULT VALUES", None).unwrap();
+        assert!(batch.ends_with("SELECT CAST(SCOPE_IDENTITY() AS BIGINT) AS __identity;"));
+    }
+
+    #[test]
+    fn capture_plan_deserializes_lowercase() {
+        let cmd: SerializedCommand = serde_json::from_str(
+            r#"{"sql":"SELECT 1","command_type":"text","capture_plan":"actual"}"#,
+        )
+        .unwrap();
+        assert_eq!(cmd.capture_plan, Some(PlanCapture::Actual));
+        let cmd: SerializedCommand =
+            serde_json::from_str(r#"{"sql":"SELECT 1","command_type":"text"}"#).unwrap();
+        assert_eq!(cmd.capture_plan, None);
+    }
+
+    #[test]
+    fn output_batch_parameterizes_proc_inputs() {
+        let cmd = command(
+            "sp_Test",
+            "stored_procedure",
+            vec![
+                param("id"),
+                output_param("total", "int", serde_json::Value::Null),
+            ],
+        );
+        let (batch, order, outputs) = output_batch(&cmd, false).unwrap();
+        assert_eq!(
+            batch,
+            "DECLARE @total INT;\n\
+             DECLARE @__ret INT;\n\
+             EXEC @__ret = sp_Test @id = @P1, @total = @total OUTPUT;\n\
+             SELECT @@ROWCOUNT AS __rc, @__ret AS __ret;\n\
+             SELECT @total AS [__out_total];"
+        );
+        assert_eq!(order, vec![0]);
+        assert_eq!(outputs, vec!["total".to_string()]);
+    }
+
+    #[test]
+    fn output_batch_binds_initial_output_value() {
+        let cmd = command(
+            "sp_Test",
+            "stored_procedure",
+            vec![
+                output_param("@counter", "bigint", serde_json::json!(5)),
+                param("name"),
+            ],
+        );
+        let (batch, order, _) = output_batch(&cmd, false).unwrap();
+        assert!(batch.starts_with("DECLARE @counter BIGINT = @P2;\n"));
+        assert!(batch.contains("@counter = @counter OUTPUT, @name = @P1"));
+        assert_eq!(order, vec![1, 0]);
+    }
+
+    #[test]
+    fn output_batch_text_command_keeps_output_locals() {
+        let cmd = command(
+            "SET @out = @a + 1",
+            "text",
+            vec![param("a"), output_param("out", "int", serde_json::Value::Null)],
+        );
+        let (batch, order, _) = output_batch(&cmd, false).unwrap();
+        assert_eq!(
+            batch,
+            "DECLARE @out INT;\nSET @out = @P1 + 1;\nSELECT @@ROWCOUNT AS __rc;\n\
+             SELECT @out AS [__out_out];"
+        );
+        assert_eq!(order, vec![0]);
+    }
+
+    #[test]
+    fn output_batch_declares_native_json_when_supported() {
+        let cmd = command(
+            "SET @doc = JSON_OBJECT('a': 1)",
+            "text",
+            vec![output_param("doc", "json", serde_json::Value::Null)],
+        );
+        let (batch, _, _) = output_batch(&cmd, true).unwrap();
+        assert!(batch.starts_with("DECLARE @doc JSON;\n"));
+        let (batch, _, _) = output_batch(&cmd, false).unwrap();
+        assert!(batch.starts_with("DECLARE @doc NVARCHAR(MAX);\n"));
+    }
+
+    #[test]
+    fn output_batch_sizes_and_infers_declarations() {
+        let mut code = output_param("code", "nvarchar", serde_json::Value::Null);
+        code.length = Some(20);
+        let mut total = output_param("total", "decimal", serde_json::Value::Null);
+        total.precision = Some(18);
+        total.scale = Some(2);
+        let mut count = output_param("count", "int", serde_json::json!(0));
+        count.param_type = None;
+        let cmd = command("sp_Totals", "stored_procedure", vec![code, total, count]);
+        let (batch, _, _) = output_batch(&cmd, false).unwrap();
+        assert!(batch.starts_with(
+            "DECLARE @code NVARCHAR(20);
+             DECLARE @total DECIMAL(18, 2);
+             DECLARE @count BIGINT = @P1;
+"
+        ));
+    }
+
+    #[test]
+    fn output_batch_declares_untyped_proc_outputs_by_the_procedure() {
+        let mut total = output_param("Total", "int", serde_json::Value::Null);
+        total.param_type = None;
+        let cmd = command("sp_Totals", "stored_procedure", vec![total]);
+        let proc_types = HashMap::from([("total".to_string(), "DECIMAL(18, 2)".to_string())]);
+        let (batch, _, names) = build_output_batch(&cmd, false, &proc_types, "__out_9f_").unwrap();
+        assert!(batch.starts_with("DECLARE @Total DECIMAL(18, 2);\n"));
+        assert!(batch.ends_with("SELECT @Total AS [__out_9f_Total];"));
+        assert_eq!(names, vec!["Total".to_string()]);
+    }
+
+    #[test]
+    fn output_row_needs_this_calls_prefix_and_names() {
+        let prefix = output_prefix();
+        assert_ne!(prefix, output_prefix());
+        let names = vec!["total".to_string()];
+        let column = |name: &str| Column::new(name, 0, "int");
+        let ours = column(&format!("{prefix}total"));
+        assert!(is_output_row(&[ours.clone()], &prefix, &names));
+        assert!(!is_output_row(&[column("__out_total")], &prefix, &names));
+        let user_set = [ours, column(&format!("{prefix}x"))];
+        assert!(!is_output_row(&user_set, &prefix, &names));
+    }
+
+    #[test]
+    fn output_values_convert_by_declared_type() {
+        let params = vec![
+            output_param("version", "rowversion", serde_json::Value::Null),
+            output_param("doc", "json", serde_json::Value::Null),
+        ];
+        let opts = SerializeOptions {
+            parse_json: true,
+            ..Default::default()
+        };
+        let bytes = SqlValue::Binary(vec![0, 0, 0, 0, 0, 0, 0x07, 0xD1].into());
+        let version = output_to_json(&params, "version", Some(bytes), "binary", &opts);
+        assert_eq!(version.unwrap(), "0x00000000000007D1");
+        let doc = SqlValue::String(r#"{"a":1}"#.into());
+        let doc = output_to_json(&params, "doc", Some(doc), "nvarchar", &opts);
+        assert_eq!(doc.unwrap(), serde_json::json!({ "a": 1 }));
+    }
+
+    #[test]
+    fn vector_params_cast_to_dimension_and_columns_decode() {
+        let params = vec![SerializedParam {
+            value: serde_json::json!([0.1, 0.2, 0.3]),
+            param_type: Some("vector".into()),
+            ..param("q")
+        }];
+        let sql = "SELECT VECTOR_DISTANCE('cosine', v, @q) FROM t";
+        let (sql, order) = rewrite_named_params(sql, &params);
+        let (batch, _) = bind_params(sql, &params, &order).unwrap();
+        assert!(batch.ends_with(", N'@P1 VECTOR(3)', @P1"));
+
+        let opts = SerializeOptions::default();
+        let text = Some(SqlValue::String("[0.1, 2, 30]".into()));
+        assert_eq!(value_to_json(text, "vector", &opts).unwrap(), serde_json::json!([0.1, 2.0, 30.0]));
+        let text = Some(SqlValue::String("[0.1]".into()));
+        assert_eq!(value_to_json(text, "nvarchar", &opts).unwrap(), serde_json::json!("[0.1]"));
+    }
+
+    #[test]
+    fn udt_columns_are_tagged_base64() {
+        assert_eq!(
+            udt_to_json("hierarchyid", &[0x58]),
+            Some(serde_json::json!({ "__type": "udt", "type": "hierarchyid", "value": "WA==" }))
+        );
+        assert_eq!(udt_to_json("Udt", &[0x58]).unwrap()["__type"], "udt");
+        for binary in ["varbinary", "VarBinary(max)", "BigVarBinary", "Image", "timestamp", ""] {
+            assert_eq!(udt_to_json(binary, &[0x58]), None, "{binary}");
+        }
+    }
+
+    #[test]
+    fn json_columns_parse_when_enabled() {
+        let doc = || Some(SqlValue::String(r#"{"a":[1,2]}"#.into()));
+        let mut opts = SerializeOptions::default();
+        assert_eq!(value_to_json(doc(), "json", &opts).unwrap(), serde_json::json!(r#"{"a":[1,2]}"#));
+        opts.parse_json = true;
+        assert_eq!(value_to_json(doc(), "json", &opts).unwrap(), serde_json::json!({ "a": [1, 2] }));
+        assert_eq!(
+            value_to_json(doc(), "nvarchar", &opts).unwrap(),
+            serde_json::json!(r#"{"a":[1,2]}"#)
+        );
+    }
+
+    #[test]
+    fn proc_call_without_params() {
+        let (sql, order) = build_proc_call("sp_NoArgs", &[]);
+        assert!(sql.contains("EXEC @__ret = sp_NoArgs;"));
+        assert!(order.is_empty());
+    }
+
+    #[test]
+    fn decimal_param_parses_exact_string() {
+        let p = decimal_param(serde_json::json!("12345678901234567890.123456"), None, None);
+        let d = parse_decimal("12345678901234567890.123456", &p).unwrap();
+        assert_eq!(d.to_string(), "12345678901234567890.123456");
+    }
+
+    #[test]
+    fn decimal_param_rounds_to_scale() {
+        let p = decimal_param(serde_json::Value::Null, Some(10), Some(2));
+        assert_eq!(parse_decimal("1.005", &p).unwrap().to_string(), "1.01");
+        assert_eq!(parse_decimal("-2.345", &p).unwrap().to_string(), "-2.35");
+    }
+
+    #[test]
+    fn decimal_param_rejects_precision_overflow() {
+        let p = decimal_param(serde_json::Value::Null, Some(5), Some(2));
+        assert!(parse_decimal("999.99", &p).is_ok());
+        assert!(parse_decimal("1000.00", &p).is_err());
+        assert!(parse_decimal("abc", &p).is_err());
+    }
+
+    #[test]
+    fn money_params_bind_exactly_at_four_places() {
+        let p = SerializedParam {
+            param_type: Some("money".into()),
+            ..param("price")
+        };
+        let sum = parse_decimal(&(0.1f64 + 0.2f64).to_string(), &p).unwrap();
+        assert_eq!(sum.to_string(), "0.3000");
+        assert!(parse_decimal("1000000000000000.0000", &p).is_err());
+        let small = SerializedParam {
+            param_type: Some("smallmoney".into()),
+            ..p
+        };
+        assert!(parse_decimal("214748.3647", &small).is_ok());
+        assert!(parse_decimal("2147483.6470", &small).is_err());
+        assert!(param_to_boxed(&SerializedParam {
+            value: serde_json::json!("19.99"),
+            ..small
+        })
+        .is_ok());
+    }
+
+    #[test]
+    fn decimal_param_rejects_invalid_size() {
+        let p = decimal_param(serde_json::Value::Null, Some(4), Some(6));
+        assert!(parse_decimal("0.1", &p).is_err());
+        let p = decimal_param(serde_json::Value::Null, Some(39), None);
+        assert!(parse_decimal("1", &p).is_err());
+    }
+
+    #[test]
+    fn sized_params_declare_their_type() {
+        let params = vec![
+            SerializedParam {
+                param_type: Some("varchar".into()),
+                length: Some(50),
+                ..param("code")
+            },
+            decimal_param(serde_json::json!(1), Some(18), Some(4)),
+            SerializedParam {
+                param_type: Some("nvarchar".into()),
+                length: Some(-1),
+                ..param("note")
+            },
+            SerializedParam {
+                value: serde_json::json!(7),
+                ..param("id")
+            },
+        ];
+        let sql = "SELECT 1 WHERE c = @code AND a = @amount AND n = @note AND i = @id";
+        let (sql, order) = rewrite_named_params(sql, &params);
+        assert_eq!(
+            sql,
+            "SELECT 1 WHERE c = @P1 AND a = @P2 AND n = @P3 AND i = @P4"
+        );
+        let (batch, values) = bind_params(sql, &params, &order).unwrap();
+        assert_eq!(
+            batch,
+            "EXEC sp_executesql N'SELECT 1 WHERE c = @P1 AND a = @P2 AND n = @P3 AND i = @P4', \
+             N'@P1 VARCHAR(50), @P2 DECIMAL(18, 4), @P3 NVARCHAR(MAX), @P4 INT', @P1, @P2, @P3, @P4"
+        );
+        assert_eq!(values.len(), 4);
+
+        // Without a declared type the batch goes as it is
+        let params = vec![SerializedParam {
+            value: serde_json::json!("it's"),
+            ..param("s")
+        }];
+        let (sql, order) = rewrite_named_params("SELECT 'x', @s", &params);
+        let (batch, _) = bind_params(sql, &params, &order).unwrap();
+        assert_eq!(batch, "SELECT 'x', @P1");
+        let params = vec![SerializedParam {
+            length: Some(5),
+            param_type: Some("nchar".into()),
+            ..params[0].clone()
+        }];
+        let (batch, _) = bind_params("SELECT 'x', @P1".into(), &params, &order).unwrap();
+        assert_eq!(
+            batch,
+            "EXEC sp_executesql N'SELECT ''x'', @P1', N'@P1 NCHAR(5)', @P1"
+        );
+
+        let cmd = command(
+            "sp_Test",
+            "stored_procedure",
+            vec![SerializedParam {
+                length: Some(20),
+                ..output_param("status", "nvarchar", serde_json::Value::Null)
+            }],
+        );
+        let (batch, _, _) = output_batch(&cmd, false).unwrap();
+        assert!(batch.starts_with("DECLARE @status NVARCHAR(20);\n"));
+    }
+
+    #[test]
+    fn sized_params_reject_values_over_their_length() {
+        let short = SerializedParam {
+            value: serde_json::json!("abc"),
+            param_type: Some("nvarchar".into()),
+            length: Some(3),
+            ..param("code")
+        };
+        assert!(param_to_boxed(&short).is_ok());
+        let long = SerializedParam {
+            value: serde_json::json!("abcd"),
+            ..short
+        };
+        let err = param_to_boxed(&long).err().unwrap().to_string();
+        assert!(err.contains("over its declared length 3"));
+    }
+
+    #[test]
+    fn decimal_serializes_as_string_by_default() {
+        let d = Decimal::from_str("0.10").unwrap();
+        let opts = SerializeOptions::default();
+        assert_eq!(decimal_to_json(d, &opts), serde_json::json!("0.10"));
+    }
+
+    #[test]
+    fn decimal_serializes_as_number_when_configured() {
+        let d = Decimal::from_str("19.99").unwrap();
+        let opts = SerializeOptions {
+            decimal_mode: DecimalMode::Number,
+            ..Default::default()
+        };
+        assert_eq!(decimal_to_json(d, &opts), serde_json::json!(19.99));
+    }
+
+    #[test]
+    fn datetime2_keeps_seven_fractional_digits() {
+        let dt = parse_datetime("2024-06-15T10:30:45.1234567").unwrap();
+        assert_eq!(dt.nanosecond(), 123_456_700);
+        assert_eq!(format_datetime(&dt), "2024-06-15T10:30:45.1234567");
+    }
+
+    #[test]
+    fn datetime_formats_as_strict_iso() {
+        let dt = parse_datetime("2024-06-15 10:30:45").unwrap();
+        assert_eq!(format_datetime(&dt), "2024-06-15T10:30:45.0000000");
+    }
+
+    #[test]
+    fn time7_truncates_to_100ns_ticks() {
+        let t: chrono::NaiveTime = "23:59:59.999999999".parse().unwrap();
+        assert_eq!(truncate_to_ticks(t).nanosecond(), 999_999_900);
+        assert_eq!(format_time(&t), "23:59:59.9999999");
+    }
+
+    #[test]
+    fn datetimeoffset_formats_with_offset() {
+        let dt: chrono::DateTime<chrono::FixedOffset> =
+            "2024-06-15T10:30:45.0000001+05:30".parse().unwrap();
+        assert_eq!(format_datetimeoffset(&dt), "2024-06-15T10:30:45.0000001+05:30");
+    }
+
+    #[test]
+    fn temporal_modes() {
+        let iso = || "2024-06-15T10:30:45.0000000".to_string();
+        let mut opts = SerializeOptions::default();
+        assert_eq!(
+            temporal_to_json("datetime2", iso(), Some(1718447445000), &opts),
+            serde_json::json!("2024-06-15T10:30:45.0000000")
+        );
+        opts.temporal_mode = TemporalMode::Epoch;
+        assert_eq!(
+            temporal_to_json("datetime2", iso(), Some(1718447445000), &opts),
+            serde_json::json!(1718447445000i64)
+        );
+        assert_eq!(
+            temporal_to_json("time", "10:30:45.0000000".into(), None, &opts),
+            serde_json::json!("10:30:45.0000000")
+        );
+        opts.temporal_mode = TemporalMode::Tagged;
+        assert_eq!(
+            temporal_to_json("datetime2", iso(), Some(1718447445000), &opts),
+            serde_json::json!({ "__type": "datetime2", "value": "2024-06-15T10:30:45.0000000" })
+        );
+    }
+
+    #[test]
+    fn tagged_datetime_uses_column_type() {
+        let opts = SerializeOptions {
+            temporal_mode: TemporalMode::Tagged,
+            ..Default::default()
+        };
+        let dt = chrono::NaiveDate::from_ymd_opt(2024, 6, 15)
+            .unwrap()
+            .and_hms_opt(10, 30, 0)
+            .unwrap();
+        let tag = |type_name| {
+            value_to_json(Some(SqlValue::DateTime(dt)), type_name, &opts).unwrap()["__type"].clone()
+        };
+        assert_eq!(tag("datetime"), "datetime");
+        assert_eq!(tag("smalldatetime"), "smalldatetime");
+        assert_eq!(tag("datetime2"), "datetime2");
+        assert_eq!(tag(""), "datetime2");
+    }
+
+    #[test]
+    fn column_names_are_recased_except_driver_columns() {
+        let camel = |name| column_key(name, ColumnCase::Camel);
+        assert_eq!(camel("OrderID"), "orderId");
+        assert_eq!(camel("order_id"), "orderId");
+        assert_eq!(camel("First Name"), "firstName");
+        assert_eq!(camel("URLPath"), "urlPath");
+        assert_eq!(camel("Line2Total"), "line2Total");
+        assert_eq!(camel("createdAt"), "createdAt");
+        assert_eq!(camel("__rc"), "__rc");
+        assert_eq!(camel("__$operation"), "__$operation");
+        assert_eq!(camel(""), "");
+        assert_eq!(column_key("OrderID", ColumnCase::Lower), "orderid");
+        assert_eq!(column_key("OrderID", ColumnCase::AsIs), "OrderID");
+
+        let mut cmd = command("SELECT 1", "text", vec![]);
+        cmd.column_case = Some(ColumnCase::Lower);
+        let opts = SerializeOptions {
+            column_case: ColumnCase::Camel,
+            ..Default::default()
+        };
+        assert_eq!(opts.for_command(&cmd).column_case, ColumnCase::Lower);
+    }
+
+    #[test]
+    fn unnamed_and_duplicate_columns_get_unique_keys() {
+        let keys = |names: &[&str]| unique_keys(names.iter().copied(), ColumnCase::AsIs);
+        assert_eq!(keys(&["", ""]), vec!["column0", "column1"]);
+        assert_eq!(
+            keys(&["Id", "", "Id", "Id"]),
+            vec!["Id", "column1", "Id_1", "Id_2"]
+        );
+        // A suffix never takes another column's name
+        assert_eq!(keys(&["a", "a", "a_1"]), vec!["a", "a_2", "a_1"]);
+        let keys = unique_keys(["OrderId", "order_id"].into_iter(), ColumnCase::Camel);
+        assert_eq!(keys, vec!["orderId", "orderId_1"]);
+    }
+
+    #[test]
+    fn for_json_fragments_join_into_one_document() {
+        let fragments = vec![
+            r#"[{"id":1,"name":"Wid"#.to_string(),
+            r#"get"}]"#.to_string(),
+        ];
+        assert_eq!(
+            for_json_document(&fragments).unwrap(),
+            r#"[{"id":1,"name":"Widget"}]"#
+        );
+        assert_eq!(for_json_document(&[]).unwrap(), "null");
+        assert!(for_json_document(&fragments[..1]).is_err());
+    }
+
+    #[test]
+    fn command_temporal_mode_overrides_handle() {
+        let mut cmd = command("SELECT 1", "text", vec![]);
+        let opts = SerializeOptions::default();
+        assert_eq!(opts.for_command(&cmd).temporal_mode, TemporalMode::Iso);
+        cmd.temporal_mode = Some(TemporalMode::Tagged);
+        assert_eq!(opts.for_command(&cmd).temporal_mode, TemporalMode::Tagged);
+        assert!(!opts.for_command(&cmd).raw_binary);
+        cmd.raw_binary = true;
+        assert!(opts.for_command(&cmd).raw_binary);
+    }
+
+    #[test]
+    fn bigint_policies() {
+        let big = 1i64 << 60;
+        let mut opts = SerializeOptions::default();
+        assert_eq!(bigint_to_json(42, &opts), serde_json::json!(42));
+        assert_eq!(bigint_to_json(big, &opts), serde_json::json!(big.to_string()));
+        opts.bigint_mode = BigIntMode::AlwaysString;
+        assert_eq!(bigint_to_json(42, &opts), serde_json::json!("42"));
+        opts.bigint_mode = BigIntMode::TaggedBigInt;
+        assert_eq!(
+            bigint_to_json(-7, &opts),
+            serde_json::json!({ "__type": "bigint", "value": "-7" })
+        );
+    }
+
+    #[test]
+    fn sql_variant_values_are_tagged_with_base_type() {
+        let opts = SerializeOptions::default();
+        assert_eq!(
+            value_to_json(Some(SqlValue::Int(5)), "sql_variant", &opts).unwrap(),
+            serde_json::json!({ "__type": "sql_variant", "type": "int", "value": 5 })
+        );
+        assert_eq!(
+            value_to_json(Some(SqlValue::String("x".into())), "SQL_VARIANT", &opts).unwrap(),
+            serde_json::json!({ "__type": "sql_variant", "type": "nvarchar", "value": "x" })
+        );
+        assert_eq!(value_to_json(Some(SqlValue::Null), "sql_variant", &opts).unwrap(), serde_json::Value::Null);
+    }
+
+    #[test]
+    fn xml_params_drop_declared_encoding_and_cast() {
+        assert_eq!(
+            strip_xml_encoding(r#"<?xml version="1.0" encoding="utf-8"?><a>é</a>"#),
+            r#"<?xml version="1.0"?><a>é</a>"#
+        );
+        assert_eq!(
+            strip_xml_encoding("<?xml version='1.0' encoding='UTF-8' standalone='yes'?><a/>"),
+            "<?xml version='1.0' standalone='yes'?><a/>"
+        );
+        assert_eq!(strip_xml_encoding("<a encoding=\"x\"/>"), "<a encoding=\"x\"/>");
+
+        let params = vec![SerializedParam { param_type: Some("xml".into()), ..param("doc") }];
+        let (sql, order) = rewrite_named_params("SELECT @doc.value('(/a)[1]', 'int')", &params);
+        let (batch, _) = bind_params(sql, &params, &order).unwrap();
+        assert_eq!(
+            batch,
+            "EXEC sp_executesql N'SELECT @P1.value(''(/a)[1]'', ''int'')', N'@P1 XML', @P1"
+        );
+    }
+
+    #[test]
+    fn rowversion_round_trips_as_hex() {
+        let bytes = parse_rowversion("0x00000000000007D1").unwrap();
+        assert_eq!(bytes, vec![0, 0, 0, 0, 0, 0, 0x07, 0xD1]);
+        assert_eq!(parse_rowversion("00000000000007d1").unwrap(), bytes);
+        assert_eq!(
+            rowversion_to_json("timestamp", &bytes),
+            Some(serde_json::json!("0x00000000000007D1"))
+        );
+        assert_eq!(rowversion_to_json("varbinary", &bytes), None);
+        assert!(parse_rowversion("0x07D1").is_err());
+        assert!(parse_rowversion("0x00000000000007DZ").is_err());
+    }
 }
@@ -0,0 +1,128 @@
+//! Read-only snapshot transactions: check the database allows snapshot
+//! isolation before beginning one, and spot statements that write so they
+//! can be rejected before they reach the server.
+
+use mssql_client::{Client, Ready, Row, SqlValue};
+
+use crate::error::{MssqlError, Result};
+use crate::query::{closing, is_ident_byte, skip_trivia};
+
+/// Words that start a statement that writes data, schema or permissions.
+const WRITE_KEYWORDS: &[&str] = &[
+    "INSERT",
+    "UPDATE",
+    "DELETE",
+    "MERGE",
+    "TRUNCATE",
+    "CREATE",
+    "ALTER",
+    "DROP",
+    "GRANT",
+    "REVOKE",
+    "DENY",
+    "BULK",
+    "UPDATETEXT",
+    "WRITETEXT",
+];
+
+/// Fail unless the current database has `ALLOW_SNAPSHOT_ISOLATION ON`.
+pub async fn check_snapshot(client: &mut Client<Ready>) -> Result<()> {
+    let rows = client
+        .query(
+            "SELECT DB_NAME(), snapshot_isolation_state FROM sys.databases \
+             WHERE database_id = DB_ID()",
+            &[],
+        )
+        .await
+        .map_err(MssqlError::from)?;
+    for row in rows {
+        let row: Row = row.map_err(MssqlError::from)?;
+        let name = match row.get_raw(0) {
+            Some(SqlValue::String(name)) => name,
+            _ => String::new(),
+        };
+        // 1 is ON; 3 (turning on) still waits on older transactions
+        return match row.get_raw(1) {
+            Some(SqlValue::TinyInt(1)) => Ok(()),
+            _ => Err(MssqlError::Transaction(format!(
+                "Snapshot isolation is not enabled on database '{name}' \
+                 (ALTER DATABASE [{name}] SET ALLOW_SNAPSHOT_ISOLATION ON)"
+            ))),
+        };
+    }
+    Err(MssqlError::Transaction(
+        "Cannot determine snapshot isolation state".into(),
+    ))
+}
+
+/// The first write keyword in `sql`, skipping strings, quoted names,
+/// comments, variables and qualified names. `SELECT ... INTO` a table counts;
+/// `FETCH ... INTO @var` doesn't. Procedures called with `EXEC` aren't
+/// inspected.
+pub fn write_keyword(sql: &str) -> Option<&'static str> {
+    let b = sql.as_bytes();
+    let mut i = 0;
+    let mut prev = 0u8;
+    loop {
+        i = skip_trivia(b, i);
+        let c = *b.get(i)?;
+        let start = i;
+        i = match c {
+            b'\'' => closing(b, i, b'\'').unwrap_or(b.len()),
+            b'[' => closing(b, i, b']').unwrap_or(b.len()),
+            b'"' => closing(b, i, b'"').unwrap_or(b.len()),
+            c if is_ident_byte(c) || c == b'@' || c == b'#' => {
+                i += 1;
+                while i < b.len() && is_ident_byte(b[i]) {
+                    i += 1;
+                }
+                let word = &b[start..i];
+                if prev != b'.' && c != b'@' && c != b'#' {
+                    if let Some(kw) = WRITE_KEYWORDS
+                        .iter()
+                        .find(|kw| word.eq_ignore_ascii_case(kw.as_bytes()))
+                    {
+                        return Some(kw);
+                    }
+                    let into_var = b.get(skip_trivia(b, i)) == Some(&b'@');
+                    if word.eq_ignore_ascii_case(b"INTO") && !into_var {
+                        return Some("SELECT INTO");
+                    }
+                }
+                i
+            }
+            _ => i + 1,
+        };
+        prev = c;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_writes_outside_strings_comments_and_names() {
+        let reads = [
+            "SELECT * FROM Orders WHERE note = 'please DELETE me'",
+            "SELECT [Update], \"Insert\" FROM t -- DROP TABLE t",
+            "/* MERGE */ SELECT o.Delete FROM Orders o",
+            "DECLARE @update INT; FETCH NEXT FROM c INTO @update",
+            "SELECT UpdatedAt FROM #delete",
+            "EXEC dbo.ReportSales @year = 2024",
+        ];
+        for sql in reads {
+            assert_eq!(write_keyword(sql), None, "{sql}");
+        }
+        let writes = [
+            ("update Orders SET n = 1", "UPDATE"),
+            ("WITH x AS (SELECT 1 AS n) DELETE FROM x", "DELETE"),
+            ("SELECT 1;\nINSERT INTO t VALUES (N'x')", "INSERT"),
+            ("SELECT * INTO #copy FROM Orders", "SELECT INTO"),
+            ("TRUNCATE TABLE Log", "TRUNCATE"),
+        ];
+        for (sql, kw) in writes {
+            assert_eq!(write_keyword(sql), Some(kw), "{sql}");
+        }
+    }
+}
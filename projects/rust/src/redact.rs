@@ -0,0 +1,251 @@
+//! Redaction for text that leaves the driver through logs, the slow query
+//! log and span attributes: SQL literals and credential-looking values are
+//! masked unless redaction has been switched off for local debugging.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static ENABLED: AtomicBool = AtomicBool::new(true);
+
+pub const MASK: &str = "***";
+
+/// Keys whose values are masked in free text (`Password=...`,
+/// `"client_secret":"..."`), matched case-insensitively on word boundaries.
+const SECRET_KEYS: &[&str] = &[
+    "password",
+    "pwd",
+    "secret",
+    "client_secret",
+    "token",
+    "access_token",
+    "refresh_token",
+    "accountkey",
+    "sharedaccesskey",
+    "sig",
+    "apikey",
+    "api_key",
+    "bearer",
+];
+
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::SeqCst);
+}
+
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// SQL text for output: literals masked, cut to `max_chars` characters.
+pub fn statement(sql: &str, max_chars: usize) -> String {
+    let masked = if enabled() {
+        mask_literals(sql)
+    } else {
+        sql.to_string()
+    };
+    masked.chars().take(max_chars).collect()
+}
+
+/// Free text (log messages, errors) with secret values masked.
+pub fn text(s: String) -> String {
+    if enabled() {
+        mask_secrets(&s)
+    } else {
+        s
+    }
+}
+
+/// Replace string (`'...'`, `N'...'`) and binary (`0x...`) literals with
+/// `***`. Identifiers, comments and numbers are kept.
+fn mask_literals(sql: &str) -> String {
+    let mut out = String::with_capacity(sql.len());
+    let mut chars = sql.chars().peekable();
+    let mut prev = ' ';
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                // '' inside a literal is an escaped quote
+                while let Some(c) = chars.next() {
+                    if c == '\'' && chars.next_if_eq(&'\'').is_none() {
+                        break;
+                    }
+                }
+                out.push('\'');
+                out.push_str(MASK);
+                out.push('\'');
+            }
+            '[' | '"' => {
+                let close = if c == '[' { ']' } else { '"' };
+                out.push(c);
+                for c in chars.by_ref() {
+                    out.push(c);
+                    if c == close {
+                        break;
+                    }
+                }
+            }
+            '-' if chars.peek() == Some(&'-') => {
+                out.push(c);
+                for c in chars.by_ref() {
+                    out.push(c);
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                out.push(c);
+                out.push(chars.next().unwrap());
+                let mut last = ' ';
+                for c in chars.by_ref() {
+                    out.push(c);
+                    if last == '*' && c == '/' {
+                        break;
+                    }
+                    last = c;
+                }
+            }
+            '0' if !is_word(prev) && matches!(chars.peek(), Some('x' | 'X')) => {
+                out.push(c);
+                out.push(chars.next().unwrap());
+                while chars.next_if(|c| c.is_ascii_hexdigit()).is_some() {}
+                out.push_str(MASK);
+            }
+            _ => out.push(c),
+        }
+        prev = c;
+    }
+    out
+}
+
+/// Mask the value after each secret key: `key=value`, `"key": "value"` or
+/// `Bearer value`. A colon only separates quoted (JSON) keys, so prose like
+/// `token: timed out` is left alone.
+fn mask_secrets(s: &str) -> String {
+    let lower = s.to_ascii_lowercase().into_bytes();
+    let bytes = s.as_bytes();
+    let mut out = String::with_capacity(s.len());
+    let mut copied = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        let before = i.checked_sub(1).map(|p| bytes[p]);
+        let key = SECRET_KEYS.iter().find(|key| {
+            lower[i..].starts_with(key.as_bytes())
+                && !before.is_some_and(|b| is_word(b as char))
+                && !bytes
+                    .get(i + key.len())
+                    .is_some_and(|&b| is_word(b as char))
+        });
+        let Some(key) = key else {
+            i += 1;
+            continue;
+        };
+        let separator = if *key == "bearer" {
+            Separator::Space
+        } else if matches!(before, Some(b'"' | b'\'')) {
+            Separator::EqualsOrColon
+        } else {
+            Separator::Equals
+        };
+        let Some((start, end)) = value_span(bytes, i + key.len(), separator) else {
+            i += key.len();
+            continue;
+        };
+        out.push_str(&s[copied..start]);
+        out.push_str(MASK);
+        copied = end;
+        i = end;
+    }
+    out.push_str(&s[copied..]);
+    out
+}
+
+enum Separator {
+    Equals,
+    EqualsOrColon,
+    Space,
+}
+
+/// Byte range of the value following a key that ends at `pos`.
+fn value_span(bytes: &[u8], mut pos: usize, separator: Separator) -> Option<(usize, usize)> {
+    let skip_spaces = |mut p: usize| {
+        while bytes.get(p).is_some_and(|b| *b == b' ') {
+            p += 1;
+        }
+        p
+    };
+    match separator {
+        Separator::Space => pos = skip_spaces(pos),
+        Separator::Equals | Separator::EqualsOrColon => {
+            // closing quote of a JSON key
+            if matches!(bytes.get(pos), Some(b'"' | b'\'')) {
+                pos += 1;
+            }
+            pos = skip_spaces(pos);
+            match bytes.get(pos) {
+                Some(b'=') => {}
+                Some(b':') if matches!(separator, Separator::EqualsOrColon) => {}
+                _ => return None,
+            }
+            pos = skip_spaces(pos + 1);
+        }
+    }
+    let start = pos;
+    let end = match bytes.get(pos) {
+        Some(&q @ (b'"' | b'\'')) => {
+            let close = bytes[pos + 1..].iter().position(|&b| b == q)?;
+            return Some((pos + 1, pos + 1 + close));
+        }
+        Some(_) => bytes[pos..]
+            .iter()
+            .position(|b| matches!(b, b';' | b'&' | b',' | b' ' | b'"' | b'\'' | b'}' | b'\n'))
+            .map_or(bytes.len(), |n| pos + n),
+        None => return None,
+    };
+    (end > start).then_some((start, end))
+}
+
+fn is_word(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn masks_sql_literals() {
+        assert_eq!(
+            mask_literals("SELECT * FROM [it's] WHERE pw = N'a''b' AND k = 0xDEAD AND n = 10"),
+            "SELECT * FROM [it's] WHERE pw = N'***' AND k = 0x*** AND n = 10"
+        );
+        assert_eq!(
+            mask_literals("EXEC p @x -- don't\nSELECT 'x'"),
+            "EXEC p @x -- don't\nSELECT '***'"
+        );
+        assert_eq!(mask_literals("SELECT a10x FROM t"), "SELECT a10x FROM t");
+        assert_eq!(mask_literals("/* it's */ SELECT 1"), "/* it's */ SELECT 1");
+    }
+
+    #[test]
+    fn masks_secret_values() {
+        assert_eq!(
+            mask_secrets("Server=db;User Id=sa;Password=hunter2;Database=x"),
+            "Server=db;User Id=sa;Password=***;Database=x"
+        );
+        assert_eq!(
+            mask_secrets(r#"{"client_secret": "abc", "tenant":"t"}"#),
+            r#"{"client_secret": "***", "tenant":"t"}"#
+        );
+        assert_eq!(
+            mask_secrets("Authorization: Bearer eyJhbGci.x.y failed"),
+            "Authorization: Bearer *** failed"
+        );
+        assert_eq!(
+            mask_secrets("token_expires_on=5, passwords are fine"),
+            "token_expires_on=5, passwords are fine"
+        );
+        assert_eq!(
+            mask_secrets("Failed to get token: timed out"),
+            "Failed to get token: timed out"
+        );
+    }
+}
@@ -0,0 +1,103 @@
+//! Row buffers for bulk reads. Rows are written one after another as JSON
+//! objects into a buffer the connection keeps between queries, with an index
+//! of where each row starts, so a large result is never gathered into one
+//! JSON array string. The caller copies both out with `mssql_read_rows` into
+//! buffers it keeps and grows itself.
+
+use crate::error::{MssqlError, Result};
+
+/// Capacity kept between queries; a larger buffer is shrunk back to this.
+pub const RETAIN_BYTES: usize = 64 << 20;
+
+#[derive(Default)]
+pub struct RowBuffer {
+    data: Vec<u8>,
+    /// Start of each row in `data`.
+    starts: Vec<u32>,
+}
+
+impl RowBuffer {
+    /// Empty the buffer for the next query, keeping its capacity up to
+    /// `RETAIN_BYTES`.
+    pub fn clear(&mut self) {
+        self.data.clear();
+        self.data.shrink_to(RETAIN_BYTES);
+        self.starts.clear();
+        self.starts.shrink_to(RETAIN_BYTES / 64);
+    }
+
+    pub fn push(&mut self, row: &serde_json::Value) -> Result<()> {
+        let start = u32::try_from(self.data.len()).map_err(|_| too_large())?;
+        serde_json::to_writer(&mut self.data, row).map_err(|e| MssqlError::Query(e.to_string()))?;
+        if u32::try_from(self.data.len()).is_err() {
+            return Err(too_large());
+        }
+        self.starts.push(start);
+        Ok(())
+    }
+
+    pub fn rows(&self) -> usize {
+        self.starts.len()
+    }
+
+    pub fn bytes(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Memory the buffer holds on to, used or not.
+    pub fn capacity(&self) -> usize {
+        self.data.capacity() + self.starts.capacity() * std::mem::size_of::<u32>()
+    }
+
+    /// `{"rows": n, "length": bytes}`: what the caller's buffers must hold.
+    /// The offset index has one more entry than there are rows.
+    pub fn header(&self) -> String {
+        serde_json::json!({ "rows": self.rows(), "length": self.bytes() }).to_string()
+    }
+
+    /// Copy the rows into `data` and their offsets into `offsets`, ending with
+    /// the total length so row `i` is `data[offsets[i]..offsets[i + 1]]`.
+    /// Returns the row count, or None if either buffer is too small.
+    pub fn copy_to(&self, data: &mut [u8], offsets: &mut [u32]) -> Option<usize> {
+        let n = self.rows();
+        if data.len() < self.data.len() || offsets.len() <= n {
+            return None;
+        }
+        data[..self.data.len()].copy_from_slice(&self.data);
+        offsets[..n].copy_from_slice(&self.starts);
+        offsets[n] = self.data.len() as u32;
+        Some(n)
+    }
+}
+
+fn too_large() -> MssqlError {
+    MssqlError::Query("Result is over 4 GiB; read it with a stream instead".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn indexes_each_row_and_copies_into_caller_buffers() {
+        let mut buf = RowBuffer::default();
+        buf.push(&json!({ "id": 1, "name": "a" })).unwrap();
+        buf.push(&json!({ "id": 2, "name": null })).unwrap();
+        assert_eq!(buf.header(), r#"{"length":39,"rows":2}"#);
+
+        let mut data = vec![0; 39];
+        assert_eq!(buf.copy_to(&mut data, &mut [0; 2]), None);
+        assert_eq!(buf.copy_to(&mut data[..38], &mut [0; 3]), None);
+        let mut offsets = [0; 3];
+        assert_eq!(buf.copy_to(&mut data, &mut offsets), Some(2));
+        assert_eq!(offsets, [0, 19, 39]);
+        let row: serde_json::Value = serde_json::from_slice(&data[19..39]).unwrap();
+        assert_eq!(row, json!({ "id": 2, "name": null }));
+
+        buf.clear();
+        assert_eq!(buf.header(), r#"{"length":0,"rows":0}"#);
+        assert_eq!(buf.copy_to(&mut [], &mut offsets), Some(0));
+        assert_eq!(offsets[0], 0);
+    }
+}
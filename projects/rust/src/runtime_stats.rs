@@ -0,0 +1,65 @@
+//! Tokio runtime stats for diagnostics, to tell a slow server from a
+//! starved runtime: worker threads, tasks alive and queued, and how many FFI
+//! callers are blocked waiting on `block_on`.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use tokio::runtime::Runtime;
+
+static BLOCKED: AtomicUsize = AtomicUsize::new(0);
+
+/// A caller blocked in `block_on`; dropping it counts the caller out.
+pub struct Blocked(());
+
+impl Drop for Blocked {
+    fn drop(&mut self) {
+        BLOCKED.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Count a caller into `block_on` until the guard is dropped.
+pub fn blocked() -> Blocked {
+    BLOCKED.fetch_add(1, Ordering::Relaxed);
+    Blocked(())
+}
+
+/// `{"workers", "blocked_callers", "alive_tasks", "queued_tasks",
+/// "spawned_tasks", "busy_ms"}`. `spawned_tasks` is only counted in builds
+/// with `--cfg tokio_unstable`, and is null otherwise.
+pub fn snapshot(rt: &Runtime) -> serde_json::Value {
+    let metrics = rt.metrics();
+    let busy: Duration = (0..metrics.num_workers())
+        .map(|worker| metrics.worker_total_busy_duration(worker))
+        .sum();
+    #[cfg(tokio_unstable)]
+    let spawned = Some(metrics.spawned_tasks_count());
+    #[cfg(not(tokio_unstable))]
+    let spawned: Option<u64> = None;
+    serde_json::json!({
+        "workers": metrics.num_workers(),
+        "blocked_callers": BLOCKED.load(Ordering::Relaxed),
+        "alive_tasks": metrics.num_alive_tasks(),
+        "queued_tasks": metrics.global_queue_depth(),
+        "spawned_tasks": spawned,
+        "busy_ms": busy.as_millis() as u64,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_workers_and_blocked_callers() {
+        let rt = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(2)
+            .build()
+            .unwrap();
+        let guard = blocked();
+        let stats = snapshot(&rt);
+        assert_eq!(stats["workers"], 2);
+        assert!(stats["blocked_callers"].as_u64().unwrap() >= 1);
+        drop(guard);
+    }
+}
@@ -0,0 +1,89 @@
+//! Graceful shutdown: FFI operations that run on the runtime register as in
+//! flight, so `mssql_close_all_graceful` can refuse new ones and wait for the
+//! running ones before dropping handles.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Condvar, Mutex};
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::error::{MssqlError, Result};
+
+static CLOSING: AtomicBool = AtomicBool::new(false);
+static IN_FLIGHT: Mutex<usize> = Mutex::new(0);
+static IDLE: Condvar = Condvar::new();
+
+/// An operation in flight; finishing it is dropping the guard.
+pub struct Operation(());
+
+impl Drop for Operation {
+    fn drop(&mut self) {
+        let mut count = IN_FLIGHT.lock().unwrap();
+        *count -= 1;
+        if *count == 0 {
+            IDLE.notify_all();
+        }
+    }
+}
+
+/// What a close found still open.
+#[derive(Serialize, Debug, Default)]
+pub struct Summary {
+    /// Operations that had not finished when the wait timed out.
+    pub in_flight: usize,
+    pub pools: usize,
+    pub connections: usize,
+    pub cursors: usize,
+    pub blobs: usize,
+    pub filestreams: usize,
+    pub subscriptions: usize,
+}
+
+/// Register an operation, or fail if a graceful close has started.
+pub fn enter() -> Result<Operation> {
+    let mut count = IN_FLIGHT.lock().unwrap();
+    // Checked under the lock so `drain` never misses an operation that
+    // slipped in after the flag was set.
+    if CLOSING.load(Ordering::SeqCst) {
+        return Err(MssqlError::Connection("Driver is shutting down".into()));
+    }
+    *count += 1;
+    Ok(Operation(()))
+}
+
+/// Stop accepting operations and wait up to `timeout` for the running ones.
+/// Returns how many were still running when the wait ended.
+pub fn drain(timeout: Duration) -> usize {
+    let count = IN_FLIGHT.lock().unwrap();
+    CLOSING.store(true, Ordering::SeqCst);
+    let (count, _) = IDLE
+        .wait_timeout_while(count, timeout, |count| *count > 0)
+        .unwrap();
+    *count
+}
+
+/// Accept operations again once the handles have been cleaned up.
+pub fn reopen() {
+    let _count = IN_FLIGHT.lock().unwrap();
+    CLOSING.store(false, Ordering::SeqCst);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drain_waits_for_running_operations_and_refuses_new_ones() {
+        let op = enter().unwrap();
+        let worker = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(50));
+            drop(op);
+        });
+        assert_eq!(drain(Duration::from_secs(5)), 0);
+        assert!(enter().is_err());
+        reopen();
+        worker.join().unwrap();
+        drop(enter().unwrap());
+    }
+}
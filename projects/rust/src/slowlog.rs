@@ -0,0 +1,118 @@
+//! Slow query log: commands that take longer than their handle's
+//! `slow_query_ms` are kept in a bounded in-memory ring, newest last.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use lazy_static::lazy_static;
+use serde::Serialize;
+
+use crate::handle::ConnHandle;
+
+/// Entries kept before the oldest are dropped.
+const CAPACITY: usize = 256;
+/// SQL text kept per entry, in characters (literals masked).
+const SQL_PREFIX_CHARS: usize = 200;
+
+lazy_static! {
+    static ref SLOW_QUERIES: Mutex<VecDeque<SlowQuery>> =
+        Mutex::new(VecDeque::with_capacity(CAPACITY));
+}
+
+/// One command that exceeded the threshold.
+#[derive(Serialize, Clone, Debug)]
+pub struct SlowQuery {
+    /// `query`, `execute`, `exec`, `script` or `bulk`.
+    pub kind: &'static str,
+    /// Start of the SQL text (`INSERT BULK <table>` for bulk loads).
+    pub sql: String,
+    pub duration_ms: u64,
+    /// Rows returned or affected; null when the command failed.
+    pub rows: Option<u64>,
+    pub conn_id: u64,
+    pub pool_id: Option<u64>,
+    /// The config's label and the connection's tag.
+    pub label: Option<String>,
+    pub tag: Option<String>,
+    /// Unix time in milliseconds when the command finished.
+    pub finished_at_ms: u64,
+}
+
+/// Record the command if it ran longer than the handle's threshold.
+/// `rows` is only evaluated for slow commands.
+pub fn record(
+    conn: &ConnHandle,
+    conn_id: u64,
+    kind: &'static str,
+    sql: &str,
+    started: Instant,
+    rows: impl FnOnce() -> Option<u64>,
+) {
+    let Some(threshold) = conn.slow_query_ms else {
+        return;
+    };
+    let duration_ms = started.elapsed().as_millis() as u64;
+    if duration_ms < threshold {
+        return;
+    }
+    let entry = SlowQuery {
+        kind,
+        sql: crate::redact::statement(sql, SQL_PREFIX_CHARS),
+        duration_ms,
+        rows: rows(),
+        conn_id,
+        pool_id: conn.pool_id,
+        label: conn.label.clone(),
+        tag: conn.tag(),
+        finished_at_ms: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or_default(),
+    };
+    let context = conn.context().map(|c| format!(" [{c}]"));
+    crate::debug::warn_log!(
+        target: "mssqlts::query",
+        "Slow {} on conn {}{} ({} ms): {}",
+        entry.kind,
+        conn_id,
+        context.unwrap_or_default(),
+        duration_ms,
+        entry.sql
+    );
+    push(entry);
+}
+
+fn push(entry: SlowQuery) {
+    let mut log = SLOW_QUERIES.lock().unwrap();
+    if log.len() == CAPACITY {
+        log.pop_front();
+    }
+    log.push_back(entry);
+}
+
+/// The logged commands, oldest first.
+pub fn snapshot() -> Vec<SlowQuery> {
+    SLOW_QUERIES.lock().unwrap().iter().cloned().collect()
+}
+
+/// Row count from a non-query or exec (`{"rowsAffected":n,...}`) result.
+pub fn rows_in(json: &str) -> Option<u64> {
+    #[derive(serde::Deserialize)]
+    struct Affected {
+        #[serde(rename = "rowsAffected")]
+        rows_affected: Option<u64>,
+    }
+    serde_json::from_str::<Affected>(json).ok()?.rows_affected
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_in_reads_affected() {
+        assert_eq!(rows_in(r#"{"rowsAffected":5,"rows":[]}"#), Some(5));
+        assert_eq!(rows_in(r#"{"rows":[],"plans":[]}"#), None);
+    }
+}
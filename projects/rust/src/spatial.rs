@@ -0,0 +1,323 @@
+//! Decoder for SQL Server's native geometry/geography serialization
+//! (MS-SSCLRT) into WKT, so spatial columns reach JS as readable text.
+
+const HAS_Z: u8 = 0x01;
+const HAS_M: u8 = 0x02;
+const IS_SINGLE_POINT: u8 = 0x08;
+const IS_SINGLE_LINE_SEGMENT: u8 = 0x10;
+
+/// A decoded spatial value.
+#[derive(Debug, PartialEq)]
+pub struct Spatial {
+    pub srid: i32,
+    pub wkt: String,
+}
+
+struct Shape {
+    parent: i32,
+    figure: i32,
+    kind: u8,
+}
+
+struct Geo {
+    /// x, y, z, m — z/m are NaN when absent.
+    points: Vec<[f64; 4]>,
+    has_z: bool,
+    has_m: bool,
+    figures: Vec<usize>,
+    shapes: Vec<Shape>,
+}
+
+struct Reader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn take<const N: usize>(&mut self) -> Option<[u8; N]> {
+        let bytes = self.buf.get(self.pos..self.pos + N)?;
+        self.pos += N;
+        bytes.try_into().ok()
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.take::<1>().map(|b| b[0])
+    }
+
+    fn i32(&mut self) -> Option<i32> {
+        self.take().map(i32::from_le_bytes)
+    }
+
+    fn f64(&mut self) -> Option<f64> {
+        self.take().map(f64::from_le_bytes)
+    }
+
+    /// Read an element count, rejecting counts the remaining bytes can't hold.
+    fn count(&mut self, min_size: usize) -> Option<usize> {
+        let n = self.take().map(u32::from_le_bytes)? as usize;
+        (n.checked_mul(min_size)? <= self.buf.len() - self.pos).then_some(n)
+    }
+}
+
+/// Decode a serialized geometry (x, y) or geography (lat, long) value.
+/// Returns None for malformed input or curve types (version 2 arcs).
+pub fn decode(bytes: &[u8], geography: bool) -> Option<Spatial> {
+    let mut r = Reader { buf: bytes, pos: 0 };
+    let srid = r.i32()?;
+    let version = r.u8()?;
+    if version != 1 && version != 2 {
+        return None;
+    }
+    let props = r.u8()?;
+    let has_z = props & HAS_Z != 0;
+    let has_m = props & HAS_M != 0;
+
+    let n_points = if props & IS_SINGLE_POINT != 0 {
+        1
+    } else if props & IS_SINGLE_LINE_SEGMENT != 0 {
+        2
+    } else {
+        r.count(16)?
+    };
+
+    let mut points = Vec::with_capacity(n_points);
+    for _ in 0..n_points {
+        let (a, b) = (r.f64()?, r.f64()?);
+        // Geography stores latitude first; WKT is longitude first.
+        let (x, y) = if geography { (b, a) } else { (a, b) };
+        points.push([x, y, f64::NAN, f64::NAN]);
+    }
+    if has_z {
+        for p in points.iter_mut() {
+            p[2] = r.f64()?;
+        }
+    }
+    if has_m {
+        for p in points.iter_mut() {
+            p[3] = r.f64()?;
+        }
+    }
+
+    let (figures, shapes) = if props & (IS_SINGLE_POINT | IS_SINGLE_LINE_SEGMENT) != 0 {
+        let kind = if n_points == 1 { 1 } else { 2 };
+        (vec![0], vec![Shape { parent: -1, figure: 0, kind }])
+    } else {
+        let n_figures = r.count(5)?;
+        let mut figures = Vec::with_capacity(n_figures);
+        for _ in 0..n_figures {
+            let _attribute = r.u8()?;
+            figures.push(usize::try_from(r.i32()?).ok()?.min(n_points));
+        }
+        let n_shapes = r.count(9)?;
+        let mut shapes = Vec::with_capacity(n_shapes);
+        for _ in 0..n_shapes {
+            let parent = r.i32()?;
+            let figure = r.i32()?;
+            let kind = r.u8()?;
+            // Circular strings, compound curves and curve polygons need arc segments
+            if (8..=10).contains(&kind) {
+                return None;
+            }
+            shapes.push(Shape { parent, figure, kind });
+        }
+        (figures, shapes)
+    };
+
+    let geo = Geo {
+        points,
+        has_z,
+        has_m,
+        figures,
+        shapes,
+    };
+    let wkt = if geo.shapes.is_empty() {
+        "GEOMETRYCOLLECTION EMPTY".to_string()
+    } else {
+        geo.shape_wkt(0)?
+    };
+    Some(Spatial { srid, wkt })
+}
+
+impl Geo {
+    fn coord(&self, p: &[f64; 4]) -> String {
+        let fmt = |v: f64| if v.is_nan() { "NULL".to_string() } else { v.to_string() };
+        let mut s = format!("{} {}", p[0], p[1]);
+        if self.has_z || self.has_m {
+            s.push(' ');
+            s.push_str(&fmt(p[2]));
+        }
+        if self.has_m {
+            s.push(' ');
+            s.push_str(&fmt(p[3]));
+        }
+        s
+    }
+
+    /// Points of figure `f` as `x y, x y, ...`.
+    fn figure_coords(&self, f: usize) -> Option<String> {
+        let start = *self.figures.get(f)?;
+        let end = self.figures.get(f + 1).copied().unwrap_or(self.points.len());
+        let pts = self.points.get(start..end.max(start))?;
+        Some(pts.iter().map(|p| self.coord(p)).collect::<Vec<_>>().join(", "))
+    }
+
+    /// Figure indices belonging to shape `i`.
+    fn shape_figures(&self, i: usize) -> std::ops::Range<usize> {
+        let start = self.shapes[i].figure;
+        if start < 0 {
+            return 0..0;
+        }
+        let end = self.shapes[i + 1..]
+            .iter()
+            .find(|s| s.figure >= 0)
+            .map_or(self.figures.len(), |s| s.figure as usize);
+        start as usize..end
+    }
+
+    fn shape_wkt(&self, i: usize) -> Option<String> {
+        let shape = self.shapes.get(i)?;
+        let name = match shape.kind {
+            1 => "POINT",
+            2 => "LINESTRING",
+            3 => "POLYGON",
+            4 => "MULTIPOINT",
+            5 => "MULTILINESTRING",
+            6 => "MULTIPOLYGON",
+            7 => "GEOMETRYCOLLECTION",
+            11 => return Some("FULLGLOBE".into()),
+            _ => return None,
+        };
+
+        let body = match shape.kind {
+            1 | 2 => {
+                let f = self.shape_figures(i).next();
+                f.map(|f| self.figure_coords(f).map(|c| format!("({c})")))
+                    .transpose()?
+            }
+            3 => {
+                let rings = self
+                    .shape_figures(i)
+                    .map(|f| self.figure_coords(f).map(|c| format!("({c})")))
+                    .collect::<Option<Vec<_>>>()?;
+                (!rings.is_empty()).then(|| format!("({})", rings.join(", ")))
+            }
+            _ => {
+                let children = (i + 1..self.shapes.len())
+                    .filter(|&j| self.shapes[j].parent == i as i32)
+                    .map(|j| {
+                        let wkt = self.shape_wkt(j)?;
+                        // Multi* members drop their type name; collections keep it
+                        Some(if shape.kind == 7 {
+                            wkt
+                        } else {
+                            wkt.split_once(' ').map_or(wkt.clone(), |(_, rest)| rest.to_string())
+                        })
+                    })
+                    .collect::<Option<Vec<_>>>()?;
+                (!children.is_empty()).then(|| format!("({})", children.join(", ")))
+            }
+        };
+
+        Some(match body {
+            Some(b) => format!("{name} {b}"),
+            None => format!("{name} EMPTY"),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(srid: i32, props: u8) -> Vec<u8> {
+        let mut b = srid.to_le_bytes().to_vec();
+        b.push(1);
+        b.push(props);
+        b
+    }
+
+    fn push_points(b: &mut Vec<u8>, pts: &[(f64, f64)]) {
+        b.extend((pts.len() as u32).to_le_bytes());
+        for (x, y) in pts {
+            b.extend(x.to_le_bytes());
+            b.extend(y.to_le_bytes());
+        }
+    }
+
+    fn push_figures(b: &mut Vec<u8>, offsets: &[i32]) {
+        b.extend((offsets.len() as u32).to_le_bytes());
+        for o in offsets {
+            b.push(1);
+            b.extend(o.to_le_bytes());
+        }
+    }
+
+    fn push_shapes(b: &mut Vec<u8>, shapes: &[(i32, i32, u8)]) {
+        b.extend((shapes.len() as u32).to_le_bytes());
+        for (parent, figure, kind) in shapes {
+            b.extend(parent.to_le_bytes());
+            b.extend(figure.to_le_bytes());
+            b.push(*kind);
+        }
+    }
+
+    #[test]
+    fn single_point_geography_swaps_lat_long() {
+        let mut b = header(4326, 0x0C);
+        b.extend(47.5f64.to_le_bytes());
+        b.extend((-122.25f64).to_le_bytes());
+        assert_eq!(
+            decode(&b, true),
+            Some(Spatial { srid: 4326, wkt: "POINT (-122.25 47.5)".into() })
+        );
+    }
+
+    #[test]
+    fn single_line_segment_geometry() {
+        let mut b = header(0, 0x14);
+        for v in [0.0f64, 0.0, 3.0, 4.5] {
+            b.extend(v.to_le_bytes());
+        }
+        assert_eq!(decode(&b, false).unwrap().wkt, "LINESTRING (0 0, 3 4.5)");
+    }
+
+    #[test]
+    fn polygon_with_hole() {
+        let mut b = header(0, 0x04);
+        push_points(
+            &mut b,
+            &[(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 0.0), (2.0, 2.0), (3.0, 2.0), (2.0, 3.0), (2.0, 2.0)],
+        );
+        push_figures(&mut b, &[0, 4]);
+        push_shapes(&mut b, &[(-1, 0, 3)]);
+        assert_eq!(
+            decode(&b, false).unwrap().wkt,
+            "POLYGON ((0 0, 10 0, 10 10, 0 0), (2 2, 3 2, 2 3, 2 2))"
+        );
+    }
+
+    #[test]
+    fn multipoint_and_empty_collection() {
+        let mut b = header(0, 0x04);
+        push_points(&mut b, &[(1.0, 2.0), (3.0, 4.0)]);
+        push_figures(&mut b, &[0, 1]);
+        push_shapes(&mut b, &[(-1, 0, 4), (0, 0, 1), (0, 1, 1)]);
+        assert_eq!(decode(&b, false).unwrap().wkt, "MULTIPOINT ((1 2), (3 4))");
+
+        let mut b = header(0, 0x04);
+        push_points(&mut b, &[]);
+        push_figures(&mut b, &[]);
+        push_shapes(&mut b, &[(-1, -1, 7)]);
+        assert_eq!(decode(&b, false).unwrap().wkt, "GEOMETRYCOLLECTION EMPTY");
+    }
+
+    #[test]
+    fn rejects_truncated_and_curves() {
+        assert_eq!(decode(&[0, 0, 0], false), None);
+        let mut b = header(0, 0x04);
+        push_points(&mut b, &[(0.0, 0.0), (1.0, 1.0), (2.0, 0.0)]);
+        push_figures(&mut b, &[0]);
+        push_shapes(&mut b, &[(-1, 0, 8)]);
+        assert_eq!(decode(&b, false), None);
+    }
+}
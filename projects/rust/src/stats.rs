@@ -0,0 +1,145 @@
+//! `SET STATISTICS IO, TIME` capture: parse the informational messages SQL
+//! Server sends for each statement into per-table reads and CPU/elapsed times.
+
+use serde::Serialize;
+
+/// Statistics for one command, summed over its statements.
+#[derive(Serialize, Debug, Default, PartialEq)]
+pub struct Statistics {
+    /// Reads per table, in the order tables were first reported.
+    pub tables: Vec<TableIo>,
+    /// Parse and compile time.
+    pub compile: Timing,
+    /// Execution time.
+    pub execution: Timing,
+}
+
+/// `STATISTICS IO` counters for one table (or `Worktable`/`Workfile`).
+#[derive(Serialize, Debug, Default, PartialEq)]
+pub struct TableIo {
+    pub table: String,
+    pub scan_count: i64,
+    pub logical_reads: i64,
+    pub physical_reads: i64,
+    pub read_ahead_reads: i64,
+    pub lob_logical_reads: i64,
+    pub lob_physical_reads: i64,
+    pub lob_read_ahead_reads: i64,
+}
+
+/// `STATISTICS TIME` totals in milliseconds.
+#[derive(Serialize, Debug, Default, PartialEq)]
+pub struct Timing {
+    pub cpu_ms: i64,
+    pub elapsed_ms: i64,
+}
+
+impl Statistics {
+    /// Build from the message texts; anything that isn't a statistics
+    /// message (PRINT output, warnings) is ignored.
+    pub fn from_messages<'a>(messages: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut stats = Statistics::default();
+        for message in messages {
+            let message = message.trim();
+            if let Some(rest) = message.strip_prefix("Table '") {
+                stats.add_io(rest);
+            } else if message.contains("parse and compile time") {
+                add_timing(&mut stats.compile, message);
+            } else if message.contains("Execution Times") {
+                add_timing(&mut stats.execution, message);
+            }
+        }
+        stats
+    }
+
+    /// Parse `Orders'. Scan count 1, logical reads 12, ...`.
+    fn add_io(&mut self, rest: &str) {
+        let Some((table, counters)) = rest.split_once("'.") else {
+            return;
+        };
+        let pos = match self.tables.iter().position(|t| t.table == table) {
+            Some(pos) => pos,
+            None => {
+                self.tables.push(TableIo {
+                    table: table.to_string(),
+                    ..TableIo::default()
+                });
+                self.tables.len() - 1
+            }
+        };
+        let io = &mut self.tables[pos];
+        for (name, value) in counters_of(counters) {
+            let counter = match name.as_str() {
+                "scan count" => &mut io.scan_count,
+                "logical reads" => &mut io.logical_reads,
+                "physical reads" => &mut io.physical_reads,
+                "read-ahead reads" => &mut io.read_ahead_reads,
+                "lob logical reads" => &mut io.lob_logical_reads,
+                "lob physical reads" => &mut io.lob_physical_reads,
+                "lob read-ahead reads" => &mut io.lob_read_ahead_reads,
+                // page server counters (Hyperscale) and future additions
+                _ => continue,
+            };
+            *counter += value;
+        }
+    }
+}
+
+/// Add `CPU time = 15 ms,  elapsed time = 20 ms.` to `timing`.
+fn add_timing(timing: &mut Timing, message: &str) {
+    for (name, value) in counters_of(&message.replace(" = ", " ").replace(" ms", "")) {
+        if name.ends_with("cpu time") {
+            timing.cpu_ms += value;
+        } else if name.ends_with("elapsed time") {
+            timing.elapsed_ms += value;
+        }
+    }
+}
+
+/// Split `Name 1, Other name 2.` into lowercase `(name, value)` pairs.
+fn counters_of(s: &str) -> impl Iterator<Item = (String, i64)> + '_ {
+    s.split(',').filter_map(|part| {
+        let (name, value) = part.trim().trim_end_matches('.').rsplit_once(' ')?;
+        Some((name.trim().to_ascii_lowercase(), value.parse().ok()?))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_io_and_sums_per_table() {
+        let stats = Statistics::from_messages([
+            "Table 'Orders'. Scan count 1, logical reads 12, physical reads 2, \
+             page server reads 0, read-ahead reads 8, page server read-ahead reads 0, \
+             lob logical reads 0, lob physical reads 0, lob page server reads 0, \
+             lob read-ahead reads 0, lob page server read-ahead reads 0.",
+            "Table 'Worktable'. Scan count 0, logical reads 0, physical reads 0.",
+            "Table 'Orders'. Scan count 1, logical reads 3, physical reads 0.",
+        ]);
+        assert_eq!(stats.tables.len(), 2);
+        assert_eq!(stats.tables[0].table, "Orders");
+        assert_eq!(stats.tables[0].scan_count, 2);
+        assert_eq!(stats.tables[0].logical_reads, 15);
+        assert_eq!(stats.tables[0].physical_reads, 2);
+        assert_eq!(stats.tables[0].read_ahead_reads, 8);
+        assert_eq!(stats.tables[1].table, "Worktable");
+    }
+
+    #[test]
+    fn parses_compile_and_execution_times() {
+        let stats = Statistics::from_messages([
+            "SQL Server parse and compile time: \n   CPU time = 3 ms, elapsed time = 4 ms.",
+            "\n SQL Server Execution Times:\n   CPU time = 15 ms,  elapsed time = 20 ms.",
+            "\n SQL Server Execution Times:\n   CPU time = 0 ms,  elapsed time = 1 ms.",
+            "Warning: Null value is eliminated by an aggregate or other SET operation.",
+        ]);
+        assert_eq!((stats.compile.cpu_ms, stats.compile.elapsed_ms), (3, 4));
+        assert_eq!(
+            (stats.execution.cpu_ms, stats.execution.elapsed_ms),
+            (15, 21)
+        );
+        assert!(stats.tables.is_empty());
+    }
+}
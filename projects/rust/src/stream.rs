@@ -1,6 +1,10 @@
 use std::collections::VecDeque;
 
-use mssql_client::Row;
+use mssql_client::{Row, SqlValue};
+
+use crate::error::Result;
+use crate::memory::{self, Usage};
+use crate::query::{column_keys, row_to_json, SerializeOptions};
 
 /// A buffered cursor for streaming query results row-by-row across FFI.
 ///
@@ -8,29 +12,85 @@ use mssql_client::Row;
 /// QueryStream buffers all rows upfront. We store the rows and column
 /// metadata, then serialize to JSON one row at a time on each stream_next call.
 pub struct RowCursor {
-    rows: VecDeque<Row>,
+    /// Rows not yet read, with their approximate size.
+    rows: VecDeque<(Row, u64)>,
+    /// The row most recently returned by `next_row`, kept so raw binary
+    /// columns can be copied out after its JSON has been sent.
+    current: Option<(Row, u64)>,
+    /// Row keys, worked out once from the result's columns.
+    keys: Vec<String>,
     done: bool,
+    /// Serialization options of the connection that opened the cursor.
+    pub serialize: SerializeOptions,
+    /// Approximate bytes of the rows still held.
+    pub usage: Usage,
 }
 
 impl RowCursor {
-    pub fn new(rows: Vec<Row>) -> Self {
+    pub fn new(rows: Vec<Row>, serialize: SerializeOptions) -> Self {
+        let keys = rows
+            .first()
+            .map(|row| column_keys(row.columns(), serialize.column_case))
+            .unwrap_or_default();
+        let usage = Usage::default();
+        let rows: VecDeque<(Row, u64)> = rows
+            .into_iter()
+            .map(|row| {
+                let size = memory::row_size(&row);
+                usage.add(size);
+                (row, size)
+            })
+            .collect();
         Self {
-            rows: VecDeque::from(rows),
+            rows,
+            current: None,
+            keys,
             done: false,
+            serialize,
+            usage,
         }
     }
 
-    /// Pop the next row, or None if exhausted.
-    pub fn next_row(&mut self) -> Option<Row> {
+    /// Advance to the next row, or None if exhausted.
+    fn next_row(&mut self) -> Option<&Row> {
         if self.done {
             return None;
         }
-        match self.rows.pop_front() {
-            Some(row) => Some(row),
-            None => {
-                self.done = true;
-                None
+        if let Some((_, size)) = self.current.take() {
+            self.usage.sub(size);
+        }
+        self.current = self.rows.pop_front();
+        if self.current.is_none() {
+            self.done = true;
+        }
+        self.current.as_ref().map(|(row, _)| row)
+    }
+
+    /// Advance to the next row and convert it to JSON, or None if exhausted.
+    pub fn next_json(&mut self) -> Option<Result<serde_json::Value>> {
+        self.next_row()?;
+        let (row, _) = self.current.as_ref()?;
+        Some(row_to_json(row, &self.keys, &self.serialize))
+    }
+
+    /// Rows not yet read.
+    pub fn rows_left(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Copy a binary column of the current row into `out`, returning the
+    /// number of bytes copied. `column` is the row key, so it is cased the way
+    /// the row's JSON was. None if there is no such non-NULL binary column.
+    pub fn read_binary(&self, column: &str, out: &mut [u8]) -> Option<usize> {
+        let (row, _) = self.current.as_ref()?;
+        let col = &row.columns()[self.keys.iter().position(|key| key == column)?];
+        match row.get_raw(col.index) {
+            Some(SqlValue::Binary(bytes)) => {
+                let n = bytes.len().min(out.len());
+                out[..n].copy_from_slice(&bytes[..n]);
+                Some(n)
             }
+            _ => None,
         }
     }
 
@@ -0,0 +1,185 @@
+//! OpenTelemetry tracing: `tracing` spans around connects, acquires, commands
+//! and transactions, exported over OTLP/HTTP once `mssql_configure_tracing`
+//! installs an exporter. Without one the spans have no subscriber and cost
+//! next to nothing. The exporter is behind the `otel` cargo feature (on by
+//! default); without it the spans are still created but never exported.
+
+use std::collections::HashMap;
+#[cfg(feature = "otel")]
+use std::sync::{Mutex, OnceLock};
+#[cfg(feature = "otel")]
+use std::time::Duration;
+
+#[cfg(feature = "otel")]
+use opentelemetry::trace::TracerProvider as _;
+#[cfg(feature = "otel")]
+use opentelemetry::KeyValue;
+#[cfg(feature = "otel")]
+use opentelemetry_otlp::{WithExportConfig, WithHttpConfig};
+#[cfg(feature = "otel")]
+use opentelemetry_sdk::trace::TracerProvider;
+use serde::Deserialize;
+use tracing::field::Empty;
+use tracing::Span;
+#[cfg(feature = "otel")]
+use tracing_subscriber::layer::SubscriberExt;
+#[cfg(feature = "otel")]
+use tracing_subscriber::util::SubscriberInitExt;
+#[cfg(feature = "otel")]
+use tracing_subscriber::{reload, Layer, Registry};
+
+use crate::error::{MssqlError, Result};
+
+/// Statement text kept on a span, in characters (literals masked).
+const STATEMENT_PREFIX_CHARS: usize = 200;
+
+#[cfg(feature = "otel")]
+type BoxedLayer = Box<dyn Layer<Registry> + Send + Sync>;
+
+/// Swaps the OpenTelemetry layer in and out of the global subscriber, which
+/// can only be installed once per process.
+#[cfg(feature = "otel")]
+static RELOAD: OnceLock<reload::Handle<Option<BoxedLayer>, Registry>> = OnceLock::new();
+#[cfg(feature = "otel")]
+static PROVIDER: Mutex<Option<TracerProvider>> = Mutex::new(None);
+
+/// Exporter settings from the JS side.
+#[derive(Deserialize, Debug, Default)]
+#[cfg_attr(not(feature = "otel"), allow(dead_code))]
+pub struct TracingConfig {
+    /// Full OTLP/HTTP traces URL; `OTEL_EXPORTER_OTLP_*` or
+    /// `http://localhost:4318/v1/traces` when omitted.
+    pub endpoint: Option<String>,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    pub service_name: Option<String>,
+    pub timeout_ms: Option<u64>,
+}
+
+/// Install (or replace) the OTLP exporter; `None` removes it, flushing
+/// spans that are still buffered.
+#[cfg(feature = "otel")]
+pub fn configure(config: Option<TracingConfig>) -> Result<()> {
+    let mut provider = PROVIDER.lock().unwrap();
+    if let Some(old) = provider.take() {
+        reload_handle()?
+            .reload(None)
+            .map_err(|e| MssqlError::Config(e.to_string()))?;
+        let _ = old.shutdown();
+    }
+    let Some(config) = config else {
+        crate::debug::debug_log!("Tracing exporter removed");
+        return Ok(());
+    };
+
+    let mut builder = opentelemetry_otlp::SpanExporter::builder()
+        .with_http()
+        .with_headers(config.headers);
+    if let Some(endpoint) = config.endpoint {
+        builder = builder.with_endpoint(endpoint);
+    }
+    if let Some(ms) = config.timeout_ms {
+        builder = builder.with_timeout(Duration::from_millis(ms));
+    }
+    let exporter = builder
+        .build()
+        .map_err(|e| MssqlError::Config(format!("OTLP exporter: {e}")))?;
+
+    // The batch processor spawns its export task on our runtime.
+    let _guard = crate::rt().enter();
+    let service_name = config.service_name.unwrap_or_else(|| "mssqlts".into());
+    let new = TracerProvider::builder()
+        .with_batch_exporter(exporter, opentelemetry_sdk::runtime::Tokio)
+        .with_resource(opentelemetry_sdk::Resource::new([KeyValue::new(
+            "service.name",
+            service_name,
+        )]))
+        .build();
+    let layer = tracing_opentelemetry::layer().with_tracer(new.tracer("mssqlts"));
+    reload_handle()?
+        .reload(Some(Box::new(layer) as BoxedLayer))
+        .map_err(|e| MssqlError::Config(e.to_string()))?;
+    *provider = Some(new);
+    crate::debug::info_log!("Tracing exporter installed");
+    Ok(())
+}
+
+/// Built without `otel`: removing is a no-op, installing is an error.
+#[cfg(not(feature = "otel"))]
+pub fn configure(config: Option<TracingConfig>) -> Result<()> {
+    match config {
+        None => Ok(()),
+        Some(_) => Err(MssqlError::Config(
+            "Tracing export needs the native library built with the otel feature".into(),
+        )),
+    }
+}
+
+#[cfg(feature = "otel")]
+fn reload_handle() -> Result<&'static reload::Handle<Option<BoxedLayer>, Registry>> {
+    if let Some(handle) = RELOAD.get() {
+        return Ok(handle);
+    }
+    let (layer, handle) = reload::Layer::new(None::<BoxedLayer>);
+    tracing_subscriber::registry()
+        .with(layer)
+        .try_init()
+        .map_err(|e| MssqlError::Config(format!("Cannot install tracing subscriber: {e}")))?;
+    Ok(RELOAD.get_or_init(|| handle))
+}
+
+/// Span for opening a connection (`connect`) or taking one from a pool
+/// (`acquire`); the connection id is recorded once known.
+pub fn connect_span(operation: &'static str, pool_id: Option<u64>) -> Span {
+    tracing::info_span!(
+        target: "mssqlts",
+        "mssql",
+        otel.name = operation,
+        otel.kind = "client",
+        otel.status_code = Empty,
+        otel.status_message = Empty,
+        db.system = "mssql",
+        db.operation = operation,
+        mssql.pool_id = pool_id,
+        mssql.conn_id = Empty,
+    )
+}
+
+/// Span for one command on a connection.
+pub fn command_span(
+    operation: &'static str,
+    conn_id: u64,
+    pool_id: Option<u64>,
+    sql: &str,
+) -> Span {
+    let statement = crate::redact::statement(sql, STATEMENT_PREFIX_CHARS);
+    tracing::info_span!(
+        target: "mssqlts",
+        "mssql",
+        otel.name = operation,
+        otel.kind = "client",
+        otel.status_code = Empty,
+        otel.status_message = Empty,
+        db.system = "mssql",
+        db.operation = operation,
+        db.statement = %statement,
+        db.rows = Empty,
+        mssql.pool_id = pool_id,
+        mssql.conn_id = conn_id,
+    )
+}
+
+/// Record the outcome on `span`: row count when known, error status otherwise.
+pub fn finish<T>(span: &Span, result: &Result<T>, rows: Option<u64>) {
+    match result {
+        Ok(_) => {
+            if let Some(rows) = rows {
+                span.record("db.rows", rows);
+            }
+        }
+        Err(e) => {
+            span.record("otel.status_code", "ERROR");
+            span.record("otel.status_message", tracing::field::display(e));
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use crate::config::VarcharDecodePolicy;
+use crate::error::{MssqlError, Result};
+use crate::query::SerializeOptions;
+
+/// Decode single-byte character data. UTF-8 is tried first, then the
+/// configured fallback encoding; anything still undecodable is handled per
+/// the decode policy.
+pub fn decode_varchar(bytes: &[u8], opts: &SerializeOptions) -> Result<String> {
+    if let Ok(s) = std::str::from_utf8(bytes) {
+        return Ok(s.to_string());
+    }
+    if let Some(encoding) = opts.varchar_encoding {
+        if let Some(s) = encoding.decode_without_bom_handling_and_without_replacement(bytes) {
+            return Ok(s.into_owned());
+        }
+    }
+
+    match opts.varchar_decode {
+        VarcharDecodePolicy::Error => Err(MssqlError::Query(format!(
+            "varchar value is not valid {}",
+            opts.varchar_encoding.map_or("UTF-8", |e| e.name())
+        ))),
+        VarcharDecodePolicy::Lossy => Ok(match opts.varchar_encoding {
+            Some(encoding) => encoding.decode_without_bom_handling(bytes).0.into_owned(),
+            None => String::from_utf8_lossy(bytes).into_owned(),
+        }),
+        VarcharDecodePolicy::Latin1 => Ok(bytes.iter().map(|&b| b as char).collect()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn opts(encoding: Option<&str>, policy: VarcharDecodePolicy) -> SerializeOptions {
+        SerializeOptions {
+            varchar_encoding: encoding.and_then(|l| encoding_rs::Encoding::for_label(l.as_bytes())),
+            varchar_decode: policy,
+            ..Default::default()
+        }
+    }
+
+    // "café €5" in windows-1252
+    const CP1252: &[u8] = &[0x63, 0x61, 0x66, 0xE9, 0x20, 0x80, 0x35];
+
+    #[test]
+    fn utf8_passes_through() {
+        let o = opts(None, VarcharDecodePolicy::Error);
+        assert_eq!(decode_varchar("café".as_bytes(), &o).unwrap(), "café");
+    }
+
+    #[test]
+    fn fallback_encoding_decodes_legacy_bytes() {
+        let o = opts(Some("windows-1252"), VarcharDecodePolicy::Error);
+        assert_eq!(decode_varchar(CP1252, &o).unwrap(), "café €5");
+    }
+
+    #[test]
+    fn policies_without_fallback() {
+        assert!(decode_varchar(CP1252, &opts(None, VarcharDecodePolicy::Error)).is_err());
+        assert_eq!(
+            decode_varchar(CP1252, &opts(None, VarcharDecodePolicy::Lossy)).unwrap(),
+            "caf\u{FFFD} \u{FFFD}5"
+        );
+        assert_eq!(
+            decode_varchar(CP1252, &opts(None, VarcharDecodePolicy::Latin1)).unwrap(),
+            "caf\u{E9} \u{80}5"
+        );
+    }
+}
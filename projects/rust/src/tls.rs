@@ -0,0 +1,180 @@
+//! TLS policy for encrypted connections: protocol floor, cipher suites and
+//! extra trusted CAs.
+//!
+//! The driver uses rustls, which never negotiates TLS 1.0/1.1 or non-AEAD
+//! suites; these settings only narrow what rustls already allows.
+
+use base64::Engine;
+use mssql_client::{Config, TlsVersion};
+use rustls_pki_types::CertificateDer;
+
+use crate::config::{NormalizedConfig, TlsMinVersion};
+use crate::error::{MssqlError, Result};
+
+/// Cipher suites rustls supports, by rustls name.
+const CIPHER_SUITES: [&str; 9] = [
+    "TLS13_AES_256_GCM_SHA384",
+    "TLS13_AES_128_GCM_SHA256",
+    "TLS13_CHACHA20_POLY1305_SHA256",
+    "TLS_ECDHE_ECDSA_WITH_AES_256_GCM_SHA384",
+    "TLS_ECDHE_ECDSA_WITH_AES_128_GCM_SHA256",
+    "TLS_ECDHE_ECDSA_WITH_CHACHA20_POLY1305_SHA256",
+    "TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384",
+    "TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256",
+    "TLS_ECDHE_RSA_WITH_CHACHA20_POLY1305_SHA256",
+];
+
+/// Map a suite name to its rustls name. TLS 1.3 suites are also accepted
+/// under their IANA names (`TLS_AES_256_GCM_SHA384`).
+fn canonical_suite(name: &str) -> Option<&'static str> {
+    let upper = name.trim().to_uppercase();
+    let upper = match upper.strip_prefix("TLS_") {
+        Some(rest) if rest.starts_with("AES_") || rest.starts_with("CHACHA20_") => {
+            format!("TLS13_{rest}")
+        }
+        _ => upper,
+    };
+    CIPHER_SUITES.iter().copied().find(|s| *s == upper)
+}
+
+/// Validate the configured cipher suites against the protocol floor.
+fn cipher_suites(names: &[String], min: TlsMinVersion) -> Result<Vec<&'static str>> {
+    let mut suites = Vec::with_capacity(names.len());
+    for name in names {
+        let suite = canonical_suite(name)
+            .ok_or_else(|| MssqlError::Config(format!("Unsupported TLS cipher suite: {name}")))?;
+        if !suites.contains(&suite) {
+            suites.push(suite);
+        }
+    }
+    let usable = suites
+        .iter()
+        .any(|s| min == TlsMinVersion::Tls12 || s.starts_with("TLS13_"));
+    if !usable {
+        return Err(MssqlError::Config(
+            "tlsCipherSuites allows no suite usable with tlsMinVersion".into(),
+        ));
+    }
+    Ok(suites)
+}
+
+/// DER certificates from every `CERTIFICATE` block of a PEM document.
+fn pem_certificates(pem: &str) -> Result<Vec<Vec<u8>>> {
+    const BEGIN: &str = "-----BEGIN CERTIFICATE-----";
+    const END: &str = "-----END CERTIFICATE-----";
+    let mut certs = Vec::new();
+    let mut rest = pem;
+    while let Some(start) = rest.find(BEGIN) {
+        let body = &rest[start + BEGIN.len()..];
+        let end = body
+            .find(END)
+            .ok_or_else(|| MssqlError::Config("Unterminated PEM certificate".into()))?;
+        let b64: String = body[..end].split_whitespace().collect();
+        let der = base64::engine::general_purpose::STANDARD
+            .decode(b64)
+            .map_err(|e| MssqlError::Config(format!("Invalid PEM certificate: {e}")))?;
+        certs.push(der);
+        rest = &body[end + END.len()..];
+    }
+    Ok(certs)
+}
+
+/// CA certificates to trust in addition to the platform roots, from
+/// `ca_cert_pem` and/or `ca_cert_path` (PEM, or a single DER certificate).
+fn ca_certificates(cfg: &NormalizedConfig) -> Result<Vec<Vec<u8>>> {
+    let mut certs = Vec::new();
+    if let Some(pem) = &cfg.ca_cert_pem {
+        certs.extend(pem_certificates(pem)?);
+        if certs.is_empty() {
+            return Err(MssqlError::Config("caCertPem contains no certificate".into()));
+        }
+    }
+    if let Some(path) = &cfg.ca_cert_path {
+        let bytes = std::fs::read(path)
+            .map_err(|e| MssqlError::Config(format!("Cannot read CA certificate {path}: {e}")))?;
+        match std::str::from_utf8(&bytes).map(pem_certificates) {
+            Ok(Ok(pem)) if !pem.is_empty() => certs.extend(pem),
+            Ok(Err(e)) => return Err(e),
+            _ => certs.push(bytes),
+        }
+    }
+    Ok(certs)
+}
+
+/// Apply the TLS policy to a client config.
+pub fn apply(config: &mut Config, cfg: &NormalizedConfig) -> Result<()> {
+    config.tls.min_protocol_version = match cfg.tls_min_version {
+        TlsMinVersion::Tls12 => TlsVersion::Tls12,
+        TlsMinVersion::Tls13 => TlsVersion::Tls13,
+    };
+    if let Some(names) = &cfg.tls_cipher_suites {
+        config.tls.cipher_suites = Some(cipher_suites(names, cfg.tls_min_version)?);
+    }
+    config
+        .tls
+        .root_certificates
+        .extend(ca_certificates(cfg)?.into_iter().map(CertificateDer::from));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn accepts_rustls_and_iana_names() {
+        let suites = cipher_suites(
+            &names(&["tls_aes_256_gcm_sha384", "TLS13_AES_256_GCM_SHA384", "TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256"]),
+            TlsMinVersion::Tls12,
+        )
+        .unwrap();
+        assert_eq!(suites, ["TLS13_AES_256_GCM_SHA384", "TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256"]);
+    }
+
+    #[test]
+    fn rejects_unknown_or_weak_suites() {
+        assert!(cipher_suites(&names(&["TLS_RSA_WITH_AES_128_CBC_SHA"]), TlsMinVersion::Tls12).is_err());
+    }
+
+    // Not a real certificate; only the PEM framing matters here
+    const PEM: &str = "-----BEGIN CERTIFICATE-----\nAQID\nBA==\n-----END CERTIFICATE-----\n\
+                       -----BEGIN CERTIFICATE-----\nBQY=\n-----END CERTIFICATE-----\n";
+
+    #[test]
+    fn parses_pem_bundle() {
+        assert_eq!(pem_certificates(PEM).unwrap(), vec![vec![1, 2, 3, 4], vec![5, 6]]);
+        assert!(pem_certificates("no certs here").unwrap().is_empty());
+        assert!(pem_certificates("-----BEGIN CERTIFICATE-----\nAQID").is_err());
+    }
+
+    #[test]
+    fn reads_pem_or_der_ca_files() {
+        let dir = std::env::temp_dir();
+        let pem_path = dir.join(format!("mssqlts-ca-{}.pem", std::process::id()));
+        let der_path = dir.join(format!("mssqlts-ca-{}.der", std::process::id()));
+        std::fs::write(&pem_path, PEM).unwrap();
+        std::fs::write(&der_path, [0x30, 0x82, 0xFF]).unwrap();
+
+        let mut cfg = crate::config::tests::make_config("localhost", "db", None, None);
+        cfg.ca_cert_path = Some(pem_path.to_string_lossy().into_owned());
+        assert_eq!(ca_certificates(&cfg).unwrap().len(), 2);
+        cfg.ca_cert_path = Some(der_path.to_string_lossy().into_owned());
+        assert_eq!(ca_certificates(&cfg).unwrap(), vec![vec![0x30, 0x82, 0xFF]]);
+        cfg.ca_cert_path = Some("/nonexistent/ca.pem".into());
+        assert!(ca_certificates(&cfg).is_err());
+
+        std::fs::remove_file(pem_path).ok();
+        std::fs::remove_file(der_path).ok();
+    }
+
+    #[test]
+    fn requires_a_tls13_suite_when_floor_is_13() {
+        let tls12_only = names(&["TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384"]);
+        assert!(cipher_suites(&tls12_only, TlsMinVersion::Tls13).is_err());
+        assert!(cipher_suites(&tls12_only, TlsMinVersion::Tls12).is_ok());
+    }
+}
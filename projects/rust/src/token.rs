@@ -0,0 +1,260 @@
+//! Azure AD access token acquisition for managed identities and service
+//! principals, with a process-wide cache so pools and reconnects reuse a
+//! token until it nears expiry.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use lazy_static::lazy_static;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use zeroize::Zeroizing;
+
+use crate::debug::debug_log;
+use crate::error::{MssqlError, Result};
+use crate::secret::Secret;
+
+/// Resource (audience) for Azure SQL tokens.
+const RESOURCE: &str = "https://database.windows.net/";
+const SCOPE: &str = "https://database.windows.net/.default";
+const AUTHORITY_HOST: &str = "https://login.microsoftonline.com";
+const IMDS_ENDPOINT: &str = "http://169.254.169.254/metadata/identity/oauth2/token";
+/// Tokens are refreshed this long before they expire.
+const REFRESH_MARGIN: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Clone)]
+pub struct AccessToken {
+    pub token: Secret,
+    /// Seconds since the Unix epoch.
+    pub expires_on: u64,
+}
+
+lazy_static! {
+    static ref TOKENS: Mutex<HashMap<String, AccessToken>> = Mutex::new(HashMap::new());
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// Whether a token expiring at `expires_on` is within the refresh margin.
+pub fn needs_refresh(expires_on: u64) -> bool {
+    expires_on <= now_secs() + REFRESH_MARGIN.as_secs()
+}
+
+/// A cached token that is still outside the refresh margin.
+fn cached(key: &str) -> Option<AccessToken> {
+    let tokens = TOKENS.lock().unwrap();
+    let t = tokens.get(key)?;
+    (!needs_refresh(t.expires_on)).then(|| t.clone())
+}
+
+fn store(key: &str, token: AccessToken) {
+    TOKENS.lock().unwrap().insert(key.to_string(), token);
+}
+
+/// Token endpoint responses encode numbers as strings (IMDS, App Service)
+/// or numbers (AAD v2), so accept both.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum NumberOrString {
+    Number(u64),
+    String(String),
+}
+
+impl NumberOrString {
+    fn value(&self) -> Option<u64> {
+        match self {
+            NumberOrString::Number(n) => Some(*n),
+            NumberOrString::String(s) => s.parse().ok(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: Secret,
+    expires_on: Option<NumberOrString>,
+    expires_in: Option<NumberOrString>,
+}
+
+impl TokenResponse {
+    fn into_token(self) -> AccessToken {
+        let expires_on = self.expires_on();
+        AccessToken {
+            token: self.access_token,
+            expires_on,
+        }
+    }
+
+    /// Absolute expiry in epoch seconds, defaulting to one hour.
+    fn expires_on(&self) -> u64 {
+        self.expires_on
+            .as_ref()
+            .and_then(NumberOrString::value)
+            .or_else(|| Some(now_secs() + self.expires_in.as_ref()?.value()?))
+            .unwrap_or_else(|| now_secs() + 3600)
+    }
+}
+
+fn http_client() -> Result<reqwest::Client> {
+    reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()
+        .map_err(|e| MssqlError::Connection(e.to_string()))
+}
+
+async fn read_token(response: reqwest::Response) -> Result<TokenResponse> {
+    let status = response.status();
+    // A successful response holds the token
+    let body = Zeroizing::new(
+        response
+            .text()
+            .await
+            .map_err(|e| MssqlError::Connection(format!("Token request failed: {e}")))?,
+    );
+    if !status.is_success() {
+        return Err(MssqlError::Connection(format!(
+            "Token request failed ({status}): {}",
+            body.as_str()
+        )));
+    }
+    serde_json::from_str(&body)
+        .map_err(|e| MssqlError::Connection(format!("Invalid token response: {e}")))
+}
+
+/// Fetch a managed identity token from the App Service/Functions identity
+/// endpoint when `IDENTITY_ENDPOINT` is set, otherwise from the VM's IMDS.
+async fn fetch_managed_identity(client_id: Option<&str>) -> Result<TokenResponse> {
+    let http = http_client()?;
+
+    let endpoint = std::env::var("IDENTITY_ENDPOINT").ok();
+    let header = std::env::var("IDENTITY_HEADER").ok();
+    let mut query = vec![("resource", RESOURCE)];
+    if let Some(id) = client_id {
+        query.push(("client_id", id));
+    }
+
+    let request = match (&endpoint, &header) {
+        (Some(endpoint), Some(header)) => {
+            debug_log!(
+                target: "mssqlts::pool",
+                "Requesting managed identity token from {}",
+                endpoint
+            );
+            query.push(("api-version", "2019-08-01"));
+            http.get(endpoint).header("X-IDENTITY-HEADER", header)
+        }
+        _ => {
+            debug_log!(target: "mssqlts::pool", "Requesting managed identity token from IMDS");
+            query.push(("api-version", "2018-02-01"));
+            http.get(IMDS_ENDPOINT).header("Metadata", "true")
+        }
+    };
+    let response = request.query(&query).send().await.map_err(|e| {
+        MssqlError::Connection(format!("Managed identity endpoint unreachable: {e}"))
+    })?;
+    read_token(response).await
+}
+
+/// Access token for a system-assigned (`client_id` None) or user-assigned
+/// managed identity.
+pub async fn managed_identity(client_id: Option<&str>) -> Result<AccessToken> {
+    let key = format!("msi|{}", client_id.unwrap_or(""));
+    if let Some(token) = cached(&key) {
+        return Ok(token);
+    }
+    let token = fetch_managed_identity(client_id).await?.into_token();
+    store(&key, token.clone());
+    Ok(token)
+}
+
+/// Cache key for a service principal's token. It includes a hash of the
+/// secret, so a rotated or mistyped secret isn't served a token fetched
+/// with another one.
+fn service_principal_key(tenant_id: &str, client_id: &str, client_secret: &str) -> String {
+    let hash: String = Sha256::digest(client_secret.as_bytes())
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect();
+    format!("sp|{tenant_id}|{client_id}|{hash}")
+}
+
+/// Access token for a service principal via the OAuth2 client credentials
+/// flow. `AZURE_AUTHORITY_HOST` overrides the login host for sovereign clouds.
+pub async fn service_principal(
+    tenant_id: &str,
+    client_id: &str,
+    client_secret: &str,
+) -> Result<AccessToken> {
+    let key = service_principal_key(tenant_id, client_id, client_secret);
+    if let Some(token) = cached(&key) {
+        return Ok(token);
+    }
+    let host = std::env::var("AZURE_AUTHORITY_HOST").unwrap_or_else(|_| AUTHORITY_HOST.into());
+    let url = format!("{}/{tenant_id}/oauth2/v2.0/token", host.trim_end_matches('/'));
+    debug_log!(
+        target: "mssqlts::pool",
+        "Requesting service principal token for {} from {}",
+        client_id,
+        url
+    );
+    let response = http_client()?
+        .post(&url)
+        .form(&[
+            ("grant_type", "client_credentials"),
+            ("client_id", client_id),
+            ("client_secret", client_secret),
+            ("scope", SCOPE),
+        ])
+        .send()
+        .await
+        .map_err(|e| MssqlError::Connection(format!("Token endpoint unreachable: {e}")))?;
+    let token = read_token(response).await?.into_token();
+    store(&key, token.clone());
+    Ok(token)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_string_and_numeric_expiry() {
+        let imds: TokenResponse =
+            serde_json::from_str(r#"{"access_token":"a","expires_on":"1700000000"}"#).unwrap();
+        assert_eq!(imds.expires_on(), 1_700_000_000);
+
+        let aad: TokenResponse =
+            serde_json::from_str(r#"{"access_token":"a","expires_in":3599}"#).unwrap();
+        let expires = aad.expires_on();
+        assert!(expires >= now_secs() + 3598 && expires <= now_secs() + 3600);
+    }
+
+    #[test]
+    fn service_principal_key_depends_on_secret() {
+        let key = service_principal_key("t", "c", "s1");
+        assert!(key.starts_with("sp|t|c|") && !key.contains("s1"));
+        assert_eq!(key, service_principal_key("t", "c", "s1"));
+        assert_ne!(key, service_principal_key("t", "c", "s2"));
+    }
+
+    #[test]
+    fn cache_honours_refresh_margin() {
+        let token = |t: &str, ttl| AccessToken {
+            token: t.into(),
+            expires_on: now_secs() + ttl,
+        };
+        store("test|fresh", token("fresh", 3600));
+        store("test|stale", token("stale", 60));
+        let fresh = cached("test|fresh").unwrap();
+        assert_eq!(fresh.token.expose(), "fresh");
+        assert!(cached("test|stale").is_none());
+        assert!(cached("test|missing").is_none());
+        assert!(needs_refresh(now_secs() + 60));
+        assert!(!needs_refresh(now_secs() + 3600));
+    }
+}
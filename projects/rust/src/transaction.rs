@@ -0,0 +1,191 @@
+//! Transaction helpers. `SET TRANSACTION ISOLATION LEVEL` and
+//! `SET XACT_ABORT` outlive the transaction they were issued for, so the
+//! session's settings are read at begin and put back, in a batch of their
+//! own, once the transaction has ended.
+
+use mssql_client::{Client, Ready, Row, SqlValue};
+
+use crate::debug::warn_log;
+use crate::error::{MssqlError, Result};
+
+/// The session settings a transaction changes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Session {
+    /// Isolation level in `SET` form.
+    pub isolation: &'static str,
+    pub xact_abort: bool,
+}
+
+/// The `SET TRANSACTION ISOLATION LEVEL` form of an isolation level name.
+pub fn isolation_sql(name: &str) -> Result<&'static str> {
+    match name {
+        "READ_UNCOMMITTED" => Ok("READ UNCOMMITTED"),
+        "READ_COMMITTED" => Ok("READ COMMITTED"),
+        "REPEATABLE_READ" => Ok("REPEATABLE READ"),
+        "SNAPSHOT" => Ok("SNAPSHOT"),
+        "SERIALIZABLE" => Ok("SERIALIZABLE"),
+        other => Err(MssqlError::Transaction(format!(
+            "Unknown isolation level: {other}"
+        ))),
+    }
+}
+
+/// The session's current settings.
+pub async fn session_state(client: &mut Client<Ready>) -> Result<Session> {
+    let rows = client
+        .query(
+            "SELECT transaction_isolation_level, CAST(@@OPTIONS & 16384 AS INT) \
+             FROM sys.dm_exec_sessions WHERE session_id = @@SPID",
+            &[],
+        )
+        .await
+        .map_err(MssqlError::from)?;
+    for row in rows {
+        let row: Row = row.map_err(MssqlError::from)?;
+        let level = match row.get_raw(0) {
+            Some(SqlValue::SmallInt(n)) => n,
+            _ => 0,
+        };
+        return Ok(Session {
+            isolation: level_sql(level),
+            xact_abort: !matches!(row.get_raw(1), Some(SqlValue::Int(0)) | None),
+        });
+    }
+    Err(MssqlError::Transaction(
+        "Cannot determine session settings".into(),
+    ))
+}
+
+/// `XACT_STATE()` (1 active, 0 none, -1 doomed: it can only roll back) and
+/// `@@TRANCOUNT`.
+pub async fn server_state(client: &mut Client<Ready>) -> Result<(i32, i32)> {
+    let rows = client
+        .query("SELECT CAST(XACT_STATE() AS INT), @@TRANCOUNT", &[])
+        .await
+        .map_err(MssqlError::from)?;
+    for row in rows {
+        let row: Row = row.map_err(MssqlError::from)?;
+        let int = |i| match row.get_raw(i) {
+            Some(SqlValue::Int(n)) => n,
+            _ => 0,
+        };
+        return Ok((int(0), int(1)));
+    }
+    Err(MssqlError::Transaction(
+        "Cannot determine transaction state".into(),
+    ))
+}
+
+/// `sys.dm_exec_sessions.transaction_isolation_level` in `SET` form;
+/// unspecified (0) is the server default, READ COMMITTED.
+fn level_sql(level: i16) -> &'static str {
+    match level {
+        1 => "READ UNCOMMITTED",
+        3 => "REPEATABLE READ",
+        4 => "SERIALIZABLE",
+        5 => "SNAPSHOT",
+        _ => "READ COMMITTED",
+    }
+}
+
+fn on_off(on: bool) -> &'static str {
+    if on {
+        "ON"
+    } else {
+        "OFF"
+    }
+}
+
+/// The batch that begins a transaction, setting XACT_ABORT first if asked.
+pub fn begin_sql(isolation: &str, xact_abort: Option<bool>) -> String {
+    let xact_abort = match xact_abort {
+        Some(on) => format!("SET XACT_ABORT {}; ", on_off(on)),
+        None => String::new(),
+    };
+    format!("{xact_abort}SET TRANSACTION ISOLATION LEVEL {isolation}; BEGIN TRANSACTION")
+}
+
+/// The batch that puts `prior` back once no transaction is open, then
+/// reports whether it did (1) or a transaction is still open (0).
+pub fn restore_sql(prior: Session) -> String {
+    format!(
+        "IF @@TRANCOUNT = 0 BEGIN SET TRANSACTION ISOLATION LEVEL {}; SET XACT_ABORT {}; END; \
+         SELECT CAST(CASE WHEN @@TRANCOUNT = 0 THEN 1 ELSE 0 END AS INT)",
+        prior.isolation,
+        on_off(prior.xact_abort)
+    )
+}
+
+/// Run `end` (COMMIT or ROLLBACK), then put `prior` back in a batch of its
+/// own, so an end that fails can't skip the restore. Returns the end's
+/// result and the settings still to restore: `prior` again while a failed
+/// end leaves the transaction open, or if the restore itself failed.
+pub async fn end(
+    client: &mut Client<Ready>,
+    end: &str,
+    prior: Option<Session>,
+) -> (Result<()>, Option<Session>) {
+    let ended = client
+        .simple_query(end)
+        .await
+        .map_err(|e| MssqlError::Transaction(e.to_string()));
+    let Some(prior) = prior else {
+        return (ended, None);
+    };
+    let pending = match restore(client, prior).await {
+        Ok(true) => None,
+        Ok(false) => Some(prior),
+        Err(e) => {
+            warn_log!(target: "mssqlts::query", "Restoring session settings failed: {}", e);
+            Some(prior)
+        }
+    };
+    (ended, pending)
+}
+
+async fn restore(client: &mut Client<Ready>, prior: Session) -> Result<bool> {
+    let rows = client
+        .query(&restore_sql(prior), &[])
+        .await
+        .map_err(MssqlError::from)?;
+    for row in rows {
+        let row: Row = row.map_err(MssqlError::from)?;
+        return Ok(matches!(row.get_raw(0), Some(SqlValue::Int(1))));
+    }
+    Ok(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restores_the_prior_settings_after_ending() {
+        assert_eq!(isolation_sql("SERIALIZABLE").unwrap(), "SERIALIZABLE");
+        assert!(isolation_sql("CHAOS").is_err());
+        assert_eq!(level_sql(0), "READ COMMITTED");
+        assert_eq!(level_sql(4), "SERIALIZABLE");
+        let prior = Session {
+            isolation: level_sql(2),
+            xact_abort: false,
+        };
+        assert_eq!(
+            restore_sql(prior),
+            "IF @@TRANCOUNT = 0 BEGIN SET TRANSACTION ISOLATION LEVEL READ COMMITTED; \
+             SET XACT_ABORT OFF; END; \
+             SELECT CAST(CASE WHEN @@TRANCOUNT = 0 THEN 1 ELSE 0 END AS INT)"
+        );
+    }
+
+    #[test]
+    fn sets_xact_abort_only_when_asked() {
+        assert_eq!(
+            begin_sql("SERIALIZABLE", Some(true)),
+            "SET XACT_ABORT ON; SET TRANSACTION ISOLATION LEVEL SERIALIZABLE; BEGIN TRANSACTION"
+        );
+        assert_eq!(
+            begin_sql("READ COMMITTED", None),
+            "SET TRANSACTION ISOLATION LEVEL READ COMMITTED; BEGIN TRANSACTION"
+        );
+    }
+}
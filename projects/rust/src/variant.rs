@@ -0,0 +1,228 @@
+//! Decoder for raw `sql_variant` payloads (MS-TDS 2.2.5.5.4): a base type
+//! byte, a property length byte, type properties, then the value itself.
+
+use chrono::{Duration, NaiveDate, NaiveTime};
+use mssql_client::SqlValue;
+use rust_decimal::Decimal;
+
+/// The base value of a sql_variant. Binary and single-byte character data
+/// are kept apart so callers can encode/decode them the same way as ordinary
+/// binary and varchar columns.
+pub enum VariantValue {
+    Sql(SqlValue),
+    Binary(Vec<u8>),
+    Varchar(Vec<u8>),
+}
+
+fn le<const N: usize>(data: &[u8]) -> Option<[u8; N]> {
+    data.get(..N)?.try_into().ok()
+}
+
+/// Unsigned little-endian integer of 1–8 bytes.
+fn le_uint(data: &[u8]) -> u64 {
+    data.iter().rev().fold(0, |acc, &b| (acc << 8) | b as u64)
+}
+
+fn date_from_days(days: u64) -> Option<NaiveDate> {
+    NaiveDate::from_ymd_opt(1, 1, 1)?.checked_add_signed(Duration::days(days as i64))
+}
+
+/// Time of day stored as `10^-scale` second units in 3–5 bytes.
+fn time_from_ticks(data: &[u8], scale: u8) -> Option<NaiveTime> {
+    if scale > 7 || data.is_empty() || data.len() > 5 {
+        return None;
+    }
+    let nanos = le_uint(data).checked_mul(10u64.pow(9 - scale as u32))?;
+    let secs = nanos / 1_000_000_000;
+    NaiveTime::from_num_seconds_from_midnight_opt(secs as u32, (nanos % 1_000_000_000) as u32)
+}
+
+/// Byte width of a TIME value at the given scale.
+fn time_len(scale: u8) -> usize {
+    match scale {
+        0..=2 => 3,
+        3..=4 => 4,
+        _ => 5,
+    }
+}
+
+/// Decode a sql_variant into its base type name and value.
+/// Returns None for malformed payloads or unsupported base types.
+pub fn decode(bytes: &[u8]) -> Option<(&'static str, VariantValue)> {
+    let base = *bytes.first()?;
+    let prop_len = *bytes.get(1)? as usize;
+    let props = bytes.get(2..2 + prop_len)?;
+    let data = &bytes[2 + prop_len..];
+    let sql = |v| Some(VariantValue::Sql(v));
+
+    let decoded = match base {
+        0x30 => ("tinyint", sql(SqlValue::TinyInt(*data.first()?))),
+        0x34 => ("smallint", sql(SqlValue::SmallInt(i16::from_le_bytes(le(data)?)))),
+        0x38 => ("int", sql(SqlValue::Int(i32::from_le_bytes(le(data)?)))),
+        0x7F => ("bigint", sql(SqlValue::BigInt(i64::from_le_bytes(le(data)?)))),
+        0x3B => ("real", sql(SqlValue::Float(f32::from_le_bytes(le(data)?)))),
+        0x3E => ("float", sql(SqlValue::Double(f64::from_le_bytes(le(data)?)))),
+        0x32 => ("bit", sql(SqlValue::Bool(*data.first()? != 0))),
+        0x3C => {
+            // High 4 bytes first, then low 4 bytes, in 1/10000 units
+            let high = i32::from_le_bytes(le(data)?) as i64;
+            let low = u32::from_le_bytes(le(data.get(4..)?)?) as i64;
+            ("money", sql(SqlValue::Decimal(Decimal::new((high << 32) | low, 4))))
+        }
+        0x7A => {
+            let n = i32::from_le_bytes(le(data)?) as i64;
+            ("smallmoney", sql(SqlValue::Decimal(Decimal::new(n, 4))))
+        }
+        0x6A | 0x6C => {
+            let scale = *props.get(1)? as u32;
+            let (&sign, digits) = data.split_first()?;
+            if digits.len() > 16 || scale > 28 {
+                return None;
+            }
+            let magnitude = digits.iter().rev().fold(0i128, |acc, &b| (acc << 8) | b as i128);
+            let value = if sign == 0 { -magnitude } else { magnitude };
+            let d = Decimal::try_from_i128_with_scale(value, scale).ok()?;
+            let name = if base == 0x6A { "decimal" } else { "numeric" };
+            (name, sql(SqlValue::Decimal(d)))
+        }
+        0x24 => ("uniqueidentifier", sql(SqlValue::Uuid(uuid::Uuid::from_bytes_le(le(data)?)))),
+        0x28 => ("date", sql(SqlValue::Date(date_from_days(le_uint(data.get(..3)?))?))),
+        0x29 => {
+            let scale = *props.first()?;
+            ("time", sql(SqlValue::Time(time_from_ticks(data, scale)?)))
+        }
+        0x2A => {
+            let scale = *props.first()?;
+            let n = time_len(scale);
+            let time = time_from_ticks(data.get(..n)?, scale)?;
+            let date = date_from_days(le_uint(data.get(n..n + 3)?))?;
+            ("datetime2", sql(SqlValue::DateTime(date.and_time(time))))
+        }
+        0x2B => {
+            let scale = *props.first()?;
+            let n = time_len(scale);
+            let time = time_from_ticks(data.get(..n)?, scale)?;
+            let utc = date_from_days(le_uint(data.get(n..n + 3)?))?.and_time(time);
+            let minutes = i16::from_le_bytes(le(data.get(n + 3..)?)?);
+            let offset = chrono::FixedOffset::east_opt(minutes as i32 * 60)?;
+            let dt = chrono::DateTime::<chrono::Utc>::from_naive_utc_and_offset(utc, chrono::Utc)
+                .with_timezone(&offset);
+            ("datetimeoffset", sql(SqlValue::DateTimeOffset(dt)))
+        }
+        0x3D => {
+            // Days since 1900-01-01, then 1/300 second ticks
+            let days = i32::from_le_bytes(le(data)?) as i64;
+            let ticks = u32::from_le_bytes(le(data.get(4..)?)?) as i64;
+            let dt = NaiveDate::from_ymd_opt(1900, 1, 1)?
+                .and_time(NaiveTime::MIN)
+                .checked_add_signed(Duration::days(days))?
+                .checked_add_signed(Duration::nanoseconds(ticks * 10_000_000 / 3))?;
+            ("datetime", sql(SqlValue::DateTime(dt)))
+        }
+        0x3A => {
+            let days = u16::from_le_bytes(le(data)?) as i64;
+            let minutes = u16::from_le_bytes(le(data.get(2..)?)?) as i64;
+            let dt = NaiveDate::from_ymd_opt(1900, 1, 1)?
+                .and_time(NaiveTime::MIN)
+                .checked_add_signed(Duration::days(days))?
+                .checked_add_signed(Duration::minutes(minutes))?;
+            ("smalldatetime", sql(SqlValue::DateTime(dt)))
+        }
+        // Collation (5 bytes) and max length precede character data
+        0xA7 | 0xAF => {
+            let name = if base == 0xA7 { "varchar" } else { "char" };
+            (name, Some(VariantValue::Varchar(data.to_vec())))
+        }
+        0xE7 | 0xEF => {
+            if data.len() % 2 != 0 {
+                return None;
+            }
+            let units: Vec<u16> = data
+                .chunks_exact(2)
+                .map(|c| u16::from_le_bytes([c[0], c[1]]))
+                .collect();
+            let name = if base == 0xE7 { "nvarchar" } else { "nchar" };
+            (name, sql(SqlValue::String(String::from_utf16_lossy(&units))))
+        }
+        0xA5 | 0xAD => {
+            let name = if base == 0xA5 { "varbinary" } else { "binary" };
+            (name, Some(VariantValue::Binary(data.to_vec())))
+        }
+        _ => return None,
+    };
+    Some((decoded.0, decoded.1?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn variant(base: u8, props: &[u8], data: &[u8]) -> Vec<u8> {
+        let mut b = vec![base, props.len() as u8];
+        b.extend_from_slice(props);
+        b.extend_from_slice(data);
+        b
+    }
+
+    fn sql(bytes: &[u8]) -> (&'static str, SqlValue) {
+        match decode(bytes) {
+            Some((name, VariantValue::Sql(v))) => (name, v),
+            _ => panic!("expected a decoded SQL value"),
+        }
+    }
+
+    #[test]
+    fn decodes_int() {
+        let (name, value) = sql(&variant(0x38, &[], &42i32.to_le_bytes()));
+        assert_eq!(name, "int");
+        assert!(matches!(value, SqlValue::Int(42)));
+    }
+
+    #[test]
+    fn decodes_decimal() {
+        // decimal(10, 2) = -123.45
+        let mut data = vec![0u8];
+        data.extend(12345u32.to_le_bytes());
+        let (name, value) = sql(&variant(0x6A, &[10, 2], &data));
+        assert_eq!(name, "decimal");
+        assert!(matches!(value, SqlValue::Decimal(d) if d.to_string() == "-123.45"));
+    }
+
+    #[test]
+    fn decodes_nvarchar() {
+        let text: Vec<u8> = "héllo".encode_utf16().flat_map(|u| u.to_le_bytes()).collect();
+        let props = [0x09, 0x04, 0xD0, 0x00, 0x34, 0x40, 0x1F];
+        let (name, value) = sql(&variant(0xE7, &props, &text));
+        assert_eq!(name, "nvarchar");
+        assert!(matches!(value, SqlValue::String(s) if s == "héllo"));
+    }
+
+    #[test]
+    fn decodes_datetime2() {
+        // 2024-01-02 00:00:01.5 at scale 1: 15 ticks, then 738886 days
+        let mut data = 15u32.to_le_bytes()[..3].to_vec();
+        data.extend(&738_886u32.to_le_bytes()[..3]);
+        let (name, value) = sql(&variant(0x2A, &[1], &data));
+        assert_eq!(name, "datetime2");
+        let expected = NaiveDate::from_ymd_opt(2024, 1, 2)
+            .unwrap()
+            .and_hms_milli_opt(0, 0, 1, 500)
+            .unwrap();
+        assert!(matches!(value, SqlValue::DateTime(dt) if dt == expected));
+    }
+
+    #[test]
+    fn rejects_out_of_range_datetime() {
+        let mut data = i32::MAX.to_le_bytes().to_vec();
+        data.extend(0u32.to_le_bytes());
+        assert!(decode(&variant(0x3D, &[], &data)).is_none());
+    }
+
+    #[test]
+    fn keeps_binary_apart_and_rejects_unknown() {
+        let bytes = variant(0xA5, &[0x10, 0x00], &[1, 2, 3]);
+        assert!(matches!(decode(&bytes), Some(("varbinary", VariantValue::Binary(b))) if b == [1, 2, 3]));
+        assert!(decode(&variant(0x01, &[], &[])).is_none());
+        assert!(decode(&[0x38, 5]).is_none());
+    }
+}
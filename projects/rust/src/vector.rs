@@ -0,0 +1,88 @@
+//! Decoder for SQL Server 2025 VECTOR values.
+//!
+//! The binary layout is an 8-byte header (0xA9 magic, version 1, u16 LE
+//! dimension count, element type 0 = float32, 3 reserved bytes) followed by
+//! little-endian float32 elements. Clients without vector support receive the
+//! JSON array text instead, which is parsed here too.
+
+const MAGIC: u8 = 0xA9;
+const HEADER_LEN: usize = 8;
+const FLOAT32: u8 = 0x00;
+
+/// Decode the binary vector format. Returns None for anything unrecognized.
+pub fn decode(bytes: &[u8]) -> Option<Vec<f32>> {
+    let header = bytes.get(..HEADER_LEN)?;
+    if header[0] != MAGIC || header[1] != 1 || header[4] != FLOAT32 {
+        return None;
+    }
+    let dims = u16::from_le_bytes([header[2], header[3]]) as usize;
+    let body = &bytes[HEADER_LEN..];
+    if body.len() != dims * 4 {
+        return None;
+    }
+    Some(
+        body.chunks_exact(4)
+            .map(|c| f32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+            .collect(),
+    )
+}
+
+/// Parse the JSON array text form, e.g. `"[0.1, 2, 30]"`.
+pub fn parse_text(s: &str) -> Option<Vec<f32>> {
+    serde_json::from_str(s).ok()
+}
+
+/// Convert elements to a JSON array. Each float32 goes through its shortest
+/// decimal form so `0.1f32` becomes `0.1`, not `0.10000000149011612`.
+pub fn to_json(values: &[f32]) -> serde_json::Value {
+    serde_json::Value::Array(
+        values
+            .iter()
+            .map(|v| {
+                v.to_string()
+                    .parse::<f64>()
+                    .ok()
+                    .and_then(serde_json::Number::from_f64)
+                    .map_or(serde_json::Value::Null, serde_json::Value::Number)
+            })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(values: &[f32]) -> Vec<u8> {
+        let mut b = vec![MAGIC, 1];
+        b.extend((values.len() as u16).to_le_bytes());
+        b.extend([FLOAT32, 0, 0, 0]);
+        for v in values {
+            b.extend(v.to_le_bytes());
+        }
+        b
+    }
+
+    #[test]
+    fn decodes_binary_vector() {
+        let bytes = encode(&[0.1, -2.5, 30.0]);
+        let values = decode(&bytes).unwrap();
+        assert_eq!(to_json(&values), serde_json::json!([0.1, -2.5, 30.0]));
+    }
+
+    #[test]
+    fn rejects_bad_header_or_length() {
+        let mut bytes = encode(&[1.0, 2.0]);
+        bytes[0] = 0;
+        assert!(decode(&bytes).is_none());
+        let mut bytes = encode(&[1.0, 2.0]);
+        bytes.pop();
+        assert!(decode(&bytes).is_none());
+    }
+
+    #[test]
+    fn parses_json_text_form() {
+        assert_eq!(parse_text("[0.1, 2, 30]"), Some(vec![0.1, 2.0, 30.0]));
+        assert_eq!(parse_text("not a vector"), None);
+    }
+}
@@ -0,0 +1,66 @@
+//! Build and capability report for compatibility checks and bug reports.
+
+/// TDS protocol versions mssql-client can negotiate.
+const TDS_VERSIONS: [&str; 3] = ["7.3", "7.4", "8.0"];
+
+/// Result compression codecs compiled in.
+fn compression() -> Vec<&'static str> {
+    [
+        ("lz4", cfg!(feature = "lz4")),
+        ("zstd", cfg!(feature = "zstd")),
+    ]
+    .into_iter()
+    .filter_map(|(codec, on)| on.then_some(codec))
+    .collect()
+}
+
+/// A locked dependency: its version, and git revision when built from git.
+fn dependency(version: &str, rev: &str) -> serde_json::Value {
+    serde_json::json!({
+        "version": version,
+        "revision": Some(rev).filter(|rev| !rev.is_empty()),
+    })
+}
+
+/// The driver's version, the versions of the crates it's built on, the
+/// target and compiled-in features, as JSON.
+pub fn info() -> serde_json::Value {
+    serde_json::json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "mssql_client": dependency(env!("MSSQLTS_CLIENT_VERSION"), env!("MSSQLTS_CLIENT_REV")),
+        "mssql_driver_pool": dependency(env!("MSSQLTS_POOL_VERSION"), env!("MSSQLTS_POOL_REV")),
+        "target": {
+            "os": std::env::consts::OS,
+            "arch": std::env::consts::ARCH,
+            "debug": cfg!(debug_assertions),
+        },
+        "features": {
+            // Opening FILESTREAM data by path needs the Windows API; other
+            // targets emulate it with chunked statements
+            "filestream": if cfg!(windows) { "native" } else { "emulated" },
+            "tls": "rustls",
+            "integrated_auth": if cfg!(windows) { "sspi" } else { "kerberos" },
+            "compression": compression(),
+            "opentelemetry": cfg!(feature = "otel"),
+        },
+        "tds_versions": TDS_VERSIONS,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_versions_and_features() {
+        let info = info();
+        assert_eq!(info["version"], env!("CARGO_PKG_VERSION"));
+        assert!(info["mssql_client"]["version"].is_string());
+        let filestream = if cfg!(windows) { "native" } else { "emulated" };
+        assert_eq!(info["features"]["filestream"], filestream);
+        assert_eq!(info["features"]["opentelemetry"], cfg!(feature = "otel"));
+        let codecs = info["features"]["compression"].as_array().unwrap();
+        assert_eq!(codecs.contains(&"zstd".into()), cfg!(feature = "zstd"));
+        assert_eq!(info["tds_versions"].as_array().unwrap().len(), 3);
+    }
+}
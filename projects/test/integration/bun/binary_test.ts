@@ -8,7 +8,7 @@
 import { describe, expect, test } from "bun:test";
 import { mkdtemp, readFile, rm, writeFile } from "node:fs/promises";
 import { createReadStream, createWriteStream } from "node:fs";
-import { pipeline } from "node:stream/promises";
+import { finished, pipeline } from "node:stream/promises";
 import { tmpdir } from "node:os";
 import { join } from "node:path";
 import { getTestEnv, skipFilestream, skipMssql } from "./test_helpers.ts";
@@ -26,8 +26,52 @@ function fromBase64(b64: string): Uint8Array {
   return bytes;
 }
 
+/** Bytes appended through an emulated FILESTREAM handle. */
+const TAIL = new TextEncoder().encode(" appended");
+
+/** Length to extend to: the zero fill spans three 1 MiB statements. */
+const EXTENDED_LENGTH = TEST_BYTES.length + TAIL.length + 2 * 1024 * 1024 + 100;
+
+/** `TEST_BYTES` + `TAIL`, zero-filled to `EXTENDED_LENGTH`. */
+function extendedBytes(): Uint8Array {
+  const bytes = new Uint8Array(EXTENDED_LENGTH);
+  bytes.set(TEST_BYTES);
+  bytes.set(TAIL, TEST_BYTES.length);
+  return bytes;
+}
+
+/**
+ * Append `TAIL` to a row's value and extend it to `EXTENDED_LENGTH`. Off
+ * Windows this runs as `UPDATE ... SET col.WRITE(...)` statements.
+ */
+async function appendAndExtend(
+  cn: mssql.MssqlConnection,
+  table: string,
+  column: string,
+  key: Record<string, mssql.ParamValue>,
+): Promise<void> {
+  const stream = await cn.openFilestreamFor(
+    table,
+    column,
+    key,
+    "write",
+    "append",
+  );
+  try {
+    await new Promise<void>((resolve, reject) =>
+      stream.write(TAIL, (err) => (err ? reject(err) : resolve()))
+    );
+    await stream.setLength(EXTENDED_LENGTH);
+    // Outside a transaction the writes commit when the stream finishes
+    stream.end();
+    await finished(stream);
+  } finally {
+    stream.destroy();
+  }
+}
+
 describe("filestreamAvailable", () => {
-  test.skipIf(skipMssql)("returns false on non-Windows", async () => {
+  test.skipIf(skipMssql)("returns false on a Linux server", async () => {
     const env = getTestEnv();
     if (env.isWindows) return;
     await using cn = await mssql.connect(env.connectionString);
@@ -313,3 +357,100 @@ describe("FILESTREAM", () => {
     }
   });
 });
+
+describe("emulated FILESTREAM", () => {
+  test.skipIf(skipMssql)("append and extend on VARBINARY(max)", async () => {
+    const env = getTestEnv();
+    if (env.isWindows) return; // Native FILESTREAM there
+    await using cn = await mssql.connect(env.connectionString);
+
+    await cn.execute(`CREATE TABLE #fs_emulated (
+      id INT PRIMARY KEY,
+      data VARBINARY(MAX)
+    )`);
+    await cn.execute("INSERT INTO #fs_emulated (id, data) VALUES (1, @data)", {
+      data: { value: TEST_BYTES, type: "varbinary" },
+    });
+
+    await appendAndExtend(cn, "#fs_emulated", "data", { id: 1 });
+
+    const row = await cn.querySingle<{ data: string }>(
+      "SELECT data FROM #fs_emulated WHERE id = 1",
+    );
+    expect(fromBase64(row.data)).toEqual(extendedBytes());
+  });
+
+  test.skipIf(skipMssql)("holds the connection and rolls back unfinished writes", async () => {
+    const env = getTestEnv();
+    if (env.isWindows) return; // Native FILESTREAM there
+    await using cn = await mssql.connect(env.connectionString);
+
+    await cn.execute(`CREATE TABLE #fs_unfinished (
+      id INT PRIMARY KEY,
+      data VARBINARY(MAX)
+    )`);
+    await cn.execute("INSERT INTO #fs_unfinished (id, data) VALUES (1, @data)", {
+      data: { value: TEST_BYTES, type: "varbinary" },
+    });
+
+    // Overwrite empties the value, but only inside the stream's transaction
+    const stream = await cn.openFilestreamFor(
+      "#fs_unfinished",
+      "data",
+      { id: 1 },
+      "write",
+    );
+    await new Promise<void>((resolve, reject) =>
+      stream.write(TAIL, (err) => (err ? reject(err) : resolve()))
+    );
+    // The stream holds the connection until it is closed
+    await expect(cn.scalar("SELECT 1")).rejects.toThrow();
+    stream.destroy();
+
+    const row = await cn.querySingle<{ data: string }>(
+      "SELECT data FROM #fs_unfinished WHERE id = 1",
+    );
+    expect(fromBase64(row.data)).toEqual(TEST_BYTES);
+  });
+
+  test.skipIf(skipMssql)(".WRITE on a FILESTREAM column", async () => {
+    const env = getTestEnv();
+    if (env.isWindows) return; // Native FILESTREAM there
+    await using cn = await mssql.connect(env.connectionString);
+
+    // Needs a FILESTREAM-enabled server with db-setup run against it
+    const isFilestream = await cn.scalar<number>(
+      "SELECT COLUMNPROPERTY(OBJECT_ID('dbo.BinaryFiles'), 'file_data', 'IsFileStream')",
+    );
+    if (isFilestream !== 1) return;
+
+    const testName = `test_fs_emulated_${Date.now()}.bin`;
+
+    try {
+      await cn.execute(
+        "INSERT INTO dbo.BinaryFiles (file_name, file_data) VALUES (@name, @data)",
+        {
+          name: testName,
+          data: { value: TEST_BYTES, type: "varbinary" },
+        },
+      );
+
+      await appendAndExtend(cn, "dbo.BinaryFiles", "file_data", {
+        file_name: testName,
+      });
+
+      const row = await cn.querySingle<{ file_data: string }>(
+        "SELECT file_data FROM dbo.BinaryFiles WHERE file_name = @name",
+        { name: testName },
+      );
+      expect(fromBase64(row.file_data)).toEqual(extendedBytes());
+    } finally {
+      await cn
+        .execute(
+          "DELETE FROM dbo.BinaryFiles WHERE file_name = @name",
+          { name: testName },
+        )
+        .catch(() => {});
+    }
+  });
+});
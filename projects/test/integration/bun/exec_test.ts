@@ -7,6 +7,22 @@ import { describe, expect, test } from "bun:test";
 import { getTestEnv, skipMssql } from "./test_helpers.ts";
 import * as mssql from "../../../mssql/mod.ts";
 
+// GO inside a string and a block comment, and a GO 3 repeat
+const GO_SCRIPT = [
+  "CREATE TABLE #go_test (id INT IDENTITY, note NVARCHAR(50))",
+  "GO",
+  "INSERT INTO #go_test (note) VALUES (N'",
+  "GO",
+  "')",
+  "-- GO",
+  "GO 3",
+  "/*",
+  "GO",
+  "*/",
+  "SELECT COUNT(*) AS n FROM #go_test",
+  "go",
+].join("\n");
+
 describe("stored procedures", () => {
   test.skipIf(skipMssql)("exec with output params and multiple result sets", async () => {
     const env = getTestEnv();
@@ -32,6 +48,7 @@ describe("stored procedures", () => {
       -- Set output params
       SET @totalCount = 2;
       SET @greeting = (N'Hello, ' COLLATE DATABASE_DEFAULT) + @inputName + (N'!' COLLATE DATABASE_DEFAULT);
+      RETURN 7;
     END
   `);
 
@@ -49,6 +66,9 @@ describe("stored procedures", () => {
     // @ prefix should also work
     expect(result.getOutput<number>("@totalCount")).toBe(2);
 
+    // Verify procedure RETURN code
+    expect(result.returnValue).toBe(7);
+
     // Verify multiple result sets
     expect(result.resultSets).toBe(2);
 
@@ -99,4 +119,35 @@ describe("stored procedures", () => {
     expect(rows.length).toBe(1);
     expect(rows[0].original).toBe(21);
   });
+
+  test.skipIf(skipMssql)("runScript splits on GO lines and repeats GO n batches", async () => {
+    const env = getTestEnv();
+    await using cn = await mssql.connect(env.connectionString);
+
+    const result = await cn.runScript(GO_SCRIPT);
+    expect(result.batches).toBe(3);
+    expect(result.results.map((r) => [r.line, r.runs, r.error])).toEqual(
+      [[1, 1, null], [3, 3, null], [8, 1, null]],
+    );
+    expect(result.results[1].rowsAffected).toBe(3);
+
+    const rows = await cn.query<{ note: string }>("SELECT note FROM #go_test");
+    expect(rows.length).toBe(3);
+    expect(rows[0].note).toBe("\nGO\n");
+  });
+
+  test.skipIf(skipMssql)("runScript stops at a failing batch unless continueOnError", async () => {
+    const env = getTestEnv();
+    await using cn = await mssql.connect(env.connectionString);
+    const script = "SELECT 1/0\nGO\nSELECT 1";
+
+    await expect(cn.runScript(script)).rejects.toThrow("Batch 1 (line 1) failed");
+
+    const result = await cn.runScript(script, undefined, {
+      continueOnError: true,
+    });
+    expect(result.results[0].error).toBeTruthy();
+    expect(result.results[1].error).toBeNull();
+    expect(result.results[1].runs).toBe(1);
+  });
 });
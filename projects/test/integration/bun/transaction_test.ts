@@ -57,4 +57,66 @@ describe("transactions", () => {
     );
     expect(rows.length).toBe(1);
   });
+
+  test.skipIf(skipMssql)("read-only snapshot reads one point in time", async () => {
+    const env = getTestEnv();
+    await using cn = await mssql.connect(env.connectionString);
+    await using writer = await mssql.connect(env.connectionString);
+
+    // Snapshot isolation is per database, so not a #temp table in tempdb
+    await cn.execute(
+      "DROP TABLE IF EXISTS dbo.SnapshotTest; CREATE TABLE dbo.SnapshotTest (id INT)",
+    );
+    try {
+      await cn.execute("INSERT INTO dbo.SnapshotTest VALUES (1)");
+      const count = () =>
+        cn.scalar<number>("SELECT COUNT(*) FROM dbo.SnapshotTest");
+      {
+        await using tx = await cn.beginReadOnly();
+        expect(tx.readOnly).toBe(true);
+        expect(await count()).toBe(1);
+
+        // A commit from another session isn't seen, and doesn't block
+        await writer.execute("INSERT INTO dbo.SnapshotTest VALUES (2)");
+        expect(await count()).toBe(1);
+
+        await expect(cn.execute("DELETE FROM dbo.SnapshotTest")).rejects.toThrow(
+          "DELETE is not allowed in a read-only transaction",
+        );
+        await tx.commit();
+      }
+      expect(await count()).toBe(2);
+    } finally {
+      await cn.execute("DROP TABLE dbo.SnapshotTest");
+    }
+  });
+
+  test.skipIf(skipMssql)("transaction end restores the isolation level and XACT_ABORT", async () => {
+    const env = getTestEnv();
+    await using cn = await mssql.connect(env.connectionString);
+    const session = () =>
+      cn.queryFirst<{ level: number; xactAbort: number }>(
+        `SELECT transaction_isolation_level AS level,
+                @@OPTIONS & 16384 AS xactAbort
+         FROM sys.dm_exec_sessions WHERE session_id = @@SPID`,
+      );
+    const before = await session();
+    // 2 is READ COMMITTED
+    expect(before?.level).toBe(2);
+
+    for (const end of ["commit", "rollback"] as const) {
+      const tx = await cn.beginTransaction("SERIALIZABLE", { xactAbort: true });
+      expect(await session()).toEqual({ level: 4, xactAbort: 16384 });
+      await tx[end]();
+      expect(await session()).toEqual(before);
+    }
+
+    // XACT_ABORT rolls the transaction back server-side, so the end fails
+    for (const end of ["commit", "rollback"] as const) {
+      const tx = await cn.beginTransaction("SERIALIZABLE", { xactAbort: true });
+      await expect(cn.execute("SELECT 1 / 0", undefined, { transaction: tx })).rejects.toThrow();
+      await expect(tx[end]()).rejects.toThrow();
+      expect(await session()).toEqual(before);
+    }
+  });
 });
@@ -5,11 +5,11 @@
  * @module
  */
 
-import { assertEquals } from "jsr:@std/assert";
+import { assertEquals, assertRejects } from "jsr:@std/assert";
 import { getTestEnv, skipFilestream, skipMssql } from "./test_helpers.ts";
 import * as mssql from "../../../mssql/mod.ts";
 import { createReadStream, createWriteStream } from "node:fs";
-import { pipeline } from "node:stream/promises";
+import { finished, pipeline } from "node:stream/promises";
 
 const TEST_CONTENT =
   "Hello, world! \u{1F30D}\u{1F389} H\u00E9llo \u00E9mojis: \u{1F680}\u{1F4BB}\u{1F3B5} \u65E5\u672C\u8A9E\u30C6\u30B9\u30C8 caf\u00E9 na\u00EFve r\u00E9sum\u00E9";
@@ -23,14 +23,60 @@ function fromBase64(b64: string): Uint8Array {
   return bytes;
 }
 
+/** Bytes appended through an emulated FILESTREAM handle. */
+const TAIL = new TextEncoder().encode(" appended");
+
+/** Length to extend to: the zero fill spans three 1 MiB statements. */
+const EXTENDED_LENGTH = TEST_BYTES.length + TAIL.length + 2 * 1024 * 1024 + 100;
+
+/** `TEST_BYTES` + `TAIL`, zero-filled to `EXTENDED_LENGTH`. */
+function extendedBytes(): Uint8Array {
+  const bytes = new Uint8Array(EXTENDED_LENGTH);
+  bytes.set(TEST_BYTES);
+  bytes.set(TAIL, TEST_BYTES.length);
+  return bytes;
+}
+
+/**
+ * Append `TAIL` to a row's value and extend it to `EXTENDED_LENGTH`. Off
+ * Windows this runs as `UPDATE ... SET col.WRITE(...)` statements.
+ */
+async function appendAndExtend(
+  cn: mssql.MssqlConnection,
+  table: string,
+  column: string,
+  key: Record<string, mssql.ParamValue>,
+): Promise<void> {
+  const stream = await cn.openFilestreamFor(
+    table,
+    column,
+    key,
+    "write",
+    "append",
+  );
+  try {
+    await new Promise<void>((resolve, reject) =>
+      stream.write(TAIL, (err) => (err ? reject(err) : resolve()))
+    );
+    await stream.setLength(EXTENDED_LENGTH);
+    // Outside a transaction the writes commit when the stream finishes
+    stream.end();
+    await finished(stream);
+  } finally {
+    stream.destroy();
+  }
+}
+
 // ── FILESTREAM availability checks ────────────────────────────
 
 Deno.test({
-  name: "binary - filestreamAvailable returns false on non-Windows",
+  name: "binary - filestreamAvailable returns false on a Linux server",
   ignore: skipMssql,
   async fn() {
     const env = getTestEnv();
-    if (env.isWindows) return; // Only meaningful on non-Windows
+    // The emulation is available locally, but SQL Server on Linux has no
+    // FILESTREAM
+    if (env.isWindows) return;
     await using cn = await mssql.connect(env.connectionString);
     assertEquals(await cn.filestreamAvailable(), false);
   },
@@ -345,3 +391,112 @@ Deno.test({
     }
   },
 });
+
+// ── Emulated FILESTREAM (non-Windows) ────────────────────────
+
+Deno.test({
+  name: "binary - emulated FILESTREAM append and extend on VARBINARY(max)",
+  ignore: skipMssql,
+  async fn() {
+    const env = getTestEnv();
+    if (env.isWindows) return; // Native FILESTREAM there
+    await using cn = await mssql.connect(env.connectionString);
+
+    await cn.execute(`CREATE TABLE #fs_emulated (
+      id INT PRIMARY KEY,
+      data VARBINARY(MAX)
+    )`);
+    await cn.execute("INSERT INTO #fs_emulated (id, data) VALUES (1, @data)", {
+      data: { value: TEST_BYTES, type: "varbinary" },
+    });
+
+    await appendAndExtend(cn, "#fs_emulated", "data", { id: 1 });
+
+    const row = await cn.querySingle<{ data: string }>(
+      "SELECT data FROM #fs_emulated WHERE id = 1",
+    );
+    assertEquals(fromBase64(row.data), extendedBytes());
+  },
+});
+
+Deno.test({
+  name: "binary - emulated FILESTREAM holds the connection and rolls back unfinished writes",
+  ignore: skipMssql,
+  async fn() {
+    const env = getTestEnv();
+    if (env.isWindows) return; // Native FILESTREAM there
+    await using cn = await mssql.connect(env.connectionString);
+
+    await cn.execute(`CREATE TABLE #fs_unfinished (
+      id INT PRIMARY KEY,
+      data VARBINARY(MAX)
+    )`);
+    await cn.execute("INSERT INTO #fs_unfinished (id, data) VALUES (1, @data)", {
+      data: { value: TEST_BYTES, type: "varbinary" },
+    });
+
+    // Overwrite empties the value, but only inside the stream's transaction
+    const stream = await cn.openFilestreamFor(
+      "#fs_unfinished",
+      "data",
+      { id: 1 },
+      "write",
+    );
+    await new Promise<void>((resolve, reject) =>
+      stream.write(TAIL, (err) => (err ? reject(err) : resolve()))
+    );
+    // The stream holds the connection until it is closed
+    await assertRejects(() => cn.scalar("SELECT 1"));
+    stream.destroy();
+
+    const row = await cn.querySingle<{ data: string }>(
+      "SELECT data FROM #fs_unfinished WHERE id = 1",
+    );
+    assertEquals(fromBase64(row.data), TEST_BYTES);
+  },
+});
+
+Deno.test({
+  name: "binary - emulated FILESTREAM .WRITE on a FILESTREAM column",
+  ignore: skipMssql,
+  async fn() {
+    const env = getTestEnv();
+    if (env.isWindows) return; // Native FILESTREAM there
+    await using cn = await mssql.connect(env.connectionString);
+
+    // Needs a FILESTREAM-enabled server with db-setup run against it
+    const isFilestream = await cn.scalar<number>(
+      "SELECT COLUMNPROPERTY(OBJECT_ID('dbo.BinaryFiles'), 'file_data', 'IsFileStream')",
+    );
+    if (isFilestream !== 1) return;
+
+    const testName = `test_fs_emulated_${Date.now()}.bin`;
+
+    try {
+      await cn.execute(
+        "INSERT INTO dbo.BinaryFiles (file_name, file_data) VALUES (@name, @data)",
+        {
+          name: testName,
+          data: { value: TEST_BYTES, type: "varbinary" },
+        },
+      );
+
+      await appendAndExtend(cn, "dbo.BinaryFiles", "file_data", {
+        file_name: testName,
+      });
+
+      const row = await cn.querySingle<{ file_data: string }>(
+        "SELECT file_data FROM dbo.BinaryFiles WHERE file_name = @name",
+        { name: testName },
+      );
+      assertEquals(fromBase64(row.file_data), extendedBytes());
+    } finally {
+      await cn
+        .execute(
+          "DELETE FROM dbo.BinaryFiles WHERE file_name = @name",
+          { name: testName },
+        )
+        .catch(() => {});
+    }
+  },
+});
@@ -3,10 +3,26 @@
  * @module
  */
 
-import { assertEquals, assertExists } from "jsr:@std/assert";
+import { assertEquals, assertExists, assertRejects } from "jsr:@std/assert";
 import { getTestEnv, skipMssql } from "./test_helpers.ts";
 import * as mssql from "../../../mssql/mod.ts";
 
+// GO inside a string and a block comment, and a GO 3 repeat
+const GO_SCRIPT = [
+  "CREATE TABLE #go_test (id INT IDENTITY, note NVARCHAR(50))",
+  "GO",
+  "INSERT INTO #go_test (note) VALUES (N'",
+  "GO",
+  "')",
+  "-- GO",
+  "GO 3",
+  "/*",
+  "GO",
+  "*/",
+  "SELECT COUNT(*) AS n FROM #go_test",
+  "go",
+].join("\n");
+
 Deno.test({
   name: "exec - output params and multiple result sets",
   ignore: skipMssql,
@@ -34,6 +50,7 @@ Deno.test({
       -- Set output params
       SET @totalCount = 2;
       SET @greeting = (N'Hello, ' COLLATE DATABASE_DEFAULT) + @inputName + (N'!' COLLATE DATABASE_DEFAULT);
+      RETURN 7;
     END
   `);
 
@@ -51,6 +68,9 @@ Deno.test({
     // @ prefix should also work
     assertEquals(result.getOutput<number>("@totalCount"), 2);
 
+    // Verify procedure RETURN code
+    assertEquals(result.returnValue, 7);
+
     // Verify multiple result sets
     assertEquals(result.resultSets, 2);
 
@@ -106,3 +126,47 @@ Deno.test({
     assertEquals(rows[0].original, 21);
   },
 });
+
+Deno.test({
+  name: "runScript - splits on GO lines and repeats GO n batches",
+  ignore: skipMssql,
+  async fn() {
+    const env = getTestEnv();
+    await using cn = await mssql.connect(env.connectionString);
+
+    const result = await cn.runScript(GO_SCRIPT);
+    assertEquals(result.batches, 3);
+    assertEquals(
+      result.results.map((r) => [r.line, r.runs, r.error]),
+      [[1, 1, null], [3, 3, null], [8, 1, null]],
+    );
+    assertEquals(result.results[1].rowsAffected, 3);
+
+    const rows = await cn.query<{ note: string }>("SELECT note FROM #go_test");
+    assertEquals(rows.length, 3);
+    assertEquals(rows[0].note, "\nGO\n");
+  },
+});
+
+Deno.test({
+  name: "runScript - stops at a failing batch unless continueOnError",
+  ignore: skipMssql,
+  async fn() {
+    const env = getTestEnv();
+    await using cn = await mssql.connect(env.connectionString);
+    const script = "SELECT 1/0\nGO\nSELECT 1";
+
+    await assertRejects(
+      () => cn.runScript(script),
+      Error,
+      "Batch 1 (line 1) failed",
+    );
+
+    const result = await cn.runScript(script, undefined, {
+      continueOnError: true,
+    });
+    assertExists(result.results[0].error);
+    assertEquals(result.results[1].error, null);
+    assertEquals(result.results[1].runs, 1);
+  },
+});
@@ -3,7 +3,7 @@
  * @module
  */
 
-import { assertEquals } from "jsr:@std/assert";
+import { assertEquals, assertRejects } from "jsr:@std/assert";
 import { getTestEnv, skipMssql } from "./test_helpers.ts";
 import * as mssql from "../../../mssql/mod.ts";
 
@@ -64,3 +64,77 @@ Deno.test({
     assertEquals(rows.length, 1);
   },
 });
+
+Deno.test({
+  name: "integration - read-only snapshot reads one point in time",
+  ignore: skipMssql,
+  async fn() {
+    const env = getTestEnv();
+    await using cn = await mssql.connect(env.connectionString);
+    await using writer = await mssql.connect(env.connectionString);
+
+    // Snapshot isolation is per database, so not a #temp table in tempdb
+    await cn.execute(
+      "DROP TABLE IF EXISTS dbo.SnapshotTest; CREATE TABLE dbo.SnapshotTest (id INT)",
+    );
+    try {
+      await cn.execute("INSERT INTO dbo.SnapshotTest VALUES (1)");
+      const count = () =>
+        cn.scalar<number>("SELECT COUNT(*) FROM dbo.SnapshotTest");
+      {
+        await using tx = await cn.beginReadOnly();
+        assertEquals(tx.readOnly, true);
+        assertEquals(await count(), 1);
+
+        // A commit from another session isn't seen, and doesn't block
+        await writer.execute("INSERT INTO dbo.SnapshotTest VALUES (2)");
+        assertEquals(await count(), 1);
+
+        await assertRejects(
+          () => cn.execute("DELETE FROM dbo.SnapshotTest"),
+          Error,
+          "DELETE is not allowed in a read-only transaction",
+        );
+        await tx.commit();
+      }
+      assertEquals(await count(), 2);
+    } finally {
+      await cn.execute("DROP TABLE dbo.SnapshotTest");
+    }
+  },
+});
+
+Deno.test({
+  name: "integration - transaction end restores the isolation level and XACT_ABORT",
+  ignore: skipMssql,
+  async fn() {
+    const env = getTestEnv();
+    await using cn = await mssql.connect(env.connectionString);
+    const session = () =>
+      cn.queryFirst<{ level: number; xactAbort: number }>(
+        `SELECT transaction_isolation_level AS level,
+                @@OPTIONS & 16384 AS xactAbort
+         FROM sys.dm_exec_sessions WHERE session_id = @@SPID`,
+      );
+    const before = await session();
+    // 2 is READ COMMITTED
+    assertEquals(before?.level, 2);
+
+    for (const end of ["commit", "rollback"] as const) {
+      const tx = await cn.beginTransaction("SERIALIZABLE", { xactAbort: true });
+      assertEquals(await session(), { level: 4, xactAbort: 16384 });
+      await tx[end]();
+      assertEquals(await session(), before);
+    }
+
+    // XACT_ABORT rolls the transaction back server-side, so the end fails
+    for (const end of ["commit", "rollback"] as const) {
+      const tx = await cn.beginTransaction("SERIALIZABLE", { xactAbort: true });
+      await assertRejects(() =>
+        cn.execute("SELECT 1 / 0", undefined, { transaction: tx })
+      );
+      await assertRejects(() => tx[end]());
+      assertEquals(await session(), before);
+    }
+  },
+});
@@ -4,10 +4,26 @@
  */
 
 import { describe, test } from "node:test";
-import { ok, strictEqual } from "node:assert/strict";
+import { deepStrictEqual, ok, rejects, strictEqual } from "node:assert/strict";
 import { getTestEnv, skipMssql } from "./test_helpers.ts";
 import * as mssql from "../../../mssql/mod.ts";
 
+// GO inside a string and a block comment, and a GO 3 repeat
+const GO_SCRIPT = [
+  "CREATE TABLE #go_test (id INT IDENTITY, note NVARCHAR(50))",
+  "GO",
+  "INSERT INTO #go_test (note) VALUES (N'",
+  "GO",
+  "')",
+  "-- GO",
+  "GO 3",
+  "/*",
+  "GO",
+  "*/",
+  "SELECT COUNT(*) AS n FROM #go_test",
+  "go",
+].join("\n");
+
 describe("stored procedures", () => {
   test("exec with output params and multiple result sets", { skip: skipMssql }, async () => {
     const env = getTestEnv();
@@ -33,6 +49,7 @@ describe("stored procedures", () => {
       -- Set output params
       SET @totalCount = 2;
       SET @greeting = (N'Hello, ' COLLATE DATABASE_DEFAULT) + @inputName + (N'!' COLLATE DATABASE_DEFAULT);
+      RETURN 7;
     END
   `);
 
@@ -50,6 +67,9 @@ describe("stored procedures", () => {
     // @ prefix should also work
     strictEqual(result.getOutput<number>("@totalCount"), 2);
 
+    // Verify procedure RETURN code
+    strictEqual(result.returnValue, 7);
+
     // Verify multiple result sets
     strictEqual(result.resultSets, 2);
 
@@ -100,4 +120,36 @@ describe("stored procedures", () => {
     strictEqual(rows.length, 1);
     strictEqual(rows[0].original, 21);
   });
+
+  test("runScript splits on GO lines and repeats GO n batches", { skip: skipMssql }, async () => {
+    const env = getTestEnv();
+    await using cn = await mssql.connect(env.connectionString);
+
+    const result = await cn.runScript(GO_SCRIPT);
+    strictEqual(result.batches, 3);
+    deepStrictEqual(
+      result.results.map((r) => [r.line, r.runs, r.error]),
+      [[1, 1, null], [3, 3, null], [8, 1, null]],
+    );
+    strictEqual(result.results[1].rowsAffected, 3);
+
+    const rows = await cn.query<{ note: string }>("SELECT note FROM #go_test");
+    strictEqual(rows.length, 3);
+    strictEqual(rows[0].note, "\nGO\n");
+  });
+
+  test("runScript stops at a failing batch unless continueOnError", { skip: skipMssql }, async () => {
+    const env = getTestEnv();
+    await using cn = await mssql.connect(env.connectionString);
+    const script = "SELECT 1/0\nGO\nSELECT 1";
+
+    await rejects(() => cn.runScript(script), /Batch 1 \(line 1\) failed/);
+
+    const result = await cn.runScript(script, undefined, {
+      continueOnError: true,
+    });
+    ok(result.results[0].error);
+    strictEqual(result.results[1].error, null);
+    strictEqual(result.results[1].runs, 1);
+  });
 });
@@ -4,10 +4,88 @@
  */
 
 import { describe, test } from "node:test";
-import { deepStrictEqual, strictEqual } from "node:assert/strict";
+import { deepStrictEqual, rejects, strictEqual } from "node:assert/strict";
 import { getTestEnv, skipMssql } from "./test_helpers.ts";
 import * as mssql from "../../../mssql/mod.ts";
 
+// ── Always Encrypted fixtures ───────────────────────────────
+
+const AE_KEY_PATH = "CurrentUser/My/AeTest";
+
+const AE_CLEANUP = `
+  DROP TABLE IF EXISTS dbo.AeTest;
+  IF EXISTS (SELECT 1 FROM sys.column_encryption_keys WHERE name = 'AeTestCek')
+    DROP COLUMN ENCRYPTION KEY AeTestCek;
+  IF EXISTS (SELECT 1 FROM sys.column_master_keys WHERE name = 'AeTestCmk')
+    DROP COLUMN MASTER KEY AeTestCmk;`;
+
+/**
+ * A random CEK wrapped by a fresh RSA key, as the `ENCRYPTED_VALUE` hex
+ * literal, plus the private key to unwrap it.
+ */
+async function wrappedCek(): Promise<{ value: string; pkcs8: Uint8Array }> {
+  const pair = await crypto.subtle.generateKey(
+    {
+      name: "RSA-OAEP",
+      modulusLength: 2048,
+      publicExponent: new Uint8Array([1, 0, 1]),
+      hash: "SHA-1",
+    },
+    true,
+    ["encrypt", "decrypt"],
+  );
+  const ciphertext = new Uint8Array(
+    await crypto.subtle.encrypt(
+      { name: "RSA-OAEP" },
+      pair.publicKey,
+      crypto.getRandomValues(new Uint8Array(32)),
+    ),
+  );
+  // version | path length | ciphertext length | path | ciphertext | signature
+  const path = new Uint8Array(
+    [...AE_KEY_PATH].flatMap((c) => [c.charCodeAt(0), 0]),
+  );
+  const value = new Uint8Array(5 + path.length + ciphertext.length + 4);
+  const view = new DataView(value.buffer);
+  value[0] = 0x01;
+  view.setUint16(1, path.length, true);
+  view.setUint16(3, ciphertext.length, true);
+  value.set(path, 5);
+  value.set(ciphertext, 5 + path.length);
+  const hex = Array.from(value, (b) => b.toString(16).padStart(2, "0"));
+  return {
+    value: `0x${hex.join("")}`,
+    pkcs8: new Uint8Array(
+      await crypto.subtle.exportKey("pkcs8", pair.privateKey),
+    ),
+  };
+}
+
+/** Create a CMK, a CEK and `dbo.AeTest` with an encrypted column. */
+async function createEncryptedTable(
+  cn: mssql.MssqlConnection,
+  cekValue: string,
+): Promise<void> {
+  await cn.execute(AE_CLEANUP);
+  await cn.execute(`
+    CREATE COLUMN MASTER KEY AeTestCmk
+      WITH (KEY_STORE_PROVIDER_NAME = 'TEST_STORE', KEY_PATH = '${AE_KEY_PATH}')`);
+  await cn.execute(`
+    CREATE COLUMN ENCRYPTION KEY AeTestCek
+      WITH VALUES (COLUMN_MASTER_KEY = AeTestCmk, ALGORITHM = 'RSA_OAEP',
+                   ENCRYPTED_VALUE = ${cekValue})`);
+  await cn.execute(`
+    CREATE TABLE dbo.AeTest (
+      id INT NOT NULL,
+      salary INT ENCRYPTED WITH (COLUMN_ENCRYPTION_KEY = AeTestCek,
+        ENCRYPTION_TYPE = RANDOMIZED,
+        ALGORITHM = 'AEAD_AES_256_CBC_HMAC_SHA_256') NULL
+    )`);
+  // Plain T-SQL can't write ciphertext, so the encrypted cell stays NULL;
+  // decrypting cells is covered by the native unit tests.
+  await cn.execute("INSERT INTO dbo.AeTest (id) VALUES (1)");
+}
+
 describe("queries", () => {
   test("connect to MSSQL", { skip: skipMssql }, async () => {
     const env = getTestEnv();
@@ -72,6 +150,36 @@ describe("queries", () => {
     strictEqual(result[0].str_val, "hello");
   });
 
+  test("datetime2(7) and time(7) round-trip", { skip: skipMssql }, async () => {
+    const env = getTestEnv();
+    await using cn = await mssql.connect(env.connectionString);
+
+    const result = await cn.query<{ dt: string; t: string }>(
+      "SELECT CAST(@dt AS DATETIME2(7)) AS dt, CAST(@t AS TIME(7)) AS t",
+      {
+        dt: { value: "2024-06-15T10:30:45.1234567", type: "datetime2" },
+        t: { value: "23:59:59.9999999", type: "time" },
+      },
+    );
+
+    strictEqual(result[0].dt, "2024-06-15T10:30:45.1234567");
+    strictEqual(result[0].t, "23:59:59.9999999");
+  });
+
+  test("xml param with encoding declaration", { skip: skipMssql }, async () => {
+    const env = getTestEnv();
+    await using cn = await mssql.connect(env.connectionString);
+
+    const result = await cn.query<{ n: number }>(
+      "SELECT @doc.value('(/a)[1]', 'int') AS n",
+      {
+        doc: { value: '<?xml version="1.0" encoding="utf-8"?><a>7</a>', type: "xml" },
+      },
+    );
+
+    strictEqual(result[0].n, 7);
+  });
+
   test("null handling", { skip: skipMssql }, async () => {
     const env = getTestEnv();
     await using cn = await mssql.connect(env.connectionString);
@@ -169,4 +277,39 @@ describe("queries", () => {
     strictEqual(rows[0].name, "Alice");
     strictEqual(rows[2].name, "Charlie");
   });
+
+  test("compressed results decode with the native decompressor", { skip: skipMssql }, async () => {
+    const env = getTestEnv();
+    await using cn = await mssql.connect(env.connectionString);
+    const decompress = await mssql.nativeDecompressor();
+    for (const codec of ["lz4", "zstd"] as const) {
+      const result = await cn.queryCompressed(
+        `SELECT n, REPLICATE('x', 20) AS pad
+         FROM (VALUES (1), (2), (3)) AS v(n)`,
+        {},
+        { compress: codec, compressThreshold: 0 },
+      );
+      strictEqual(result.codec, codec);
+      const rows = mssql.decodeCompressedRows<{ n: number }>(result, decompress);
+      deepStrictEqual(rows.map((r) => r.n), [1, 2, 3]);
+    }
+  });
+
+  test("Always Encrypted keys unwrapped from the database", { skip: skipMssql }, async () => {
+    const env = getTestEnv();
+    await using cn = await mssql.connect(env.connectionString);
+    const cek = await wrappedCek();
+    await createEncryptedTable(cn, cek.value);
+    try {
+      await rejects(() => cn.enableColumnEncryption({}), /TEST_STORE/);
+      await cn.enableColumnEncryption(
+        { TEST_STORE: mssql.rsaKeyStoreProvider(cek.pkcs8) },
+        { tables: ["dbo.AeTest"] },
+      );
+      const rows = await cn.query("SELECT id, salary FROM dbo.AeTest");
+      deepStrictEqual(rows, [{ id: 1, salary: null }]);
+    } finally {
+      await cn.execute(AE_CLEANUP);
+    }
+  });
 });
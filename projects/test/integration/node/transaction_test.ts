@@ -4,7 +4,7 @@
  */
 
 import { describe, test } from "node:test";
-import { strictEqual } from "node:assert/strict";
+import { deepStrictEqual, rejects, strictEqual } from "node:assert/strict";
 import { getTestEnv, skipMssql } from "./test_helpers.ts";
 import * as mssql from "../../../mssql/mod.ts";
 
@@ -58,4 +58,67 @@ describe("transactions", () => {
     );
     strictEqual(rows.length, 1);
   });
+
+  test("read-only snapshot reads one point in time", { skip: skipMssql }, async () => {
+    const env = getTestEnv();
+    await using cn = await mssql.connect(env.connectionString);
+    await using writer = await mssql.connect(env.connectionString);
+
+    // Snapshot isolation is per database, so not a #temp table in tempdb
+    await cn.execute(
+      "DROP TABLE IF EXISTS dbo.SnapshotTest; CREATE TABLE dbo.SnapshotTest (id INT)",
+    );
+    try {
+      await cn.execute("INSERT INTO dbo.SnapshotTest VALUES (1)");
+      const count = () =>
+        cn.scalar<number>("SELECT COUNT(*) FROM dbo.SnapshotTest");
+      {
+        await using tx = await cn.beginReadOnly();
+        strictEqual(tx.readOnly, true);
+        strictEqual(await count(), 1);
+
+        // A commit from another session isn't seen, and doesn't block
+        await writer.execute("INSERT INTO dbo.SnapshotTest VALUES (2)");
+        strictEqual(await count(), 1);
+
+        await rejects(
+          () => cn.execute("DELETE FROM dbo.SnapshotTest"),
+          /DELETE is not allowed in a read-only transaction/,
+        );
+        await tx.commit();
+      }
+      strictEqual(await count(), 2);
+    } finally {
+      await cn.execute("DROP TABLE dbo.SnapshotTest");
+    }
+  });
+
+  test("transaction end restores the isolation level and XACT_ABORT", { skip: skipMssql }, async () => {
+    const env = getTestEnv();
+    await using cn = await mssql.connect(env.connectionString);
+    const session = () =>
+      cn.queryFirst<{ level: number; xactAbort: number }>(
+        `SELECT transaction_isolation_level AS level,
+                @@OPTIONS & 16384 AS xactAbort
+         FROM sys.dm_exec_sessions WHERE session_id = @@SPID`,
+      );
+    const before = await session();
+    // 2 is READ COMMITTED
+    strictEqual(before?.level, 2);
+
+    for (const end of ["commit", "rollback"] as const) {
+      const tx = await cn.beginTransaction("SERIALIZABLE", { xactAbort: true });
+      deepStrictEqual(await session(), { level: 4, xactAbort: 16384 });
+      await tx[end]();
+      deepStrictEqual(await session(), before);
+    }
+
+    // XACT_ABORT rolls the transaction back server-side, so the end fails
+    for (const end of ["commit", "rollback"] as const) {
+      const tx = await cn.beginTransaction("SERIALIZABLE", { xactAbort: true });
+      await rejects(cn.execute("SELECT 1 / 0", undefined, { transaction: tx }));
+      await rejects(tx[end]());
+      deepStrictEqual(await session(), before);
+    }
+  });
 });
@@ -71,6 +71,11 @@ try {
     log(`Database [${TEST_DB}] already exists with correct collation.`);
   }
 
+  // Read-only transactions (beginReadOnly) need snapshot isolation
+  await cn.execute(
+    `ALTER DATABASE [${TEST_DB}] SET ALLOW_SNAPSHOT_ISOLATION ON`,
+  );
+
   // Windows FILESTREAM setup
   if (Deno.build.os === "windows") {
     log("Windows detected — configuring FILESTREAM...");